@@ -14,6 +14,11 @@ pub struct Claims {
     pub roles: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permissions: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// OAuth scopes granted to the client, space-delimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
 }
 
 /// Authentication context extracted from request
@@ -22,6 +27,8 @@ pub struct AuthContext {
     pub user_id: String,
     pub roles: Vec<String>,
     pub permissions: Vec<String>,
+    pub tenant_id: Option<String>,
+    pub scopes: Vec<String>,
 }
 
 impl AuthContext {
@@ -30,6 +37,8 @@ impl AuthContext {
             user_id,
             roles: vec![],
             permissions: vec![],
+            tenant_id: None,
+            scopes: vec![],
         }
     }
 
@@ -43,6 +52,16 @@ impl AuthContext {
         self
     }
 
+    pub fn with_tenant(mut self, tenant_id: String) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
     pub fn has_role(&self, role: &str) -> bool {
         self.roles.iter().any(|r| r == role)
     }
@@ -50,6 +69,10 @@ impl AuthContext {
     pub fn has_permission(&self, permission: &str) -> bool {
         self.permissions.iter().any(|p| p == permission)
     }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
 }
 
 /// Validate JWT token from request
@@ -78,6 +101,11 @@ pub fn validate_token(req: &HttpRequest) -> Result<AuthContext, ActixError> {
         user_id: claims.sub,
         roles: claims.roles.unwrap_or_default(),
         permissions: claims.permissions.unwrap_or_default(),
+        tenant_id: claims.tenant_id,
+        scopes: claims
+            .scope
+            .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
     })
 }
 
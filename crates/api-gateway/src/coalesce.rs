@@ -0,0 +1,360 @@
+//! Request Coalescing for API Gateway
+//!
+//! Collapses concurrent identical requests into a single upstream call. The
+//! first request for a key becomes the leader and is forwarded; identical
+//! requests arriving while it is in flight wait for the leader's response and
+//! receive a copy of it instead of reaching the backend. This keeps cache-miss
+//! storms on expensive endpoints down to one backend call per key.
+//!
+//! Only safe methods (GET and HEAD) without a request body from an
+//! authenticated principal are coalesced. The key covers the principal and
+//! tenant, the client certificate if one was presented, the method, path,
+//! query and the configured vary headers, which include `Authorization` by
+//! default, so responses are never shared between callers with different
+//! identities or credentials. If the leader fails or is cancelled,
+//! waiting requests fall back to calling the backend themselves.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        Method, StatusCode,
+    },
+    web, Error, HttpMessage, HttpRequest, HttpResponse,
+};
+use dashmap::{mapref::entry::Entry, DashMap};
+use futures::future::LocalBoxFuture;
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::auth::AuthContext;
+use crate::mtls::ClientCertificate;
+
+/// Metrics for coalesced requests
+pub struct CoalesceMetrics {
+    pub requests_total: IntCounterVec,
+}
+
+impl CoalesceMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let requests_total = IntCounterVec::new(
+            Opts::new(
+                "api_gateway_coalesce_requests_total",
+                "Coalescable requests by role (leader, follower or fallback)",
+            ),
+            &["role"],
+        )
+        .expect("Failed to create coalesce requests_total metric");
+
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("Failed to register coalesce requests_total");
+
+        Self { requests_total }
+    }
+
+    fn record(&self, role: &str) {
+        self.requests_total.with_label_values(&[role]).inc();
+    }
+}
+
+/// Request coalescing configuration
+#[derive(Clone)]
+pub struct CoalesceConfig {
+    /// Path prefixes to coalesce; empty coalesces every path
+    pub path_prefixes: Vec<String>,
+    /// Request headers that are part of the coalescing key
+    pub vary_headers: Vec<HeaderName>,
+    pub metrics: Arc<CoalesceMetrics>,
+    /// Calls in flight by key, shared by every worker using this config
+    in_flight: InFlight,
+}
+
+impl CoalesceConfig {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            path_prefixes: Vec::new(),
+            vary_headers: vec![
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::ACCEPT_ENCODING,
+                header::ACCEPT_LANGUAGE,
+            ],
+            metrics: Arc::new(CoalesceMetrics::new(registry)),
+            in_flight: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn with_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.path_prefixes.push(prefix.into());
+        self
+    }
+
+    pub fn with_vary_header(mut self, name: HeaderName) -> Self {
+        self.vary_headers.push(name);
+        self
+    }
+
+    /// Coalescing key of a request, or `None` if it must not be coalesced
+    pub fn key(&self, req: &ServiceRequest) -> Option<String> {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) || has_body(req) {
+            return None;
+        }
+
+        let path = req.path();
+        if !self.path_prefixes.is_empty()
+            && !self
+                .path_prefixes
+                .iter()
+                .any(|prefix| path.starts_with(prefix.as_str()))
+        {
+            return None;
+        }
+
+        // Responses are only shared within one authenticated principal
+        let extensions = req.extensions();
+        let ctx = extensions.get::<AuthContext>()?;
+        let mut key = format!(
+            "{}\n{}\n",
+            ctx.user_id,
+            ctx.tenant_id.as_deref().unwrap_or_default()
+        );
+        if let Some(cert) = extensions.get::<ClientCertificate>() {
+            key.push_str(&format!("{}|{}\n", cert.issuer, cert.subject));
+        }
+
+        key.push_str(&format!(
+            "{} {}",
+            req.method(),
+            req.uri()
+                .path_and_query()
+                .map(|pq| pq.as_str())
+                .unwrap_or(path)
+        ));
+        for name in &self.vary_headers {
+            key.push('\n');
+            key.push_str(name.as_str());
+            key.push(':');
+            for value in req.headers().get_all(name) {
+                key.push_str(&String::from_utf8_lossy(value.as_bytes()));
+                key.push(',');
+            }
+        }
+        Some(key)
+    }
+}
+
+/// Whether a request declares a body
+fn has_body(req: &ServiceRequest) -> bool {
+    req.headers().contains_key(header::TRANSFER_ENCODING)
+        || req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim() != "0")
+}
+
+/// Buffered response shared between the leader and its followers
+struct SharedResponse {
+    status: StatusCode,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    body: web::Bytes,
+}
+
+impl SharedResponse {
+    fn respond_to(&self, req: HttpRequest) -> ServiceResponse<BoxBody> {
+        let mut builder = HttpResponse::build(self.status);
+        for (name, value) in &self.headers {
+            builder.append_header((name.clone(), value.clone()));
+        }
+        ServiceResponse::new(req, builder.body(self.body.clone()))
+    }
+}
+
+type InFlight = Arc<DashMap<String, broadcast::Sender<Arc<SharedResponse>>>>;
+
+/// Role of a request within its coalescing group
+enum Role {
+    Leader(broadcast::Sender<Arc<SharedResponse>>),
+    Follower(broadcast::Receiver<Arc<SharedResponse>>),
+}
+
+/// Removes the leader's in-flight entry if it finishes without a response,
+/// which wakes its followers so they fall back to the backend
+struct LeaderGuard {
+    in_flight: InFlight,
+    key: Option<String>,
+}
+
+impl LeaderGuard {
+    /// Remove the entry and hand the response to the followers
+    fn complete(
+        mut self,
+        sender: &broadcast::Sender<Arc<SharedResponse>>,
+        shared: Arc<SharedResponse>,
+    ) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.remove(&key);
+        }
+        // No receivers simply means nobody was waiting
+        let _ = sender.send(shared);
+    }
+}
+
+impl Drop for LeaderGuard {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.remove(&key);
+        }
+    }
+}
+
+/// Request coalescing middleware
+pub struct CoalesceMiddleware {
+    config: Option<CoalesceConfig>,
+}
+
+impl CoalesceMiddleware {
+    pub fn new(config: Option<CoalesceConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CoalesceMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CoalesceMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CoalesceMiddlewareService {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct CoalesceMiddlewareService<S> {
+    service: Rc<S>,
+    config: Option<CoalesceConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for CoalesceMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let coalescable = self
+            .config
+            .as_ref()
+            .and_then(|config| config.key(&req).map(|key| (config.clone(), key)));
+        let Some((config, key)) = coalescable else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        };
+        let in_flight = Arc::clone(&config.in_flight);
+
+        Box::pin(async move {
+            // Join an in-flight call for the key or become its leader
+            let role = match in_flight.entry(key.clone()) {
+                Entry::Occupied(entry) => Role::Follower(entry.get().subscribe()),
+                Entry::Vacant(entry) => {
+                    let (sender, _) = broadcast::channel(1);
+                    entry.insert(sender.clone());
+                    Role::Leader(sender)
+                }
+            };
+
+            match role {
+                Role::Follower(mut receiver) => match receiver.recv().await {
+                    Ok(shared) => {
+                        config.metrics.record("follower");
+                        Ok(shared.respond_to(req.into_parts().0))
+                    }
+                    Err(_) => {
+                        // Leader failed or was cancelled: go to the backend directly
+                        config.metrics.record("fallback");
+                        Ok(service.call(req).await?.map_into_boxed_body())
+                    }
+                },
+                Role::Leader(sender) => {
+                    config.metrics.record("leader");
+                    let guard = LeaderGuard {
+                        in_flight,
+                        key: Some(key),
+                    };
+
+                    let res = service.call(req).await?;
+                    let (req, res) = res.into_parts();
+                    let status = res.status();
+                    let headers = res
+                        .headers()
+                        .iter()
+                        .filter(|(name, _)| *name != header::CONTENT_LENGTH)
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect();
+                    let body = actix_web::body::to_bytes(res.into_body())
+                        .await
+                        .map_err(|e| actix_web::error::ErrorInternalServerError(e.into()))?;
+
+                    let shared = Arc::new(SharedResponse {
+                        status,
+                        headers,
+                        body,
+                    });
+                    guard.complete(&sender, Arc::clone(&shared));
+                    Ok(shared.respond_to(req))
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn request_as(principal: Option<&str>) -> ServiceRequest {
+        let req = TestRequest::get().uri("/catalog?x=1").to_srv_request();
+        if let Some(principal) = principal {
+            req.extensions_mut()
+                .insert(AuthContext::new(principal.to_string()));
+        }
+        req
+    }
+
+    #[test]
+    fn key_is_scoped_to_the_principal() {
+        let config = CoalesceConfig::new(&Registry::new());
+
+        assert!(config.key(&request_as(None)).is_none());
+        assert_eq!(
+            config.key(&request_as(Some("alice"))),
+            config.key(&request_as(Some("alice")))
+        );
+        assert_ne!(
+            config.key(&request_as(Some("alice"))),
+            config.key(&request_as(Some("bob")))
+        );
+    }
+}
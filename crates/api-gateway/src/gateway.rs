@@ -1,7 +1,12 @@
 //! API Gateway Main Module
 
+use crate::coalesce::{CoalesceConfig, CoalesceMiddleware};
+use crate::masking::{MaskingConfig, MaskingMiddleware};
 use crate::middleware::{AuthMiddleware, LoggingMiddleware, RateLimitMiddleware};
+use crate::mirror::{MirrorConfig, MirrorMiddleware};
+use crate::mtls::{MtlsConfig, MtlsMiddleware};
 use crate::rate_limit::{RateLimitConfig, RateLimitIdentifier};
+use crate::tenant_limit::{TenantRateLimitConfig, TenantRateLimitMiddleware};
 use crate::validation::ValidationMiddleware;
 use crate::versioning::ApiVersion;
 use actix_web::App;
@@ -12,6 +17,11 @@ pub struct GatewayConfig {
     pub rate_limit: RateLimitConfig,
     pub require_auth: bool,
     pub supported_versions: Vec<ApiVersion>,
+    pub mtls: Option<MtlsConfig>,
+    pub mirror: Option<MirrorConfig>,
+    pub tenant_limits: Option<TenantRateLimitConfig>,
+    pub coalesce: Option<CoalesceConfig>,
+    pub masking: Option<MaskingConfig>,
 }
 
 impl Default for GatewayConfig {
@@ -24,6 +34,11 @@ impl Default for GatewayConfig {
             },
             require_auth: true,
             supported_versions: vec![ApiVersion::v4()],
+            mtls: None,
+            mirror: None,
+            tenant_limits: None,
+            coalesce: None,
+            masking: None,
         }
     }
 }
@@ -59,6 +74,45 @@ impl ApiGateway {
         self
     }
 
+    pub fn with_mtls(mut self, config: MtlsConfig) -> Self {
+        self.config.mtls = Some(config);
+        self
+    }
+
+    pub fn with_mirror(mut self, config: MirrorConfig) -> Self {
+        self.config.mirror = Some(config);
+        self
+    }
+
+    pub fn with_tenant_limits(mut self, config: TenantRateLimitConfig) -> Self {
+        self.config.tenant_limits = Some(config);
+        self
+    }
+
+    pub fn with_coalescing(mut self, config: CoalesceConfig) -> Self {
+        self.config.coalesce = Some(config);
+        self
+    }
+
+    pub fn with_masking(mut self, config: MaskingConfig) -> Self {
+        self.config.masking = Some(config);
+        self
+    }
+
+    /// Mirror configuration that also strips the forwarded client certificate
+    fn mirror_config(&self) -> Option<MirrorConfig> {
+        let mirror = self.config.mirror.clone()?;
+        let forwarded_cert_header = self
+            .config
+            .mtls
+            .as_ref()
+            .and_then(|mtls| mtls.forwarded_cert_header.clone());
+        Some(match forwarded_cert_header {
+            Some(header) => mirror.with_credential_header(header),
+            None => mirror,
+        })
+    }
+
     /// Apply gateway middleware to an Actix App
     pub fn configure_app<F>(
         &self,
@@ -84,10 +138,21 @@ impl ApiGateway {
                 Error = actix_web::Error,
             > + 'static,
     {
+        // Coalescing is innermost so every request is authenticated, limited
+        // and logged on its own before it can share an upstream call
+        // Masking sits just outside it so a shared response is masked for
+        // each caller's own scopes
         let app = app
+            .wrap(CoalesceMiddleware::new(self.config.coalesce.clone()))
+            .wrap(MaskingMiddleware::new(self.config.masking.clone()))
             .wrap(LoggingMiddleware)
             .wrap(ValidationMiddleware::default());
 
+        // Mirroring sits just inside auth and the rate limiters so only
+        // authenticated, admitted requests are mirrored and shadow copies
+        // never count against the limits
+        let app = app.wrap(MirrorMiddleware::new(self.mirror_config()));
+
         // Conditionally apply auth middleware
         // Note: This requires all middleware to be applied due to type constraints
         let app = if self.config.require_auth {
@@ -98,7 +163,17 @@ impl ApiGateway {
             app.wrap(AuthMiddleware)
         };
 
-        app.wrap(RateLimitMiddleware::new(self.config.rate_limit.clone()))
+        // Tenant limits run after the mTLS check so certificate clients are
+        // accounted to their certificate's tenant
+        // Client certificates are checked before token auth so mTLS routes
+        // can authenticate without a bearer token
+        // The global limiter runs first so globally rejected requests never
+        // occupy a tenant's request slots
+        app.wrap(TenantRateLimitMiddleware::new(
+            self.config.tenant_limits.clone(),
+        ))
+        .wrap(MtlsMiddleware::new(self.config.mtls.clone()))
+        .wrap(RateLimitMiddleware::new(self.config.rate_limit.clone()))
     }
 }
 
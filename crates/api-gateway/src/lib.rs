@@ -2,17 +2,29 @@
 //!
 //! This module provides:
 //! - Centralized authentication (JWT)
+//! - Mutual TLS client authentication
 //! - Rate limiting
+//! - Per-tenant rate limiting and isolation
+//! - Shadow traffic mirroring
+//! - Coalescing of identical in-flight requests
+//! - Latency-aware upstream selection
+//! - PII masking of response bodies by client scope
 //! - Request/response logging
 //! - API versioning
 //! - OpenAPI auto-generation
 //! - Metrics and observability
 
 pub mod auth;
+pub mod coalesce;
 pub mod gateway;
+pub mod masking;
 pub mod metrics;
 pub mod middleware;
+pub mod mirror;
+pub mod mtls;
 pub mod rate_limit;
+pub mod tenant_limit;
+pub mod upstream;
 pub mod validation;
 pub mod versioning;
 
@@ -0,0 +1,703 @@
+//! PII Masking of Response Bodies for API Gateway
+//!
+//! Masks personal data in JSON responses according to the OAuth scopes of the
+//! calling client, so each client only sees the data it needs. Rules select
+//! fields by JSON path and say how to mask them: redacted outright, partially
+//! (e.g. a CPF as `123.***.***-09`), as an email address (`j***@example.com`)
+//! or replaced by a token.
+//!
+//! Clients holding an unmasked scope (e.g. `internal`) see full values. Other
+//! clients get the rules of every scope they hold, or the default rules if
+//! none of their scopes has rules. The default rules redact common PII fields
+//! unless replaced. Tokens are keyed HMACs of the value, so the
+//! same value always gives the same token and clients can still correlate
+//! records without seeing the data; without a tokenization key, tokenized
+//! fields are redacted.
+//!
+//! JSON paths support `$`, `.field`, `['field']`, `[n]`, `[*]`, `.*` and
+//! recursive descent with `..field`. Matching an object or array masks every
+//! value inside it.
+//!
+//! Only routes under the configured path prefixes are masked; without
+//! prefixes nothing is. Error and 204 No Content responses pass through
+//! untouched. On masked routes masking fails closed: the upstream is asked for
+//! an uncompressed response, and a successful response whose body is
+//! compressed, not JSON, not parsable or larger than the buffering limit is
+//! answered with 500 instead of being passed on unmasked.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, StatusCode},
+    web, Error, HttpMessage, HttpResponse,
+};
+use futures::future::LocalBoxFuture;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::auth::{extract_auth_context, AuthContext};
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "****";
+
+/// Prefix of tokenized values
+pub const TOKEN_PREFIX: &str = "tok_";
+
+/// Fields redacted for clients without scope rules unless the default rules
+/// are replaced
+pub const DEFAULT_PII_PATHS: &[&str] = &[
+    "$..email",
+    "$..emailAddress",
+    "$..email_address",
+    "$..phone_number",
+    "$..phoneNumber",
+    "$..cpf",
+    "$..tax_id",
+    "$..taxId",
+    "$..address",
+    "$..contact_medium",
+    "$..contactMedium",
+    "$..birth_date",
+    "$..birthDate",
+];
+
+/// Masking configuration error
+#[derive(Debug, thiserror::Error)]
+pub enum MaskingError {
+    #[error("Invalid JSON path {path:?}: {reason}")]
+    InvalidPath { path: String, reason: String },
+}
+
+/// How a matched value is masked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskStrategy {
+    /// Replace the whole value
+    Redact,
+    /// Keep the first and last characters and mask letters and digits in
+    /// between, leaving separators in place
+    Partial { keep_start: usize, keep_end: usize },
+    /// Keep the first character of the local part and the domain
+    Email,
+    /// Replace with a deterministic keyed token
+    Tokenize,
+}
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    Wildcard,
+    Descendant(String),
+}
+
+/// Parsed JSON path selecting the fields a rule masks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, MaskingError> {
+        let invalid = |reason: &str| MaskingError::InvalidPath {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut rest = path
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with $"))?;
+
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                let (name, tail) = split_name(after);
+                if name.is_empty() || name == "*" {
+                    return Err(invalid("recursive descent needs a field name"));
+                }
+                segments.push(Segment::Descendant(name.to_string()));
+                rest = tail;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let (name, tail) = split_name(after);
+                segments.push(match name {
+                    "" => return Err(invalid("empty field name")),
+                    "*" => Segment::Wildcard,
+                    name => Segment::Field(name.to_string()),
+                });
+                rest = tail;
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let (inner, tail) = after.split_once(']').ok_or_else(|| invalid("unclosed ["))?;
+                let inner = inner.trim();
+                segments.push(if inner == "*" {
+                    Segment::Wildcard
+                } else if let Some(name) = inner
+                    .strip_prefix('\'')
+                    .and_then(|n| n.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|n| n.strip_suffix('"')))
+                {
+                    Segment::Field(name.to_string())
+                } else {
+                    Segment::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| invalid("expected an index, * or quoted name in []"))?,
+                    )
+                });
+                rest = tail;
+            } else {
+                return Err(invalid("expected . or ["));
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// Call `f` on every value the path selects
+    fn for_each_match(&self, value: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+        visit(value, &self.segments, f);
+    }
+}
+
+/// Split a dotted field name from the rest of the path
+fn split_name(path: &str) -> (&str, &str) {
+    let end = path.find(['.', '[']).unwrap_or(path.len());
+    path.split_at(end)
+}
+
+fn visit(value: &mut Value, segments: &[Segment], f: &mut dyn FnMut(&mut Value)) {
+    let Some((segment, rest)) = segments.split_first() else {
+        f(value);
+        return;
+    };
+    match segment {
+        Segment::Field(name) => {
+            if let Some(child) = value.get_mut(name.as_str()) {
+                visit(child, rest, f);
+            }
+        }
+        Segment::Index(index) => {
+            if let Some(child) = value.get_mut(*index) {
+                visit(child, rest, f);
+            }
+        }
+        Segment::Wildcard => match value {
+            Value::Array(items) => items.iter_mut().for_each(|item| visit(item, rest, f)),
+            Value::Object(map) => map.values_mut().for_each(|item| visit(item, rest, f)),
+            _ => {}
+        },
+        Segment::Descendant(name) => match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    if key == name {
+                        visit(child, rest, f);
+                    } else {
+                        visit(child, segments, f);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| visit(item, segments, f)),
+            _ => {}
+        },
+    }
+}
+
+/// Fields selected by a JSON path and how to mask them
+#[derive(Debug, Clone)]
+pub struct MaskRule {
+    pub path: JsonPath,
+    pub strategy: MaskStrategy,
+}
+
+impl MaskRule {
+    pub fn new(path: &str, strategy: MaskStrategy) -> Result<Self, MaskingError> {
+        Ok(Self {
+            path: JsonPath::parse(path)?,
+            strategy,
+        })
+    }
+}
+
+/// Response masking configuration
+#[derive(Clone)]
+pub struct MaskingConfig {
+    /// Path prefixes whose responses are masked; empty masks nothing
+    pub path_prefixes: Vec<String>,
+    /// Scopes whose clients see full values
+    pub unmasked_scopes: Vec<String>,
+    /// Rules applied to clients holding each scope
+    pub scope_rules: HashMap<String, Vec<MaskRule>>,
+    /// Rules for clients none of whose scopes has rules
+    pub default_rules: Vec<MaskRule>,
+    /// Largest response body buffered for masking; larger bodies fail closed
+    pub max_body_bytes: usize,
+    /// Key for deterministic tokens; tokenized fields are redacted without it
+    tokenization_key: Option<Arc<[u8]>>,
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        let default_rules = DEFAULT_PII_PATHS
+            .iter()
+            .map(|path| {
+                MaskRule::new(path, MaskStrategy::Redact).expect("default PII paths are valid")
+            })
+            .collect();
+        Self {
+            path_prefixes: Vec::new(),
+            unmasked_scopes: Vec::new(),
+            scope_rules: HashMap::new(),
+            default_rules,
+            max_body_bytes: 1024 * 1024,
+            tokenization_key: None,
+        }
+    }
+}
+
+impl MaskingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.path_prefixes.push(prefix.into());
+        self
+    }
+
+    pub fn with_unmasked_scope(mut self, scope: impl Into<String>) -> Self {
+        self.unmasked_scopes.push(scope.into());
+        self
+    }
+
+    pub fn with_scope_rule(mut self, scope: impl Into<String>, rule: MaskRule) -> Self {
+        self.scope_rules.entry(scope.into()).or_default().push(rule);
+        self
+    }
+
+    pub fn with_default_rule(mut self, rule: MaskRule) -> Self {
+        self.default_rules.push(rule);
+        self
+    }
+
+    /// Replace the default rules, including the built-in PII rules
+    pub fn with_default_rules(mut self, rules: Vec<MaskRule>) -> Self {
+        self.default_rules = rules;
+        self
+    }
+
+    /// Set the largest response body that is buffered and masked
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_tokenization_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.tokenization_key = Some(Arc::from(key.as_ref()));
+        self
+    }
+
+    /// Whether responses for a path are masked
+    pub fn applies_to(&self, path: &str) -> bool {
+        self.path_prefixes
+            .iter()
+            .any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    /// Rules for a client with the given scopes; empty if nothing is masked
+    pub fn rules_for(&self, scopes: &[String]) -> Vec<MaskRule> {
+        if scopes.iter().any(|s| self.unmasked_scopes.contains(s)) {
+            return Vec::new();
+        }
+        let rules: Vec<MaskRule> = scopes
+            .iter()
+            .filter_map(|scope| self.scope_rules.get(scope))
+            .flatten()
+            .cloned()
+            .collect();
+        if rules.is_empty() {
+            self.default_rules.clone()
+        } else {
+            rules
+        }
+    }
+
+    /// Mask the fields selected by `rules` in a JSON document
+    pub fn mask(&self, document: &mut Value, rules: &[MaskRule]) {
+        for rule in rules {
+            rule.path.for_each_match(document, &mut |value| {
+                self.mask_value(value, &rule.strategy)
+            });
+        }
+    }
+
+    /// Mask a value, or every value inside an object or array
+    fn mask_value(&self, value: &mut Value, strategy: &MaskStrategy) {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Array(items) => {
+                items
+                    .iter_mut()
+                    .for_each(|item| self.mask_value(item, strategy));
+                return;
+            }
+            Value::Object(map) => {
+                map.values_mut()
+                    .for_each(|item| self.mask_value(item, strategy));
+                return;
+            }
+            Value::Bool(_) | Value::Null => return,
+        };
+        *value = Value::String(self.mask_text(&text, strategy));
+    }
+
+    fn mask_text(&self, text: &str, strategy: &MaskStrategy) -> String {
+        match strategy {
+            MaskStrategy::Redact => REDACTED.to_string(),
+            MaskStrategy::Partial {
+                keep_start,
+                keep_end,
+            } => mask_partial(text, *keep_start, *keep_end),
+            MaskStrategy::Email => match text.rsplit_once('@') {
+                Some((local, domain)) if !local.is_empty() => {
+                    let first: String = local.chars().take(1).collect();
+                    format!("{}***@{}", first, domain)
+                }
+                _ => REDACTED.to_string(),
+            },
+            MaskStrategy::Tokenize => match &self.tokenization_key {
+                Some(key) => tokenize(key, text),
+                None => REDACTED.to_string(),
+            },
+        }
+    }
+}
+
+/// Mask letters and digits outside the first `keep_start` and last
+/// `keep_end` characters; values too short to keep anything are fully masked
+fn mask_partial(text: &str, keep_start: usize, keep_end: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let len = chars.len();
+    let fully_masked = len <= keep_start + keep_end;
+    chars
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let kept = !fully_masked && (i < keep_start || i >= len - keep_end);
+            if kept || !c.is_alphanumeric() {
+                c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
+/// Deterministic token for a value: a truncated HMAC-SHA256 under the key
+fn tokenize(key: &[u8], text: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(text.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", TOKEN_PREFIX, hex)
+}
+
+/// Scopes of the client making a request
+fn client_scopes(req: &ServiceRequest) -> Vec<String> {
+    let from_extensions = req
+        .extensions()
+        .get::<AuthContext>()
+        .map(|ctx| ctx.scopes.clone());
+
+    from_extensions
+        .or_else(|| extract_auth_context(req.request()).map(|ctx| ctx.scopes))
+        .unwrap_or_default()
+}
+
+/// Whether a response carries an uncompressed JSON body
+fn is_plain_json(res: &ServiceResponse<impl MessageBody>) -> bool {
+    let headers = res.headers();
+    !headers.contains_key(header::CONTENT_ENCODING)
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|v| {
+                let v = v.trim();
+                v.eq_ignore_ascii_case("application/json") || v.ends_with("+json")
+            })
+}
+
+/// Response for a body that could not be inspected for masking
+fn unmaskable(reason: &str) -> HttpResponse {
+    tracing::error!(reason, "Response withheld because it could not be masked");
+    HttpResponse::InternalServerError()
+        .json(serde_json::json!({ "error": "Response could not be masked" }))
+}
+
+/// Response masking middleware
+pub struct MaskingMiddleware {
+    config: Option<Arc<MaskingConfig>>,
+}
+
+impl MaskingMiddleware {
+    pub fn new(config: Option<MaskingConfig>) -> Self {
+        Self {
+            config: config.map(Arc::new),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MaskingMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MaskingMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaskingMiddlewareService {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct MaskingMiddlewareService<S> {
+    service: Rc<S>,
+    config: Option<Arc<MaskingConfig>>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaskingMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let masking = self
+            .config
+            .as_ref()
+            .filter(|config| config.applies_to(req.path()))
+            .map(|config| (Arc::clone(config), config.rules_for(&client_scopes(&req))))
+            .filter(|(_, rules)| !rules.is_empty());
+        let Some((config, rules)) = masking else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        };
+
+        // Compressed bodies cannot be inspected, so ask for identity encoding
+        req.headers_mut().remove(header::ACCEPT_ENCODING);
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            let status = res.status();
+            if !status.is_success() || status == StatusCode::NO_CONTENT {
+                return Ok(res.map_into_boxed_body());
+            }
+            let plain_json = is_plain_json(&res);
+
+            let (req, res) = res.into_parts();
+            let (mut head, body) = res.into_parts();
+            let bytes = match actix_web::body::to_bytes_limited(body, config.max_body_bytes).await {
+                Ok(bytes) => {
+                    bytes.map_err(|e| actix_web::error::ErrorInternalServerError(e.into()))?
+                }
+                Err(_) => {
+                    return Ok(ServiceResponse::new(req, unmaskable("body too large")));
+                }
+            };
+            if bytes.is_empty() {
+                return Ok(ServiceResponse::new(
+                    req,
+                    head.set_body(bytes).map_into_boxed_body(),
+                ));
+            }
+            if !plain_json {
+                return Ok(ServiceResponse::new(
+                    req,
+                    unmaskable("compressed or non-JSON body"),
+                ));
+            }
+
+            let mut document = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(document) => document,
+                Err(_) => {
+                    return Ok(ServiceResponse::new(
+                        req,
+                        unmaskable("unparsable JSON body"),
+                    ));
+                }
+            };
+            config.mask(&mut document, &rules);
+            let body = match serde_json::to_vec(&document) {
+                Ok(body) => web::Bytes::from(body),
+                Err(_) => {
+                    return Ok(ServiceResponse::new(
+                        req,
+                        unmaskable("masked body could not be serialized"),
+                    ));
+                }
+            };
+            head.headers_mut().remove(header::CONTENT_LENGTH);
+            Ok(ServiceResponse::new(
+                req,
+                head.set_body(body).map_into_boxed_body(),
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, web, App, HttpRequest, HttpResponse};
+
+    async fn customer(req: HttpRequest) -> HttpResponse {
+        if req.headers().contains_key(header::ACCEPT_ENCODING) {
+            return HttpResponse::Ok()
+                .insert_header((header::CONTENT_ENCODING, "gzip"))
+                .content_type("application/json")
+                .body(vec![0x1f, 0x8b, 0x08]);
+        }
+        HttpResponse::Ok().json(serde_json::json!({
+            "id": "42",
+            "contact": { "email": "jane@example.com" }
+        }))
+    }
+
+    async fn compressed() -> HttpResponse {
+        HttpResponse::Ok()
+            .insert_header((header::CONTENT_ENCODING, "br"))
+            .content_type("application/json")
+            .body(vec![0x0b, 0x02])
+    }
+
+    async fn text() -> HttpResponse {
+        HttpResponse::Ok().body("email=jane@example.com")
+    }
+
+    async fn not_found() -> HttpResponse {
+        HttpResponse::NotFound().body("no customer jane@example.com")
+    }
+
+    async fn no_content() -> HttpResponse {
+        HttpResponse::NoContent().finish()
+    }
+
+    #[actix_web::test]
+    async fn default_rules_redact_pii_and_strip_accept_encoding() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MaskingMiddleware::new(Some(
+                    MaskingConfig::new().with_path_prefix("/"),
+                )))
+                .route("/customer", web::get().to(customer)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/customer")
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["id"], "42");
+        assert_eq!(body["contact"]["email"], REDACTED);
+    }
+
+    #[actix_web::test]
+    async fn uninspectable_bodies_fail_closed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MaskingMiddleware::new(Some(
+                    MaskingConfig::new().with_path_prefix("/"),
+                )))
+                .route("/compressed", web::get().to(compressed))
+                .route("/text", web::get().to(text)),
+        )
+        .await;
+
+        for uri in ["/compressed", "/text"] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR, "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn routes_outside_the_prefixes_pass_through() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MaskingMiddleware::new(Some(
+                    MaskingConfig::new().with_path_prefix("/api"),
+                )))
+                .route("/customer", web::get().to(customer))
+                .route("/text", web::get().to(text)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/customer").to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["contact"]["email"], "jane@example.com");
+
+        let req = test::TestRequest::get().uri("/text").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        assert!(!MaskingConfig::new().applies_to("/customer"));
+    }
+
+    #[actix_web::test]
+    async fn error_and_no_content_responses_pass_through() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MaskingMiddleware::new(Some(
+                    MaskingConfig::new().with_path_prefix("/"),
+                )))
+                .route("/missing", web::get().to(not_found))
+                .route("/empty", web::get().to(no_content)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/missing").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get().uri("/empty").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[actix_web::test]
+    async fn oversized_bodies_fail_closed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MaskingMiddleware::new(Some(
+                    MaskingConfig::new()
+                        .with_path_prefix("/")
+                        .with_max_body_bytes(16),
+                )))
+                .route("/customer", web::get().to(customer)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/customer").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -216,6 +216,12 @@ where
     actix_web::dev::forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        // Already authenticated upstream (e.g. by a client certificate)
+        if req.extensions().contains::<crate::auth::AuthContext>() {
+            let service = Rc::clone(&self.service);
+            return Box::pin(async move { service.call(req).await });
+        }
+
         // Extract and validate auth context
         let auth_context = match crate::auth::validate_token(req.request()) {
             Ok(ctx) => ctx,
@@ -0,0 +1,439 @@
+//! Shadow Traffic Mirroring for API Gateway
+//!
+//! Duplicates a percentage of live requests to a shadow upstream so a new
+//! backend can be compared against the current one before cut-over. Shadow
+//! responses are discarded; only latency and outcome metrics are recorded.
+//!
+//! Mirrored requests are sent from the gateway itself after the rate limiter
+//! has admitted the live request, so they never consume rate-limit quota. They
+//! carry the `X-Shadow-Request` header so the shadow side can exclude them from
+//! billing and usage accounting.
+//!
+//! Only authenticated requests are mirrored, and credential headers
+//! (`Authorization`, `Cookie`, API keys and forwarded client certificates) are
+//! stripped from the shadow copy unless forwarding them is explicitly enabled.
+
+use actix_web::body::MessageBody;
+use actix_web::HttpMessage;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error,
+};
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry};
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Header marking a request as mirrored shadow traffic
+pub const SHADOW_REQUEST_HEADER: &str = "X-Shadow-Request";
+
+/// Headers carrying credentials, stripped from mirrored requests by default
+pub const DEFAULT_CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+];
+
+/// Metrics comparing live and shadow responses for mirrored requests
+pub struct MirrorMetrics {
+    pub requests_total: IntCounterVec,
+    pub request_duration: HistogramVec,
+    pub status_mismatch_total: IntCounter,
+}
+
+impl MirrorMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let requests_total = IntCounterVec::new(
+            Opts::new(
+                "api_gateway_mirror_requests_total",
+                "Mirrored requests by target and outcome",
+            ),
+            &["target", "outcome"],
+        )
+        .expect("Failed to create mirror requests_total metric");
+
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "api_gateway_mirror_request_duration_seconds",
+                "Duration of mirrored requests by target in seconds",
+            )
+            .buckets(vec![
+                0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+            ]),
+            &["target"],
+        )
+        .expect("Failed to create mirror request_duration metric");
+
+        let status_mismatch_total = IntCounter::new(
+            "api_gateway_mirror_status_mismatch_total",
+            "Mirrored requests where the shadow status differed from the live status",
+        )
+        .expect("Failed to create mirror status_mismatch metric");
+
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("Failed to register mirror requests_total");
+        registry
+            .register(Box::new(request_duration.clone()))
+            .expect("Failed to register mirror request_duration");
+        registry
+            .register(Box::new(status_mismatch_total.clone()))
+            .expect("Failed to register mirror status_mismatch");
+
+        Self {
+            requests_total,
+            request_duration,
+            status_mismatch_total,
+        }
+    }
+
+    fn record(&self, target: &str, outcome: &str, duration: Duration) {
+        self.requests_total
+            .with_label_values(&[target, outcome])
+            .inc();
+        self.request_duration
+            .with_label_values(&[target])
+            .observe(duration.as_secs_f64());
+    }
+}
+
+/// Mirror mode configuration
+#[derive(Clone)]
+pub struct MirrorConfig {
+    /// Base URL of the shadow upstream, e.g. `http://new-backend:8080`
+    pub shadow_upstream: String,
+    /// Percentage of requests to mirror (0-100)
+    pub percentage: f64,
+    /// Timeout for shadow requests
+    pub timeout: Duration,
+    /// Requests with larger (or unknown-length) bodies are not mirrored
+    pub max_body_bytes: usize,
+    /// Headers stripped from mirrored requests unless credentials are forwarded
+    pub credential_headers: Vec<String>,
+    /// Whether credential headers are sent to the shadow upstream
+    pub forward_credentials: bool,
+    pub metrics: Arc<MirrorMetrics>,
+}
+
+impl MirrorConfig {
+    pub fn new(shadow_upstream: String, percentage: f64, registry: &Registry) -> Self {
+        Self {
+            shadow_upstream: shadow_upstream.trim_end_matches('/').to_string(),
+            percentage: percentage.clamp(0.0, 100.0),
+            timeout: Duration::from_secs(5),
+            max_body_bytes: 256 * 1024,
+            credential_headers: DEFAULT_CREDENTIAL_HEADERS
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            forward_credentials: false,
+            metrics: Arc::new(MirrorMetrics::new(registry)),
+        }
+    }
+
+    /// Set the largest request body that is buffered and mirrored
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// Strip another credential-bearing header from mirrored requests
+    pub fn with_credential_header(mut self, header: impl Into<String>) -> Self {
+        self.credential_headers.push(header.into());
+        self
+    }
+
+    /// Send credential headers to the shadow upstream as well; only for
+    /// shadow backends trusted with live credentials
+    pub fn with_forwarded_credentials(mut self, forward: bool) -> Self {
+        self.forward_credentials = forward;
+        self
+    }
+
+    /// Whether a header is stripped from mirrored requests
+    fn strips(&self, name: &str) -> bool {
+        !self.forward_credentials
+            && self
+                .credential_headers
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Deterministic sampler selecting an exact share of requests
+struct MirrorSampler {
+    seen: AtomicU64,
+    percentage: f64,
+}
+
+impl MirrorSampler {
+    fn should_mirror(&self) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.percentage / 100.0).floor() > (n * self.percentage / 100.0).floor()
+    }
+}
+
+/// Outcome label for a response status
+fn outcome_label(status: u16) -> &'static str {
+    match status {
+        200..=399 => "success",
+        400..=499 => "client_error",
+        _ => "server_error",
+    }
+}
+
+/// Request captured for replay against the shadow upstream
+struct MirroredRequest {
+    method: reqwest::Method,
+    url: String,
+    headers: reqwest::header::HeaderMap,
+    body: web::Bytes,
+}
+
+impl MirroredRequest {
+    async fn send(self, client: &reqwest::Client) -> Result<u16, reqwest::Error> {
+        let response = client
+            .request(self.method, &self.url)
+            .headers(self.headers)
+            .header(SHADOW_REQUEST_HEADER, "true")
+            .body(self.body)
+            .send()
+            .await?;
+        Ok(response.status().as_u16())
+    }
+}
+
+/// Capture method, URL and headers of a request for mirroring
+fn capture_request(req: &ServiceRequest, config: &MirrorConfig) -> Option<MirroredRequest> {
+    let method = reqwest::Method::from_bytes(req.method().as_str().as_bytes()).ok()?;
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (name, value) in req.headers() {
+        if name == header::HOST || name == header::CONTENT_LENGTH || config.strips(name.as_str()) {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(name.as_str().as_bytes()),
+            reqwest::header::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+
+    Some(MirroredRequest {
+        method,
+        url: format!("{}{}", config.shadow_upstream, path_and_query),
+        headers,
+        body: web::Bytes::new(),
+    })
+}
+
+/// Body length if known and small enough to mirror
+fn mirrorable_body_len(req: &ServiceRequest, max_body_bytes: usize) -> Option<usize> {
+    match req.headers().get(header::CONTENT_LENGTH) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|len| *len <= max_body_bytes),
+        None if req.headers().contains_key(header::TRANSFER_ENCODING) => None,
+        None => Some(0),
+    }
+}
+
+/// Read the request body up to `max_body_bytes`
+///
+/// Reads the payload stream directly rather than through the `Bytes`
+/// extractor, whose limit comes from the app's `PayloadConfig` (256 KiB by
+/// default) and would reject bodies the mirror is configured to accept.
+async fn read_body(
+    req: &mut ServiceRequest,
+    expected_len: usize,
+    max_body_bytes: usize,
+) -> Result<web::Bytes, Error> {
+    let mut payload = req.take_payload();
+    let mut body = web::BytesMut::with_capacity(expected_len);
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > max_body_bytes {
+            return Err(actix_web::error::PayloadError::Overflow.into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// Shadow traffic mirroring middleware
+pub struct MirrorMiddleware {
+    config: Option<MirrorConfig>,
+}
+
+impl MirrorMiddleware {
+    pub fn new(config: Option<MirrorConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MirrorMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MirrorMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let state = self.config.clone().map(|config| {
+            let client = reqwest::Client::builder()
+                .timeout(config.timeout)
+                .build()
+                .unwrap_or_default();
+            Rc::new(MirrorState {
+                sampler: MirrorSampler {
+                    seen: AtomicU64::new(0),
+                    percentage: config.percentage,
+                },
+                client,
+                config,
+            })
+        });
+
+        ready(Ok(MirrorMiddlewareService {
+            service: Rc::new(service),
+            state,
+        }))
+    }
+}
+
+struct MirrorState {
+    config: MirrorConfig,
+    sampler: MirrorSampler,
+    client: reqwest::Client,
+}
+
+pub struct MirrorMiddlewareService<S> {
+    service: Rc<S>,
+    state: Option<Rc<MirrorState>>,
+}
+
+impl<S, B> Service<ServiceRequest> for MirrorMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let state = match &self.state {
+            Some(state) if state.sampler.should_mirror() => Rc::clone(state),
+            _ => return Box::pin(async move { service.call(req).await }),
+        };
+
+        let body_len = mirrorable_body_len(&req, state.config.max_body_bytes);
+        let mirrored = body_len.and_then(|_| capture_request(&req, &state.config));
+
+        Box::pin(async move {
+            let Some(mut mirrored) = mirrored else {
+                return service.call(req).await;
+            };
+
+            // Buffer the body so it can be replayed, then hand it back to the handler
+            if let Some(len) = body_len.filter(|len| *len > 0) {
+                let body = read_body(&mut req, len, state.config.max_body_bytes).await?;
+                req.set_payload(body.clone().into());
+                mirrored.body = body;
+            }
+
+            let started = Instant::now();
+            let res = service.call(req).await?;
+            let live_status = res.status().as_u16();
+            state
+                .config
+                .metrics
+                .record("live", outcome_label(live_status), started.elapsed());
+
+            // Fire and forget: the shadow response never affects the live one
+            actix_web::rt::spawn(async move {
+                let started = Instant::now();
+                let metrics = &state.config.metrics;
+                match mirrored.send(&state.client).await {
+                    Ok(shadow_status) => {
+                        metrics.record("shadow", outcome_label(shadow_status), started.elapsed());
+                        if shadow_status != live_status {
+                            metrics.status_mismatch_total.inc();
+                        }
+                    }
+                    Err(e) => {
+                        metrics.record("shadow", "transport_error", started.elapsed());
+                        tracing::debug!(error = %e, "Shadow request failed");
+                    }
+                }
+            });
+
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn config() -> MirrorConfig {
+        MirrorConfig::new("http://shadow".to_string(), 100.0, &Registry::new())
+    }
+
+    fn request() -> ServiceRequest {
+        TestRequest::get()
+            .uri("/orders?page=2")
+            .insert_header((header::AUTHORIZATION, "Bearer secret"))
+            .insert_header((header::COOKIE, "session=secret"))
+            .insert_header(("X-Client-Cert", "pem"))
+            .insert_header((header::ACCEPT, "application/json"))
+            .to_srv_request()
+    }
+
+    #[test]
+    fn credential_headers_are_stripped_by_default() {
+        let config = config().with_credential_header("X-Client-Cert");
+        let mirrored = capture_request(&request(), &config).unwrap();
+
+        assert_eq!(mirrored.url, "http://shadow/orders?page=2");
+        assert!(mirrored.headers.get("authorization").is_none());
+        assert!(mirrored.headers.get("cookie").is_none());
+        assert!(mirrored.headers.get("x-client-cert").is_none());
+        assert_eq!(mirrored.headers["accept"], "application/json");
+    }
+
+    #[test]
+    fn credential_headers_are_forwarded_when_enabled() {
+        let config = config().with_forwarded_credentials(true);
+        let mirrored = capture_request(&request(), &config).unwrap();
+
+        assert_eq!(mirrored.headers["authorization"], "Bearer secret");
+        assert_eq!(mirrored.headers["cookie"], "session=secret");
+    }
+}
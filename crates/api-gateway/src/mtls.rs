@@ -0,0 +1,435 @@
+//! Mutual TLS Client Authentication for API Gateway
+//!
+//! Requires and verifies client certificates on configured routes and maps the
+//! certificate to a principal for downstream authorization. The certificate is
+//! taken from the TLS connection (see [`PeerCertificate`]) or, when the gateway
+//! sits behind a TLS-terminating proxy, from a configured forwarding header.
+//! The forwarding header is only honoured from configured trusted proxies and
+//! is stripped from every other request.
+
+use actix_web::body::MessageBody;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use futures::future::LocalBoxFuture;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::sync::Arc;
+use thiserror::Error;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::prelude::FromDer;
+
+use crate::auth::AuthContext;
+
+/// DER-encoded client certificate presented on the TLS connection
+///
+/// Insert this into the connection data from the server's `on_connect` hook
+/// so the middleware can read the verified peer certificate.
+#[derive(Debug, Clone)]
+pub struct PeerCertificate(pub Vec<u8>);
+
+/// How a client certificate is mapped to a principal
+#[derive(Debug, Clone)]
+pub enum PrincipalMapping {
+    /// Use the subject common name
+    CommonName,
+    /// Use the first DNS, email or URI subject alternative name
+    SubjectAltName,
+    /// Look up the subject DN or any SAN in an explicit table
+    Mapped(HashMap<String, String>),
+}
+
+/// Mutual TLS configuration
+#[derive(Debug, Clone)]
+pub struct MtlsConfig {
+    /// Path prefixes that require a client certificate
+    pub routes: Vec<String>,
+    /// Trusted issuing CA certificates (DER)
+    pub trusted_cas: Vec<Vec<u8>>,
+    /// Header carrying the URL-encoded PEM certificate from a TLS-terminating proxy
+    pub forwarded_cert_header: Option<String>,
+    /// Peer addresses allowed to set the forwarded certificate header
+    pub trusted_proxies: Vec<IpAddr>,
+    pub principal_mapping: PrincipalMapping,
+    /// Tenant each certificate principal belongs to
+    pub principal_tenants: HashMap<String, String>,
+}
+
+impl MtlsConfig {
+    pub fn new(trusted_cas: Vec<Vec<u8>>) -> Self {
+        Self {
+            routes: vec![],
+            trusted_cas,
+            forwarded_cert_header: None,
+            trusted_proxies: vec![],
+            principal_mapping: PrincipalMapping::CommonName,
+            principal_tenants: HashMap::new(),
+        }
+    }
+
+    pub fn with_route(mut self, prefix: impl Into<String>) -> Self {
+        self.routes.push(prefix.into());
+        self
+    }
+
+    pub fn with_forwarded_header(mut self, header: impl Into<String>) -> Self {
+        self.forwarded_cert_header = Some(header.into());
+        self
+    }
+
+    pub fn with_trusted_proxy(mut self, addr: IpAddr) -> Self {
+        self.trusted_proxies.push(addr);
+        self
+    }
+
+    pub fn with_principal_mapping(mut self, mapping: PrincipalMapping) -> Self {
+        self.principal_mapping = mapping;
+        self
+    }
+
+    pub fn with_principal_tenant(
+        mut self,
+        principal: impl Into<String>,
+        tenant_id: impl Into<String>,
+    ) -> Self {
+        self.principal_tenants
+            .insert(principal.into(), tenant_id.into());
+        self
+    }
+
+    /// Authentication context for a verified certificate's principal
+    pub fn auth_context(&self, principal: &str) -> AuthContext {
+        let ctx = AuthContext::new(principal.to_string());
+        match self.principal_tenants.get(principal) {
+            Some(tenant_id) => ctx.with_tenant(tenant_id.clone()),
+            None => ctx,
+        }
+    }
+
+    /// Whether a request path requires a client certificate
+    pub fn requires_client_cert(&self, path: &str) -> bool {
+        self.routes.iter().any(|prefix| path.starts_with(prefix))
+    }
+
+    /// Whether the forwarded certificate header may be trusted from this peer
+    pub fn is_trusted_proxy(&self, peer: Option<IpAddr>) -> bool {
+        peer.is_some_and(|ip| self.trusted_proxies.contains(&ip))
+    }
+}
+
+/// Verified client certificate details
+#[derive(Debug, Clone)]
+pub struct ClientCertificate {
+    pub subject: String,
+    pub common_name: Option<String>,
+    pub subject_alt_names: Vec<String>,
+    pub issuer: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub principal: String,
+}
+
+/// mTLS verification errors
+#[derive(Debug, Error)]
+pub enum MtlsError {
+    #[error("Client certificate required")]
+    MissingCertificate,
+    #[error("Invalid client certificate: {0}")]
+    InvalidCertificate(String),
+    #[error("Client certificate expired or not yet valid")]
+    OutsideValidity,
+    #[error("Client certificate not issued by a trusted CA")]
+    Untrusted,
+    #[error("No principal mapped for client certificate")]
+    NoPrincipal,
+}
+
+impl From<MtlsError> for HttpResponse {
+    fn from(err: MtlsError) -> Self {
+        let body = serde_json::json!({ "error": err.to_string() });
+        match err {
+            MtlsError::NoPrincipal => HttpResponse::Forbidden().json(body),
+            _ => HttpResponse::Unauthorized().json(body),
+        }
+    }
+}
+
+/// Verify a DER-encoded client certificate against the configuration
+pub fn verify_client_certificate(
+    der: &[u8],
+    config: &MtlsConfig,
+    now: DateTime<Utc>,
+) -> Result<ClientCertificate, MtlsError> {
+    let (_, cert) =
+        X509Certificate::from_der(der).map_err(|e| MtlsError::InvalidCertificate(e.to_string()))?;
+
+    let not_before = to_utc(cert.validity().not_before.timestamp())?;
+    let not_after = to_utc(cert.validity().not_after.timestamp())?;
+    if now < not_before || now > not_after {
+        return Err(MtlsError::OutsideValidity);
+    }
+
+    let trusted = config.trusted_cas.iter().any(|ca_der| {
+        X509Certificate::from_der(ca_der)
+            .map(|(_, ca)| {
+                ca.subject() == cert.issuer()
+                    && cert.verify_signature(Some(ca.public_key())).is_ok()
+            })
+            .unwrap_or(false)
+    });
+    if !trusted {
+        return Err(MtlsError::Untrusted);
+    }
+
+    let subject = cert.subject().to_string();
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string);
+    let subject_alt_names: Vec<String> = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|ext| {
+            ext.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(s) | GeneralName::RFC822Name(s) | GeneralName::URI(s) => {
+                        Some(s.to_string())
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let principal = match &config.principal_mapping {
+        PrincipalMapping::CommonName => common_name.clone(),
+        PrincipalMapping::SubjectAltName => subject_alt_names.first().cloned(),
+        PrincipalMapping::Mapped(table) => table
+            .get(&subject)
+            .or_else(|| subject_alt_names.iter().find_map(|san| table.get(san)))
+            .cloned(),
+    }
+    .ok_or(MtlsError::NoPrincipal)?;
+
+    Ok(ClientCertificate {
+        subject,
+        common_name,
+        subject_alt_names,
+        issuer: cert.issuer().to_string(),
+        not_before,
+        not_after,
+        principal,
+    })
+}
+
+fn to_utc(timestamp: i64) -> Result<DateTime<Utc>, MtlsError> {
+    DateTime::from_timestamp(timestamp, 0).ok_or_else(|| {
+        MtlsError::InvalidCertificate("Certificate validity out of range".to_string())
+    })
+}
+
+/// Find the presented client certificate, as DER
+///
+/// The forwarded header is expected to have been stripped already unless the
+/// peer is a trusted proxy.
+fn extract_certificate(req: &ServiceRequest, config: &MtlsConfig) -> Option<Vec<u8>> {
+    if let Some(peer) = req.conn_data::<PeerCertificate>() {
+        return Some(peer.0.clone());
+    }
+
+    let header = config.forwarded_cert_header.as_deref()?;
+    let value = req.headers().get(header)?.to_str().ok()?;
+    let pem = percent_decode_str(value).decode_utf8().ok()?;
+    parse_x509_pem(pem.as_bytes())
+        .ok()
+        .map(|(_, pem)| pem.contents)
+}
+
+/// Mutual TLS middleware
+pub struct MtlsMiddleware {
+    config: Option<Arc<MtlsConfig>>,
+}
+
+impl MtlsMiddleware {
+    pub fn new(config: Option<MtlsConfig>) -> Self {
+        Self {
+            config: config.map(Arc::new),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MtlsMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MtlsMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MtlsMiddlewareService {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct MtlsMiddlewareService<S> {
+    service: Rc<S>,
+    config: Option<Arc<MtlsConfig>>,
+}
+
+impl<S, B> Service<ServiceRequest> for MtlsMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        // A client talking to the gateway directly must not be able to assert a
+        // certificate through the proxy header, on any route
+        if let Some(config) = &self.config {
+            if let Some(header) = config.forwarded_cert_header.as_deref() {
+                let peer = req.peer_addr().map(|addr| addr.ip());
+                if !config.is_trusted_proxy(peer) && req.headers().contains_key(header) {
+                    tracing::warn!(path = %req.path(), peer = ?peer, "Stripped forwarded client certificate from untrusted peer");
+                    req.headers_mut().remove(header);
+                }
+            }
+        }
+
+        let config = match &self.config {
+            Some(config) if config.requires_client_cert(req.path()) => Arc::clone(config),
+            _ => {
+                return Box::pin(async move {
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_boxed_body())
+                });
+            }
+        };
+
+        let verified = extract_certificate(&req, &config)
+            .ok_or(MtlsError::MissingCertificate)
+            .and_then(|der| verify_client_certificate(&der, &config, Utc::now()));
+
+        match verified {
+            Ok(cert) => {
+                // The certificate principal authenticates the request downstream
+                req.extensions_mut()
+                    .insert(config.auth_context(&cert.principal));
+                req.extensions_mut().insert(cert);
+                Box::pin(async move {
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_boxed_body())
+                })
+            }
+            Err(e) => {
+                tracing::warn!(path = %req.path(), error = %e, "Client certificate rejected");
+                let http_resp: HttpResponse = e.into();
+                let (req, _) = req.into_parts();
+                Box::pin(
+                    async move { Ok(ServiceResponse::new(req, http_resp.map_into_boxed_body())) },
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpRequest};
+
+    const CERT_HEADER: &str = "X-Client-Cert";
+
+    fn config() -> MtlsConfig {
+        MtlsConfig::new(vec![])
+            .with_route("/secure")
+            .with_forwarded_header(CERT_HEADER)
+            .with_trusted_proxy("10.0.0.1".parse().unwrap())
+    }
+
+    async fn echo_cert_header(req: HttpRequest) -> HttpResponse {
+        let forwarded = req.headers().contains_key(CERT_HEADER);
+        HttpResponse::Ok().body(forwarded.to_string())
+    }
+
+    #[actix_web::test]
+    async fn direct_client_cannot_assert_forwarded_certificate() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MtlsMiddleware::new(Some(config())))
+                .route("/secure/resource", web::get().to(echo_cert_header)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/secure/resource")
+            .peer_addr("203.0.113.5:40000".parse().unwrap())
+            .insert_header((CERT_HEADER, "-----BEGIN%20CERTIFICATE-----"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn forwarded_header_is_stripped_from_untrusted_peers() {
+        let app = test::init_service(
+            App::new()
+                .wrap(MtlsMiddleware::new(Some(config())))
+                .route("/open", web::get().to(echo_cert_header)),
+        )
+        .await;
+
+        let untrusted = test::TestRequest::get()
+            .uri("/open")
+            .peer_addr("203.0.113.5:40000".parse().unwrap())
+            .insert_header((CERT_HEADER, "spoofed"))
+            .to_request();
+        let body = test::call_and_read_body(&app, untrusted).await;
+        assert_eq!(body, "false");
+
+        let trusted = test::TestRequest::get()
+            .uri("/open")
+            .peer_addr("10.0.0.1:40000".parse().unwrap())
+            .insert_header((CERT_HEADER, "forwarded"))
+            .to_request();
+        let body = test::call_and_read_body(&app, trusted).await;
+        assert_eq!(body, "true");
+    }
+
+    #[actix_web::test]
+    async fn principal_tenant_is_carried_in_auth_context() {
+        let config = config().with_principal_tenant("billing-batch", "tenant-a");
+
+        let mapped = config.auth_context("billing-batch");
+        assert_eq!(mapped.user_id, "billing-batch");
+        assert_eq!(mapped.tenant_id.as_deref(), Some("tenant-a"));
+
+        assert_eq!(config.auth_context("unknown").tenant_id, None);
+    }
+}
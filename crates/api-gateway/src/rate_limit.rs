@@ -118,6 +118,11 @@ pub enum RateLimitError {
         limit: u64,
         window: u64,
     },
+    #[error("Concurrency limit exceeded: {max_concurrent} requests in flight. Retry after {retry_after} seconds")]
+    ConcurrencyLimitExceeded {
+        retry_after: u64,
+        max_concurrent: usize,
+    },
 }
 
 impl From<RateLimitError> for HttpResponse {
@@ -137,6 +142,17 @@ impl From<RateLimitError> for HttpResponse {
                     "limit": limit,
                     "window_seconds": window
                 })),
+            RateLimitError::ConcurrencyLimitExceeded {
+                retry_after,
+                max_concurrent,
+            } => HttpResponse::TooManyRequests()
+                .append_header(("X-RateLimit-Concurrency", max_concurrent.to_string()))
+                .append_header(("X-RateLimit-Retry-After", retry_after.to_string()))
+                .json(serde_json::json!({
+                    "error": "Concurrency limit exceeded",
+                    "retry_after": retry_after,
+                    "max_concurrent": max_concurrent
+                })),
         }
     }
 }
@@ -0,0 +1,252 @@
+//! Per-Tenant Rate Limiting and Isolation for API Gateway
+//!
+//! Each tenant, resolved from the client certificate's principal on mTLS
+//! routes or from the `tenant_id` claim of the bearer token, gets its own
+//! request-rate window and its own pool of concurrent request slots.
+//! Requests beyond a tenant's slots wait in that tenant's FIFO queue, so a
+//! traffic spike from one tenant only ever delays that tenant. A request that
+//! exceeds the tenant's rate, or cannot get a slot within the queue timeout,
+//! is answered with 429 while other tenants are unaffected.
+
+use actix_web::body::MessageBody;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    Error, HttpMessage, HttpResponse,
+};
+use dashmap::DashMap;
+use futures::future::LocalBoxFuture;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::auth::{extract_auth_context, AuthContext};
+use crate::rate_limit::{RateLimitConfig, RateLimitError, RateLimitIdentifier, RateLimiter};
+
+/// Tenant that requests without a resolvable tenant are accounted to
+pub const ANONYMOUS_TENANT: &str = "anonymous";
+
+/// Header naming the tenant a limit applied to
+pub const TENANT_HEADER: &str = "X-Tenant-Id";
+
+/// Limits applied to a single tenant
+#[derive(Debug, Clone)]
+pub struct TenantLimit {
+    pub max_requests: u64,
+    pub window_seconds: u64,
+    /// Guaranteed concurrent requests; further requests queue
+    pub max_concurrent: usize,
+}
+
+impl Default for TenantLimit {
+    fn default() -> Self {
+        Self {
+            max_requests: 1000,
+            window_seconds: 60,
+            max_concurrent: 20,
+        }
+    }
+}
+
+/// Per-tenant rate limit configuration
+#[derive(Debug, Clone, Default)]
+pub struct TenantRateLimitConfig {
+    /// Limit for tenants without an override
+    pub default_limit: TenantLimit,
+    /// Overrides by tenant ID
+    pub tenant_limits: HashMap<String, TenantLimit>,
+    /// Longest a request waits in its tenant's queue for a slot
+    pub queue_timeout: Duration,
+}
+
+impl TenantRateLimitConfig {
+    pub fn new(default_limit: TenantLimit) -> Self {
+        Self {
+            default_limit,
+            tenant_limits: HashMap::new(),
+            queue_timeout: Duration::from_secs(2),
+        }
+    }
+
+    pub fn with_tenant_limit(mut self, tenant_id: impl Into<String>, limit: TenantLimit) -> Self {
+        self.tenant_limits.insert(tenant_id.into(), limit);
+        self
+    }
+
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+
+    /// Limit applying to a tenant
+    pub fn limit_for(&self, tenant_id: &str) -> &TenantLimit {
+        self.tenant_limits
+            .get(tenant_id)
+            .unwrap_or(&self.default_limit)
+    }
+}
+
+/// Rate window and request slots of one tenant
+struct TenantState {
+    limiter: RateLimiter,
+    slots: Arc<Semaphore>,
+    max_concurrent: usize,
+}
+
+/// Per-tenant limiter, creating each tenant's state on first use
+#[derive(Clone)]
+pub struct TenantLimiter {
+    config: Arc<TenantRateLimitConfig>,
+    tenants: Arc<DashMap<String, Arc<TenantState>>>,
+}
+
+impl TenantLimiter {
+    pub fn new(config: TenantRateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            tenants: Arc::new(DashMap::new()),
+        }
+    }
+
+    fn tenant(&self, tenant_id: &str) -> Arc<TenantState> {
+        self.tenants
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| {
+                let limit = self.config.limit_for(tenant_id);
+                Arc::new(TenantState {
+                    limiter: RateLimiter::new(RateLimitConfig {
+                        max_requests: limit.max_requests,
+                        window_seconds: limit.window_seconds,
+                        identifier: RateLimitIdentifier::Header(TENANT_HEADER.to_string()),
+                    }),
+                    slots: Arc::new(Semaphore::new(limit.max_concurrent)),
+                    max_concurrent: limit.max_concurrent,
+                })
+            })
+            .clone()
+    }
+
+    /// Admit a request for a tenant, waiting in the tenant's queue for a slot
+    ///
+    /// The returned permit holds the slot until dropped.
+    pub async fn acquire(
+        &self,
+        tenant_id: &str,
+    ) -> Result<tokio::sync::OwnedSemaphorePermit, RateLimitError> {
+        let tenant = self.tenant(tenant_id);
+        tenant.limiter.check(tenant_id)?;
+
+        let queue_timeout = self.config.queue_timeout;
+        match tokio::time::timeout(queue_timeout, Arc::clone(&tenant.slots).acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => Err(RateLimitError::ConcurrencyLimitExceeded {
+                retry_after: queue_timeout.as_secs().max(1),
+                max_concurrent: tenant.max_concurrent,
+            }),
+        }
+    }
+
+    /// Clean up expired rate windows (call periodically)
+    pub fn cleanup(&self) {
+        for tenant in self.tenants.iter() {
+            tenant.limiter.cleanup();
+        }
+    }
+}
+
+/// Resolve the tenant of a request from its authentication context or token
+///
+/// A request already authenticated by client certificate is accounted to the
+/// certificate's tenant; its bearer token, if any, cannot choose another.
+pub fn resolve_tenant(req: &ServiceRequest) -> String {
+    let from_extensions = req
+        .extensions()
+        .get::<AuthContext>()
+        .map(|ctx| ctx.tenant_id.clone());
+
+    from_extensions
+        .unwrap_or_else(|| extract_auth_context(req.request()).and_then(|ctx| ctx.tenant_id))
+        .unwrap_or_else(|| ANONYMOUS_TENANT.to_string())
+}
+
+/// Per-tenant rate limiting middleware
+pub struct TenantRateLimitMiddleware {
+    limiter: Option<TenantLimiter>,
+}
+
+impl TenantRateLimitMiddleware {
+    pub fn new(config: Option<TenantRateLimitConfig>) -> Self {
+        Self {
+            limiter: config.map(TenantLimiter::new),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TenantRateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TenantRateLimitMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenantRateLimitMiddlewareService {
+            service: Rc::new(service),
+            limiter: self.limiter.clone(),
+        }))
+    }
+}
+
+pub struct TenantRateLimitMiddlewareService<S> {
+    service: Rc<S>,
+    limiter: Option<TenantLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantRateLimitMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let Some(limiter) = self.limiter.clone() else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        };
+        let tenant_id = resolve_tenant(&req);
+
+        Box::pin(async move {
+            match limiter.acquire(&tenant_id).await {
+                Ok(permit) => {
+                    let res = service.call(req).await?;
+                    drop(permit);
+                    Ok(res.map_into_boxed_body())
+                }
+                Err(e) => {
+                    let mut http_resp: HttpResponse = e.into();
+                    if let Ok(value) = HeaderValue::from_str(&tenant_id) {
+                        http_resp
+                            .headers_mut()
+                            .insert(HeaderName::from_static("x-tenant-id"), value);
+                    }
+                    let (req, _) = req.into_parts();
+                    Ok(ServiceResponse::new(req, http_resp.map_into_boxed_body()))
+                }
+            }
+        })
+    }
+}
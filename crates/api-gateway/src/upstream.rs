@@ -0,0 +1,300 @@
+//! Latency-Aware Upstream Selection for API Gateway
+//!
+//! A route served by several upstream instances keeps an exponentially
+//! weighted moving average (EWMA) of each instance's response times and sends
+//! traffic to the fastest one. A small share of selections is spent exploring
+//! the other instances in turn, so a degraded instance that has recovered is
+//! noticed and picked up again. Failed calls count as slow responses.
+//!
+//! Per-instance latency, request and selection metrics are exported to
+//! Prometheus.
+
+use prometheus::{GaugeVec, IntCounterVec, Opts, Registry};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Metrics for upstream instances
+pub struct UpstreamMetrics {
+    pub latency_ewma: GaugeVec,
+    pub requests_total: IntCounterVec,
+    pub selections_total: IntCounterVec,
+}
+
+impl UpstreamMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let latency_ewma = GaugeVec::new(
+            Opts::new(
+                "api_gateway_upstream_latency_ewma_seconds",
+                "Moving average of upstream instance response times in seconds",
+            ),
+            &["route", "instance"],
+        )
+        .expect("Failed to create upstream latency_ewma metric");
+
+        let requests_total = IntCounterVec::new(
+            Opts::new(
+                "api_gateway_upstream_requests_total",
+                "Upstream requests by route, instance and outcome",
+            ),
+            &["route", "instance", "outcome"],
+        )
+        .expect("Failed to create upstream requests_total metric");
+
+        let selections_total = IntCounterVec::new(
+            Opts::new(
+                "api_gateway_upstream_selections_total",
+                "Upstream instance selections by route, instance and reason",
+            ),
+            &["route", "instance", "reason"],
+        )
+        .expect("Failed to create upstream selections_total metric");
+
+        registry
+            .register(Box::new(latency_ewma.clone()))
+            .expect("Failed to register upstream latency_ewma");
+        registry
+            .register(Box::new(requests_total.clone()))
+            .expect("Failed to register upstream requests_total");
+        registry
+            .register(Box::new(selections_total.clone()))
+            .expect("Failed to register upstream selections_total");
+
+        Self {
+            latency_ewma,
+            requests_total,
+            selections_total,
+        }
+    }
+}
+
+/// Upstream selection tuning
+#[derive(Debug, Clone)]
+pub struct UpstreamSelectionConfig {
+    /// Weight of the newest sample in the moving average (0-1]
+    pub ewma_alpha: f64,
+    /// Percentage of selections spent on instances other than the fastest
+    pub exploration_percentage: f64,
+    /// Latency charged for a failed call on top of its measured duration
+    pub failure_penalty: Duration,
+}
+
+impl Default for UpstreamSelectionConfig {
+    fn default() -> Self {
+        Self {
+            ewma_alpha: 0.3,
+            exploration_percentage: 5.0,
+            failure_penalty: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Snapshot of an upstream instance's statistics
+#[derive(Debug, Clone)]
+pub struct UpstreamStats {
+    pub url: String,
+    /// Moving average response time, `None` before the first response
+    pub latency_ewma: Option<Duration>,
+    pub requests: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone)]
+struct InstanceState {
+    url: String,
+    ewma_secs: Option<f64>,
+    requests: u64,
+    failures: u64,
+}
+
+/// Reason an instance was selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionReason {
+    /// Lowest moving average, or not measured yet
+    Fastest,
+    /// Exploration of a slower instance
+    Explore,
+}
+
+impl SelectionReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SelectionReason::Fastest => "fastest",
+            SelectionReason::Explore => "explore",
+        }
+    }
+}
+
+/// Upstream instances serving one route
+pub struct UpstreamPool {
+    route: String,
+    config: UpstreamSelectionConfig,
+    instances: Mutex<Vec<InstanceState>>,
+    selections: AtomicU64,
+    explorations: AtomicU64,
+    metrics: Option<Arc<UpstreamMetrics>>,
+}
+
+impl UpstreamPool {
+    pub fn new(route: impl Into<String>, urls: Vec<String>) -> Self {
+        Self {
+            route: route.into(),
+            config: UpstreamSelectionConfig::default(),
+            instances: Mutex::new(
+                urls.into_iter()
+                    .map(|url| InstanceState {
+                        url: url.trim_end_matches('/').to_string(),
+                        ewma_secs: None,
+                        requests: 0,
+                        failures: 0,
+                    })
+                    .collect(),
+            ),
+            selections: AtomicU64::new(0),
+            explorations: AtomicU64::new(0),
+            metrics: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: UpstreamSelectionConfig) -> Self {
+        self.config = UpstreamSelectionConfig {
+            ewma_alpha: config.ewma_alpha.clamp(f64::EPSILON, 1.0),
+            exploration_percentage: config.exploration_percentage.clamp(0.0, 100.0),
+            failure_penalty: config.failure_penalty,
+        };
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<UpstreamMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    /// Whether this selection is an exploration, spread evenly over selections
+    fn should_explore(&self) -> bool {
+        let n = self.selections.fetch_add(1, Ordering::Relaxed) as f64;
+        let pct = self.config.exploration_percentage;
+        ((n + 1.0) * pct / 100.0).floor() > (n * pct / 100.0).floor()
+    }
+
+    /// Pick the instance for the next call
+    ///
+    /// Unmeasured instances are tried first. Otherwise the fastest instance is
+    /// chosen, except on exploration turns, which cycle through the others.
+    pub fn select(&self) -> Option<(String, SelectionReason)> {
+        let explore = self.should_explore();
+        let instances = self.instances.lock().expect("upstream pool lock poisoned");
+
+        if let Some(unmeasured) = instances.iter().find(|i| i.ewma_secs.is_none()) {
+            return Some(self.selected(&unmeasured.url, SelectionReason::Fastest));
+        }
+
+        let fastest = instances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, i)| i.ewma_secs.map(|ewma| (index, ewma)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)?;
+
+        if explore && instances.len() > 1 {
+            let turn = self.explorations.fetch_add(1, Ordering::Relaxed) as usize;
+            let offset = 1 + turn % (instances.len() - 1);
+            let index = (fastest + offset) % instances.len();
+            return Some(self.selected(&instances[index].url, SelectionReason::Explore));
+        }
+
+        Some(self.selected(&instances[fastest].url, SelectionReason::Fastest))
+    }
+
+    fn selected(&self, url: &str, reason: SelectionReason) -> (String, SelectionReason) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .selections_total
+                .with_label_values(&[&self.route, url, reason.as_str()])
+                .inc();
+        }
+        (url.to_string(), reason)
+    }
+
+    /// Record the outcome of a call to an instance
+    pub fn record(&self, url: &str, latency: Duration, success: bool) {
+        let mut instances = self.instances.lock().expect("upstream pool lock poisoned");
+        let Some(instance) = instances.iter_mut().find(|i| i.url == url) else {
+            return;
+        };
+
+        let mut sample = latency.as_secs_f64();
+        if !success {
+            sample += self.config.failure_penalty.as_secs_f64();
+            instance.failures += 1;
+        }
+        instance.requests += 1;
+
+        let alpha = self.config.ewma_alpha;
+        let ewma = match instance.ewma_secs {
+            Some(previous) => alpha * sample + (1.0 - alpha) * previous,
+            None => sample,
+        };
+        instance.ewma_secs = Some(ewma);
+
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .latency_ewma
+                .with_label_values(&[&self.route, url])
+                .set(ewma);
+            metrics
+                .requests_total
+                .with_label_values(&[
+                    &self.route,
+                    url,
+                    if success { "success" } else { "failure" },
+                ])
+                .inc();
+        }
+    }
+
+    /// Statistics of every instance
+    pub fn stats(&self) -> Vec<UpstreamStats> {
+        self.instances
+            .lock()
+            .expect("upstream pool lock poisoned")
+            .iter()
+            .map(|i| UpstreamStats {
+                url: i.url.clone(),
+                latency_ewma: i.ewma_secs.map(Duration::from_secs_f64),
+                requests: i.requests,
+                failures: i.failures,
+            })
+            .collect()
+    }
+}
+
+/// Upstream pools by route path prefix
+#[derive(Clone, Default)]
+pub struct UpstreamRoutes {
+    routes: Vec<(String, Arc<UpstreamPool>)>,
+}
+
+impl UpstreamRoutes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_route(mut self, prefix: impl Into<String>, pool: UpstreamPool) -> Self {
+        self.routes.push((prefix.into(), Arc::new(pool)));
+        self
+    }
+
+    /// Pool of the longest route prefix matching a path
+    pub fn pool_for(&self, path: &str) -> Option<Arc<UpstreamPool>> {
+        self.routes
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, pool)| Arc::clone(pool))
+    }
+}
@@ -81,6 +81,7 @@ pub fn get_api_version(req: &HttpRequest) -> ApiVersion {
 }
 
 /// Version validation middleware
+#[allow(clippy::result_large_err)]
 pub fn validate_version(
     req: &HttpRequest,
     supported_versions: &[ApiVersion],
@@ -120,6 +121,7 @@ pub fn find_compatible_version(
 }
 
 /// Version negotiation - finds the best version match
+#[allow(clippy::result_large_err)]
 pub fn negotiate_version(
     req: &HttpRequest,
     supported_versions: &[ApiVersion],
@@ -0,0 +1,184 @@
+//! Audit Event Anchoring
+//!
+//! Forwards selected security and TMF audit events to the blockchain so they
+//! get an immutable record. Events are queued and sealed together once a batch
+//! fills up, or by the periodic flush, instead of mining a block per event.
+//! Only the configured event types are anchored; everything else stays in the
+//! regular audit log alone.
+//!
+//! Security events arrive through the `AuditSink` implementation, which can be
+//! registered on the security `AuditLogger` policy. TMF events from the
+//! `audit-logging` crate are passed to [`BlockchainAnchor::anchor`] directly.
+
+use crate::error::BlockchainAuditError;
+use crate::service::BlockchainAuditService;
+use async_trait::async_trait;
+use audit_logging::models::{AuditEventType, AuditLogEntry, AuditResult};
+use security::audit::AuditSink;
+use security::error::SecurityError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Which events are anchored and how they are batched
+#[derive(Debug, Clone)]
+pub struct AnchorConfig {
+    /// Event types forwarded to the blockchain
+    pub event_types: Vec<AuditEventType>,
+    /// Pending entries that trigger sealing a block
+    pub batch_size: usize,
+    /// Longest an anchored entry waits before the flush seals it
+    pub flush_interval: Duration,
+}
+
+impl Default for AnchorConfig {
+    fn default() -> Self {
+        Self {
+            event_types: vec![
+                AuditEventType::RoleAssignment,
+                AuditEventType::PermissionChange,
+                AuditEventType::PasswordChange,
+                AuditEventType::MfaDisabled,
+                AuditEventType::AccountLocked,
+                AuditEventType::AccountUnlocked,
+                AuditEventType::SecurityPolicyViolation,
+                AuditEventType::CustomerDeleted,
+                AuditEventType::PaymentProcessed,
+                AuditEventType::RefundIssued,
+                AuditEventType::ConfigurationChanged,
+                AuditEventType::PolicyUpdated,
+            ],
+            batch_size: 50,
+            flush_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl AnchorConfig {
+    /// Anchor only the given event types
+    pub fn new(event_types: Vec<AuditEventType>) -> Self {
+        Self {
+            event_types,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_event_type(mut self, event_type: AuditEventType) -> Self {
+        if !self.event_types.contains(&event_type) {
+            self.event_types.push(event_type);
+        }
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+}
+
+/// Forwards selected audit events to the blockchain in batches
+pub struct BlockchainAnchor {
+    service: Arc<BlockchainAuditService>,
+    config: AnchorConfig,
+}
+
+impl BlockchainAnchor {
+    pub fn new(service: Arc<BlockchainAuditService>, config: AnchorConfig) -> Self {
+        Self { service, config }
+    }
+
+    /// Whether events of this type are anchored
+    pub fn should_anchor(&self, event_type: AuditEventType) -> bool {
+        self.config.event_types.contains(&event_type)
+    }
+
+    /// Queue an event for anchoring, sealing a block once the batch is full
+    ///
+    /// Returns whether the event was selected for anchoring.
+    pub async fn anchor(&self, entry: AuditLogEntry) -> Result<bool, BlockchainAuditError> {
+        if !self.should_anchor(entry.event_type) {
+            return Ok(false);
+        }
+
+        self.service.append_entry(entry).await?;
+        if self.service.pending_entries().await >= self.config.batch_size {
+            self.service.finalize_block().await?;
+        }
+        Ok(true)
+    }
+
+    /// Seal all queued events into a block
+    pub async fn flush(&self) -> Result<(), BlockchainAuditError> {
+        self.service.finalize_block().await
+    }
+
+    /// Periodically seal queued events so a partial batch is not held forever
+    pub fn spawn_flush(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.flush_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.flush().await {
+                    log::warn!("Failed to seal anchored audit events: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for BlockchainAnchor {
+    async fn send(&self, entry: &security::models::AuditLogEntry) -> Result<(), SecurityError> {
+        self.anchor(from_security_entry(entry))
+            .await
+            .map(|_| ())
+            .map_err(|e| SecurityError::Audit(e.to_string()))
+    }
+}
+
+/// Convert a security audit entry to the shared audit log model
+fn from_security_entry(entry: &security::models::AuditLogEntry) -> AuditLogEntry {
+    use security::models::{AuditEventType as Security, AuditResult as SecurityResult};
+
+    let event_type = match entry.event_type {
+        Security::Authentication => AuditEventType::Authentication,
+        Security::Authorization => AuditEventType::Authorization,
+        Security::RoleAssignment => AuditEventType::RoleAssignment,
+        Security::PermissionChange => AuditEventType::PermissionChange,
+        Security::OAuthTokenIssued => AuditEventType::OAuthTokenIssued,
+        Security::OAuthTokenRevoked => AuditEventType::OAuthTokenRevoked,
+        Security::MfaEnabled => AuditEventType::MfaEnabled,
+        Security::MfaDisabled => AuditEventType::MfaDisabled,
+        Security::MfaVerified => AuditEventType::MfaVerified,
+        Security::PasswordChange => AuditEventType::PasswordChange,
+        Security::AccountLocked => AuditEventType::AccountLocked,
+        Security::AccountUnlocked => AuditEventType::AccountUnlocked,
+        // Tampering with the audit log is itself a policy violation
+        Security::SecurityPolicyViolation | Security::AuditTamperDetected => {
+            AuditEventType::SecurityPolicyViolation
+        }
+    };
+    let result = match entry.result {
+        SecurityResult::Success => AuditResult::Success,
+        SecurityResult::Failure => AuditResult::Failure,
+        SecurityResult::Denied => AuditResult::Denied,
+    };
+
+    AuditLogEntry {
+        id: entry.id,
+        event_type,
+        identity_id: entry.identity_id,
+        user_id: entry.user_id.clone(),
+        resource: entry.resource.clone(),
+        action: entry.action.clone(),
+        result,
+        ip_address: entry.ip_address.clone(),
+        user_agent: entry.user_agent.clone(),
+        details: entry.details.clone(),
+        timestamp: entry.timestamp,
+    }
+}
@@ -46,6 +46,11 @@ impl BlockchainAuditChain {
         Ok(())
     }
 
+    /// Number of entries waiting to be sealed into a block
+    pub async fn pending_count(&self) -> usize {
+        self.pending_entries.read().await.len()
+    }
+
     /// Create a new block with pending entries
     pub async fn create_block(&self) -> Result<(), BlockchainAuditError> {
         let entries: Vec<audit_logging::models::AuditLogEntry> = {
@@ -3,11 +3,13 @@
 //! Provides immutable audit trail capabilities using blockchain technology.
 //! Ensures tamper-proof audit logs for compliance and security.
 
+pub mod anchor;
 pub mod block;
 pub mod chain;
 pub mod error;
 pub mod service;
 
+pub use anchor::{AnchorConfig, BlockchainAnchor};
 pub use block::AuditBlock;
 pub use chain::BlockchainAuditChain;
 pub use error::BlockchainAuditError;
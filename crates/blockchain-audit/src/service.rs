@@ -64,6 +64,16 @@ impl BlockchainAuditService {
         Ok(entry.id)
     }
 
+    /// Queue an existing audit entry for the next block without sealing one
+    pub async fn append_entry(&self, entry: AuditLogEntry) -> Result<(), BlockchainAuditError> {
+        self.chain.add_audit_entry(entry).await
+    }
+
+    /// Number of entries waiting to be sealed into a block
+    pub async fn pending_entries(&self) -> usize {
+        self.chain.pending_count().await
+    }
+
     /// Force create a block with pending entries
     pub async fn finalize_block(&self) -> Result<(), BlockchainAuditError> {
         self.chain.create_block().await
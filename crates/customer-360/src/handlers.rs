@@ -0,0 +1,70 @@
+//! HTTP handlers for the customer 360 view
+
+use crate::service::{Customer360Config, Customer360Service};
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use sqlx::PgPool;
+use tmf629_customer::auth::validate_token;
+use tmf_apis_core::TmfError;
+use uuid::Uuid;
+
+/// Get the 360 view of a customer
+///
+/// Sources that fail are listed in the view's `errors` instead of failing the
+/// request.
+#[utoipa::path(
+    get,
+    path = "/customer360/v1/customer/{id}",
+    responses(
+        (status = 200, description = "Customer 360 view, possibly partial", body = Customer360),
+        (status = 404, description = "Customer not found"),
+        (status = 400, description = "Invalid customer ID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller may not view this customer")
+    ),
+    params(
+        ("id" = String, Path, description = "Customer ID (UUID)")
+    ),
+    tag = "Customer 360"
+)]
+pub async fn get_customer_360(
+    pool: web::Data<PgPool>,
+    config: Option<web::Data<Customer360Config>>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    let subject = validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid customer ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let config = config.map(|c| c.get_ref().clone()).unwrap_or_default();
+    if !config.may_view(&subject, id) {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Not allowed to view this customer"
+        })));
+    }
+
+    let service = Customer360Service::new(pool.get_ref().clone()).with_config(config);
+    match service.customer_view(id).await {
+        Ok(view) => Ok(HttpResponse::Ok().json(view)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Configure customer 360 routes
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/customer360/v1/customer/{id}").route(web::get().to(get_customer_360)),
+    );
+}
@@ -0,0 +1,13 @@
+//! Customer 360 View for BSS/OSS Rust Ecosystem
+//!
+//! Assembles a customer's profile (TMF629), active products (TMF637), recent
+//! orders (TMF622), latest bill (TMF678) and open trouble tickets (TMF633)
+//! into one view for care agents. Sources are queried concurrently; a source
+//! that fails or times out is reported in the view instead of failing it.
+
+pub mod handlers;
+pub mod models;
+pub mod service;
+
+pub use models::{Customer360, Customer360Source, Customer360SourceError};
+pub use service::{Customer360Config, Customer360Service};
@@ -0,0 +1,69 @@
+//! Customer 360 view models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tmf622_ordering::models::ProductOrder;
+use tmf629_customer::models::Customer;
+use tmf633_trouble_ticket::models::TroubleTicket;
+use tmf637_inventory::models::ProductInventory;
+use tmf678_billing::models::CustomerBill;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Source of a part of the customer 360 view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Customer360Source {
+    /// Customer profile (TMF629)
+    Profile,
+    /// Active products (TMF637)
+    ActiveProducts,
+    /// Recent orders (TMF622)
+    RecentOrders,
+    /// Latest bill (TMF678)
+    LatestBill,
+    /// Open trouble tickets (TMF633)
+    OpenTickets,
+}
+
+/// Error of a source that could not be included in the view
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Customer360SourceError {
+    pub source: Customer360Source,
+    pub message: String,
+}
+
+/// Customer 360 view
+///
+/// Parts whose source failed are left out and listed in `errors`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Customer360 {
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Uuid,
+    /// Customer profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Customer>,
+    /// Products in use by the customer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_products: Option<Vec<ProductInventory>>,
+    /// Most recent product orders, newest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_orders: Option<Vec<ProductOrder>>,
+    /// Latest bill (absent if the customer has none or its source failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_bill: Option<CustomerBill>,
+    /// Trouble tickets not yet resolved, closed or cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_tickets: Option<Vec<TroubleTicket>>,
+    /// Sources that could not be included
+    pub errors: Vec<Customer360SourceError>,
+    #[schema(value_type = String, format = "date-time")]
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Customer360 {
+    /// Whether every source was included
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
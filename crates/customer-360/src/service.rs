@@ -0,0 +1,176 @@
+//! Customer 360 aggregation service
+
+use crate::models::{Customer360, Customer360Source, Customer360SourceError};
+use chrono::Utc;
+use sqlx::PgPool;
+use std::future::Future;
+use std::time::Duration;
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+/// Customer 360 configuration
+#[derive(Debug, Clone)]
+pub struct Customer360Config {
+    /// Number of recent orders included
+    pub recent_order_limit: i64,
+    /// Time after which a source is reported as unavailable
+    pub source_timeout: Duration,
+    /// Token subjects of care agents, who may view any customer; other
+    /// callers may only view themselves
+    pub agents: Vec<String>,
+}
+
+impl Default for Customer360Config {
+    fn default() -> Self {
+        Self {
+            recent_order_limit: 10,
+            source_timeout: Duration::from_secs(5),
+            agents: Vec::new(),
+        }
+    }
+}
+
+impl Customer360Config {
+    /// Whether the caller with this token subject may view a customer
+    pub fn may_view(&self, subject: &str, customer_id: Uuid) -> bool {
+        self.agents.iter().any(|agent| agent == subject)
+            || Uuid::parse_str(subject).is_ok_and(|id| id == customer_id)
+    }
+}
+
+/// Customer 360 aggregation service
+pub struct Customer360Service {
+    pool: PgPool,
+    config: Customer360Config,
+}
+
+impl Customer360Service {
+    /// Create a new customer 360 service
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            config: Customer360Config::default(),
+        }
+    }
+
+    /// Use a different configuration
+    pub fn with_config(mut self, config: Customer360Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Assemble the 360 view of a customer
+    ///
+    /// Every source is queried concurrently; products and bills are linked to
+    /// the customer by the id of their customer related party. Fails only if
+    /// the customer does not exist.
+    pub async fn customer_view(&self, customer_id: Uuid) -> TmfResult<Customer360> {
+        let pool = &self.pool;
+        let mut errors = Vec::new();
+
+        let (profile, recent_orders, open_tickets, active_products, latest_bill) = tokio::join!(
+            self.fetch(tmf629_customer::db::get_customer_by_id(pool, customer_id)),
+            self.fetch(tmf622_ordering::db::get_recent_orders_by_customer(
+                pool,
+                customer_id,
+                self.config.recent_order_limit,
+            )),
+            self.fetch(
+                tmf633_trouble_ticket::db::get_open_trouble_tickets_by_customer(pool, customer_id)
+            ),
+            self.fetch(tmf637_inventory::db::get_active_inventories_by_customer(
+                pool,
+                customer_id
+            )),
+            self.fetch(tmf678_billing::db::get_latest_bill_by_customer(
+                pool,
+                customer_id
+            )),
+        );
+
+        // A customer that does not exist has no view
+        if let Err(TmfError::NotFound(msg)) = profile {
+            return Err(TmfError::NotFound(msg));
+        }
+        let profile = include(
+            customer_id,
+            Customer360Source::Profile,
+            profile,
+            &mut errors,
+        );
+        let recent_orders = include(
+            customer_id,
+            Customer360Source::RecentOrders,
+            recent_orders,
+            &mut errors,
+        );
+        let open_tickets = include(
+            customer_id,
+            Customer360Source::OpenTickets,
+            open_tickets,
+            &mut errors,
+        );
+
+        let active_products = include(
+            customer_id,
+            Customer360Source::ActiveProducts,
+            active_products,
+            &mut errors,
+        );
+        let latest_bill = include(
+            customer_id,
+            Customer360Source::LatestBill,
+            latest_bill,
+            &mut errors,
+        )
+        .flatten();
+
+        Ok(Customer360 {
+            customer_id,
+            profile,
+            active_products,
+            recent_orders,
+            latest_bill,
+            open_tickets,
+            errors,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Query a source, failing it if it does not answer within the timeout
+    async fn fetch<T>(&self, query: impl Future<Output = TmfResult<T>>) -> TmfResult<T> {
+        tokio::time::timeout(self.config.source_timeout, query)
+            .await
+            .unwrap_or_else(|_| {
+                Err(TmfError::Internal(format!(
+                    "No response within {:?}",
+                    self.config.source_timeout
+                )))
+            })
+    }
+}
+
+/// Take the result of a source, recording its error if it failed
+fn include<T>(
+    customer_id: Uuid,
+    source: Customer360Source,
+    result: TmfResult<T>,
+    errors: &mut Vec<Customer360SourceError>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!(
+                "Customer 360 view of {} is missing {:?}: {}",
+                customer_id,
+                source,
+                e
+            );
+            errors.push(Customer360SourceError {
+                source,
+                message: e.to_string(),
+            });
+            None
+        }
+    }
+}
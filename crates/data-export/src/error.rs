@@ -24,4 +24,7 @@ pub enum DataExportError {
 
     #[error("Validation error: {0}")]
     Validation(String),
+
+    #[error("Integrity check failed: {0}")]
+    IntegrityCheckFailed(String),
 }
@@ -1,11 +1,13 @@
 //! Data export functionality
 
 use crate::error::DataExportError;
+use crate::manifest::{file_extension, sha256_hex, ExportManifest, ManifestFile};
 use crate::models::{ExportFormat, ExportRequest};
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 use sqlx::{postgres::PgRow, Column, PgPool, Row};
 use std::collections::HashMap;
+use std::path::Path;
 use uuid::Uuid;
 
 /// Data exporter
@@ -24,26 +26,89 @@ impl DataExporter {
         let mut data = HashMap::new();
 
         for entity_type in &request.entity_types {
-            let entity_data = match entity_type.as_str() {
-                "catalogs" => self.export_catalogs(request.tenant_id).await?,
-                "customers" => self.export_customers(request.tenant_id).await?,
-                "orders" => self.export_orders(request.tenant_id).await?,
-                "products" => self.export_products(request.tenant_id).await?,
-                _ => {
-                    return Err(DataExportError::InvalidFormat(format!(
-                        "Unknown entity type: {}",
-                        entity_type
-                    )))
-                }
-            };
-
+            let entity_data = self.export_entity(entity_type, request.tenant_id).await?;
             data.insert(entity_type.clone(), entity_data);
         }
 
-        match request.format {
-            ExportFormat::Json => Ok(serde_json::to_string_pretty(&data)?),
-            ExportFormat::Csv => self.export_as_csv(&data),
-            ExportFormat::Xml => self.export_as_xml(&data),
+        self.format(request.format, &data)
+    }
+
+    /// Export each entity type to its own file in `dir`, plus a manifest with
+    /// record counts and checksums
+    ///
+    /// Only JSON is supported, as CSV and XML exports are not implemented yet.
+    pub async fn export_to_directory(
+        &self,
+        request: ExportRequest,
+        dir: &Path,
+    ) -> Result<ExportManifest, DataExportError> {
+        if request.format != ExportFormat::Json {
+            return Err(DataExportError::InvalidFormat(format!(
+                "Directory export supports JSON only, not {:?}",
+                request.format
+            )));
+        }
+
+        tokio::fs::create_dir_all(dir).await?;
+
+        let mut files = Vec::with_capacity(request.entity_types.len());
+        for entity_type in &request.entity_types {
+            let entity_data = self.export_entity(entity_type, request.tenant_id).await?;
+            let record_count = entity_data.as_array().map_or(0, |r| r.len() as u64);
+
+            let contents = serde_json::to_string_pretty(&entity_data)?;
+
+            let file_name = format!("{}.{}", entity_type, file_extension(request.format));
+            tokio::fs::write(dir.join(&file_name), contents.as_bytes()).await?;
+
+            files.push(ManifestFile {
+                file_name,
+                entity_type: entity_type.clone(),
+                record_count,
+                size_bytes: contents.len() as u64,
+                sha256: sha256_hex(contents.as_bytes()),
+            });
+        }
+
+        let manifest = ExportManifest {
+            export_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            parameters: request,
+            files,
+        };
+        // Written last so a manifest only exists for a complete export
+        manifest.write(dir).await?;
+
+        Ok(manifest)
+    }
+
+    /// Export the records of one entity type
+    async fn export_entity(
+        &self,
+        entity_type: &str,
+        tenant_id: Option<Uuid>,
+    ) -> Result<Value, DataExportError> {
+        match entity_type {
+            "catalogs" => self.export_catalogs(tenant_id).await,
+            "customers" => self.export_customers(tenant_id).await,
+            "orders" => self.export_orders(tenant_id).await,
+            "products" => self.export_products(tenant_id).await,
+            _ => Err(DataExportError::InvalidFormat(format!(
+                "Unknown entity type: {}",
+                entity_type
+            ))),
+        }
+    }
+
+    fn format(
+        &self,
+        format: ExportFormat,
+        data: &HashMap<String, Value>,
+    ) -> Result<String, DataExportError> {
+        match format {
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(data)?),
+            ExportFormat::Csv => self.export_as_csv(data),
+            ExportFormat::Xml => self.export_as_xml(data),
         }
     }
 
@@ -1,8 +1,13 @@
 //! Data import functionality
 
 use crate::error::DataExportError;
+use crate::manifest::ExportManifest;
+use crate::mapping::ImportMapping;
 use crate::models::{ExportFormat, ImportRequest};
+use serde_json::Value;
 use sqlx::PgPool;
+use std::path::Path;
+use uuid::Uuid;
 
 /// Data importer
 pub struct DataImporter {
@@ -25,9 +30,43 @@ impl DataImporter {
         }
     }
 
+    /// Verify an export directory against its manifest
+    pub async fn verify_export(&self, dir: &Path) -> Result<ExportManifest, DataExportError> {
+        let manifest = ExportManifest::read(dir).await?;
+        manifest.verify(dir).await?;
+        Ok(manifest)
+    }
+
+    /// Import every file of an export directory, after verifying all of them
+    /// against the manifest so a damaged transfer imports nothing
+    pub async fn import_from_directory(
+        &self,
+        dir: &Path,
+        tenant_id: Option<Uuid>,
+        validate_only: bool,
+        mapping: Option<ImportMapping>,
+    ) -> Result<ExportManifest, DataExportError> {
+        let manifest = self.verify_export(dir).await?;
+
+        for file in &manifest.files {
+            let data = tokio::fs::read_to_string(dir.join(&file.file_name)).await?;
+            self.import(ImportRequest {
+                tenant_id,
+                format: manifest.parameters.format,
+                data,
+                validate_only,
+                mapping: mapping.clone(),
+            })
+            .await?;
+        }
+
+        Ok(manifest)
+    }
+
     /// Import JSON data
     async fn import_json(&self, request: &ImportRequest) -> Result<(), DataExportError> {
-        let _data: serde_json::Value = serde_json::from_str(&request.data)?;
+        let data: Value = serde_json::from_str(&request.data)?;
+        let _data = apply_mapping(request, data)?;
 
         if request.validate_only {
             // Just validate the structure
@@ -41,7 +80,17 @@ impl DataImporter {
     }
 
     /// Import CSV data
-    async fn import_csv(&self, _request: &ImportRequest) -> Result<(), DataExportError> {
+    ///
+    /// CSV data can be validated against the mapping, but not imported yet.
+    async fn import_csv(&self, request: &ImportRequest) -> Result<(), DataExportError> {
+        let records = parse_csv(&request.data)?;
+        let _data = apply_mapping(request, Value::Array(records))?;
+
+        if request.validate_only {
+            log::info!("Validating CSV import data");
+            return Ok(());
+        }
+
         Err(DataExportError::ImportFailed(
             "CSV import not yet fully implemented".to_string(),
         ))
@@ -54,3 +103,59 @@ impl DataImporter {
         ))
     }
 }
+
+/// Apply the request's column mapping to a list of records, or to each list of
+/// an object keyed by entity type
+fn apply_mapping(request: &ImportRequest, data: Value) -> Result<Value, DataExportError> {
+    let Some(mapping) = &request.mapping else {
+        return Ok(data);
+    };
+
+    match data {
+        Value::Array(records) => Ok(Value::Array(mapping.apply_all(&records)?)),
+        Value::Object(entities) => entities
+            .into_iter()
+            .map(|(entity_type, records)| match records {
+                Value::Array(records) => match mapping.apply_all(&records) {
+                    Ok(mapped) => Ok((entity_type, Value::Array(mapped))),
+                    Err(DataExportError::Validation(msg)) => Err(DataExportError::Validation(
+                        format!("{}: {}", entity_type, msg),
+                    )),
+                    Err(e) => Err(e),
+                },
+                _ => Err(DataExportError::InvalidFormat(format!(
+                    "Expected a list of records for {}",
+                    entity_type
+                ))),
+            })
+            .collect::<Result<serde_json::Map<_, _>, _>>()
+            .map(Value::Object),
+        _ => Err(DataExportError::InvalidFormat(
+            "Expected a list of records".to_string(),
+        )),
+    }
+}
+
+/// Parse CSV with a header row into records keyed by column name
+fn parse_csv(data: &str) -> Result<Vec<Value>, DataExportError> {
+    let mut reader = csv::Reader::from_reader(data.as_bytes());
+    let header = reader
+        .headers()
+        .map_err(|e| DataExportError::InvalidFormat(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record
+                .map_err(|e| DataExportError::InvalidFormat(format!("Invalid CSV: {}", e)))?;
+            Ok(Value::Object(
+                header
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(column, field)| (column.to_string(), Value::String(field.to_string())))
+                    .collect(),
+            ))
+        })
+        .collect()
+}
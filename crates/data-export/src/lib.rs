@@ -1,13 +1,19 @@
 //! Data Export and Import
 //!
-//! Provides capabilities for exporting and importing data in various formats.
+//! Provides capabilities for exporting and importing data in various formats,
+//! with declarative column mapping for imported feeds and checksummed export
+//! manifests.
 
 pub mod error;
 pub mod export;
 pub mod import;
+pub mod manifest;
+pub mod mapping;
 pub mod models;
 
 pub use error::DataExportError;
 pub use export::DataExporter;
 pub use import::DataImporter;
+pub use manifest::{ExportManifest, ManifestFile};
+pub use mapping::{ColumnMapping, ImportMapping, Transform};
 pub use models::{ExportFormat, ExportRequest, ImportRequest};
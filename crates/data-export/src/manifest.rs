@@ -0,0 +1,124 @@
+//! Export Manifests
+//!
+//! A file export writes one file per entity type plus a `manifest.json`
+//! listing each file with its record count, size and SHA-256 checksum, along
+//! with the export parameters and time. Receivers verify the files against the
+//! manifest before processing them, which catches truncated or corrupted
+//! transfers.
+
+use crate::error::DataExportError;
+use crate::models::{ExportFormat, ExportRequest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use uuid::Uuid;
+
+/// File name of the manifest within an export directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// One exported file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub file_name: String,
+    pub entity_type: String,
+    pub record_count: u64,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// Manifest describing a complete export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub export_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub parameters: ExportRequest,
+    pub files: Vec<ManifestFile>,
+}
+
+impl ExportManifest {
+    /// Total records across all files
+    pub fn total_records(&self) -> u64 {
+        self.files.iter().map(|f| f.record_count).sum()
+    }
+
+    /// Read a manifest from an export directory
+    pub async fn read(dir: &Path) -> Result<Self, DataExportError> {
+        let contents = tokio::fs::read(dir.join(MANIFEST_FILE_NAME)).await?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+
+    /// Write the manifest into an export directory
+    pub async fn write(&self, dir: &Path) -> Result<(), DataExportError> {
+        let contents = serde_json::to_vec_pretty(self)?;
+        tokio::fs::write(dir.join(MANIFEST_FILE_NAME), contents).await?;
+        Ok(())
+    }
+
+    /// Verify every listed file in `dir` against its size, checksum and, for
+    /// JSON exports, record count
+    pub async fn verify(&self, dir: &Path) -> Result<(), DataExportError> {
+        for file in &self.files {
+            // File names come from the manifest; never let them escape `dir`
+            if Path::new(&file.file_name).components().count() != 1 {
+                return Err(DataExportError::IntegrityCheckFailed(format!(
+                    "Invalid file name in manifest: {}",
+                    file.file_name
+                )));
+            }
+
+            let contents = tokio::fs::read(dir.join(&file.file_name))
+                .await
+                .map_err(|e| {
+                    DataExportError::IntegrityCheckFailed(format!(
+                        "{} cannot be read: {}",
+                        file.file_name, e
+                    ))
+                })?;
+
+            if contents.len() as u64 != file.size_bytes {
+                return Err(DataExportError::IntegrityCheckFailed(format!(
+                    "{} is {} bytes, manifest lists {}",
+                    file.file_name,
+                    contents.len(),
+                    file.size_bytes
+                )));
+            }
+
+            let checksum = sha256_hex(&contents);
+            if checksum != file.sha256 {
+                return Err(DataExportError::IntegrityCheckFailed(format!(
+                    "{} checksum {} does not match manifest checksum {}",
+                    file.file_name, checksum, file.sha256
+                )));
+            }
+
+            if self.parameters.format == ExportFormat::Json {
+                let records: serde_json::Value = serde_json::from_slice(&contents)?;
+                let count = records.as_array().map_or(0, |r| r.len() as u64);
+                if count != file.record_count {
+                    return Err(DataExportError::IntegrityCheckFailed(format!(
+                        "{} has {} records, manifest lists {}",
+                        file.file_name, count, file.record_count
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hex-encoded SHA-256 of some bytes
+pub fn sha256_hex(contents: &[u8]) -> String {
+    hex::encode(Sha256::digest(contents))
+}
+
+/// File extension for an export format
+pub fn file_extension(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Json => "json",
+        ExportFormat::Csv => "csv",
+        ExportFormat::Xml => "xml",
+    }
+}
@@ -0,0 +1,249 @@
+//! Import Column Mapping
+//!
+//! Declarative mapping from source columns to target fields, so a new feed is
+//! onboarded with configuration instead of code. Each mapping reads a source
+//! column, runs it through a chain of transforms and writes the result to a
+//! target field. Target fields listed as required must be produced by some
+//! mapping and must not end up empty for any record.
+//!
+//! ```json
+//! {
+//!   "required_fields": ["name", "created_at"],
+//!   "columns": [
+//!     { "source": "Customer Name", "target": "name", "transforms": [{ "type": "trim" }] },
+//!     { "source": "Signup", "target": "created_at",
+//!       "transforms": [{ "type": "parse_date", "format": "%d/%m/%Y" }] },
+//!     { "source": "Tier", "target": "tier",
+//!       "transforms": [{ "type": "trim" }, { "type": "default", "value": "standard" }] }
+//!   ]
+//! }
+//! ```
+
+use crate::error::DataExportError;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Transform applied to a source value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transform {
+    /// Strip surrounding whitespace; blank strings become empty
+    Trim,
+    Lowercase,
+    Uppercase,
+    /// Parse a date or date-time with a chrono format string into RFC 3339;
+    /// RFC 3339 input is accepted when no format is given
+    ParseDate {
+        format: Option<String>,
+    },
+    /// Parse a number
+    ParseNumber,
+    /// Parse `true`/`false`, `yes`/`no`, `y`/`n` or `1`/`0`
+    ParseBool,
+    /// Use a value when the source is missing or empty
+    Default {
+        value: Value,
+    },
+}
+
+impl Transform {
+    fn apply(&self, value: Value) -> Result<Value, String> {
+        // Empty values pass through every transform except `Default`
+        if is_empty(&value) {
+            return Ok(match self {
+                Transform::Default { value } => value.clone(),
+                _ => Value::Null,
+            });
+        }
+
+        match self {
+            Transform::Trim => Ok(match value {
+                Value::String(s) => Value::String(s.trim().to_string()),
+                other => other,
+            }),
+            Transform::Lowercase => Ok(map_string(value, |s| s.to_lowercase())),
+            Transform::Uppercase => Ok(map_string(value, |s| s.to_uppercase())),
+            Transform::ParseDate { format } => {
+                let text = as_text(&value);
+                parse_date(&text, format.as_deref())
+                    .map(|dt| Value::String(dt.to_rfc3339()))
+                    .ok_or_else(|| match format {
+                        Some(format) => format!("'{}' is not a date in format '{}'", text, format),
+                        None => format!("'{}' is not an RFC 3339 date", text),
+                    })
+            }
+            Transform::ParseNumber => {
+                if value.is_number() {
+                    return Ok(value);
+                }
+                let text = as_text(&value);
+                let trimmed = text.trim();
+                trimmed
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .ok()
+                    .or_else(|| {
+                        trimmed
+                            .parse::<f64>()
+                            .ok()
+                            .and_then(serde_json::Number::from_f64)
+                            .map(Value::Number)
+                    })
+                    .ok_or_else(|| format!("'{}' is not a number", text))
+            }
+            Transform::ParseBool => {
+                if value.is_boolean() {
+                    return Ok(value);
+                }
+                let text = as_text(&value);
+                match text.trim().to_lowercase().as_str() {
+                    "true" | "yes" | "y" | "1" => Ok(Value::Bool(true)),
+                    "false" | "no" | "n" | "0" => Ok(Value::Bool(false)),
+                    _ => Err(format!("'{}' is not a boolean", text)),
+                }
+            }
+            Transform::Default { .. } => Ok(value),
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn map_string(value: Value, f: impl Fn(&str) -> String) -> Value {
+    match value {
+        Value::String(s) => Value::String(f(&s)),
+        other => other,
+    }
+}
+
+fn parse_date(text: &str, format: Option<&str>) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    let Some(format) = format else {
+        return DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+    };
+
+    DateTime::parse_from_str(text, format)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(text, format)
+                .ok()
+                .map(|dt| dt.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, format)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        })
+}
+
+/// Mapping of one source column to one target field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+}
+
+/// Declarative mapping applied to every imported record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportMapping {
+    pub columns: Vec<ColumnMapping>,
+    /// Target fields every record must have a non-empty value for
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+}
+
+impl ImportMapping {
+    /// Check the mapping itself: every required field must be a mapping target
+    pub fn validate(&self) -> Result<(), DataExportError> {
+        let unmapped: Vec<&str> = self
+            .required_fields
+            .iter()
+            .filter(|field| !self.columns.iter().any(|c| &c.target == *field))
+            .map(String::as_str)
+            .collect();
+
+        if !unmapped.is_empty() {
+            return Err(DataExportError::Validation(format!(
+                "Required fields have no column mapping: {}",
+                unmapped.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Map a single source record to a target record
+    pub fn apply(
+        &self,
+        record: &Map<String, Value>,
+    ) -> Result<Map<String, Value>, DataExportError> {
+        let mut target = Map::new();
+
+        for column in &self.columns {
+            let mut value = record.get(&column.source).cloned().unwrap_or(Value::Null);
+            for transform in &column.transforms {
+                value = transform.apply(value).map_err(|e| {
+                    DataExportError::Validation(format!(
+                        "Column '{}' -> '{}': {}",
+                        column.source, column.target, e
+                    ))
+                })?;
+            }
+            target.insert(column.target.clone(), value);
+        }
+
+        let missing: Vec<&str> = self
+            .required_fields
+            .iter()
+            .filter(|field| target.get(*field).is_none_or(is_empty))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(DataExportError::Validation(format!(
+                "Missing required fields: {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(target)
+    }
+
+    /// Map a list of source records, reporting the first failing record by index
+    pub fn apply_all(&self, records: &[Value]) -> Result<Vec<Value>, DataExportError> {
+        self.validate()?;
+        records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let object = record.as_object().ok_or_else(|| {
+                    DataExportError::Validation(format!("Record {} is not an object", index))
+                })?;
+                self.apply(object).map(Value::Object).map_err(|e| match e {
+                    DataExportError::Validation(msg) => {
+                        DataExportError::Validation(format!("Record {}: {}", index, msg))
+                    }
+                    other => other,
+                })
+            })
+            .collect()
+    }
+}
@@ -1,5 +1,6 @@
 //! Data export/import models
 
+use crate::mapping::ImportMapping;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -27,6 +28,9 @@ pub struct ImportRequest {
     pub format: ExportFormat,
     pub data: String,
     pub validate_only: bool,
+    /// Column mapping from the source feed to target fields
+    #[serde(default)]
+    pub mapping: Option<ImportMapping>,
 }
 
 /// Export job status
@@ -0,0 +1,132 @@
+//! Edge Task Result Cache
+//!
+//! Each edge node keeps the results of the cacheable tasks it completed, keyed
+//! by the task type and input payload. An identical task placed on a node
+//! holding a fresh result is completed from the cache instead of being
+//! executed again. Results expire after a TTL, and a full cache evicts its
+//! least recently used result.
+
+use crate::models::TaskType;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// Default time a cached result stays valid, in seconds
+pub const DEFAULT_RESULT_TTL_SECS: i64 = 300;
+
+/// Default number of results each node keeps
+pub const DEFAULT_MAX_CACHED_RESULTS: usize = 1024;
+
+/// Key identifying a task's input
+///
+/// Holds the full serialized payload so a lookup only hits on an identical
+/// input, never on a hash collision. JSON object keys are serialized in sorted
+/// order, so payloads that differ only in key order share a key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TaskInputKey {
+    task_type: TaskType,
+    payload: String,
+}
+
+impl TaskInputKey {
+    pub fn new(task_type: TaskType, payload: &serde_json::Value) -> Self {
+        Self {
+            task_type,
+            payload: payload.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedResult {
+    result: serde_json::Value,
+    expires_at: DateTime<Utc>,
+    last_used: DateTime<Utc>,
+}
+
+/// Result cache held by a single edge node
+#[derive(Debug, Clone)]
+pub struct TaskResultCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: HashMap<TaskInputKey, CachedResult>,
+}
+
+impl TaskResultCache {
+    /// Create an empty cache whose results expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_MAX_CACHED_RESULTS,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Keep at most `max_entries` results
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Fresh cached result for the input, if any
+    pub fn get(&mut self, key: &TaskInputKey, now: DateTime<Utc>) -> Option<&serde_json::Value> {
+        self.entries
+            .get_mut(key)
+            .filter(|entry| now < entry.expires_at)
+            .map(|entry| {
+                entry.last_used = now;
+                &entry.result
+            })
+    }
+
+    /// Cache a result, replacing any previous one for the same input
+    ///
+    /// When the cache is full, expired results are dropped first and then the
+    /// least recently used one.
+    pub fn insert(&mut self, key: TaskInputKey, result: serde_json::Value, now: DateTime<Utc>) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            self.purge_expired(now);
+            if self.entries.len() >= self.max_entries {
+                let least_recent = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(least_recent) = least_recent {
+                    self.entries.remove(&least_recent);
+                }
+            }
+        }
+
+        self.entries.insert(
+            key,
+            CachedResult {
+                result,
+                expires_at: now + self.ttl,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Drop expired results, returning how many were removed
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| now < entry.expires_at);
+        before - self.entries.len()
+    }
+
+    /// Number of cached results, including expired ones not yet purged
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no results
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for TaskResultCache {
+    fn default() -> Self {
+        Self::new(Duration::seconds(DEFAULT_RESULT_TTL_SECS))
+    }
+}
@@ -0,0 +1,92 @@
+//! Conflict-free Replicated Counters
+//!
+//! Each node only ever increments its own slot, and merging takes the per-node
+//! maximum, so concurrent offline increments from different nodes are never lost
+//! and merges can be applied in any order, any number of times.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Grow-only counter
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GCounter {
+    counts: HashMap<Uuid, u64>,
+}
+
+impl GCounter {
+    /// Create an empty counter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment the slot owned by `node_id`
+    pub fn increment(&mut self, node_id: Uuid, amount: u64) {
+        let slot = self.counts.entry(node_id).or_insert(0);
+        *slot = slot.saturating_add(amount);
+    }
+
+    /// Total across all nodes
+    pub fn value(&self) -> u64 {
+        self.counts
+            .values()
+            .fold(0u64, |total, count| total.saturating_add(*count))
+    }
+
+    /// Contribution of a single node
+    pub fn node_value(&self, node_id: Uuid) -> u64 {
+        self.counts.get(&node_id).copied().unwrap_or(0)
+    }
+
+    /// Merge another replica into this one
+    pub fn merge(&mut self, other: &GCounter) {
+        for (node_id, count) in &other.counts {
+            let slot = self.counts.entry(*node_id).or_insert(0);
+            *slot = (*slot).max(*count);
+        }
+    }
+}
+
+/// Counter supporting both increments and decrements
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PNCounter {
+    increments: GCounter,
+    decrements: GCounter,
+}
+
+impl PNCounter {
+    /// Create an empty counter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Increment the slot owned by `node_id`
+    pub fn increment(&mut self, node_id: Uuid, amount: u64) {
+        self.increments.increment(node_id, amount);
+    }
+
+    /// Decrement the slot owned by `node_id`
+    pub fn decrement(&mut self, node_id: Uuid, amount: u64) {
+        self.decrements.increment(node_id, amount);
+    }
+
+    /// Apply a signed delta on behalf of `node_id`
+    pub fn apply(&mut self, node_id: Uuid, delta: i64) {
+        if delta >= 0 {
+            self.increment(node_id, delta as u64);
+        } else {
+            self.decrement(node_id, delta.unsigned_abs());
+        }
+    }
+
+    /// Net value across all nodes
+    pub fn value(&self) -> i64 {
+        self.increments.value() as i64 - self.decrements.value() as i64
+    }
+
+    /// Merge another replica into this one
+    pub fn merge(&mut self, other: &PNCounter) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+}
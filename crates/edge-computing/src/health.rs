@@ -0,0 +1,104 @@
+//! Heartbeat-driven Edge Node Health
+//!
+//! Nodes move through `Healthy → Suspect → Down → Recovering → Healthy` based on
+//! missed and received heartbeats, so a single late heartbeat does not take a
+//! node out of task placement.
+
+use crate::models::NodeHealthState;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Heartbeat grace thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    /// Expected interval between heartbeats, in seconds
+    pub interval_secs: i64,
+    /// Missed heartbeats before a healthy node becomes suspect
+    pub suspect_after_misses: u32,
+    /// Missed heartbeats before a node is marked down
+    pub down_after_misses: u32,
+    /// Consecutive heartbeats a recovering node needs to become healthy again
+    pub recovery_heartbeats: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            suspect_after_misses: 1,
+            down_after_misses: 3,
+            recovery_heartbeats: 3,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    /// Number of heartbeats missed since the last one
+    ///
+    /// The next heartbeat is not counted as missed until a full interval after it
+    /// was due, so normal jitter never registers as a miss.
+    pub fn missed_heartbeats(&self, last_heartbeat: DateTime<Utc>, now: DateTime<Utc>) -> u32 {
+        if self.interval_secs <= 0 {
+            return 0;
+        }
+        let elapsed = (now - last_heartbeat).num_seconds().max(0);
+        (elapsed / self.interval_secs)
+            .saturating_sub(1)
+            .min(u32::MAX as i64) as u32
+    }
+
+    /// Time without a heartbeat before a node is marked down
+    pub fn down_grace_period(&self) -> Duration {
+        Duration::seconds(self.interval_secs * (self.down_after_misses as i64 + 1))
+    }
+}
+
+/// Node health state change, emitted to transition subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeStateTransition {
+    pub node_id: Uuid,
+    pub from: NodeHealthState,
+    pub to: NodeHealthState,
+    pub missed_heartbeats: u32,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Next state after `missed` heartbeats have been missed
+pub fn state_after_misses(
+    current: NodeHealthState,
+    missed: u32,
+    config: &HeartbeatConfig,
+) -> NodeHealthState {
+    match current {
+        NodeHealthState::Down => NodeHealthState::Down,
+        _ if missed >= config.down_after_misses => NodeHealthState::Down,
+        // A recovering node that misses again is still unstable
+        NodeHealthState::Recovering if missed >= config.suspect_after_misses => {
+            NodeHealthState::Down
+        }
+        NodeHealthState::Healthy if missed >= config.suspect_after_misses => {
+            NodeHealthState::Suspect
+        }
+        state => state,
+    }
+}
+
+/// Next state after a heartbeat is received
+///
+/// `consecutive` is the number of consecutive heartbeats including this one.
+pub fn state_after_heartbeat(
+    current: NodeHealthState,
+    consecutive: u32,
+    config: &HeartbeatConfig,
+) -> NodeHealthState {
+    match current {
+        NodeHealthState::Healthy | NodeHealthState::Suspect => NodeHealthState::Healthy,
+        NodeHealthState::Down if config.recovery_heartbeats <= 1 => NodeHealthState::Healthy,
+        NodeHealthState::Down => NodeHealthState::Recovering,
+        NodeHealthState::Recovering if consecutive >= config.recovery_heartbeats => {
+            NodeHealthState::Healthy
+        }
+        NodeHealthState::Recovering => NodeHealthState::Recovering,
+    }
+}
@@ -2,17 +2,25 @@
 //!
 //! Provides edge computing capabilities for distributed processing:
 //! - Edge node management
+//! - Heartbeat-driven node health with grace periods
 //! - Task distribution and load balancing
 //! - Edge-to-cloud synchronization
-//! - Local processing and caching
+//! - Conflict-free replicated counters for metrics sync
+//! - Local processing and task result caching
 
+pub mod cache;
+pub mod crdt;
 pub mod error;
+pub mod health;
 pub mod models;
 pub mod node;
 pub mod orchestrator;
 pub mod sync;
 
+pub use cache::{TaskInputKey, TaskResultCache};
+pub use crdt::{GCounter, PNCounter};
 pub use error::EdgeComputingError;
+pub use health::{HeartbeatConfig, NodeStateTransition};
 pub use models::*;
 pub use node::EdgeNodeManager;
 pub use orchestrator::EdgeOrchestrator;
@@ -13,6 +13,22 @@ pub enum NodeStatus {
     Overloaded,
 }
 
+/// Heartbeat-driven node health
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeHealthState {
+    Healthy,
+    Suspect,
+    Down,
+    Recovering,
+}
+
+impl NodeHealthState {
+    /// Whether new tasks may be placed on a node in this state
+    pub fn accepts_tasks(&self) -> bool {
+        matches!(self, NodeHealthState::Healthy | NodeHealthState::Suspect)
+    }
+}
+
 /// Edge node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeNode {
@@ -23,6 +39,9 @@ pub struct EdgeNode {
     pub status: NodeStatus,
     pub capacity: NodeCapacity,
     pub last_heartbeat: DateTime<Utc>,
+    pub health: NodeHealthState,
+    /// Consecutive heartbeats received on time, used for recovery
+    pub consecutive_heartbeats: u32,
     pub metadata: serde_json::Value,
 }
 
@@ -50,10 +69,21 @@ pub struct EdgeTask {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub result: Option<serde_json::Value>,
+    /// Whether an identical task may be answered from a node's result cache;
+    /// false for non-deterministic tasks
+    #[serde(default = "default_cacheable")]
+    pub cacheable: bool,
+    /// Whether the result was served from cache instead of executed
+    #[serde(default)]
+    pub cache_hit: bool,
+}
+
+fn default_cacheable() -> bool {
+    true
 }
 
 /// Task type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TaskType {
     DataProcessing,
     Analytics,
@@ -104,3 +134,11 @@ pub enum SyncStatus {
     Completed,
     Failed,
 }
+
+/// Result of merging replicated counters during a sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterSyncResult {
+    pub sync_id: Uuid,
+    /// Merged total per counter name
+    pub totals: std::collections::HashMap<String, i64>,
+}
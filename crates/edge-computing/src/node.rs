@@ -1,25 +1,46 @@
 //! Edge Node Management
 
 use crate::error::EdgeComputingError;
-use crate::models::{EdgeNode, NodeCapacity, NodeStatus};
-use chrono::Utc;
+use crate::health::{
+    state_after_heartbeat, state_after_misses, HeartbeatConfig, NodeStateTransition,
+};
+use crate::models::{EdgeNode, NodeCapacity, NodeHealthState, NodeStatus};
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 
+/// Capacity of the node state transition channel
+const TRANSITION_CHANNEL_CAPACITY: usize = 256;
+
 /// Edge Node Manager
 pub struct EdgeNodeManager {
     nodes: Arc<RwLock<std::collections::HashMap<Uuid, EdgeNode>>>,
+    heartbeat_config: HeartbeatConfig,
+    transitions: broadcast::Sender<NodeStateTransition>,
 }
 
 impl EdgeNodeManager {
     /// Create a new edge node manager
     pub fn new() -> Self {
+        Self::with_heartbeat_config(HeartbeatConfig::default())
+    }
+
+    /// Create a new edge node manager with custom heartbeat grace thresholds
+    pub fn with_heartbeat_config(heartbeat_config: HeartbeatConfig) -> Self {
+        let (transitions, _) = broadcast::channel(TRANSITION_CHANNEL_CAPACITY);
         Self {
             nodes: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            heartbeat_config,
+            transitions,
         }
     }
 
+    /// Subscribe to node health state transitions
+    pub fn subscribe_transitions(&self) -> broadcast::Receiver<NodeStateTransition> {
+        self.transitions.subscribe()
+    }
+
     /// Register a new edge node
     pub async fn register_node(
         &self,
@@ -37,6 +58,8 @@ impl EdgeNodeManager {
             status: NodeStatus::Online,
             capacity,
             last_heartbeat: Utc::now(),
+            health: NodeHealthState::Healthy,
+            consecutive_heartbeats: 0,
             metadata: serde_json::json!({}),
         };
 
@@ -46,15 +69,95 @@ impl EdgeNodeManager {
 
     /// Update node heartbeat
     pub async fn update_heartbeat(&self, node_id: Uuid) -> Result<(), EdgeComputingError> {
+        let now = Utc::now();
         let mut nodes = self.nodes.write().await;
         if let Some(node) = nodes.get_mut(&node_id) {
-            node.last_heartbeat = Utc::now();
+            // A heartbeat that arrives after a missed interval restarts the streak
+            let missed = self
+                .heartbeat_config
+                .missed_heartbeats(node.last_heartbeat, now);
+            node.consecutive_heartbeats = if missed > 0 {
+                1
+            } else {
+                node.consecutive_heartbeats.saturating_add(1)
+            };
+            node.last_heartbeat = now;
+
+            let next = state_after_heartbeat(
+                node.health,
+                node.consecutive_heartbeats,
+                &self.heartbeat_config,
+            );
+            self.transition(node, next, 0, now);
             Ok(())
         } else {
             Err(EdgeComputingError::NodeNotFound(node_id.to_string()))
         }
     }
 
+    /// Check all nodes for missed heartbeats and apply state transitions
+    ///
+    /// Intended to be called periodically, e.g. once per heartbeat interval.
+    pub async fn evaluate_heartbeats(&self, now: DateTime<Utc>) -> Vec<NodeStateTransition> {
+        let mut nodes = self.nodes.write().await;
+        nodes
+            .values_mut()
+            .filter_map(|node| {
+                let missed = self
+                    .heartbeat_config
+                    .missed_heartbeats(node.last_heartbeat, now);
+                if missed == 0 {
+                    return None;
+                }
+                node.consecutive_heartbeats = 0;
+                let next = state_after_misses(node.health, missed, &self.heartbeat_config);
+                self.transition(node, next, missed, now)
+            })
+            .collect()
+    }
+
+    /// Move a node to a new health state, emitting a transition if it changed
+    fn transition(
+        &self,
+        node: &mut EdgeNode,
+        next: NodeHealthState,
+        missed_heartbeats: u32,
+        now: DateTime<Utc>,
+    ) -> Option<NodeStateTransition> {
+        if node.health == next {
+            return None;
+        }
+
+        let transition = NodeStateTransition {
+            node_id: node.id,
+            from: node.health,
+            to: next,
+            missed_heartbeats,
+            occurred_at: now,
+        };
+        node.health = next;
+
+        match next {
+            NodeHealthState::Down if node.status == NodeStatus::Online => {
+                node.status = NodeStatus::Offline;
+            }
+            NodeHealthState::Healthy if node.status == NodeStatus::Offline => {
+                node.status = NodeStatus::Online;
+            }
+            _ => {}
+        }
+
+        log::info!(
+            "Edge node {} health {:?} -> {:?}",
+            node.id,
+            transition.from,
+            transition.to
+        );
+        // No subscribers is fine; transitions are also returned to the caller
+        let _ = self.transitions.send(transition.clone());
+        Some(transition)
+    }
+
     /// Get node by ID
     pub async fn get_node(&self, node_id: Uuid) -> Option<EdgeNode> {
         self.nodes.read().await.get(&node_id).cloned()
@@ -66,7 +169,7 @@ impl EdgeNodeManager {
             .read()
             .await
             .values()
-            .filter(|n| n.status == NodeStatus::Online)
+            .filter(|n| n.status == NodeStatus::Online && n.health.accepts_tasks())
             .cloned()
             .collect()
     }
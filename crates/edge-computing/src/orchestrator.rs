@@ -1,9 +1,12 @@
 //! Edge Task Orchestrator
 
+use crate::cache::{
+    TaskInputKey, TaskResultCache, DEFAULT_MAX_CACHED_RESULTS, DEFAULT_RESULT_TTL_SECS,
+};
 use crate::error::EdgeComputingError;
-use crate::models::{EdgeTask, TaskPriority, TaskStatus, TaskType};
+use crate::models::{EdgeNode, EdgeTask, TaskPriority, TaskStatus, TaskType};
 use crate::node::EdgeNodeManager;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -12,6 +15,10 @@ use uuid::Uuid;
 pub struct EdgeOrchestrator {
     node_manager: Arc<EdgeNodeManager>,
     tasks: Arc<RwLock<std::collections::HashMap<Uuid, EdgeTask>>>,
+    /// Result cache of each edge node
+    result_caches: Arc<RwLock<std::collections::HashMap<Uuid, TaskResultCache>>>,
+    result_ttl: Duration,
+    max_cached_results: usize,
 }
 
 impl EdgeOrchestrator {
@@ -20,9 +27,24 @@ impl EdgeOrchestrator {
         Self {
             node_manager,
             tasks: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            result_caches: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            result_ttl: Duration::seconds(DEFAULT_RESULT_TTL_SECS),
+            max_cached_results: DEFAULT_MAX_CACHED_RESULTS,
         }
     }
 
+    /// Keep cached task results for `ttl` instead of the default
+    pub fn with_result_ttl(mut self, ttl: Duration) -> Self {
+        self.result_ttl = ttl;
+        self
+    }
+
+    /// Keep at most `max_results` cached results per node instead of the default
+    pub fn with_max_cached_results(mut self, max_results: usize) -> Self {
+        self.max_cached_results = max_results;
+        self
+    }
+
     /// Submit a task for execution
     pub async fn submit_task(
         &self,
@@ -42,13 +64,31 @@ impl EdgeOrchestrator {
             started_at: None,
             completed_at: None,
             result: None,
+            cacheable: true,
+            cache_hit: false,
         };
 
         self.tasks.write().await.insert(task_id, task);
         task_id
     }
 
+    /// Mark a task as non-deterministic so it is always executed
+    pub async fn mark_non_cacheable(&self, task_id: Uuid) -> Result<(), EdgeComputingError> {
+        let mut tasks = self.tasks.write().await;
+        if let Some(task) = tasks.get_mut(&task_id) {
+            task.cacheable = false;
+            Ok(())
+        } else {
+            Err(EdgeComputingError::TaskExecutionFailed(
+                "Task not found".to_string(),
+            ))
+        }
+    }
+
     /// Assign task to best available node
+    ///
+    /// A cacheable task whose result is cached on an online node is assigned to
+    /// that node and completed immediately from its cache.
     pub async fn assign_task(&self, task_id: Uuid) -> Result<Uuid, EdgeComputingError> {
         let online_nodes = self.node_manager.get_online_nodes().await;
 
@@ -58,6 +98,10 @@ impl EdgeOrchestrator {
             ));
         }
 
+        if let Some(node_id) = self.complete_from_cache(task_id, &online_nodes).await? {
+            return Ok(node_id);
+        }
+
         // Simple load balancing: select node with most available resources
         let best_node = online_nodes
             .iter()
@@ -80,6 +124,52 @@ impl EdgeOrchestrator {
         }
     }
 
+    /// Complete a cacheable task from the result cache of one of the given nodes
+    async fn complete_from_cache(
+        &self,
+        task_id: Uuid,
+        nodes: &[EdgeNode],
+    ) -> Result<Option<Uuid>, EdgeComputingError> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks
+            .get_mut(&task_id)
+            .ok_or_else(|| EdgeComputingError::TaskExecutionFailed("Task not found".to_string()))?;
+        if !task.cacheable {
+            return Ok(None);
+        }
+
+        let key = TaskInputKey::new(task.task_type, &task.payload);
+        let now = Utc::now();
+        let mut caches = self.result_caches.write().await;
+        let cached = nodes.iter().find_map(|node| {
+            caches
+                .get_mut(&node.id)
+                .and_then(|cache| cache.get(&key, now))
+                .map(|result| (node.id, result.clone()))
+        });
+
+        Ok(cached.map(|(node_id, result)| {
+            task.assigned_node = Some(node_id);
+            task.status = TaskStatus::Completed;
+            task.started_at = Some(now);
+            task.completed_at = Some(now);
+            task.result = Some(result);
+            task.cache_hit = true;
+            node_id
+        }))
+    }
+
+    /// Drop expired results from every node's cache, returning how many were removed
+    pub async fn purge_expired_results(&self) -> usize {
+        let now = Utc::now();
+        self.result_caches
+            .write()
+            .await
+            .values_mut()
+            .map(|cache| cache.purge_expired(now))
+            .sum()
+    }
+
     /// Get task status
     pub async fn get_task_status(&self, task_id: Uuid) -> Option<TaskStatus> {
         self.tasks.read().await.get(&task_id).map(|t| t.status)
@@ -95,6 +185,8 @@ impl EdgeOrchestrator {
     }
 
     /// Complete a task
+    ///
+    /// The result of a cacheable task is cached on the node that executed it.
     pub async fn complete_task(
         &self,
         task_id: Uuid,
@@ -102,8 +194,24 @@ impl EdgeOrchestrator {
     ) -> Result<(), EdgeComputingError> {
         let mut tasks = self.tasks.write().await;
         if let Some(task) = tasks.get_mut(&task_id) {
+            let now = Utc::now();
+            if let (true, Some(node_id)) = (task.cacheable, task.assigned_node) {
+                self.result_caches
+                    .write()
+                    .await
+                    .entry(node_id)
+                    .or_insert_with(|| {
+                        TaskResultCache::new(self.result_ttl)
+                            .with_max_entries(self.max_cached_results)
+                    })
+                    .insert(
+                        TaskInputKey::new(task.task_type, &task.payload),
+                        result.clone(),
+                        now,
+                    );
+            }
             task.status = TaskStatus::Completed;
-            task.completed_at = Some(Utc::now());
+            task.completed_at = Some(now);
             task.result = Some(result);
             Ok(())
         } else {
@@ -1,8 +1,10 @@
 //! Edge-to-Cloud Synchronization
 
+use crate::crdt::PNCounter;
 use crate::error::EdgeComputingError;
-use crate::models::{SyncOperation, SyncStatus};
+use crate::models::{CounterSyncResult, SyncOperation, SyncStatus};
 use chrono::Utc;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -10,6 +12,7 @@ use uuid::Uuid;
 /// Edge Synchronization Service
 pub struct EdgeSyncService {
     sync_operations: Arc<RwLock<std::collections::HashMap<Uuid, SyncOperation>>>,
+    counters: Arc<RwLock<HashMap<String, PNCounter>>>,
     _cloud_endpoint: String,
 }
 
@@ -18,6 +21,7 @@ impl EdgeSyncService {
     pub fn new(cloud_endpoint: String) -> Self {
         Self {
             sync_operations: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            counters: Arc::new(RwLock::new(HashMap::new())),
             _cloud_endpoint: cloud_endpoint,
         }
     }
@@ -84,4 +88,70 @@ impl EdgeSyncService {
             .cloned()
             .collect()
     }
+
+    /// Record a local change to a replicated metrics counter
+    pub async fn increment_counter(&self, node_id: Uuid, name: &str, delta: i64) {
+        self.counters
+            .write()
+            .await
+            .entry(name.to_string())
+            .or_default()
+            .apply(node_id, delta);
+    }
+
+    /// Current value of a replicated counter
+    pub async fn get_counter(&self, name: &str) -> i64 {
+        self.counters
+            .read()
+            .await
+            .get(name)
+            .map(|c| c.value())
+            .unwrap_or(0)
+    }
+
+    /// Snapshot of all counter replicas, to be sent to a peer
+    pub async fn counter_state(&self) -> HashMap<String, PNCounter> {
+        self.counters.read().await.clone()
+    }
+
+    /// Merge counter replicas received from another node
+    ///
+    /// The merge is recorded as a completed sync operation whose data holds the
+    /// merged totals.
+    pub async fn sync_counters(
+        &self,
+        source_node: Uuid,
+        target_node: Uuid,
+        remote: &HashMap<String, PNCounter>,
+    ) -> CounterSyncResult {
+        let totals: HashMap<String, i64> = {
+            let mut counters = self.counters.write().await;
+            for (name, counter) in remote {
+                counters.entry(name.clone()).or_default().merge(counter);
+            }
+            counters
+                .iter()
+                .map(|(name, counter)| (name.clone(), counter.value()))
+                .collect()
+        };
+
+        let now = Utc::now();
+        let sync_id = Uuid::new_v4();
+        let operation = SyncOperation {
+            id: sync_id,
+            source_node,
+            target_node,
+            data_type: "crdt_counters".to_string(),
+            data: serde_json::json!({ "totals": totals }),
+            status: SyncStatus::Completed,
+            created_at: now,
+            completed_at: Some(now),
+        };
+        self.sync_operations
+            .write()
+            .await
+            .insert(sync_id, operation);
+
+        CounterSyncResult { sync_id, totals }
+    }
 }
@@ -0,0 +1,252 @@
+//! In-Memory Broker
+//!
+//! Routes events from in-memory publishers to in-memory subscribers. Every
+//! subscription has its own bounded queue, so a slow subscriber can never grow
+//! memory without limit. When a queue is full the configured overflow policy
+//! decides whether the publisher waits, the oldest queued event is dropped, or
+//! the new event is dropped. Queue depth, drops and blocked publishes are
+//! reported per subscription.
+
+use crate::events::EventEnvelope;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// What happens when an event is published to a full subscriber queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    /// The publisher waits until the subscriber has room
+    Block,
+    /// The oldest queued event is dropped to make room
+    DropOldest,
+    /// The new event is dropped for this subscriber
+    DropNewest,
+}
+
+/// In-memory broker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerConfig {
+    /// Maximum queued events per subscription
+    pub queue_capacity: usize,
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for BrokerConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 1024,
+            overflow_policy: OverflowPolicy::Block,
+        }
+    }
+}
+
+/// Queue statistics of one subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionStats {
+    pub subscription_id: Uuid,
+    pub topic: String,
+    pub overflow_policy: OverflowPolicy,
+    pub capacity: usize,
+    /// Events currently queued
+    pub depth: usize,
+    /// Events handed to the subscriber
+    pub delivered: u64,
+    /// Events dropped by the overflow policy
+    pub dropped: u64,
+    /// Publishes that had to wait for room
+    pub blocked_publishes: u64,
+}
+
+/// Bounded queue of one subscription
+pub(crate) struct SubscriptionQueue {
+    id: Uuid,
+    topic: String,
+    capacity: usize,
+    policy: OverflowPolicy,
+    events: Mutex<VecDeque<EventEnvelope>>,
+    not_empty: Notify,
+    not_full: Notify,
+    closed: AtomicBool,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    blocked_publishes: AtomicU64,
+}
+
+impl SubscriptionQueue {
+    /// Offer an event without waiting; returns false, leaving the event in
+    /// place, if the queue is full under the blocking policy
+    fn try_push(&self, event: &mut Option<EventEnvelope>) -> bool {
+        let mut events = self
+            .events
+            .lock()
+            .expect("subscription queue lock poisoned");
+        if events.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::Block => return false,
+                OverflowPolicy::DropNewest => {
+                    event.take();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return true;
+                }
+                OverflowPolicy::DropOldest => {
+                    events.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        events.extend(event.take());
+        drop(events);
+        self.not_empty.notify_one();
+        true
+    }
+
+    async fn push(&self, event: EventEnvelope) {
+        let mut event = Some(event);
+        let mut blocked = false;
+        loop {
+            if self.closed.load(Ordering::Acquire) {
+                return;
+            }
+            // Register for wake-up before checking, so a pop in between is not missed
+            let room = self.not_full.notified();
+            if self.try_push(&mut event) {
+                return;
+            }
+            if !blocked {
+                blocked = true;
+                self.blocked_publishes.fetch_add(1, Ordering::Relaxed);
+            }
+            room.await;
+        }
+    }
+
+    pub(crate) async fn pop(&self) -> EventEnvelope {
+        loop {
+            let ready = self.not_empty.notified();
+            let next = self
+                .events
+                .lock()
+                .expect("subscription queue lock poisoned")
+                .pop_front();
+            if let Some(event) = next {
+                self.delivered.fetch_add(1, Ordering::Relaxed);
+                self.not_full.notify_one();
+                return event;
+            }
+            ready.await;
+        }
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        // Release publishers waiting for room that will never come
+        self.not_full.notify_waiters();
+    }
+
+    fn stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            subscription_id: self.id,
+            topic: self.topic.clone(),
+            overflow_policy: self.policy,
+            capacity: self.capacity,
+            depth: self
+                .events
+                .lock()
+                .expect("subscription queue lock poisoned")
+                .len(),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            blocked_publishes: self.blocked_publishes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Closes its subscription queue when the subscriber's stream is dropped
+pub(crate) struct SubscriptionHandle(pub(crate) Arc<SubscriptionQueue>);
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Shared in-memory broker connecting publishers and subscribers
+#[derive(Clone, Default)]
+pub struct InMemoryBroker {
+    config: BrokerConfig,
+    topics: Arc<Mutex<HashMap<String, Vec<Arc<SubscriptionQueue>>>>>,
+}
+
+impl InMemoryBroker {
+    pub fn new(config: BrokerConfig) -> Self {
+        Self {
+            config: BrokerConfig {
+                queue_capacity: config.queue_capacity.max(1),
+                ..config
+            },
+            topics: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &BrokerConfig {
+        &self.config
+    }
+
+    /// Deliver an event to every subscription of a topic
+    ///
+    /// Under the blocking policy this waits until each subscriber has room.
+    pub async fn publish(&self, topic: &str, event: EventEnvelope) {
+        let queues: Vec<Arc<SubscriptionQueue>> = {
+            let mut topics = self.topics.lock().expect("broker lock poisoned");
+            let Some(queues) = topics.get_mut(topic) else {
+                return;
+            };
+            queues.retain(|queue| !queue.closed.load(Ordering::Acquire));
+            queues.clone()
+        };
+
+        for queue in queues {
+            queue.push(event.clone()).await;
+        }
+    }
+
+    /// Open a bounded subscription to a topic
+    pub(crate) fn subscribe(&self, topic: &str) -> SubscriptionHandle {
+        let queue = Arc::new(SubscriptionQueue {
+            id: Uuid::new_v4(),
+            topic: topic.to_string(),
+            capacity: self.config.queue_capacity,
+            policy: self.config.overflow_policy,
+            events: Mutex::new(VecDeque::new()),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+            closed: AtomicBool::new(false),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            blocked_publishes: AtomicU64::new(0),
+        });
+
+        self.topics
+            .lock()
+            .expect("broker lock poisoned")
+            .entry(topic.to_string())
+            .or_default()
+            .push(Arc::clone(&queue));
+        SubscriptionHandle(queue)
+    }
+
+    /// Statistics of every open subscription
+    pub fn stats(&self) -> Vec<SubscriptionStats> {
+        self.topics
+            .lock()
+            .expect("broker lock poisoned")
+            .values()
+            .flatten()
+            .filter(|queue| !queue.closed.load(Ordering::Acquire))
+            .map(|queue| queue.stats())
+            .collect()
+    }
+}
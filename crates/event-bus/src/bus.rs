@@ -1,5 +1,6 @@
 //! Event Bus Interface
 
+use crate::broker::{BrokerConfig, InMemoryBroker};
 use crate::publisher::{EventPublisher, InMemoryPublisher};
 use crate::subscriber::{EventSubscriber, InMemorySubscriber};
 use async_trait::async_trait;
@@ -14,11 +15,24 @@ pub trait EventBus: Send + Sync {
 /// In-memory event bus (for development/testing)
 pub struct InMemoryEventBus {
     // In production, this would connect to Kafka/NATS/etc.
+    broker: InMemoryBroker,
 }
 
 impl InMemoryEventBus {
     pub fn new() -> Self {
-        Self {}
+        Self::with_config(BrokerConfig::default())
+    }
+
+    /// Bus whose subscriber queues use the given capacity and overflow policy
+    pub fn with_config(config: BrokerConfig) -> Self {
+        Self {
+            broker: InMemoryBroker::new(config),
+        }
+    }
+
+    /// Broker shared by this bus's publishers and subscribers
+    pub fn broker(&self) -> &InMemoryBroker {
+        &self.broker
     }
 }
 
@@ -31,10 +45,10 @@ impl Default for InMemoryEventBus {
 #[async_trait]
 impl EventBus for InMemoryEventBus {
     fn publisher(&self) -> Box<dyn EventPublisher> {
-        Box::new(InMemoryPublisher::new())
+        Box::new(InMemoryPublisher::with_broker(self.broker.clone()))
     }
 
     fn subscriber(&self) -> Box<dyn EventSubscriber> {
-        Box::new(InMemorySubscriber::new())
+        Box::new(InMemorySubscriber::with_broker(self.broker.clone()))
     }
 }
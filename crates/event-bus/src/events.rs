@@ -51,4 +51,8 @@ pub mod topics {
     pub const INVENTORY_EVENTS: &str = "inventory.events";
     pub const BILLING_EVENTS: &str = "billing.events";
     pub const ALARM_EVENTS: &str = "alarm.events";
+    pub const CATALOG_EVENTS: &str = "catalog.events";
+    pub const FRAUD_EVENTS: &str = "fraud.events";
+    pub const PARTNER_EVENTS: &str = "partner.events";
+    pub const SECURITY_EVENTS: &str = "security.events";
 }
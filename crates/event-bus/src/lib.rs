@@ -3,11 +3,13 @@
 //! Provides a unified interface for event publishing and subscription
 //! Supports multiple backends: Kafka, NATS, Redpanda, or in-memory
 
+pub mod broker;
 pub mod bus;
 pub mod events;
 pub mod publisher;
 pub mod subscriber;
 
+pub use broker::{BrokerConfig, InMemoryBroker, OverflowPolicy, SubscriptionStats};
 pub use bus::EventBus;
 pub use publisher::EventPublisher;
 pub use subscriber::EventSubscriber;
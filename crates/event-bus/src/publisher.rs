@@ -1,5 +1,6 @@
 //! Event Publisher
 
+use crate::broker::InMemoryBroker;
 use crate::events::EventEnvelope;
 use async_trait::async_trait;
 use thiserror::Error;
@@ -25,19 +26,29 @@ pub enum PublishError {
 #[derive(Default)]
 pub struct InMemoryPublisher {
     // In production, this would publish to Kafka/NATS/etc.
+    broker: Option<InMemoryBroker>,
 }
 
 impl InMemoryPublisher {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Publisher delivering to the subscribers of a broker
+    pub fn with_broker(broker: InMemoryBroker) -> Self {
+        Self {
+            broker: Some(broker),
+        }
+    }
 }
 
 #[async_trait]
 impl EventPublisher for InMemoryPublisher {
-    async fn publish(&self, _topic: &str, _event: EventEnvelope) -> Result<(), PublishError> {
-        // In-memory implementation - just log for now
-        log::info!("Publishing event to topic: {}", _topic);
+    async fn publish(&self, topic: &str, event: EventEnvelope) -> Result<(), PublishError> {
+        log::info!("Publishing event to topic: {}", topic);
+        if let Some(broker) = &self.broker {
+            broker.publish(topic, event).await;
+        }
         Ok(())
     }
 }
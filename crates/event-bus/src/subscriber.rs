@@ -1,5 +1,6 @@
 //! Event Subscriber
 
+use crate::broker::InMemoryBroker;
 use crate::events::EventEnvelope;
 use async_trait::async_trait;
 use futures::Stream;
@@ -31,25 +32,42 @@ pub enum SubscribeError {
 #[derive(Default)]
 pub struct InMemorySubscriber {
     // In production, this would subscribe to Kafka/NATS/etc.
+    broker: Option<InMemoryBroker>,
 }
 
 impl InMemorySubscriber {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Subscriber receiving from a broker through bounded queues
+    pub fn with_broker(broker: InMemoryBroker) -> Self {
+        Self {
+            broker: Some(broker),
+        }
+    }
 }
 
 #[async_trait]
 impl EventSubscriber for InMemorySubscriber {
     async fn subscribe(
         &self,
-        _topic: &str,
+        topic: &str,
     ) -> Result<
         Pin<Box<dyn Stream<Item = Result<EventEnvelope, SubscribeError>> + Send>>,
         SubscribeError,
     > {
-        // In-memory implementation - return empty stream for now
         use futures::stream;
-        Ok(Box::pin(stream::empty()))
+
+        let Some(broker) = &self.broker else {
+            return Ok(Box::pin(stream::empty()));
+        };
+
+        // The handle lives in the stream state and closes the queue on drop
+        let handle = broker.subscribe(topic);
+        Ok(Box::pin(stream::unfold(handle, |handle| async move {
+            let event = handle.0.pop().await;
+            Some((Ok(event), handle))
+        })))
     }
 }
@@ -0,0 +1,475 @@
+//! Device Certificate Lifecycle
+//!
+//! Issues a certificate for each device on provisioning and rotates it before
+//! expiry. Rotation issues a replacement for the device's public key, pushes
+//! it over the command channel and only revokes the old certificate once the
+//! device confirms it has installed the new one. Devices that are offline at
+//! rotation time receive the pending certificate when they reconnect.
+
+use crate::error::IoTError;
+use crate::models::{
+    CertificateStatus, DeviceCertificate, DeviceCommand, DeviceStatus, IssuedCertificate,
+    ProvisionDeviceRequest,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Command sent to a device to install a new certificate
+pub const INSTALL_CERTIFICATE_COMMAND: &str = "INSTALL_CERTIFICATE";
+
+/// Certificate authority issuing device certificates
+#[async_trait]
+pub trait DeviceCertificateAuthority: Send + Sync {
+    /// Issue a certificate for a device public key
+    async fn issue(
+        &self,
+        device_id: Uuid,
+        public_key_pem: &str,
+        validity: Duration,
+    ) -> Result<IssuedCertificate, IoTError>;
+
+    /// Revoke a previously issued certificate
+    async fn revoke(&self, serial_number: &str) -> Result<(), IoTError>;
+}
+
+/// Channel for pushing commands to devices
+#[async_trait]
+pub trait DeviceCommandChannel: Send + Sync {
+    async fn send(&self, command: DeviceCommand) -> Result<(), IoTError>;
+}
+
+/// Certificate rotation configuration
+#[derive(Debug, Clone)]
+pub struct CertificateRotationConfig {
+    /// Validity of issued certificates
+    pub validity: Duration,
+    /// Rotate certificates this long before they expire
+    pub rotate_before: Duration,
+    /// How often the rotation job runs
+    pub check_interval: std::time::Duration,
+}
+
+impl Default for CertificateRotationConfig {
+    fn default() -> Self {
+        Self {
+            validity: Duration::days(365),
+            rotate_before: Duration::days(30),
+            check_interval: std::time::Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Device Certificate Service
+pub struct DeviceCertificateService {
+    pool: PgPool,
+    authority: Arc<dyn DeviceCertificateAuthority>,
+    commands: Arc<dyn DeviceCommandChannel>,
+    config: CertificateRotationConfig,
+}
+
+impl DeviceCertificateService {
+    /// Create a new device certificate service
+    pub fn new(
+        pool: PgPool,
+        authority: Arc<dyn DeviceCertificateAuthority>,
+        commands: Arc<dyn DeviceCommandChannel>,
+    ) -> Self {
+        Self {
+            pool,
+            authority,
+            commands,
+            config: CertificateRotationConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: CertificateRotationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Provision a device: issue its first certificate and mark it provisioned
+    pub async fn provision_device(
+        &self,
+        device_id: Uuid,
+        request: ProvisionDeviceRequest,
+    ) -> Result<DeviceCertificate, IoTError> {
+        let exists: Option<Uuid> = sqlx::query_scalar("SELECT id FROM iot_devices WHERE id = $1")
+            .bind(device_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_none() {
+            return Err(IoTError::DeviceNotFound(device_id.to_string()));
+        }
+        if self.get_active_certificate(device_id).await?.is_some() {
+            return Err(IoTError::CertificateError(format!(
+                "Device {} already has an active certificate",
+                device_id
+            )));
+        }
+
+        let issued = self
+            .authority
+            .issue(device_id, &request.public_key_pem, self.config.validity)
+            .await?;
+
+        let now = Utc::now();
+        let certificate = self
+            .insert_certificate(
+                device_id,
+                &issued,
+                &request.public_key_pem,
+                CertificateStatus::Active,
+                None,
+            )
+            .await?;
+
+        // The device receives its first certificate in the provisioning response
+        sqlx::query(
+            "UPDATE iot_device_certificates SET delivered_at = $1, confirmed_at = $1 WHERE id = $2",
+        )
+        .bind(now)
+        .bind(certificate.id)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("UPDATE iot_devices SET status = $1, last_update = $2 WHERE id = $3")
+            .bind(format!("{:?}", DeviceStatus::Provisioned))
+            .bind(now)
+            .bind(device_id)
+            .execute(&self.pool)
+            .await?;
+
+        info!(
+            "Provisioned device {} with certificate {}",
+            device_id, certificate.serial_number
+        );
+        self.get_certificate(certificate.id).await
+    }
+
+    /// Issue replacements for certificates expiring within the rotation window
+    ///
+    /// Returns the replacement certificates issued in this run.
+    pub async fn rotate_expiring_certificates(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<DeviceCertificate>, IoTError> {
+        let rows = sqlx::query(
+            "SELECT c.id FROM iot_device_certificates c
+             WHERE c.status = 'ACTIVE' AND c.not_after <= $1
+             AND NOT EXISTS (
+                SELECT 1 FROM iot_device_certificates p
+                WHERE p.replaces_certificate_id = c.id AND p.status = 'PENDING'
+             )",
+        )
+        .bind(now + self.config.rotate_before)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut rotated = Vec::new();
+        for row in rows {
+            let current = self.get_certificate(row.get("id")).await?;
+            if current.not_after <= now {
+                warn!(
+                    "Certificate {} of device {} expired at {} before rotation",
+                    current.serial_number, current.device_id, current.not_after
+                );
+            }
+
+            let issued = match self
+                .authority
+                .issue(
+                    current.device_id,
+                    &current.public_key_pem,
+                    self.config.validity,
+                )
+                .await
+            {
+                Ok(issued) => issued,
+                Err(e) => {
+                    error!(
+                        "Failed to issue replacement certificate for device {}: {}",
+                        current.device_id, e
+                    );
+                    continue;
+                }
+            };
+
+            let replacement = self
+                .insert_certificate(
+                    current.device_id,
+                    &issued,
+                    &current.public_key_pem,
+                    CertificateStatus::Pending,
+                    Some(current.id),
+                )
+                .await?;
+
+            rotated.push(self.deliver(replacement).await?);
+        }
+
+        Ok(rotated)
+    }
+
+    /// Deliver any pending certificate to a device that has just reconnected
+    pub async fn handle_reconnect(
+        &self,
+        device_id: Uuid,
+    ) -> Result<Option<DeviceCertificate>, IoTError> {
+        match self.get_pending_certificate(device_id).await? {
+            Some(pending) => Ok(Some(self.deliver(pending).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Device confirmed it installed a certificate: activate it and revoke the old one
+    pub async fn confirm_certificate(
+        &self,
+        device_id: Uuid,
+        certificate_id: Uuid,
+    ) -> Result<DeviceCertificate, IoTError> {
+        let certificate = self.get_certificate(certificate_id).await?;
+        if certificate.device_id != device_id {
+            return Err(IoTError::CertificateError(format!(
+                "Certificate {} does not belong to device {}",
+                certificate_id, device_id
+            )));
+        }
+        if certificate.status != CertificateStatus::Pending {
+            return Err(IoTError::CertificateError(format!(
+                "Certificate {} is not pending confirmation",
+                certificate_id
+            )));
+        }
+
+        let previous = match certificate.replaces_certificate_id {
+            Some(previous_id) => Some(self.get_certificate(previous_id).await?),
+            None => None,
+        };
+
+        // Activate the new certificate and revoke the old one together, and
+        // only if the certificate is still pending, so concurrent confirmations
+        // cannot both succeed
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        let activated = sqlx::query(
+            "UPDATE iot_device_certificates SET status = 'ACTIVE', confirmed_at = $1
+             WHERE id = $2 AND status = 'PENDING'",
+        )
+        .bind(now)
+        .bind(certificate_id)
+        .execute(&mut *tx)
+        .await?;
+        if activated.rows_affected() == 0 {
+            return Err(IoTError::CertificateError(format!(
+                "Certificate {} is not pending confirmation",
+                certificate_id
+            )));
+        }
+
+        if let Some(previous) = &previous {
+            sqlx::query(
+                "UPDATE iot_device_certificates SET status = 'REVOKED', revoked_at = $1 WHERE id = $2",
+            )
+            .bind(now)
+            .bind(previous.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        // The CA is only told once the swap is committed, so a failed
+        // transaction never leaves the device without a valid certificate
+        if let Some(previous) = previous {
+            if let Err(e) = self.authority.revoke(&previous.serial_number).await {
+                error!(
+                    "Failed to revoke certificate {} at the CA: {}",
+                    previous.serial_number, e
+                );
+            }
+        }
+
+        info!(
+            "Device {} confirmed certificate {}",
+            device_id, certificate.serial_number
+        );
+        self.get_certificate(certificate_id).await
+    }
+
+    /// Get the certificate a device currently authenticates with
+    pub async fn get_active_certificate(
+        &self,
+        device_id: Uuid,
+    ) -> Result<Option<DeviceCertificate>, IoTError> {
+        let row = sqlx::query(
+            "SELECT id FROM iot_device_certificates
+             WHERE device_id = $1 AND status = 'ACTIVE'
+             ORDER BY not_after DESC LIMIT 1",
+        )
+        .bind(device_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.get_certificate(row.get("id")).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Run certificate rotation periodically in the background
+    pub fn spawn_rotation(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.check_interval);
+            loop {
+                interval.tick().await;
+                match self.rotate_expiring_certificates(Utc::now()).await {
+                    Ok(rotated) if !rotated.is_empty() => {
+                        info!("Rotated {} device certificates", rotated.len())
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Device certificate rotation failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Push a pending certificate to its device unless the device is offline
+    async fn deliver(&self, certificate: DeviceCertificate) -> Result<DeviceCertificate, IoTError> {
+        let status: Option<String> =
+            sqlx::query_scalar("SELECT status FROM iot_devices WHERE id = $1")
+                .bind(certificate.device_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if status.as_deref() == Some(format!("{:?}", DeviceStatus::Offline).as_str()) {
+            info!(
+                "Device {} offline; certificate {} will be delivered on reconnect",
+                certificate.device_id, certificate.serial_number
+            );
+            return Ok(certificate);
+        }
+
+        let command = DeviceCommand {
+            device_id: certificate.device_id,
+            command: INSTALL_CERTIFICATE_COMMAND.to_string(),
+            parameters: Some(serde_json::json!({
+                "certificate_id": certificate.id,
+                "serial_number": certificate.serial_number,
+                "certificate_pem": certificate.certificate_pem,
+                "not_after": certificate.not_after,
+            })),
+            timeout_seconds: Some(60),
+        };
+
+        if let Err(e) = self.commands.send(command).await {
+            warn!(
+                "Failed to deliver certificate {} to device {}: {}",
+                certificate.serial_number, certificate.device_id, e
+            );
+            return Ok(certificate);
+        }
+
+        sqlx::query("UPDATE iot_device_certificates SET delivered_at = $1 WHERE id = $2")
+            .bind(Utc::now())
+            .bind(certificate.id)
+            .execute(&self.pool)
+            .await?;
+
+        self.get_certificate(certificate.id).await
+    }
+
+    async fn get_pending_certificate(
+        &self,
+        device_id: Uuid,
+    ) -> Result<Option<DeviceCertificate>, IoTError> {
+        let row = sqlx::query(
+            "SELECT id FROM iot_device_certificates
+             WHERE device_id = $1 AND status = 'PENDING'
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(device_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.get_certificate(row.get("id")).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn insert_certificate(
+        &self,
+        device_id: Uuid,
+        issued: &IssuedCertificate,
+        public_key_pem: &str,
+        status: CertificateStatus,
+        replaces_certificate_id: Option<Uuid>,
+    ) -> Result<DeviceCertificate, IoTError> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO iot_device_certificates (
+                id, device_id, serial_number, certificate_pem, public_key_pem,
+                status, not_before, not_after, replaces_certificate_id, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(id)
+        .bind(device_id)
+        .bind(&issued.serial_number)
+        .bind(&issued.certificate_pem)
+        .bind(public_key_pem)
+        .bind(certificate_status_to_db(&status))
+        .bind(issued.not_before)
+        .bind(issued.not_after)
+        .bind(replaces_certificate_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        self.get_certificate(id).await
+    }
+
+    async fn get_certificate(&self, certificate_id: Uuid) -> Result<DeviceCertificate, IoTError> {
+        let row = sqlx::query(
+            "SELECT id, device_id, serial_number, certificate_pem, public_key_pem, status,
+             not_before, not_after, replaces_certificate_id, delivered_at, confirmed_at,
+             revoked_at, created_at
+             FROM iot_device_certificates WHERE id = $1",
+        )
+        .bind(certificate_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| {
+            IoTError::CertificateError(format!("Certificate not found: {}", certificate_id))
+        })?;
+
+        let status_str: String = row.get("status");
+        let status = serde_json::from_str(&format!("\"{}\"", status_str))
+            .map_err(|e| IoTError::SerializationError(format!("Invalid status: {}", e)))?;
+
+        Ok(DeviceCertificate {
+            id: row.get("id"),
+            device_id: row.get("device_id"),
+            serial_number: row.get("serial_number"),
+            certificate_pem: row.get("certificate_pem"),
+            public_key_pem: row.get("public_key_pem"),
+            status,
+            not_before: row.get("not_before"),
+            not_after: row.get("not_after"),
+            replaces_certificate_id: row.get("replaces_certificate_id"),
+            delivered_at: row.get("delivered_at"),
+            confirmed_at: row.get("confirmed_at"),
+            revoked_at: row.get("revoked_at"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
+
+fn certificate_status_to_db(status: &CertificateStatus) -> &'static str {
+    match status {
+        CertificateStatus::Active => "ACTIVE",
+        CertificateStatus::Pending => "PENDING",
+        CertificateStatus::Revoked => "REVOKED",
+    }
+}
@@ -17,6 +17,12 @@ pub enum IoTError {
     #[error("Device offline: {0}")]
     DeviceOffline(String),
 
+    #[error("Certificate error: {0}")]
+    CertificateError(String),
+
+    #[error("Command delivery failed: {0}")]
+    CommandDeliveryFailed(String),
+
     #[error("Database error: {0}")]
     DatabaseError(String),
 
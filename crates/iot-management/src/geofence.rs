@@ -0,0 +1,209 @@
+//! Geofencing
+//!
+//! Detects devices entering or leaving geofenced areas from telemetry
+//! coordinates. Telemetry is expected to carry `latitude`/`longitude` either at
+//! the top level of the metrics or under a `location` object; telemetry
+//! without coordinates is ignored.
+//!
+//! The first position seen for a device only establishes whether it is inside
+//! each geofence; events are raised on subsequent changes.
+
+use crate::error::IoTError;
+use crate::models::{
+    DeviceTelemetry, GeoPoint, Geofence, GeofenceEvent, GeofenceShape, GeofenceTransition,
+};
+use std::collections::HashMap;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+/// Capacity of the geofence event channel
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Mean Earth radius in meters
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// Great-circle distance between two points in meters
+pub fn haversine_distance(a: GeoPoint, b: GeoPoint) -> f64 {
+    let lat1 = a.latitude.to_radians();
+    let lat2 = b.latitude.to_radians();
+    let dlat = (b.latitude - a.latitude).to_radians();
+    let dlon = (b.longitude - a.longitude).to_radians();
+
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().asin()
+}
+
+/// Ray-casting point-in-polygon test on latitude/longitude
+fn polygon_contains(vertices: &[GeoPoint], point: GeoPoint) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len() - 1;
+    for i in 0..vertices.len() {
+        let (vi, vj) = (vertices[i], vertices[j]);
+        if (vi.latitude > point.latitude) != (vj.latitude > point.latitude)
+            && point.longitude
+                < (vj.longitude - vi.longitude) * (point.latitude - vi.latitude)
+                    / (vj.latitude - vi.latitude)
+                    + vi.longitude
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl GeofenceShape {
+    /// Whether a point lies within the area
+    pub fn contains(&self, point: GeoPoint) -> bool {
+        match self {
+            GeofenceShape::Circle {
+                center,
+                radius_meters,
+            } => haversine_distance(*center, point) <= *radius_meters,
+            GeofenceShape::Polygon { vertices } => polygon_contains(vertices, point),
+        }
+    }
+
+    fn validate(&self) -> Result<(), IoTError> {
+        let valid_point = |p: &GeoPoint| {
+            (-90.0..=90.0).contains(&p.latitude) && (-180.0..=180.0).contains(&p.longitude)
+        };
+        match self {
+            GeofenceShape::Circle {
+                center,
+                radius_meters,
+            } if valid_point(center) && *radius_meters > 0.0 => Ok(()),
+            GeofenceShape::Polygon { vertices }
+                if vertices.len() >= 3 && vertices.iter().all(valid_point) =>
+            {
+                Ok(())
+            }
+            _ => Err(IoTError::InvalidConfiguration(
+                "Geofence needs a positive radius or at least 3 valid polygon vertices".to_string(),
+            )),
+        }
+    }
+}
+
+impl Geofence {
+    fn applies_to(&self, device_id: Uuid) -> bool {
+        self.device_ids.is_empty() || self.device_ids.contains(&device_id)
+    }
+}
+
+/// Extract a position from telemetry metrics
+pub fn telemetry_position(metrics: &serde_json::Value) -> Option<GeoPoint> {
+    let source = match metrics.get("location") {
+        Some(location) if location.is_object() => location,
+        _ => metrics,
+    };
+    let latitude = source.get("latitude")?.as_f64()?;
+    let longitude = source.get("longitude")?.as_f64()?;
+    Some(GeoPoint {
+        latitude,
+        longitude,
+    })
+}
+
+/// Geofence Engine
+pub struct GeofenceEngine {
+    geofences: RwLock<HashMap<Uuid, Geofence>>,
+    /// Last known inside/outside state per (device, geofence)
+    membership: RwLock<HashMap<(Uuid, Uuid), bool>>,
+    events: broadcast::Sender<GeofenceEvent>,
+}
+
+impl GeofenceEngine {
+    /// Create a new geofence engine
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            geofences: RwLock::new(HashMap::new()),
+            membership: RwLock::new(HashMap::new()),
+            events,
+        }
+    }
+
+    /// Subscribe to geofence enter/exit events
+    pub fn subscribe(&self) -> broadcast::Receiver<GeofenceEvent> {
+        self.events.subscribe()
+    }
+
+    /// Add or replace a geofence
+    pub async fn upsert_geofence(&self, geofence: Geofence) -> Result<(), IoTError> {
+        geofence.shape.validate()?;
+        let id = geofence.id;
+        self.geofences.write().await.insert(id, geofence);
+        // Shape may have changed; re-establish membership on next position
+        self.membership.write().await.retain(|(_, g), _| *g != id);
+        Ok(())
+    }
+
+    /// Remove a geofence
+    pub async fn remove_geofence(&self, geofence_id: Uuid) -> Result<(), IoTError> {
+        if self.geofences.write().await.remove(&geofence_id).is_none() {
+            return Err(IoTError::InvalidConfiguration(format!(
+                "Geofence not found: {}",
+                geofence_id
+            )));
+        }
+        self.membership
+            .write()
+            .await
+            .retain(|(_, g), _| *g != geofence_id);
+        Ok(())
+    }
+
+    /// List geofences
+    pub async fn list_geofences(&self) -> Vec<Geofence> {
+        self.geofences.read().await.values().cloned().collect()
+    }
+
+    /// Evaluate telemetry against all geofences, emitting enter/exit events
+    pub async fn process_telemetry(&self, telemetry: &DeviceTelemetry) -> Vec<GeofenceEvent> {
+        let Some(position) = telemetry_position(&telemetry.metrics) else {
+            return Vec::new();
+        };
+
+        let geofences = self.geofences.read().await;
+        let mut membership = self.membership.write().await;
+        let mut events = Vec::new();
+
+        for geofence in geofences
+            .values()
+            .filter(|g| g.applies_to(telemetry.device_id))
+        {
+            let inside = geofence.shape.contains(position);
+            let previous = membership.insert((telemetry.device_id, geofence.id), inside);
+
+            let transition = match previous {
+                Some(false) if inside => GeofenceTransition::Enter,
+                Some(true) if !inside => GeofenceTransition::Exit,
+                _ => continue,
+            };
+            if !geofence.alert_on.contains(&transition) {
+                continue;
+            }
+
+            let event = GeofenceEvent {
+                geofence_id: geofence.id,
+                geofence_name: geofence.name.clone(),
+                device_id: telemetry.device_id,
+                transition,
+                position,
+                timestamp: telemetry.timestamp,
+            };
+            // No subscribers is not an error
+            let _ = self.events.send(event.clone());
+            events.push(event);
+        }
+
+        events
+    }
+}
+
+impl Default for GeofenceEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
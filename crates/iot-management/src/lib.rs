@@ -6,11 +6,17 @@
 //! - Remote device control and configuration
 //! - Device telemetry data collection
 //! - Device lifecycle management
+//! - Device certificate provisioning and rotation
+//! - Geofencing with enter/exit events
 
+pub mod certificates;
 pub mod error;
+pub mod geofence;
 pub mod models;
 pub mod service;
 
+pub use certificates::*;
 pub use error::*;
+pub use geofence::*;
 pub use models::*;
 pub use service::*;
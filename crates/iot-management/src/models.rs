@@ -123,3 +123,104 @@ pub struct DeviceCommand {
     pub parameters: Option<serde_json::Value>,
     pub timeout_seconds: Option<u64>,
 }
+
+/// Device Certificate Status
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CertificateStatus {
+    /// Certificate the device currently authenticates with
+    Active,
+    /// Replacement issued by rotation, awaiting device confirmation
+    Pending,
+    /// Replaced or withdrawn; no longer accepted
+    Revoked,
+}
+
+/// Device Certificate
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeviceCertificate {
+    pub id: Uuid,
+    pub device_id: Uuid,
+    pub serial_number: String,
+    pub certificate_pem: String,
+    /// Device public key the certificate was issued for
+    pub public_key_pem: String,
+    pub status: CertificateStatus,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    /// Certificate this one replaces, for rotations
+    pub replaces_certificate_id: Option<Uuid>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Certificate returned by a certificate authority
+#[derive(Debug, Clone)]
+pub struct IssuedCertificate {
+    pub serial_number: String,
+    pub certificate_pem: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// Provision Device Request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProvisionDeviceRequest {
+    /// Public key generated on the device; the private key never leaves it
+    pub public_key_pem: String,
+}
+
+/// Geographic coordinate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Geofence area
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GeofenceShape {
+    Circle {
+        center: GeoPoint,
+        radius_meters: f64,
+    },
+    Polygon {
+        vertices: Vec<GeoPoint>,
+    },
+}
+
+/// Geofence transition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GeofenceTransition {
+    Enter,
+    Exit,
+}
+
+/// Geofence Definition
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Geofence {
+    pub id: Uuid,
+    pub name: String,
+    pub shape: GeofenceShape,
+    /// Devices the geofence applies to; empty applies to all devices
+    #[serde(default)]
+    pub device_ids: Vec<Uuid>,
+    /// Transitions that raise an event
+    pub alert_on: Vec<GeofenceTransition>,
+    pub tenant_id: Option<Uuid>,
+}
+
+/// Geofence Event raised when a device enters or leaves a geofence
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GeofenceEvent {
+    pub geofence_id: Uuid,
+    pub geofence_name: String,
+    pub device_id: Uuid,
+    pub transition: GeofenceTransition,
+    pub position: GeoPoint,
+    pub timestamp: DateTime<Utc>,
+}
@@ -1,23 +1,35 @@
 //! IoT Device Management Service
 
 use crate::error::IoTError;
+use crate::geofence::GeofenceEngine;
 use crate::models::{
     CreateDeviceRequest, DeviceStatus, DeviceTelemetry, IoTDevice, UpdateDeviceRequest,
 };
 use chrono::Utc;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::BaseEntity;
 use uuid::Uuid;
 
 /// IoT Device Management Service
 pub struct IoTService {
     pool: PgPool,
+    geofences: Option<Arc<GeofenceEngine>>,
 }
 
 impl IoTService {
     /// Create a new IoT service
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            geofences: None,
+        }
+    }
+
+    /// Evaluate stored telemetry against geofences
+    pub fn with_geofences(mut self, geofences: Arc<GeofenceEngine>) -> Self {
+        self.geofences = Some(geofences);
+        self
     }
 
     /// Register a new IoT device
@@ -248,6 +260,10 @@ impl IoTService {
         .execute(&self.pool)
         .await?;
 
+        if let Some(geofences) = &self.geofences {
+            geofences.process_telemetry(&telemetry).await;
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,204 @@
+//! Forecast Backtesting
+//!
+//! Evaluates a forecaster against history with rolling-origin evaluation: the
+//! forecaster is trained on a window of observations, forecasts the following
+//! steps, and the forecasts are compared with the held-out actuals. The origin
+//! then moves forward and the process repeats. Errors are reported per
+//! forecast horizon so short- and long-range accuracy can be judged apart.
+
+use crate::error::MlPredictiveError;
+use serde::{Deserialize, Serialize};
+
+/// Forecasting model that can be backtested
+pub trait Forecaster {
+    /// Forecast the next `horizon` values from a history ordered oldest first
+    fn forecast(&self, history: &[f64], horizon: usize) -> Vec<f64>;
+}
+
+/// Forecasts the mean of the training window for every step
+///
+/// This is the average-rate model used by the demand and revenue predictions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeanForecaster;
+
+impl Forecaster for MeanForecaster {
+    fn forecast(&self, history: &[f64], horizon: usize) -> Vec<f64> {
+        let mean = if history.is_empty() {
+            0.0
+        } else {
+            history.iter().sum::<f64>() / history.len() as f64
+        };
+        vec![mean; horizon]
+    }
+}
+
+/// Extends a least-squares linear trend fitted to the training window
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinearTrendForecaster;
+
+impl Forecaster for LinearTrendForecaster {
+    fn forecast(&self, history: &[f64], horizon: usize) -> Vec<f64> {
+        let n = history.len() as f64;
+        if history.len() < 2 {
+            return MeanForecaster.forecast(history, horizon);
+        }
+
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = history.iter().sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (x, y) in history.iter().enumerate() {
+            let dx = x as f64 - mean_x;
+            cov += dx * (y - mean_y);
+            var += dx * dx;
+        }
+        let slope = cov / var;
+        let intercept = mean_y - slope * mean_x;
+
+        (0..horizon)
+            .map(|step| intercept + slope * (n + step as f64))
+            .collect()
+    }
+}
+
+/// Rolling-origin backtest configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Observations the forecaster is trained on at the first origin
+    pub train_window: usize,
+    /// Steps forecast from each origin
+    pub horizon: usize,
+    /// Observations the origin advances between folds
+    pub step: usize,
+    /// Grow the training window from the start of the series instead of
+    /// sliding a fixed-size window
+    pub expanding_window: bool,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            train_window: 30,
+            horizon: 7,
+            step: 1,
+            expanding_window: false,
+        }
+    }
+}
+
+/// Forecast accuracy at one horizon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonAccuracy {
+    /// Steps ahead of the origin, starting at 1
+    pub horizon: usize,
+    /// Mean absolute percentage error in percent; `None` if every actual was
+    /// zero
+    pub mape: Option<f64>,
+    /// Root mean squared error
+    pub rmse: f64,
+    pub samples: usize,
+}
+
+/// Backtest results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub folds: usize,
+    pub horizons: Vec<HorizonAccuracy>,
+    /// Accuracy across all horizons; `horizon` is 0
+    pub overall: HorizonAccuracy,
+}
+
+#[derive(Default)]
+struct ErrorAccumulator {
+    squared_error: f64,
+    percentage_error: f64,
+    percentage_samples: usize,
+    samples: usize,
+}
+
+impl ErrorAccumulator {
+    fn add(&mut self, forecast: f64, actual: f64) {
+        let error = forecast - actual;
+        self.squared_error += error * error;
+        self.samples += 1;
+        // Percentage error is undefined for zero actuals
+        if actual != 0.0 {
+            self.percentage_error += (error / actual).abs();
+            self.percentage_samples += 1;
+        }
+    }
+
+    fn accuracy(&self, horizon: usize) -> HorizonAccuracy {
+        HorizonAccuracy {
+            horizon,
+            mape: (self.percentage_samples > 0)
+                .then(|| self.percentage_error / self.percentage_samples as f64 * 100.0),
+            rmse: (self.squared_error / self.samples.max(1) as f64).sqrt(),
+            samples: self.samples,
+        }
+    }
+}
+
+/// Backtest a forecaster over a series ordered oldest first
+pub fn backtest(
+    series: &[f64],
+    forecaster: &dyn Forecaster,
+    config: &BacktestConfig,
+) -> Result<BacktestReport, MlPredictiveError> {
+    if config.train_window == 0 || config.horizon == 0 || config.step == 0 {
+        return Err(MlPredictiveError::InvalidInput(
+            "train_window, horizon and step must be positive".to_string(),
+        ));
+    }
+    if series.len() < config.train_window + config.horizon {
+        return Err(MlPredictiveError::InvalidInput(format!(
+            "Backtesting needs at least {} observations, got {}",
+            config.train_window + config.horizon,
+            series.len()
+        )));
+    }
+
+    let mut per_horizon: Vec<ErrorAccumulator> = (0..config.horizon)
+        .map(|_| ErrorAccumulator::default())
+        .collect();
+    let mut overall = ErrorAccumulator::default();
+    let mut folds = 0;
+
+    let mut origin = config.train_window;
+    while origin + config.horizon <= series.len() {
+        let start = if config.expanding_window {
+            0
+        } else {
+            origin - config.train_window
+        };
+        let forecast = forecaster.forecast(&series[start..origin], config.horizon);
+        if forecast.len() != config.horizon {
+            return Err(MlPredictiveError::PredictionFailed(format!(
+                "Forecaster returned {} values for a horizon of {}",
+                forecast.len(),
+                config.horizon
+            )));
+        }
+
+        for (h, (predicted, actual)) in forecast
+            .iter()
+            .zip(&series[origin..origin + config.horizon])
+            .enumerate()
+        {
+            per_horizon[h].add(*predicted, *actual);
+            overall.add(*predicted, *actual);
+        }
+
+        folds += 1;
+        origin += config.step;
+    }
+
+    Ok(BacktestReport {
+        folds,
+        horizons: per_horizon
+            .iter()
+            .enumerate()
+            .map(|(h, acc)| acc.accuracy(h + 1))
+            .collect(),
+        overall: overall.accuracy(0),
+    })
+}
@@ -0,0 +1,159 @@
+//! Customer Lifetime Value Cohort Analysis
+//!
+//! Groups customers into cohorts by acquisition month and/or segment (such as
+//! acquisition channel) and reports average predicted CLV, retention curves and
+//! cumulative value per cohort. Cohorts smaller than the configured threshold
+//! are suppressed to avoid reporting noise.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Per-customer input for cohort analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerCohortRecord {
+    pub customer_id: Uuid,
+    pub acquired_at: DateTime<Utc>,
+    /// Segment or acquisition channel
+    pub segment: String,
+    pub predicted_ltv: f64,
+    /// Revenue for each month the customer was active, starting with the
+    /// acquisition month; churned customers have shorter histories
+    pub monthly_revenue: Vec<f64>,
+    /// Whether the customer has churned; an active customer's history ends
+    /// at the present and is right-censored, not a churn
+    pub churned: bool,
+}
+
+/// How customers are grouped into cohorts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CohortGrouping {
+    AcquisitionMonth,
+    Segment,
+    AcquisitionMonthAndSegment,
+}
+
+/// Cohort identifier
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CohortKey {
+    /// Acquisition month as `YYYY-MM`
+    pub acquisition_month: Option<String>,
+    pub segment: Option<String>,
+}
+
+/// Aggregated view of one cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortSummary {
+    pub key: CohortKey,
+    pub customer_count: usize,
+    pub average_predicted_ltv: f64,
+    /// Estimated share of the cohort still active in each month since
+    /// acquisition (Kaplan-Meier, so customers acquired too recently to have
+    /// reached a month do not count as churned in it)
+    pub retention_curve: Vec<f64>,
+    /// Cohort revenue accumulated up to each month since acquisition
+    pub cumulative_value: Vec<f64>,
+}
+
+/// Cohort analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortReport {
+    pub grouping: CohortGrouping,
+    pub min_cohort_size: usize,
+    pub cohorts: Vec<CohortSummary>,
+    /// Number of cohorts left out for being below `min_cohort_size`
+    pub suppressed_cohorts: usize,
+}
+
+/// Aggregate customers into cohorts
+pub fn analyze_cohorts(
+    records: &[CustomerCohortRecord],
+    grouping: CohortGrouping,
+    min_cohort_size: usize,
+) -> CohortReport {
+    let mut groups: BTreeMap<CohortKey, Vec<&CustomerCohortRecord>> = BTreeMap::new();
+    for record in records {
+        let acquisition_month = record.acquired_at.format("%Y-%m").to_string();
+        let key = match grouping {
+            CohortGrouping::AcquisitionMonth => CohortKey {
+                acquisition_month: Some(acquisition_month),
+                segment: None,
+            },
+            CohortGrouping::Segment => CohortKey {
+                acquisition_month: None,
+                segment: Some(record.segment.clone()),
+            },
+            CohortGrouping::AcquisitionMonthAndSegment => CohortKey {
+                acquisition_month: Some(acquisition_month),
+                segment: Some(record.segment.clone()),
+            },
+        };
+        groups.entry(key).or_default().push(record);
+    }
+
+    let mut cohorts = Vec::new();
+    let mut suppressed_cohorts = 0;
+    for (key, members) in groups {
+        if members.len() < min_cohort_size.max(1) {
+            suppressed_cohorts += 1;
+            continue;
+        }
+        cohorts.push(summarize_cohort(key, &members));
+    }
+
+    CohortReport {
+        grouping,
+        min_cohort_size,
+        cohorts,
+        suppressed_cohorts,
+    }
+}
+
+fn summarize_cohort(key: CohortKey, members: &[&CustomerCohortRecord]) -> CohortSummary {
+    let customer_count = members.len();
+    let average_predicted_ltv =
+        members.iter().map(|m| m.predicted_ltv).sum::<f64>() / customer_count as f64;
+
+    let months = members
+        .iter()
+        .map(|m| m.monthly_revenue.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut retention_curve = Vec::with_capacity(months);
+    let mut cumulative_value = Vec::with_capacity(months);
+    let mut running_total = 0.0;
+    let mut survival = 1.0;
+    for month in 0..months {
+        // Customers still observed at the start of the month, and those who
+        // churned before it ended; censored histories leave the risk set
+        // without counting as churn
+        let at_risk = members
+            .iter()
+            .filter(|m| m.monthly_revenue.len() >= month)
+            .count();
+        let churned = members
+            .iter()
+            .filter(|m| m.churned && m.monthly_revenue.len() == month)
+            .count();
+        if at_risk > 0 {
+            survival *= 1.0 - churned as f64 / at_risk as f64;
+        }
+        retention_curve.push(survival);
+
+        running_total += members
+            .iter()
+            .filter_map(|m| m.monthly_revenue.get(month))
+            .sum::<f64>();
+        cumulative_value.push(running_total);
+    }
+
+    CohortSummary {
+        key,
+        customer_count,
+        average_predicted_ltv,
+        retention_curve,
+        cumulative_value,
+    }
+}
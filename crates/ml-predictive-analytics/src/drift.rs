@@ -0,0 +1,269 @@
+//! Churn Model Drift Detection
+//!
+//! Compares the distribution of each input feature, and of the model's
+//! predictions, in recent data against the baseline the model was trained on.
+//! A feature or prediction distribution whose drift score exceeds the
+//! configured threshold is flagged, and the detector can retrain the churn
+//! model through `ModelTrainer` when drift is found.
+
+use crate::error::MlPredictiveError;
+use crate::models::{ModelMetrics, TrainingDataPoint};
+use crate::training::ModelTrainer;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Smallest bin proportion used by PSI, avoiding division by zero and log(0)
+const PSI_EPSILON: f64 = 1e-4;
+
+/// Statistic used to measure drift between two distributions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriftMetric {
+    /// Population stability index over baseline quantile bins
+    PopulationStabilityIndex,
+    /// Two-sample Kolmogorov-Smirnov statistic
+    KolmogorovSmirnov,
+}
+
+/// Drift detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftConfig {
+    pub metric: DriftMetric,
+    /// Score above which a distribution counts as drifted
+    pub threshold: f64,
+    /// Number of quantile bins for PSI
+    pub bins: usize,
+    /// Retrain the churn model when drift is detected
+    pub retrain_on_drift: bool,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            metric: DriftMetric::PopulationStabilityIndex,
+            // Common rule of thumb: PSI above 0.2 is a significant shift
+            threshold: 0.2,
+            bins: 10,
+            retrain_on_drift: false,
+        }
+    }
+}
+
+/// Drift score of one distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionDrift {
+    /// Feature index, or `None` for the prediction distribution
+    pub feature_index: Option<usize>,
+    pub score: f64,
+    pub drifted: bool,
+}
+
+/// Drift detection result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub metric: DriftMetric,
+    pub threshold: f64,
+    pub features: Vec<DistributionDrift>,
+    pub predictions: Option<DistributionDrift>,
+    pub drifted: bool,
+    pub checked_at: DateTime<Utc>,
+    /// Metrics of the retrained model, if retraining was triggered
+    pub retrained: Option<ModelMetrics>,
+}
+
+/// Training-time distributions the model is compared against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftBaseline {
+    /// Sorted values of each feature
+    features: Vec<Vec<f64>>,
+    /// Sorted predictions on the training data
+    predictions: Vec<f64>,
+}
+
+impl DriftBaseline {
+    /// Capture the baseline from training data and the model's predictions on it
+    pub fn from_training(
+        training_data: &[TrainingDataPoint],
+        predictions: &[f64],
+    ) -> Result<Self, MlPredictiveError> {
+        let rows: Vec<Vec<f64>> = training_data.iter().map(|p| p.features.clone()).collect();
+        Ok(Self {
+            features: columns(&rows)?,
+            predictions: sorted(predictions.to_vec()),
+        })
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+}
+
+/// Monitors churn model inputs and predictions for drift
+pub struct DriftDetector {
+    baseline: DriftBaseline,
+    config: DriftConfig,
+}
+
+impl DriftDetector {
+    pub fn new(baseline: DriftBaseline, config: DriftConfig) -> Self {
+        Self { baseline, config }
+    }
+
+    pub fn config(&self) -> &DriftConfig {
+        &self.config
+    }
+
+    /// Compare recent feature vectors and predictions against the baseline
+    pub fn detect(
+        &self,
+        features: &[Vec<f64>],
+        predictions: &[f64],
+    ) -> Result<DriftReport, MlPredictiveError> {
+        if self.config.metric == DriftMetric::PopulationStabilityIndex && self.config.bins < 2 {
+            return Err(MlPredictiveError::InvalidInput(
+                "PSI needs at least 2 bins".to_string(),
+            ));
+        }
+
+        let recent = columns(features)?;
+        if !recent.is_empty() && recent.len() != self.baseline.features.len() {
+            return Err(MlPredictiveError::InvalidInput(format!(
+                "Expected {} features, got {}",
+                self.baseline.features.len(),
+                recent.len()
+            )));
+        }
+
+        let features: Vec<DistributionDrift> = recent
+            .iter()
+            .zip(&self.baseline.features)
+            .enumerate()
+            .filter(|(_, (recent, baseline))| !recent.is_empty() && !baseline.is_empty())
+            .map(|(index, (recent, baseline))| self.compare(Some(index), baseline, recent))
+            .collect();
+
+        let predictions =
+            (!predictions.is_empty() && !self.baseline.predictions.is_empty()).then(|| {
+                self.compare(
+                    None,
+                    &self.baseline.predictions,
+                    &sorted(predictions.to_vec()),
+                )
+            });
+
+        let drifted =
+            features.iter().any(|f| f.drifted) || predictions.as_ref().is_some_and(|p| p.drifted);
+
+        Ok(DriftReport {
+            metric: self.config.metric,
+            threshold: self.config.threshold,
+            features,
+            predictions,
+            drifted,
+            checked_at: Utc::now(),
+            retrained: None,
+        })
+    }
+
+    /// Replace the baseline, e.g. after the model has been retrained
+    pub fn set_baseline(&mut self, baseline: DriftBaseline) {
+        self.baseline = baseline;
+    }
+
+    /// Detect drift and, if configured, retrain the churn model when found
+    ///
+    /// After a successful retrain the baseline is reset to the new training
+    /// distribution, so later checks measure drift against the data the
+    /// current model was trained on. The retrained model's predictions are not
+    /// known here, so prediction drift is skipped until the caller supplies
+    /// them through `set_baseline`.
+    pub async fn check_and_retrain(
+        &mut self,
+        trainer: &ModelTrainer,
+        features: &[Vec<f64>],
+        predictions: &[f64],
+        training_data: Vec<TrainingDataPoint>,
+    ) -> Result<DriftReport, MlPredictiveError> {
+        let mut report = self.detect(features, predictions)?;
+        if report.drifted && self.config.retrain_on_drift {
+            let baseline = DriftBaseline::from_training(&training_data, &[])?;
+            report.retrained = Some(trainer.train_churn_model(training_data).await?);
+            self.baseline = baseline;
+        }
+        Ok(report)
+    }
+
+    fn compare(
+        &self,
+        feature_index: Option<usize>,
+        baseline: &[f64],
+        recent: &[f64],
+    ) -> DistributionDrift {
+        let score = match self.config.metric {
+            DriftMetric::PopulationStabilityIndex => {
+                population_stability_index(baseline, recent, self.config.bins)
+            }
+            DriftMetric::KolmogorovSmirnov => kolmogorov_smirnov(baseline, recent),
+        };
+        DistributionDrift {
+            feature_index,
+            score,
+            drifted: score > self.config.threshold,
+        }
+    }
+}
+
+/// Split rows into sorted per-feature columns
+fn columns(rows: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, MlPredictiveError> {
+    let width = rows.first().map_or(0, Vec::len);
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(MlPredictiveError::InvalidInput(
+            "All feature vectors must have the same length".to_string(),
+        ));
+    }
+    Ok((0..width)
+        .map(|i| sorted(rows.iter().map(|row| row[i]).collect()))
+        .collect())
+}
+
+fn sorted(mut values: Vec<f64>) -> Vec<f64> {
+    values.retain(|v| v.is_finite());
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+/// Share of a sorted sample at or below `x`
+fn cdf(sorted: &[f64], x: f64) -> f64 {
+    sorted.partition_point(|v| *v <= x) as f64 / sorted.len() as f64
+}
+
+/// PSI between two sorted samples over the baseline's quantile bins
+fn population_stability_index(baseline: &[f64], recent: &[f64], bins: usize) -> f64 {
+    let mut edges: Vec<f64> = (1..bins)
+        .map(|i| baseline[(i * baseline.len() / bins).min(baseline.len() - 1)])
+        .collect();
+    edges.dedup();
+
+    let mut psi = 0.0;
+    let mut lower: Option<f64> = None;
+    for upper in edges.iter().copied().map(Some).chain(std::iter::once(None)) {
+        let share = |sample: &[f64]| {
+            let below_upper = upper.map_or(1.0, |u| cdf(sample, u));
+            let below_lower = lower.map_or(0.0, |l| cdf(sample, l));
+            (below_upper - below_lower).max(PSI_EPSILON)
+        };
+        let expected = share(baseline);
+        let actual = share(recent);
+        psi += (actual - expected) * (actual / expected).ln();
+        lower = upper;
+    }
+    psi
+}
+
+/// Largest gap between the empirical CDFs of two sorted samples
+fn kolmogorov_smirnov(baseline: &[f64], recent: &[f64]) -> f64 {
+    baseline
+        .iter()
+        .chain(recent)
+        .map(|x| (cdf(baseline, *x) - cdf(recent, *x)).abs())
+        .fold(0.0, f64::max)
+}
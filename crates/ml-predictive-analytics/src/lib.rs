@@ -2,17 +2,34 @@
 //!
 //! Provides ML-based predictive analytics capabilities including:
 //! - Demand forecasting
-//! - Churn prediction
+//! - Predictive auto-scaling recommendations
+//! - Churn prediction with drift detection and retraining
 //! - Revenue forecasting
 //! - Anomaly detection
-//! - Customer lifetime value prediction
+//! - Customer lifetime value prediction and cohort analysis
+//! - Forecast backtesting and accuracy evaluation
 
+pub mod backtest;
+pub mod cohort;
+pub mod drift;
 pub mod error;
 pub mod models;
 pub mod predictor;
+pub mod scaling;
 pub mod training;
 
+pub use backtest::{
+    backtest, BacktestConfig, BacktestReport, Forecaster, HorizonAccuracy, LinearTrendForecaster,
+    MeanForecaster,
+};
+pub use cohort::{
+    analyze_cohorts, CohortGrouping, CohortKey, CohortReport, CohortSummary, CustomerCohortRecord,
+};
+pub use drift::{
+    DistributionDrift, DriftBaseline, DriftConfig, DriftDetector, DriftMetric, DriftReport,
+};
 pub use error::MlPredictiveError;
 pub use models::*;
 pub use predictor::PredictiveAnalyticsService;
+pub use scaling::{recommend_scaling, CapacityModel, ScalingRecommendation};
 pub use training::ModelTrainer;
@@ -0,0 +1,110 @@
+//! Predictive Auto-scaling Recommendations
+//!
+//! Converts demand forecasts into instance counts for the autoscaler. Each
+//! recommendation carries instance counts for the forecast's lower bound, point
+//! estimate and upper bound so callers can pick how conservative to be.
+
+use crate::error::MlPredictiveError;
+use crate::models::DemandForecast;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How much demand a single instance can serve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityModel {
+    /// Demand units one instance handles at target utilization
+    pub demand_per_instance: f64,
+    pub min_instances: u32,
+    pub max_instances: Option<u32>,
+}
+
+/// Scaling recommendation derived from a demand forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingRecommendation {
+    pub product_id: Option<Uuid>,
+    pub service_id: Option<Uuid>,
+    pub forecast_date: DateTime<Utc>,
+    pub predicted_demand: f64,
+    pub confidence_interval_lower: f64,
+    pub confidence_interval_upper: f64,
+    /// Headroom applied on top of forecast demand (0.2 = 20%)
+    pub headroom: f64,
+    /// Instances for the lower confidence bound
+    pub minimum_instances: u32,
+    /// Instances for the point forecast
+    pub recommended_instances: u32,
+    /// Instances for the upper confidence bound
+    pub conservative_instances: u32,
+    /// Whether any count was limited by `max_instances`
+    pub capped: bool,
+    pub rationale: String,
+}
+
+/// Convert a demand forecast into capacity recommendations
+pub fn recommend_scaling(
+    forecast: &DemandForecast,
+    capacity: &CapacityModel,
+    headroom: f64,
+) -> Result<ScalingRecommendation, MlPredictiveError> {
+    if capacity.demand_per_instance <= 0.0 {
+        return Err(MlPredictiveError::InvalidInput(
+            "demand_per_instance must be positive".to_string(),
+        ));
+    }
+    if headroom < 0.0 {
+        return Err(MlPredictiveError::InvalidInput(
+            "headroom must not be negative".to_string(),
+        ));
+    }
+    if let Some(max) = capacity.max_instances {
+        if max < capacity.min_instances {
+            return Err(MlPredictiveError::InvalidInput(
+                "max_instances must not be below min_instances".to_string(),
+            ));
+        }
+    }
+
+    let mut capped = false;
+    let mut instances_for = |demand: f64| {
+        let needed = (demand.max(0.0) * (1.0 + headroom) / capacity.demand_per_instance).ceil();
+        let needed = (needed.min(u32::MAX as f64) as u32).max(capacity.min_instances);
+        match capacity.max_instances {
+            Some(max) if needed > max => {
+                capped = true;
+                max
+            }
+            _ => needed,
+        }
+    };
+
+    let minimum_instances = instances_for(forecast.confidence_interval_lower);
+    let recommended_instances = instances_for(forecast.predicted_demand);
+    let conservative_instances = instances_for(forecast.confidence_interval_upper);
+
+    let rationale = format!(
+        "Scale to {} instances for {} (predicted demand {:.1}, {:.0}% headroom); \
+         {}-{} instances across the confidence interval",
+        recommended_instances,
+        forecast.forecast_date.format("%Y-%m-%d %H:%M"),
+        forecast.predicted_demand,
+        headroom * 100.0,
+        minimum_instances,
+        conservative_instances
+    );
+
+    Ok(ScalingRecommendation {
+        product_id: forecast.product_id,
+        service_id: forecast.service_id,
+        forecast_date: forecast.forecast_date,
+        predicted_demand: forecast.predicted_demand,
+        confidence_interval_lower: forecast.confidence_interval_lower,
+        confidence_interval_upper: forecast.confidence_interval_upper,
+        headroom,
+        minimum_instances,
+        recommended_instances,
+        conservative_instances,
+        capped,
+        rationale,
+    })
+}
@@ -26,6 +26,7 @@ pub struct AppState {
     pub require_bearer: bool,
 }
 
+#[allow(clippy::result_large_err)]
 fn authorize(req: &HttpRequest, state: &AppState) -> Result<(), HttpResponse> {
     if !state.require_bearer {
         return Ok(());
@@ -112,6 +112,10 @@ pub fn sample_ar_vr_request(subscriber_id: impl Into<String>) -> PolicyRequest {
         application_id: Some("com.example.ar.stadium".into()),
         location: Some("stadium-42".into()),
         time_of_day: None,
+        roaming: None,
+        sni: None,
+        destination_port: None,
+        congestion_level: None,
     }
 }
 
@@ -136,6 +140,8 @@ pub fn seed_demo_subscriber(engine: &PcfEngine) {
             exceeded: false,
             throttled_bandwidth_kbps: None,
             last_update: Utc::now(),
+            rollover: None,
+            rolled_over: None,
         },
         active_policies: vec!["enterprise_ar".into()],
         zero_rated_services: vec![],
@@ -170,6 +170,53 @@ pub enum AnomalyAction {
     NotifySecurity,
 }
 
+/// External model suggesting adjustments to policy decisions
+#[async_trait]
+pub trait PolicyHintProvider: Send + Sync {
+    /// Suggest a hint for a policy request
+    async fn suggest(&self, request: &PolicyRequest) -> Result<PolicyHint, PcfError>;
+}
+
+/// Hint from an external model, merged into a policy decision
+///
+/// Hints never override hard policy constraints: gated requests ignore them,
+/// and priority is only raised when no quota throttling, roaming policy or
+/// congestion downgrade applies.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyHint {
+    /// Priority levels to raise (positive) or lower (negative) the QoS by
+    pub priority_bias: i8,
+    /// Whether the request's usage looks anomalous
+    pub anomalous_usage: bool,
+    /// Anomaly type (if anomalous usage was flagged)
+    pub anomaly_type: Option<AnomalyType>,
+}
+
+impl PolicyHint {
+    /// Apply the priority bias to `qos`, keeping priority within 1-15
+    ///
+    /// A positive bias is ignored when `may_raise` is false, and also when
+    /// anomalous usage is flagged.
+    pub fn apply(&self, qos: &mut crate::models::QoS, may_raise: bool) {
+        if self.priority_bias > 0 && (!may_raise || self.anomalous_usage) {
+            return;
+        }
+        let priority = (qos.priority as i16 + self.priority_bias as i16).clamp(1, 15);
+        qos.priority = priority as u8;
+    }
+}
+
+/// Hint provider that never suggests anything
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopPolicyHintProvider;
+
+#[async_trait]
+impl PolicyHintProvider for NoopPolicyHintProvider {
+    async fn suggest(&self, _request: &PolicyRequest) -> Result<PolicyHint, PcfError> {
+        Ok(PolicyHint::default())
+    }
+}
+
 /// Default AI service implementation (placeholder for ML integration)
 pub struct DefaultAIService;
 
@@ -1,15 +1,47 @@
 //! Charging Rules Module
 //!
-//! Handles online and offline charging decisions
+//! Handles online and offline charging decisions, including zero-rating by
+//! hostname, and writes offline charging CDR files
 
 use crate::error::PcfError;
-use crate::models::{ChargingMethod, ChargingRule, PolicyRequest, ZeroRatingRule};
+use crate::models::{
+    ChargingMethod, ChargingRule, PolicyRequest, RatingGroupMapping, ZeroRatingRule,
+};
+use crate::zero_rating::validate_hostname_pattern;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+pub mod cdr;
+
+pub use cdr::{CdrRecord, CdrWriter, CdrWriterConfig};
+
+/// Service-to-rating-group configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingGroupConfig {
+    /// Mappings keyed by application ID or service type (case-insensitive)
+    #[serde(default)]
+    pub services: HashMap<String, RatingGroupMapping>,
+    /// Mapping for services without one of their own
+    pub default_mapping: RatingGroupMapping,
+}
+
+impl Default for RatingGroupConfig {
+    fn default() -> Self {
+        Self {
+            services: HashMap::new(),
+            default_mapping: RatingGroupMapping {
+                rating_group: 1,
+                service_identifier: None,
+            },
+        }
+    }
+}
+
 /// Charging rules engine trait
 #[async_trait]
 pub trait ChargingRulesTrait: Send + Sync {
@@ -36,10 +68,14 @@ pub trait ChargingRulesTrait: Send + Sync {
 
 /// Charging rules engine implementation
 pub struct ChargingRulesEngine {
-    /// Zero-rating rules cache
+    /// Zero-rating rules keyed by lowercase hostname pattern
     zero_rating_rules: Arc<DashMap<String, ZeroRatingRule>>,
     /// Service-specific charging rules
     service_charging_rules: Arc<DashMap<String, ChargingRule>>,
+    /// Rating group mappings keyed by lowercase application ID or service type
+    rating_groups: Arc<DashMap<String, RatingGroupMapping>>,
+    /// Rating group mapping for unmapped services
+    default_rating_group: RatingGroupMapping,
 }
 
 impl ChargingRulesEngine {
@@ -48,6 +84,8 @@ impl ChargingRulesEngine {
         let engine = Self {
             zero_rating_rules: Arc::new(DashMap::new()),
             service_charging_rules: Arc::new(DashMap::new()),
+            rating_groups: Arc::new(DashMap::new()),
+            default_rating_group: RatingGroupConfig::default().default_mapping,
         };
 
         // Initialize default zero-rating rules
@@ -62,6 +100,7 @@ impl ChargingRulesEngine {
             rule_id: Uuid::new_v4(),
             service_identifier: "whatsapp.com".to_string(),
             plan_name: None, // Applies to all plans
+            port_range: None,
             active: true,
         };
         self.zero_rating_rules
@@ -72,6 +111,7 @@ impl ChargingRulesEngine {
             rule_id: Uuid::new_v4(),
             service_identifier: "facebook.com".to_string(),
             plan_name: Some("Social Media Plan".to_string()),
+            port_range: None,
             active: true,
         };
         self.zero_rating_rules
@@ -79,11 +119,50 @@ impl ChargingRulesEngine {
     }
 
     /// Add zero-rating rule
-    pub fn add_zero_rating_rule(&self, rule: ZeroRatingRule) {
+    ///
+    /// Rejects hostname patterns that are malformed or overly broad, such as
+    /// a bare `*`.
+    pub fn add_zero_rating_rule(&self, rule: ZeroRatingRule) -> Result<(), PcfError> {
+        validate_hostname_pattern(&rule.service_identifier)?;
         let service_id = rule.service_identifier.clone();
         self.zero_rating_rules
-            .insert(service_id.clone(), rule);
+            .insert(service_id.to_lowercase(), rule);
         info!("Added zero-rating rule for: {}", service_id);
+        Ok(())
+    }
+
+    /// Find the zero-rating rule covering a request
+    ///
+    /// The request's application ID and SNI are both matched against the
+    /// active rules for the subscriber's plan. When several rules match, the
+    /// most specific one wins.
+    pub fn match_zero_rating(
+        &self,
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+    ) -> Option<ZeroRatingRule> {
+        let hostnames: Vec<&str> = [request.application_id.as_deref(), request.sni.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if hostnames.is_empty() {
+            return None;
+        }
+
+        self.zero_rating_rules
+            .iter()
+            .map(|entry| entry.value().clone())
+            .filter(|rule| {
+                rule.active
+                    && rule
+                        .plan_name
+                        .as_ref()
+                        .is_none_or(|plan| plan == &subscriber_profile.plan_name)
+                    && hostnames
+                        .iter()
+                        .any(|host| rule.matches(host, request.destination_port))
+            })
+            .max_by_key(|rule| rule.specificity())
     }
 
     /// Add charging rule for a service
@@ -91,33 +170,60 @@ impl ChargingRulesEngine {
         self.service_charging_rules.insert(service_id, rule);
     }
 
+    /// Use a service-to-rating-group configuration
+    pub fn with_rating_group_config(mut self, config: RatingGroupConfig) -> Self {
+        for (service, mapping) in config.services {
+            self.add_rating_group_mapping(&service, mapping);
+        }
+        self.default_rating_group = config.default_mapping;
+        self
+    }
+
+    /// Add or update the rating group mapping of an application ID or service type
+    pub fn add_rating_group_mapping(&self, service: &str, mapping: RatingGroupMapping) {
+        self.rating_groups.insert(service.to_lowercase(), mapping);
+        info!(
+            "Mapped service {} to rating group {}",
+            service, mapping.rating_group
+        );
+    }
+
+    /// Rating group for a request's service
+    ///
+    /// The application ID's mapping takes precedence over the service type's;
+    /// services with neither map to the default rating group.
+    pub fn rating_group_for(
+        &self,
+        application_id: Option<&str>,
+        service_type: &str,
+    ) -> RatingGroupMapping {
+        application_id
+            .and_then(|app| self.rating_groups.get(&app.to_lowercase()))
+            .or_else(|| self.rating_groups.get(&service_type.to_lowercase()))
+            .map(|m| *m.value())
+            .unwrap_or(self.default_rating_group)
+    }
+
     /// Check if service is zero-rated for subscriber
     fn check_zero_rating(
         &self,
-        service_identifier: Option<&str>,
+        request: &PolicyRequest,
         subscriber_profile: &crate::models::SubscriberProfile,
     ) -> bool {
         // Check subscriber's zero-rated services list
-        if let Some(service_id) = service_identifier {
-            if subscriber_profile.zero_rated_services.contains(&service_id.to_string()) {
+        if let Some(service_id) = request.application_id.as_deref() {
+            if subscriber_profile
+                .zero_rated_services
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(service_id))
+            {
                 return true;
             }
-
-            // Check global zero-rating rules
-            if let Some(rule) = self.zero_rating_rules.get(service_id) {
-                let rule = rule.value();
-                if rule.active {
-                    // If rule is plan-specific, check if subscriber's plan matches
-                    if let Some(ref plan_name) = rule.plan_name {
-                        return plan_name == &subscriber_profile.plan_name;
-                    }
-                    // If not plan-specific, applies to all
-                    return true;
-                }
-            }
         }
 
-        false
+        // Check global zero-rating rules
+        self.match_zero_rating(request, subscriber_profile)
+            .is_some()
     }
 
     /// Create default charging rule
@@ -135,14 +241,14 @@ impl ChargingRulesEngine {
         ChargingRule {
             rule_id: format!("default_{}", request.subscriber_id),
             service_identifier: request.application_id.clone(),
-            rating_group: Some(1), // Default rating group
-            zero_rating: self.check_zero_rating(
-                request.application_id.as_deref(),
-                subscriber_profile,
+            rating_group: Some(
+                self.rating_group_for(request.application_id.as_deref(), &request.service_type)
+                    .rating_group,
             ),
+            zero_rating: self.check_zero_rating(request, subscriber_profile),
             charging_method,
             metering_method: "volume".to_string(), // Volume-based by default
-            unit_cost: None, // Would be determined by rating engine
+            unit_cost: None,                       // Would be determined by rating engine
         }
     }
 }
@@ -164,7 +270,15 @@ impl ChargingRulesTrait for ChargingRulesEngine {
         // Check for service-specific charging rule
         if let Some(service_id) = &request.application_id {
             if let Some(rule) = self.service_charging_rules.get(service_id) {
-                rules.push(rule.value().clone());
+                let mut rule = rule.value().clone();
+                // Usage must always be reported under some rating group
+                if rule.rating_group.is_none() {
+                    rule.rating_group = Some(
+                        self.rating_group_for(Some(service_id), &request.service_type)
+                            .rating_group,
+                    );
+                }
+                rules.push(rule);
                 debug!("Found service-specific charging rule for: {}", service_id);
             }
         }
@@ -189,10 +303,7 @@ impl ChargingRulesTrait for ChargingRulesEngine {
         request: &PolicyRequest,
         subscriber_profile: &crate::models::SubscriberProfile,
     ) -> Result<bool, PcfError> {
-        let is_zero_rated = self.check_zero_rating(
-            request.application_id.as_deref(),
-            subscriber_profile,
-        );
+        let is_zero_rated = self.check_zero_rating(request, subscriber_profile);
 
         if is_zero_rated {
             info!(
@@ -0,0 +1,498 @@
+//! Gz Offline Charging CDR Files
+//!
+//! Buffers rated Charging Data Records (CDRs) built from Gz offline charging
+//! messages and writes them to files for reconciliation with the billing
+//! system.
+//!
+//! Files are JSON Lines, one [`CdrRecord`] per line. Each record goes to the
+//! file of the time window its record time falls into, named
+//! `<prefix>_<window start as YYYYMMDDTHHMMSSZ>.jsonl`. Windows are aligned to
+//! the Unix epoch in UTC, so the same record always lands in the same file
+//! and files rotate as records enter a new window.
+//!
+//! Buffered records are written when the buffer is full, on every flush
+//! interval while the writer is started, when it is stopped, and when it is
+//! dropped. Records that fail to be written stay buffered for the next flush.
+//! A record may be written twice if a flush fails part way through a file, so
+//! consumers should deduplicate by record sequence number.
+
+use crate::diameter::{GzMessage, GzRecordType};
+use crate::error::PcfError;
+use crate::models::ChargingRule;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Default length of the time window covered by one CDR file
+const DEFAULT_ROTATION_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Default interval between background flushes
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Default number of buffered records that triggers a flush
+const DEFAULT_MAX_BUFFERED_RECORDS: usize = 1000;
+
+/// Octets per MB when rating volume
+const OCTETS_PER_MB: f64 = 1_000_000.0;
+
+/// Rated Charging Data Record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CdrRecord {
+    /// Position in the writer's output, increasing by one per record
+    pub record_sequence_number: u64,
+    /// Record type
+    pub record_type: GzRecordType,
+    /// Session ID
+    pub session_id: String,
+    /// Subscriber ID
+    pub subscriber_id: String,
+    /// Time the usage was reported
+    pub record_time: DateTime<Utc>,
+    /// Charging rule the usage was rated with
+    pub charging_rule_id: Option<String>,
+    /// Rating group the usage is reported under
+    pub rating_group: Option<u32>,
+    /// Upload volume in octets
+    pub input_octets: u64,
+    /// Download volume in octets
+    pub output_octets: u64,
+    /// Total volume in octets
+    pub total_octets: u64,
+    /// Usage time in seconds
+    pub duration_seconds: Option<u64>,
+    /// Number of events
+    pub events: Option<u32>,
+    /// Charge for the usage, if a charging rule with a unit cost rated it
+    pub charged_amount: Option<f64>,
+}
+
+impl CdrRecord {
+    /// Build a record from a Gz message, rated with `charging_rule` if given
+    ///
+    /// Volume is charged per MB, time per minute and events per event, as the
+    /// rule's metering method says. Zero-rated usage is charged nothing. The
+    /// sequence number is assigned by the [`CdrWriter`].
+    pub fn from_gz_message(message: &GzMessage, charging_rule: Option<&ChargingRule>) -> Self {
+        let units = &message.service_units;
+        let input_octets = units.input_octets.unwrap_or(0);
+        let output_octets = units.output_octets.unwrap_or(0);
+        let total_octets = units.total_octets.unwrap_or(input_octets + output_octets);
+
+        let charged_amount = charging_rule.and_then(|rule| {
+            if rule.zero_rating {
+                return Some(0.0);
+            }
+            let unit_cost = rule.unit_cost?;
+            let units = match rule.metering_method.to_lowercase().as_str() {
+                "volume" => total_octets as f64 / OCTETS_PER_MB,
+                "time" => units.time? as f64 / 60.0,
+                "event" => units.events? as f64,
+                _ => return None,
+            };
+            Some(units * unit_cost)
+        });
+
+        Self {
+            record_sequence_number: 0,
+            record_type: message.record_type,
+            session_id: message.session_id.clone(),
+            subscriber_id: message.subscriber_id.clone(),
+            record_time: message.timestamp,
+            charging_rule_id: charging_rule.map(|rule| rule.rule_id.clone()),
+            rating_group: charging_rule.and_then(|rule| rule.rating_group),
+            input_octets,
+            output_octets,
+            total_octets,
+            duration_seconds: units.time,
+            events: units.events,
+            charged_amount,
+        }
+    }
+}
+
+/// CDR writer configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdrWriterConfig {
+    /// Directory the CDR files are written to
+    pub directory: PathBuf,
+    /// File name prefix
+    pub file_prefix: String,
+    /// Length of the time window covered by one file (whole seconds)
+    pub rotation_interval: Duration,
+    /// Interval between background flushes while the writer is started
+    pub flush_interval: Duration,
+    /// Number of buffered records that triggers a flush
+    pub max_buffered_records: usize,
+}
+
+impl CdrWriterConfig {
+    /// Configuration writing to `directory` with default settings
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            file_prefix: "gz_cdr".to_string(),
+            rotation_interval: DEFAULT_ROTATION_INTERVAL,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            max_buffered_records: DEFAULT_MAX_BUFFERED_RECORDS,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PcfError> {
+        let invalid = |reason: &str| {
+            Err(PcfError::ConfigurationError(format!(
+                "Invalid CDR writer configuration: {}",
+                reason
+            )))
+        };
+
+        if self.file_prefix.is_empty()
+            || self
+                .file_prefix
+                .chars()
+                .any(|c| std::path::is_separator(c) || c == '.')
+        {
+            return invalid("file prefix must be non-empty and contain no '.' or path separator");
+        }
+        if self.rotation_interval.as_secs() == 0 {
+            return invalid("rotation interval must be at least one second");
+        }
+        if self.flush_interval.is_zero() {
+            return invalid("flush interval must be above zero");
+        }
+        Ok(())
+    }
+}
+
+/// Buffered records and the files they are written to
+struct CdrSink {
+    config: CdrWriterConfig,
+    buffer: Mutex<Vec<CdrRecord>>,
+    next_sequence: AtomicU64,
+}
+
+impl CdrSink {
+    /// Path of the file covering the window `record_time` falls into
+    fn file_path(&self, record_time: DateTime<Utc>) -> PathBuf {
+        let interval = self.config.rotation_interval.as_secs() as i64;
+        let seconds = record_time.timestamp();
+        let window_start = DateTime::from_timestamp(seconds - seconds.rem_euclid(interval), 0)
+            .unwrap_or(record_time);
+        self.config.directory.join(format!(
+            "{}_{}.jsonl",
+            self.config.file_prefix,
+            window_start.format("%Y%m%dT%H%M%SZ")
+        ))
+    }
+
+    /// Write all buffered records, returning how many were written
+    fn flush(&self) -> Result<usize, PcfError> {
+        let mut buffer = self.buffer.lock().expect("CDR buffer lock poisoned");
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let mut files: BTreeMap<PathBuf, Vec<CdrRecord>> = BTreeMap::new();
+        for record in buffer.drain(..) {
+            files
+                .entry(self.file_path(record.record_time))
+                .or_default()
+                .push(record);
+        }
+
+        let mut written = 0;
+        let mut files = files.into_iter();
+        while let Some((path, records)) = files.next() {
+            if let Err(e) = Self::append(&path, &records) {
+                // Keep everything not yet written for the next flush
+                buffer.extend(records);
+                buffer.extend(files.flat_map(|(_, records)| records));
+                buffer.sort_by_key(|record| record.record_sequence_number);
+                return Err(PcfError::CdrWriteError(format!(
+                    "Failed to write CDRs to {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+            written += records.len();
+        }
+
+        debug!("Flushed {} CDRs", written);
+        Ok(written)
+    }
+
+    fn append(path: &Path, records: &[CdrRecord]) -> std::io::Result<()> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(&lines)?;
+        file.sync_data()
+    }
+}
+
+/// Writes Gz offline charging CDRs to time-windowed files
+pub struct CdrWriter {
+    sink: Arc<CdrSink>,
+    /// Background flush task while started
+    flusher: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CdrWriter {
+    /// Create a writer, creating the CDR directory if needed
+    pub fn new(config: CdrWriterConfig) -> Result<Self, PcfError> {
+        config.validate()?;
+        fs::create_dir_all(&config.directory).map_err(|e| {
+            PcfError::ConfigurationError(format!(
+                "Cannot create CDR directory {}: {}",
+                config.directory.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            sink: Arc::new(CdrSink {
+                config,
+                buffer: Mutex::new(Vec::new()),
+                next_sequence: AtomicU64::new(1),
+            }),
+            flusher: Mutex::new(None),
+        })
+    }
+
+    /// Start flushing in the background every flush interval
+    ///
+    /// Requires a Tokio runtime. Does nothing if already started.
+    pub fn start(&self) -> Result<(), PcfError> {
+        let mut flusher = self.flusher.lock().expect("CDR flusher lock poisoned");
+        if flusher.is_some() {
+            return Ok(());
+        }
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            PcfError::ConfigurationError("CDR writer must be started in a Tokio runtime".into())
+        })?;
+
+        let sink = Arc::clone(&self.sink);
+        *flusher = Some(runtime.spawn(async move {
+            let mut interval = tokio::time::interval(sink.config.flush_interval);
+            loop {
+                interval.tick().await;
+                let sink = Arc::clone(&sink);
+                match tokio::task::spawn_blocking(move || sink.flush()).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => error!("Background CDR flush failed: {}", e),
+                    Err(e) => error!("Background CDR flush task failed: {}", e),
+                }
+            }
+        }));
+        info!(
+            "Started CDR writer in {}",
+            self.sink.config.directory.display()
+        );
+        Ok(())
+    }
+
+    /// Stop background flushing and write all buffered records
+    pub fn stop(&self) -> Result<usize, PcfError> {
+        if let Some(flusher) = self
+            .flusher
+            .lock()
+            .expect("CDR flusher lock poisoned")
+            .take()
+        {
+            flusher.abort();
+            info!("Stopped CDR writer");
+        }
+        self.sink.flush()
+    }
+
+    /// Buffer a record, returning its assigned sequence number
+    ///
+    /// Flushes when the buffer reaches the configured size.
+    pub fn write(&self, mut record: CdrRecord) -> Result<u64, PcfError> {
+        let sequence = {
+            let mut buffer = self.sink.buffer.lock().expect("CDR buffer lock poisoned");
+            let sequence = self.sink.next_sequence.fetch_add(1, Ordering::Relaxed);
+            record.record_sequence_number = sequence;
+            buffer.push(record);
+            if buffer.len() < self.sink.config.max_buffered_records {
+                return Ok(sequence);
+            }
+            sequence
+        };
+
+        self.sink.flush()?;
+        Ok(sequence)
+    }
+
+    /// Rate a Gz message and buffer its record
+    pub fn write_gz_message(
+        &self,
+        message: &GzMessage,
+        charging_rule: Option<&ChargingRule>,
+    ) -> Result<u64, PcfError> {
+        self.write(CdrRecord::from_gz_message(message, charging_rule))
+    }
+
+    /// Write all buffered records, returning how many were written
+    pub fn flush(&self) -> Result<usize, PcfError> {
+        self.sink.flush()
+    }
+
+    /// Number of records waiting to be written
+    pub fn buffered_records(&self) -> usize {
+        self.sink
+            .buffer
+            .lock()
+            .expect("CDR buffer lock poisoned")
+            .len()
+    }
+}
+
+impl Drop for CdrWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            error!("Lost {} CDRs on shutdown: {}", self.buffered_records(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diameter::ServiceUnits;
+    use crate::models::ChargingMethod;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("pcf-cdr-{}", Uuid::new_v4()))
+    }
+
+    fn message(session_id: &str, timestamp: DateTime<Utc>) -> GzMessage {
+        GzMessage {
+            session_id: session_id.to_string(),
+            subscriber_id: "1234567890".to_string(),
+            record_type: GzRecordType::Interim,
+            service_units: ServiceUnits {
+                total_octets: None,
+                input_octets: Some(500_000),
+                output_octets: Some(1_500_000),
+                time: Some(120),
+                events: None,
+            },
+            timestamp,
+        }
+    }
+
+    fn read_records(path: &PathBuf) -> Vec<CdrRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_records_are_rated_and_written_to_window_files() {
+        let dir = temp_dir();
+        let writer = CdrWriter::new(CdrWriterConfig::new(&dir)).unwrap();
+        let rule = ChargingRule {
+            rule_id: "video".to_string(),
+            service_identifier: None,
+            rating_group: Some(10),
+            zero_rating: false,
+            charging_method: ChargingMethod::Offline,
+            metering_method: "volume".to_string(),
+            unit_cost: Some(0.5),
+        };
+
+        let first = Utc.with_ymd_and_hms(2026, 3, 1, 10, 14, 59).unwrap();
+        let second = Utc.with_ymd_and_hms(2026, 3, 1, 10, 15, 0).unwrap();
+        writer
+            .write_gz_message(&message("a", first), Some(&rule))
+            .unwrap();
+        writer
+            .write_gz_message(&message("b", second), None)
+            .unwrap();
+        assert_eq!(writer.flush().unwrap(), 2);
+        assert_eq!(writer.buffered_records(), 0);
+
+        let records = read_records(&dir.join("gz_cdr_20260301T100000Z.jsonl"));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].record_sequence_number, 1);
+        assert_eq!(records[0].total_octets, 2_000_000);
+        assert_eq!(records[0].rating_group, Some(10));
+        assert_eq!(records[0].charged_amount, Some(1.0));
+
+        let records = read_records(&dir.join("gz_cdr_20260301T101500Z.jsonl"));
+        assert_eq!(records[0].record_sequence_number, 2);
+        assert_eq!(records[0].charged_amount, None);
+
+        drop(writer);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_drop_flushes_buffered_records() {
+        let dir = temp_dir();
+        let timestamp = Utc.with_ymd_and_hms(2026, 3, 1, 23, 59, 0).unwrap();
+        {
+            let writer = CdrWriter::new(CdrWriterConfig::new(&dir)).unwrap();
+            for session in ["a", "b", "c"] {
+                writer
+                    .write_gz_message(&message(session, timestamp), None)
+                    .unwrap();
+            }
+            assert_eq!(writer.buffered_records(), 3);
+        }
+
+        let records = read_records(&dir.join("gz_cdr_20260301T234500Z.jsonl"));
+        let sessions: Vec<&str> = records.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(sessions, ["a", "b", "c"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_background_flush_and_stop() {
+        let dir = temp_dir();
+        let mut config = CdrWriterConfig::new(&dir);
+        config.flush_interval = Duration::from_millis(10);
+        let writer = CdrWriter::new(config).unwrap();
+        writer.start().unwrap();
+
+        let timestamp = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        writer
+            .write_gz_message(&message("a", timestamp), None)
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(writer.buffered_records(), 0);
+
+        writer
+            .write_gz_message(&message("b", timestamp), None)
+            .unwrap();
+        writer.stop().unwrap();
+        let records = read_records(&dir.join("gz_cdr_20260301T000000Z.jsonl"));
+        assert_eq!(records.len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        let mut config = CdrWriterConfig::new(temp_dir());
+        config.file_prefix = "../cdr".to_string();
+        assert!(CdrWriter::new(config.clone()).is_err());
+
+        config.file_prefix = "cdr".to_string();
+        config.rotation_interval = Duration::from_millis(500);
+        assert!(CdrWriter::new(config).is_err());
+    }
+}
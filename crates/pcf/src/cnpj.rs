@@ -0,0 +1,228 @@
+//! CNPJ (Cadastro Nacional da Pessoa Jurídica) - Brazilian Business Tax ID
+//!
+//! CNPJ is a 14-digit number used to identify companies in Brazil: an 8-digit
+//! company root, a 4-digit branch number and two check digits.
+//! This module provides validation and formatting utilities for CNPJ numbers.
+
+use crate::error::PcfError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Weights for the first check digit
+const FIRST_CHECK_WEIGHTS: [u32; 12] = [5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+/// Weights for the second check digit
+const SECOND_CHECK_WEIGHTS: [u32; 13] = [6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2];
+
+/// CNPJ (Brazilian Business Tax Identification Number)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Cnpj {
+    /// The CNPJ number as a string (stored without formatting)
+    number: String,
+}
+
+impl Cnpj {
+    /// Create a new CNPJ from a string
+    ///
+    /// The input can be formatted (XX.XXX.XXX/XXXX-XX) or unformatted (XXXXXXXXXXXXXX)
+    ///
+    /// # Example
+    /// ```
+    /// use bss_oss_pcf::cnpj::Cnpj;
+    ///
+    /// let cnpj = Cnpj::new("11.222.333/0001-81").unwrap();
+    /// assert_eq!(cnpj.as_str(), "11222333000181");
+    /// ```
+    pub fn new(input: &str) -> Result<Self, PcfError> {
+        let cleaned = Self::clean(input);
+
+        if let Some(c) = cleaned.chars().find(|c| !c.is_ascii_digit()) {
+            return Err(PcfError::InvalidSubscriberData(format!(
+                "Invalid CNPJ format: {} (unexpected character '{}')",
+                input, c
+            )));
+        }
+
+        if cleaned.len() != 14 {
+            return Err(PcfError::InvalidSubscriberData(format!(
+                "Invalid CNPJ format: {} (expected 14 digits, got {})",
+                input,
+                cleaned.len()
+            )));
+        }
+
+        // Repeated digits are never issued, though all zeros passes the checksum
+        if cleaned
+            .chars()
+            .all(|c| c == cleaned.chars().next().unwrap())
+        {
+            return Err(PcfError::InvalidSubscriberData(format!(
+                "Invalid CNPJ: {} (all digits are the same)",
+                input
+            )));
+        }
+
+        if !Self::validate_checksum(&cleaned) {
+            return Err(PcfError::InvalidSubscriberData(format!(
+                "Invalid CNPJ checksum: {}",
+                input
+            )));
+        }
+
+        Ok(Self { number: cleaned })
+    }
+
+    /// Check if a string is a valid CNPJ
+    pub fn is_valid(input: &str) -> bool {
+        Self::new(input).is_ok()
+    }
+
+    /// Get CNPJ as string (unformatted)
+    pub fn as_str(&self) -> &str {
+        &self.number
+    }
+
+    /// Get CNPJ as formatted string (XX.XXX.XXX/XXXX-XX)
+    pub fn formatted(&self) -> String {
+        format!(
+            "{}.{}.{}/{}-{}",
+            &self.number[0..2],
+            &self.number[2..5],
+            &self.number[5..8],
+            &self.number[8..12],
+            &self.number[12..14]
+        )
+    }
+
+    /// Clean CNPJ string (remove formatting characters)
+    fn clean(input: &str) -> String {
+        input
+            .trim()
+            .chars()
+            .filter(|c| !matches!(c, '.' | '/' | '-'))
+            .collect()
+    }
+
+    /// Calculate a check digit from the preceding digits
+    fn check_digit(digits: &[u32], weights: &[u32]) -> u32 {
+        let sum: u32 = digits.iter().zip(weights).map(|(d, w)| d * w).sum();
+        let remainder = sum % 11;
+        if remainder < 2 {
+            0
+        } else {
+            11 - remainder
+        }
+    }
+
+    /// Validate CNPJ checksum digits
+    ///
+    /// CNPJ validation algorithm:
+    /// 1. Calculate first check digit using first 12 digits
+    /// 2. Calculate second check digit using first 13 digits
+    /// 3. Compare with provided check digits
+    fn validate_checksum(cnpj: &str) -> bool {
+        let digits: Vec<u32> = cnpj.chars().map(|c| c.to_digit(10).unwrap()).collect();
+
+        Self::check_digit(&digits[..12], &FIRST_CHECK_WEIGHTS) == digits[12]
+            && Self::check_digit(&digits[..13], &SECOND_CHECK_WEIGHTS) == digits[13]
+    }
+}
+
+impl fmt::Display for Cnpj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.formatted())
+    }
+}
+
+impl TryFrom<&str> for Cnpj {
+    type Error = PcfError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Cnpj::new(value)
+    }
+}
+
+impl TryFrom<String> for Cnpj {
+    type Error = PcfError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Cnpj::new(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_cnpj() {
+        let valid_cnpjs = vec![
+            "11.222.333/0001-81",
+            "11222333000181",
+            "45.997.418/0001-53",
+            "45997418000153",
+        ];
+
+        for cnpj_str in valid_cnpjs {
+            assert!(
+                Cnpj::is_valid(cnpj_str),
+                "CNPJ {} should be valid",
+                cnpj_str
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_cnpj_format() {
+        let invalid_cnpjs = vec![
+            "1122233300018",      // Too short
+            "112223330001811",    // Too long
+            "11.222.333/0001",    // Missing check digits
+            "11.222.333/0001-8A", // Non-numeric
+            "11 222 333 0001 81", // Unsupported separator
+        ];
+
+        for cnpj_str in invalid_cnpjs {
+            let err = Cnpj::new(cnpj_str).unwrap_err();
+            assert!(
+                err.to_string().contains("Invalid CNPJ format"),
+                "CNPJ {} should have an invalid format, got: {}",
+                cnpj_str,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_cnpj_checksum() {
+        let invalid_cnpjs = vec![
+            "11.222.333/0001-80", // Wrong second check digit
+            "11.222.333/0001-91", // Wrong first check digit
+            "11.222.333/0002-81", // Altered branch number
+        ];
+
+        for cnpj_str in invalid_cnpjs {
+            assert!(
+                !Cnpj::is_valid(cnpj_str),
+                "CNPJ {} should be invalid",
+                cnpj_str
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_same_digits_rejected() {
+        for digit in 0..=9 {
+            let cnpj_str = digit.to_string().repeat(14);
+            let err = Cnpj::new(&cnpj_str).unwrap_err();
+            assert!(err.to_string().contains("all digits are the same"));
+        }
+    }
+
+    #[test]
+    fn test_cnpj_formatting() {
+        let cnpj = Cnpj::new("11222333000181").unwrap();
+        assert_eq!(cnpj.formatted(), "11.222.333/0001-81");
+        assert_eq!(cnpj.as_str(), "11222333000181");
+        assert_eq!(format!("{}", cnpj), "11.222.333/0001-81");
+    }
+}
@@ -0,0 +1,219 @@
+//! Congestion-Aware QoS Downgrade
+//!
+//! Applies congestion rules to the QoS of a policy decision using the
+//! congestion level reported with the request. A rule applies from its minimum
+//! congestion level upwards and either lowers the priority or caps the
+//! bandwidth. Downgrades never raise the QoS.
+//!
+//! When several rules match, the one leaving the least bandwidth, then the
+//! lowest priority, wins, with ties broken by rule ID, so identical requests
+//! at the same congestion level always get identical QoS.
+
+use crate::error::PcfError;
+use crate::models::{CongestionAction, CongestionRule, PolicyRequest, QoS};
+
+/// Check that a congestion rule actually downgrades something
+pub fn validate_congestion_rule(rule: &CongestionRule) -> Result<(), PcfError> {
+    let invalid = |reason: &str| {
+        Err(PcfError::ConfigurationError(format!(
+            "Invalid congestion rule '{}': {}",
+            rule.rule_name, reason
+        )))
+    };
+
+    match rule.action {
+        CongestionAction::DowngradePriority { levels: 0 } => {
+            invalid("priority downgrade must be at least one level")
+        }
+        CongestionAction::CapBandwidth {
+            max_download_kbps,
+            max_upload_kbps,
+        } if max_download_kbps == 0 || max_upload_kbps == 0 => {
+            invalid("bandwidth cap must be above zero, use gating to block traffic")
+        }
+        _ => Ok(()),
+    }
+}
+
+impl CongestionRule {
+    /// Check if the rule applies to a request from a subscriber on `plan_name`
+    ///
+    /// A request without a congestion level never matches.
+    pub fn matches(&self, request: &PolicyRequest, plan_name: &str) -> bool {
+        self.active
+            && request
+                .congestion_level
+                .is_some_and(|level| level >= self.min_congestion_level)
+            && self.plan_name.as_deref().is_none_or(|p| p == plan_name)
+            && self
+                .service_type
+                .as_deref()
+                .is_none_or(|s| s == request.service_type)
+    }
+
+    /// QoS after applying the rule's downgrade
+    pub fn apply(&self, qos: &QoS) -> QoS {
+        let mut qos = qos.clone();
+        match self.action {
+            CongestionAction::DowngradePriority { levels } => {
+                qos.priority = qos.priority.saturating_sub(levels).max(1);
+            }
+            CongestionAction::CapBandwidth {
+                max_download_kbps,
+                max_upload_kbps,
+            } => {
+                qos.max_download_bandwidth_kbps =
+                    qos.max_download_bandwidth_kbps.min(max_download_kbps);
+                qos.max_upload_bandwidth_kbps = qos.max_upload_bandwidth_kbps.min(max_upload_kbps);
+                qos.mbr_download_kbps = qos.mbr_download_kbps.map(|m| m.min(max_download_kbps));
+                qos.mbr_upload_kbps = qos.mbr_upload_kbps.map(|m| m.min(max_upload_kbps));
+            }
+        }
+        qos
+    }
+}
+
+/// Apply the most aggressive matching rule to `qos`, returning the rule and
+/// the downgraded QoS
+pub fn most_aggressive_downgrade<'a>(
+    rules: impl IntoIterator<Item = &'a CongestionRule>,
+    request: &PolicyRequest,
+    plan_name: &str,
+    qos: &QoS,
+) -> Option<(&'a CongestionRule, QoS)> {
+    rules
+        .into_iter()
+        .filter(|rule| rule.matches(request, plan_name))
+        .map(|rule| (rule, rule.apply(qos)))
+        .min_by_key(|(rule, qos)| {
+            (
+                qos.max_download_bandwidth_kbps,
+                qos.max_upload_bandwidth_kbps,
+                qos.priority,
+                rule.rule_id,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CongestionLevel, NetworkGeneration};
+    use crate::pcf_engine::{PcfEngine, PcfEngineTrait};
+    use uuid::Uuid;
+
+    fn rule(min_congestion_level: CongestionLevel, action: CongestionAction) -> CongestionRule {
+        CongestionRule {
+            rule_id: Uuid::new_v4(),
+            rule_name: format!("{:?}", action),
+            min_congestion_level,
+            plan_name: None,
+            service_type: None,
+            action,
+            active: true,
+        }
+    }
+
+    fn request(congestion_level: Option<CongestionLevel>) -> PolicyRequest {
+        #[allow(deprecated)]
+        PolicyRequest {
+            subscriber_id: "1234567890".to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            network_generation: NetworkGeneration::FourG,
+            apn: "internet".to_string(),
+            service_type: "file_download".to_string(),
+            application_id: None,
+            location: None,
+            time_of_day: None,
+            roaming: None,
+            sni: None,
+            destination_port: None,
+            congestion_level,
+        }
+    }
+
+    #[test]
+    fn test_rule_applies_at_and_above_threshold() {
+        let rule = rule(
+            CongestionLevel::High,
+            CongestionAction::DowngradePriority { levels: 10 },
+        );
+
+        assert!(!rule.matches(&request(None), "Premium Unlimited"));
+        assert!(!rule.matches(&request(Some(CongestionLevel::Medium)), "Premium Unlimited"));
+        assert!(rule.matches(&request(Some(CongestionLevel::High)), "Premium Unlimited"));
+        assert!(rule.matches(
+            &request(Some(CongestionLevel::Critical)),
+            "Premium Unlimited"
+        ));
+        assert_eq!(rule.apply(&QoS::default()).priority, 1);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let no_op = rule(
+            CongestionLevel::Low,
+            CongestionAction::DowngradePriority { levels: 0 },
+        );
+        let blocking = rule(
+            CongestionLevel::Low,
+            CongestionAction::CapBandwidth {
+                max_download_kbps: 0,
+                max_upload_kbps: 100,
+            },
+        );
+
+        assert!(validate_congestion_rule(&no_op).is_err());
+        assert!(validate_congestion_rule(&blocking).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_policy_applies_most_aggressive_downgrade() {
+        let engine = PcfEngine::new();
+        let downgrade = rule(
+            CongestionLevel::Medium,
+            CongestionAction::DowngradePriority { levels: 2 },
+        );
+        let soft_cap = rule(
+            CongestionLevel::High,
+            CongestionAction::CapBandwidth {
+                max_download_kbps: 20_000,
+                max_upload_kbps: 10_000,
+            },
+        );
+        let hard_cap = rule(
+            CongestionLevel::Critical,
+            CongestionAction::CapBandwidth {
+                max_download_kbps: 2_000,
+                max_upload_kbps: 1_000,
+            },
+        );
+        for rule in [&downgrade, &soft_cap, &hard_cap] {
+            engine.add_congestion_rule(rule.clone()).unwrap();
+        }
+
+        let decision = engine.evaluate_policy(&request(None)).await.unwrap();
+        assert!(decision.congestion_rule_id.is_none());
+        let baseline = decision.qos;
+
+        let decision = engine
+            .evaluate_policy(&request(Some(CongestionLevel::Medium)))
+            .await
+            .unwrap();
+        assert_eq!(decision.congestion_rule_id, Some(downgrade.rule_id));
+        assert_eq!(decision.qos.priority, baseline.priority - 2);
+
+        for _ in 0..3 {
+            let decision = engine
+                .evaluate_policy(&request(Some(CongestionLevel::Critical)))
+                .await
+                .unwrap();
+            assert_eq!(decision.congestion_rule_id, Some(hard_cap.rule_id));
+            assert_eq!(decision.qos.max_download_bandwidth_kbps, 2_000);
+            assert_eq!(decision.qos.max_upload_bandwidth_kbps, 1_000);
+            assert_eq!(decision.qos.priority, baseline.priority);
+        }
+    }
+}
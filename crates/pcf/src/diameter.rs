@@ -8,10 +8,14 @@
 //! - **Gy**: Online charging between PCEF and OCS (Online Charging System)
 //! - **Gz**: Offline charging between PCEF and CGF (Charging Gateway Function)
 
+use crate::charging::{CdrWriter, ChargingRulesEngine};
 use crate::error::PcfError;
 use crate::models::{PolicyDecision, PolicyRequest};
-use log::{debug, info};
+use crate::quota::QuotaManagerTrait;
+use dashmap::DashMap;
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Diameter application IDs
 pub mod application_ids {
@@ -29,9 +33,9 @@ pub mod command_codes {
     pub const CCR: u32 = 272;
     /// Credit Control Answer (CCA) - used in Gy
     pub const CCA: u32 = 272;
-    /// Re-Auth Request (RAR) - used in Gx
+    /// Re-Auth Request (RAR) - used in Gx and Gy
     pub const RAR: u32 = 258;
-    /// Re-Auth Answer (RAA) - used in Gx
+    /// Re-Auth Answer (RAA) - used in Gx and Gy
     pub const RAA: u32 = 258;
     /// Credit Control Request Initial (CCR-I)
     pub const CCR_INITIAL: u32 = 1;
@@ -75,6 +79,101 @@ pub mod avp_codes {
     pub const ORIGIN_HOST: u32 = 264;
     /// Origin-Realm
     pub const ORIGIN_REALM: u32 = 296;
+    /// Destination-Realm
+    pub const DESTINATION_REALM: u32 = 283;
+    /// Re-Auth-Request-Type
+    pub const RE_AUTH_REQUEST_TYPE: u32 = 285;
+    /// Multiple-Services-Credit-Control
+    pub const MULTIPLE_SERVICES_CREDIT_CONTROL: u32 = 456;
+    /// Requested-Service-Unit
+    pub const REQUESTED_SERVICE_UNIT: u32 = 437;
+    /// Rating-Group
+    pub const RATING_GROUP: u32 = 432;
+    /// Service-Identifier
+    pub const SERVICE_IDENTIFIER: u32 = 439;
+}
+
+/// Diameter AVP flags
+pub mod avp_flags {
+    /// Vendor-Specific bit
+    pub const VENDOR_SPECIFIC: u8 = 0x80;
+    /// Mandatory bit
+    pub const MANDATORY: u8 = 0x40;
+}
+
+/// Re-Auth-Request-Type values
+pub mod re_auth_request_types {
+    /// AUTHORIZE_ONLY
+    pub const AUTHORIZE_ONLY: u32 = 0;
+    /// AUTHORIZE_AUTHENTICATE
+    pub const AUTHORIZE_AUTHENTICATE: u32 = 1;
+}
+
+/// Diameter AVP (without Vendor-Id)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Avp {
+    /// AVP code
+    pub code: u32,
+    /// AVP flags
+    pub flags: u8,
+    /// Unpadded AVP data
+    pub data: Vec<u8>,
+}
+
+impl Avp {
+    /// Mandatory UTF8String / DiameterIdentity AVP
+    pub fn utf8(code: u32, value: &str) -> Self {
+        Self {
+            code,
+            flags: avp_flags::MANDATORY,
+            data: value.as_bytes().to_vec(),
+        }
+    }
+
+    /// Mandatory Unsigned32 / Enumerated AVP
+    pub fn unsigned32(code: u32, value: u32) -> Self {
+        Self {
+            code,
+            flags: avp_flags::MANDATORY,
+            data: value.to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Mandatory Unsigned64 AVP
+    pub fn unsigned64(code: u32, value: u64) -> Self {
+        Self {
+            code,
+            flags: avp_flags::MANDATORY,
+            data: value.to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Mandatory Grouped AVP
+    pub fn grouped(code: u32, avps: &[Avp]) -> Self {
+        Self {
+            code,
+            flags: avp_flags::MANDATORY,
+            data: avps.iter().flat_map(Avp::encode).collect(),
+        }
+    }
+
+    /// AVP length as carried in the header (header plus unpadded data)
+    pub fn length(&self) -> usize {
+        8 + self.data.len()
+    }
+
+    /// Encode the AVP in wire format (RFC 6733 section 4.1), padded to a
+    /// multiple of four bytes
+    pub fn encode(&self) -> Vec<u8> {
+        let length = self.length() as u32;
+        let mut encoded = Vec::with_capacity(self.length().div_ceil(4) * 4);
+        encoded.extend_from_slice(&self.code.to_be_bytes());
+        encoded.push(self.flags);
+        encoded.extend_from_slice(&length.to_be_bytes()[1..]);
+        encoded.extend_from_slice(&self.data);
+        encoded.resize(self.length().div_ceil(4) * 4, 0);
+        encoded
+    }
 }
 
 /// Diameter message types
@@ -130,6 +229,50 @@ pub struct GyMessage {
     pub granted_service_units: Option<ServiceUnits>,
     /// Result code
     pub result_code: Option<u32>,
+    /// Rating group the units are reported under
+    #[serde(default)]
+    pub rating_group: Option<u32>,
+    /// Service identifier the units are reported under
+    #[serde(default)]
+    pub service_identifier: Option<u32>,
+}
+
+impl GyMessage {
+    /// Multiple-Services-Credit-Control AVP reporting the message's units
+    /// under its rating group, or `None` if it has no rating group
+    pub fn mscc_avp(&self) -> Option<Avp> {
+        let rating_group = self.rating_group?;
+
+        let mut avps = Vec::new();
+        let units = [
+            (avp_codes::GRANTED_SERVICE_UNIT, &self.granted_service_units),
+            (
+                avp_codes::REQUESTED_SERVICE_UNIT,
+                &self.requested_service_units,
+            ),
+            (avp_codes::USED_SERVICE_UNIT, &self.used_service_units),
+        ];
+        for (code, units) in units {
+            if let Some(octets) = units.as_ref().and_then(|u| u.total_octets) {
+                avps.push(Avp::grouped(
+                    code,
+                    &[Avp::unsigned64(avp_codes::CC_TOTAL_OCTETS, octets)],
+                ));
+            }
+        }
+        if let Some(service_identifier) = self.service_identifier {
+            avps.push(Avp::unsigned32(
+                avp_codes::SERVICE_IDENTIFIER,
+                service_identifier,
+            ));
+        }
+        avps.push(Avp::unsigned32(avp_codes::RATING_GROUP, rating_group));
+
+        Some(Avp::grouped(
+            avp_codes::MULTIPLE_SERVICES_CREDIT_CONTROL,
+            &avps,
+        ))
+    }
 }
 
 /// Gy request types
@@ -190,16 +333,156 @@ pub enum GzRecordType {
     Event,
 }
 
+/// Why an OCS asks the PCEF to reauthorize a Gy session mid-session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReauthReason {
+    /// The subscriber crossed the quota notification threshold
+    QuotaThresholdReached,
+    /// A tariff boundary was reached and units must be re-rated
+    TariffTimeChange,
+    /// Operator-initiated reauthorization
+    ForcedReauth,
+}
+
+/// Gy Re-Auth-Request (RAR)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GyReauthRequest {
+    /// Session ID
+    pub session_id: String,
+    /// Subscriber ID
+    pub subscriber_id: String,
+    /// Reason for the reauthorization
+    pub reason: ReauthReason,
+    /// RAR AVPs, in message order
+    pub avps: Vec<Avp>,
+}
+
+impl GyReauthRequest {
+    /// Encoded AVP set of the RAR
+    pub fn encode_avps(&self) -> Vec<u8> {
+        self.avps.iter().flat_map(Avp::encode).collect()
+    }
+}
+
+/// Gy Re-Auth-Answer (RAA)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GyReauthAnswer {
+    /// Session ID
+    pub session_id: String,
+    /// Result code
+    pub result_code: u32,
+}
+
+/// Active Gy session
+#[derive(Debug, Clone)]
+struct GySession {
+    subscriber_id: String,
+    granted_service_units: ServiceUnits,
+    /// Reason of the RAR awaiting an answer, if any
+    pending_reauth: Option<ReauthReason>,
+}
+
+/// Units granted per Gy request when no quota manager is configured
+const DEFAULT_GRANTED_OCTETS: u64 = 100_000_000; // 100 MB
+
 /// Diameter protocol handler
 pub struct DiameterHandler {
     /// PCF engine reference
     pcf_engine: Option<std::sync::Arc<dyn crate::pcf_engine::PcfEngineTrait>>,
+    /// Quota manager used to size granted service units
+    quota_manager: Option<Arc<dyn QuotaManagerTrait>>,
+    /// Charging rules engine providing rating group mappings
+    charging_rules: Arc<ChargingRulesEngine>,
+    /// Active Gy sessions keyed by Session-Id
+    gy_sessions: DashMap<String, GySession>,
+    /// Origin-Host of messages sent by this node
+    origin_host: String,
+    /// Origin-Realm of messages sent by this node
+    origin_realm: String,
+    /// Writer of offline charging CDRs for Gz records
+    cdr_writer: Option<Arc<CdrWriter>>,
 }
 
 impl DiameterHandler {
     /// Create a new Diameter handler
     pub fn new() -> Self {
-        Self { pcf_engine: None }
+        Self {
+            pcf_engine: None,
+            quota_manager: None,
+            charging_rules: Arc::new(ChargingRulesEngine::new()),
+            gy_sessions: DashMap::new(),
+            origin_host: "pcf.localdomain".to_string(),
+            origin_realm: "localdomain".to_string(),
+            cdr_writer: None,
+        }
+    }
+
+    /// Set quota manager reference
+    pub fn set_quota_manager(&mut self, quota_manager: Arc<dyn QuotaManagerTrait>) {
+        self.quota_manager = Some(quota_manager);
+    }
+
+    /// Set charging rules engine reference
+    pub fn set_charging_rules(&mut self, charging_rules: Arc<ChargingRulesEngine>) {
+        self.charging_rules = charging_rules;
+    }
+
+    /// Set the writer that Gz records are stored as CDRs with
+    pub fn set_cdr_writer(&mut self, cdr_writer: Arc<CdrWriter>) {
+        self.cdr_writer = Some(cdr_writer);
+    }
+
+    /// Build a Gy Credit-Control-Request for a request's service, reporting
+    /// usage under the service's rating group
+    pub fn build_ccr(
+        &self,
+        session_id: &str,
+        request_type: GyRequestType,
+        request: &PolicyRequest,
+        used_service_units: Option<ServiceUnits>,
+        requested_service_units: Option<ServiceUnits>,
+    ) -> GyMessage {
+        let mapping = self
+            .charging_rules
+            .rating_group_for(request.application_id.as_deref(), &request.service_type);
+
+        GyMessage {
+            session_id: session_id.to_string(),
+            subscriber_id: request.subscriber_id.clone(),
+            request_type,
+            used_service_units,
+            requested_service_units,
+            granted_service_units: None,
+            result_code: None,
+            rating_group: Some(mapping.rating_group),
+            service_identifier: mapping.service_identifier,
+        }
+    }
+
+    /// Set the Diameter identity of this node
+    pub fn set_origin(&mut self, origin_host: impl Into<String>, origin_realm: impl Into<String>) {
+        self.origin_host = origin_host.into();
+        self.origin_realm = origin_realm.into();
+    }
+
+    /// Service units to grant a subscriber: the default grant, capped by the
+    /// remaining allowance when a quota manager is configured
+    async fn grant_service_units(&self, subscriber_id: &str) -> Result<ServiceUnits, PcfError> {
+        let mut octets = DEFAULT_GRANTED_OCTETS;
+        if let Some(ref quota_manager) = self.quota_manager {
+            if let Some(quota) = quota_manager.get_quota(subscriber_id).await? {
+                octets = octets.min(quota.remaining_quota_bytes);
+            }
+        }
+
+        Ok(ServiceUnits {
+            total_octets: Some(octets),
+            input_octets: Some(octets / 2),
+            output_octets: Some(octets - octets / 2),
+            time: Some(3600), // 1 hour
+            events: None,
+        })
     }
 
     /// Set PCF engine reference
@@ -236,6 +519,10 @@ impl DiameterHandler {
             application_id: None,
             location: None,
             time_of_day: None,
+            roaming: None, // Would come from the visited PLMN AVPs
+            sni: None,
+            destination_port: None,
+            congestion_level: None,
         };
 
         // Evaluate policy using PCF engine
@@ -271,13 +558,25 @@ impl DiameterHandler {
         // 2. Reserve quota
         // 3. Return granted service units
 
-        let granted_units = ServiceUnits {
-            total_octets: Some(100_000_000), // 100 MB granted
-            input_octets: Some(50_000_000),
-            output_octets: Some(50_000_000),
-            time: Some(3600), // 1 hour
-            events: None,
-        };
+        let granted_units = self.grant_service_units(&message.subscriber_id).await?;
+
+        // Track the session so the OCS side can reauthorize it later
+        match message.request_type {
+            GyRequestType::Initial | GyRequestType::Update => {
+                self.gy_sessions.insert(
+                    message.session_id.clone(),
+                    GySession {
+                        subscriber_id: message.subscriber_id.clone(),
+                        granted_service_units: granted_units.clone(),
+                        pending_reauth: None,
+                    },
+                );
+            }
+            GyRequestType::Terminate => {
+                self.gy_sessions.remove(&message.session_id);
+            }
+            GyRequestType::Event => {}
+        }
 
         let response = GyMessage {
             session_id: message.session_id.clone(),
@@ -287,12 +586,113 @@ impl DiameterHandler {
             requested_service_units: message.requested_service_units.clone(),
             granted_service_units: Some(granted_units),
             result_code: Some(2001), // DIAMETER_SUCCESS
+            rating_group: message.rating_group,
+            service_identifier: message.service_identifier,
         };
 
         debug!("Gy response generated for session: {}", message.session_id);
         Ok(response)
     }
 
+    /// Service units currently granted to an active Gy session
+    pub fn granted_service_units(&self, session_id: &str) -> Option<ServiceUnits> {
+        self.gy_sessions
+            .get(session_id)
+            .map(|session| session.granted_service_units.clone())
+    }
+
+    /// Build a Gy Re-Auth-Request for an active session
+    ///
+    /// The returned RAR carries the encoded AVP set to send to the PCEF; the
+    /// session awaits the matching RAA in [`Self::handle_reauth_answer`].
+    pub fn send_reauth_request(
+        &self,
+        session_id: &str,
+        reason: ReauthReason,
+    ) -> Result<GyReauthRequest, PcfError> {
+        let mut session = self
+            .gy_sessions
+            .get_mut(session_id)
+            .ok_or_else(|| PcfError::SessionNotFound(session_id.to_string()))?;
+        session.pending_reauth = Some(reason);
+
+        let avps = vec![
+            Avp::utf8(avp_codes::SESSION_ID, session_id),
+            Avp::utf8(avp_codes::ORIGIN_HOST, &self.origin_host),
+            Avp::utf8(avp_codes::ORIGIN_REALM, &self.origin_realm),
+            // The PCEF's realm is not carried in GyMessage; assume a single realm
+            Avp::utf8(avp_codes::DESTINATION_REALM, &self.origin_realm),
+            Avp::unsigned32(
+                avp_codes::AUTH_APPLICATION_ID,
+                application_ids::GY_APPLICATION_ID,
+            ),
+            Avp::unsigned32(
+                avp_codes::RE_AUTH_REQUEST_TYPE,
+                re_auth_request_types::AUTHORIZE_ONLY,
+            ),
+        ];
+
+        info!(
+            "Sending Gy RAR: session={}, subscriber={}, reason={:?}",
+            session_id, session.subscriber_id, reason
+        );
+
+        Ok(GyReauthRequest {
+            session_id: session_id.to_string(),
+            subscriber_id: session.subscriber_id.clone(),
+            reason,
+            avps,
+        })
+    }
+
+    /// Handle a Gy Re-Auth-Answer
+    ///
+    /// Matches the RAA to the session its RAR was sent for and, on success,
+    /// recomputes the session's granted service units from the current quota.
+    pub async fn handle_reauth_answer(
+        &self,
+        answer: &GyReauthAnswer,
+    ) -> Result<ServiceUnits, PcfError> {
+        let (subscriber_id, reason) = {
+            let mut session = self
+                .gy_sessions
+                .get_mut(&answer.session_id)
+                .ok_or_else(|| PcfError::SessionNotFound(answer.session_id.clone()))?;
+            let reason = session.pending_reauth.take().ok_or_else(|| {
+                PcfError::DiameterError(format!(
+                    "RAA for session {} without an outstanding RAR",
+                    answer.session_id
+                ))
+            })?;
+            (session.subscriber_id.clone(), reason)
+        };
+
+        if answer.result_code != result_codes::DIAMETER_SUCCESS {
+            warn!(
+                "Gy RAR rejected: session={}, reason={:?}, result_code={}",
+                answer.session_id, reason, answer.result_code
+            );
+            return Err(PcfError::DiameterError(format!(
+                "Reauthorization of session {} failed with result code {}",
+                answer.session_id, answer.result_code
+            )));
+        }
+
+        let granted_units = self.grant_service_units(&subscriber_id).await?;
+        // The session may have terminated while the grant was computed
+        let mut session = self
+            .gy_sessions
+            .get_mut(&answer.session_id)
+            .ok_or_else(|| PcfError::SessionNotFound(answer.session_id.clone()))?;
+        session.granted_service_units = granted_units.clone();
+
+        debug!(
+            "Gy session {} reauthorized ({:?}): {:?} octets granted",
+            answer.session_id, reason, granted_units.total_octets
+        );
+        Ok(granted_units)
+    }
+
     /// Handle Gz message (Offline Charging)
     pub async fn handle_gz_message(&self, message: &GzMessage) -> Result<(), PcfError> {
         info!(
@@ -300,10 +700,13 @@ impl DiameterHandler {
             message.session_id, message.subscriber_id, message.record_type
         );
 
-        // In production, this would:
-        // 1. Store CDR (Call Detail Record)
-        // 2. Forward to billing system
-        // 3. Update usage statistics
+        if let Some(ref cdr_writer) = self.cdr_writer {
+            cdr_writer.write_gz_message(message, None)?;
+        }
+
+        // In production, this would also:
+        // 1. Forward to billing system
+        // 2. Update usage statistics
 
         debug!("Gz message processed for session: {}", message.session_id);
         Ok(())
@@ -361,3 +764,170 @@ pub mod result_codes {
     /// Credit limit reached
     pub const DIAMETER_CREDIT_LIMIT_REACHED: u32 = 4012;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quota::QuotaManager;
+
+    fn gy_request(session_id: &str, request_type: GyRequestType) -> GyMessage {
+        GyMessage {
+            session_id: session_id.to_string(),
+            subscriber_id: "1234567890".to_string(),
+            request_type,
+            used_service_units: None,
+            requested_service_units: None,
+            granted_service_units: None,
+            result_code: None,
+            rating_group: None,
+            service_identifier: None,
+        }
+    }
+
+    #[test]
+    fn test_avp_encoding_is_padded() {
+        let avp = Avp::utf8(avp_codes::SESSION_ID, "abcde");
+        let encoded = avp.encode();
+
+        assert_eq!(avp.length(), 13);
+        assert_eq!(encoded.len(), 16);
+        assert_eq!(&encoded[0..4], &263u32.to_be_bytes());
+        assert_eq!(encoded[4], avp_flags::MANDATORY);
+        assert_eq!(&encoded[5..8], &[0, 0, 13]);
+        assert_eq!(&encoded[8..13], b"abcde");
+        assert_eq!(&encoded[13..], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_ccr_reports_usage_under_rating_group() {
+        let mut services = std::collections::HashMap::new();
+        services.insert(
+            "youtube.com".to_string(),
+            crate::models::RatingGroupMapping {
+                rating_group: 20,
+                service_identifier: Some(2001),
+            },
+        );
+        let charging = crate::charging::ChargingRulesEngine::new().with_rating_group_config(
+            crate::charging::RatingGroupConfig {
+                services,
+                default_mapping: crate::models::RatingGroupMapping {
+                    rating_group: 99,
+                    service_identifier: None,
+                },
+            },
+        );
+        let mut handler = DiameterHandler::new();
+        handler.set_charging_rules(Arc::new(charging));
+
+        #[allow(deprecated)]
+        let mut request = PolicyRequest {
+            subscriber_id: "1234567890".to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            network_generation: crate::models::NetworkGeneration::FourG,
+            apn: "internet".to_string(),
+            service_type: "video_streaming".to_string(),
+            application_id: Some("YouTube.com".to_string()),
+            location: None,
+            time_of_day: None,
+            roaming: None,
+            sni: None,
+            destination_port: None,
+            congestion_level: None,
+        };
+
+        let ccr = handler.build_ccr("gy-3", GyRequestType::Initial, &request, None, None);
+        assert_eq!(ccr.rating_group, Some(20));
+        assert_eq!(ccr.service_identifier, Some(2001));
+
+        let mscc = ccr.mscc_avp().unwrap();
+        assert_eq!(mscc.code, avp_codes::MULTIPLE_SERVICES_CREDIT_CONTROL);
+        assert!(mscc
+            .data
+            .ends_with(&Avp::unsigned32(avp_codes::RATING_GROUP, 20).encode()));
+
+        request.application_id = Some("unknown.example".to_string());
+        let ccr = handler.build_ccr("gy-4", GyRequestType::Initial, &request, None, None);
+        assert_eq!(ccr.rating_group, Some(99));
+        assert_eq!(ccr.service_identifier, None);
+    }
+
+    #[tokio::test]
+    async fn test_reauth_recomputes_grant_from_quota() {
+        let quota_manager = Arc::new(QuotaManager::new());
+        quota_manager.initialize_quota("1234567890".to_string(), 150_000_000, 80);
+        let mut handler = DiameterHandler::new();
+        handler.set_quota_manager(quota_manager.clone());
+
+        handler
+            .handle_gy_request(&gy_request("gy-1", GyRequestType::Initial))
+            .await
+            .unwrap();
+        assert_eq!(
+            handler.granted_service_units("gy-1").unwrap().total_octets,
+            Some(DEFAULT_GRANTED_OCTETS)
+        );
+
+        let rar = handler
+            .send_reauth_request("gy-1", ReauthReason::QuotaThresholdReached)
+            .unwrap();
+        assert_eq!(rar.avps[0], Avp::utf8(avp_codes::SESSION_ID, "gy-1"));
+        assert_eq!(
+            rar.encode_avps().len(),
+            rar.avps
+                .iter()
+                .map(|a| a.length().div_ceil(4) * 4)
+                .sum::<usize>()
+        );
+
+        quota_manager
+            .update_quota_usage("1234567890", 120_000_000)
+            .await
+            .unwrap();
+        let granted = handler
+            .handle_reauth_answer(&GyReauthAnswer {
+                session_id: "gy-1".to_string(),
+                result_code: result_codes::DIAMETER_SUCCESS,
+            })
+            .await
+            .unwrap();
+        assert_eq!(granted.total_octets, Some(30_000_000));
+        assert_eq!(
+            handler.granted_service_units("gy-1").unwrap().total_octets,
+            Some(30_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reauth_unknown_session() {
+        let handler = DiameterHandler::new();
+        assert!(matches!(
+            handler.send_reauth_request("missing", ReauthReason::ForcedReauth),
+            Err(PcfError::SessionNotFound(_))
+        ));
+        assert!(matches!(
+            handler
+                .handle_reauth_answer(&GyReauthAnswer {
+                    session_id: "missing".to_string(),
+                    result_code: result_codes::DIAMETER_SUCCESS,
+                })
+                .await,
+            Err(PcfError::SessionNotFound(_))
+        ));
+
+        handler
+            .handle_gy_request(&gy_request("gy-2", GyRequestType::Initial))
+            .await
+            .unwrap();
+        handler
+            .handle_gy_request(&gy_request("gy-2", GyRequestType::Terminate))
+            .await
+            .unwrap();
+        assert!(matches!(
+            handler.send_reauth_request("gy-2", ReauthReason::TariffTimeChange),
+            Err(PcfError::SessionNotFound(_))
+        ));
+    }
+}
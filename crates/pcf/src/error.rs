@@ -1,5 +1,6 @@
 //! Error types for PCF
 
+use crate::policy_conflict::PolicyConflict;
 use thiserror::Error;
 
 /// PCF-specific errors
@@ -20,6 +21,9 @@ pub enum PcfError {
     #[error("Diameter protocol error: {0}")]
     DiameterError(String),
 
+    #[error("Diameter session not found: {0}")]
+    SessionNotFound(String),
+
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
 
@@ -43,6 +47,15 @@ pub enum PcfError {
 
     #[error("Service not available: {0}")]
     ServiceUnavailable(String),
+
+    #[error("Session store error: {0}")]
+    SessionStoreError(String),
+
+    #[error("CDR write error: {0}")]
+    CdrWriteError(String),
+
+    #[error("Conflicting policy rules: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    PolicyConflicts(Vec<PolicyConflict>),
 }
 
 impl PcfError {
@@ -50,7 +63,10 @@ impl PcfError {
     pub fn is_retryable(&self) -> bool {
         matches!(
             self,
-            PcfError::Timeout | PcfError::ServiceUnavailable(_) | PcfError::DatabaseError(_)
+            PcfError::Timeout
+                | PcfError::ServiceUnavailable(_)
+                | PcfError::DatabaseError(_)
+                | PcfError::SessionStoreError(_)
         )
     }
 }
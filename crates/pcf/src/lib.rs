@@ -17,12 +17,13 @@
 //! - AI/ML integration hooks for intelligent policy decisions
 //! - Zero-rating support (unlimited apps/services)
 //! - Network congestion management
+//! - Time-of-day policy windows with timezone support
 //! - Real-time quota monitoring and notifications
 //!
 //! ## Example Usage
 //!
 //! ```rust
-//! use bss_oss_pcf::{PcfEngine, PolicyRequest, NetworkGeneration};
+//! use bss_oss_pcf::{PcfEngine, PolicyRequest, NetworkGeneration, TaxId, TaxIdCountry};
 //! use bss_oss_pcf::pcf_engine::PcfEngineTrait;
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,13 +32,19 @@
 //! let request = PolicyRequest {
 //!     subscriber_id: "1234567890".to_string(),
 //!     imsi: "123456789012345".to_string(),
-//!     cpf: Some("123.456.789-09".to_string()),
+//!     tax_id: Some(TaxId::from_string("123.456.789-09", TaxIdCountry::BR)?),
+//!     #[allow(deprecated)]
+//!     cpf: None,
 //!     network_generation: NetworkGeneration::FourG,
 //!     apn: "internet".to_string(),
 //!     service_type: "video_streaming".to_string(),
 //!     application_id: Some("youtube.com".to_string()),
 //!     location: None,
 //!     time_of_day: None,
+//!     roaming: None,
+//!     sni: None,
+//!     destination_port: None,
+//!     congestion_level: None,
 //! };
 //!
 //! let policy = pcf.evaluate_policy(&request).await?;
@@ -47,19 +54,39 @@
 //! # }
 //! ```
 
+#![allow(
+    clippy::collapsible_if,
+    clippy::collapsible_match,
+    clippy::manual_range_contains,
+    clippy::needless_range_loop,
+    clippy::useless_vec
+)]
+
 pub mod ai;
 pub mod charging;
+pub mod cnpj;
+pub mod congestion;
 pub mod cpf;
 pub mod diameter;
 pub mod error;
 pub mod models;
 pub mod pcf_engine;
 pub mod policy;
+pub mod policy_conflict;
 pub mod quota;
+pub mod session;
 pub mod tax_id;
+pub mod time_window;
+pub mod zero_rating;
 
+pub use cnpj::Cnpj;
 pub use cpf::Cpf;
 pub use error::PcfError;
 pub use models::*;
 pub use pcf_engine::PcfEngine;
+pub use policy_conflict::{PolicyConflict, PolicyConflictKind};
+#[cfg(feature = "redis")]
+pub use session::RedisSessionStore;
+pub use session::{InMemorySessionStore, PcfSession, SessionStore};
 pub use tax_id::{TaxId, TaxIdCountry};
+pub use time_window::TimeWindow;
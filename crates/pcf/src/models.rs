@@ -1,5 +1,6 @@
 //! Core models for PCF/PCRF
 
+use crate::time_window::TimeWindow;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -107,6 +108,16 @@ pub struct ChargingRule {
     pub unit_cost: Option<f64>,
 }
 
+/// Rating group and Diameter Service-Identifier that a service's usage is
+/// reported under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatingGroupMapping {
+    /// Rating-Group
+    pub rating_group: u32,
+    /// Service-Identifier
+    pub service_identifier: Option<u32>,
+}
+
 /// Quota information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quota {
@@ -124,6 +135,35 @@ pub struct Quota {
     pub throttled_bandwidth_kbps: Option<u64>,
     /// Last update timestamp
     pub last_update: DateTime<Utc>,
+    /// Rollover of unused quota into the next period, if the plan allows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollover: Option<RolloverConfig>,
+    /// Bytes carried over from the previous period, included in the totals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_over: Option<RolledOverQuota>,
+}
+
+/// Rollover of unused quota into the next billing period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RolloverConfig {
+    /// Maximum bytes carried into the next period
+    pub max_carry_bytes: u64,
+    /// Days after the start of the next period at which carried bytes expire
+    pub expiry_days: u32,
+}
+
+/// Bytes carried over from the previous billing period
+///
+/// Carried bytes are consumed before the period's own allowance and never
+/// roll over again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolledOverQuota {
+    /// Bytes carried over at the start of the period
+    pub carried_bytes: u64,
+    /// Carried bytes neither used nor expired yet
+    pub remaining_bytes: u64,
+    /// When the unused carried bytes expire
+    pub expires_at: DateTime<Utc>,
 }
 
 impl Quota {
@@ -139,6 +179,32 @@ impl Quota {
     pub fn should_notify(&self) -> bool {
         self.usage_percent() >= self.notification_threshold_percent as f64 && !self.exceeded
     }
+
+    /// Remaining bytes of the period's own allowance, excluding carried bytes
+    pub fn fresh_remaining_bytes(&self) -> u64 {
+        let carried = self.rolled_over.as_ref().map_or(0, |r| r.remaining_bytes);
+        self.remaining_quota_bytes.saturating_sub(carried)
+    }
+
+    /// Forfeit carried bytes that are unused at their expiry, returning how
+    /// many were forfeited
+    ///
+    /// The forfeited bytes are taken off the total and remaining quota; bytes
+    /// already used stay counted as used.
+    pub fn expire_rollover(&mut self, now: DateTime<Utc>) -> u64 {
+        let Some(rolled_over) = self.rolled_over.as_mut() else {
+            return 0;
+        };
+        if rolled_over.expires_at > now {
+            return 0;
+        }
+
+        let forfeited = rolled_over.remaining_bytes;
+        rolled_over.remaining_bytes = 0;
+        self.total_quota_bytes = self.total_quota_bytes.saturating_sub(forfeited);
+        self.remaining_quota_bytes = self.remaining_quota_bytes.saturating_sub(forfeited);
+        forfeited
+    }
 }
 
 /// Policy request from network equipment (P-GW, SMF, etc.)
@@ -165,8 +231,38 @@ pub struct PolicyRequest {
     pub application_id: Option<String>,
     /// Current location (optional)
     pub location: Option<String>,
-    /// Time of day (optional, for time-based policies)
+    /// Time of day (optional, for time-based policies). Rules restricted to a
+    /// time window do not match requests without it.
     pub time_of_day: Option<DateTime<Utc>>,
+    /// Roaming context when the subscriber is attached to a visited network
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roaming: Option<RoamingContext>,
+    /// TLS Server Name Indication or DNS name of the flow (e.g., "r3.googlevideo.com")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sni: Option<String>,
+    /// Destination port of the flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_port: Option<u16>,
+    /// Current congestion of the cell or network slice serving the subscriber
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub congestion_level: Option<CongestionLevel>,
+}
+
+/// Roaming context for a policy request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoamingContext {
+    /// Visited PLMN identifier (MCC + MNC, e.g., "310260")
+    pub visited_plmn: String,
+}
+
+/// Congestion level reported by the network, from least to most congested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CongestionLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
 }
 
 /// Policy decision result
@@ -192,6 +288,24 @@ pub struct PolicyDecision {
     pub timestamp: DateTime<Utc>,
     /// Validity period in seconds
     pub validity_period: Option<u64>,
+    /// Roaming policy applied (if the subscriber is roaming)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roaming_policy_id: Option<Uuid>,
+    /// Policy rule selected for the request (if any rule matched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_id: Option<Uuid>,
+    /// Whether the request's traffic is zero-rated
+    #[serde(default)]
+    pub zero_rated: bool,
+    /// Zero-rating rule that matched the request (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zero_rating_rule_id: Option<Uuid>,
+    /// Congestion rule that downgraded the QoS (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub congestion_rule_id: Option<Uuid>,
+    /// Whether the policy hint provider flagged the usage as anomalous
+    #[serde(default)]
+    pub anomalous_usage: bool,
 }
 
 /// Subscriber profile
@@ -251,6 +365,45 @@ pub struct PolicyRule {
     pub valid_to: Option<DateTime<Utc>>,
     /// Network generation requirements
     pub required_network_generation: Option<NetworkGeneration>,
+    /// Share of subscribers assigned to this rule when several rules match the
+    /// same request under weighted selection (unset counts as 1)
+    #[serde(default)]
+    pub weight: Option<u32>,
+    /// Local time window in which the rule applies (unset = at any time).
+    /// Requests without a time of day never match a time-gated rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_window: Option<TimeWindow>,
+}
+
+/// How one rule is chosen when several policy rules match a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PolicySelectionMode {
+    /// Highest priority wins; among equal priorities the latest added wins
+    #[default]
+    Deterministic,
+    /// Subscribers are spread across matching rules in proportion to their
+    /// weights, each subscriber always landing on the same rule
+    Weighted,
+}
+
+/// Roaming policy definition
+///
+/// Applied instead of the home network policy while a subscriber is roaming.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoamingPolicy {
+    /// Policy ID
+    pub policy_id: Uuid,
+    /// Policy name
+    pub policy_name: String,
+    /// Visited PLMN this policy applies to (`None` for the default roaming policy)
+    pub visited_plmn: Option<String>,
+    /// QoS configuration while roaming
+    pub qos: QoS,
+    /// Charging rules while roaming
+    pub charging_rules: Vec<ChargingRule>,
+    /// Whether policy is active
+    pub active: bool,
 }
 
 /// Zero-rating rule
@@ -258,14 +411,71 @@ pub struct PolicyRule {
 pub struct ZeroRatingRule {
     /// Rule ID
     pub rule_id: Uuid,
-    /// Service/application identifier
+    /// Hostname or hostname pattern (e.g., "whatsapp.com", "*.youtube.com")
+    ///
+    /// A leading `*.` matches any subdomain, at any depth, but not the domain
+    /// itself. Matching is case-insensitive.
     pub service_identifier: String,
     /// Plan name (if plan-specific)
     pub plan_name: Option<String>,
+    /// Destination ports the rule is limited to (all ports if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_range: Option<PortRange>,
     /// Whether rule is active
     pub active: bool,
 }
 
+/// Inclusive range of ports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    /// First port of the range
+    pub start: u16,
+    /// Last port of the range
+    pub end: u16,
+}
+
+impl PortRange {
+    /// Check if a port is within the range
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+}
+
+/// Congestion rule definition
+///
+/// Downgrades the QoS of matching requests while the reported congestion is at
+/// or above `min_congestion_level`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CongestionRule {
+    /// Rule ID
+    pub rule_id: Uuid,
+    /// Rule name
+    pub rule_name: String,
+    /// Lowest congestion level at which the rule applies
+    pub min_congestion_level: CongestionLevel,
+    /// Plan name (if plan-specific)
+    pub plan_name: Option<String>,
+    /// Service type (if service-specific)
+    pub service_type: Option<String>,
+    /// Downgrade applied to the QoS
+    pub action: CongestionAction,
+    /// Whether rule is active
+    pub active: bool,
+}
+
+/// QoS downgrade applied by a congestion rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CongestionAction {
+    /// Lower the priority by the given number of levels (never below 1)
+    DowngradePriority { levels: u8 },
+    /// Cap the download and upload bandwidth
+    CapBandwidth {
+        max_download_kbps: u64,
+        max_upload_kbps: u64,
+    },
+}
+
 /// Quota threshold notification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaNotification {
@@ -2,15 +2,30 @@
 //!
 //! Orchestrates policy control, charging rules, and quota management
 
-use crate::charging::{ChargingRulesEngine, ChargingRulesTrait};
+use crate::ai::{NoopPolicyHintProvider, PolicyHint, PolicyHintProvider};
+use crate::charging::{ChargingRulesEngine, ChargingRulesTrait, RatingGroupConfig};
+use crate::congestion::most_aggressive_downgrade;
 use crate::error::PcfError;
-use crate::models::{PolicyDecision, PolicyRequest, SubscriberProfile};
-use crate::policy::{PolicyControlEngine, PolicyControlTrait};
+use crate::models::{
+    CongestionRule, PolicyDecision, PolicyRequest, PolicyRule, PolicySelectionMode,
+    SubscriberProfile,
+};
+use crate::policy::{PolicyControlEngine, PolicyControlTrait, PolicyRuleSnapshot};
+use crate::policy_conflict::PolicyConflict;
 use crate::quota::{QuotaManager, QuotaManagerTrait};
+use crate::session::{InMemorySessionStore, PcfSession, SessionStore};
 use async_trait::async_trait;
 use chrono::Utc;
+use futures::stream::{self, StreamExt};
 use log::{debug, info, warn};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Default session time-to-live, matching the default policy validity
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(3600);
+
+/// Default number of requests of a batch evaluated at a time
+const DEFAULT_BATCH_CONCURRENCY: usize = 64;
 
 /// Main PCF engine trait
 #[async_trait]
@@ -39,6 +54,14 @@ pub struct PcfEngine {
     quota_manager: Arc<QuotaManager>,
     /// Subscriber profiles cache (in production, this would be backed by database)
     subscriber_profiles: Arc<dashmap::DashMap<String, SubscriberProfile>>,
+    /// Active sessions (in-memory unless a shared store is configured)
+    session_store: Arc<dyn SessionStore>,
+    /// Time after which a session that is not updated expires
+    session_ttl: Duration,
+    /// Number of requests of a batch evaluated at a time
+    batch_concurrency: usize,
+    /// External model whose hints are merged into decisions
+    hint_provider: Box<dyn PolicyHintProvider>,
 }
 
 impl PcfEngine {
@@ -49,6 +72,10 @@ impl PcfEngine {
             charging_rules: Arc::new(ChargingRulesEngine::new()),
             quota_manager: Arc::new(QuotaManager::new()),
             subscriber_profiles: Arc::new(dashmap::DashMap::new()),
+            session_store: Arc::new(InMemorySessionStore::new()),
+            session_ttl: DEFAULT_SESSION_TTL,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            hint_provider: Box::new(NoopPolicyHintProvider),
         };
 
         // Initialize some example subscriber profiles for testing
@@ -81,6 +108,8 @@ impl PcfEngine {
                 exceeded: false,
                 throttled_bandwidth_kbps: None,
                 last_update: Utc::now(),
+                rollover: None,
+                rolled_over: None,
             },
             active_policies: vec!["premium_qos".to_string()],
             zero_rated_services: vec!["whatsapp.com".to_string()],
@@ -115,6 +144,8 @@ impl PcfEngine {
                 exceeded: false,
                 throttled_bandwidth_kbps: None,
                 last_update: Utc::now(),
+                rollover: None,
+                rolled_over: None,
             },
             active_policies: vec!["economy_qos".to_string()],
             zero_rated_services: vec!["whatsapp.com".to_string()],
@@ -147,11 +178,192 @@ impl PcfEngine {
 
         info!("Registered subscriber: {}", subscriber_id);
     }
-}
 
-#[async_trait]
-impl PcfEngineTrait for PcfEngine {
-    async fn evaluate_policy(&self, request: &PolicyRequest) -> Result<PolicyDecision, PcfError> {
+    /// Report usage under the rating groups of the given configuration
+    pub fn with_rating_group_config(mut self, config: RatingGroupConfig) -> Self {
+        self.charging_rules = Arc::new(ChargingRulesEngine::new().with_rating_group_config(config));
+        self
+    }
+
+    /// Get the charging rules engine, e.g. for the Diameter handler
+    pub fn charging_rules_engine(&self) -> Arc<ChargingRulesEngine> {
+        Arc::clone(&self.charging_rules)
+    }
+
+    /// Choose among policy rules matching the same request using `mode`
+    pub fn with_policy_selection_mode(self, mode: PolicySelectionMode) -> Self {
+        self.policy_control.set_selection_mode(mode);
+        self
+    }
+
+    /// Check the loaded policy rules for overlapping conditions with
+    /// conflicting actions, returning the conflicting rule pairs
+    pub fn validate_policies(&self) -> Vec<PolicyConflict> {
+        self.policy_control.validate_policies()
+    }
+
+    /// Validate and activate a set of policy rules
+    ///
+    /// Nothing is activated if any rule conflicts with another new rule or
+    /// with a loaded rule it does not replace.
+    pub fn load_policy_rules(&self, rules: Vec<PolicyRule>) -> Result<(), PcfError> {
+        let count = rules.len();
+        self.policy_control.load_policy_rules(rules)?;
+        info!("Loaded {} policy rules", count);
+        Ok(())
+    }
+
+    /// Add or update a rule downgrading QoS under network congestion
+    pub fn add_congestion_rule(&self, rule: CongestionRule) -> Result<(), PcfError> {
+        self.policy_control.add_congestion_rule(rule)
+    }
+
+    /// Keep sessions in the given store, e.g. one shared by a PCF cluster
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        self.session_store = store;
+        self
+    }
+
+    /// Expire sessions that are not updated within `ttl`
+    pub fn with_session_ttl(mut self, ttl: Duration) -> Self {
+        self.session_ttl = ttl;
+        self
+    }
+
+    /// Evaluate up to `concurrency` requests of a batch at a time
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Merge hints of an external model into policy decisions
+    pub fn with_policy_hint_provider(mut self, provider: Box<dyn PolicyHintProvider>) -> Self {
+        self.hint_provider = provider;
+        self
+    }
+
+    /// Get the session store
+    pub fn session_store(&self) -> Arc<dyn SessionStore> {
+        Arc::clone(&self.session_store)
+    }
+
+    /// Evaluate policy for a new session and store the session
+    pub async fn start_session(
+        &self,
+        session_id: &str,
+        request: &PolicyRequest,
+    ) -> Result<PolicyDecision, PcfError> {
+        let decision = self.evaluate_policy(request).await?;
+
+        let now = Utc::now();
+        let session = PcfSession {
+            session_id: session_id.to_string(),
+            subscriber_id: request.subscriber_id.clone(),
+            apn: request.apn.clone(),
+            policy_decision: Some(decision.clone()),
+            quota: decision.quota.clone(),
+            created_at: now,
+            last_update: now,
+            version: 0,
+        };
+        self.session_store.put(&session, self.session_ttl).await?;
+
+        debug!(
+            "Started session {} for subscriber {}",
+            session_id, request.subscriber_id
+        );
+        Ok(decision)
+    }
+
+    /// Get an active session
+    pub async fn get_session(&self, session_id: &str) -> Result<Option<PcfSession>, PcfError> {
+        self.session_store.get(session_id).await
+    }
+
+    /// Record usage on a session, refreshing its quota snapshot and expiry
+    ///
+    /// The session is written back with compare-and-swap: if another update
+    /// or a terminate got there first, the session is read again, so the
+    /// latest quota snapshot wins and a terminated session is not recreated.
+    pub async fn update_session_usage(
+        &self,
+        session_id: &str,
+        bytes_used: u64,
+    ) -> Result<PcfSession, PcfError> {
+        let unknown_session = || {
+            PcfError::InvalidSubscriberData(format!("Unknown or expired session: {}", session_id))
+        };
+        let session = self
+            .session_store
+            .get(session_id)
+            .await?
+            .ok_or_else(unknown_session)?;
+
+        self.update_quota_usage(&session.subscriber_id, bytes_used)
+            .await?;
+
+        let mut session = session;
+        loop {
+            let expected_version = session.version;
+            session.quota = self.quota_manager.get_quota(&session.subscriber_id).await?;
+            session.last_update = Utc::now();
+            session.version = expected_version + 1;
+            if self
+                .session_store
+                .replace_if_version(&session, expected_version, self.session_ttl)
+                .await?
+            {
+                return Ok(session);
+            }
+            session = self
+                .session_store
+                .get(session_id)
+                .await?
+                .ok_or_else(unknown_session)?;
+        }
+    }
+
+    /// End a session, returning whether it was active
+    pub async fn terminate_session(&self, session_id: &str) -> Result<bool, PcfError> {
+        let removed = self.session_store.remove(session_id).await?;
+        if removed {
+            debug!("Terminated session {}", session_id);
+        }
+        Ok(removed)
+    }
+
+    /// Evaluate policies for a batch of requests, e.g. when a cell reloads
+    ///
+    /// Shared rule state is read once for the whole batch and up to the batch
+    /// concurrency limit of requests are evaluated at a time. Results are in
+    /// the order of the requests, and a failing request only fails its own
+    /// result.
+    pub async fn evaluate_policies(
+        &self,
+        requests: &[PolicyRequest],
+    ) -> Vec<Result<PolicyDecision, PcfError>> {
+        let rules = self.policy_control.snapshot();
+        let results: Vec<Result<PolicyDecision, PcfError>> = stream::iter(requests)
+            .map(|request| self.evaluate_policy_with(request, &rules))
+            .buffered(self.batch_concurrency)
+            .collect()
+            .await;
+
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        info!(
+            "Evaluated batch of {} policy requests ({} failed)",
+            results.len(),
+            failed
+        );
+        results
+    }
+
+    /// Evaluate policy against a snapshot of the shared rule state
+    async fn evaluate_policy_with(
+        &self,
+        request: &PolicyRequest,
+        rules: &PolicyRuleSnapshot,
+    ) -> Result<PolicyDecision, PcfError> {
         info!(
             "Evaluating policy for subscriber: {}, service: {}",
             request.subscriber_id, request.service_type
@@ -181,21 +393,55 @@ impl PcfEngineTrait for PcfEngine {
                 policy_rule_name: "gate_rule".to_string(),
                 timestamp: Utc::now(),
                 validity_period: None,
+                roaming_policy_id: None,
+                policy_id: None,
+                zero_rated: false,
+                zero_rating_rule_id: None,
+                congestion_rule_id: None,
+                anomalous_usage: false,
             });
         }
 
-        // Evaluate QoS
-        let qos = self
-            .policy_control
-            .evaluate_policy(request, &subscriber_profile)
-            .await?;
+        // Evaluate QoS, recording which policy rule was chosen for auditing
+        let (qos, policy_rule) =
+            self.policy_control
+                .evaluate_qos(request, &subscriber_profile, rules)?;
+        let policy_id = policy_rule.map(|rule| rule.rule_id);
 
         // Get charging rules
-        let charging_rules = self
+        let mut charging_rules = self
             .charging_rules
             .get_charging_rules(request, &subscriber_profile)
             .await?;
 
+        // Zero-rating by application ID or SNI hostname
+        let zero_rating_rule_id = self
+            .charging_rules
+            .match_zero_rating(request, &subscriber_profile)
+            .map(|rule| rule.rule_id);
+        let zero_rated = self
+            .charging_rules
+            .is_zero_rated(request, &subscriber_profile)
+            .await?;
+
+        // Roaming subscribers get the visited network's policy instead of the home one
+        let mut qos = qos;
+        let mut roaming_policy_id = None;
+        if let Some(ref roaming) = request.roaming {
+            if let Some(roaming_policy) = self
+                .policy_control
+                .get_roaming_policy(&roaming.visited_plmn)
+            {
+                debug!(
+                    "Applying roaming policy {} for subscriber {} in {}",
+                    roaming_policy.policy_name, request.subscriber_id, roaming.visited_plmn
+                );
+                qos = roaming_policy.qos;
+                charging_rules = roaming_policy.charging_rules;
+                roaming_policy_id = Some(roaming_policy.policy_id);
+            }
+        }
+
         // Get current quota
         let quota = self.quota_manager.get_quota(&request.subscriber_id).await?;
 
@@ -215,6 +461,44 @@ impl PcfEngineTrait for PcfEngine {
             }
         }
 
+        // Downgrade QoS while the network is congested
+        let mut congestion_rule_id = None;
+        if let Some((rule, downgraded_qos)) = most_aggressive_downgrade(
+            &rules.congestion_rules,
+            request,
+            &subscriber_profile.plan_name,
+            &final_qos,
+        ) {
+            debug!(
+                "Applying congestion rule {} for subscriber {} at {:?} congestion",
+                rule.rule_name, request.subscriber_id, request.congestion_level
+            );
+            final_qos = downgraded_qos;
+            congestion_rule_id = Some(rule.rule_id);
+        }
+
+        // Merge the hint of the external model; a failing model is ignored
+        let hint = self
+            .hint_provider
+            .suggest(request)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Policy hint unavailable for subscriber {}: {}",
+                    request.subscriber_id, e
+                );
+                PolicyHint::default()
+            });
+        let throttled = quota.as_ref().is_some_and(|quota| quota.exceeded);
+        let may_raise = !throttled && roaming_policy_id.is_none() && congestion_rule_id.is_none();
+        hint.apply(&mut final_qos, may_raise);
+        if hint.anomalous_usage {
+            warn!(
+                "Anomalous usage flagged for subscriber {}: {:?}",
+                request.subscriber_id, hint.anomaly_type
+            );
+        }
+
         // Check for threshold notifications
         if let Some(notification) = self
             .quota_manager
@@ -239,6 +523,12 @@ impl PcfEngineTrait for PcfEngine {
             policy_rule_name: format!("policy_{}", subscriber_profile.plan_name),
             timestamp: Utc::now(),
             validity_period: Some(3600), // 1 hour default validity
+            roaming_policy_id,
+            policy_id,
+            zero_rated,
+            zero_rating_rule_id,
+            congestion_rule_id,
+            anomalous_usage: hint.anomalous_usage,
         };
 
         debug!(
@@ -248,6 +538,14 @@ impl PcfEngineTrait for PcfEngine {
 
         Ok(decision)
     }
+}
+
+#[async_trait]
+impl PcfEngineTrait for PcfEngine {
+    async fn evaluate_policy(&self, request: &PolicyRequest) -> Result<PolicyDecision, PcfError> {
+        let rules = self.policy_control.snapshot();
+        self.evaluate_policy_with(request, &rules).await
+    }
 
     async fn get_subscriber_profile(
         &self,
@@ -286,3 +584,144 @@ impl Default for PcfEngine {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AnomalyType;
+    use crate::models::{CongestionAction, CongestionLevel, NetworkGeneration};
+    use uuid::Uuid;
+
+    struct FixedHintProvider(Result<PolicyHint, String>);
+
+    #[async_trait]
+    impl PolicyHintProvider for FixedHintProvider {
+        async fn suggest(&self, _request: &PolicyRequest) -> Result<PolicyHint, PcfError> {
+            self.0.clone().map_err(PcfError::AIServiceError)
+        }
+    }
+
+    fn hinted_engine(hint: Result<PolicyHint, String>) -> PcfEngine {
+        PcfEngine::new().with_policy_hint_provider(Box::new(FixedHintProvider(hint)))
+    }
+
+    fn request(subscriber_id: &str, network_generation: NetworkGeneration) -> PolicyRequest {
+        #[allow(deprecated)]
+        PolicyRequest {
+            subscriber_id: subscriber_id.to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            network_generation,
+            apn: "internet".to_string(),
+            service_type: "web_browsing".to_string(),
+            application_id: None,
+            location: None,
+            time_of_day: None,
+            roaming: None,
+            sni: None,
+            destination_port: None,
+            congestion_level: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_evaluation_keeps_order_and_isolates_failures() {
+        let engine = PcfEngine::new().with_batch_concurrency(2);
+        let requests = vec![
+            request("1234567890", NetworkGeneration::FiveG),
+            request("unknown", NetworkGeneration::FourG),
+            request("0987654321", NetworkGeneration::FourG),
+            request("0987654321", NetworkGeneration::FiveG),
+            request("1234567890", NetworkGeneration::FourG),
+        ];
+
+        let results = engine.evaluate_policies(&requests).await;
+
+        assert_eq!(results.len(), requests.len());
+        assert!(matches!(results[1], Err(PcfError::PolicyNotFound(_))));
+        assert!(matches!(
+            results[3],
+            Err(PcfError::UnsupportedNetworkGeneration(_))
+        ));
+        for i in [0, 2, 4] {
+            let decision = results[i].as_ref().unwrap();
+            let single = engine.evaluate_policy(&requests[i]).await.unwrap();
+            assert_eq!(decision.subscriber_id, requests[i].subscriber_id);
+            assert_eq!(decision.qos.priority, single.qos.priority);
+            assert_eq!(
+                decision.qos.max_download_bandwidth_kbps,
+                single.qos.max_download_bandwidth_kbps
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policy_hint_biases_priority() {
+        let request = request("0987654321", NetworkGeneration::FourG);
+        let baseline = PcfEngine::new().evaluate_policy(&request).await.unwrap();
+
+        let raised = hinted_engine(Ok(PolicyHint {
+            priority_bias: 3,
+            ..Default::default()
+        }))
+        .evaluate_policy(&request)
+        .await
+        .unwrap();
+        assert_eq!(raised.qos.priority, baseline.qos.priority + 3);
+        assert!(!raised.anomalous_usage);
+
+        let anomalous = hinted_engine(Ok(PolicyHint {
+            priority_bias: 3,
+            anomalous_usage: true,
+            anomaly_type: Some(AnomalyType::DataVolumeSpike),
+        }))
+        .evaluate_policy(&request)
+        .await
+        .unwrap();
+        assert_eq!(anomalous.qos.priority, baseline.qos.priority);
+        assert!(anomalous.anomalous_usage);
+
+        let failing = hinted_engine(Err("model unavailable".to_string()))
+            .evaluate_policy(&request)
+            .await
+            .unwrap();
+        assert_eq!(failing.qos.priority, baseline.qos.priority);
+    }
+
+    #[tokio::test]
+    async fn test_policy_hint_does_not_override_congestion_downgrade() {
+        let mut request = request("1234567890", NetworkGeneration::FiveG);
+        request.congestion_level = Some(CongestionLevel::High);
+        let rule = CongestionRule {
+            rule_id: Uuid::new_v4(),
+            rule_name: "downgrade".to_string(),
+            min_congestion_level: CongestionLevel::Medium,
+            plan_name: None,
+            service_type: None,
+            action: CongestionAction::DowngradePriority { levels: 2 },
+            active: true,
+        };
+
+        let baseline = PcfEngine::new();
+        baseline.add_congestion_rule(rule.clone()).unwrap();
+        let downgraded = baseline.evaluate_policy(&request).await.unwrap();
+
+        let raising = hinted_engine(Ok(PolicyHint {
+            priority_bias: 5,
+            ..Default::default()
+        }));
+        raising.add_congestion_rule(rule.clone()).unwrap();
+        let decision = raising.evaluate_policy(&request).await.unwrap();
+        assert_eq!(decision.qos.priority, downgraded.qos.priority);
+        assert_eq!(decision.congestion_rule_id, Some(rule.rule_id));
+
+        let lowering = hinted_engine(Ok(PolicyHint {
+            priority_bias: -1,
+            ..Default::default()
+        }));
+        lowering.add_congestion_rule(rule).unwrap();
+        let decision = lowering.evaluate_policy(&request).await.unwrap();
+        assert_eq!(decision.qos.priority, downgraded.qos.priority - 1);
+    }
+}
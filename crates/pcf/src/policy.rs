@@ -2,12 +2,19 @@
 //!
 //! Handles QoS, bandwidth, prioritization, and gating decisions
 
+use crate::congestion::{most_aggressive_downgrade, validate_congestion_rule};
 use crate::error::PcfError;
-use crate::models::{NetworkGeneration, PolicyRequest, PolicyRule, QoS};
+use crate::models::{
+    ChargingMethod, ChargingRule, CongestionRule, NetworkGeneration, PolicyRequest, PolicyRule,
+    PolicySelectionMode, QoS, RoamingPolicy,
+};
+use crate::policy_conflict::{detect_conflicts, PolicyConflict};
+use crate::time_window::window_applies;
 use async_trait::async_trait;
 use dashmap::DashMap;
-use log::{debug, info};
-use std::sync::Arc;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 /// Policy control engine trait
 #[async_trait]
@@ -34,12 +41,38 @@ pub trait PolicyControlTrait: Send + Sync {
     ) -> Result<bool, PcfError>;
 }
 
+/// Rule state read once and shared by every evaluation of a batch
+pub(crate) struct PolicyRuleSnapshot {
+    pub(crate) policy_rules: HashMap<String, Vec<PolicyRule>>,
+    pub(crate) selection_mode: PolicySelectionMode,
+    pub(crate) congestion_rules: Vec<CongestionRule>,
+}
+
+impl PolicyRuleSnapshot {
+    /// Choose the active policy rule for a request from the snapshot
+    pub(crate) fn select_policy_rule(
+        &self,
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+    ) -> Option<PolicyRule> {
+        let key = PolicyControlEngine::request_key(request, subscriber_profile);
+        let rules = self.policy_rules.get(&key)?;
+        choose_policy_rule(rules, &key, request, self.selection_mode)
+    }
+}
+
 /// Policy control engine implementation
 pub struct PolicyControlEngine {
-    /// Cache of policy rules
-    policy_rules: Arc<DashMap<String, PolicyRule>>,
+    /// Cache of policy rules, grouped by the conditions they match
+    policy_rules: Arc<DashMap<String, Vec<PolicyRule>>>,
+    /// How a rule is chosen among rules matching the same request
+    selection_mode: Arc<RwLock<PolicySelectionMode>>,
     /// Default QoS per network generation
     default_qos: Arc<DashMap<NetworkGeneration, QoS>>,
+    /// Roaming policies keyed by visited PLMN ("default" for the fallback policy)
+    roaming_policies: Arc<DashMap<String, RoamingPolicy>>,
+    /// Congestion rules keyed by rule ID
+    congestion_rules: Arc<DashMap<uuid::Uuid, CongestionRule>>,
 }
 
 impl PolicyControlEngine {
@@ -47,11 +80,15 @@ impl PolicyControlEngine {
     pub fn new() -> Self {
         let engine = Self {
             policy_rules: Arc::new(DashMap::new()),
+            selection_mode: Arc::new(RwLock::new(PolicySelectionMode::default())),
             default_qos: Arc::new(DashMap::new()),
+            roaming_policies: Arc::new(DashMap::new()),
+            congestion_rules: Arc::new(DashMap::new()),
         };
 
         // Initialize default QoS for each network generation
         engine.initialize_default_qos();
+        engine.initialize_default_roaming_policy();
         engine
     }
 
@@ -126,58 +163,305 @@ impl PolicyControlEngine {
         );
     }
 
-    /// Add or update a policy rule
-    pub fn add_policy_rule(&self, rule: PolicyRule) {
-        let key = format!(
-            "{}_{}_{}",
-            rule.plan_name.as_deref().unwrap_or("default"),
-            rule.service_type.as_deref().unwrap_or("default"),
-            rule.application_id.as_deref().unwrap_or("default")
+    /// Initialize the fallback policy for visited networks without a specific agreement
+    fn initialize_default_roaming_policy(&self) {
+        self.add_roaming_policy(RoamingPolicy {
+            policy_id: uuid::Uuid::new_v4(),
+            policy_name: "default_roaming".to_string(),
+            visited_plmn: None,
+            qos: QoS {
+                max_download_bandwidth_kbps: 5000, // 5 Mbps cap to control roaming cost
+                max_upload_bandwidth_kbps: 2000,   // 2 Mbps
+                qci: Some(9),
+                arp: Some(10),
+                gbr_download_kbps: None,
+                gbr_upload_kbps: None,
+                mbr_download_kbps: Some(5000),
+                mbr_upload_kbps: Some(2000),
+                priority: 3,
+                gating: false,
+            },
+            charging_rules: vec![ChargingRule {
+                rule_id: "roaming_default".to_string(),
+                service_identifier: None,
+                rating_group: None,
+                zero_rating: false,
+                charging_method: ChargingMethod::Online,
+                metering_method: "volume".to_string(),
+                unit_cost: None,
+            }],
+            active: true,
+        });
+    }
+
+    /// Add or update a roaming policy
+    pub fn add_roaming_policy(&self, policy: RoamingPolicy) {
+        let key = policy
+            .visited_plmn
+            .clone()
+            .unwrap_or_else(|| "default".to_string());
+        info!("Added roaming policy: {} ({})", policy.policy_name, key);
+        self.roaming_policies.insert(key, policy);
+    }
+
+    /// Get the roaming policy for a visited network
+    ///
+    /// Falls back to the default roaming policy when the visited PLMN has no
+    /// active policy of its own.
+    pub fn get_roaming_policy(&self, visited_plmn: &str) -> Option<RoamingPolicy> {
+        self.roaming_policies
+            .get(visited_plmn)
+            .filter(|p| p.active)
+            .or_else(|| self.roaming_policies.get("default").filter(|p| p.active))
+            .map(|p| p.value().clone())
+    }
+
+    /// Add or update a congestion rule
+    pub fn add_congestion_rule(&self, rule: CongestionRule) -> Result<(), PcfError> {
+        validate_congestion_rule(&rule)?;
+        info!(
+            "Added congestion rule: {} (from {:?})",
+            rule.rule_name, rule.min_congestion_level
         );
-        let key_clone = key.clone();
-        self.policy_rules.insert(key, rule);
-        info!("Added policy rule: {}", key_clone);
+        self.congestion_rules.insert(rule.rule_id, rule);
+        Ok(())
     }
 
-    /// Get policy rule
-    pub fn get_policy_rule(
+    /// Remove a congestion rule, returning whether it existed
+    pub fn remove_congestion_rule(&self, rule_id: uuid::Uuid) -> bool {
+        self.congestion_rules.remove(&rule_id).is_some()
+    }
+
+    /// Get all congestion rules
+    pub fn congestion_rules(&self) -> Vec<CongestionRule> {
+        self.congestion_rules
+            .iter()
+            .map(|r| r.value().clone())
+            .collect()
+    }
+
+    /// Apply the most aggressive congestion rule matching the request to `qos`
+    ///
+    /// Returns the downgraded QoS and the rule that fired, if any.
+    pub fn apply_congestion_rules(
         &self,
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+        qos: &QoS,
+    ) -> Option<(CongestionRule, QoS)> {
+        // Skip the scan for the common case of no congestion signal
+        request.congestion_level?;
+        let rules = self.congestion_rules();
+        most_aggressive_downgrade(&rules, request, &subscriber_profile.plan_name, qos)
+            .map(|(rule, qos)| (rule.clone(), qos))
+    }
+
+    /// Read the rule state shared by all requests
+    pub(crate) fn snapshot(&self) -> PolicyRuleSnapshot {
+        PolicyRuleSnapshot {
+            policy_rules: self
+                .policy_rules
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+            selection_mode: self.selection_mode(),
+            congestion_rules: self.congestion_rules(),
+        }
+    }
+
+    /// Add or update a policy rule
+    ///
+    /// Replaces the rule with the same rule ID. Other rules matching the same
+    /// conditions are kept as alternatives for the selection mode to choose from.
+    pub fn add_policy_rule(&self, rule: PolicyRule) {
+        let key = Self::policy_rule_key(&rule);
+        self.remove_policy_rule(rule.rule_id);
+        self.policy_rules.entry(key.clone()).or_default().push(rule);
+        info!("Added policy rule: {}", key);
+    }
+
+    /// Remove a policy rule, returning whether it existed
+    pub fn remove_policy_rule(&self, rule_id: uuid::Uuid) -> bool {
+        let mut removed = false;
+        for mut rules in self.policy_rules.iter_mut() {
+            let before = rules.len();
+            rules.retain(|r| r.rule_id != rule_id);
+            removed |= rules.len() != before;
+        }
+        self.policy_rules.retain(|_, rules| !rules.is_empty());
+        removed
+    }
+
+    /// Key of the conditions a rule matches
+    fn policy_rule_key(rule: &PolicyRule) -> String {
+        Self::conditions_key(
+            rule.plan_name.as_deref(),
+            rule.service_type.as_deref(),
+            rule.application_id.as_deref(),
+        )
+    }
+
+    fn conditions_key(
         plan_name: Option<&str>,
         service_type: Option<&str>,
         application_id: Option<&str>,
-    ) -> Option<PolicyRule> {
-        let key = format!(
+    ) -> String {
+        format!(
             "{}_{}_{}",
             plan_name.unwrap_or("default"),
             service_type.unwrap_or("default"),
             application_id.unwrap_or("default")
-        );
-        self.policy_rules.get(&key).map(|r| r.value().clone())
+        )
     }
 
-    /// Calculate QoS based on plan and service type
-    fn calculate_qos(
+    /// Get all policy rules
+    pub fn policy_rules(&self) -> Vec<PolicyRule> {
+        self.policy_rules
+            .iter()
+            .flat_map(|r| r.value().clone())
+            .collect()
+    }
+
+    /// Set how a rule is chosen among rules matching the same request
+    pub fn set_selection_mode(&self, mode: PolicySelectionMode) {
+        *self
+            .selection_mode
+            .write()
+            .expect("selection mode lock poisoned") = mode;
+        info!("Policy selection mode set to {:?}", mode);
+    }
+
+    /// Get the policy selection mode
+    pub fn selection_mode(&self) -> PolicySelectionMode {
+        *self
+            .selection_mode
+            .read()
+            .expect("selection mode lock poisoned")
+    }
+
+    /// Choose the active policy rule for a request, if any matches
+    ///
+    /// Rules restricted to a time window only match while the window is open
+    /// at the request's time of day.
+    pub fn select_policy_rule(
         &self,
         request: &PolicyRequest,
         subscriber_profile: &crate::models::SubscriberProfile,
-    ) -> QoS {
-        // Try to find a specific policy rule
-        let rule = self.get_policy_rule(
+    ) -> Option<PolicyRule> {
+        let key = Self::request_key(request, subscriber_profile);
+        let rules = self.policy_rules.get(&key)?;
+        choose_policy_rule(&rules, &key, request, self.selection_mode())
+    }
+
+    /// Key of the conditions a request matches
+    fn request_key(
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+    ) -> String {
+        Self::conditions_key(
             Some(&subscriber_profile.plan_name),
             Some(&request.service_type),
             request.application_id.as_deref(),
+        )
+    }
+
+    /// Report conflicting pairs among the loaded policy rules
+    pub fn validate_policies(&self) -> Vec<PolicyConflict> {
+        detect_conflicts(&self.policy_rules())
+    }
+
+    /// Add a set of policy rules only if they conflict neither with each other
+    /// nor with the loaded rules they do not replace
+    pub fn load_policy_rules(&self, rules: Vec<PolicyRule>) -> Result<(), PcfError> {
+        let new_ids: Vec<uuid::Uuid> = rules.iter().map(|r| r.rule_id).collect();
+        let mut candidate: Vec<PolicyRule> = self
+            .policy_rules()
+            .into_iter()
+            .filter(|r| !new_ids.contains(&r.rule_id))
+            .collect();
+        candidate.extend(rules.iter().cloned());
+
+        let conflicts = detect_conflicts(&candidate);
+        if !conflicts.is_empty() {
+            for conflict in &conflicts {
+                warn!("Policy conflict: {}", conflict);
+            }
+            return Err(PcfError::PolicyConflicts(conflicts));
+        }
+
+        for rule in rules {
+            self.add_policy_rule(rule);
+        }
+        Ok(())
+    }
+
+    /// Get policy rule
+    ///
+    /// Returns the highest-priority rule for the conditions, active or not.
+    pub fn get_policy_rule(
+        &self,
+        plan_name: Option<&str>,
+        service_type: Option<&str>,
+        application_id: Option<&str>,
+    ) -> Option<PolicyRule> {
+        let key = Self::conditions_key(plan_name, service_type, application_id);
+        let rules = self.policy_rules.get(&key)?;
+        highest_priority(&rules.iter().collect::<Vec<_>>()).cloned()
+    }
+
+    /// Evaluate QoS for a request against a rule snapshot, returning the
+    /// policy rule it was taken from
+    pub(crate) fn evaluate_qos(
+        &self,
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+        rules: &PolicyRuleSnapshot,
+    ) -> Result<(QoS, Option<PolicyRule>), PcfError> {
+        info!(
+            "Evaluating policy for subscriber: {}, service: {}, network: {:?}",
+            request.subscriber_id, request.service_type, request.network_generation
         );
 
-        if let Some(policy_rule) = rule {
-            if policy_rule.active {
+        // Check if network generation is supported
+        if !subscriber_profile
+            .supported_networks
+            .contains(&request.network_generation)
+        {
+            return Err(PcfError::UnsupportedNetworkGeneration(format!(
+                "Subscriber {} does not support {:?}",
+                request.subscriber_id, request.network_generation
+            )));
+        }
+
+        let policy_rule = rules.select_policy_rule(request, subscriber_profile);
+        let qos = match policy_rule {
+            Some(ref policy_rule) => {
                 debug!(
                     "Using policy rule: {} for subscriber {}",
                     policy_rule.rule_name, request.subscriber_id
                 );
-                return policy_rule.qos.clone();
+                policy_rule.qos.clone()
             }
-        }
+            None => self.calculate_qos(request, subscriber_profile),
+        };
+
+        debug!(
+            "Policy decision for {}: QoS priority={}, download={} Kbps, upload={} Kbps",
+            request.subscriber_id,
+            qos.priority,
+            qos.max_download_bandwidth_kbps,
+            qos.max_upload_bandwidth_kbps
+        );
+
+        Ok((qos, policy_rule))
+    }
 
+    /// Calculate QoS based on plan and service type when no policy rule matches
+    fn calculate_qos(
+        &self,
+        request: &PolicyRequest,
+        subscriber_profile: &crate::models::SubscriberProfile,
+    ) -> QoS {
         // Fall back to default QoS for network generation
         let default_qos = self
             .default_qos
@@ -265,33 +549,8 @@ impl PolicyControlTrait for PolicyControlEngine {
         request: &PolicyRequest,
         subscriber_profile: &crate::models::SubscriberProfile,
     ) -> Result<QoS, PcfError> {
-        info!(
-            "Evaluating policy for subscriber: {}, service: {}, network: {:?}",
-            request.subscriber_id, request.service_type, request.network_generation
-        );
-
-        // Check if network generation is supported
-        if !subscriber_profile
-            .supported_networks
-            .contains(&request.network_generation)
-        {
-            return Err(PcfError::UnsupportedNetworkGeneration(format!(
-                "Subscriber {} does not support {:?}",
-                request.subscriber_id, request.network_generation
-            )));
-        }
-
-        let qos = self.calculate_qos(request, subscriber_profile);
-
-        debug!(
-            "Policy decision for {}: QoS priority={}, download={} Kbps, upload={} Kbps",
-            request.subscriber_id,
-            qos.priority,
-            qos.max_download_bandwidth_kbps,
-            qos.max_upload_bandwidth_kbps
-        );
-
-        Ok(qos)
+        self.evaluate_qos(request, subscriber_profile, &self.snapshot())
+            .map(|(qos, _)| qos)
     }
 
     async fn get_qos_for_service(
@@ -328,3 +587,219 @@ impl Default for PolicyControlEngine {
         Self::new()
     }
 }
+
+/// Active rule chosen among the rules matching a request's conditions
+fn choose_policy_rule(
+    rules: &[PolicyRule],
+    key: &str,
+    request: &PolicyRequest,
+    mode: PolicySelectionMode,
+) -> Option<PolicyRule> {
+    let active: Vec<&PolicyRule> = rules
+        .iter()
+        .filter(|r| r.active && window_applies(r.time_window.as_ref(), request.time_of_day))
+        .collect();
+
+    match mode {
+        PolicySelectionMode::Deterministic => highest_priority(&active),
+        PolicySelectionMode::Weighted => {
+            weighted_choice(&active, &format!("{}:{}", key, request.subscriber_id))
+                .or_else(|| highest_priority(&active))
+        }
+    }
+    .cloned()
+}
+
+/// Highest-priority rule; among equal priorities the latest added
+fn highest_priority<'a>(rules: &[&'a PolicyRule]) -> Option<&'a PolicyRule> {
+    // max_by_key returns the last of equal maxima
+    rules.iter().max_by_key(|r| r.priority).copied()
+}
+
+/// Rule chosen for a selection key in proportion to the rules' weights
+///
+/// Rules are ordered by ID and the key is hashed with FNV-1a, so the same key
+/// lands on the same rule on every instance and after restarts.
+fn weighted_choice<'a>(rules: &[&'a PolicyRule], selection_key: &str) -> Option<&'a PolicyRule> {
+    let mut rules = rules.to_vec();
+    rules.sort_by_key(|r| r.rule_id);
+
+    let total: u64 = rules.iter().map(|r| r.weight.unwrap_or(1) as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut bucket = fnv1a(selection_key.as_bytes()) % total;
+    for rule in rules {
+        let weight = rule.weight.unwrap_or(1) as u64;
+        if bucket < weight {
+            return Some(rule);
+        }
+        bucket -= weight;
+    }
+    None
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SubscriberProfile;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn rule(name: &str, priority: u32, weight: Option<u32>) -> PolicyRule {
+        PolicyRule {
+            rule_id: Uuid::new_v4(),
+            rule_name: name.to_string(),
+            plan_name: Some("Premium Unlimited".to_string()),
+            service_type: Some("video_streaming".to_string()),
+            application_id: None,
+            qos: QoS::default(),
+            charging_rules: vec![],
+            priority,
+            active: true,
+            valid_from: None,
+            valid_to: None,
+            required_network_generation: None,
+            weight,
+            time_window: None,
+        }
+    }
+
+    fn request(subscriber_id: &str) -> PolicyRequest {
+        #[allow(deprecated)]
+        PolicyRequest {
+            subscriber_id: subscriber_id.to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            network_generation: NetworkGeneration::FourG,
+            apn: "internet".to_string(),
+            service_type: "video_streaming".to_string(),
+            application_id: None,
+            location: None,
+            time_of_day: None,
+            roaming: None,
+            sni: None,
+            destination_port: None,
+            congestion_level: None,
+        }
+    }
+
+    fn profile() -> SubscriberProfile {
+        #[allow(deprecated)]
+        SubscriberProfile {
+            subscriber_id: "any".to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            plan_name: "Premium Unlimited".to_string(),
+            plan_type: "postpaid".to_string(),
+            quota: crate::models::Quota {
+                total_quota_bytes: 1_000,
+                used_quota_bytes: 0,
+                remaining_quota_bytes: 1_000,
+                notification_threshold_percent: 80,
+                exceeded: false,
+                throttled_bandwidth_kbps: None,
+                last_update: Utc::now(),
+                rollover: None,
+                rolled_over: None,
+            },
+            active_policies: vec![],
+            zero_rated_services: vec![],
+            supported_networks: vec![NetworkGeneration::FourG],
+            last_update: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_deterministic_selection_prefers_priority() {
+        let engine = PolicyControlEngine::new();
+        let high = rule("high", 10, None);
+        engine.add_policy_rule(high.clone());
+        engine.add_policy_rule(rule("low", 1, None));
+
+        let selected = engine.select_policy_rule(&request("sub"), &profile());
+        assert_eq!(selected.unwrap().rule_id, high.rule_id);
+    }
+
+    #[test]
+    fn test_weighted_selection_is_stable_and_proportional() {
+        let engine = PolicyControlEngine::new();
+        engine.set_selection_mode(PolicySelectionMode::Weighted);
+        let control = rule("control", 1, Some(75));
+        let variant = rule("variant", 1, Some(25));
+        let disabled = rule("disabled", 1, Some(0));
+        engine.add_policy_rule(control.clone());
+        engine.add_policy_rule(variant.clone());
+        engine.add_policy_rule(disabled.clone());
+
+        let profile = profile();
+        let mut variant_count = 0;
+        for i in 0..1_000 {
+            let request = request(&format!("sub-{}", i));
+            let first = engine.select_policy_rule(&request, &profile).unwrap();
+            let second = engine.select_policy_rule(&request, &profile).unwrap();
+            assert_eq!(first.rule_id, second.rule_id);
+            assert_ne!(first.rule_id, disabled.rule_id);
+            if first.rule_id == variant.rule_id {
+                variant_count += 1;
+            }
+        }
+        assert!((150..350).contains(&variant_count), "{}", variant_count);
+    }
+
+    #[test]
+    fn test_time_window_gates_selection() {
+        use crate::time_window::TimeWindow;
+        use chrono::{NaiveTime, TimeZone};
+
+        let engine = PolicyControlEngine::new();
+        let default = rule("default", 1, None);
+        let mut night = rule("night_unlimited", 10, None);
+        night.time_window = Some(TimeWindow::daily(
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            chrono_tz::America::Sao_Paulo,
+        ));
+        engine.add_policy_rule(default.clone());
+        engine.add_policy_rule(night.clone());
+
+        // Sao Paulo is UTC-3: 01:30 UTC is 22:30 local, 15:00 UTC is 12:00 local
+        let mut request = request("sub");
+        request.time_of_day = Some(Utc.with_ymd_and_hms(2026, 3, 3, 1, 30, 0).unwrap());
+        let selected = engine.select_policy_rule(&request, &profile()).unwrap();
+        assert_eq!(selected.rule_id, night.rule_id);
+
+        request.time_of_day = Some(Utc.with_ymd_and_hms(2026, 3, 3, 15, 0, 0).unwrap());
+        let selected = engine.select_policy_rule(&request, &profile()).unwrap();
+        assert_eq!(selected.rule_id, default.rule_id);
+
+        request.time_of_day = None;
+        let selected = engine.select_policy_rule(&request, &profile()).unwrap();
+        assert_eq!(selected.rule_id, default.rule_id);
+    }
+
+    #[test]
+    fn test_snapshot_ignores_later_rule_changes() {
+        let engine = PolicyControlEngine::new();
+        let low = rule("low", 1, None);
+        engine.add_policy_rule(low.clone());
+        let snapshot = engine.snapshot();
+
+        let high = rule("high", 10, None);
+        engine.add_policy_rule(high.clone());
+
+        let selected = snapshot.select_policy_rule(&request("sub"), &profile());
+        assert_eq!(selected.unwrap().rule_id, low.rule_id);
+        let selected = engine.select_policy_rule(&request("sub"), &profile());
+        assert_eq!(selected.unwrap().rule_id, high.rule_id);
+    }
+}
@@ -0,0 +1,261 @@
+//! Policy Conflict Detection
+//!
+//! Finds pairs of active policy rules that can apply to the same request but
+//! prescribe contradictory actions, so authoring mistakes are reported when
+//! rules are loaded instead of surfacing as inconsistent decisions at runtime.
+//!
+//! Two rules overlap when they agree on every condition dimension: plan,
+//! service type, application, required network generation, validity period
+//! and time window. An unset dimension matches any value. Overlapping rules conflict
+//! when one grants access and the other gates it, or when one zero-rates a
+//! service that the other charges for.
+
+use crate::models::PolicyRule;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// Kind of contradiction between two overlapping rules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyConflictKind {
+    /// One rule grants access, the other gates it
+    AccessConflict,
+    /// One rule zero-rates a service the other charges for
+    ZeroRatingConflict {
+        /// Service identifier of the contradicting charging rules
+        service_identifier: String,
+    },
+}
+
+/// Pair of conflicting policy rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyConflict {
+    /// First rule ID
+    pub rule_id: Uuid,
+    /// First rule name
+    pub rule_name: String,
+    /// Second rule ID
+    pub conflicting_rule_id: Uuid,
+    /// Second rule name
+    pub conflicting_rule_name: String,
+    /// What the rules disagree on
+    pub kind: PolicyConflictKind,
+}
+
+impl fmt::Display for PolicyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PolicyConflictKind::AccessConflict => write!(
+                f,
+                "rules '{}' and '{}' overlap but disagree on access",
+                self.rule_name, self.conflicting_rule_name
+            ),
+            PolicyConflictKind::ZeroRatingConflict { service_identifier } => write!(
+                f,
+                "rules '{}' and '{}' overlap but disagree on zero-rating of '{}'",
+                self.rule_name, self.conflicting_rule_name, service_identifier
+            ),
+        }
+    }
+}
+
+/// Find every pair of active rules with overlapping conditions and
+/// conflicting actions
+pub fn detect_conflicts(rules: &[PolicyRule]) -> Vec<PolicyConflict> {
+    let active: Vec<&PolicyRule> = rules.iter().filter(|r| r.active).collect();
+    let mut conflicts = Vec::new();
+
+    for (i, a) in active.iter().enumerate() {
+        for b in &active[i + 1..] {
+            if !rules_overlap(a, b) {
+                continue;
+            }
+            for kind in conflicting_actions(a, b) {
+                conflicts.push(PolicyConflict {
+                    rule_id: a.rule_id,
+                    rule_name: a.rule_name.clone(),
+                    conflicting_rule_id: b.rule_id,
+                    conflicting_rule_name: b.rule_name.clone(),
+                    kind,
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Whether some request can match both rules
+fn rules_overlap(a: &PolicyRule, b: &PolicyRule) -> bool {
+    dimension_overlaps(a.plan_name.as_deref(), b.plan_name.as_deref())
+        && dimension_overlaps(a.service_type.as_deref(), b.service_type.as_deref())
+        && dimension_overlaps(a.application_id.as_deref(), b.application_id.as_deref())
+        && match (a.required_network_generation, b.required_network_generation) {
+            (Some(x), Some(y)) => x == y,
+            _ => true,
+        }
+        && validity_overlaps(a.valid_from, a.valid_to, b.valid_from, b.valid_to)
+        && match (&a.time_window, &b.time_window) {
+            (Some(x), Some(y)) => x.overlaps(y),
+            _ => true,
+        }
+}
+
+fn dimension_overlaps(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a, b) {
+        (Some(x), Some(y)) => x.eq_ignore_ascii_case(y),
+        _ => true,
+    }
+}
+
+fn validity_overlaps(
+    a_from: Option<DateTime<Utc>>,
+    a_to: Option<DateTime<Utc>>,
+    b_from: Option<DateTime<Utc>>,
+    b_to: Option<DateTime<Utc>>,
+) -> bool {
+    let starts_before_end =
+        |from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>| match (from, to) {
+            (Some(from), Some(to)) => from < to,
+            _ => true,
+        };
+    starts_before_end(a_from, b_to) && starts_before_end(b_from, a_to)
+}
+
+fn conflicting_actions(a: &PolicyRule, b: &PolicyRule) -> Vec<PolicyConflictKind> {
+    let mut kinds = Vec::new();
+
+    if a.qos.gating != b.qos.gating {
+        kinds.push(PolicyConflictKind::AccessConflict);
+    }
+
+    for a_rule in &a.charging_rules {
+        let Some(service) = a_rule.service_identifier.as_deref() else {
+            continue;
+        };
+        let contradicts = b.charging_rules.iter().any(|b_rule| {
+            b_rule
+                .service_identifier
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case(service))
+                && b_rule.zero_rating != a_rule.zero_rating
+        });
+        let already_reported = kinds.iter().any(|k| {
+            matches!(k, PolicyConflictKind::ZeroRatingConflict { service_identifier }
+                if service_identifier.eq_ignore_ascii_case(service))
+        });
+        if contradicts && !already_reported {
+            kinds.push(PolicyConflictKind::ZeroRatingConflict {
+                service_identifier: service.to_string(),
+            });
+        }
+    }
+
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChargingMethod, ChargingRule, NetworkGeneration, QoS};
+    use crate::time_window::TimeWindow;
+    use chrono::NaiveTime;
+
+    fn rule(name: &str, service_type: Option<&str>, gating: bool) -> PolicyRule {
+        PolicyRule {
+            rule_id: Uuid::new_v4(),
+            rule_name: name.to_string(),
+            plan_name: Some("Premium Unlimited".to_string()),
+            service_type: service_type.map(str::to_string),
+            application_id: None,
+            qos: QoS {
+                gating,
+                ..Default::default()
+            },
+            charging_rules: vec![],
+            priority: 1,
+            active: true,
+            valid_from: None,
+            valid_to: None,
+            required_network_generation: None,
+            weight: None,
+            time_window: None,
+        }
+    }
+
+    fn hm(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn charging(service: &str, zero_rating: bool) -> ChargingRule {
+        ChargingRule {
+            rule_id: format!("{}_rule", service),
+            service_identifier: Some(service.to_string()),
+            rating_group: None,
+            zero_rating,
+            charging_method: ChargingMethod::Online,
+            metering_method: "volume".to_string(),
+            unit_cost: None,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_overlap_with_contradicting_access() {
+        let grant = rule("grant_all", None, false);
+        let deny = rule("block_video", Some("video"), true);
+
+        let conflicts = detect_conflicts(&[grant.clone(), deny.clone()]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].rule_id, grant.rule_id);
+        assert_eq!(conflicts[0].conflicting_rule_id, deny.rule_id);
+        assert_eq!(conflicts[0].kind, PolicyConflictKind::AccessConflict);
+    }
+
+    #[test]
+    fn test_disjoint_conditions_do_not_conflict() {
+        let video = rule("block_video", Some("video"), true);
+        let voice = rule("allow_voice", Some("voice"), false);
+
+        let mut five_g = rule("allow_video_5g", Some("video"), false);
+        five_g.required_network_generation = Some(NetworkGeneration::FiveG);
+        let mut four_g = rule("block_video_4g", Some("video"), true);
+        four_g.required_network_generation = Some(NetworkGeneration::FourG);
+
+        let mut inactive = rule("allow_video", Some("video"), false);
+        inactive.active = false;
+
+        let now = Utc::now();
+        let mut earlier = rule("allow_video_before", Some("video"), false);
+        earlier.valid_to = Some(now);
+        let mut later = rule("block_video_after", Some("video"), true);
+        later.valid_from = Some(now);
+        let mut night = rule("allow_video_at_night", Some("video"), false);
+        night.time_window = Some(TimeWindow::daily(hm(22), hm(6), chrono_tz::UTC));
+        let mut day = rule("block_video_by_day", Some("video"), true);
+        day.time_window = Some(TimeWindow::daily(hm(6), hm(22), chrono_tz::UTC));
+
+        assert!(detect_conflicts(&[video.clone(), voice]).is_empty());
+        assert!(detect_conflicts(&[five_g, four_g]).is_empty());
+        assert!(detect_conflicts(&[video, inactive]).is_empty());
+        assert!(detect_conflicts(&[earlier, later]).is_empty());
+        assert!(detect_conflicts(&[night, day]).is_empty());
+    }
+
+    #[test]
+    fn test_zero_rating_conflict() {
+        let mut a = rule("whatsapp_free", None, false);
+        a.charging_rules = vec![charging("whatsapp.com", true)];
+        let mut b = rule("whatsapp_charged", Some("messaging"), false);
+        b.charging_rules = vec![charging("WhatsApp.com", false)];
+
+        let conflicts = detect_conflicts(&[a, b]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].kind,
+            PolicyConflictKind::ZeroRatingConflict {
+                service_identifier: "whatsapp.com".to_string()
+            }
+        );
+    }
+}
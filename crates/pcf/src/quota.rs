@@ -1,10 +1,15 @@
 //! Quota Management Module
 //!
-//! Handles data quota tracking, monitoring, and throttling
+//! Handles data quota tracking, monitoring, and throttling, including the
+//! rollover of unused quota into the next billing period
 
 use crate::error::PcfError;
-use crate::models::{Quota, QuotaNotification, QuotaNotificationType};
+use crate::models::{
+    Quota, QuotaNotification, QuotaNotificationType, RolledOverQuota, RolloverConfig,
+};
 use async_trait::async_trait;
+use chrono::Utc;
+use dashmap::mapref::one::RefMut;
 use dashmap::DashMap;
 use log::{debug, info, warn};
 use std::sync::Arc;
@@ -22,6 +27,13 @@ pub trait QuotaManagerTrait: Send + Sync {
         bytes_used: u64,
     ) -> Result<Quota, PcfError>;
 
+    /// Atomically consume bytes from the allowance
+    ///
+    /// Fails without consuming anything if the request exceeds the remaining
+    /// allowance, so concurrent callers can never overspend. Returns the
+    /// remaining bytes after consumption.
+    async fn consume(&self, subscriber_id: &str, bytes: u64) -> Result<u64, PcfError>;
+
     /// Check if quota threshold is reached
     async fn check_threshold(&self, subscriber_id: &str) -> Result<Option<QuotaNotification>, PcfError>;
 
@@ -71,6 +83,8 @@ impl QuotaManager {
             exceeded: false,
             throttled_bandwidth_kbps: None,
             last_update: chrono::Utc::now(),
+            rollover: None,
+            rolled_over: None,
         };
 
         let subscriber_id_clone = subscriber_id.clone();
@@ -84,42 +98,96 @@ impl QuotaManager {
         );
     }
 
-    /// Calculate throttled bandwidth based on usage
-    fn calculate_throttled_bandwidth(&self, quota: &Quota) -> Option<u64> {
-        if quota.exceeded {
-            // When quota is exceeded, throttle to 64 Kbps (typical fair use policy)
-            Some(64)
-        } else {
-            // Progressive throttling as quota approaches limit
-            let usage_percent = quota.usage_percent();
-            if usage_percent >= 95.0 {
-                Some(128) // 128 Kbps when at 95%+
-            } else if usage_percent >= 90.0 {
-                Some(256) // 256 Kbps when at 90%+
-            } else {
-                None // No throttling
-            }
-        }
-    }
-}
-
-#[async_trait]
-impl QuotaManagerTrait for QuotaManager {
-    async fn get_quota(&self, subscriber_id: &str) -> Result<Option<Quota>, PcfError> {
-        Ok(self.quota_cache.get(subscriber_id).map(|q| q.value().clone()))
+    /// Set or clear the rollover configuration of a subscriber's quota
+    ///
+    /// Takes effect at the next [`QuotaManager::apply_rollover`].
+    pub fn set_rollover_config(
+        &self,
+        subscriber_id: &str,
+        rollover: Option<RolloverConfig>,
+    ) -> Result<(), PcfError> {
+        let mut entry = self.quota_entry(subscriber_id)?;
+        entry.rollover = rollover;
+        Ok(())
     }
 
-    async fn update_quota_usage(
+    /// Start a new billing period, carrying unused quota forward
+    ///
+    /// The new period starts with `new_quota_bytes` plus the unused part of
+    /// the previous period's own allowance, capped at the configured maximum.
+    /// Carried bytes expire `expiry_days` after the period starts and are
+    /// consumed before the new allowance. Bytes that were themselves carried
+    /// over are never carried again. Without a rollover configuration this is
+    /// a plain reset.
+    pub fn apply_rollover(
         &self,
         subscriber_id: &str,
-        bytes_used: u64,
+        new_quota_bytes: u64,
     ) -> Result<Quota, PcfError> {
-        let mut quota = self
-            .quota_cache
-            .get(subscriber_id)
-            .ok_or_else(|| PcfError::QuotaExceeded(format!("Quota not found for {}", subscriber_id)))?
-            .value()
-            .clone();
+        let now = Utc::now();
+        let mut entry = self.quota_entry(subscriber_id)?;
+
+        let rollover = entry.rollover.clone();
+        let rolled_over = rollover.as_ref().and_then(|config| {
+            let carried = entry.fresh_remaining_bytes().min(config.max_carry_bytes);
+            (carried > 0).then(|| RolledOverQuota {
+                carried_bytes: carried,
+                remaining_bytes: carried,
+                expires_at: now + chrono::Duration::days(i64::from(config.expiry_days)),
+            })
+        });
+        let carried = rolled_over.as_ref().map_or(0, |r| r.carried_bytes);
+        let total_quota_bytes = new_quota_bytes + carried;
+
+        let quota = Quota {
+            total_quota_bytes,
+            used_quota_bytes: 0,
+            remaining_quota_bytes: total_quota_bytes,
+            notification_threshold_percent: entry.notification_threshold_percent,
+            exceeded: false,
+            throttled_bandwidth_kbps: None,
+            last_update: now,
+            rollover,
+            rolled_over,
+        };
+        *entry.value_mut() = quota.clone();
+        drop(entry);
+        self.throttled_bandwidth.remove(subscriber_id);
+
+        info!(
+            "Started new quota period for subscriber {}: {} bytes ({} rolled over)",
+            subscriber_id, total_quota_bytes, carried
+        );
+
+        Ok(quota)
+    }
+
+    /// Lock a subscriber's quota, forfeiting carried bytes that have expired
+    fn quota_entry(&self, subscriber_id: &str) -> Result<RefMut<'_, String, Quota>, PcfError> {
+        let mut entry = self.quota_cache.get_mut(subscriber_id).ok_or_else(|| {
+            PcfError::QuotaExceeded(format!("Quota not found for {}", subscriber_id))
+        })?;
+
+        let forfeited = entry.expire_rollover(Utc::now());
+        if forfeited > 0 {
+            info!(
+                "Expired {} rolled-over bytes for subscriber {}",
+                forfeited, subscriber_id
+            );
+            self.apply_usage(subscriber_id, entry.value_mut(), 0);
+        }
+
+        Ok(entry)
+    }
+
+    /// Record usage on a quota and refresh derived state
+    ///
+    /// Callers must hold the cache entry lock for `quota`.
+    fn apply_usage(&self, subscriber_id: &str, quota: &mut Quota, bytes_used: u64) {
+        // Rolled-over bytes are used up first
+        if let Some(rolled_over) = quota.rolled_over.as_mut() {
+            rolled_over.remaining_bytes -= bytes_used.min(rolled_over.remaining_bytes);
+        }
 
         // Update usage
         quota.used_quota_bytes += bytes_used;
@@ -133,7 +201,7 @@ impl QuotaManagerTrait for QuotaManager {
         quota.exceeded = quota.used_quota_bytes >= quota.total_quota_bytes;
 
         // Update throttled bandwidth
-        quota.throttled_bandwidth_kbps = self.calculate_throttled_bandwidth(&quota);
+        quota.throttled_bandwidth_kbps = self.calculate_throttled_bandwidth(quota);
 
         // If throttling is needed, store it
         if let Some(bandwidth) = quota.throttled_bandwidth_kbps {
@@ -149,9 +217,6 @@ impl QuotaManagerTrait for QuotaManager {
             );
         }
 
-        // Update cache
-        self.quota_cache.insert(subscriber_id.to_string(), quota.clone());
-
         debug!(
             "Updated quota for {}: {}/{} bytes ({}%)",
             subscriber_id,
@@ -159,17 +224,68 @@ impl QuotaManagerTrait for QuotaManager {
             quota.total_quota_bytes,
             quota.usage_percent()
         );
+    }
 
-        Ok(quota)
+    /// Calculate throttled bandwidth based on usage
+    fn calculate_throttled_bandwidth(&self, quota: &Quota) -> Option<u64> {
+        if quota.exceeded {
+            // When quota is exceeded, throttle to 64 Kbps (typical fair use policy)
+            Some(64)
+        } else {
+            // Progressive throttling as quota approaches limit
+            let usage_percent = quota.usage_percent();
+            if usage_percent >= 95.0 {
+                Some(128) // 128 Kbps when at 95%+
+            } else if usage_percent >= 90.0 {
+                Some(256) // 256 Kbps when at 90%+
+            } else {
+                None // No throttling
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl QuotaManagerTrait for QuotaManager {
+    async fn get_quota(&self, subscriber_id: &str) -> Result<Option<Quota>, PcfError> {
+        if !self.quota_cache.contains_key(subscriber_id) {
+            return Ok(None);
+        }
+        Ok(Some(self.quota_entry(subscriber_id)?.value().clone()))
+    }
+
+    async fn update_quota_usage(
+        &self,
+        subscriber_id: &str,
+        bytes_used: u64,
+    ) -> Result<Quota, PcfError> {
+        // Hold the entry lock for the whole read-modify-write so concurrent
+        // updates for the same subscriber are serialized
+        let mut entry = self.quota_entry(subscriber_id)?;
+
+        self.apply_usage(subscriber_id, entry.value_mut(), bytes_used);
+
+        Ok(entry.value().clone())
+    }
+
+    async fn consume(&self, subscriber_id: &str, bytes: u64) -> Result<u64, PcfError> {
+        let mut entry = self.quota_entry(subscriber_id)?;
+
+        // Check and decrement under the same lock so the allowance is never double-spent
+        if bytes > entry.remaining_quota_bytes {
+            return Err(PcfError::QuotaExceeded(format!(
+                "{}: requested {} bytes, {} remaining",
+                subscriber_id, bytes, entry.remaining_quota_bytes
+            )));
+        }
+
+        self.apply_usage(subscriber_id, entry.value_mut(), bytes);
+
+        Ok(entry.remaining_quota_bytes)
     }
 
     async fn check_threshold(&self, subscriber_id: &str) -> Result<Option<QuotaNotification>, PcfError> {
-        let quota = self
-            .quota_cache
-            .get(subscriber_id)
-            .ok_or_else(|| PcfError::QuotaExceeded(format!("Quota not found for {}", subscriber_id)))?
-            .value()
-            .clone();
+        let quota = self.quota_entry(subscriber_id)?.value().clone();
 
         // Check if threshold notification should be sent
         if quota.should_notify() {
@@ -210,6 +326,10 @@ impl QuotaManagerTrait for QuotaManager {
             .get(subscriber_id)
             .map(|t| *t.value())
             .unwrap_or(80);
+        let rollover = self
+            .quota_cache
+            .get(subscriber_id)
+            .and_then(|q| q.rollover.clone());
 
         let quota = Quota {
             total_quota_bytes: new_quota_bytes,
@@ -219,6 +339,8 @@ impl QuotaManagerTrait for QuotaManager {
             exceeded: false,
             throttled_bandwidth_kbps: None,
             last_update: chrono::Utc::now(),
+            rollover,
+            rolled_over: None,
         };
 
         self.quota_cache.insert(subscriber_id.to_string(), quota.clone());
@@ -259,3 +381,111 @@ impl Default for QuotaManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_concurrent_consume_never_exceeds_allowance() {
+        let manager = Arc::new(QuotaManager::new());
+        manager.initialize_quota("shared".to_string(), 1_000, 80);
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                tokio::spawn(async move { manager.consume("shared", 30).await })
+            })
+            .collect();
+
+        let mut granted = 0;
+        for handle in handles {
+            if handle.await.unwrap().is_ok() {
+                granted += 1;
+            }
+        }
+
+        let quota = manager.get_quota("shared").await.unwrap().unwrap();
+        assert_eq!(granted, 33);
+        assert_eq!(quota.used_quota_bytes, 990);
+        assert_eq!(quota.remaining_quota_bytes, 10);
+    }
+
+    #[tokio::test]
+    async fn test_consume_returns_remaining() {
+        let manager = QuotaManager::new();
+        manager.initialize_quota("sub".to_string(), 100, 80);
+
+        assert_eq!(manager.consume("sub", 40).await.unwrap(), 60);
+        assert!(manager.consume("sub", 61).await.is_err());
+        assert_eq!(manager.consume("sub", 60).await.unwrap(), 0);
+    }
+
+    fn rollover_manager(total: u64, max_carry_bytes: u64) -> QuotaManager {
+        let manager = QuotaManager::new();
+        manager.initialize_quota("sub".to_string(), total, 80);
+        manager
+            .set_rollover_config(
+                "sub",
+                Some(RolloverConfig {
+                    max_carry_bytes,
+                    expiry_days: 10,
+                }),
+            )
+            .unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_rollover_is_capped() {
+        let manager = rollover_manager(1_000, 300);
+        manager.consume("sub", 400).await.unwrap();
+
+        let quota = manager.apply_rollover("sub", 1_000).unwrap();
+        assert_eq!(quota.total_quota_bytes, 1_300);
+        assert_eq!(quota.remaining_quota_bytes, 1_300);
+        assert_eq!(quota.rolled_over.unwrap().carried_bytes, 300);
+
+        // Without a rollover configuration nothing is carried over
+        manager.set_rollover_config("sub", None).unwrap();
+        let quota = manager.apply_rollover("sub", 1_000).unwrap();
+        assert_eq!(quota.total_quota_bytes, 1_000);
+        assert!(quota.rolled_over.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rolled_over_bytes_are_consumed_first_and_not_carried_again() {
+        let manager = rollover_manager(1_000, 500);
+        manager.consume("sub", 800).await.unwrap();
+        manager.apply_rollover("sub", 1_000).unwrap();
+
+        manager.consume("sub", 150).await.unwrap();
+        let quota = manager.get_quota("sub").await.unwrap().unwrap();
+        assert_eq!(quota.rolled_over.as_ref().unwrap().remaining_bytes, 50);
+        assert_eq!(quota.fresh_remaining_bytes(), 1_000);
+
+        // Only the unused part of the fresh allowance rolls over
+        let quota = manager.apply_rollover("sub", 1_000).unwrap();
+        assert_eq!(quota.total_quota_bytes, 1_500);
+    }
+
+    #[tokio::test]
+    async fn test_unused_rolled_over_bytes_expire_mid_cycle() {
+        let manager = rollover_manager(1_000, 500);
+        manager.consume("sub", 600).await.unwrap();
+        manager.apply_rollover("sub", 1_000).unwrap();
+        manager.consume("sub", 100).await.unwrap();
+
+        let mut entry = manager.quota_cache.get_mut("sub").unwrap();
+        entry.rolled_over.as_mut().unwrap().expires_at = Utc::now() - chrono::Duration::seconds(1);
+        drop(entry);
+
+        // The 300 unused carried bytes are forfeited, the 100 used stay used
+        let quota = manager.get_quota("sub").await.unwrap().unwrap();
+        assert_eq!(quota.total_quota_bytes, 1_100);
+        assert_eq!(quota.used_quota_bytes, 100);
+        assert_eq!(quota.remaining_quota_bytes, 1_000);
+        assert!(manager.consume("sub", 1_001).await.is_err());
+        assert_eq!(manager.consume("sub", 1_000).await.unwrap(), 0);
+    }
+}
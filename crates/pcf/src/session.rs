@@ -0,0 +1,410 @@
+//! Session Store Module
+//!
+//! Keeps PCF session state behind a pluggable store so that it can be shared
+//! between PCF instances and survive restarts. Every session is stored with a
+//! time-to-live; a session that is not refreshed before it expires is gone, so
+//! sessions whose terminate request was never received do not leak.
+
+use crate::error::PcfError;
+use crate::models::{PolicyDecision, Quota};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// State of an active policy session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PcfSession {
+    /// Diameter Session-Id
+    pub session_id: String,
+    /// Subscriber ID
+    pub subscriber_id: String,
+    /// APN the session was opened on
+    pub apn: String,
+    /// Last policy decision sent for the session
+    pub policy_decision: Option<PolicyDecision>,
+    /// Quota snapshot at the last update
+    pub quota: Option<Quota>,
+    /// Session start time
+    pub created_at: DateTime<Utc>,
+    /// Last update time
+    pub last_update: DateTime<Utc>,
+    /// Incremented on every update, so concurrent updates can detect each other
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// Session store trait
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Get a session, or `None` if it does not exist or has expired
+    async fn get(&self, session_id: &str) -> Result<Option<PcfSession>, PcfError>;
+
+    /// Insert or replace a session, expiring it after `ttl`
+    async fn put(&self, session: &PcfSession, ttl: Duration) -> Result<(), PcfError>;
+
+    /// Replace a session only if the stored one still has `expected_version`,
+    /// expiring it after `ttl`
+    ///
+    /// Returns false without storing anything if the session was changed,
+    /// removed or has expired since it was read.
+    async fn replace_if_version(
+        &self,
+        session: &PcfSession,
+        expected_version: u64,
+        ttl: Duration,
+    ) -> Result<bool, PcfError>;
+
+    /// Remove a session, returning whether it existed
+    async fn remove(&self, session_id: &str) -> Result<bool, PcfError>;
+
+    /// Reset the time-to-live of a session, returning whether it existed
+    async fn expire(&self, session_id: &str, ttl: Duration) -> Result<bool, PcfError>;
+}
+
+/// In-memory session store for single-instance deployments and tests
+///
+/// Expired sessions are never returned. They are dropped when next accessed
+/// and by [`InMemorySessionStore::purge_expired`].
+pub struct InMemorySessionStore {
+    sessions: Arc<DashMap<String, (PcfSession, Instant)>>,
+}
+
+impl InMemorySessionStore {
+    /// Create a new in-memory session store
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Number of stored sessions, including expired ones not yet purged
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether the store holds no sessions
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Drop all expired sessions, returning how many were dropped
+    pub fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let before = self.sessions.len();
+        self.sessions.retain(|_, (_, expires_at)| *expires_at > now);
+        let purged = before.saturating_sub(self.sessions.len());
+        if purged > 0 {
+            debug!("Purged {} expired PCF sessions", purged);
+        }
+        purged
+    }
+
+    /// Purge expired sessions periodically in the background
+    pub fn spawn_purge(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                store.purge_expired();
+            }
+        })
+    }
+}
+
+impl Default for InMemorySessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SessionStore for InMemorySessionStore {
+    async fn get(&self, session_id: &str) -> Result<Option<PcfSession>, PcfError> {
+        let now = Instant::now();
+        // Drop the entry if it has expired, otherwise return it
+        if self
+            .sessions
+            .remove_if(session_id, |_, (_, expires_at)| *expires_at <= now)
+            .is_some()
+        {
+            return Ok(None);
+        }
+        Ok(self
+            .sessions
+            .get(session_id)
+            .map(|entry| entry.value().0.clone()))
+    }
+
+    async fn put(&self, session: &PcfSession, ttl: Duration) -> Result<(), PcfError> {
+        self.sessions.insert(
+            session.session_id.clone(),
+            (session.clone(), Instant::now() + ttl),
+        );
+        Ok(())
+    }
+
+    async fn replace_if_version(
+        &self,
+        session: &PcfSession,
+        expected_version: u64,
+        ttl: Duration,
+    ) -> Result<bool, PcfError> {
+        let now = Instant::now();
+        match self.sessions.get_mut(&session.session_id) {
+            Some(mut entry) if entry.1 > now && entry.0.version == expected_version => {
+                *entry = (session.clone(), now + ttl);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn remove(&self, session_id: &str) -> Result<bool, PcfError> {
+        let now = Instant::now();
+        Ok(self
+            .sessions
+            .remove(session_id)
+            .is_some_and(|(_, (_, expires_at))| expires_at > now))
+    }
+
+    async fn expire(&self, session_id: &str, ttl: Duration) -> Result<bool, PcfError> {
+        let now = Instant::now();
+        match self.sessions.get_mut(session_id) {
+            Some(mut entry) if entry.1 > now => {
+                entry.1 = now + ttl;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Redis-backed session store shared by all PCF instances of a cluster
+///
+/// Sessions are stored as JSON under `{key_prefix}{session_id}` and expiry is
+/// enforced by Redis itself.
+#[cfg(feature = "redis")]
+pub struct RedisSessionStore {
+    connection: redis::aio::ConnectionManager,
+    key_prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisSessionStore {
+    /// Connect to Redis
+    pub async fn new(url: &str) -> Result<Self, PcfError> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        let connection = redis::aio::ConnectionManager::new(client)
+            .await
+            .map_err(redis_error)?;
+
+        Ok(Self {
+            connection,
+            key_prefix: "pcf:session:".to_string(),
+        })
+    }
+
+    /// Use a different key prefix, e.g. to separate PCF clusters sharing a Redis
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    fn key(&self, session_id: &str) -> String {
+        format!("{}{}", self.key_prefix, session_id)
+    }
+}
+
+/// Store a session only if the stored one has the expected version
+#[cfg(feature = "redis")]
+const REPLACE_IF_VERSION_SCRIPT: &str = r"
+local current = redis.call('GET', KEYS[1])
+if not current then
+    return 0
+end
+if (cjson.decode(current)['version'] or 0) ~= tonumber(ARGV[1]) then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[2], 'PX', ARGV[3])
+return 1
+";
+
+#[cfg(feature = "redis")]
+fn redis_error(err: redis::RedisError) -> PcfError {
+    PcfError::SessionStoreError(err.to_string())
+}
+
+#[cfg(feature = "redis")]
+fn ttl_millis(ttl: Duration) -> u64 {
+    // Redis rejects a zero expiry
+    (ttl.as_millis() as u64).max(1)
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn get(&self, session_id: &str) -> Result<Option<PcfSession>, PcfError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.connection.clone();
+        let value: Option<String> = conn.get(self.key(session_id)).await.map_err(redis_error)?;
+        match value {
+            Some(v) => Ok(Some(serde_json::from_str(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, session: &PcfSession, ttl: Duration) -> Result<(), PcfError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.connection.clone();
+        let serialized = serde_json::to_string(session)?;
+        conn.pset_ex::<_, _, ()>(self.key(&session.session_id), serialized, ttl_millis(ttl))
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn replace_if_version(
+        &self,
+        session: &PcfSession,
+        expected_version: u64,
+        ttl: Duration,
+    ) -> Result<bool, PcfError> {
+        let mut conn = self.connection.clone();
+        let serialized = serde_json::to_string(session)?;
+        let replaced: i64 = redis::Script::new(REPLACE_IF_VERSION_SCRIPT)
+            .key(self.key(&session.session_id))
+            .arg(expected_version)
+            .arg(serialized)
+            .arg(ttl_millis(ttl))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(replaced == 1)
+    }
+
+    async fn remove(&self, session_id: &str) -> Result<bool, PcfError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.connection.clone();
+        let removed: u64 = conn.del(self.key(session_id)).await.map_err(redis_error)?;
+        Ok(removed > 0)
+    }
+
+    async fn expire(&self, session_id: &str, ttl: Duration) -> Result<bool, PcfError> {
+        use redis::AsyncCommands;
+
+        let mut conn = self.connection.clone();
+        conn.pexpire(self.key(session_id), ttl_millis(ttl) as i64)
+            .await
+            .map_err(redis_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(session_id: &str) -> PcfSession {
+        PcfSession {
+            session_id: session_id.to_string(),
+            subscriber_id: "1234567890".to_string(),
+            apn: "internet".to_string(),
+            policy_decision: None,
+            quota: None,
+            created_at: Utc::now(),
+            last_update: Utc::now(),
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_get_remove() {
+        let store = InMemorySessionStore::new();
+        store
+            .put(&session("s1"), Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let stored = store.get("s1").await.unwrap().unwrap();
+        assert_eq!(stored.subscriber_id, "1234567890");
+        assert!(store.remove("s1").await.unwrap());
+        assert!(store.get("s1").await.unwrap().is_none());
+        assert!(!store.remove("s1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_not_returned_or_kept() {
+        let store = InMemorySessionStore::new();
+        store
+            .put(&session("short"), Duration::from_millis(10))
+            .await
+            .unwrap();
+        store
+            .put(&session("long"), Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(store.get("short").await.unwrap().is_none());
+        assert!(!store
+            .expire("short", Duration::from_secs(60))
+            .await
+            .unwrap());
+        assert_eq!(store.purge_expired(), 0);
+        assert_eq!(store.len(), 1);
+        assert!(store.get("long").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_replace_if_version_rejects_stale_and_removed_sessions() {
+        let store = InMemorySessionStore::new();
+        store
+            .put(&session("s1"), Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let mut updated = session("s1");
+        updated.version = 1;
+        assert!(store
+            .replace_if_version(&updated, 0, Duration::from_secs(60))
+            .await
+            .unwrap());
+        // A writer that read version 0 lost the race
+        assert!(!store
+            .replace_if_version(&updated, 0, Duration::from_secs(60))
+            .await
+            .unwrap());
+        assert_eq!(store.get("s1").await.unwrap().unwrap().version, 1);
+
+        // A removed session is not recreated
+        assert!(store.remove("s1").await.unwrap());
+        updated.version = 2;
+        assert!(!store
+            .replace_if_version(&updated, 1, Duration::from_secs(60))
+            .await
+            .unwrap());
+        assert!(store.get("s1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expire_extends_ttl() {
+        let store = InMemorySessionStore::new();
+        store
+            .put(&session("s1"), Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert!(store.expire("s1", Duration::from_secs(60)).await.unwrap());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(store.get("s1").await.unwrap().is_some());
+        assert_eq!(store.purge_expired(), 0);
+    }
+}
@@ -2,10 +2,12 @@
 //!
 //! Provides support for multiple tax identification number formats:
 //! - CPF (Brazilian Tax ID)
+//! - CNPJ (Brazilian Business Tax ID)
 //! - NIF (European Tax ID - Portugal, Spain, etc.)
 //! - SSN (US Social Security Number)
 //! - And more...
 
+use crate::cnpj::Cnpj;
 use crate::cpf::Cpf;
 use crate::error::PcfError;
 use serde::{Deserialize, Serialize};
@@ -15,7 +17,7 @@ use std::fmt;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum TaxIdCountry {
-    /// Brazil - CPF
+    /// Brazil - CPF or CNPJ
     BR,
     /// Portugal - NIF
     PT,
@@ -56,6 +58,9 @@ pub enum TaxId {
     /// Brazilian CPF
     #[serde(rename = "cpf")]
     Cpf(Cpf),
+    /// Brazilian CNPJ
+    #[serde(rename = "cnpj")]
+    Cnpj(Cnpj),
     /// Portuguese NIF
     #[serde(rename = "nif_pt")]
     NifPt(String),
@@ -81,11 +86,27 @@ pub enum TaxId {
 
 impl TaxId {
     /// Create a TaxId from a string and country code
+    ///
+    /// Same as [`TaxId::parse`].
     pub fn from_string(value: &str, country: TaxIdCountry) -> Result<Self, PcfError> {
+        Self::parse(value, country)
+    }
+
+    /// Parse and validate a tax ID for a country
+    ///
+    /// Brazilian IDs are recognized by their number of digits: 11 for a CPF
+    /// and 14 for a CNPJ.
+    pub fn parse(value: &str, country: TaxIdCountry) -> Result<Self, PcfError> {
         match country {
             TaxIdCountry::BR => {
-                let cpf = Cpf::new(value)?;
-                Ok(TaxId::Cpf(cpf))
+                let digits = value.chars().filter(|c| c.is_ascii_digit()).count();
+                if digits == 14 {
+                    let cnpj = Cnpj::new(value)?;
+                    Ok(TaxId::Cnpj(cnpj))
+                } else {
+                    let cpf = Cpf::new(value)?;
+                    Ok(TaxId::Cpf(cpf))
+                }
             }
             TaxIdCountry::PT => {
                 let nif = NifPt::new(value)?;
@@ -121,7 +142,7 @@ impl TaxId {
     /// Get the country for this tax ID
     pub fn country(&self) -> TaxIdCountry {
         match self {
-            TaxId::Cpf(_) => TaxIdCountry::BR,
+            TaxId::Cpf(_) | TaxId::Cnpj(_) => TaxIdCountry::BR,
             TaxId::NifPt(_) => TaxIdCountry::PT,
             TaxId::NifEs(_) => TaxIdCountry::ES,
             TaxId::Ssn(_) => TaxIdCountry::US,
@@ -136,6 +157,7 @@ impl TaxId {
     pub fn as_str(&self) -> &str {
         match self {
             TaxId::Cpf(cpf) => cpf.as_str(),
+            TaxId::Cnpj(cnpj) => cnpj.as_str(),
             TaxId::NifPt(s) => s,
             TaxId::NifEs(s) => s,
             TaxId::Ssn(s) => s,
@@ -150,6 +172,7 @@ impl TaxId {
     pub fn formatted(&self) -> String {
         match self {
             TaxId::Cpf(cpf) => cpf.formatted(),
+            TaxId::Cnpj(cnpj) => cnpj.formatted(),
             TaxId::NifPt(nif) => NifPt::format(nif),
             TaxId::NifEs(nif) => NifEs::format(nif),
             TaxId::Ssn(ssn) => Ssn::format(ssn),
@@ -163,8 +186,8 @@ impl TaxId {
     /// Validate the tax ID
     pub fn validate(&self) -> Result<(), PcfError> {
         match self {
-            TaxId::Cpf(_cpf) => {
-                // CPF is already validated during creation
+            TaxId::Cpf(_) | TaxId::Cnpj(_) => {
+                // CPF and CNPJ are already validated during creation
                 Ok(())
             }
             TaxId::NifPt(nif) => NifPt::validate(nif),
@@ -613,6 +636,13 @@ mod tests {
         let tax_id = TaxId::from_string("123.456.789-09", TaxIdCountry::BR);
         assert!(tax_id.is_ok());
 
+        // Test CNPJ
+        let tax_id = TaxId::parse("11.222.333/0001-81", TaxIdCountry::BR).unwrap();
+        assert!(matches!(tax_id, TaxId::Cnpj(_)));
+        assert_eq!(tax_id.country(), TaxIdCountry::BR);
+        assert_eq!(tax_id.formatted(), "11.222.333/0001-81");
+        assert!(TaxId::parse("11.222.333/0001-80", TaxIdCountry::BR).is_err());
+
         // Test NIF PT
         let tax_id = TaxId::from_string("123456789", TaxIdCountry::PT);
         assert!(tax_id.is_ok());
@@ -0,0 +1,212 @@
+//! Time-of-Day Policy Windows
+//!
+//! A time window restricts a policy rule to certain hours of certain days,
+//! expressed in the local time of an IANA timezone (e.g. "unlimited social
+//! media from 22:00 to 06:00 in America/Sao_Paulo").
+//!
+//! Windows are evaluated against local wall-clock time, so they follow DST
+//! transitions: a window from 22:00 to 06:00 always starts at 22:00 local,
+//! whatever the UTC offset of that day. A window whose start falls in the hour
+//! skipped when clocks go forward opens when clocks resume; local times
+//! repeated when clocks go back match both times.
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+const SECONDS_PER_WEEK: u32 = 7 * SECONDS_PER_DAY;
+
+/// Recurring local time window of a policy rule
+///
+/// The window opens at `start` on each listed day and closes at `end`. When
+/// `end` is not after `start` the window crosses midnight and closes on the
+/// following day; equal times make a 24-hour window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// Days on which the window opens (empty = every day)
+    #[serde(default)]
+    pub days_of_week: Vec<Weekday>,
+    /// Local time at which the window opens
+    pub start: NaiveTime,
+    /// Local time at which the window closes (exclusive)
+    pub end: NaiveTime,
+    /// IANA timezone of the local times (e.g. "Europe/Lisbon")
+    pub timezone: Tz,
+}
+
+impl TimeWindow {
+    /// Create a window open every day
+    pub fn daily(start: NaiveTime, end: NaiveTime, timezone: Tz) -> Self {
+        Self {
+            days_of_week: Vec::new(),
+            start,
+            end,
+            timezone,
+        }
+    }
+
+    /// Restrict the window to the given days
+    pub fn on_days(mut self, days: impl IntoIterator<Item = Weekday>) -> Self {
+        self.days_of_week = days.into_iter().collect();
+        self
+    }
+
+    /// Whether the window crosses local midnight
+    pub fn crosses_midnight(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// Whether the window is open at the given instant
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&self.timezone);
+        let time = local.time();
+        let day = local.weekday();
+
+        if self.crosses_midnight() {
+            (self.opens_on(day) && time >= self.start)
+                || (self.opens_on(day.pred()) && time < self.end)
+        } else {
+            self.opens_on(day) && time >= self.start && time < self.end
+        }
+    }
+
+    /// Whether some instant falls in both windows
+    ///
+    /// Windows in different timezones are compared conservatively and always
+    /// overlap, since their offsets differ from one date to the next.
+    pub fn overlaps(&self, other: &TimeWindow) -> bool {
+        if self.timezone != other.timezone {
+            return true;
+        }
+        let theirs = other.week_intervals();
+        self.week_intervals().iter().any(|&(a_start, a_end)| {
+            theirs
+                .iter()
+                .any(|&(b_start, b_end)| a_start < b_end && b_start < a_end)
+        })
+    }
+
+    fn opens_on(&self, day: Weekday) -> bool {
+        self.days_of_week.is_empty() || self.days_of_week.contains(&day)
+    }
+
+    /// Open intervals as seconds since Monday 00:00, split at the end of the week
+    fn week_intervals(&self) -> Vec<(u32, u32)> {
+        let start = self.start.num_seconds_from_midnight();
+        let end = self.end.num_seconds_from_midnight();
+        let length = if self.crosses_midnight() {
+            SECONDS_PER_DAY - start + end
+        } else {
+            end - start
+        };
+
+        let mut intervals = Vec::new();
+        for day in 0..7 {
+            let weekday = Weekday::try_from(day as u8).expect("day index in range");
+            if !self.opens_on(weekday) {
+                continue;
+            }
+            let open = day * SECONDS_PER_DAY + start;
+            let close = open + length;
+            if close > SECONDS_PER_WEEK {
+                intervals.push((open, SECONDS_PER_WEEK));
+                intervals.push((0, close - SECONDS_PER_WEEK));
+            } else {
+                intervals.push((open, close));
+            }
+        }
+        intervals
+    }
+}
+
+/// Whether a rule restricted to `window` applies at `time_of_day`
+///
+/// Rules without a window always apply. Rules with a window never apply to a
+/// request without a time of day.
+pub fn window_applies(window: Option<&TimeWindow>, time_of_day: Option<DateTime<Utc>>) -> bool {
+    match (window, time_of_day) {
+        (None, _) => true,
+        (Some(window), Some(at)) => window.contains(at),
+        (Some(_), None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn hm(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_same_day_window() {
+        let window = TimeWindow::daily(hm(9, 0), hm(17, 0), Tz::UTC);
+
+        assert!(window.contains(utc(2026, 3, 2, 9, 0)));
+        assert!(window.contains(utc(2026, 3, 2, 16, 59)));
+        assert!(!window.contains(utc(2026, 3, 2, 17, 0)));
+        assert!(!window.contains(utc(2026, 3, 2, 8, 59)));
+    }
+
+    #[test]
+    fn test_window_crossing_midnight_uses_opening_day() {
+        // Friday night 22:00 until Saturday 06:00
+        let window = TimeWindow::daily(hm(22, 0), hm(6, 0), Tz::UTC).on_days([Weekday::Fri]);
+
+        // 2026-03-06 is a Friday
+        assert!(window.contains(utc(2026, 3, 6, 23, 0)));
+        assert!(window.contains(utc(2026, 3, 7, 5, 59)));
+        assert!(!window.contains(utc(2026, 3, 7, 6, 0)));
+        assert!(!window.contains(utc(2026, 3, 7, 23, 0)));
+        assert!(!window.contains(utc(2026, 3, 6, 5, 0)));
+    }
+
+    #[test]
+    fn test_window_follows_local_time_across_dst() {
+        let window = TimeWindow::daily(hm(22, 0), hm(6, 0), chrono_tz::Europe::Lisbon);
+
+        // Winter: Lisbon is UTC+0
+        assert!(window.contains(utc(2026, 1, 15, 22, 0)));
+        assert!(!window.contains(utc(2026, 1, 15, 21, 30)));
+        // Summer: Lisbon is UTC+1, so 22:00 local is 21:00 UTC
+        assert!(window.contains(utc(2026, 7, 15, 21, 0)));
+        assert!(!window.contains(utc(2026, 7, 15, 20, 30)));
+        // Clocks go forward at 01:00 UTC on 2026-03-29; 06:00 local is 05:00 UTC
+        assert!(window.contains(utc(2026, 3, 29, 4, 59)));
+        assert!(!window.contains(utc(2026, 3, 29, 5, 0)));
+    }
+
+    #[test]
+    fn test_window_applies_without_time_of_day() {
+        let window = TimeWindow::daily(hm(22, 0), hm(6, 0), Tz::UTC);
+
+        assert!(window_applies(None, None));
+        assert!(!window_applies(Some(&window), None));
+        assert!(window_applies(Some(&window), Some(utc(2026, 3, 2, 23, 0))));
+    }
+
+    #[test]
+    fn test_overlapping_windows() {
+        let night = TimeWindow::daily(hm(22, 0), hm(6, 0), Tz::UTC);
+        let day = TimeWindow::daily(hm(6, 0), hm(22, 0), Tz::UTC);
+        let early = TimeWindow::daily(hm(5, 0), hm(7, 0), Tz::UTC);
+        let sunday_night = TimeWindow::daily(hm(23, 0), hm(1, 0), Tz::UTC).on_days([Weekday::Sun]);
+        let monday_morning = TimeWindow::daily(hm(0, 0), hm(2, 0), Tz::UTC).on_days([Weekday::Mon]);
+
+        assert!(!night.overlaps(&day));
+        assert!(night.overlaps(&early));
+        assert!(sunday_night.overlaps(&monday_morning));
+        assert!(night.overlaps(&TimeWindow::daily(
+            hm(6, 0),
+            hm(22, 0),
+            chrono_tz::Europe::Lisbon
+        )));
+    }
+}
@@ -0,0 +1,213 @@
+//! Zero-Rating Hostname Matching
+//!
+//! Matches the hostname of a flow, taken from its application ID or TLS SNI,
+//! against zero-rating rules. A rule names either an exact hostname or a
+//! wildcard pattern such as `*.youtube.com`, which covers every subdomain of
+//! the domain. Matching is case-insensitive and a single suffix comparison, so
+//! no pattern can make it expensive.
+//!
+//! Patterns that would zero-rate far more than intended, such as a bare `*` or
+//! a whole top-level domain (`*.com`), are rejected.
+
+use crate::error::PcfError;
+use crate::models::ZeroRatingRule;
+
+/// Maximum length of a hostname (RFC 1035)
+const MAX_HOSTNAME_LEN: usize = 253;
+/// Maximum length of a hostname label (RFC 1035)
+const MAX_LABEL_LEN: usize = 63;
+
+/// Lowercase a hostname and strip the trailing dot of a fully qualified name
+fn normalize(hostname: &str) -> String {
+    hostname.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Check that a hostname pattern is well-formed and not overly broad
+pub fn validate_hostname_pattern(pattern: &str) -> Result<(), PcfError> {
+    let pattern = normalize(pattern);
+    let invalid = |reason: &str| {
+        Err(PcfError::ConfigurationError(format!(
+            "Invalid zero-rating pattern '{}': {}",
+            pattern, reason
+        )))
+    };
+
+    if pattern.is_empty() {
+        return invalid("pattern is empty");
+    }
+    if pattern.len() > MAX_HOSTNAME_LEN {
+        return invalid("pattern is too long");
+    }
+
+    let (wildcard, domain) = match pattern.strip_prefix("*.") {
+        Some(domain) => (true, domain),
+        None => (false, pattern.as_str()),
+    };
+    if domain.contains('*') {
+        return invalid("'*' is only allowed as the whole leftmost label");
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    for label in &labels {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return invalid("labels must be 1 to 63 characters");
+        }
+        if !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return invalid("labels may only contain letters, digits, '-' and '_'");
+        }
+    }
+    if wildcard && labels.len() < 2 {
+        return invalid("wildcard must be followed by a domain, not a top-level domain");
+    }
+
+    Ok(())
+}
+
+/// Check if a hostname matches a pattern
+///
+/// The pattern is assumed to have passed [`validate_hostname_pattern`].
+pub fn hostname_matches(pattern: &str, hostname: &str) -> bool {
+    let pattern = normalize(pattern);
+    let hostname = normalize(hostname);
+
+    match pattern.strip_prefix("*.") {
+        Some(domain) => hostname
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => hostname == pattern,
+    }
+}
+
+impl ZeroRatingRule {
+    /// Check if the rule covers a flow to `hostname` on `port`
+    ///
+    /// A rule limited to a port range never matches a flow whose port is unknown.
+    pub fn matches(&self, hostname: &str, port: Option<u16>) -> bool {
+        let port_matches = match (self.port_range, port) {
+            (None, _) => true,
+            (Some(range), Some(port)) => range.contains(port),
+            (Some(_), None) => false,
+        };
+        port_matches && hostname_matches(&self.service_identifier, hostname)
+    }
+
+    /// How specific the rule's pattern is; exact hostnames beat wildcards,
+    /// and longer domains beat shorter ones
+    pub(crate) fn specificity(&self) -> (bool, usize) {
+        let pattern = normalize(&self.service_identifier);
+        (!pattern.starts_with("*."), pattern.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NetworkGeneration, PolicyRequest, PortRange};
+    use crate::pcf_engine::{PcfEngine, PcfEngineTrait};
+    use uuid::Uuid;
+
+    fn rule(pattern: &str) -> ZeroRatingRule {
+        ZeroRatingRule {
+            rule_id: Uuid::new_v4(),
+            service_identifier: pattern.to_string(),
+            plan_name: None,
+            port_range: None,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_matches_subdomains_case_insensitively() {
+        assert!(hostname_matches("*.youtube.com", "www.youtube.com"));
+        assert!(hostname_matches(
+            "*.YouTube.com",
+            "R3.SN-ab.googlevideo.youtube.com."
+        ));
+        assert!(!hostname_matches("*.youtube.com", "youtube.com"));
+        assert!(!hostname_matches("*.youtube.com", "notyoutube.com"));
+        assert!(!hostname_matches("*.youtube.com", "youtube.com.evil.net"));
+        assert!(hostname_matches("WhatsApp.com", "whatsapp.com"));
+        assert!(!hostname_matches("whatsapp.com", "web.whatsapp.com"));
+    }
+
+    #[test]
+    fn test_overly_broad_and_malformed_patterns_are_rejected() {
+        for pattern in [
+            "*",
+            "*.",
+            "*.com",
+            "**.example.com",
+            "video*.example.com",
+            "",
+            "a..b",
+            "*.exa mple.com",
+        ] {
+            assert!(
+                validate_hostname_pattern(pattern).is_err(),
+                "{} should be rejected",
+                pattern
+            );
+        }
+        for pattern in ["youtube.com", "*.youtube.com", "*.co.uk", "localhost"] {
+            assert!(validate_hostname_pattern(pattern).is_ok(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_port_range_limits_rule() {
+        let mut rule = rule("*.youtube.com");
+        rule.port_range = Some(PortRange {
+            start: 443,
+            end: 443,
+        });
+
+        assert!(rule.matches("www.youtube.com", Some(443)));
+        assert!(!rule.matches("www.youtube.com", Some(80)));
+        assert!(!rule.matches("www.youtube.com", None));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_policy_reports_most_specific_sni_match() {
+        let engine = PcfEngine::new();
+        let charging = engine.charging_rules_engine();
+        let wildcard = rule("*.youtube.com");
+        let exact = rule("music.youtube.com");
+        charging.add_zero_rating_rule(wildcard.clone()).unwrap();
+        charging.add_zero_rating_rule(exact.clone()).unwrap();
+        assert!(charging.add_zero_rating_rule(rule("*")).is_err());
+
+        #[allow(deprecated)]
+        let mut request = PolicyRequest {
+            subscriber_id: "1234567890".to_string(),
+            imsi: "123456789012345".to_string(),
+            tax_id: None,
+            cpf: None,
+            network_generation: NetworkGeneration::FourG,
+            apn: "internet".to_string(),
+            service_type: "video_streaming".to_string(),
+            application_id: None,
+            location: None,
+            time_of_day: None,
+            roaming: None,
+            sni: Some("R3.googlevideo.YouTube.com".to_string()),
+            destination_port: Some(443),
+            congestion_level: None,
+        };
+
+        let decision = engine.evaluate_policy(&request).await.unwrap();
+        assert!(decision.zero_rated);
+        assert_eq!(decision.zero_rating_rule_id, Some(wildcard.rule_id));
+
+        request.sni = Some("music.youtube.com".to_string());
+        let decision = engine.evaluate_policy(&request).await.unwrap();
+        assert_eq!(decision.zero_rating_rule_id, Some(exact.rule_id));
+
+        request.sni = Some("netflix.com".to_string());
+        let decision = engine.evaluate_policy(&request).await.unwrap();
+        assert!(!decision.zero_rated);
+        assert!(decision.zero_rating_rule_id.is_none());
+    }
+}
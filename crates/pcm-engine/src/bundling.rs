@@ -11,6 +11,9 @@ pub struct Bundle {
     pub bundle_type: BundleType,
     pub products: Vec<BundleProduct>,
     pub bundle_price: Option<BundlePrice>,
+    /// Minimum number of components the bundle must keep to retain its discount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_components: Option<u32>,
 }
 
 /// Bundle type
@@ -59,15 +62,11 @@ pub fn validate_bundle(bundle: &Bundle) -> Result<(), String> {
     }
 
     match bundle.bundle_type {
-        BundleType::Mandatory => {
-            if bundle.products.iter().any(|p| !p.is_required) {
-                return Err("Mandatory bundles cannot have optional products".to_string());
-            }
+        BundleType::Mandatory if bundle.products.iter().any(|p| !p.is_required) => {
+            return Err("Mandatory bundles cannot have optional products".to_string());
         }
-        BundleType::Exclusive => {
-            if bundle.products.len() < 2 {
-                return Err("Exclusive bundles must have at least 2 products".to_string());
-            }
+        BundleType::Exclusive if bundle.products.len() < 2 => {
+            return Err("Exclusive bundles must have at least 2 products".to_string());
         }
         _ => {}
     }
@@ -103,3 +102,317 @@ pub fn calculate_bundle_price(
         None => Ok(total_individual_price),
     }
 }
+
+/// What to do with a bundle whose cardinality is violated after a component change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BundleDissolutionPolicy {
+    /// Keep the bundle but mark it invalid for follow-up
+    FlagInvalid,
+    /// Dissolve the bundle and price the remaining components standalone
+    ConvertToStandalone,
+}
+
+/// Result of recalculating a bundle after a component was removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRecalculation {
+    pub bundle_id: Uuid,
+    pub removed_product_offering_id: Uuid,
+    pub previous_price: f64,
+    pub new_price: f64,
+    /// Whether the remaining components still satisfy the bundle's cardinality
+    pub is_valid: bool,
+    /// Whether the bundle discount was dropped in favour of standalone pricing
+    pub dissolved: bool,
+    /// Components now priced standalone (only populated when dissolved)
+    pub standalone_products: Vec<Uuid>,
+    /// Human-readable reason the cardinality check failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violation: Option<String>,
+}
+
+/// Remove a component from a bundle and recalculate its price
+///
+/// If the removal violates the bundle's cardinality (a required component removed,
+/// fewer than `min_components` left, or the bundle type no longer validates), the
+/// `policy` decides whether the bundle is flagged invalid or dissolved into
+/// standalone components.
+pub fn recalculate_bundle_on_removal(
+    bundle: &mut Bundle,
+    removed_product_offering_id: Uuid,
+    individual_prices: &[(Uuid, f64)],
+    policy: &BundleDissolutionPolicy,
+) -> Result<BundleRecalculation, String> {
+    let position = bundle
+        .products
+        .iter()
+        .position(|bp| bp.product_offering_id == removed_product_offering_id)
+        .ok_or_else(|| {
+            format!(
+                "Product offering {} is not a component of bundle {}",
+                removed_product_offering_id, bundle.id
+            )
+        })?;
+
+    let previous_price = calculate_bundle_price(bundle, individual_prices)?;
+    let removed = bundle.products.remove(position);
+
+    let violation = if removed.is_required {
+        Some(format!(
+            "Required component {} was removed",
+            removed.product_offering_id
+        ))
+    } else if bundle
+        .min_components
+        .is_some_and(|min| (bundle.products.len() as u32) < min)
+    {
+        Some(format!(
+            "Bundle has {} component(s), below minimum of {}",
+            bundle.products.len(),
+            bundle.min_components.unwrap_or_default()
+        ))
+    } else {
+        validate_bundle(bundle).err()
+    };
+
+    let mut recalculation = BundleRecalculation {
+        bundle_id: bundle.id,
+        removed_product_offering_id,
+        previous_price,
+        new_price: previous_price,
+        is_valid: violation.is_none(),
+        dissolved: false,
+        standalone_products: Vec::new(),
+        violation,
+    };
+
+    if recalculation.is_valid || *policy == BundleDissolutionPolicy::FlagInvalid {
+        recalculation.new_price = calculate_bundle_price(bundle, individual_prices)?;
+    } else {
+        let standalone = Bundle {
+            bundle_price: None,
+            ..bundle.clone()
+        };
+        recalculation.new_price = calculate_bundle_price(&standalone, individual_prices)?;
+        recalculation.dissolved = true;
+        recalculation.standalone_products = bundle
+            .products
+            .iter()
+            .map(|bp| bp.product_offering_id)
+            .collect();
+    }
+
+    Ok(recalculation)
+}
+
+/// Item in a customer's cart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartItem {
+    pub product_offering_id: Uuid,
+    pub quantity: u32,
+}
+
+/// Bundle suggested for a cart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRecommendation {
+    pub bundle_id: Uuid,
+    pub bundle_name: String,
+    /// Components the bundle would be priced for
+    pub product_offering_ids: Vec<Uuid>,
+    /// Component the cart lacks; set for upsell opportunities only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_product_offering_id: Option<Uuid>,
+    /// Price of the components bought separately
+    pub standalone_price: f64,
+    /// Price of the components as a bundle
+    pub bundle_price: f64,
+    pub savings: f64,
+    /// Whether the cart is one component short of the bundle
+    pub upsell: bool,
+}
+
+/// Whether a bundle could be recommended for a cart: it has a bundle price, is
+/// not exclusive and shares at least one component with the cart
+pub fn is_candidate_bundle(bundle: &Bundle, cart_items: &[CartItem]) -> bool {
+    bundle.bundle_type != BundleType::Exclusive
+        && bundle.bundle_price.is_some()
+        && bundle.products.iter().any(|bp| {
+            cart_items
+                .iter()
+                .any(|item| item.product_offering_id == bp.product_offering_id)
+        })
+}
+
+/// Recommend bundles that would make the cart cheaper
+///
+/// Only candidate bundles are considered (see [`is_candidate_bundle`]). A
+/// bundle is recommended when the cart holds all of its required components,
+/// in the bundle's quantities, and at least `min_components` of its components.
+/// Bundles the cart is exactly one component short of are returned as upsell
+/// opportunities, priced as if the missing component were added. Only bundles
+/// that save money are returned, complete matches first, then by savings.
+/// Exclusive bundles are never recommended, as their components are
+/// alternatives rather than a set. Bundles with a component missing from
+/// `individual_prices` are skipped, as their savings cannot be known.
+pub fn recommend_bundles<'a, I>(
+    bundles: I,
+    cart_items: &[CartItem],
+    individual_prices: &[(Uuid, f64)],
+) -> Result<Vec<BundleRecommendation>, String>
+where
+    I: IntoIterator<Item = &'a Bundle>,
+{
+    let in_cart = |bp: &BundleProduct| {
+        cart_items.iter().any(|item| {
+            item.product_offering_id == bp.product_offering_id && item.quantity >= bp.quantity
+        })
+    };
+    let unit_price = |id: Uuid| {
+        individual_prices
+            .iter()
+            .find(|(price_id, _)| *price_id == id)
+            .map(|(_, price)| *price)
+    };
+    let priced = |bp: &&BundleProduct| unit_price(bp.product_offering_id).is_some();
+
+    let mut recommendations = Vec::new();
+    for bundle in bundles {
+        if !is_candidate_bundle(bundle, cart_items) {
+            continue;
+        }
+
+        let (present, absent): (Vec<&BundleProduct>, Vec<&BundleProduct>) =
+            bundle.products.iter().partition(|bp| in_cart(bp));
+        let min_components = bundle.min_components.unwrap_or(1).max(1) as usize;
+        if min_components > bundle.products.len() {
+            continue;
+        }
+        let missing_required: Vec<&BundleProduct> =
+            absent.iter().copied().filter(|bp| bp.is_required).collect();
+
+        // Component to add when one short: the missing required one, or else
+        // the cheapest optional one if the cart is below the minimum
+        let missing = match missing_required.len() {
+            0 if present.len() >= min_components => None,
+            0 if present.len() + 1 == min_components => {
+                let cost = |bp: &BundleProduct| {
+                    unit_price(bp.product_offering_id).unwrap_or(0.0) * bp.quantity as f64
+                };
+                match absent
+                    .iter()
+                    .copied()
+                    .filter(priced)
+                    .min_by(|a, b| cost(a).total_cmp(&cost(b)))
+                {
+                    Some(cheapest) => Some(cheapest),
+                    None => continue,
+                }
+            }
+            1 if present.len() + 1 >= min_components => Some(missing_required[0]),
+            _ => continue,
+        };
+
+        if !present.iter().chain(missing.as_ref()).all(priced) {
+            continue;
+        }
+
+        let mut components: Vec<BundleProduct> = present.into_iter().cloned().collect();
+        components.extend(missing.cloned());
+        let priced = Bundle {
+            products: components,
+            ..bundle.clone()
+        };
+        let standalone = Bundle {
+            bundle_price: None,
+            ..priced.clone()
+        };
+        let standalone_price = calculate_bundle_price(&standalone, individual_prices)?;
+        let bundle_price = calculate_bundle_price(&priced, individual_prices)?;
+        let savings = standalone_price - bundle_price;
+        if savings <= 0.0 {
+            continue;
+        }
+
+        recommendations.push(BundleRecommendation {
+            bundle_id: bundle.id,
+            bundle_name: bundle.name.clone(),
+            product_offering_ids: priced
+                .products
+                .iter()
+                .map(|bp| bp.product_offering_id)
+                .collect(),
+            missing_product_offering_id: missing.map(|bp| bp.product_offering_id),
+            standalone_price,
+            bundle_price,
+            savings,
+            upsell: missing.is_some(),
+        });
+    }
+
+    recommendations.sort_by(|a, b| {
+        a.upsell
+            .cmp(&b.upsell)
+            .then_with(|| b.savings.total_cmp(&a.savings))
+    });
+    Ok(recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(components: &[Uuid]) -> Bundle {
+        Bundle {
+            id: Uuid::new_v4(),
+            name: "bundle".to_string(),
+            bundle_type: BundleType::Mandatory,
+            products: components
+                .iter()
+                .map(|id| BundleProduct {
+                    product_offering_id: *id,
+                    quantity: 1,
+                    is_required: true,
+                })
+                .collect(),
+            bundle_price: Some(BundlePrice {
+                discount_type: BundleDiscountType::PercentageOff,
+                value: 10.0,
+            }),
+            min_components: None,
+        }
+    }
+
+    fn cart(ids: &[Uuid]) -> Vec<CartItem> {
+        ids.iter()
+            .map(|id| CartItem {
+                product_offering_id: *id,
+                quantity: 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bundles_with_unpriced_components_are_skipped() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let priced = bundle(&[a, b]);
+        let unpriced = bundle(&[a, c]);
+        let prices = [(a, 10.0), (b, 20.0)];
+
+        let recommendations =
+            recommend_bundles([&priced, &unpriced], &cart(&[a, b, c]), &prices).unwrap();
+        assert_eq!(recommendations.len(), 1);
+        assert_eq!(recommendations[0].bundle_id, priced.id);
+        assert_eq!(recommendations[0].standalone_price, 30.0);
+    }
+
+    #[test]
+    fn bundles_sharing_nothing_with_the_cart_are_not_candidates() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let bundle = bundle(&[a]);
+        assert!(is_candidate_bundle(&bundle, &cart(&[a])));
+        assert!(!is_candidate_bundle(&bundle, &cart(&[b])));
+
+        let recommendations = recommend_bundles([&bundle], &cart(&[b]), &[(a, 10.0)]).unwrap();
+        assert!(recommendations.is_empty());
+    }
+}
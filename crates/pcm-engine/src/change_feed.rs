@@ -0,0 +1,166 @@
+//! Offering change feed
+//!
+//! Records every create, update, delete and price change of a product offering
+//! as it happens, so consumers can react to catalog changes instead of polling
+//! the whole catalog. Changes are pushed to registered subscribers and kept in
+//! a bounded history that consumers can read incrementally by sequence number.
+//!
+//! To publish changes on the event bus, subscribe with an
+//! `std::sync::mpsc::Sender` and forward each received event under its
+//! [`OfferingChangeEvent::event_type`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use uuid::Uuid;
+
+/// Number of changes kept for [`OfferingChangeFeed::changes_since`]
+pub const DEFAULT_CHANGE_HISTORY: usize = 1000;
+
+/// Kind of change made to a product offering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OfferingChangeType {
+    Created,
+    Updated,
+    Deleted,
+    PriceChanged,
+}
+
+/// Change made to a product offering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferingChangeEvent {
+    /// Position in the feed, increasing by one per change
+    pub sequence: u64,
+    pub offering_id: Uuid,
+    pub change_type: OfferingChangeType,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl OfferingChangeEvent {
+    /// Event type name used when publishing on the event bus
+    pub fn event_type(&self) -> &'static str {
+        match self.change_type {
+            OfferingChangeType::Created => "catalog.offering.created",
+            OfferingChangeType::Updated => "catalog.offering.updated",
+            OfferingChangeType::Deleted => "catalog.offering.deleted",
+            OfferingChangeType::PriceChanged => "catalog.offering.price_changed",
+        }
+    }
+}
+
+/// Receives offering changes as they are made
+///
+/// Called synchronously while the catalog is being modified, so
+/// implementations should hand the event off rather than do slow work.
+pub trait OfferingChangeSubscriber: Send + Sync {
+    fn on_offering_change(&self, event: &OfferingChangeEvent);
+}
+
+impl OfferingChangeSubscriber for Mutex<mpsc::Sender<OfferingChangeEvent>> {
+    fn on_offering_change(&self, event: &OfferingChangeEvent) {
+        if let Ok(sender) = self.lock() {
+            // A dropped receiver only means nobody is listening any more
+            let _ = sender.send(event.clone());
+        }
+    }
+}
+
+/// Change feed of the product offerings in a catalog
+pub struct OfferingChangeFeed {
+    subscribers: Vec<Arc<dyn OfferingChangeSubscriber>>,
+    history: VecDeque<OfferingChangeEvent>,
+    history_limit: usize,
+    next_sequence: u64,
+}
+
+impl OfferingChangeFeed {
+    /// Create a feed keeping the last [`DEFAULT_CHANGE_HISTORY`] changes
+    pub fn new() -> Self {
+        Self::with_history_limit(DEFAULT_CHANGE_HISTORY)
+    }
+
+    /// Create a feed keeping the last `history_limit` changes
+    pub fn with_history_limit(history_limit: usize) -> Self {
+        Self {
+            subscribers: Vec::new(),
+            history: VecDeque::new(),
+            history_limit,
+            next_sequence: 1,
+        }
+    }
+
+    /// Register a subscriber for all future changes
+    pub fn subscribe(&mut self, subscriber: Arc<dyn OfferingChangeSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Subscribe through a channel, e.g. to forward changes to the event bus
+    pub fn subscribe_channel(&mut self) -> mpsc::Receiver<OfferingChangeEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribe(Arc::new(Mutex::new(sender)));
+        receiver
+    }
+
+    /// Record a change and notify subscribers
+    pub fn record(
+        &mut self,
+        offering_id: Uuid,
+        change_type: OfferingChangeType,
+    ) -> OfferingChangeEvent {
+        let event = OfferingChangeEvent {
+            sequence: self.next_sequence,
+            offering_id,
+            change_type,
+            occurred_at: Utc::now(),
+        };
+        self.next_sequence += 1;
+
+        if self.history_limit > 0 {
+            if self.history.len() == self.history_limit {
+                self.history.pop_front();
+            }
+            self.history.push_back(event.clone());
+        }
+        for subscriber in &self.subscribers {
+            subscriber.on_offering_change(&event);
+        }
+
+        event
+    }
+
+    /// Sequence number of the latest change, or 0 if nothing has changed
+    pub fn latest_sequence(&self) -> u64 {
+        self.next_sequence - 1
+    }
+
+    /// Changes after `sequence` still held in the history, oldest first
+    ///
+    /// Returns `None` if changes after `sequence` have already been dropped
+    /// from the history, in which case the consumer has to resync from the
+    /// full catalog.
+    pub fn changes_since(&self, sequence: u64) -> Option<Vec<OfferingChangeEvent>> {
+        let oldest_kept = self
+            .history
+            .front()
+            .map_or(self.next_sequence, |event| event.sequence);
+        if sequence + 1 < oldest_kept {
+            return None;
+        }
+
+        Some(
+            self.history
+                .iter()
+                .filter(|event| event.sequence > sequence)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+impl Default for OfferingChangeFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
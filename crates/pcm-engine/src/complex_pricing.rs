@@ -2,8 +2,9 @@
 //!
 //! Supports tiered pricing, volume-based pricing, subscription models, and dynamic pricing
 
+use crate::price_trace::{PriceStepKind, PriceTrace, Tracer};
 use crate::pricing::Money;
-use chrono::{DateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -26,9 +27,27 @@ pub enum ComplexPricingModel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TieredPricing {
     pub tiers: Vec<PricingTier>,
+    /// How tier rates apply to a quantity (all-units if unset)
+    #[serde(default)]
+    pub mode: TierMode,
+}
+
+/// How tier rates apply to a quantity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TierMode {
+    /// Each tier's rate applies only to the units within that tier
+    Graduated,
+    /// The rate of the highest tier reached applies to every unit
+    #[default]
+    AllUnits,
 }
 
 /// Pricing tier
+///
+/// A tier covers quantities from `min_quantity` to `max_quantity`, both
+/// inclusive. Units are charged `price_per_unit` each, or the flat `price`
+/// when no per-unit price is set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricingTier {
     pub min_quantity: u32,
@@ -45,6 +64,9 @@ pub struct VolumePricing {
 }
 
 /// Volume discount
+///
+/// The largest discount whose minimum volume is reached applies to the whole
+/// base price.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolumeDiscount {
     pub min_volume: u32,
@@ -73,6 +95,30 @@ pub enum BillingCycle {
     Daily,
 }
 
+/// Day-count basis of proration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DayCountBasis {
+    /// Actual calendar days, so leap days and cycle lengths count as they are
+    #[default]
+    ActualActual,
+    /// Every month counts 30 days (European 30E/360)
+    Thirty360,
+}
+
+/// Proration of a mid-cycle plan change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proration {
+    /// Refund of the old plan for the rest of the cycle
+    pub credit: Money,
+    /// Charge of the new plan for the rest of the cycle
+    pub charge: Money,
+    /// Days from the change to the end of the cycle
+    pub remaining_days: u32,
+    /// Days in the cycle
+    pub cycle_days: u32,
+}
+
 /// Cancellation policy
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -132,8 +178,43 @@ pub enum AdjustmentType {
 pub struct BundlePricing {
     pub bundle_id: Uuid,
     pub component_prices: Vec<ComponentPrice>,
+    /// Discount percentage granted to every subscriber of the bundle
     pub bundle_discount: f64,
     pub minimum_components: Option<u32>,
+    /// Further discounts for subscribers who qualify
+    #[serde(default)]
+    pub additional_discounts: Vec<BundleDiscount>,
+    /// How the bundle discount and the additional discounts combine
+    #[serde(default)]
+    pub stacking_policy: StackingPolicy,
+}
+
+/// Additional bundle discount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleDiscount {
+    pub name: String,
+    pub discount_percentage: f64,
+    /// Subscriptions the subscriber must hold to qualify (none = everyone)
+    #[serde(default)]
+    pub required_subscriptions: Vec<Uuid>,
+}
+
+/// How the discounts of a bundle combine
+///
+/// Discounts are considered largest first, so policies that grant only part
+/// of them favour the subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StackingPolicy {
+    /// Only the largest discount applies
+    #[default]
+    BestOnly,
+    /// Percentages add up and are taken off the component total
+    Additive,
+    /// Each discount applies to the price left by the previous ones
+    Multiplicative,
+    /// Percentages add up to at most `max_total_percentage` (itself at most 100)
+    Capped { max_total_percentage: f64 },
 }
 
 /// Component price in a bundle
@@ -150,13 +231,37 @@ pub fn calculate_complex_price(
     quantity: u32,
     context: &PricingContext,
 ) -> Money {
-    match model {
-        ComplexPricingModel::Tiered(tiered) => calculate_tiered_price(tiered, quantity),
-        ComplexPricingModel::VolumeBased(volume) => calculate_volume_price(volume, quantity),
-        ComplexPricingModel::Subscription(sub) => calculate_subscription_price(sub),
-        ComplexPricingModel::Dynamic(dynamic) => calculate_dynamic_price(dynamic, context),
-        ComplexPricingModel::Bundle(bundle) => calculate_bundle_price(bundle, context),
-    }
+    complex_price(model, quantity, context, &mut Tracer::off())
+}
+
+/// Calculate price like [`calculate_complex_price`], also returning how it was
+/// derived
+pub fn calculate_complex_price_with_trace(
+    model: &ComplexPricingModel,
+    quantity: u32,
+    context: &PricingContext,
+) -> (Money, PriceTrace) {
+    let mut trace = PriceTrace::new();
+    let price = complex_price(model, quantity, context, &mut Tracer::on(&mut trace));
+    (price, trace)
+}
+
+fn complex_price(
+    model: &ComplexPricingModel,
+    quantity: u32,
+    context: &PricingContext,
+    tracer: &mut Tracer,
+) -> Money {
+    let price = match model {
+        ComplexPricingModel::Tiered(tiered) => calculate_tiered_price(tiered, quantity, tracer),
+        ComplexPricingModel::VolumeBased(volume) => {
+            calculate_volume_price(volume, quantity, tracer)
+        }
+        ComplexPricingModel::Subscription(sub) => calculate_subscription_price(sub, tracer),
+        ComplexPricingModel::Dynamic(dynamic) => calculate_dynamic_price(dynamic, context, tracer),
+        ComplexPricingModel::Bundle(bundle) => calculate_bundle_price(bundle, context, tracer),
+    };
+    tracer.finish(price)
 }
 
 /// Pricing context for complex calculations
@@ -170,63 +275,208 @@ pub struct PricingContext {
     pub existing_subscriptions: Vec<Uuid>,
 }
 
-fn calculate_tiered_price(tiered: &TieredPricing, quantity: u32) -> Money {
-    for tier in &tiered.tiers {
-        if quantity >= tier.min_quantity {
-            if let Some(max) = tier.max_quantity {
-                if quantity <= max {
-                    return apply_tier_price(tier, quantity);
+/// Price a quantity over tiers
+///
+/// Units below the lowest tier are charged at its rate, and no quantity costs
+/// nothing.
+fn calculate_tiered_price(tiered: &TieredPricing, quantity: u32, tracer: &mut Tracer) -> Money {
+    let mut tiers: Vec<&PricingTier> = tiered.tiers.iter().collect();
+    tiers.sort_by_key(|tier| tier.min_quantity);
+
+    let unit = tiers
+        .first()
+        .map(|tier| tier_rate(tier).unit.clone())
+        .unwrap_or_else(|| "USD".to_string());
+    tracer.base(&Money {
+        value: 0.0,
+        unit: unit.clone(),
+    });
+    if quantity == 0 || tiers.is_empty() {
+        return Money { value: 0.0, unit };
+    }
+
+    let value = match tiered.mode {
+        TierMode::Graduated => {
+            let mut total = 0.0;
+            let mut charged = 0;
+            for (index, tier) in tiers.iter().enumerate() {
+                let lower = if index == 0 {
+                    1
+                } else {
+                    tier.min_quantity.max(charged + 1)
+                };
+                if quantity < lower {
+                    break;
+                }
+                let upper = tier.max_quantity.map_or(quantity, |max| max.min(quantity));
+                if upper < lower {
+                    continue;
                 }
-            } else {
-                return apply_tier_price(tier, quantity);
+                let units = upper - lower + 1;
+                let before = total;
+                total += apply_tier_price(tier, units, before, tracer);
+                charged = upper;
             }
+            total
         }
-    }
-    // Default to first tier if no match
-    tiered
-        .tiers
-        .first()
-        .map(|t| apply_tier_price(t, quantity))
-        .unwrap_or_else(|| Money {
-            value: 0.0,
-            unit: "USD".to_string(),
-        })
+        TierMode::AllUnits => {
+            let tier = tiers
+                .iter()
+                .rev()
+                .find(|tier| quantity >= tier.min_quantity)
+                .unwrap_or(&tiers[0]);
+            apply_tier_price(tier, quantity, 0.0, tracer)
+        }
+    };
+
+    Money { value, unit }
 }
 
-fn apply_tier_price(tier: &PricingTier, quantity: u32) -> Money {
-    if let Some(ref per_unit) = tier.price_per_unit {
-        Money {
-            value: per_unit.value * quantity as f64,
-            unit: per_unit.unit.clone(),
-        }
-    } else {
-        tier.price.clone()
-    }
+/// Rate of a tier: its per-unit price, or its flat price
+fn tier_rate(tier: &PricingTier) -> &Money {
+    tier.price_per_unit.as_ref().unwrap_or(&tier.price)
 }
 
-fn calculate_volume_price(volume: &VolumePricing, quantity: u32) -> Money {
+/// Charge `units` at a tier's rate, tracing it on top of `running_total`
+fn apply_tier_price(
+    tier: &PricingTier,
+    units: u32,
+    running_total: f64,
+    tracer: &mut Tracer,
+) -> f64 {
+    let charge = match tier.price_per_unit {
+        Some(ref per_unit) => per_unit.value * units as f64,
+        None => tier.price.value,
+    };
+
+    tracer.step(
+        PriceStepKind::Tier,
+        || {
+            let range = match tier.max_quantity {
+                Some(max) => format!("{}-{}", tier.min_quantity, max),
+                None => format!("{}+", tier.min_quantity),
+            };
+            match tier.price_per_unit {
+                Some(ref per_unit) => {
+                    format!("Tier {}: {} x {} per unit", range, units, per_unit.value)
+                }
+                None => format!("Tier {}: flat price", range),
+            }
+        },
+        running_total,
+        running_total + charge,
+    );
+    charge
+}
+
+fn calculate_volume_price(volume: &VolumePricing, quantity: u32, tracer: &mut Tracer) -> Money {
+    tracer.base(&volume.base_price);
     let mut final_price = volume.base_price.value;
     let mut best_discount: f64 = 0.0;
+    let mut best_min_volume = 0;
 
     for discount in &volume.volume_discounts {
-        if quantity >= discount.min_volume {
-            best_discount = best_discount.max(discount.discount_percentage);
+        if quantity >= discount.min_volume && discount.discount_percentage > best_discount {
+            best_discount = discount.discount_percentage;
+            best_min_volume = discount.min_volume;
         }
     }
 
+    let before = final_price;
     final_price *= 1.0 - (best_discount / 100.0);
+    if best_discount > 0.0 {
+        tracer.step(
+            PriceStepKind::VolumeDiscount,
+            || format!("{}% off from volume {}", best_discount, best_min_volume),
+            before,
+            final_price,
+        );
+    }
 
     Money {
-        value: final_price.max(0.0),
+        value: tracer.floor_at_zero(final_price),
         unit: volume.base_price.unit.clone(),
     }
 }
 
-fn calculate_subscription_price(sub: &SubscriptionPricing) -> Money {
+fn calculate_subscription_price(sub: &SubscriptionPricing, tracer: &mut Tracer) -> Money {
+    tracer.base(&sub.recurring_price);
     sub.recurring_price.clone()
 }
 
-fn calculate_dynamic_price(dynamic: &DynamicPricing, context: &PricingContext) -> Money {
+/// Prorate a plan change taking effect on `change_date`, within the cycle
+/// from `cycle_start` to `cycle_end` (exclusive)
+///
+/// Days are whole days, the change day being billed on the new plan. A change
+/// on the first day of the cycle credits and charges both plans in full; one
+/// taking effect when the cycle ends credits and charges nothing. Setup fees
+/// and trial periods are not prorated.
+pub fn prorate_change(
+    old: &SubscriptionPricing,
+    new: &SubscriptionPricing,
+    cycle_start: DateTime<Utc>,
+    cycle_end: DateTime<Utc>,
+    change_date: DateTime<Utc>,
+    basis: DayCountBasis,
+) -> Result<Proration, String> {
+    if old.billing_cycle != new.billing_cycle {
+        return Err(format!(
+            "Cannot prorate a change from a {:?} to a {:?} billing cycle",
+            old.billing_cycle, new.billing_cycle
+        ));
+    }
+    let (start, end, change) = (
+        cycle_start.date_naive(),
+        cycle_end.date_naive(),
+        change_date.date_naive(),
+    );
+    if end <= start {
+        return Err(format!("Billing cycle from {} to {} is empty", start, end));
+    }
+    if change < start || change > end {
+        return Err(format!(
+            "Change on {} is outside the billing cycle from {} to {}",
+            change, start, end
+        ));
+    }
+
+    let cycle_days = day_count(start, end, basis);
+    let remaining_days = day_count(change, end, basis).min(cycle_days);
+    let fraction = remaining_days as f64 / cycle_days as f64;
+    Ok(Proration {
+        credit: prorated(&old.recurring_price, fraction)?,
+        charge: prorated(&new.recurring_price, fraction)?,
+        remaining_days,
+        cycle_days,
+    })
+}
+
+/// Days from `from` to `to` under a day-count basis
+fn day_count(from: NaiveDate, to: NaiveDate, basis: DayCountBasis) -> u32 {
+    match basis {
+        DayCountBasis::ActualActual => (to - from).num_days().max(0) as u32,
+        DayCountBasis::Thirty360 => {
+            let days = 360 * (to.year() - from.year())
+                + 30 * (to.month() as i32 - from.month() as i32)
+                + (to.day().min(30) as i32 - from.day().min(30) as i32);
+            days.max(0) as u32
+        }
+    }
+}
+
+/// Fraction of a price, rounded to the precision of its currency
+fn prorated(price: &Money, fraction: f64) -> Result<Money, String> {
+    let currency = price.currency()?;
+    let amount = (price.minor_units()? as f64 * fraction).round() as i64;
+    Ok(Money::from_minor_units(amount, currency))
+}
+
+fn calculate_dynamic_price(
+    dynamic: &DynamicPricing,
+    context: &PricingContext,
+    tracer: &mut Tracer,
+) -> Money {
+    tracer.base(&dynamic.base_price);
     let mut price = dynamic.base_price.value;
 
     for factor in &dynamic.factors {
@@ -253,31 +503,538 @@ fn calculate_dynamic_price(dynamic: &DynamicPricing, context: &PricingContext) -
         };
 
         if let Some(adj) = adjustment {
+            let before = price;
             price *= 1.0 + adj;
+            tracer.step(
+                PriceStepKind::Factor,
+                || format!("{:?} factor: {:+}%", factor.factor_type, adj * 100.0),
+                before,
+                price,
+            );
         }
     }
 
     Money {
-        value: price.max(0.0),
+        value: tracer.floor_at_zero(price),
         unit: dynamic.base_price.unit.clone(),
     }
 }
 
-fn calculate_bundle_price(bundle: &BundlePricing, _context: &PricingContext) -> Money {
-    let total: f64 = bundle
+fn calculate_bundle_price(
+    bundle: &BundlePricing,
+    context: &PricingContext,
+    tracer: &mut Tracer,
+) -> Money {
+    let unit = bundle
         .component_prices
-        .iter()
-        .map(|cp| cp.price.value)
-        .sum();
+        .first()
+        .map(|cp| cp.price.unit.clone())
+        .unwrap_or_else(|| "USD".to_string());
+    tracer.base(&Money {
+        value: 0.0,
+        unit: unit.clone(),
+    });
+
+    let mut total = 0.0;
+    for cp in &bundle.component_prices {
+        let before = total;
+        total += cp.price.value;
+        tracer.step(
+            PriceStepKind::BundleComponent,
+            || format!("Component {}", cp.product_offering_id),
+            before,
+            total,
+        );
+    }
+
+    let mut discounts = Vec::new();
+    if bundle.bundle_discount != 0.0 {
+        discounts.push(("Bundle discount", bundle.bundle_discount));
+    }
+    for discount in &bundle.additional_discounts {
+        let missing = discount
+            .required_subscriptions
+            .iter()
+            .find(|id| !context.existing_subscriptions.contains(id));
+        match missing {
+            Some(missing) => tracer.suppress(
+                || format!("{}: {}% off", discount.name, discount.discount_percentage),
+                || format!("Requires subscription {}", missing),
+            ),
+            None => discounts.push((discount.name.as_str(), discount.discount_percentage)),
+        }
+    }
 
-    let discounted = total * (1.0 - bundle.bundle_discount / 100.0);
+    let discounted = apply_stacked_discounts(total, discounts, bundle.stacking_policy, tracer);
 
     Money {
-        value: discounted.max(0.0),
-        unit: bundle
-            .component_prices
-            .first()
-            .map(|cp| cp.price.unit.clone())
-            .unwrap_or_else(|| "USD".to_string()),
+        value: tracer.floor_at_zero(discounted),
+        unit,
+    }
+}
+
+/// Take the qualifying discounts off `total` as the stacking policy allows
+fn apply_stacked_discounts(
+    total: f64,
+    mut discounts: Vec<(&str, f64)>,
+    policy: StackingPolicy,
+    tracer: &mut Tracer,
+) -> f64 {
+    // Stable sort: equal discounts keep the order they are listed in
+    discounts.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut price = total;
+    let mut granted = 0.0;
+    for (index, (name, percentage)) in discounts.into_iter().enumerate() {
+        let describe = || format!("{}: {}% off", name, percentage);
+        let (applied, reduction) = match policy {
+            StackingPolicy::BestOnly if index > 0 => {
+                tracer.suppress(describe, || "Only the best discount applies".to_string());
+                continue;
+            }
+            StackingPolicy::BestOnly | StackingPolicy::Additive => {
+                (percentage, total * percentage / 100.0)
+            }
+            StackingPolicy::Multiplicative => (percentage, price * percentage / 100.0),
+            StackingPolicy::Capped {
+                max_total_percentage,
+            } => {
+                let cap = max_total_percentage.clamp(0.0, 100.0);
+                let allowed = percentage.min(cap - granted);
+                if allowed <= 0.0 {
+                    tracer.suppress(describe, || {
+                        format!("Maximum total discount of {}% reached", cap)
+                    });
+                    continue;
+                }
+                granted += allowed;
+                (allowed, total * allowed / 100.0)
+            }
+        };
+
+        let before = price;
+        price -= reduction;
+        tracer.step(
+            PriceStepKind::BundleDiscount,
+            || {
+                if applied < percentage {
+                    format!("{} (capped at {}%)", describe(), applied)
+                } else {
+                    describe()
+                }
+            },
+            before,
+            price,
+        );
+    }
+    price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(value: f64) -> Money {
+        Money {
+            value,
+            unit: "USD".to_string(),
+        }
+    }
+
+    fn tier(min_quantity: u32, max_quantity: Option<u32>, per_unit: f64) -> PricingTier {
+        PricingTier {
+            min_quantity,
+            max_quantity,
+            price: usd(0.0),
+            price_per_unit: Some(usd(per_unit)),
+        }
+    }
+
+    /// 1-10 at 1.00, 11-20 at 0.80, 21+ at 0.50
+    fn tiered(mode: TierMode) -> ComplexPricingModel {
+        ComplexPricingModel::Tiered(TieredPricing {
+            tiers: vec![
+                tier(1, Some(10), 1.0),
+                tier(11, Some(20), 0.8),
+                tier(21, None, 0.5),
+            ],
+            mode,
+        })
+    }
+
+    fn price(model: &ComplexPricingModel, quantity: u32) -> f64 {
+        let context = PricingContext {
+            quantity,
+            customer_id: None,
+            timestamp: Utc::now(),
+            demand_level: None,
+            inventory_level: None,
+            existing_subscriptions: Vec::new(),
+        };
+        calculate_complex_price(model, quantity, &context).value
+    }
+
+    fn assert_price(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_graduated_tiers_at_boundaries() {
+        let model = tiered(TierMode::Graduated);
+
+        assert_price(price(&model, 10), 10.0);
+        assert_price(price(&model, 11), 10.8);
+        assert_price(price(&model, 20), 18.0);
+        assert_price(price(&model, 21), 18.5);
+    }
+
+    #[test]
+    fn test_all_units_tiers_at_boundaries() {
+        let model = tiered(TierMode::AllUnits);
+
+        assert_price(price(&model, 10), 10.0);
+        assert_price(price(&model, 11), 8.8);
+        assert_price(price(&model, 20), 16.0);
+        assert_price(price(&model, 21), 10.5);
+    }
+
+    #[test]
+    fn test_zero_quantity_is_free() {
+        let mut flat = tier(1, None, 0.0);
+        flat.price_per_unit = None;
+        flat.price = usd(25.0);
+        let flat_model = ComplexPricingModel::Tiered(TieredPricing {
+            tiers: vec![flat],
+            mode: TierMode::AllUnits,
+        });
+
+        assert_price(price(&tiered(TierMode::Graduated), 0), 0.0);
+        assert_price(price(&tiered(TierMode::AllUnits), 0), 0.0);
+        assert_price(price(&flat_model, 0), 0.0);
+        assert_price(price(&flat_model, 1), 25.0);
+    }
+
+    #[test]
+    fn test_tier_mode_round_trips() {
+        let json = serde_json::to_value(tiered(TierMode::Graduated)).unwrap();
+        assert_eq!(json["Tiered"]["mode"], "GRADUATED");
+
+        let model: ComplexPricingModel = serde_json::from_value(json).unwrap();
+        match model {
+            ComplexPricingModel::Tiered(tiered) => assert_eq!(tiered.mode, TierMode::Graduated),
+            other => panic!("unexpected model {:?}", other),
+        }
+
+        // Catalogs written before modes existed keep all-units pricing
+        let legacy: TieredPricing = serde_json::from_str(r#"{"tiers": []}"#).unwrap();
+        assert_eq!(legacy.mode, TierMode::AllUnits);
+    }
+
+    /// Components worth 100, a 10% bundle discount, a 20% loyalty discount the
+    /// subscriber qualifies for and a 15% student discount they do not
+    fn bundle(stacking_policy: StackingPolicy, loyalty: Uuid) -> ComplexPricingModel {
+        ComplexPricingModel::Bundle(BundlePricing {
+            bundle_id: Uuid::new_v4(),
+            component_prices: vec![
+                ComponentPrice {
+                    product_offering_id: Uuid::new_v4(),
+                    price: usd(60.0),
+                    required: true,
+                },
+                ComponentPrice {
+                    product_offering_id: Uuid::new_v4(),
+                    price: usd(40.0),
+                    required: true,
+                },
+            ],
+            bundle_discount: 10.0,
+            minimum_components: None,
+            additional_discounts: vec![
+                BundleDiscount {
+                    name: "Loyalty".to_string(),
+                    discount_percentage: 20.0,
+                    required_subscriptions: vec![loyalty],
+                },
+                BundleDiscount {
+                    name: "Student".to_string(),
+                    discount_percentage: 15.0,
+                    required_subscriptions: vec![Uuid::new_v4()],
+                },
+            ],
+            stacking_policy,
+        })
+    }
+
+    fn bundle_trace(model: &ComplexPricingModel, subscriptions: Vec<Uuid>) -> (f64, PriceTrace) {
+        let context = PricingContext {
+            quantity: 1,
+            customer_id: None,
+            timestamp: Utc::now(),
+            demand_level: None,
+            inventory_level: None,
+            existing_subscriptions: subscriptions,
+        };
+        let (price, trace) = calculate_complex_price_with_trace(model, 1, &context);
+        (price.value, trace)
+    }
+
+    fn discount_steps(trace: &PriceTrace) -> usize {
+        trace
+            .steps
+            .iter()
+            .filter(|step| step.kind == PriceStepKind::BundleDiscount)
+            .count()
+    }
+
+    #[test]
+    fn test_bundle_stacking_policies() {
+        let loyalty = Uuid::new_v4();
+
+        let (price, trace) =
+            bundle_trace(&bundle(StackingPolicy::BestOnly, loyalty), vec![loyalty]);
+        assert_price(price, 80.0);
+        assert_eq!(discount_steps(&trace), 1);
+        assert_eq!(trace.suppressed.len(), 2);
+
+        let (price, trace) =
+            bundle_trace(&bundle(StackingPolicy::Additive, loyalty), vec![loyalty]);
+        assert_price(price, 70.0);
+        assert_eq!(discount_steps(&trace), 2);
+        assert_eq!(trace.suppressed.len(), 1);
+        assert!(trace.suppressed[0].description.starts_with("Student"));
+
+        let (price, _) = bundle_trace(
+            &bundle(StackingPolicy::Multiplicative, loyalty),
+            vec![loyalty],
+        );
+        assert_price(price, 72.0);
+
+        // Without the loyalty subscription only the bundle discount qualifies
+        let (price, trace) = bundle_trace(&bundle(StackingPolicy::Additive, loyalty), vec![]);
+        assert_price(price, 90.0);
+        assert_eq!(trace.suppressed.len(), 2);
+    }
+
+    #[test]
+    fn test_capped_stacking_limits_total_discount() {
+        let loyalty = Uuid::new_v4();
+        let capped = |max_total_percentage| {
+            bundle(
+                StackingPolicy::Capped {
+                    max_total_percentage,
+                },
+                loyalty,
+            )
+        };
+
+        // Loyalty in full, bundle discount partly
+        let (price, trace) = bundle_trace(&capped(25.0), vec![loyalty]);
+        assert_price(price, 75.0);
+        assert_eq!(discount_steps(&trace), 2);
+        assert_eq!(trace.suppressed.len(), 1);
+
+        // Bundle discount left out entirely
+        let (price, trace) = bundle_trace(&capped(20.0), vec![loyalty]);
+        assert_price(price, 80.0);
+        assert_eq!(trace.suppressed.len(), 2);
+    }
+
+    #[test]
+    fn test_stacked_discounts_never_go_negative() {
+        let loyalty = Uuid::new_v4();
+        let mut model = bundle(StackingPolicy::Additive, loyalty);
+        if let ComplexPricingModel::Bundle(ref mut pricing) = model {
+            pricing.bundle_discount = 90.0;
+        }
+
+        let (price, trace) = bundle_trace(&model, vec![loyalty]);
+        assert_price(price, 0.0);
+        assert!(trace
+            .steps
+            .iter()
+            .any(|step| step.kind == PriceStepKind::MinimumPrice));
+
+        if let ComplexPricingModel::Bundle(ref mut pricing) = model {
+            pricing.stacking_policy = StackingPolicy::Capped {
+                max_total_percentage: 150.0,
+            };
+        }
+        let (price, trace) = bundle_trace(&model, vec![loyalty]);
+        assert_price(price, 0.0);
+        assert!(trace.steps.iter().all(|step| step.price_after >= 0.0));
+    }
+
+    #[test]
+    fn test_stacking_policy_round_trips() {
+        let policy = StackingPolicy::Capped {
+            max_total_percentage: 25.0,
+        };
+        let json = serde_json::to_value(policy).unwrap();
+        assert_eq!(json["CAPPED"]["max_total_percentage"], 25.0);
+        assert_eq!(
+            serde_json::from_value::<StackingPolicy>(json).unwrap(),
+            policy
+        );
+        assert_eq!(
+            serde_json::to_value(StackingPolicy::BestOnly).unwrap(),
+            "BEST_ONLY"
+        );
+
+        // Bundles written before stacking policies existed do not stack
+        let legacy: BundlePricing = serde_json::from_str(
+            r#"{"bundle_id": "6f1d3c8e-2a4b-4c5d-8e9f-0a1b2c3d4e5f", "component_prices": [],
+                "bundle_discount": 10.0, "minimum_components": null}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.stacking_policy, StackingPolicy::BestOnly);
+        assert!(legacy.additional_discounts.is_empty());
+    }
+
+    fn monthly(price: f64) -> SubscriptionPricing {
+        SubscriptionPricing {
+            recurring_price: usd(price),
+            billing_cycle: BillingCycle::Monthly,
+            setup_fee: None,
+            trial_period_days: None,
+            cancellation_policy: CancellationPolicy::ProRated,
+        }
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_proration_counts_actual_days() {
+        // February 2024 has 29 days; 15 remain from the 15th
+        let proration = prorate_change(
+            &monthly(29.0),
+            &monthly(58.0),
+            day(2024, 2, 1),
+            day(2024, 3, 1),
+            day(2024, 2, 15),
+            DayCountBasis::ActualActual,
+        )
+        .unwrap();
+        assert_eq!((proration.remaining_days, proration.cycle_days), (15, 29));
+        assert_price(proration.credit.value, 15.0);
+        assert_price(proration.charge.value, 30.0);
+
+        // A 31-day cycle
+        let proration = prorate_change(
+            &monthly(31.0),
+            &monthly(62.0),
+            day(2026, 1, 1),
+            day(2026, 2, 1),
+            day(2026, 1, 21),
+            DayCountBasis::ActualActual,
+        )
+        .unwrap();
+        assert_eq!((proration.remaining_days, proration.cycle_days), (11, 31));
+        assert_price(proration.credit.value, 11.0);
+        assert_price(proration.charge.value, 22.0);
+    }
+
+    #[test]
+    fn test_proration_on_thirty_360_basis() {
+        let proration = prorate_change(
+            &monthly(30.0),
+            &monthly(60.0),
+            day(2026, 1, 1),
+            day(2026, 2, 1),
+            day(2026, 1, 21),
+            DayCountBasis::Thirty360,
+        )
+        .unwrap();
+        assert_eq!((proration.remaining_days, proration.cycle_days), (10, 30));
+        assert_price(proration.credit.value, 10.0);
+        assert_price(proration.charge.value, 20.0);
+
+        // February counts 30 days too
+        let proration = prorate_change(
+            &monthly(30.0),
+            &monthly(30.0),
+            day(2026, 2, 1),
+            day(2026, 3, 1),
+            day(2026, 2, 16),
+            DayCountBasis::Thirty360,
+        )
+        .unwrap();
+        assert_eq!((proration.remaining_days, proration.cycle_days), (15, 30));
+    }
+
+    #[test]
+    fn test_same_day_changes_net_zero() {
+        let (start, end) = (day(2026, 3, 1), day(2026, 4, 1));
+
+        // Nothing is left of the cycle when the change takes effect at its end
+        let at_end = prorate_change(
+            &monthly(20.0),
+            &monthly(50.0),
+            start,
+            end,
+            end,
+            DayCountBasis::ActualActual,
+        )
+        .unwrap();
+        assert_price(at_end.credit.value, 0.0);
+        assert_price(at_end.charge.value, 0.0);
+
+        // Changing to an equally priced plan credits what it charges
+        let same_price = prorate_change(
+            &monthly(19.99),
+            &monthly(19.99),
+            start,
+            end,
+            day(2026, 3, 10),
+            DayCountBasis::ActualActual,
+        )
+        .unwrap();
+        assert_eq!(same_price.credit.value, same_price.charge.value);
+
+        // Changing on the first day swaps the plans for the whole cycle
+        let at_start = prorate_change(
+            &monthly(20.0),
+            &monthly(50.0),
+            start,
+            end,
+            start,
+            DayCountBasis::ActualActual,
+        )
+        .unwrap();
+        assert_price(at_start.credit.value, 20.0);
+        assert_price(at_start.charge.value, 50.0);
+    }
+
+    #[test]
+    fn test_proration_rejects_invalid_changes() {
+        let mut annual = monthly(240.0);
+        annual.billing_cycle = BillingCycle::Annual;
+        let (start, end) = (day(2026, 3, 1), day(2026, 4, 1));
+
+        assert!(prorate_change(
+            &monthly(20.0),
+            &annual,
+            start,
+            end,
+            day(2026, 3, 10),
+            DayCountBasis::ActualActual
+        )
+        .is_err());
+        assert!(prorate_change(
+            &monthly(20.0),
+            &monthly(30.0),
+            start,
+            end,
+            day(2026, 4, 2),
+            DayCountBasis::ActualActual
+        )
+        .is_err());
     }
 }
@@ -0,0 +1,157 @@
+//! Currencies and exchange rates
+//!
+//! Currencies are identified by their ISO 4217 code and know their minor-unit
+//! precision, so amounts can be held as integer minor units (cents, yen, fils)
+//! and rounded the way each currency is.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Currencies without minor units
+const ZERO_DECIMAL: [&str; 17] = [
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND",
+    "VUV", "XAF", "XOF", "XPF",
+];
+
+/// Currencies with three decimal places
+const THREE_DECIMAL: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// ISO 4217 currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const USD: Currency = Currency(*b"USD");
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const GBP: Currency = Currency(*b"GBP");
+    pub const BRL: Currency = Currency(*b"BRL");
+    pub const JPY: Currency = Currency(*b"JPY");
+
+    /// Three-letter currency code
+    pub fn code(&self) -> &str {
+        // Only ASCII letters are accepted on construction
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    /// Number of decimal places of the currency's minor unit
+    pub fn minor_units(&self) -> u32 {
+        let code = self.code();
+        if ZERO_DECIMAL.contains(&code) {
+            0
+        } else if THREE_DECIMAL.contains(&code) {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Number of minor units in one major unit
+    pub fn minor_unit_factor(&self) -> i64 {
+        10_i64.pow(self.minor_units())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let bytes = code.trim().as_bytes();
+        match bytes {
+            [a, b, c] if bytes.iter().all(u8::is_ascii_alphabetic) => Ok(Currency([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => Err(format!("Invalid currency code '{}'", code)),
+        }
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        code.parse()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Exchange rates between currencies
+///
+/// A rate gives the amount of the target currency bought by one unit of the
+/// source currency. A rate registered one way is also used, inverted, the
+/// other way.
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeRateTable {
+    rates: HashMap<(Currency, Currency), f64>,
+}
+
+impl ExchangeRateTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rate, replacing any previous rate between the same currencies
+    pub fn with_rate(mut self, from: Currency, to: Currency, rate: f64) -> Result<Self, String> {
+        self.set_rate(from, to, rate)?;
+        Ok(self)
+    }
+
+    /// Set a rate, replacing any previous rate between the same currencies
+    ///
+    /// Rates must be finite and positive.
+    pub fn set_rate(&mut self, from: Currency, to: Currency, rate: f64) -> Result<(), String> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(format!(
+                "Exchange rate from {} to {} must be finite and positive, got {}",
+                from, to, rate
+            ));
+        }
+        self.rates.remove(&(to, from));
+        self.rates.insert((from, to), rate);
+        Ok(())
+    }
+
+    /// Rate from one currency to another, if known
+    pub fn rate(&self, from: Currency, to: Currency) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&(from, to))
+            .copied()
+            .or_else(|| self.rates.get(&(to, from)).map(|rate| 1.0 / rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_rates_are_rejected() {
+        let mut rates = ExchangeRateTable::new();
+        for rate in [0.0, -1.5, f64::NAN, f64::INFINITY] {
+            assert!(rates.set_rate(Currency::USD, Currency::BRL, rate).is_err());
+        }
+        assert_eq!(rates.rate(Currency::USD, Currency::BRL), None);
+
+        let rates = rates.with_rate(Currency::USD, Currency::BRL, 5.0).unwrap();
+        assert_eq!(rates.rate(Currency::BRL, Currency::USD), Some(0.2));
+    }
+}
@@ -42,6 +42,10 @@ pub enum EligibilityConditionOperator {
     NotContains,
     In,
     NotIn,
+    /// Customer must already hold every product offering listed (comma-separated ids)
+    RequiresProducts,
+    /// Customer must hold none of the product offerings listed (comma-separated ids)
+    ForbidsProducts,
 }
 
 /// Eligibility context for validation
@@ -49,10 +53,278 @@ pub enum EligibilityConditionOperator {
 pub struct EligibilityContext {
     pub customer_id: Option<Uuid>,
     pub customer_segment: Option<String>,
+    /// Product offerings the customer currently holds as active subscriptions
     pub existing_products: Vec<Uuid>,
     pub customer_attributes: std::collections::HashMap<String, String>,
 }
 
+/// Eligibility outcome with the reasons behind a rejection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EligibilityOutcome {
+    pub eligible: bool,
+    pub reasons: Vec<String>,
+}
+
+/// Step-by-step account of an eligibility evaluation, for support agents
+/// looking into why an offering was refused
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EligibilityTrace {
+    pub product_offering_id: Uuid,
+    pub eligible: bool,
+    /// Every rule of the offering, in evaluation order
+    pub rules: Vec<EligibilityRuleTrace>,
+}
+
+/// Evaluation of one eligibility rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EligibilityRuleTrace {
+    pub rule_id: Uuid,
+    pub rule_type: EligibilityRuleType,
+    pub passed: bool,
+    pub conditions: Vec<EligibilityConditionTrace>,
+}
+
+/// Evaluation of one eligibility condition
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EligibilityConditionTrace {
+    pub field: String,
+    pub operator: EligibilityConditionOperator,
+    /// Value the condition expects
+    pub expected: String,
+    /// Customer value the condition was evaluated against; for product
+    /// conditions, the listed product offerings the customer holds
+    pub actual: String,
+    pub passed: bool,
+    /// Why the condition is not met
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Named set of eligibility rules, one variant of an experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EligibilityRuleSet {
+    pub name: String,
+    pub rules: Vec<EligibilityRule>,
+}
+
+/// Variant of an eligibility experiment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EligibilityVariant {
+    /// Control rule set
+    A,
+    /// Rule set under test
+    B,
+}
+
+/// A/B test of two eligibility rule sets
+///
+/// Customers are split by a hash of the experiment name and customer id, so a
+/// customer always sees the same variant of an experiment, on every instance
+/// and across restarts, while different experiments split customers
+/// independently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EligibilityExperiment {
+    pub name: String,
+    pub variant_a: EligibilityRuleSet,
+    pub variant_b: EligibilityRuleSet,
+    /// Percentage of customers (0-100) evaluated with variant B
+    pub variant_b_percent: u8,
+}
+
+impl EligibilityExperiment {
+    /// Rule set of a variant
+    pub fn rule_set(&self, variant: EligibilityVariant) -> &EligibilityRuleSet {
+        match variant {
+            EligibilityVariant::A => &self.variant_a,
+            EligibilityVariant::B => &self.variant_b,
+        }
+    }
+}
+
+/// Eligibility outcome together with the experiment variant that produced it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VariantEligibilityOutcome {
+    pub experiment: String,
+    pub variant: EligibilityVariant,
+    pub rule_set: String,
+    pub outcome: EligibilityOutcome,
+}
+
+/// Validate an eligibility rule
+///
+/// An `Any` rule without conditions could never be met, so it is rejected
+/// rather than silently refusing the offering to every customer.
+pub fn validate_eligibility_rule(rule: &EligibilityRule) -> Result<(), String> {
+    if rule.rule_type == EligibilityRuleType::Any && rule.conditions.is_empty() {
+        return Err(format!(
+            "Eligibility rule {} of type ANY must have at least one condition",
+            rule.id
+        ));
+    }
+    Ok(())
+}
+
+/// Validate an eligibility experiment
+pub fn validate_eligibility_experiment(experiment: &EligibilityExperiment) -> Result<(), String> {
+    if experiment.name.trim().is_empty() {
+        return Err("Eligibility experiment must have a name".to_string());
+    }
+    if experiment.variant_b_percent > 100 {
+        return Err(format!(
+            "Eligibility experiment {} assigns {}% of customers to variant B, must be at most 100%",
+            experiment.name, experiment.variant_b_percent
+        ));
+    }
+    if experiment.variant_a.name == experiment.variant_b.name {
+        return Err(format!(
+            "Eligibility experiment {} must have differently named rule sets, both are named {}",
+            experiment.name, experiment.variant_a.name
+        ));
+    }
+    experiment
+        .variant_a
+        .rules
+        .iter()
+        .chain(&experiment.variant_b.rules)
+        .try_for_each(validate_eligibility_rule)
+}
+
+/// Select the variant a customer is evaluated with
+///
+/// Customers without an id cannot be assigned consistently and always get
+/// variant A.
+pub fn select_eligibility_variant(
+    experiment: &EligibilityExperiment,
+    customer_id: Option<Uuid>,
+) -> EligibilityVariant {
+    let Some(customer_id) = customer_id else {
+        return EligibilityVariant::A;
+    };
+
+    let mut key = experiment.name.as_bytes().to_vec();
+    key.push(b':');
+    key.extend_from_slice(customer_id.as_bytes());
+    if fnv1a(&key) % 100 < experiment.variant_b_percent as u64 {
+        EligibilityVariant::B
+    } else {
+        EligibilityVariant::A
+    }
+}
+
+/// Evaluate a product offering under the customer's variant of an experiment
+pub fn evaluate_eligibility_variant(
+    experiment: &EligibilityExperiment,
+    product_offering_id: Uuid,
+    context: &EligibilityContext,
+) -> VariantEligibilityOutcome {
+    let variant = select_eligibility_variant(experiment, context.customer_id);
+    let rule_set = experiment.rule_set(variant);
+
+    VariantEligibilityOutcome {
+        experiment: experiment.name.clone(),
+        variant,
+        rule_set: rule_set.name.clone(),
+        outcome: evaluate_offering_eligibility(&rule_set.rules, product_offering_id, context),
+    }
+}
+
+/// Evaluate every rule of a product offering, collecting the reasons of all
+/// rules that are not met
+pub fn evaluate_offering_eligibility(
+    rules: &[EligibilityRule],
+    product_offering_id: Uuid,
+    context: &EligibilityContext,
+) -> EligibilityOutcome {
+    let failed: Vec<EligibilityOutcome> = rules
+        .iter()
+        .filter(|rule| rule.product_offering_id == product_offering_id)
+        .map(|rule| evaluate_eligibility(rule, context))
+        .filter(|outcome| !outcome.eligible)
+        .collect();
+
+    EligibilityOutcome {
+        eligible: failed.is_empty(),
+        reasons: failed
+            .into_iter()
+            .flat_map(|outcome| outcome.reasons)
+            .collect(),
+    }
+}
+
+/// Evaluate every rule of a product offering like
+/// [`evaluate_offering_eligibility`], recording each condition with the
+/// customer value it was checked against
+///
+/// Slower than [`is_eligible`], as every condition is evaluated and
+/// described; use it to explain decisions rather than to make them.
+pub fn evaluate_offering_eligibility_with_trace(
+    rules: &[EligibilityRule],
+    product_offering_id: Uuid,
+    context: &EligibilityContext,
+) -> EligibilityTrace {
+    let rules: Vec<EligibilityRuleTrace> = rules
+        .iter()
+        .filter(|rule| rule.product_offering_id == product_offering_id)
+        .map(|rule| {
+            let conditions: Vec<EligibilityConditionTrace> = rule
+                .conditions
+                .iter()
+                .map(|condition| trace_condition(condition, context))
+                .collect();
+            let failed = conditions.iter().filter(|c| !c.passed).count();
+            EligibilityRuleTrace {
+                rule_id: rule.id,
+                rule_type: rule.rule_type.clone(),
+                passed: match rule.rule_type {
+                    EligibilityRuleType::All => failed == 0,
+                    EligibilityRuleType::Any => failed < conditions.len(),
+                },
+                conditions,
+            }
+        })
+        .collect();
+
+    EligibilityTrace {
+        product_offering_id,
+        eligible: rules.iter().all(|rule| rule.passed),
+        rules,
+    }
+}
+
+fn trace_condition(
+    condition: &EligibilityCondition,
+    context: &EligibilityContext,
+) -> EligibilityConditionTrace {
+    let actual = match condition.operator {
+        EligibilityConditionOperator::RequiresProducts
+        | EligibilityConditionOperator::ForbidsProducts => parse_product_ids(&condition.value)
+            .into_iter()
+            .filter_map(|id| id.ok())
+            .filter(|id| context.existing_products.contains(id))
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => get_field_value(&condition.field, context),
+    };
+    let reason = condition_failure(condition, context);
+
+    EligibilityConditionTrace {
+        field: condition.field.clone(),
+        operator: condition.operator.clone(),
+        expected: condition.value.clone(),
+        actual,
+        passed: reason.is_none(),
+        reason,
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
 /// Check if a product offering is eligible for a customer
 pub fn is_eligible(rule: &EligibilityRule, context: &EligibilityContext) -> bool {
     match rule.rule_type {
@@ -67,7 +339,99 @@ pub fn is_eligible(rule: &EligibilityRule, context: &EligibilityContext) -> bool
     }
 }
 
+/// Evaluate a rule and explain which conditions were not met
+pub fn evaluate_eligibility(
+    rule: &EligibilityRule,
+    context: &EligibilityContext,
+) -> EligibilityOutcome {
+    let mut failures: Vec<String> = rule
+        .conditions
+        .iter()
+        .filter_map(|condition| condition_failure(condition, context))
+        .collect();
+    if rule.rule_type == EligibilityRuleType::Any && rule.conditions.is_empty() {
+        failures.push(format!("Eligibility rule {} has no conditions", rule.id));
+    }
+
+    let eligible = match rule.rule_type {
+        EligibilityRuleType::All => failures.is_empty(),
+        EligibilityRuleType::Any => failures.len() < rule.conditions.len(),
+    };
+
+    EligibilityOutcome {
+        eligible,
+        reasons: if eligible { Vec::new() } else { failures },
+    }
+}
+
 fn evaluate_condition(condition: &EligibilityCondition, context: &EligibilityContext) -> bool {
+    condition_failure(condition, context).is_none()
+}
+
+/// Returns the reason a condition is not met, or `None` when it holds
+fn condition_failure(
+    condition: &EligibilityCondition,
+    context: &EligibilityContext,
+) -> Option<String> {
+    match condition.operator {
+        EligibilityConditionOperator::RequiresProducts => {
+            let missing: Vec<String> = parse_product_ids(&condition.value)
+                .into_iter()
+                .filter(|id| !matches!(id, Ok(id) if context.existing_products.contains(id)))
+                .map(|id| id.map(|id| id.to_string()).unwrap_or_else(|raw| raw))
+                .collect();
+            if missing.is_empty() {
+                None
+            } else {
+                Some(format!(
+                    "Requires active product(s) not held by customer: {}",
+                    missing.join(", ")
+                ))
+            }
+        }
+        EligibilityConditionOperator::ForbidsProducts => {
+            let held: Vec<String> = parse_product_ids(&condition.value)
+                .into_iter()
+                .filter_map(|id| id.ok())
+                .filter(|id| context.existing_products.contains(id))
+                .map(|id| id.to_string())
+                .collect();
+            if held.is_empty() {
+                None
+            } else {
+                Some(format!(
+                    "Incompatible with active product(s) held by customer: {}",
+                    held.join(", ")
+                ))
+            }
+        }
+        _ => {
+            if evaluate_value_condition(condition, context) {
+                None
+            } else {
+                Some(format!(
+                    "Condition not met: {} {:?} {}",
+                    condition.field, condition.operator, condition.value
+                ))
+            }
+        }
+    }
+}
+
+/// Parse a comma-separated list of product offering ids, keeping unparseable entries
+fn parse_product_ids(value: &str) -> Vec<Result<Uuid, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| Uuid::parse_str(v).map_err(|_| v.to_string()))
+        .collect()
+}
+
+fn evaluate_value_condition(
+    condition: &EligibilityCondition,
+    context: &EligibilityContext,
+) -> bool {
     let field_value = get_field_value(&condition.field, context);
 
     match condition.operator {
@@ -99,6 +463,8 @@ fn evaluate_condition(condition: &EligibilityCondition, context: &EligibilityCon
         EligibilityConditionOperator::NotIn => {
             !condition.value.split(',').any(|v| v.trim() == field_value)
         }
+        EligibilityConditionOperator::RequiresProducts
+        | EligibilityConditionOperator::ForbidsProducts => false,
     }
 }
 
@@ -1,38 +1,153 @@
 //! Main Catalog Engine
 
-use crate::bundling::{validate_bundle, Bundle};
-use crate::eligibility::{is_eligible, EligibilityContext, EligibilityRule};
-use crate::pricing::{calculate_final_price, PricingContext, PricingRule};
-use crate::rules::{evaluate_rule, CatalogRule, RuleContext};
+use crate::bundling::{
+    is_candidate_bundle, recommend_bundles, validate_bundle, Bundle, BundleRecommendation, CartItem,
+};
+use crate::change_feed::{
+    OfferingChangeEvent, OfferingChangeFeed, OfferingChangeSubscriber, OfferingChangeType,
+};
+use crate::eligibility::{
+    evaluate_eligibility_variant, evaluate_offering_eligibility,
+    evaluate_offering_eligibility_with_trace, is_eligible, validate_eligibility_experiment,
+    validate_eligibility_rule, EligibilityContext, EligibilityExperiment, EligibilityOutcome,
+    EligibilityRule, EligibilityTrace, VariantEligibilityOutcome,
+};
+use crate::import::{
+    validate_import, CatalogImport, CatalogOffering, CatalogSpecification, ExistingCatalog,
+    ImportReport,
+};
+use crate::price_trace::PriceTrace;
+use crate::pricing::{
+    calculate_final_price, calculate_final_price_with_trace, validate_effective_window,
+    GuardedPrice, PricingContext, PricingRule,
+};
+use crate::rules::{evaluate_rule, validate_rule_graph, CatalogRule, RuleContext, RuleCycleError};
+use std::collections::HashSet;
+use std::sync::{mpsc, Arc};
 use uuid::Uuid;
 
 /// Main Product Catalog Engine
 pub struct CatalogEngine {
+    product_specifications: Vec<CatalogSpecification>,
+    product_offerings: Vec<CatalogOffering>,
     pricing_rules: Vec<PricingRule>,
     eligibility_rules: Vec<EligibilityRule>,
+    eligibility_experiment: Option<EligibilityExperiment>,
     bundles: Vec<Bundle>,
     catalog_rules: Vec<CatalogRule>,
+    change_feed: OfferingChangeFeed,
 }
 
 impl CatalogEngine {
     /// Create a new catalog engine
     pub fn new() -> Self {
         Self {
+            product_specifications: Vec::new(),
+            product_offerings: Vec::new(),
             pricing_rules: Vec::new(),
             eligibility_rules: Vec::new(),
+            eligibility_experiment: None,
             bundles: Vec::new(),
             catalog_rules: Vec::new(),
+            change_feed: OfferingChangeFeed::new(),
         }
     }
 
+    /// Register a subscriber for offering changes
+    pub fn subscribe_offering_changes(&mut self, subscriber: Arc<dyn OfferingChangeSubscriber>) {
+        self.change_feed.subscribe(subscriber);
+    }
+
+    /// Receive offering changes through a channel
+    pub fn offering_change_channel(&mut self) -> mpsc::Receiver<OfferingChangeEvent> {
+        self.change_feed.subscribe_channel()
+    }
+
+    /// Offering changes after `sequence`, or `None` if some were already
+    /// dropped from the feed history and the catalog must be re-read
+    pub fn offering_changes_since(&self, sequence: u64) -> Option<Vec<OfferingChangeEvent>> {
+        self.change_feed.changes_since(sequence)
+    }
+
+    /// Sequence number of the latest offering change
+    pub fn latest_offering_change(&self) -> u64 {
+        self.change_feed.latest_sequence()
+    }
+
+    /// Add a product offering
+    pub fn add_offering(&mut self, offering: CatalogOffering) -> Result<(), String> {
+        if self.get_offering(offering.id).is_some() {
+            return Err(format!("Product offering {} already exists", offering.id));
+        }
+        let offering_id = offering.id;
+        self.product_offerings.push(offering);
+        self.change_feed
+            .record(offering_id, OfferingChangeType::Created);
+        Ok(())
+    }
+
+    /// Replace an existing product offering
+    pub fn update_offering(&mut self, offering: CatalogOffering) -> Result<(), String> {
+        let existing = self
+            .product_offerings
+            .iter_mut()
+            .find(|existing| existing.id == offering.id)
+            .ok_or_else(|| format!("Product offering {} not found", offering.id))?;
+        let offering_id = offering.id;
+        *existing = offering;
+        self.change_feed
+            .record(offering_id, OfferingChangeType::Updated);
+        Ok(())
+    }
+
+    /// Remove a product offering, returning it if it existed
+    pub fn remove_offering(&mut self, product_offering_id: Uuid) -> Option<CatalogOffering> {
+        let index = self
+            .product_offerings
+            .iter()
+            .position(|offering| offering.id == product_offering_id)?;
+        let removed = self.product_offerings.remove(index);
+        self.change_feed
+            .record(product_offering_id, OfferingChangeType::Deleted);
+        Some(removed)
+    }
+
     /// Add a pricing rule
-    pub fn add_pricing_rule(&mut self, rule: PricingRule) {
+    ///
+    /// Rejects rules whose effective window is empty or identical to an existing
+    /// rule's for the same product offering and price type; overlapping windows
+    /// are resolved by specificity when priced.
+    pub fn add_pricing_rule(&mut self, rule: PricingRule) -> Result<(), String> {
+        validate_effective_window(&self.pricing_rules, &rule)?;
+        let offering_id = rule.product_offering_id;
         self.pricing_rules.push(rule);
+        self.change_feed
+            .record(offering_id, OfferingChangeType::PriceChanged);
+        Ok(())
     }
 
     /// Add an eligibility rule
-    pub fn add_eligibility_rule(&mut self, rule: EligibilityRule) {
+    ///
+    /// Rejects `Any` rules without conditions, which no customer could meet.
+    pub fn add_eligibility_rule(&mut self, rule: EligibilityRule) -> Result<(), String> {
+        validate_eligibility_rule(&rule)?;
         self.eligibility_rules.push(rule);
+        Ok(())
+    }
+
+    /// Start an A/B test of eligibility rule sets, replacing any running one
+    pub fn set_eligibility_experiment(
+        &mut self,
+        experiment: EligibilityExperiment,
+    ) -> Result<(), String> {
+        validate_eligibility_experiment(&experiment)?;
+        self.eligibility_experiment = Some(experiment);
+        Ok(())
+    }
+
+    /// Stop the running eligibility experiment
+    pub fn clear_eligibility_experiment(&mut self) -> Option<EligibilityExperiment> {
+        self.eligibility_experiment.take()
     }
 
     /// Add a bundle
@@ -43,8 +158,88 @@ impl CatalogEngine {
     }
 
     /// Add a catalog rule
-    pub fn add_catalog_rule(&mut self, rule: CatalogRule) {
+    ///
+    /// Rejects a rule that would close a dependency cycle among the catalog
+    /// rules.
+    pub fn add_catalog_rule(&mut self, rule: CatalogRule) -> Result<(), RuleCycleError> {
         self.catalog_rules.push(rule);
+        if let Err(e) = self.validate_rule_graph() {
+            self.catalog_rules.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Check that no catalog rules depend on each other in a cycle, reporting
+    /// every cycle found
+    pub fn validate_rule_graph(&self) -> Result<(), RuleCycleError> {
+        validate_rule_graph(&self.catalog_rules)
+    }
+
+    /// Validate a catalog import without mutating the catalog
+    ///
+    /// Reports duplicate ids and dangling references as errors, and offerings
+    /// without any pricing rule as warnings.
+    pub fn dry_run_import(&self, import: &CatalogImport) -> ImportReport {
+        validate_import(
+            import,
+            &ExistingCatalog {
+                specifications: &self.product_specifications,
+                offerings: &self.product_offerings,
+                pricing_rules: &self.pricing_rules,
+                eligibility_rules: &self.eligibility_rules,
+                bundles: &self.bundles,
+                catalog_rules: &self.catalog_rules,
+            },
+        )
+    }
+
+    /// Import catalog content atomically
+    ///
+    /// Nothing is written unless the whole import validates; the failing report
+    /// is returned otherwise.
+    pub fn import_catalog(&mut self, import: CatalogImport) -> Result<ImportReport, ImportReport> {
+        let report = self.dry_run_import(&import);
+        if !report.is_valid() {
+            return Err(report);
+        }
+
+        // New offerings are reported as created only; pricing rules imported
+        // for offerings already in the catalog are reported as price changes
+        let created: Vec<Uuid> = import.product_offerings.iter().map(|o| o.id).collect();
+        let mut seen = HashSet::new();
+        let repriced: Vec<Uuid> = import
+            .pricing_rules
+            .iter()
+            .map(|rule| rule.product_offering_id)
+            .filter(|id| !created.contains(id) && seen.insert(*id))
+            .collect();
+
+        self.product_specifications
+            .extend(import.product_specifications);
+        self.product_offerings.extend(import.product_offerings);
+        self.pricing_rules.extend(import.pricing_rules);
+        self.eligibility_rules.extend(import.eligibility_rules);
+        self.bundles.extend(import.bundles);
+        self.catalog_rules.extend(import.catalog_rules);
+
+        for offering_id in created {
+            self.change_feed
+                .record(offering_id, OfferingChangeType::Created);
+        }
+        for offering_id in repriced {
+            self.change_feed
+                .record(offering_id, OfferingChangeType::PriceChanged);
+        }
+
+        Ok(report)
+    }
+
+    /// Get a product offering by id
+    pub fn get_offering(&self, product_offering_id: Uuid) -> Option<&CatalogOffering> {
+        self.product_offerings
+            .iter()
+            .find(|offering| offering.id == product_offering_id)
     }
 
     /// Check if a product is eligible for a customer
@@ -59,16 +254,72 @@ impl CatalogEngine {
             .all(|rule| is_eligible(rule, context))
     }
 
+    /// Check eligibility and collect the reasons when a product is not eligible
+    pub fn check_eligibility_with_reasons(
+        &self,
+        product_offering_id: Uuid,
+        context: &EligibilityContext,
+    ) -> EligibilityOutcome {
+        evaluate_offering_eligibility(&self.eligibility_rules, product_offering_id, context)
+    }
+
+    /// Check eligibility, recording every rule and condition evaluated with
+    /// the customer values they were checked against
+    pub fn check_eligibility_with_trace(
+        &self,
+        product_offering_id: Uuid,
+        context: &EligibilityContext,
+    ) -> EligibilityTrace {
+        evaluate_offering_eligibility_with_trace(
+            &self.eligibility_rules,
+            product_offering_id,
+            context,
+        )
+    }
+
+    /// Check eligibility with the customer's variant of the running eligibility
+    /// experiment, returning the variant used for analytics
+    ///
+    /// Returns `None` when no experiment is running.
+    pub fn check_eligibility_variant(
+        &self,
+        product_offering_id: Uuid,
+        context: &EligibilityContext,
+    ) -> Option<VariantEligibilityOutcome> {
+        self.eligibility_experiment.as_ref().map(|experiment| {
+            evaluate_eligibility_variant(experiment, product_offering_id, context)
+        })
+    }
+
     /// Calculate price for a product offering
+    ///
+    /// See [`calculate_final_price`] for currency handling and price
+    /// guardrails.
     pub fn calculate_price(
         &self,
         product_offering_id: Uuid,
         context: &PricingContext,
-    ) -> Option<crate::pricing::Money> {
-        self.pricing_rules
-            .iter()
-            .find(|rule| rule.product_offering_id == product_offering_id)
-            .map(|rule| calculate_final_price(rule, context))
+    ) -> Result<Option<GuardedPrice>, String> {
+        calculate_final_price(
+            self.pricing_rules
+                .iter()
+                .filter(|rule| rule.product_offering_id == product_offering_id),
+            context,
+        )
+    }
+
+    /// Calculate price for a product offering along with how it was derived
+    pub fn calculate_price_with_trace(
+        &self,
+        product_offering_id: Uuid,
+        context: &PricingContext,
+    ) -> Result<Option<(GuardedPrice, PriceTrace)>, String> {
+        calculate_final_price_with_trace(
+            self.pricing_rules
+                .iter()
+                .filter(|rule| rule.product_offering_id == product_offering_id),
+            context,
+        )
     }
 
     /// Get bundles for a product
@@ -84,6 +335,38 @@ impl CatalogEngine {
             .collect()
     }
 
+    /// Recommend bundles that would make a cart cheaper, priced at
+    /// `context.pricing_date`
+    ///
+    /// See [`recommend_bundles`] for how bundles are matched. Only the
+    /// components of candidate bundles are priced; a component without a price
+    /// at that date, or whose price cannot be calculated, leaves the bundles
+    /// that need it out of the recommendations.
+    pub fn recommend_bundles(
+        &self,
+        cart_items: &[CartItem],
+        context: &PricingContext,
+    ) -> Result<Vec<BundleRecommendation>, String> {
+        let candidates: Vec<&Bundle> = self
+            .bundles
+            .iter()
+            .filter(|bundle| is_candidate_bundle(bundle, cart_items))
+            .collect();
+
+        let mut individual_prices: Vec<(Uuid, f64)> = Vec::new();
+        let mut seen = HashSet::new();
+        for bp in candidates.iter().flat_map(|bundle| &bundle.products) {
+            if !seen.insert(bp.product_offering_id) {
+                continue;
+            }
+            if let Ok(Some(price)) = self.calculate_price(bp.product_offering_id, context) {
+                individual_prices.push((bp.product_offering_id, price.price.value));
+            }
+        }
+
+        recommend_bundles(candidates, cart_items, &individual_prices)
+    }
+
     /// Evaluate catalog rules for a given context
     pub fn evaluate_rules(&self, context: &RuleContext) -> Vec<&CatalogRule> {
         self.catalog_rules
@@ -0,0 +1,323 @@
+//! Catalog import with referential-integrity validation
+//!
+//! Imports are validated as a whole before anything is written to the engine,
+//! so a bad source never leaves the catalog half-populated.
+
+use crate::bundling::{validate_bundle, Bundle};
+use crate::eligibility::{
+    validate_eligibility_rule, EligibilityConditionOperator, EligibilityRule,
+};
+use crate::pricing::{validate_effective_window, PricingRule};
+use crate::rules::{find_rule_cycles, CatalogRule};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Product specification known to the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSpecification {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// Product offering known to the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogOffering {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_specification_id: Option<Uuid>,
+}
+
+/// Catalog content to be imported in one go
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogImport {
+    #[serde(default)]
+    pub product_specifications: Vec<CatalogSpecification>,
+    #[serde(default)]
+    pub product_offerings: Vec<CatalogOffering>,
+    #[serde(default)]
+    pub pricing_rules: Vec<PricingRule>,
+    #[serde(default)]
+    pub eligibility_rules: Vec<EligibilityRule>,
+    #[serde(default)]
+    pub bundles: Vec<Bundle>,
+    #[serde(default)]
+    pub catalog_rules: Vec<CatalogRule>,
+}
+
+/// Kind of entity referenced in an import issue
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportEntity {
+    ProductSpecification,
+    ProductOffering,
+    PricingRule,
+    EligibilityRule,
+    Bundle,
+    CatalogRule,
+}
+
+/// Problem found while validating an import
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportIssue {
+    /// The same id appears twice, in the import or against the existing catalog
+    DuplicateId { entity: ImportEntity, id: Uuid },
+    /// An entity references another entity that does not exist
+    DanglingReference {
+        entity: ImportEntity,
+        id: Uuid,
+        target: ImportEntity,
+        target_id: Uuid,
+    },
+    /// An entity failed its own validation
+    Invalid {
+        entity: ImportEntity,
+        id: Uuid,
+        reason: String,
+    },
+    /// Entities depend on each other in a cycle, listed along the
+    /// dependencies and ending with the id the cycle started from
+    DependencyCycle {
+        entity: ImportEntity,
+        ids: Vec<Uuid>,
+    },
+    /// An offering has no pricing rule (warning only)
+    MissingPrice { offering_id: Uuid },
+}
+
+/// Outcome of validating an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub errors: Vec<ImportIssue>,
+    pub warnings: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    /// Whether the import can be committed
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Catalog content already loaded, used to resolve references and detect duplicates
+pub(crate) struct ExistingCatalog<'a> {
+    pub specifications: &'a [CatalogSpecification],
+    pub offerings: &'a [CatalogOffering],
+    pub pricing_rules: &'a [PricingRule],
+    pub eligibility_rules: &'a [EligibilityRule],
+    pub bundles: &'a [Bundle],
+    pub catalog_rules: &'a [CatalogRule],
+}
+
+/// Validate an import against the existing catalog without mutating anything
+pub(crate) fn validate_import(import: &CatalogImport, existing: &ExistingCatalog) -> ImportReport {
+    let mut report = ImportReport::default();
+
+    let spec_ids = collect_ids(
+        ImportEntity::ProductSpecification,
+        existing.specifications.iter().map(|s| s.id),
+        import.product_specifications.iter().map(|s| s.id),
+        &mut report,
+    );
+    let offering_ids = collect_ids(
+        ImportEntity::ProductOffering,
+        existing.offerings.iter().map(|o| o.id),
+        import.product_offerings.iter().map(|o| o.id),
+        &mut report,
+    );
+    collect_ids(
+        ImportEntity::PricingRule,
+        existing.pricing_rules.iter().map(|r| r.id),
+        import.pricing_rules.iter().map(|r| r.id),
+        &mut report,
+    );
+    collect_ids(
+        ImportEntity::EligibilityRule,
+        existing.eligibility_rules.iter().map(|r| r.id),
+        import.eligibility_rules.iter().map(|r| r.id),
+        &mut report,
+    );
+    collect_ids(
+        ImportEntity::Bundle,
+        existing.bundles.iter().map(|b| b.id),
+        import.bundles.iter().map(|b| b.id),
+        &mut report,
+    );
+    let catalog_rule_ids = collect_ids(
+        ImportEntity::CatalogRule,
+        existing.catalog_rules.iter().map(|r| r.id),
+        import.catalog_rules.iter().map(|r| r.id),
+        &mut report,
+    );
+
+    for offering in &import.product_offerings {
+        if let Some(spec_id) = offering.product_specification_id {
+            check_reference(
+                &mut report,
+                ImportEntity::ProductOffering,
+                offering.id,
+                ImportEntity::ProductSpecification,
+                spec_id,
+                &spec_ids,
+            );
+        }
+    }
+
+    for rule in &import.pricing_rules {
+        check_reference(
+            &mut report,
+            ImportEntity::PricingRule,
+            rule.id,
+            ImportEntity::ProductOffering,
+            rule.product_offering_id,
+            &offering_ids,
+        );
+    }
+
+    for rule in &import.eligibility_rules {
+        check_reference(
+            &mut report,
+            ImportEntity::EligibilityRule,
+            rule.id,
+            ImportEntity::ProductOffering,
+            rule.product_offering_id,
+            &offering_ids,
+        );
+        for condition in &rule.conditions {
+            if matches!(
+                condition.operator,
+                EligibilityConditionOperator::RequiresProducts
+                    | EligibilityConditionOperator::ForbidsProducts
+            ) {
+                for target_id in condition
+                    .value
+                    .split(',')
+                    .filter_map(|v| Uuid::parse_str(v.trim()).ok())
+                {
+                    check_reference(
+                        &mut report,
+                        ImportEntity::EligibilityRule,
+                        rule.id,
+                        ImportEntity::ProductOffering,
+                        target_id,
+                        &offering_ids,
+                    );
+                }
+            }
+        }
+        if let Err(reason) = validate_eligibility_rule(rule) {
+            report.errors.push(ImportIssue::Invalid {
+                entity: ImportEntity::EligibilityRule,
+                id: rule.id,
+                reason,
+            });
+        }
+    }
+
+    for bundle in &import.bundles {
+        for product in &bundle.products {
+            check_reference(
+                &mut report,
+                ImportEntity::Bundle,
+                bundle.id,
+                ImportEntity::ProductOffering,
+                product.product_offering_id,
+                &offering_ids,
+            );
+        }
+        if let Err(reason) = validate_bundle(bundle) {
+            report.errors.push(ImportIssue::Invalid {
+                entity: ImportEntity::Bundle,
+                id: bundle.id,
+                reason,
+            });
+        }
+    }
+
+    for rule in &import.catalog_rules {
+        for dependency in rule.dependencies() {
+            check_reference(
+                &mut report,
+                ImportEntity::CatalogRule,
+                rule.id,
+                ImportEntity::CatalogRule,
+                dependency,
+                &catalog_rule_ids,
+            );
+        }
+    }
+    let staged_catalog_rules: Vec<CatalogRule> = existing
+        .catalog_rules
+        .iter()
+        .chain(&import.catalog_rules)
+        .cloned()
+        .collect();
+    for ids in find_rule_cycles(&staged_catalog_rules) {
+        report.errors.push(ImportIssue::DependencyCycle {
+            entity: ImportEntity::CatalogRule,
+            ids,
+        });
+    }
+
+    let mut staged_rules: Vec<PricingRule> = existing.pricing_rules.to_vec();
+    for rule in &import.pricing_rules {
+        match validate_effective_window(&staged_rules, rule) {
+            Ok(()) => staged_rules.push(rule.clone()),
+            Err(reason) => report.errors.push(ImportIssue::Invalid {
+                entity: ImportEntity::PricingRule,
+                id: rule.id,
+                reason,
+            }),
+        }
+    }
+
+    for offering in &import.product_offerings {
+        if !staged_rules
+            .iter()
+            .any(|rule| rule.product_offering_id == offering.id)
+        {
+            report.warnings.push(ImportIssue::MissingPrice {
+                offering_id: offering.id,
+            });
+        }
+    }
+
+    report
+}
+
+/// Record a dangling reference when `target_id` is not among the known ids
+fn check_reference(
+    report: &mut ImportReport,
+    entity: ImportEntity,
+    id: Uuid,
+    target: ImportEntity,
+    target_id: Uuid,
+    known_ids: &HashSet<Uuid>,
+) {
+    if !known_ids.contains(&target_id) {
+        report.errors.push(ImportIssue::DanglingReference {
+            entity,
+            id,
+            target,
+            target_id,
+        });
+    }
+}
+
+/// Merge existing and imported ids, recording duplicates as errors
+fn collect_ids(
+    entity: ImportEntity,
+    existing: impl Iterator<Item = Uuid>,
+    imported: impl Iterator<Item = Uuid>,
+    report: &mut ImportReport,
+) -> HashSet<Uuid> {
+    let mut ids: HashSet<Uuid> = existing.collect();
+    for id in imported {
+        if !ids.insert(id) {
+            report.errors.push(ImportIssue::DuplicateId { entity, id });
+        }
+    }
+    ids
+}
@@ -12,33 +12,51 @@
 //! Built with Rust's safety guarantees to prevent costly billing errors.
 
 pub mod bundling;
+pub mod change_feed;
 pub mod complex_pricing;
+pub mod currency;
 pub mod eligibility;
 pub mod engine;
+pub mod import;
+pub mod price_trace;
 pub mod pricing;
 pub mod rules;
 pub mod versioning;
 
 pub use bundling::*;
+pub use change_feed::{
+    OfferingChangeEvent, OfferingChangeFeed, OfferingChangeSubscriber, OfferingChangeType,
+};
+pub use currency::{Currency, ExchangeRateTable};
 pub use eligibility::*;
 pub use engine::CatalogEngine;
+pub use import::{
+    CatalogImport, CatalogOffering, CatalogSpecification, ImportEntity, ImportIssue, ImportReport,
+};
+pub use price_trace::{PriceStepKind, PriceTrace, PriceTraceStep, SuppressedDiscount};
 // Re-export pricing types except TimePeriod to avoid conflict
 pub use pricing::{
-    calculate_final_price, DiscountCondition, DiscountRule, DiscountType, Money, PriceType,
-    PricingConditionOperator, PricingContext, PricingRule,
+    calculate_final_price, calculate_final_price_with_trace, select_effective_rule,
+    validate_effective_window, DiscountCondition, DiscountRule, DiscountType, GuardedPrice,
+    Guardrail, Money, PriceType, PricingConditionOperator, PricingContext, PricingRule,
 };
 pub use rules::{
-    evaluate_rule, ActionType, CatalogRule, LogicalOperator, RuleAction, RuleCondition,
-    RuleContext, RuleOperator, RuleResult, RuleType, TimePeriod as RuleTimePeriod,
+    evaluate_rule, find_rule_cycles, validate_rule_graph, ActionType, CatalogRule, LogicalOperator,
+    RuleAction, RuleCondition, RuleContext, RuleCycleError, RuleOperator, RuleResult, RuleType,
+    TimePeriod as RuleTimePeriod,
 };
 
 // Re-export complex pricing types with specific names to avoid conflicts
 pub use complex_pricing::{
-    calculate_complex_price, AdjustmentType, BillingCycle, BundlePricing, CancellationPolicy,
-    ComplexPricingModel, ComponentPrice, DynamicPricing, FactorType, PriceAdjustmentRule,
-    PricingContext as ComplexPricingContext, PricingFactor, PricingTier, SubscriptionPricing,
-    TieredPricing, VolumeDiscount, VolumePricing,
+    calculate_complex_price, calculate_complex_price_with_trace, prorate_change, AdjustmentType,
+    BillingCycle, BundleDiscount, BundlePricing, CancellationPolicy, ComplexPricingModel,
+    ComponentPrice, DayCountBasis, DynamicPricing, FactorType, PriceAdjustmentRule,
+    PricingContext as ComplexPricingContext, PricingFactor, PricingTier, Proration, StackingPolicy,
+    SubscriptionPricing, TierMode, TieredPricing, VolumeDiscount, VolumePricing,
 };
 
 // Re-export versioning types
-pub use versioning::{CatalogVersion, VersionDiff, VersionManager};
+pub use versioning::{
+    CatalogMigration, CatalogVersion, DiffSummary, DiffSummaryOptions, HighRiskChange,
+    RollbackError, VersionDiff, VersionManager, INITIAL_SCHEMA_VERSION,
+};
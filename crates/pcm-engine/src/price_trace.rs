@@ -0,0 +1,172 @@
+//! Price calculation traces
+//!
+//! A trace records how a price was derived: the starting price, every
+//! discount, tier, factor or component applied with the amount it changed the
+//! price by, and the final adjustment to a non-negative price. The final price
+//! always equals the base price plus the sum of the step amounts, so a disputed
+//! charge can be reconstructed exactly.
+//!
+//! Discounts that were considered but not applied, such as bundle discounts
+//! suppressed by the bundle's stacking policy, are listed with the reason.
+//!
+//! Tracing is opt-in through the `*_with_trace` pricing functions; the plain
+//! functions do not build a trace.
+
+use crate::pricing::Money;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of step applied during a price calculation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PriceStepKind {
+    /// Discount rule of a pricing rule
+    Discount,
+    /// Price of the selected quantity tier
+    Tier,
+    /// Volume discount
+    VolumeDiscount,
+    /// Dynamic pricing factor
+    Factor,
+    /// Price of a bundle component
+    BundleComponent,
+    /// Discount on a whole bundle
+    BundleDiscount,
+    /// Negative price raised to zero
+    MinimumPrice,
+    /// Price converted to the currency priced in
+    CurrencyConversion,
+    /// Price clamped into the price floor and ceiling
+    Guardrail,
+}
+
+/// One step of a price calculation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTraceStep {
+    pub kind: PriceStepKind,
+    /// What was applied, e.g. the discount name
+    pub description: String,
+    pub price_before: f64,
+    /// Change to the price, negative for reductions
+    pub amount: f64,
+    pub price_after: f64,
+}
+
+/// Discount considered during a price calculation but not applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedDiscount {
+    pub description: String,
+    pub reason: String,
+}
+
+/// How a price was derived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTrace {
+    /// Pricing rule the price was taken from, when one was selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing_rule_id: Option<Uuid>,
+    pub base_price: Money,
+    pub steps: Vec<PriceTraceStep>,
+    /// Discounts not applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed: Vec<SuppressedDiscount>,
+    pub final_price: Money,
+}
+
+impl PriceTrace {
+    pub(crate) fn new() -> Self {
+        let zero = Money {
+            value: 0.0,
+            unit: String::new(),
+        };
+        Self {
+            pricing_rule_id: None,
+            base_price: zero.clone(),
+            steps: Vec::new(),
+            suppressed: Vec::new(),
+            final_price: zero,
+        }
+    }
+}
+
+/// Records steps into a trace when tracing is enabled, and does nothing
+/// otherwise
+pub(crate) struct Tracer<'a>(Option<&'a mut PriceTrace>);
+
+impl<'a> Tracer<'a> {
+    pub(crate) fn off() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn on(trace: &'a mut PriceTrace) -> Self {
+        Self(Some(trace))
+    }
+
+    pub(crate) fn rule(&mut self, pricing_rule_id: Uuid) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.pricing_rule_id = Some(pricing_rule_id);
+        }
+    }
+
+    pub(crate) fn base(&mut self, base_price: &Money) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.base_price = base_price.clone();
+        }
+    }
+
+    /// Record a step; `description` is only evaluated when tracing
+    pub(crate) fn step(
+        &mut self,
+        kind: PriceStepKind,
+        description: impl FnOnce() -> String,
+        price_before: f64,
+        price_after: f64,
+    ) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.steps.push(PriceTraceStep {
+                kind,
+                description: description(),
+                price_before,
+                amount: price_after - price_before,
+                price_after,
+            });
+        }
+    }
+
+    /// Record a discount that was not applied; the texts are only evaluated
+    /// when tracing
+    pub(crate) fn suppress(
+        &mut self,
+        description: impl FnOnce() -> String,
+        reason: impl FnOnce() -> String,
+    ) {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.suppressed.push(SuppressedDiscount {
+                description: description(),
+                reason: reason(),
+            });
+        }
+    }
+
+    /// Raise a negative price to zero, recording the adjustment
+    pub(crate) fn floor_at_zero(&mut self, value: f64) -> f64 {
+        let floored = value.max(0.0);
+        if floored != value {
+            self.step(
+                PriceStepKind::MinimumPrice,
+                || "Negative price raised to zero".to_string(),
+                value,
+                floored,
+            );
+        }
+        floored
+    }
+
+    /// Record the final price and return it
+    pub(crate) fn finish(&mut self, price: Money) -> Money {
+        if let Some(trace) = self.0.as_deref_mut() {
+            trace.final_price = price.clone();
+        }
+        price
+    }
+}
@@ -1,7 +1,10 @@
 //! Pricing rules and calculations
 
+use crate::currency::{Currency, ExchangeRateTable};
+use crate::price_trace::{PriceStepKind, PriceTrace, Tracer};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use uuid::Uuid;
 
 /// Pricing rule for a product offering
@@ -13,10 +16,70 @@ pub struct PricingRule {
     pub base_price: Money,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub discount_rules: Option<Vec<DiscountRule>>,
+    /// Period in which this price applies, end exclusive (always when absent)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub valid_for: Option<TimePeriod>,
 }
 
+impl PricingRule {
+    /// First instant at which this price applies (unbounded when absent)
+    pub fn effective_from(&self) -> Option<DateTime<Utc>> {
+        self.valid_for.as_ref().map(|period| period.start_date_time)
+    }
+
+    /// Instant at which this price stops applying, exclusive (unbounded when absent)
+    pub fn effective_to(&self) -> Option<DateTime<Utc>> {
+        self.valid_for
+            .as_ref()
+            .and_then(|period| period.end_date_time)
+    }
+
+    /// Check whether the rule is in effect at the given instant
+    pub fn is_effective_at(&self, at: DateTime<Utc>) -> bool {
+        self.effective_from().is_none_or(|from| at >= from)
+            && self.effective_to().is_none_or(|to| at < to)
+    }
+
+    /// Check whether the effective window of this rule overlaps another rule's window
+    pub fn overlaps(&self, other: &PricingRule) -> bool {
+        let starts_before_other_ends = match (self.effective_from(), other.effective_to()) {
+            (Some(from), Some(to)) => from < to,
+            _ => true,
+        };
+        let ends_after_other_starts = match (self.effective_to(), other.effective_from()) {
+            (Some(to), Some(from)) => to > from,
+            _ => true,
+        };
+        starts_before_other_ends && ends_after_other_starts
+    }
+
+    /// Order rules by how specific their effective windows are, most specific
+    /// first
+    ///
+    /// A window with fewer unbounded ends is more specific, then a shorter
+    /// bounded window. Remaining ties go to the later start, then the lower
+    /// rule id, so the order is total.
+    pub fn cmp_specificity(&self, other: &PricingRule) -> Ordering {
+        let unbounded_ends = |rule: &PricingRule| {
+            rule.effective_from().is_none() as u8 + rule.effective_to().is_none() as u8
+        };
+        let length = |rule: &PricingRule| match (rule.effective_from(), rule.effective_to()) {
+            (Some(from), Some(to)) => Some(to - from),
+            _ => None,
+        };
+        unbounded_ends(self)
+            .cmp(&unbounded_ends(other))
+            .then_with(|| length(self).cmp(&length(other)))
+            .then_with(|| other.effective_from().cmp(&self.effective_from()))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+
+    /// Rules sharing a key compete for the same price slot of an offering
+    fn same_key(&self, other: &PricingRule) -> bool {
+        self.product_offering_id == other.product_offering_id && self.price_type == other.price_type
+    }
+}
+
 /// Price type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -31,9 +94,42 @@ pub enum PriceType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Money {
     pub value: f64,
+    /// ISO 4217 currency code
     pub unit: String,
 }
 
+impl Money {
+    /// Amount given in minor units of a currency
+    pub fn from_minor_units(amount: i64, currency: Currency) -> Self {
+        Self {
+            value: amount as f64 / currency.minor_unit_factor() as f64,
+            unit: currency.code().to_string(),
+        }
+    }
+
+    /// Currency of the amount
+    pub fn currency(&self) -> Result<Currency, String> {
+        self.unit.parse()
+    }
+
+    /// Amount in minor units, rounded to the precision of its currency
+    pub fn minor_units(&self) -> Result<i64, String> {
+        let currency = self.currency()?;
+        Ok((self.value * currency.minor_unit_factor() as f64).round() as i64)
+    }
+
+    /// Convert to another currency, rounding to its precision
+    pub fn convert_to(&self, target: Currency, rates: &ExchangeRateTable) -> Result<Money, String> {
+        let source = self.currency()?;
+        let rate = rates
+            .rate(source, target)
+            .ok_or_else(|| format!("No exchange rate from {} to {}", source, target))?;
+        let scale = 10_f64.powi(target.minor_units() as i32 - source.minor_units() as i32);
+        let amount = (self.minor_units()? as f64 * rate * scale).round() as i64;
+        Ok(Money::from_minor_units(amount, target))
+    }
+}
+
 /// Discount rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscountRule {
@@ -78,22 +174,257 @@ pub struct TimePeriod {
     pub end_date_time: Option<DateTime<Utc>>,
 }
 
+/// Validate that a rule's effective window is not empty and differs from the
+/// window of every existing rule with the same key
+///
+/// Overlapping windows are allowed: where they overlap, the most specific rule
+/// wins (see [`select_effective_rule`]), so a promotion can be scheduled
+/// inside a standing price. Only a window identical to one of an existing rule
+/// for the same product offering and price type is rejected, as neither rule
+/// would be more specific and the price would fall to the rule id tiebreak.
+pub fn validate_effective_window(
+    existing: &[PricingRule],
+    rule: &PricingRule,
+) -> Result<(), String> {
+    if let (Some(from), Some(to)) = (rule.effective_from(), rule.effective_to()) {
+        if from >= to {
+            return Err(format!(
+                "Pricing rule {} has an empty effective window ({} >= {})",
+                rule.id, from, to
+            ));
+        }
+    }
+
+    if let Some(conflict) = existing.iter().find(|other| {
+        other.same_key(rule)
+            && other.effective_from() == rule.effective_from()
+            && other.effective_to() == rule.effective_to()
+    }) {
+        return Err(format!(
+            "Pricing rule {} has the same effective window as rule {} for product offering {}",
+            rule.id, conflict.id, rule.product_offering_id
+        ));
+    }
+
+    Ok(())
+}
+
+/// Select the rule in effect at the given instant
+///
+/// Rules not yet effective or already expired are ignored rather than
+/// rejected. When several are effective, the one with the narrowest window
+/// wins, as ordered by [`PricingRule::cmp_specificity`]: a promotion scheduled
+/// inside a standing price overrides it, and of two open-ended prices the later
+/// one applies.
+pub fn select_effective_rule<'a, I>(rules: I, at: DateTime<Utc>) -> Option<&'a PricingRule>
+where
+    I: IntoIterator<Item = &'a PricingRule>,
+{
+    rules
+        .into_iter()
+        .filter(|rule| rule.is_effective_at(at))
+        .min_by(|a, b| a.cmp_specificity(b))
+}
+
 /// Calculate final price after applying discounts
-pub fn calculate_final_price(rule: &PricingRule, context: &PricingContext) -> Money {
-    let mut final_price = rule.base_price.value;
+///
+/// The rule in effect at `context.pricing_date` is selected from `rules` (see
+/// [`select_effective_rule`]); `None` is returned when no rule applies at that
+/// date. Amounts are computed in integer
+/// minor units of the rule's currency. A price in another currency than
+/// `context.currency` is converted with `context.exchange_rates`, and
+/// rejected if there are none.
+///
+/// The converted price is then clamped into `context.price_floor` and
+/// `context.price_ceiling` when they are set; the result keeps the price before
+/// clamping and the guardrail that engaged. Without guardrails the price is
+/// returned unchanged.
+pub fn calculate_final_price<'a, I>(
+    rules: I,
+    context: &PricingContext,
+) -> Result<Option<GuardedPrice>, String>
+where
+    I: IntoIterator<Item = &'a PricingRule>,
+{
+    final_price(rules, context, &mut Tracer::off())
+}
+
+/// Calculate final price like [`calculate_final_price`], also returning how it
+/// was derived
+pub fn calculate_final_price_with_trace<'a, I>(
+    rules: I,
+    context: &PricingContext,
+) -> Result<Option<(GuardedPrice, PriceTrace)>, String>
+where
+    I: IntoIterator<Item = &'a PricingRule>,
+{
+    let mut trace = PriceTrace::new();
+    let price = final_price(rules, context, &mut Tracer::on(&mut trace))?;
+    Ok(price.map(|price| (price, trace)))
+}
+
+fn final_price<'a, I>(
+    rules: I,
+    context: &PricingContext,
+    tracer: &mut Tracer,
+) -> Result<Option<GuardedPrice>, String>
+where
+    I: IntoIterator<Item = &'a PricingRule>,
+{
+    let Some(rule) = select_effective_rule(rules, context.pricing_date) else {
+        return Ok(None);
+    };
+    let currency = rule.base_price.currency()?;
+    let target = context.currency.unwrap_or(currency);
+    if target != currency && context.exchange_rates.is_none() {
+        return Err(format!(
+            "Pricing rule {} is priced in {}, not {}, and no exchange rates were given",
+            rule.id, currency, target
+        ));
+    }
+
+    let major = |minor: i64| Money::from_minor_units(minor, currency).value;
+    let mut final_price = rule.base_price.minor_units()?;
+    tracer.rule(rule.id);
+    tracer.base(&Money::from_minor_units(final_price, currency));
 
     if let Some(ref discounts) = rule.discount_rules {
         for discount in discounts {
             if is_discount_applicable(discount, context) {
-                final_price = apply_discount(final_price, discount);
+                let before = final_price;
+                final_price = apply_discount(final_price, discount, currency);
+                tracer.step(
+                    PriceStepKind::Discount,
+                    || discount.name.clone(),
+                    major(before),
+                    major(final_price),
+                );
             }
         }
     }
 
-    Money {
-        value: final_price.max(0.0),
-        unit: rule.base_price.unit.clone(),
+    tracer.floor_at_zero(major(final_price));
+    let mut price = Money::from_minor_units(final_price.max(0), currency);
+
+    if let Some(rates) = context
+        .exchange_rates
+        .as_ref()
+        .filter(|_| target != currency)
+    {
+        let converted = price.convert_to(target, rates)?;
+        tracer.step(
+            PriceStepKind::CurrencyConversion,
+            || format!("{} {} converted to {}", price.value, currency, target),
+            price.value,
+            converted.value,
+        );
+        price = converted;
+    }
+
+    let guarded = apply_guardrails(price, target, context, tracer)?;
+    tracer.finish(guarded.price.clone());
+    Ok(Some(guarded))
+}
+
+/// Guardrail that clamped a price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Guardrail {
+    /// Price raised to `PricingContext::price_floor`
+    Floor,
+    /// Price lowered to `PricingContext::price_ceiling`
+    Ceiling,
+}
+
+/// Final price after the price guardrails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardedPrice {
+    /// Price as calculated, before clamping
+    pub raw: Money,
+    /// Price to charge
+    pub price: Money,
+    /// Guardrail that clamped the price, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guardrail: Option<Guardrail>,
+}
+
+impl GuardedPrice {
+    /// Whether a guardrail changed the price
+    pub fn is_clamped(&self) -> bool {
+        self.guardrail.is_some()
+    }
+}
+
+/// Clamp a price in `target` currency into the context's guardrails
+fn apply_guardrails(
+    price: Money,
+    target: Currency,
+    context: &PricingContext,
+    tracer: &mut Tracer,
+) -> Result<GuardedPrice, String> {
+    let floor = context
+        .price_floor
+        .as_ref()
+        .map(|floor| guardrail_minor_units(floor, target, context))
+        .transpose()?;
+    let ceiling = context
+        .price_ceiling
+        .as_ref()
+        .map(|ceiling| guardrail_minor_units(ceiling, target, context))
+        .transpose()?;
+    if let (Some(floor), Some(ceiling)) = (floor, ceiling) {
+        if floor > ceiling {
+            return Err(format!(
+                "Price floor {} is above price ceiling {}",
+                Money::from_minor_units(floor, target).value,
+                Money::from_minor_units(ceiling, target).value
+            ));
+        }
+    }
+
+    let raw = price.minor_units()?;
+    let (clamped, guardrail) = match (floor, ceiling) {
+        (Some(floor), _) if raw < floor => (floor, Some(Guardrail::Floor)),
+        (_, Some(ceiling)) if raw > ceiling => (ceiling, Some(Guardrail::Ceiling)),
+        _ => (raw, None),
+    };
+    let guarded = Money::from_minor_units(clamped, target);
+    if let Some(guardrail) = guardrail {
+        tracer.step(
+            PriceStepKind::Guardrail,
+            || match guardrail {
+                Guardrail::Floor => "Price raised to the price floor".to_string(),
+                Guardrail::Ceiling => "Price lowered to the price ceiling".to_string(),
+            },
+            price.value,
+            guarded.value,
+        );
+    }
+
+    Ok(GuardedPrice {
+        raw: price,
+        price: guarded,
+        guardrail,
+    })
+}
+
+/// Guardrail amount in minor units of `target`, converted with the context's
+/// exchange rates when given in another currency
+fn guardrail_minor_units(
+    guardrail: &Money,
+    target: Currency,
+    context: &PricingContext,
+) -> Result<i64, String> {
+    if guardrail.currency()? == target {
+        return guardrail.minor_units();
     }
+    let rates = context.exchange_rates.as_ref().ok_or_else(|| {
+        format!(
+            "Price guardrail is in {}, not {}, and no exchange rates were given",
+            guardrail.unit, target
+        )
+    })?;
+    guardrail.convert_to(target, rates)?.minor_units()
 }
 
 /// Pricing context for discount evaluation
@@ -102,6 +433,16 @@ pub struct PricingContext {
     pub customer_segment: Option<String>,
     pub quantity: u32,
     pub existing_products: Vec<Uuid>,
+    /// Date at which the price is evaluated
+    pub pricing_date: DateTime<Utc>,
+    /// Currency to price in (the pricing rule's currency when unset)
+    pub currency: Option<Currency>,
+    /// Rates for pricing rules in other currencies than `currency`
+    pub exchange_rates: Option<ExchangeRateTable>,
+    /// Lowest price to charge; lower prices are raised to it
+    pub price_floor: Option<Money>,
+    /// Highest price to charge; higher prices are lowered to it
+    pub price_ceiling: Option<Money>,
 }
 
 fn is_discount_applicable(discount: &DiscountRule, context: &PricingContext) -> bool {
@@ -140,9 +481,138 @@ fn evaluate_condition(condition: &DiscountCondition, context: &PricingContext) -
     }
 }
 
-fn apply_discount(base_price: f64, discount: &DiscountRule) -> f64 {
+/// Apply a discount to a price in minor units; fixed amounts are in major
+/// units of the same currency
+fn apply_discount(base_price: i64, discount: &DiscountRule, currency: Currency) -> i64 {
     match discount.discount_type {
-        DiscountType::Percentage => base_price * (1.0 - discount.value / 100.0),
-        DiscountType::FixedAmount => (base_price - discount.value).max(0.0),
+        DiscountType::Percentage => {
+            (base_price as f64 * (1.0 - discount.value / 100.0)).round() as i64
+        }
+        DiscountType::FixedAmount => {
+            let amount = (discount.value * currency.minor_unit_factor() as f64).round() as i64;
+            (base_price - amount).max(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, d, 0, 0, 0).unwrap()
+    }
+
+    fn rule(from: u32, to: Option<u32>, value: f64) -> PricingRule {
+        PricingRule {
+            id: Uuid::new_v4(),
+            product_offering_id: Uuid::nil(),
+            price_type: PriceType::Recurring,
+            base_price: Money {
+                value,
+                unit: "USD".to_string(),
+            },
+            discount_rules: None,
+            valid_for: Some(TimePeriod {
+                start_date_time: day(from),
+                end_date_time: to.map(day),
+            }),
+        }
+    }
+
+    fn context(at: DateTime<Utc>) -> PricingContext {
+        PricingContext {
+            customer_segment: None,
+            quantity: 1,
+            existing_products: vec![],
+            pricing_date: at,
+            currency: None,
+            exchange_rates: None,
+            price_floor: None,
+            price_ceiling: None,
+        }
+    }
+
+    #[test]
+    fn narrower_window_beats_wider_one() {
+        let standing = rule(1, Some(31), 10.0);
+        let promotion = rule(10, Some(20), 8.0);
+        assert!(validate_effective_window(std::slice::from_ref(&standing), &promotion).is_ok());
+
+        let rules = [standing.clone(), promotion.clone()];
+        assert_eq!(
+            select_effective_rule(&rules, day(15)).unwrap().id,
+            promotion.id
+        );
+        assert_eq!(
+            select_effective_rule(&rules, day(5)).unwrap().id,
+            standing.id
+        );
+        assert_eq!(
+            select_effective_rule(&rules, day(20)).unwrap().id,
+            standing.id
+        );
+    }
+
+    #[test]
+    fn expired_rules_are_skipped() {
+        let expired = rule(1, Some(10), 10.0);
+        let future = rule(20, None, 12.0);
+        let rules = [expired, future];
+
+        let price = calculate_final_price(&rules, &context(day(15))).unwrap();
+        assert!(price.is_none());
+
+        let price = calculate_final_price(&rules, &context(day(25)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(price.price.value, 12.0);
+    }
+
+    #[test]
+    fn specificity_tiebreak_order() {
+        // Fewer unbounded ends first
+        let bounded = rule(1, Some(31), 1.0);
+        let open_ended = rule(10, None, 1.0);
+        assert_eq!(bounded.cmp_specificity(&open_ended), Ordering::Less);
+
+        // Then the shorter window
+        let short = rule(1, Some(5), 1.0);
+        assert_eq!(short.cmp_specificity(&bounded), Ordering::Less);
+
+        // Then the later start: of two open-ended prices the later one applies
+        let later = rule(15, None, 1.0);
+        assert_eq!(later.cmp_specificity(&open_ended), Ordering::Less);
+
+        // A rule without a validity period is the least specific
+        let always = PricingRule {
+            valid_for: None,
+            ..rule(1, None, 1.0)
+        };
+        assert_eq!(open_ended.cmp_specificity(&always), Ordering::Less);
+
+        // Finally the lower rule id
+        let mut a = rule(1, Some(5), 1.0);
+        let mut b = a.clone();
+        a.id = Uuid::from_u128(1);
+        b.id = Uuid::from_u128(2);
+        assert_eq!(a.cmp_specificity(&b), Ordering::Less);
+    }
+
+    #[test]
+    fn identical_windows_are_rejected() {
+        let existing = rule(1, Some(31), 10.0);
+        let duplicate = rule(1, Some(31), 9.0);
+        assert!(validate_effective_window(&[existing], &duplicate).is_err());
+
+        let empty = PricingRule {
+            valid_for: Some(TimePeriod {
+                start_date_time: day(1),
+                end_date_time: Some(day(1) - Duration::days(1)),
+            }),
+            ..rule(1, None, 1.0)
+        };
+        assert!(validate_effective_window(&[], &empty).is_err());
     }
 }
@@ -1,6 +1,8 @@
 //! Rule engine for catalog management
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
 use uuid::Uuid;
 
 /// Catalog rule
@@ -28,6 +30,173 @@ pub struct TimePeriod {
     pub end: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl CatalogRule {
+    /// Rules this rule depends on
+    pub fn dependencies(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.parent_rule_id.into_iter()
+    }
+}
+
+/// Catalog rules depending on each other in cycles
+///
+/// Each cycle lists rule ids along the dependencies, ending with the id it
+/// started from.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Catalog rules form dependency cycles: {}", describe_cycles(.cycles))]
+pub struct RuleCycleError {
+    pub cycles: Vec<Vec<Uuid>>,
+}
+
+fn describe_cycles(cycles: &[Vec<Uuid>]) -> String {
+    cycles
+        .iter()
+        .map(|cycle| {
+            cycle
+                .iter()
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Check that no catalog rules depend on each other in a cycle
+pub fn validate_rule_graph(rules: &[CatalogRule]) -> Result<(), RuleCycleError> {
+    let cycles = find_rule_cycles(rules);
+    if cycles.is_empty() {
+        Ok(())
+    } else {
+        Err(RuleCycleError { cycles })
+    }
+}
+
+/// Find the dependency cycles among catalog rules
+///
+/// Reports one cycle for every group of rules that depend on each other,
+/// ordered by the position of the group's first rule. Dependencies on rules
+/// not in `rules` are ignored. Runs in O(V+E) using Tarjan's strongly
+/// connected components.
+pub fn find_rule_cycles(rules: &[CatalogRule]) -> Vec<Vec<Uuid>> {
+    let index: HashMap<Uuid, usize> = rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| (rule.id, i))
+        .collect();
+    let edges: Vec<Vec<usize>> = rules
+        .iter()
+        .map(|rule| {
+            rule.dependencies()
+                .filter_map(|id| index.get(&id).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut components: Vec<Vec<usize>> = strongly_connected_components(&edges)
+        .into_iter()
+        .filter(|component| match component.as_slice() {
+            [single] => edges[*single].contains(single),
+            _ => true,
+        })
+        .collect();
+    for component in &mut components {
+        component.sort_unstable();
+    }
+    components.sort_unstable_by_key(|component| component[0]);
+
+    components
+        .iter()
+        .map(|component| {
+            cycle_path(component, &edges)
+                .into_iter()
+                .map(|i| rules[i].id)
+                .collect()
+        })
+        .collect()
+}
+
+/// Strongly connected components of a graph given as adjacency lists
+///
+/// Iterative Tarjan, so deep dependency chains cannot overflow the stack.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut discovered = vec![UNVISITED; edges.len()];
+    let mut low_link = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..edges.len() {
+        if discovered[root] != UNVISITED {
+            continue;
+        }
+        discovered[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        // Nodes being explored with the position of their next edge
+        let mut work = vec![(root, 0)];
+        while let Some((node, edge)) = work.pop() {
+            if let Some(&successor) = edges[node].get(edge) {
+                work.push((node, edge + 1));
+                if discovered[successor] == UNVISITED {
+                    discovered[successor] = next_index;
+                    low_link[successor] = next_index;
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    work.push((successor, 0));
+                } else if on_stack[successor] {
+                    low_link[node] = low_link[node].min(discovered[successor]);
+                }
+                continue;
+            }
+
+            if let Some(&(parent, _)) = work.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == discovered[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// Cycle within a strongly connected component, closed by repeating its
+/// first node
+///
+/// Every member has a dependency inside the component, so following them
+/// from the first member must come back to a node already on the path.
+fn cycle_path(component: &[usize], edges: &[Vec<usize>]) -> Vec<usize> {
+    let members: HashSet<usize> = component.iter().copied().collect();
+    let mut path = vec![component[0]];
+    let mut position: HashMap<usize, usize> = HashMap::from([(component[0], 0)]);
+    let mut node = component[0];
+    while let Some(next) = edges[node].iter().copied().find(|n| members.contains(n)) {
+        if let Some(&start) = position.get(&next) {
+            let mut cycle = path.split_off(start);
+            cycle.push(next);
+            return cycle;
+        }
+        position.insert(next, path.len());
+        path.push(next);
+        node = next;
+    }
+    path
+}
+
 /// Rule type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -1,11 +1,36 @@
 //! Catalog Versioning System
 //!
 //! Manages catalog versions, allowing for version control, rollback, and A/B testing
+//!
+//! A version captures the catalog content it was created with. Rolling back
+//! to an earlier version restores its content and refuses to drop offerings
+//! that are still active.
+//!
+//! Every version records the schema version it was stored with. When stored
+//! versions are loaded, registered migrations upgrade them one schema version
+//! at a time to the schema the manager works with, so catalog history survives
+//! changes to the catalog format.
 
+use crate::import::{CatalogImport, CatalogOffering};
+use crate::pricing::Money;
+use crate::rules::validate_rule_graph;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
 use uuid::Uuid;
 
+/// Schema version of catalog versions stored before schema versions were recorded
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+fn initial_schema_version() -> u32 {
+    INITIAL_SCHEMA_VERSION
+}
+
+/// Upgrades a stored catalog version from one schema version to the next
+pub type CatalogMigration = Box<dyn Fn(Value) -> Result<Value, String> + Send + Sync>;
+
 /// Catalog version
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatalogVersion {
@@ -19,11 +44,23 @@ pub struct CatalogVersion {
     pub is_published: bool,
     pub published_at: Option<DateTime<Utc>>,
     pub metadata: Option<serde_json::Value>,
+    /// Schema version the catalog version is stored in
+    #[serde(default = "initial_schema_version")]
+    pub schema_version: u32,
+    /// Catalog content captured by the version
+    #[serde(default)]
+    pub content: CatalogImport,
 }
 
 /// Version manager for catalogs
 pub struct VersionManager {
     versions: Vec<CatalogVersion>,
+    /// Schema version of new and loaded versions
+    schema_version: u32,
+    /// Migrations keyed by the schema version they upgrade from
+    migrations: HashMap<u32, CatalogMigration>,
+    /// Offerings in use, which a rollback must not remove
+    active_offerings: HashSet<Uuid>,
 }
 
 impl VersionManager {
@@ -31,7 +68,98 @@ impl VersionManager {
     pub fn new() -> Self {
         Self {
             versions: Vec::new(),
+            schema_version: INITIAL_SCHEMA_VERSION,
+            migrations: HashMap::new(),
+            active_offerings: HashSet::new(),
+        }
+    }
+
+    /// Use a newer current schema version
+    ///
+    /// Migrations must be registered for every schema version from which
+    /// stored versions are still loaded.
+    pub fn with_schema_version(mut self, schema_version: u32) -> Self {
+        self.schema_version = schema_version;
+        self
+    }
+
+    /// Current schema version
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Register the migration from `from_schema_version` to the next schema
+    /// version, replacing any previous one
+    pub fn register_migration<F>(&mut self, from_schema_version: u32, migration: F)
+    where
+        F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.migrations
+            .insert(from_schema_version, Box::new(migration));
+    }
+
+    /// Load a stored catalog version, migrating it to the current schema version
+    ///
+    /// Fails if the version is newer than the current schema version, if a
+    /// migration is missing on the way, or if a migration fails. A loaded
+    /// version replaces a version with the same id.
+    pub fn load_version(&mut self, stored: Value) -> Result<CatalogVersion, String> {
+        let label = stored
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or("<unknown>")
+            .to_string();
+        let mut schema_version = match stored.get("schema_version") {
+            None => INITIAL_SCHEMA_VERSION,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "Catalog version {} has an invalid schema version {}",
+                        label, v
+                    )
+                })?,
+        };
+        if schema_version > self.schema_version {
+            return Err(format!(
+                "Catalog version {} has schema version {}, newer than the supported {}",
+                label, schema_version, self.schema_version
+            ));
+        }
+
+        let mut record = stored;
+        while schema_version < self.schema_version {
+            let migration = self.migrations.get(&schema_version).ok_or_else(|| {
+                format!(
+                    "Catalog version {} cannot be migrated: no migration from schema version {} to {}",
+                    label,
+                    schema_version,
+                    schema_version + 1
+                )
+            })?;
+            record = migration(record).map_err(|e| {
+                format!(
+                    "Catalog version {} failed to migrate from schema version {}: {}",
+                    label, schema_version, e
+                )
+            })?;
+            schema_version += 1;
+
+            let Value::Object(ref mut fields) = record else {
+                return Err(format!(
+                    "Catalog version {} is not an object after migrating to schema version {}",
+                    label, schema_version
+                ));
+            };
+            fields.insert("schema_version".to_string(), schema_version.into());
         }
+
+        let version: CatalogVersion = serde_json::from_value(record)
+            .map_err(|e| format!("Catalog version {} could not be loaded: {}", label, e))?;
+        self.versions.retain(|v| v.id != version.id);
+        self.versions.push(version.clone());
+        Ok(version)
     }
 
     /// Create a new version
@@ -53,21 +181,49 @@ impl VersionManager {
             is_published: false,
             published_at: None,
             metadata: None,
+            schema_version: self.schema_version,
+            content: CatalogImport::default(),
         };
         self.versions.push(catalog_version.clone());
         catalog_version
     }
 
+    /// Set the catalog content captured by a version
+    ///
+    /// Rejects content whose catalog rules depend on each other in a cycle.
+    pub fn set_version_content(
+        &mut self,
+        version_id: Uuid,
+        content: CatalogImport,
+    ) -> Result<(), String> {
+        validate_rule_graph(&content.catalog_rules).map_err(|e| e.to_string())?;
+        let version = self
+            .versions
+            .iter_mut()
+            .find(|v| v.id == version_id)
+            .ok_or_else(|| "Version not found".to_string())?;
+        version.content = content;
+        Ok(())
+    }
+
+    /// Set the offerings in use (sold or subscribed), replacing the previous set
+    pub fn set_active_offerings(&mut self, offering_ids: impl IntoIterator<Item = Uuid>) {
+        self.active_offerings = offering_ids.into_iter().collect();
+    }
+
     /// Publish a version
+    ///
+    /// Versions whose catalog rules depend on each other in a cycle, such as
+    /// loaded ones stored before cycles were checked, cannot be published.
     pub fn publish_version(&mut self, version_id: Uuid) -> Result<(), String> {
         let catalog_id = {
             let version = self
                 .versions
                 .iter()
                 .find(|v| v.id == version_id)
-                .ok_or_else(|| "Version not found".to_string())?
-                .catalog_id;
-            version
+                .ok_or_else(|| "Version not found".to_string())?;
+            validate_rule_graph(&version.content.catalog_rules).map_err(|e| e.to_string())?;
+            version.catalog_id
         };
 
         // Deactivate all other versions of the same catalog
@@ -92,6 +248,71 @@ impl VersionManager {
         self.publish_version(version_id)
     }
 
+    /// Restore the catalog to an earlier version, returning the diff applied
+    ///
+    /// The diff goes from the active version of the catalog to `version`.
+    /// Fails without changing anything if an active offering would be
+    /// orphaned: removed from the catalog, or left referencing a product
+    /// specification the version does not contain. With `dry_run` the diff
+    /// is computed and checked but the active version is left as it is.
+    pub fn rollback_to(
+        &mut self,
+        version: CatalogVersion,
+        dry_run: bool,
+    ) -> Result<VersionDiff, RollbackError> {
+        let target = self
+            .versions
+            .iter()
+            .find(|v| v.id == version.id)
+            .ok_or(RollbackError::VersionNotFound(version.id))?;
+        let current = self
+            .get_active_version(target.catalog_id)
+            .ok_or(RollbackError::NoActiveVersion(target.catalog_id))?;
+
+        let orphaned = self.orphaned_offerings(current, target);
+        if !orphaned.is_empty() {
+            return Err(RollbackError::OrphanedOfferings(orphaned));
+        }
+
+        let diff = VersionDiff::between(current, target);
+        if !dry_run {
+            self.publish_version(target.id)
+                .map_err(|_| RollbackError::VersionNotFound(version.id))?;
+        }
+        Ok(diff)
+    }
+
+    /// Active offerings of `current` that `target` would orphan
+    fn orphaned_offerings(&self, current: &CatalogVersion, target: &CatalogVersion) -> Vec<Uuid> {
+        let target_specs: HashSet<Uuid> = target
+            .content
+            .product_specifications
+            .iter()
+            .map(|s| s.id)
+            .collect();
+
+        current
+            .content
+            .product_offerings
+            .iter()
+            .filter(|o| self.active_offerings.contains(&o.id))
+            .filter(|o| {
+                match target
+                    .content
+                    .product_offerings
+                    .iter()
+                    .find(|t| t.id == o.id)
+                {
+                    None => true,
+                    Some(restored) => restored
+                        .product_specification_id
+                        .is_some_and(|spec_id| !target_specs.contains(&spec_id)),
+                }
+            })
+            .map(|o| o.id)
+            .collect()
+    }
+
     /// Get active version for a catalog
     pub fn get_active_version(&self, catalog_id: Uuid) -> Option<&CatalogVersion> {
         self.versions
@@ -124,11 +345,7 @@ impl VersionManager {
             .find(|v| v.id == version_id_2)
             .ok_or_else(|| "Version 2 not found".to_string())?;
 
-        Ok(VersionDiff {
-            version_1: v1.clone(),
-            version_2: v2.clone(),
-            differences: vec![], // Would contain actual diff in real implementation
-        })
+        Ok(VersionDiff::between(v1, v2))
     }
 }
 
@@ -144,4 +361,261 @@ pub struct VersionDiff {
     pub version_1: CatalogVersion,
     pub version_2: CatalogVersion,
     pub differences: Vec<String>,
+    /// Offerings in version 2 only
+    #[serde(default)]
+    pub added_offering_ids: Vec<Uuid>,
+    /// Offerings in version 1 only
+    #[serde(default)]
+    pub removed_offering_ids: Vec<Uuid>,
+    /// Offerings whose name or product specification differ
+    #[serde(default)]
+    pub changed_offering_ids: Vec<Uuid>,
+}
+
+impl VersionDiff {
+    /// Diff of the offerings going from `from` to `to`
+    fn between(from: &CatalogVersion, to: &CatalogVersion) -> Self {
+        let find = |offerings: &[CatalogOffering], id: Uuid| {
+            offerings.iter().find(|o| o.id == id).cloned()
+        };
+        let from_offerings = &from.content.product_offerings;
+        let to_offerings = &to.content.product_offerings;
+
+        let mut diff = VersionDiff {
+            version_1: from.clone(),
+            version_2: to.clone(),
+            differences: Vec::new(),
+            added_offering_ids: Vec::new(),
+            removed_offering_ids: Vec::new(),
+            changed_offering_ids: Vec::new(),
+        };
+
+        for old in from_offerings {
+            match find(to_offerings, old.id) {
+                None => {
+                    diff.differences
+                        .push(format!("Offering '{}' ({}) removed", old.name, old.id));
+                    diff.removed_offering_ids.push(old.id);
+                }
+                Some(new)
+                    if new.name != old.name
+                        || new.product_specification_id != old.product_specification_id =>
+                {
+                    diff.differences
+                        .push(format!("Offering '{}' ({}) changed", new.name, new.id));
+                    diff.changed_offering_ids.push(new.id);
+                }
+                Some(_) => {}
+            }
+        }
+        for new in to_offerings {
+            if find(from_offerings, new.id).is_none() {
+                diff.differences
+                    .push(format!("Offering '{}' ({}) added", new.name, new.id));
+                diff.added_offering_ids.push(new.id);
+            }
+        }
+
+        diff
+    }
+
+    /// Summarize the diff by kind of change, with the changes to review first
+    ///
+    /// Pricing and eligibility rules are compared by id, and only counted for
+    /// offerings in both versions; rules of new or removed offerings are part
+    /// of those changes.
+    pub fn summarize(&self, options: &DiffSummaryOptions) -> DiffSummary {
+        let from = &self.version_1.content;
+        let to = &self.version_2.content;
+        let specification_of = |offering_id: Uuid| {
+            to.product_offerings
+                .iter()
+                .chain(&from.product_offerings)
+                .find(|o| o.id == offering_id)
+                .and_then(|o| o.product_specification_id)
+        };
+        let in_scope = |offering_id: &Uuid| {
+            options
+                .product_specification_id
+                .is_none_or(|spec| specification_of(*offering_id) == Some(spec))
+        };
+        let added_or_removed: HashSet<Uuid> = self
+            .added_offering_ids
+            .iter()
+            .chain(&self.removed_offering_ids)
+            .copied()
+            .collect();
+        let kept_in_scope =
+            |offering_id: &Uuid| in_scope(offering_id) && !added_or_removed.contains(offering_id);
+
+        let price_changes: Vec<_> = changed_items(&from.pricing_rules, &to.pricing_rules, |r| r.id)
+            .into_iter()
+            .filter(|(old, new)| {
+                new.or(*old)
+                    .is_some_and(|r| kept_in_scope(&r.product_offering_id))
+            })
+            .collect();
+        let eligibility_changes =
+            changed_items(&from.eligibility_rules, &to.eligibility_rules, |r| r.id)
+                .into_iter()
+                .filter(|(old, new)| {
+                    new.or(*old)
+                        .is_some_and(|r| kept_in_scope(&r.product_offering_id))
+                })
+                .count();
+
+        let mut increases: Vec<_> = price_changes
+            .iter()
+            .filter_map(|(old, new)| {
+                let (old, new) = (old.as_ref()?, new.as_ref()?);
+                let increase_percent = price_increase_percent(&old.base_price, &new.base_price)?;
+                (increase_percent > options.price_increase_threshold_percent).then_some((
+                    increase_percent,
+                    old,
+                    new,
+                ))
+            })
+            .collect();
+        increases.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut high_risk_changes: Vec<HighRiskChange> = increases
+            .into_iter()
+            .map(
+                |(increase_percent, old, new)| HighRiskChange::PriceIncrease {
+                    pricing_rule_id: new.id,
+                    product_offering_id: new.product_offering_id,
+                    old_price: old.base_price.clone(),
+                    new_price: new.base_price.clone(),
+                    increase_percent,
+                },
+            )
+            .collect();
+        high_risk_changes.extend(
+            from.product_offerings
+                .iter()
+                .filter(|o| self.removed_offering_ids.contains(&o.id) && in_scope(&o.id))
+                .map(|o| HighRiskChange::RemovedOffering {
+                    product_offering_id: o.id,
+                    name: o.name.clone(),
+                }),
+        );
+
+        DiffSummary {
+            new_offerings: self
+                .added_offering_ids
+                .iter()
+                .filter(|id| in_scope(id))
+                .count(),
+            removed_offerings: self
+                .removed_offering_ids
+                .iter()
+                .filter(|id| in_scope(id))
+                .count(),
+            changed_offerings: self
+                .changed_offering_ids
+                .iter()
+                .filter(|id| in_scope(id))
+                .count(),
+            price_changes: price_changes.len(),
+            eligibility_changes,
+            high_risk_changes,
+        }
+    }
+}
+
+/// Items added, removed or changed between two lists, matched by id
+fn changed_items<'a, T: Serialize>(
+    old: &'a [T],
+    new: &'a [T],
+    id: impl Fn(&T) -> Uuid,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let find = |items: &'a [T], item_id: Uuid| items.iter().find(|item| id(item) == item_id);
+    let mut changes = Vec::new();
+    for old_item in old {
+        match find(new, id(old_item)) {
+            None => changes.push((Some(old_item), None)),
+            Some(new_item)
+                if serde_json::to_value(old_item).ok() != serde_json::to_value(new_item).ok() =>
+            {
+                changes.push((Some(old_item), Some(new_item)))
+            }
+            Some(_) => {}
+        }
+    }
+    for new_item in new {
+        if find(old, id(new_item)).is_none() {
+            changes.push((None, Some(new_item)));
+        }
+    }
+    changes
+}
+
+/// Increase from `old` to `new` in percent, if the price rose within one
+/// currency
+fn price_increase_percent(old: &Money, new: &Money) -> Option<f64> {
+    (old.unit == new.unit && old.value > 0.0 && new.value > old.value)
+        .then(|| (new.value - old.value) / old.value * 100.0)
+}
+
+/// Options of a version diff summary
+#[derive(Debug, Clone)]
+pub struct DiffSummaryOptions {
+    /// Price increases above this percentage are high risk
+    pub price_increase_threshold_percent: f64,
+    /// Summarize only offerings of this product specification
+    pub product_specification_id: Option<Uuid>,
+}
+
+impl Default for DiffSummaryOptions {
+    fn default() -> Self {
+        Self {
+            price_increase_threshold_percent: 10.0,
+            product_specification_id: None,
+        }
+    }
+}
+
+/// Version diff summarized by kind of change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSummary {
+    pub new_offerings: usize,
+    pub removed_offerings: usize,
+    /// Offerings whose name or product specification changed
+    pub changed_offerings: usize,
+    /// Pricing rules added, removed or changed
+    pub price_changes: usize,
+    /// Eligibility rules added, removed or changed
+    pub eligibility_changes: usize,
+    /// Price increases above the threshold, largest first, then removed
+    /// offerings
+    pub high_risk_changes: Vec<HighRiskChange>,
+}
+
+/// Change an approver should review before publishing a version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HighRiskChange {
+    /// Base price raised by more than the threshold
+    PriceIncrease {
+        pricing_rule_id: Uuid,
+        product_offering_id: Uuid,
+        old_price: Money,
+        new_price: Money,
+        increase_percent: f64,
+    },
+    /// Offering dropped from the catalog
+    RemovedOffering {
+        product_offering_id: Uuid,
+        name: String,
+    },
+}
+
+/// Reason a rollback was refused
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RollbackError {
+    #[error("Version {0} not found")]
+    VersionNotFound(Uuid),
+    #[error("Catalog {0} has no active version to roll back from")]
+    NoActiveVersion(Uuid),
+    #[error("Rollback would orphan active offerings: {0:?}")]
+    OrphanedOfferings(Vec<Uuid>),
 }
@@ -0,0 +1,420 @@
+//! Computed Metrics
+//!
+//! Derived metrics defined by an arithmetic expression over existing metric
+//! streams, e.g. `sales.total_revenue / customers.active_customers`. Inputs are
+//! referenced as `<metric type>.<field>` (nested fields are joined with `.`)
+//! and recomputed each time their source metrics are regenerated.
+//!
+//! Division by zero does not fail the stream: the computed value is `null`
+//! with status `DIVISION_BY_ZERO`. Computed metrics are streamed as
+//! `COMPUTED` metric updates alongside the raw metrics.
+
+use crate::error::RealtimeAnalyticsError;
+use crate::models::{ComputedMetricDefinition, ComputedMetricStatus, MetricType, MetricUpdate};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Parsed computed-metric expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+}
+
+/// Binary arithmetic operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// Why an expression produced no value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluationIssue {
+    DivisionByZero,
+    MissingInput(String),
+}
+
+impl Expr {
+    /// Parse an expression such as `revenue / (active_users + 1)`
+    pub fn parse(input: &str) -> Result<Self, RealtimeAnalyticsError> {
+        let tokens = tokenize(input)?;
+        if tokens.len() > MAX_EXPRESSION_TOKENS {
+            return Err(invalid(input, "expression too long"));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.expression()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(invalid(input, "unexpected trailing input"));
+        }
+        Ok(expr)
+    }
+
+    /// Variables referenced by the expression
+    pub fn variables(&self) -> HashSet<String> {
+        let mut vars = HashSet::new();
+        self.collect_variables(&mut vars);
+        vars
+    }
+
+    fn collect_variables(&self, vars: &mut HashSet<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) => {
+                vars.insert(name.clone());
+            }
+            Expr::Negate(inner) => inner.collect_variables(vars),
+            Expr::Binary(lhs, _, rhs) => {
+                lhs.collect_variables(vars);
+                rhs.collect_variables(vars);
+            }
+        }
+    }
+
+    /// Evaluate against the current input values
+    pub fn evaluate(&self, inputs: &HashMap<String, f64>) -> Result<f64, EvaluationIssue> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Variable(name) => inputs
+                .get(name)
+                .copied()
+                .ok_or_else(|| EvaluationIssue::MissingInput(name.clone())),
+            Expr::Negate(inner) => Ok(-inner.evaluate(inputs)?),
+            Expr::Binary(lhs, op, rhs) => {
+                let lhs = lhs.evaluate(inputs)?;
+                let rhs = rhs.evaluate(inputs)?;
+                match op {
+                    BinaryOp::Add => Ok(lhs + rhs),
+                    BinaryOp::Subtract => Ok(lhs - rhs),
+                    BinaryOp::Multiply => Ok(lhs * rhs),
+                    BinaryOp::Divide if rhs == 0.0 => Err(EvaluationIssue::DivisionByZero),
+                    BinaryOp::Divide => Ok(lhs / rhs),
+                }
+            }
+        }
+    }
+}
+
+fn invalid(input: &str, reason: &str) -> RealtimeAnalyticsError {
+    RealtimeAnalyticsError::InvalidExpression(format!("{} ({})", input, reason))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(BinaryOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, RealtimeAnalyticsError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Op(match c {
+                    '+' => BinaryOp::Add,
+                    '-' => BinaryOp::Subtract,
+                    '*' => BinaryOp::Multiply,
+                    _ => BinaryOp::Divide,
+                }));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut literal = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_digit() || d == '.' {
+                        literal.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = literal
+                    .parse::<f64>()
+                    .map_err(|_| invalid(input, "invalid number"))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&d) = chars.peek() {
+                    if d.is_ascii_alphanumeric() || d == '_' || d == '.' {
+                        ident.push(d);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return Err(invalid(input, "unexpected character")),
+        }
+    }
+
+    if tokens.is_empty() {
+        return Err(invalid(input, "empty expression"));
+    }
+    Ok(tokens)
+}
+
+/// Longest accepted expression, in tokens; bounds the depth of operator chains
+const MAX_EXPRESSION_TOKENS: usize = 256;
+
+/// Deepest accepted nesting of parentheses and unary minus
+const MAX_NESTING_DEPTH: usize = 32;
+
+/// Recursive-descent parser with the usual precedence (`*` `/` over `+` `-`)
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, reason: &str) -> RealtimeAnalyticsError {
+        RealtimeAnalyticsError::InvalidExpression(format!("{} at token {}", reason, self.pos + 1))
+    }
+
+    fn expression(&mut self) -> Result<Expr, RealtimeAnalyticsError> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ (BinaryOp::Add | BinaryOp::Subtract))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, RealtimeAnalyticsError> {
+        let mut lhs = self.factor()?;
+        while let Some(Token::Op(op @ (BinaryOp::Multiply | BinaryOp::Divide))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let rhs = self.factor()?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<Expr, RealtimeAnalyticsError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let factor = self.nested_factor();
+        self.depth -= 1;
+        factor
+    }
+
+    fn nested_factor(&mut self) -> Result<Expr, RealtimeAnalyticsError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Ident(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Op(BinaryOp::Subtract)) => Ok(Expr::Negate(Box::new(self.factor()?))),
+            Some(Token::LParen) => {
+                let inner = self.expression()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err(self.error("expected ')'")),
+                }
+            }
+            _ => Err(self.error("expected number, metric or '('")),
+        }
+    }
+}
+
+/// Metric type an input variable is read from, e.g. `sales.total_revenue` -> Sales
+pub fn input_metric_type(variable: &str) -> Option<MetricType> {
+    let prefix = variable.split('.').next()?;
+    serde_json::from_value(serde_json::Value::String(prefix.to_uppercase())).ok()
+}
+
+/// Flatten numeric fields of metric updates into `<metric type>.<field>` inputs
+pub fn collect_inputs(updates: &[MetricUpdate]) -> HashMap<String, f64> {
+    fn flatten(prefix: &str, value: &serde_json::Value, inputs: &mut HashMap<String, f64>) {
+        match value {
+            serde_json::Value::Number(n) => {
+                if let Some(n) = n.as_f64() {
+                    inputs.insert(prefix.to_string(), n);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    flatten(&format!("{}.{}", prefix, key), value, inputs);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut inputs = HashMap::new();
+    for update in updates {
+        let prefix = serde_json::to_value(&update.metric_type)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_lowercase));
+        if let Some(prefix) = prefix {
+            flatten(&prefix, &update.data, &mut inputs);
+        }
+    }
+    inputs
+}
+
+/// Registered computed metric
+struct ComputedMetric {
+    definition: ComputedMetricDefinition,
+    expr: Expr,
+    input_types: Vec<MetricType>,
+}
+
+/// Registry of computed metric definitions
+#[derive(Default)]
+pub struct ComputedMetricRegistry {
+    metrics: RwLock<HashMap<String, ComputedMetric>>,
+}
+
+impl ComputedMetricRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) a computed metric
+    pub fn register(
+        &self,
+        definition: ComputedMetricDefinition,
+    ) -> Result<(), RealtimeAnalyticsError> {
+        let expr = Expr::parse(&definition.expression)?;
+
+        let mut input_types = Vec::new();
+        for variable in expr.variables() {
+            let metric_type = input_metric_type(&variable).ok_or_else(|| {
+                RealtimeAnalyticsError::InvalidExpression(format!(
+                    "Unknown metric stream in '{}'",
+                    variable
+                ))
+            })?;
+            if metric_type == MetricType::Computed {
+                return Err(RealtimeAnalyticsError::InvalidExpression(format!(
+                    "Computed metrics cannot reference other computed metrics ('{}')",
+                    variable
+                )));
+            }
+            if !input_types.contains(&metric_type) {
+                input_types.push(metric_type);
+            }
+        }
+
+        self.metrics.write().unwrap().insert(
+            definition.name.clone(),
+            ComputedMetric {
+                definition,
+                expr,
+                input_types,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a computed metric
+    pub fn unregister(&self, name: &str) -> bool {
+        self.metrics.write().unwrap().remove(name).is_some()
+    }
+
+    /// List computed metric definitions
+    pub fn list(&self) -> Vec<ComputedMetricDefinition> {
+        self.metrics
+            .read()
+            .unwrap()
+            .values()
+            .map(|m| m.definition.clone())
+            .collect()
+    }
+
+    /// Raw metric types needed to compute the given metrics
+    pub fn input_types(&self, names: &[String]) -> Vec<MetricType> {
+        let metrics = self.metrics.read().unwrap();
+        let mut types = Vec::new();
+        for metric in names.iter().filter_map(|name| metrics.get(name)) {
+            for metric_type in &metric.input_types {
+                if !types.contains(metric_type) {
+                    types.push(metric_type.clone());
+                }
+            }
+        }
+        types
+    }
+
+    /// Recompute the given metrics from freshly generated raw updates
+    pub fn compute(
+        &self,
+        names: &[String],
+        raw_updates: &[MetricUpdate],
+        tenant_id: Option<Uuid>,
+    ) -> Vec<MetricUpdate> {
+        let inputs = collect_inputs(raw_updates);
+        let metrics = self.metrics.read().unwrap();
+
+        names
+            .iter()
+            .filter_map(|name| metrics.get(name))
+            .map(|metric| {
+                let (value, status) = match metric.expr.evaluate(&inputs) {
+                    Ok(value) if value.is_finite() => (Some(value), ComputedMetricStatus::Ok),
+                    Ok(_) => (None, ComputedMetricStatus::Overflow),
+                    Err(EvaluationIssue::DivisionByZero) => {
+                        (None, ComputedMetricStatus::DivisionByZero)
+                    }
+                    Err(EvaluationIssue::MissingInput(_)) => {
+                        (None, ComputedMetricStatus::MissingInput)
+                    }
+                };
+
+                MetricUpdate {
+                    metric_type: MetricType::Computed,
+                    timestamp: Utc::now(),
+                    data: serde_json::json!({
+                        "name": metric.definition.name,
+                        "expression": metric.definition.expression,
+                        "value": value,
+                        "status": status,
+                    }),
+                    tenant_id,
+                }
+            })
+            .collect()
+    }
+}
@@ -16,4 +16,16 @@ pub enum RealtimeAnalyticsError {
 
     #[error("Analytics service error: {0}")]
     AnalyticsError(String),
+
+    #[error("Invalid computed metric expression: {0}")]
+    InvalidExpression(String),
+
+    #[error("Dashboard layout error: {0}")]
+    LayoutError(String),
+
+    #[error("Unsupported layout schema version: {0}")]
+    UnsupportedSchemaVersion(u32),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
 }
@@ -0,0 +1,78 @@
+//! HTTP handlers for dashboard layouts
+
+use crate::error::RealtimeAnalyticsError;
+use crate::layout::DashboardLayoutService;
+use crate::models::SaveDashboardLayoutRequest;
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn error_response(err: RealtimeAnalyticsError) -> HttpResponse {
+    let body = serde_json::json!({ "error": err.to_string() });
+    match err {
+        RealtimeAnalyticsError::LayoutError(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+/// Get a user's dashboard layout
+///
+/// Called by the dashboard on login; users without a saved layout get an
+/// empty one.
+#[utoipa::path(
+    get,
+    path = "/analytics/v1/dashboard/layout/{user_id}",
+    responses(
+        (status = 200, description = "Saved dashboard layout", body = DashboardLayout)
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    tag = "Realtime Analytics"
+)]
+pub async fn get_dashboard_layout(
+    service: web::Data<Arc<DashboardLayoutService>>,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    match service.get_layout_or_default(path.into_inner()).await {
+        Ok(layout) => Ok(HttpResponse::Ok().json(layout)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Save a user's dashboard layout
+#[utoipa::path(
+    put,
+    path = "/analytics/v1/dashboard/layout/{user_id}",
+    request_body = SaveDashboardLayoutRequest,
+    responses(
+        (status = 200, description = "Dashboard layout saved", body = DashboardLayout),
+        (status = 400, description = "Invalid layout")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    tag = "Realtime Analytics"
+)]
+pub async fn save_dashboard_layout(
+    service: web::Data<Arc<DashboardLayoutService>>,
+    path: web::Path<Uuid>,
+    body: web::Json<SaveDashboardLayoutRequest>,
+) -> ActixResult<HttpResponse> {
+    match service
+        .save_layout(path.into_inner(), body.into_inner())
+        .await
+    {
+        Ok(layout) => Ok(HttpResponse::Ok().json(layout)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Configure dashboard layout routes
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/analytics/v1/dashboard/layout/{user_id}")
+            .route(web::get().to(get_dashboard_layout))
+            .route(web::put().to(save_dashboard_layout)),
+    );
+}
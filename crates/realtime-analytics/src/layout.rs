@@ -0,0 +1,151 @@
+//! Dashboard Layout Persistence
+//!
+//! Stores each user's dashboard layout (widgets, metrics and grid positions)
+//! server-side so it can be restored on login. Layouts are stored together
+//! with the widget schema version they were written in; older layouts are
+//! upgraded on read by the steps in [`LAYOUT_MIGRATIONS`].
+
+use crate::error::RealtimeAnalyticsError;
+use crate::models::{DashboardLayout, DashboardWidget, SaveDashboardLayoutRequest};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Upgrade steps for stored widget JSON; entry `i` turns schema version
+/// `i + 1` into version `i + 2`. Append a step whenever the widget schema changes.
+const LAYOUT_MIGRATIONS: &[fn(serde_json::Value) -> serde_json::Value] = &[];
+
+/// Widget schema version written by this build
+pub const CURRENT_LAYOUT_SCHEMA_VERSION: u32 = LAYOUT_MIGRATIONS.len() as u32 + 1;
+
+/// Upgrade stored widget JSON from `schema_version` to the current schema
+pub fn migrate_widgets(
+    schema_version: u32,
+    mut widgets: serde_json::Value,
+) -> Result<Vec<DashboardWidget>, RealtimeAnalyticsError> {
+    if schema_version == 0 || schema_version > CURRENT_LAYOUT_SCHEMA_VERSION {
+        return Err(RealtimeAnalyticsError::UnsupportedSchemaVersion(
+            schema_version,
+        ));
+    }
+
+    for step in &LAYOUT_MIGRATIONS[(schema_version - 1) as usize..] {
+        widgets = step(widgets);
+    }
+
+    serde_json::from_value(widgets).map_err(|e| RealtimeAnalyticsError::LayoutError(e.to_string()))
+}
+
+/// Reject layouts the frontend could not render
+fn validate_widgets(widgets: &[DashboardWidget]) -> Result<(), RealtimeAnalyticsError> {
+    let mut ids = HashSet::new();
+    for widget in widgets {
+        if !ids.insert(widget.id.as_str()) {
+            return Err(RealtimeAnalyticsError::LayoutError(format!(
+                "Duplicate widget id: {}",
+                widget.id
+            )));
+        }
+        if widget.position.width == 0 || widget.position.height == 0 {
+            return Err(RealtimeAnalyticsError::LayoutError(format!(
+                "Widget {} must have a non-zero size",
+                widget.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Dashboard Layout Service
+pub struct DashboardLayoutService {
+    pool: PgPool,
+}
+
+impl DashboardLayoutService {
+    /// Create a new dashboard layout service
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get a user's saved layout, upgraded to the current widget schema
+    pub async fn get_layout(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<DashboardLayout>, RealtimeAnalyticsError> {
+        let row = sqlx::query(
+            "SELECT user_id, tenant_id, schema_version, widgets, updated_at
+             FROM dashboard_layouts WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let schema_version: i32 = row.get("schema_version");
+        let widgets = migrate_widgets(schema_version as u32, row.get("widgets"))?;
+
+        Ok(Some(DashboardLayout {
+            user_id: row.get("user_id"),
+            tenant_id: row.get("tenant_id"),
+            schema_version: CURRENT_LAYOUT_SCHEMA_VERSION,
+            widgets,
+            updated_at: row.get::<Option<DateTime<Utc>>, _>("updated_at"),
+        }))
+    }
+
+    /// Get a user's layout for login, falling back to an empty dashboard
+    pub async fn get_layout_or_default(
+        &self,
+        user_id: Uuid,
+    ) -> Result<DashboardLayout, RealtimeAnalyticsError> {
+        Ok(self.get_layout(user_id).await?.unwrap_or(DashboardLayout {
+            user_id,
+            tenant_id: None,
+            schema_version: CURRENT_LAYOUT_SCHEMA_VERSION,
+            widgets: vec![],
+            updated_at: None,
+        }))
+    }
+
+    /// Save (replace) a user's layout in the current widget schema
+    pub async fn save_layout(
+        &self,
+        user_id: Uuid,
+        request: SaveDashboardLayoutRequest,
+    ) -> Result<DashboardLayout, RealtimeAnalyticsError> {
+        validate_widgets(&request.widgets)?;
+
+        let widgets = serde_json::to_value(&request.widgets)
+            .map_err(|e| RealtimeAnalyticsError::LayoutError(e.to_string()))?;
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT INTO dashboard_layouts (user_id, tenant_id, schema_version, widgets, updated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (user_id) DO UPDATE SET
+                tenant_id = EXCLUDED.tenant_id,
+                schema_version = EXCLUDED.schema_version,
+                widgets = EXCLUDED.widgets,
+                updated_at = EXCLUDED.updated_at",
+        )
+        .bind(user_id)
+        .bind(request.tenant_id)
+        .bind(CURRENT_LAYOUT_SCHEMA_VERSION as i32)
+        .bind(&widgets)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(DashboardLayout {
+            user_id,
+            tenant_id: request.tenant_id,
+            schema_version: CURRENT_LAYOUT_SCHEMA_VERSION,
+            widgets: request.widgets,
+            updated_at: Some(now),
+        })
+    }
+}
@@ -5,13 +5,21 @@
 //! - Real-time dashboard updates
 //! - Live monitoring of sales, revenue, usage, and customer metrics
 //! - Event-driven metric updates
+//! - Computed metrics derived from expressions over metric streams
+//! - Per-user dashboard layout persistence
 
+pub mod computed;
 pub mod error;
+pub mod handlers;
+pub mod layout;
 pub mod models;
 pub mod service;
 pub mod websocket;
 
+pub use computed::*;
 pub use error::*;
+pub use handlers::*;
+pub use layout::*;
 pub use models::*;
 pub use service::*;
 pub use websocket::*;
@@ -15,6 +15,8 @@ pub enum MetricType {
     Orders,
     Alarms,
     Devices,
+    /// Derived from other metrics by a computed-metric expression
+    Computed,
 }
 
 /// Real-time Metric Update
@@ -32,6 +34,32 @@ pub struct SubscriptionRequest {
     pub metric_types: Vec<MetricType>,
     pub tenant_id: Option<Uuid>,
     pub update_interval_seconds: Option<u64>,
+    /// Names of registered computed metrics to stream
+    #[serde(default)]
+    pub computed_metrics: Vec<String>,
+}
+
+/// Computed Metric Definition
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ComputedMetricDefinition {
+    pub name: String,
+    /// Arithmetic expression over `<metric type>.<field>` inputs,
+    /// e.g. `sales.total_revenue / customers.active_customers`
+    pub expression: String,
+    pub description: Option<String>,
+}
+
+/// Outcome of evaluating a computed metric
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ComputedMetricStatus {
+    Ok,
+    /// A divisor was zero; the value is reported as `null`
+    DivisionByZero,
+    /// An input metric field was not present in the source stream
+    MissingInput,
+    /// The result was not a finite number
+    Overflow,
 }
 
 /// WebSocket Message
@@ -51,3 +79,43 @@ pub enum WebSocketMessage {
     #[serde(rename = "pong")]
     Pong,
 }
+
+/// Widget position on the dashboard grid
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct WidgetPosition {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Dashboard Widget
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DashboardWidget {
+    pub id: String,
+    /// Widget kind understood by the frontend, e.g. `LINE_CHART`
+    pub widget_type: String,
+    pub metric_types: Vec<MetricType>,
+    pub position: WidgetPosition,
+    /// Free-form widget options (colours, time window, ...)
+    #[serde(default)]
+    pub settings: serde_json::Value,
+}
+
+/// Per-user Dashboard Layout
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DashboardLayout {
+    pub user_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    /// Widget schema version the layout is expressed in
+    pub schema_version: u32,
+    pub widgets: Vec<DashboardWidget>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Save Dashboard Layout Request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SaveDashboardLayoutRequest {
+    pub tenant_id: Option<Uuid>,
+    pub widgets: Vec<DashboardWidget>,
+}
@@ -1,5 +1,6 @@
 //! Real-time Analytics Service
 
+use crate::computed::ComputedMetricRegistry;
 use crate::error::RealtimeAnalyticsError;
 use crate::models::{MetricType, MetricUpdate};
 use analytics::service::AnalyticsService;
@@ -9,12 +10,21 @@ use uuid::Uuid;
 /// Real-time Analytics Service
 pub struct RealtimeAnalyticsService {
     analytics_service: AnalyticsService,
+    computed_metrics: ComputedMetricRegistry,
 }
 
 impl RealtimeAnalyticsService {
     /// Create a new real-time analytics service
     pub fn new(analytics_service: AnalyticsService) -> Self {
-        Self { analytics_service }
+        Self {
+            analytics_service,
+            computed_metrics: ComputedMetricRegistry::new(),
+        }
+    }
+
+    /// Computed metric definitions streamed by this service
+    pub fn computed_metrics(&self) -> &ComputedMetricRegistry {
+        &self.computed_metrics
     }
 
     /// Generate metric update for a specific metric type
@@ -101,4 +111,34 @@ impl RealtimeAnalyticsService {
 
         Ok(updates)
     }
+
+    /// Generate updates for a subscription, including computed metrics
+    ///
+    /// Raw metrics needed only as computed-metric inputs are generated but not
+    /// returned.
+    pub async fn generate_subscription_updates(
+        &self,
+        metric_types: &[MetricType],
+        computed_metrics: &[String],
+        tenant_id: Option<Uuid>,
+    ) -> Result<Vec<MetricUpdate>, RealtimeAnalyticsError> {
+        let mut required = metric_types.to_vec();
+        for metric_type in self.computed_metrics.input_types(computed_metrics) {
+            if !required.contains(&metric_type) {
+                required.push(metric_type);
+            }
+        }
+
+        let raw_updates = self.generate_metric_updates(&required, tenant_id).await?;
+        let computed = self
+            .computed_metrics
+            .compute(computed_metrics, &raw_updates, tenant_id);
+
+        let mut updates: Vec<MetricUpdate> = raw_updates
+            .into_iter()
+            .filter(|update| metric_types.contains(&update.metric_type))
+            .collect();
+        updates.extend(computed);
+        Ok(updates)
+    }
 }
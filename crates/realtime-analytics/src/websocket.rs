@@ -22,6 +22,7 @@ pub async fn analytics_websocket(
 
     actix_web::rt::spawn(async move {
         let mut subscriptions: Vec<MetricType> = Vec::new();
+        let mut computed_subscriptions: Vec<String> = Vec::new();
         let mut tenant_id: Option<uuid::Uuid> = None;
         let mut update_interval = Duration::from_secs(5);
 
@@ -34,6 +35,7 @@ pub async fn analytics_websocket(
                             WebSocketMessage::Subscribe(sub) => {
                                 info!("New subscription: {:?}", sub.metric_types);
                                 subscriptions = sub.metric_types.clone();
+                                computed_subscriptions = sub.computed_metrics.clone();
                                 tenant_id = sub.tenant_id;
                                 if let Some(interval_secs) = sub.update_interval_seconds {
                                     update_interval = Duration::from_secs(interval_secs);
@@ -62,9 +64,13 @@ pub async fn analytics_websocket(
         loop {
             tokio::select! {
                 _ = interval_timer.tick() => {
-                    if !subscriptions.is_empty() {
+                    if !subscriptions.is_empty() || !computed_subscriptions.is_empty() {
                         let updates = service
-                            .generate_metric_updates(&subscriptions, tenant_id)
+                            .generate_subscription_updates(
+                                &subscriptions,
+                                &computed_subscriptions,
+                                tenant_id,
+                            )
                             .await;
 
                         match updates {
@@ -0,0 +1,165 @@
+//! Reservation Admission Control
+//!
+//! Keeps headroom for bursts and emergencies: each capacity type has a soft
+//! utilization ceiling, and a reservation that would push utilization above it
+//! is only admitted if it is an emergency or meets the type's high-priority
+//! threshold. Hard capacity is never exceeded.
+//!
+//! Utilization counts used capacity plus the capacity held by every
+//! reservation that has not ended, which reservations add to the pool's
+//! reserved capacity when they are created and give back when they end.
+
+use crate::capacity::get_resource_capacities;
+use crate::error::{ResourceManagementError, ResourceManagementResult};
+use crate::models::{AdmissionCeiling, ResourceCapacity, SetAdmissionCeilingRequest};
+use chrono::Utc;
+use sqlx::{Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Soft ceiling applied to capacity types without a configured one
+pub const DEFAULT_SOFT_CEILING_PERCENTAGE: f64 = 85.0;
+
+/// Priority admitted above the soft ceiling when none is configured
+pub const DEFAULT_HIGH_PRIORITY_THRESHOLD: i32 = 100;
+
+/// Get the admission ceiling of a capacity type, falling back to the defaults
+pub async fn get_admission_ceiling(
+    pool: &Pool<Postgres>,
+    capacity_type: &str,
+) -> ResourceManagementResult<AdmissionCeiling> {
+    let row = sqlx::query(
+        "SELECT capacity_type, soft_ceiling_percentage::FLOAT8 AS soft_ceiling_percentage,
+         high_priority_threshold
+         FROM capacity_admission_ceilings
+         WHERE capacity_type = $1",
+    )
+    .bind(capacity_type)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some(row) => AdmissionCeiling {
+            capacity_type: row.get("capacity_type"),
+            soft_ceiling_percentage: row.get("soft_ceiling_percentage"),
+            high_priority_threshold: row.get("high_priority_threshold"),
+        },
+        None => AdmissionCeiling {
+            capacity_type: capacity_type.to_string(),
+            soft_ceiling_percentage: DEFAULT_SOFT_CEILING_PERCENTAGE,
+            high_priority_threshold: DEFAULT_HIGH_PRIORITY_THRESHOLD,
+        },
+    })
+}
+
+/// Set the admission ceiling of a capacity type
+pub async fn set_admission_ceiling(
+    pool: &Pool<Postgres>,
+    request: SetAdmissionCeilingRequest,
+) -> ResourceManagementResult<AdmissionCeiling> {
+    if !(request.soft_ceiling_percentage > 0.0 && request.soft_ceiling_percentage <= 100.0) {
+        return Err(ResourceManagementError::InvalidAdmissionCeiling(format!(
+            "Soft ceiling for {} must be above 0% and at most 100%, got {}%",
+            request.capacity_type, request.soft_ceiling_percentage
+        )));
+    }
+
+    let high_priority_threshold = request
+        .high_priority_threshold
+        .unwrap_or(DEFAULT_HIGH_PRIORITY_THRESHOLD);
+    let now = Utc::now();
+
+    sqlx::query(
+        "INSERT INTO capacity_admission_ceilings
+         (capacity_type, soft_ceiling_percentage, high_priority_threshold, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $4)
+         ON CONFLICT (capacity_type) DO UPDATE
+         SET soft_ceiling_percentage = EXCLUDED.soft_ceiling_percentage,
+             high_priority_threshold = EXCLUDED.high_priority_threshold,
+             updated_at = EXCLUDED.updated_at",
+    )
+    .bind(&request.capacity_type)
+    .bind(request.soft_ceiling_percentage)
+    .bind(high_priority_threshold)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    get_admission_ceiling(pool, &request.capacity_type).await
+}
+
+/// Check that a reservation of `amount` may be admitted on a resource
+///
+/// `freed` is capacity that will be released before the reservation takes
+/// effect, e.g. by preempted reservations. Fails with `InsufficientCapacity`
+/// when hard capacity would be exceeded, and with `SoftLimitReached` when the
+/// soft ceiling would be exceeded by a reservation that is neither an emergency
+/// nor high priority.
+pub async fn check_admission(
+    pool: &Pool<Postgres>,
+    resource_inventory_id: Uuid,
+    capacity_type: &str,
+    amount: f64,
+    freed: f64,
+    priority: i32,
+    emergency: bool,
+) -> ResourceManagementResult<()> {
+    let capacities = get_resource_capacities(pool, resource_inventory_id).await?;
+    let ceiling = get_admission_ceiling(pool, capacity_type).await?;
+
+    admit(
+        capacities
+            .iter()
+            .find(|capacity| capacity.capacity_type == capacity_type),
+        &ceiling,
+        capacity_type,
+        amount,
+        freed,
+        priority,
+        emergency,
+    )
+}
+
+/// Admission decision for a reservation of `amount` against a capacity pool
+///
+/// `capacity` is `None` when the resource does not track the capacity type.
+pub fn admit(
+    capacity: Option<&ResourceCapacity>,
+    ceiling: &AdmissionCeiling,
+    capacity_type: &str,
+    amount: f64,
+    freed: f64,
+    priority: i32,
+    emergency: bool,
+) -> ResourceManagementResult<()> {
+    let capacity = match capacity {
+        Some(capacity) if capacity.available_capacity + freed >= amount => capacity,
+        _ => {
+            return Err(ResourceManagementError::InsufficientCapacity(format!(
+                "Insufficient {} capacity for reservation",
+                capacity_type
+            )))
+        }
+    };
+
+    if capacity.total_capacity <= 0.0 {
+        return Ok(());
+    }
+
+    let utilization = (capacity.used_capacity + capacity.reserved_capacity - freed + amount)
+        / capacity.total_capacity
+        * 100.0;
+
+    if utilization > ceiling.soft_ceiling_percentage
+        && !ceiling.admits_above_ceiling(priority, emergency)
+    {
+        return Err(ResourceManagementError::SoftLimitReached(format!(
+            "Reservation would bring {} utilization to {:.1}%, above the {:.1}% ceiling for priority below {}",
+            capacity_type,
+            utilization,
+            ceiling.soft_ceiling_percentage,
+            ceiling.high_priority_threshold
+        )));
+    }
+
+    Ok(())
+}
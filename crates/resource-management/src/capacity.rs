@@ -5,7 +5,7 @@ use crate::models::{
     CreateResourceCapacityRequest, ResourceCapacity, UpdateResourceCapacityRequest,
 };
 use chrono::Utc;
-use sqlx::{Pool, Postgres, Row};
+use sqlx::{PgConnection, Pool, Postgres, Row};
 use uuid::Uuid;
 
 /// Get all capacities for a resource
@@ -14,8 +14,9 @@ pub async fn get_resource_capacities(
     resource_inventory_id: Uuid,
 ) -> ResourceManagementResult<Vec<ResourceCapacity>> {
     let rows = sqlx::query(
-        "SELECT id, resource_inventory_id, capacity_type, total_capacity, 
-         used_capacity, reserved_capacity, unit, created_at, updated_at
+        "SELECT id, resource_inventory_id, capacity_type,
+         total_capacity::FLOAT8 AS total_capacity, used_capacity::FLOAT8 AS used_capacity,
+         reserved_capacity::FLOAT8 AS reserved_capacity, unit, created_at, updated_at
          FROM resource_capacities
          WHERE resource_inventory_id = $1
          ORDER BY capacity_type",
@@ -54,8 +55,9 @@ pub async fn get_capacity_by_id(
     capacity_id: Uuid,
 ) -> ResourceManagementResult<ResourceCapacity> {
     let row = sqlx::query(
-        "SELECT id, resource_inventory_id, capacity_type, total_capacity, 
-         used_capacity, reserved_capacity, unit, created_at, updated_at
+        "SELECT id, resource_inventory_id, capacity_type,
+         total_capacity::FLOAT8 AS total_capacity, used_capacity::FLOAT8 AS used_capacity,
+         reserved_capacity::FLOAT8 AS reserved_capacity, unit, created_at, updated_at
          FROM resource_capacities
          WHERE id = $1",
     )
@@ -178,6 +180,20 @@ pub async fn check_capacity_availability(
     Ok(false)
 }
 
+/// Get available capacity of a given type, if the resource tracks it
+pub async fn get_available_capacity(
+    pool: &Pool<Postgres>,
+    resource_inventory_id: Uuid,
+    capacity_type: &str,
+) -> ResourceManagementResult<Option<f64>> {
+    let capacities = get_resource_capacities(pool, resource_inventory_id).await?;
+
+    Ok(capacities
+        .into_iter()
+        .find(|capacity| capacity.capacity_type == capacity_type)
+        .map(|capacity| capacity.available_capacity))
+}
+
 /// Reserve capacity
 pub async fn reserve_capacity(
     pool: &Pool<Postgres>,
@@ -243,3 +259,69 @@ pub async fn release_reserved_capacity(
         capacity_type, resource_inventory_id
     )))
 }
+
+/// Lock the capacities of a resource for the rest of the transaction
+///
+/// Reservations of the resource are serialized on these rows, so capacity
+/// checked while holding them stays valid until the transaction commits.
+pub(crate) async fn lock_resource_capacities(
+    conn: &mut PgConnection,
+    resource_inventory_id: Uuid,
+) -> ResourceManagementResult<Vec<ResourceCapacity>> {
+    let rows = sqlx::query(
+        "SELECT id, resource_inventory_id, capacity_type,
+         total_capacity::FLOAT8 AS total_capacity, used_capacity::FLOAT8 AS used_capacity,
+         reserved_capacity::FLOAT8 AS reserved_capacity, unit, created_at, updated_at
+         FROM resource_capacities
+         WHERE resource_inventory_id = $1
+         ORDER BY capacity_type
+         FOR UPDATE",
+    )
+    .bind(resource_inventory_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let total: f64 = row.get("total_capacity");
+            let used: f64 = row.get("used_capacity");
+            let reserved: f64 = row.get("reserved_capacity");
+
+            ResourceCapacity {
+                id: row.get("id"),
+                resource_inventory_id: row.get("resource_inventory_id"),
+                capacity_type: row.get("capacity_type"),
+                total_capacity: total,
+                used_capacity: used,
+                reserved_capacity: reserved,
+                available_capacity: total - used - reserved,
+                unit: row.get("unit"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            }
+        })
+        .collect())
+}
+
+/// Add `delta` to the reserved capacity of a type, never going below zero
+pub(crate) async fn adjust_reserved_capacity(
+    conn: &mut PgConnection,
+    resource_inventory_id: Uuid,
+    capacity_type: &str,
+    delta: f64,
+) -> ResourceManagementResult<()> {
+    sqlx::query(
+        "UPDATE resource_capacities
+         SET reserved_capacity = GREATEST(reserved_capacity + $3, 0), updated_at = $4
+         WHERE resource_inventory_id = $1 AND capacity_type = $2",
+    )
+    .bind(resource_inventory_id)
+    .bind(capacity_type)
+    .bind(delta)
+    .bind(Utc::now())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
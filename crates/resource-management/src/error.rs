@@ -14,6 +14,12 @@ pub enum ResourceManagementError {
     #[error("Insufficient capacity: {0}")]
     InsufficientCapacity(String),
 
+    #[error("Soft capacity limit reached: {0}")]
+    SoftLimitReached(String),
+
+    #[error("Invalid admission ceiling: {0}")]
+    InvalidAdmissionCeiling(String),
+
     #[error("Reservation conflict: {0}")]
     ReservationConflict(String),
 
@@ -2,15 +2,18 @@
 //!
 //! This module provides:
 //! - Resource capacity management (track usage, limits, metrics)
-//! - Resource reservation system (reserve resources with time windows)
-//! - Network topology management (connections, relationships)
+//! - Resource reservation system (reserve resources with time windows, priority and preemption)
+//! - Admission control (per capacity type soft utilization ceilings)
+//! - Network topology management (connections, relationships, region partitioning)
 
+pub mod admission;
 pub mod capacity;
 pub mod error;
 pub mod models;
 pub mod reservation;
 pub mod topology;
 
+pub use admission::*;
 pub use capacity::*;
 pub use error::*;
 pub use models::*;
@@ -62,6 +62,33 @@ pub struct UpdateResourceCapacityRequest {
     pub reserved_capacity: Option<f64>,
 }
 
+/// Admission ceiling of a capacity type
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AdmissionCeiling {
+    pub capacity_type: String,
+    /// Utilization (0-100) above which only high-priority reservations are admitted
+    pub soft_ceiling_percentage: f64,
+    /// Minimum priority admitted above the soft ceiling
+    pub high_priority_threshold: i32,
+}
+
+impl AdmissionCeiling {
+    /// Whether a reservation with the given priority may use headroom above the ceiling
+    ///
+    /// Emergency reservations always may.
+    pub fn admits_above_ceiling(&self, priority: i32, emergency: bool) -> bool {
+        emergency || priority >= self.high_priority_threshold
+    }
+}
+
+/// Set Admission Ceiling Request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetAdmissionCeilingRequest {
+    pub capacity_type: String,
+    pub soft_ceiling_percentage: f64,
+    pub high_priority_threshold: Option<i32>,
+}
+
 /// Reservation Status
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -71,6 +98,7 @@ pub enum ReservationStatus {
     Active,
     Completed,
     Cancelled,
+    Preempted,
 }
 
 /// Resource Reservation
@@ -92,6 +120,29 @@ pub struct ResourceReservation {
     pub confirmed_at: Option<DateTime<Utc>>,
     pub cancelled_at: Option<DateTime<Utc>>,
     pub cancellation_reason: Option<String>,
+    /// Priority used for preemption decisions (higher wins)
+    pub priority: i32,
+    /// Whether a higher-priority reservation may evict this one
+    pub preemptible: bool,
+    /// Emergency slice reservation
+    pub emergency: bool,
+    /// Reservation that evicted this one
+    pub preempted_by_reservation_id: Option<Uuid>,
+    /// Network slice the resources are reserved for, released when it terminates
+    pub network_slice_id: Option<Uuid>,
+}
+
+impl ResourceReservation {
+    /// Whether this reservation may be evicted by a request with the given priority
+    ///
+    /// Emergency reservations are never evicted. Emergency requests may evict
+    /// any other reservation, including non-preemptible ones.
+    pub fn can_be_preempted_by(&self, priority: i32, emergency: bool) -> bool {
+        if self.emergency {
+            return false;
+        }
+        emergency || (self.preemptible && self.priority < priority)
+    }
 }
 
 /// Create Resource Reservation Request
@@ -106,6 +157,38 @@ pub struct CreateResourceReservationRequest {
     pub service_order_id: Option<Uuid>,
     pub reserved_by_party_id: Option<Uuid>,
     pub capacity_requirements: serde_json::Value,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "default_preemptible")]
+    pub preemptible: bool,
+    #[serde(default)]
+    pub emergency: bool,
+    #[serde(default)]
+    pub network_slice_id: Option<Uuid>,
+}
+
+fn default_preemptible() -> bool {
+    true
+}
+
+/// Notification sent to the holder of an evicted reservation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReservationPreemption {
+    pub preempted_reservation_id: Uuid,
+    pub preempting_reservation_id: Uuid,
+    pub resource_inventory_id: Uuid,
+    pub reserved_by_party_id: Option<Uuid>,
+    pub preempted_priority: i32,
+    pub preempting_priority: i32,
+    pub emergency: bool,
+    pub preempted_at: DateTime<Utc>,
+}
+
+/// Result of a reservation made with preemption enabled
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreemptiveReservationOutcome {
+    pub reservation: ResourceReservation,
+    pub preempted: Vec<ReservationPreemption>,
 }
 
 /// Update Resource Reservation Request
@@ -184,3 +267,38 @@ pub struct UpdateNetworkTopologyRequest {
     pub latency_ms: Option<f64>,
     pub description: Option<String>,
 }
+
+/// Region label of a topology node
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopologyNodeRegion {
+    pub resource_inventory_id: Uuid,
+    pub region: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Nodes of a region and the links between them
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RegionSubgraph {
+    pub region: String,
+    pub nodes: Vec<Uuid>,
+    pub links: Vec<NetworkTopology>,
+}
+
+/// Link between nodes of two different regions
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CrossRegionLink {
+    pub link: NetworkTopology,
+    pub source_region: String,
+    pub target_region: String,
+}
+
+/// Path selection preference
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PathPreference {
+    /// Fewest hops
+    #[default]
+    Shortest,
+    /// Fewest cross-region links, then fewest hops
+    IntraRegion,
+}
@@ -1,12 +1,16 @@
 //! Resource Reservation System
 
-use crate::capacity::check_capacity_availability;
+use crate::admission::{admit, get_admission_ceiling};
+use crate::capacity::{adjust_reserved_capacity, lock_resource_capacities};
 use crate::error::{ResourceManagementError, ResourceManagementResult};
 use crate::models::{
-    CreateResourceReservationRequest, ResourceReservation, UpdateResourceReservationRequest,
+    CreateResourceReservationRequest, PreemptiveReservationOutcome, ReservationPreemption,
+    ResourceCapacity, ResourceReservation, UpdateResourceReservationRequest,
 };
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Postgres, Row};
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres, Row};
 use uuid::Uuid;
 
 /// Get all reservations for a resource
@@ -17,7 +21,8 @@ pub async fn get_resource_reservations(
     let rows = sqlx::query(
         "SELECT id, resource_inventory_id, reservation_name, description, reservation_status,
          start_time, end_time, resource_order_id, service_order_id, reserved_by_party_id,
-         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason
+         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason,
+         priority, preemptible, emergency, preempted_by_reservation_id, network_slice_id
          FROM resource_reservations
          WHERE resource_inventory_id = $1
          ORDER BY start_time DESC",
@@ -37,7 +42,8 @@ pub async fn get_reservation_by_id(
     let row = sqlx::query(
         "SELECT id, resource_inventory_id, reservation_name, description, reservation_status,
          start_time, end_time, resource_order_id, service_order_id, reserved_by_party_id,
-         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason
+         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason,
+         priority, preemptible, emergency, preempted_by_reservation_id, network_slice_id
          FROM resource_reservations
          WHERE id = $1",
     )
@@ -54,7 +60,15 @@ pub async fn get_reservation_by_id(
     }
 }
 
+/// Statuses of reservations that no longer hold capacity
+const ENDED_STATUSES: [&str; 3] = ["COMPLETED", "CANCELLED", "PREEMPTED"];
+
 /// Create resource reservation
+///
+/// Subject to admission control: fails with `SoftLimitReached` if the reservation
+/// would push utilization above the capacity type's soft ceiling and is neither
+/// an emergency nor high priority. The required capacity is added to the
+/// resource's reserved capacity until the reservation ends.
 pub async fn create_resource_reservation(
     pool: &Pool<Postgres>,
     request: CreateResourceReservationRequest,
@@ -64,9 +78,12 @@ pub async fn create_resource_reservation(
         return Err(ResourceManagementError::InvalidTimeRange);
     }
 
+    let mut tx = pool.begin().await?;
+    let capacities = lock_resource_capacities(&mut tx, request.resource_inventory_id).await?;
+
     // Check for overlapping reservations
     let overlapping = check_reservation_conflicts(
-        pool,
+        &mut *tx,
         request.resource_inventory_id,
         request.start_time,
         request.end_time,
@@ -81,28 +98,223 @@ pub async fn create_resource_reservation(
         )));
     }
 
-    // Check capacity availability if requirements are specified
-    if let Some(capacity_reqs) = request.capacity_requirements.as_object() {
-        for (capacity_type, value) in capacity_reqs {
-            if let Some(amount) = value.as_f64() {
-                let available = check_capacity_availability(
-                    pool,
-                    request.resource_inventory_id,
-                    capacity_type,
-                    amount,
-                )
-                .await?;
-
-                if !available {
-                    return Err(ResourceManagementError::InsufficientCapacity(format!(
-                        "Insufficient {} capacity for reservation",
-                        capacity_type
-                    )));
-                }
-            }
+    // Check capacity and admission ceilings if requirements are specified
+    let requirements = capacity_requirements(&request.capacity_requirements);
+    for (capacity_type, amount) in &requirements {
+        check_locked_admission(pool, &capacities, capacity_type, *amount, 0.0, &request).await?;
+    }
+
+    let id = insert_reservation(&mut *tx, &request).await?;
+    for (capacity_type, amount) in &requirements {
+        adjust_reserved_capacity(
+            &mut tx,
+            request.resource_inventory_id,
+            capacity_type,
+            *amount,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    get_reservation_by_id(pool, id).await
+}
+
+/// Create resource reservation, evicting lower-priority reservations if needed
+///
+/// Instead of failing on a conflict or capacity shortfall, overlapping reservations
+/// that can be preempted by this request are marked `PREEMPTED`, their capacity is
+/// released and a `ReservationPreempted` event is published for each evicted holder.
+/// Fails as `create_resource_reservation` would if any conflicting reservation cannot
+/// be evicted, or if capacity is still insufficient or the admission ceiling is still
+/// exceeded after eviction. Eviction and the new reservation commit together.
+pub async fn create_preemptive_reservation(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    request: CreateResourceReservationRequest,
+) -> ResourceManagementResult<PreemptiveReservationOutcome> {
+    // Validate time range
+    if request.end_time <= request.start_time {
+        return Err(ResourceManagementError::InvalidTimeRange);
+    }
+
+    let mut tx = pool.begin().await?;
+    let capacities = lock_resource_capacities(&mut tx, request.resource_inventory_id).await?;
+
+    let overlapping = check_reservation_conflicts(
+        &mut *tx,
+        request.resource_inventory_id,
+        request.start_time,
+        request.end_time,
+        None,
+    )
+    .await?;
+
+    let mut victims = Vec::with_capacity(overlapping.len());
+    for reservation_id in overlapping {
+        let reservation = lock_reservation(&mut tx, reservation_id).await?;
+        if !reservation.can_be_preempted_by(request.priority, request.emergency) {
+            return Err(ResourceManagementError::ReservationConflict(format!(
+                "Reservation {} (priority {}) cannot be preempted",
+                reservation.id, reservation.priority
+            )));
+        }
+        victims.push(reservation);
+    }
+
+    // Check capacity and admission ceilings, counting what the evicted reservations would free
+    let requirements = capacity_requirements(&request.capacity_requirements);
+    for (capacity_type, amount) in &requirements {
+        let freed: f64 = victims
+            .iter()
+            .filter_map(|victim| victim.capacity_requirements.get(capacity_type))
+            .filter_map(|v| v.as_f64())
+            .sum();
+        check_locked_admission(pool, &capacities, capacity_type, *amount, freed, &request).await?;
+    }
+
+    let id = insert_reservation(&mut *tx, &request).await?;
+    let now = Utc::now();
+
+    let mut preempted = Vec::with_capacity(victims.len());
+    for victim in victims {
+        sqlx::query(
+            "UPDATE resource_reservations
+             SET reservation_status = 'PREEMPTED', cancelled_at = $1, cancellation_reason = $2,
+                 preempted_by_reservation_id = $3, updated_at = $1
+             WHERE id = $4",
+        )
+        .bind(now)
+        .bind(format!("Preempted by reservation {}", id))
+        .bind(id)
+        .bind(victim.id)
+        .execute(&mut *tx)
+        .await?;
+
+        for (capacity_type, amount) in capacity_requirements(&victim.capacity_requirements) {
+            adjust_reserved_capacity(
+                &mut tx,
+                victim.resource_inventory_id,
+                &capacity_type,
+                -amount,
+            )
+            .await?;
+        }
+
+        preempted.push(ReservationPreemption {
+            preempted_reservation_id: victim.id,
+            preempting_reservation_id: id,
+            resource_inventory_id: victim.resource_inventory_id,
+            reserved_by_party_id: victim.reserved_by_party_id,
+            preempted_priority: victim.priority,
+            preempting_priority: request.priority,
+            emergency: request.emergency,
+            preempted_at: now,
+        });
+    }
+
+    for (capacity_type, amount) in &requirements {
+        adjust_reserved_capacity(
+            &mut tx,
+            request.resource_inventory_id,
+            capacity_type,
+            *amount,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    // Evicted holders are only told once the eviction is committed
+    for preemption in &preempted {
+        let data = serde_json::to_value(preemption)
+            .map_err(|e| ResourceManagementError::Serialization(e.to_string()))?;
+        let event = EventEnvelope::new(
+            "ReservationPreempted".to_string(),
+            "resource-management".to_string(),
+            data,
+        );
+        if let Err(e) = publisher.publish(topics::RESOURCE_EVENTS, event).await {
+            log::warn!(
+                "Failed to publish preemption event for reservation {}: {}",
+                preemption.preempted_reservation_id,
+                e
+            );
         }
     }
 
+    Ok(PreemptiveReservationOutcome {
+        reservation: get_reservation_by_id(pool, id).await?,
+        preempted,
+    })
+}
+
+/// Numeric capacity requirements of a reservation by capacity type
+fn capacity_requirements(requirements: &serde_json::Value) -> Vec<(String, f64)> {
+    requirements
+        .as_object()
+        .map(|reqs| {
+            reqs.iter()
+                .filter_map(|(capacity_type, value)| {
+                    value.as_f64().map(|amount| (capacity_type.clone(), amount))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Admission check against capacities locked by the calling transaction
+async fn check_locked_admission(
+    pool: &Pool<Postgres>,
+    capacities: &[ResourceCapacity],
+    capacity_type: &str,
+    amount: f64,
+    freed: f64,
+    request: &CreateResourceReservationRequest,
+) -> ResourceManagementResult<()> {
+    let ceiling = get_admission_ceiling(pool, capacity_type).await?;
+    admit(
+        capacities
+            .iter()
+            .find(|capacity| capacity.capacity_type == capacity_type),
+        &ceiling,
+        capacity_type,
+        amount,
+        freed,
+        request.priority,
+        request.emergency,
+    )
+}
+
+/// Get a reservation and lock it for the rest of the transaction
+async fn lock_reservation(
+    conn: &mut PgConnection,
+    reservation_id: Uuid,
+) -> ResourceManagementResult<ResourceReservation> {
+    let row = sqlx::query(
+        "SELECT id, resource_inventory_id, reservation_name, description, reservation_status,
+         start_time, end_time, resource_order_id, service_order_id, reserved_by_party_id,
+         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason,
+         priority, preemptible, emergency, preempted_by_reservation_id, network_slice_id
+         FROM resource_reservations
+         WHERE id = $1
+         FOR UPDATE",
+    )
+    .bind(reservation_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    row.as_ref().map(row_to_reservation).ok_or_else(|| {
+        ResourceManagementError::ReservationNotFound(format!(
+            "Reservation with id {} not found",
+            reservation_id
+        ))
+    })
+}
+
+/// Insert a new pending reservation row
+async fn insert_reservation(
+    executor: impl PgExecutor<'_>,
+    request: &CreateResourceReservationRequest,
+) -> ResourceManagementResult<Uuid> {
     let id = Uuid::new_v4();
     let now = Utc::now();
 
@@ -110,8 +322,9 @@ pub async fn create_resource_reservation(
         "INSERT INTO resource_reservations 
          (id, resource_inventory_id, reservation_name, description, reservation_status,
           start_time, end_time, resource_order_id, service_order_id, reserved_by_party_id,
-          capacity_requirements, created_at, updated_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+          capacity_requirements, created_at, updated_at, priority, preemptible, emergency,
+          network_slice_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
     )
     .bind(id)
     .bind(request.resource_inventory_id)
@@ -126,19 +339,33 @@ pub async fn create_resource_reservation(
     .bind(&request.capacity_requirements)
     .bind(now)
     .bind(now)
-    .execute(pool)
+    .bind(request.priority)
+    .bind(request.preemptible)
+    .bind(request.emergency)
+    .bind(request.network_slice_id)
+    .execute(executor)
     .await?;
 
-    get_reservation_by_id(pool, id).await
+    Ok(id)
 }
 
 /// Update resource reservation
+///
+/// A reservation that ends (completed, cancelled or preempted) gives its
+/// capacity back to the resource; an ended reservation cannot be reopened.
 pub async fn update_resource_reservation(
     pool: &Pool<Postgres>,
     reservation_id: Uuid,
     request: UpdateResourceReservationRequest,
 ) -> ResourceManagementResult<ResourceReservation> {
-    let current = get_reservation_by_id(pool, reservation_id).await?;
+    // Capacities are locked before the reservation, in the same order as
+    // reservations are created, so concurrent updates cannot deadlock
+    let resource_inventory_id = get_reservation_by_id(pool, reservation_id)
+        .await?
+        .resource_inventory_id;
+    let mut tx = pool.begin().await?;
+    lock_resource_capacities(&mut tx, resource_inventory_id).await?;
+    let current = lock_reservation(&mut tx, reservation_id).await?;
 
     let start_time = request.start_time.unwrap_or(current.start_time);
     let end_time = request.end_time.unwrap_or(current.end_time);
@@ -151,7 +378,7 @@ pub async fn update_resource_reservation(
     // Check for conflicts if time range changed
     if request.start_time.is_some() || request.end_time.is_some() {
         let overlapping = check_reservation_conflicts(
-            pool,
+            &mut *tx,
             current.resource_inventory_id,
             start_time,
             end_time,
@@ -169,6 +396,15 @@ pub async fn update_resource_reservation(
 
     let current_status = current.reservation_status.clone();
     let status = request.reservation_status.unwrap_or(current_status.clone());
+    let was_ended = ENDED_STATUSES.contains(&current_status.as_str());
+    let ends = !was_ended && ENDED_STATUSES.contains(&status.as_str());
+    if was_ended && status != current_status {
+        return Err(ResourceManagementError::ReservationConflict(format!(
+            "Reservation {} is {} and cannot be reopened",
+            reservation_id, current_status
+        )));
+    }
+
     let now = Utc::now();
     let confirmed_at = if status == "CONFIRMED" && current_status != "CONFIRMED" {
         Some(now)
@@ -196,15 +432,28 @@ pub async fn update_resource_reservation(
     .bind(cancelled_at)
     .bind(now)
     .bind(reservation_id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    if ends {
+        for (capacity_type, amount) in capacity_requirements(&current.capacity_requirements) {
+            adjust_reserved_capacity(
+                &mut tx,
+                current.resource_inventory_id,
+                &capacity_type,
+                -amount,
+            )
+            .await?;
+        }
+    }
+    tx.commit().await?;
+
     get_reservation_by_id(pool, reservation_id).await
 }
 
 /// Check for reservation conflicts
 async fn check_reservation_conflicts(
-    pool: &Pool<Postgres>,
+    executor: impl PgExecutor<'_>,
     resource_inventory_id: Uuid,
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
@@ -213,7 +462,7 @@ async fn check_reservation_conflicts(
     let mut query = sqlx::query(
         "SELECT id FROM resource_reservations
          WHERE resource_inventory_id = $1
-         AND reservation_status NOT IN ('COMPLETED', 'CANCELLED')
+         AND reservation_status NOT IN ('COMPLETED', 'CANCELLED', 'PREEMPTED')
          AND (
              (start_time <= $2 AND end_time > $2) OR
              (start_time < $3 AND end_time >= $3) OR
@@ -230,7 +479,7 @@ async fn check_reservation_conflicts(
         // For now, we'll filter in application code
     }
 
-    let rows = query.fetch_all(pool).await?;
+    let rows = query.fetch_all(executor).await?;
 
     let mut conflicts = Vec::new();
     for row in rows {
@@ -253,7 +502,8 @@ pub async fn get_active_reservations(
     let rows = sqlx::query(
         "SELECT id, resource_inventory_id, reservation_name, description, reservation_status,
          start_time, end_time, resource_order_id, service_order_id, reserved_by_party_id,
-         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason
+         capacity_requirements, created_at, updated_at, confirmed_at, cancelled_at, cancellation_reason,
+         priority, preemptible, emergency, preempted_by_reservation_id, network_slice_id
          FROM resource_reservations
          WHERE resource_inventory_id = $1
          AND reservation_status IN ('CONFIRMED', 'ACTIVE')
@@ -288,5 +538,10 @@ fn row_to_reservation(row: &sqlx::postgres::PgRow) -> ResourceReservation {
         confirmed_at: row.get("confirmed_at"),
         cancelled_at: row.get("cancelled_at"),
         cancellation_reason: row.get("cancellation_reason"),
+        priority: row.get("priority"),
+        preemptible: row.get("preemptible"),
+        emergency: row.get("emergency"),
+        preempted_by_reservation_id: row.get("preempted_by_reservation_id"),
+        network_slice_id: row.get("network_slice_id"),
     }
 }
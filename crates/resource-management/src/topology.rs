@@ -1,9 +1,14 @@
 //! Network Topology Management
 
 use crate::error::{ResourceManagementError, ResourceManagementResult};
-use crate::models::{CreateNetworkTopologyRequest, NetworkTopology, UpdateNetworkTopologyRequest};
+use crate::models::{
+    CreateNetworkTopologyRequest, CrossRegionLink, NetworkTopology, PathPreference, RegionSubgraph,
+    TopologyNodeRegion, UpdateNetworkTopologyRequest,
+};
 use chrono::Utc;
 use sqlx::{Pool, Postgres, Row};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use uuid::Uuid;
 
 /// Get all topology connections for a resource
@@ -214,6 +219,234 @@ pub async fn get_connected_resources(
         .collect())
 }
 
+/// Label a topology node with its region
+pub async fn set_node_region(
+    pool: &Pool<Postgres>,
+    resource_inventory_id: Uuid,
+    region: &str,
+) -> ResourceManagementResult<TopologyNodeRegion> {
+    if region.trim().is_empty() {
+        return Err(ResourceManagementError::InvalidTopologyRelationship(
+            "Region label cannot be empty".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+
+    sqlx::query(
+        "INSERT INTO topology_node_regions (resource_inventory_id, region, created_at, updated_at)
+         VALUES ($1, $2, $3, $3)
+         ON CONFLICT (resource_inventory_id) DO UPDATE
+         SET region = EXCLUDED.region, updated_at = EXCLUDED.updated_at",
+    )
+    .bind(resource_inventory_id)
+    .bind(region)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(TopologyNodeRegion {
+        resource_inventory_id,
+        region: region.to_string(),
+        updated_at: now,
+    })
+}
+
+/// Get the region label of a topology node, if it has one
+pub async fn get_node_region(
+    pool: &Pool<Postgres>,
+    resource_inventory_id: Uuid,
+) -> ResourceManagementResult<Option<String>> {
+    let row =
+        sqlx::query("SELECT region FROM topology_node_regions WHERE resource_inventory_id = $1")
+            .bind(resource_inventory_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(row.map(|row| row.get("region")))
+}
+
+/// Get the nodes of a region and the links between them
+pub async fn get_region_subgraph(
+    pool: &Pool<Postgres>,
+    region: &str,
+) -> ResourceManagementResult<RegionSubgraph> {
+    let nodes = sqlx::query(
+        "SELECT resource_inventory_id FROM topology_node_regions
+         WHERE region = $1
+         ORDER BY resource_inventory_id",
+    )
+    .bind(region)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| row.get("resource_inventory_id"))
+    .collect();
+
+    let rows = sqlx::query(
+        "SELECT t.id, t.source_resource_id, t.target_resource_id, t.connection_type,
+         t.relationship_type, t.connection_status, t.bandwidth_mbps, t.latency_ms,
+         t.description, t.created_at, t.updated_at
+         FROM network_topology t
+         JOIN topology_node_regions s ON s.resource_inventory_id = t.source_resource_id
+         JOIN topology_node_regions d ON d.resource_inventory_id = t.target_resource_id
+         WHERE s.region = $1 AND d.region = $1
+         ORDER BY t.created_at",
+    )
+    .bind(region)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(RegionSubgraph {
+        region: region.to_string(),
+        nodes,
+        links: rows.iter().map(row_to_topology).collect(),
+    })
+}
+
+/// Get all links between nodes of different regions
+///
+/// Links touching a node without a region label are not reported.
+pub async fn get_cross_region_links(
+    pool: &Pool<Postgres>,
+) -> ResourceManagementResult<Vec<CrossRegionLink>> {
+    let rows = sqlx::query(
+        "SELECT t.id, t.source_resource_id, t.target_resource_id, t.connection_type,
+         t.relationship_type, t.connection_status, t.bandwidth_mbps, t.latency_ms,
+         t.description, t.created_at, t.updated_at,
+         s.region AS source_region, d.region AS target_region
+         FROM network_topology t
+         JOIN topology_node_regions s ON s.resource_inventory_id = t.source_resource_id
+         JOIN topology_node_regions d ON d.resource_inventory_id = t.target_resource_id
+         WHERE s.region <> d.region
+         ORDER BY s.region, d.region, t.created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| CrossRegionLink {
+            link: row_to_topology(row),
+            source_region: row.get("source_region"),
+            target_region: row.get("target_region"),
+        })
+        .collect())
+}
+
+/// Find a multi-hop path of active links between two resources
+///
+/// With `PathPreference::IntraRegion` the path crossing the fewest region
+/// boundaries is chosen, even if it takes more hops. Returns an empty path if
+/// the resources are not connected.
+pub async fn find_topology_path(
+    pool: &Pool<Postgres>,
+    source_resource_id: Uuid,
+    target_resource_id: Uuid,
+    preference: PathPreference,
+) -> ResourceManagementResult<Vec<NetworkTopology>> {
+    let links: Vec<NetworkTopology> = sqlx::query(
+        "SELECT id, source_resource_id, target_resource_id, connection_type, relationship_type,
+         connection_status, bandwidth_mbps, latency_ms, description, created_at, updated_at
+         FROM network_topology
+         WHERE connection_status = 'ACTIVE'",
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(row_to_topology)
+    .collect();
+
+    let regions: HashMap<Uuid, String> = if preference == PathPreference::IntraRegion {
+        sqlx::query("SELECT resource_inventory_id, region FROM topology_node_regions")
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(|row| (row.get("resource_inventory_id"), row.get("region")))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    Ok(shortest_path(
+        &links,
+        &regions,
+        source_resource_id,
+        target_resource_id,
+        preference,
+    )
+    .unwrap_or_default())
+}
+
+/// Whether a link joins two nodes labelled with different regions
+pub fn is_cross_region(link: &NetworkTopology, regions: &HashMap<Uuid, String>) -> bool {
+    match (
+        regions.get(&link.source_resource_id),
+        regions.get(&link.target_resource_id),
+    ) {
+        (Some(source), Some(target)) => source != target,
+        _ => false,
+    }
+}
+
+/// Shortest path over undirected links, by (cross-region links, hops) when
+/// preferring intra-region paths and by hops otherwise
+pub fn shortest_path(
+    links: &[NetworkTopology],
+    regions: &HashMap<Uuid, String>,
+    source: Uuid,
+    target: Uuid,
+    preference: PathPreference,
+) -> Option<Vec<NetworkTopology>> {
+    let mut adjacency: HashMap<Uuid, Vec<(Uuid, usize)>> = HashMap::new();
+    for (index, link) in links.iter().enumerate() {
+        adjacency
+            .entry(link.source_resource_id)
+            .or_default()
+            .push((link.target_resource_id, index));
+        adjacency
+            .entry(link.target_resource_id)
+            .or_default()
+            .push((link.source_resource_id, index));
+    }
+
+    let mut best: HashMap<Uuid, (u32, u32)> = HashMap::from([(source, (0, 0))]);
+    let mut previous: HashMap<Uuid, (Uuid, usize)> = HashMap::new();
+    let mut queue = BinaryHeap::from([Reverse(((0u32, 0u32), source))]);
+
+    while let Some(Reverse((cost, node))) = queue.pop() {
+        if node == target {
+            break;
+        }
+        if best.get(&node).is_some_and(|known| *known < cost) {
+            continue;
+        }
+        for &(next, index) in adjacency.get(&node).into_iter().flatten() {
+            let crossing = preference == PathPreference::IntraRegion
+                && is_cross_region(&links[index], regions);
+            let next_cost = (cost.0 + u32::from(crossing), cost.1 + 1);
+            if best.get(&next).is_none_or(|known| next_cost < *known) {
+                best.insert(next, next_cost);
+                previous.insert(next, (node, index));
+                queue.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+
+    if !best.contains_key(&target) {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let mut node = target;
+    while let Some(&(from, index)) = previous.get(&node) {
+        path.push(links[index].clone());
+        node = from;
+    }
+    path.reverse();
+    Some(path)
+}
+
 /// Helper to convert database row to NetworkTopology
 fn row_to_topology(row: &sqlx::postgres::PgRow) -> NetworkTopology {
     NetworkTopology {
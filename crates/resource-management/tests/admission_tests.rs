@@ -0,0 +1,123 @@
+//! Tests for reservation admission and capacity accounting
+
+#[cfg(test)]
+mod tests {
+    use bss_oss_resource_management::{
+        admit, create_resource_capacity, create_resource_reservation, get_resource_capacities,
+        AdmissionCeiling, CreateResourceCapacityRequest, CreateResourceReservationRequest,
+        ResourceCapacity, ResourceManagementError,
+    };
+    use chrono::{Duration, Utc};
+    use test_utils::database::create_test_pool;
+    use uuid::Uuid;
+
+    fn pool_of(total: f64, reserved: f64) -> ResourceCapacity {
+        ResourceCapacity {
+            id: Uuid::new_v4(),
+            resource_inventory_id: Uuid::new_v4(),
+            capacity_type: "CPU".to_string(),
+            total_capacity: total,
+            used_capacity: 0.0,
+            reserved_capacity: reserved,
+            available_capacity: total - reserved,
+            unit: "cores".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn ceiling() -> AdmissionCeiling {
+        AdmissionCeiling {
+            capacity_type: "CPU".to_string(),
+            soft_ceiling_percentage: 80.0,
+            high_priority_threshold: 100,
+        }
+    }
+
+    #[test]
+    fn test_soft_ceiling_admits_only_high_priority() {
+        let capacity = pool_of(100.0, 70.0);
+
+        assert!(matches!(
+            admit(Some(&capacity), &ceiling(), "CPU", 20.0, 0.0, 10, false),
+            Err(ResourceManagementError::SoftLimitReached(_))
+        ));
+        assert!(admit(Some(&capacity), &ceiling(), "CPU", 20.0, 0.0, 100, false).is_ok());
+        assert!(admit(Some(&capacity), &ceiling(), "CPU", 20.0, 0.0, 0, true).is_ok());
+    }
+
+    #[test]
+    fn test_full_pool_rejects_even_emergencies() {
+        let capacity = pool_of(100.0, 100.0);
+
+        assert!(matches!(
+            admit(Some(&capacity), &ceiling(), "CPU", 1.0, 0.0, 0, true),
+            Err(ResourceManagementError::InsufficientCapacity(_))
+        ));
+        // Capacity freed by preempted reservations counts
+        assert!(admit(Some(&capacity), &ceiling(), "CPU", 1.0, 1.0, 0, true).is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_reservations_fill_the_pool() {
+        let pool = create_test_pool()
+            .await
+            .expect("Failed to create test pool");
+
+        let resource_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO resource_inventories (id, name) VALUES ($1, $2)")
+            .bind(resource_id)
+            .bind("admission-test-router")
+            .execute(&pool)
+            .await
+            .expect("Failed to create resource");
+        create_resource_capacity(
+            &pool,
+            CreateResourceCapacityRequest {
+                resource_inventory_id: resource_id,
+                capacity_type: "CPU".to_string(),
+                total_capacity: 100.0,
+                unit: "cores".to_string(),
+            },
+        )
+        .await
+        .expect("Failed to create capacity");
+
+        // Back-to-back windows, so only capacity can stop the third reservation
+        let reserve = |slot: i64, cores: f64| {
+            let start = Utc::now() + Duration::hours(slot);
+            create_resource_reservation(
+                &pool,
+                CreateResourceReservationRequest {
+                    resource_inventory_id: resource_id,
+                    reservation_name: format!("slot-{}", slot),
+                    description: None,
+                    start_time: start,
+                    end_time: start + Duration::hours(1),
+                    resource_order_id: None,
+                    service_order_id: None,
+                    reserved_by_party_id: None,
+                    capacity_requirements: serde_json::json!({ "CPU": cores }),
+                    priority: 0,
+                    preemptible: true,
+                    emergency: true,
+                    network_slice_id: None,
+                },
+            )
+        };
+
+        reserve(1, 60.0).await.expect("First reservation admitted");
+        reserve(2, 40.0).await.expect("Second reservation admitted");
+
+        let capacity = get_resource_capacities(&pool, resource_id)
+            .await
+            .expect("Failed to read capacity");
+        assert_eq!(capacity[0].reserved_capacity, 100.0);
+
+        assert!(matches!(
+            reserve(3, 1.0).await,
+            Err(ResourceManagementError::InsufficientCapacity(_))
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Charging Accounts
+//!
+//! A customer's charging account decides whether charges are paid from a
+//! prepaid balance or invoiced. Hybrid accounts split by charge category, e.g.
+//! a postpaid subscription with prepaid overage: an exhausted prepaid balance
+//! rejects prepaid charges only, while postpaid charges are invoiced as usual.
+//! Customers without an account are postpaid.
+
+use crate::error::RevenueError;
+use crate::models::{AccountType, ChargeCategory, ChargingAccount, Money};
+use crate::settlement::round_to_currency;
+use log::info;
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Charging account manager
+pub struct ChargingAccountManager {
+    pool: PgPool,
+}
+
+impl ChargingAccountManager {
+    /// Create a new charging account manager
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create or reconfigure a customer's account, keeping its balance
+    ///
+    /// The balance currency can only change while the balance is empty.
+    pub async fn set_account(
+        &self,
+        customer_id: Uuid,
+        account_type: AccountType,
+        prepaid_categories: Vec<ChargeCategory>,
+        currency: String,
+    ) -> Result<ChargingAccount, RevenueError> {
+        if account_type == AccountType::Hybrid && prepaid_categories.is_empty() {
+            return Err(RevenueError::Validation(
+                "Hybrid accounts need at least one prepaid charge category".to_string(),
+            ));
+        }
+
+        let updated = sqlx::query(
+            "INSERT INTO charging_accounts (customer_id, account_type, prepaid_categories,
+             prepaid_balance_unit)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (customer_id) DO UPDATE SET
+             account_type = EXCLUDED.account_type,
+             prepaid_categories = EXCLUDED.prepaid_categories,
+             prepaid_balance_unit = EXCLUDED.prepaid_balance_unit,
+             updated_at = CURRENT_TIMESTAMP
+             WHERE charging_accounts.prepaid_balance_unit = EXCLUDED.prepaid_balance_unit
+             OR charging_accounts.prepaid_balance_value = 0",
+        )
+        .bind(customer_id)
+        .bind(account_type_to_string(&account_type))
+        .bind(
+            prepaid_categories
+                .iter()
+                .map(charge_category_to_string)
+                .collect::<Vec<_>>(),
+        )
+        .bind(&currency)
+        .execute(&self.pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(RevenueError::Validation(format!(
+                "Prepaid balance of customer {} is not empty and cannot change currency to {}",
+                customer_id, currency
+            )));
+        }
+
+        info!(
+            "Configured {} charging account for customer {}",
+            account_type_to_string(&account_type),
+            customer_id
+        );
+        self.get_account(customer_id)
+            .await?
+            .ok_or_else(|| RevenueError::NotFound(format!("Charging account {}", customer_id)))
+    }
+
+    /// Get a customer's account, if configured
+    pub async fn get_account(
+        &self,
+        customer_id: Uuid,
+    ) -> Result<Option<ChargingAccount>, RevenueError> {
+        let row = sqlx::query_as::<_, ChargingAccountRow>(
+            "SELECT customer_id, account_type, prepaid_categories,
+             prepaid_balance_value::FLOAT8 AS prepaid_balance_value, prepaid_balance_unit
+             FROM charging_accounts WHERE customer_id = $1",
+        )
+        .bind(customer_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| ChargingAccount {
+            customer_id: r.customer_id,
+            account_type: string_to_account_type(&r.account_type),
+            prepaid_categories: r
+                .prepaid_categories
+                .iter()
+                .filter_map(|c| string_to_charge_category(c))
+                .collect(),
+            prepaid_balance: Money {
+                value: r.prepaid_balance_value,
+                unit: r.prepaid_balance_unit,
+            },
+        }))
+    }
+
+    /// Add credit to a customer's prepaid balance, returning the new balance
+    pub async fn top_up(&self, customer_id: Uuid, amount: Money) -> Result<Money, RevenueError> {
+        let value = round_to_currency(amount.value, &amount.unit);
+        if value <= 0.0 {
+            return Err(RevenueError::Validation(format!(
+                "Top-up amount must be positive, got {} {}",
+                amount.value, amount.unit
+            )));
+        }
+
+        let balance: Option<f64> = sqlx::query_scalar(
+            "UPDATE charging_accounts
+             SET prepaid_balance_value = prepaid_balance_value + $2,
+             updated_at = CURRENT_TIMESTAMP
+             WHERE customer_id = $1 AND prepaid_balance_unit = $3
+             RETURNING prepaid_balance_value::FLOAT8",
+        )
+        .bind(customer_id)
+        .bind(value)
+        .bind(&amount.unit)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let balance = balance.ok_or_else(|| {
+            RevenueError::NotFound(format!(
+                "Charging account {} with a {} balance",
+                customer_id, amount.unit
+            ))
+        })?;
+        info!(
+            "Topped up prepaid balance of customer {} by {} {}",
+            customer_id, value, amount.unit
+        );
+        Ok(Money {
+            value: balance,
+            unit: amount.unit,
+        })
+    }
+}
+
+/// Deduct a charge from a customer's prepaid balance, returning the balance
+/// left
+///
+/// Fails without deducting anything if the balance does not cover the charge.
+pub(crate) async fn debit_prepaid(
+    conn: &mut PgConnection,
+    customer_id: Uuid,
+    amount: &Money,
+) -> Result<Money, RevenueError> {
+    let balance: Option<f64> = sqlx::query_scalar(
+        "UPDATE charging_accounts
+         SET prepaid_balance_value = prepaid_balance_value - $2,
+         updated_at = CURRENT_TIMESTAMP
+         WHERE customer_id = $1 AND prepaid_balance_unit = $3 AND prepaid_balance_value >= $2
+         RETURNING prepaid_balance_value::FLOAT8",
+    )
+    .bind(customer_id)
+    .bind(amount.value)
+    .bind(&amount.unit)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    balance
+        .map(|value| Money {
+            value,
+            unit: amount.unit.clone(),
+        })
+        .ok_or_else(|| {
+            RevenueError::InsufficientBalance(format!(
+                "customer {} cannot pay {} {} from the prepaid balance",
+                customer_id, amount.value, amount.unit
+            ))
+        })
+}
+
+/// Return an earlier prepaid charge to a customer's balance, returning the
+/// balance after the refund
+pub(crate) async fn credit_prepaid(
+    conn: &mut PgConnection,
+    customer_id: Uuid,
+    amount: &Money,
+) -> Result<Money, RevenueError> {
+    let balance: Option<f64> = sqlx::query_scalar(
+        "UPDATE charging_accounts
+         SET prepaid_balance_value = prepaid_balance_value + $2,
+         updated_at = CURRENT_TIMESTAMP
+         WHERE customer_id = $1 AND prepaid_balance_unit = $3
+         RETURNING prepaid_balance_value::FLOAT8",
+    )
+    .bind(customer_id)
+    .bind(amount.value)
+    .bind(&amount.unit)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    balance
+        .map(|value| Money {
+            value,
+            unit: amount.unit.clone(),
+        })
+        .ok_or_else(|| {
+            RevenueError::NotFound(format!(
+                "Charging account {} with a {} balance to refund {} {} to",
+                customer_id, amount.unit, amount.value, amount.unit
+            ))
+        })
+}
+
+fn account_type_to_string(account_type: &AccountType) -> String {
+    match account_type {
+        AccountType::Prepaid => "PREPAID".to_string(),
+        AccountType::Postpaid => "POSTPAID".to_string(),
+        AccountType::Hybrid => "HYBRID".to_string(),
+    }
+}
+
+fn string_to_account_type(s: &str) -> AccountType {
+    match s {
+        "PREPAID" => AccountType::Prepaid,
+        "HYBRID" => AccountType::Hybrid,
+        _ => AccountType::Postpaid,
+    }
+}
+
+fn charge_category_to_string(category: &ChargeCategory) -> String {
+    match category {
+        ChargeCategory::Recurring => "RECURRING".to_string(),
+        ChargeCategory::Usage => "USAGE".to_string(),
+        ChargeCategory::Overage => "OVERAGE".to_string(),
+        ChargeCategory::OneTime => "ONE_TIME".to_string(),
+    }
+}
+
+fn string_to_charge_category(s: &str) -> Option<ChargeCategory> {
+    match s {
+        "RECURRING" => Some(ChargeCategory::Recurring),
+        "USAGE" => Some(ChargeCategory::Usage),
+        "OVERAGE" => Some(ChargeCategory::Overage),
+        "ONE_TIME" => Some(ChargeCategory::OneTime),
+        _ => None,
+    }
+}
+
+/// Internal row structure
+#[derive(Debug, FromRow)]
+struct ChargingAccountRow {
+    customer_id: Uuid,
+    account_type: String,
+    prepaid_categories: Vec<String>,
+    prepaid_balance_value: f64,
+    prepaid_balance_unit: String,
+}
@@ -3,13 +3,16 @@
 //! Manages billing cycles and generates bills automatically
 
 use crate::error::RevenueError;
-use crate::models::{BillingCycle, CycleStatus, CycleType};
+use crate::models::{BillingCycle, CycleStatus, CycleType, TaxJurisdiction};
 use crate::rating::RatingEngine;
+use crate::tax::{self, TableTaxCalculator, TaxCalculator};
 use chrono::{DateTime, Duration, Utc};
 use log::{info, warn};
 use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
 use tmf678_billing::{
-    CreateBillItemRequest, CreateCustomerBillRequest, CreateRelatedPartyRequest, Money as BillMoney,
+    BillItemCategory, CreateBillItemRequest, CreateCustomerBillRequest, CreateRelatedPartyRequest,
+    Money as BillMoney,
 };
 use uuid::Uuid;
 
@@ -17,6 +20,7 @@ use uuid::Uuid;
 pub struct BillingCycleManager {
     pool: PgPool,
     rating_engine: RatingEngine,
+    tax_calculator: Arc<dyn TaxCalculator>,
 }
 
 impl BillingCycleManager {
@@ -26,23 +30,34 @@ impl BillingCycleManager {
         Self {
             pool,
             rating_engine: RatingEngine::new(pool_clone),
+            tax_calculator: Arc::new(TableTaxCalculator::default()),
         }
     }
 
+    /// Use a different tax calculator
+    pub fn with_tax_calculator(mut self, tax_calculator: Arc<dyn TaxCalculator>) -> Self {
+        self.tax_calculator = tax_calculator;
+        self
+    }
+
     /// Create a new billing cycle for a customer
+    ///
+    /// Bill items of the cycle are taxed for `tax_jurisdiction`; without one
+    /// the bill carries no tax items.
     pub async fn create_billing_cycle(
         &self,
         customer_id: Uuid,
         cycle_type: CycleType,
         start_date: DateTime<Utc>,
+        tax_jurisdiction: Option<TaxJurisdiction>,
     ) -> Result<BillingCycle, RevenueError> {
         let (end_date, due_date) = self.calculate_cycle_dates(&cycle_type, start_date)?;
 
         let cycle_id = Uuid::new_v4();
         sqlx::query(
             "INSERT INTO billing_cycles (id, customer_id, cycle_type, start_date, end_date, 
-             due_date, status)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+             due_date, status, tax_country, tax_region)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
         )
         .bind(cycle_id)
         .bind(customer_id)
@@ -51,6 +66,8 @@ impl BillingCycleManager {
         .bind(end_date)
         .bind(due_date)
         .bind(cycle_status_to_string(&CycleStatus::Open))
+        .bind(tax_jurisdiction.as_ref().map(|j| j.country.clone()))
+        .bind(tax_jurisdiction.as_ref().and_then(|j| j.region.clone()))
         .execute(&self.pool)
         .await?;
 
@@ -68,6 +85,7 @@ impl BillingCycleManager {
             due_date,
             status: CycleStatus::Open,
             bill_id: None,
+            tax_jurisdiction,
         })
     }
 
@@ -95,8 +113,8 @@ impl BillingCycleManager {
             )
             .await?;
 
-        // Calculate total charges
-        let mut total_amount = 0.0;
+        // Rate usage into bill items, each followed by its taxes; the bill
+        // total is derived from the items
         let mut bill_items = Vec::new();
 
         for usage in aggregated_usage {
@@ -111,22 +129,58 @@ impl BillingCycleManager {
                 )
                 .await?;
 
-            total_amount += rating_result.charge_amount.value;
-
             bill_items.push(CreateBillItemRequest {
                 description: format!(
                     "{} - {} {}",
                     usage.usage_type, usage.total_amount, usage.unit
                 ),
+                category: BillItemCategory::Usage,
                 amount: BillMoney {
                     value: rating_result.charge_amount.value,
-                    unit: rating_result.charge_amount.unit,
+                    unit: rating_result.charge_amount.unit.clone(),
                 },
                 quantity: Some(usage.usage_count as i32),
                 product_offering_id: Some(usage.product_offering_id),
             });
+
+            if let Some(jurisdiction) = &cycle.tax_jurisdiction {
+                let tax_lines = self
+                    .tax_calculator
+                    .calculate(&tax::taxable_item(
+                        &usage.usage_type,
+                        &rating_result.charge_amount,
+                        jurisdiction,
+                    ))
+                    .await?;
+                for line in tax_lines {
+                    bill_items.push(CreateBillItemRequest {
+                        description: format!(
+                            "{} {}% on {}",
+                            line.tax_type.code(),
+                            line.rate_percentage,
+                            usage.usage_type
+                        ),
+                        category: BillItemCategory::Tax,
+                        amount: BillMoney {
+                            value: line.tax_amount.value,
+                            unit: line.tax_amount.unit,
+                        },
+                        quantity: None,
+                        product_offering_id: Some(usage.product_offering_id),
+                    });
+                }
+            }
         }
 
+        // The bill names its customer, falling back to the id for customers
+        // without a TMF629 profile
+        let customer_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM customers WHERE id = $1")
+                .bind(cycle.customer_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        let customer_name = customer_name.unwrap_or_else(|| cycle.customer_id.to_string());
+
         // Create the bill
         let bill_request = CreateCustomerBillRequest {
             name: format!("Bill for cycle {}", cycle.start_date.format("%Y-%m-%d")),
@@ -137,37 +191,52 @@ impl BillingCycleManager {
             version: Some("1.0".to_string()),
             bill_date: Some(Utc::now()),
             due_date: Some(cycle.due_date),
-            total_amount: Some(BillMoney {
-                value: total_amount,
+            total_amount: bill_items.is_empty().then(|| BillMoney {
+                value: 0.0,
                 unit: "USD".to_string(),
             }),
             tax_included: false,
             bill_item: Some(bill_items),
             related_party: Some(vec![CreateRelatedPartyRequest {
-                name: "Customer".to_string(),
+                id: Some(cycle.customer_id),
+                name: customer_name,
                 role: "Customer".to_string(),
             }]),
         };
 
-        let bill = tmf678_billing::db::create_bill(&self.pool, bill_request)
+        // The bill and the status change commit together, and only while the
+        // cycle is still open, so concurrent closes never bill it twice
+        let mut tx = self.pool.begin().await?;
+        let bill_id = tmf678_billing::db::insert_bill(&mut tx, bill_request)
             .await
             .map_err(|e| RevenueError::BillingCycle(e.to_string()))?;
-
-        // Update billing cycle status
-        let bill_id = bill.base.id;
-        sqlx::query(
+        let closed = sqlx::query(
             "UPDATE billing_cycles SET status = $1, bill_id = $2, updated_at = CURRENT_TIMESTAMP
-             WHERE id = $3",
+             WHERE id = $3 AND status = $4",
         )
         .bind(cycle_status_to_string(&CycleStatus::Billed))
         .bind(bill_id)
         .bind(cycle_id)
-        .execute(&self.pool)
+        .bind(cycle_status_to_string(&CycleStatus::Open))
+        .execute(&mut *tx)
         .await?;
+        if closed.rows_affected() == 0 {
+            return Err(RevenueError::BillingCycle(
+                "Billing cycle is not open".to_string(),
+            ));
+        }
+        tx.commit().await?;
 
+        let bill = tmf678_billing::db::get_bill_by_id(&self.pool, bill_id)
+            .await
+            .map_err(|e| RevenueError::BillingCycle(e.to_string()))?;
+        let total = bill.total_amount.as_ref();
         info!(
-            "Billing cycle {} closed and bill {} created with total: {} USD",
-            cycle_id, bill_id, total_amount
+            "Billing cycle {} closed and bill {} created with total: {} {}",
+            cycle_id,
+            bill_id,
+            total.map_or(0.0, |m| m.value),
+            total.map_or("USD", |m| m.unit.as_str())
         );
 
         Ok(bill_id)
@@ -176,7 +245,8 @@ impl BillingCycleManager {
     /// Get billing cycle by ID
     pub async fn get_billing_cycle(&self, cycle_id: Uuid) -> Result<BillingCycle, RevenueError> {
         let row = sqlx::query_as::<_, BillingCycleRow>(
-            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id
+            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id,
+             tax_country, tax_region
              FROM billing_cycles WHERE id = $1",
         )
         .bind(cycle_id)
@@ -194,6 +264,10 @@ impl BillingCycleManager {
             due_date: r.due_date,
             status: string_to_cycle_status(&r.status),
             bill_id: r.bill_id,
+            tax_jurisdiction: r.tax_country.map(|country| TaxJurisdiction {
+                country,
+                region: r.tax_region,
+            }),
         })
     }
 
@@ -203,7 +277,8 @@ impl BillingCycleManager {
         customer_id: Uuid,
     ) -> Result<Vec<BillingCycle>, RevenueError> {
         let rows = sqlx::query_as::<_, BillingCycleRow>(
-            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id
+            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id,
+             tax_country, tax_region
              FROM billing_cycles WHERE customer_id = $1 ORDER BY start_date DESC",
         )
         .bind(customer_id)
@@ -221,6 +296,10 @@ impl BillingCycleManager {
                 due_date: r.due_date,
                 status: string_to_cycle_status(&r.status),
                 bill_id: r.bill_id,
+                tax_jurisdiction: r.tax_country.map(|country| TaxJurisdiction {
+                    country,
+                    region: r.tax_region,
+                }),
             })
             .collect())
     }
@@ -228,7 +307,8 @@ impl BillingCycleManager {
     /// Process all open billing cycles that are due
     pub async fn process_due_cycles(&self) -> Result<Vec<Uuid>, RevenueError> {
         let cycles = sqlx::query_as::<_, BillingCycleRow>(
-            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id
+            "SELECT id, customer_id, cycle_type, start_date, end_date, due_date, status, bill_id,
+             tax_country, tax_region
              FROM billing_cycles
              WHERE status = 'OPEN' AND end_date <= CURRENT_TIMESTAMP",
         )
@@ -278,7 +358,7 @@ impl BillingCycleManager {
 }
 
 /// Helper functions for cycle type conversion
-fn cycle_type_to_string(cycle_type: &CycleType) -> String {
+pub(crate) fn cycle_type_to_string(cycle_type: &CycleType) -> String {
     match cycle_type {
         CycleType::Monthly => "MONTHLY".to_string(),
         CycleType::Quarterly => "QUARTERLY".to_string(),
@@ -288,7 +368,7 @@ fn cycle_type_to_string(cycle_type: &CycleType) -> String {
     }
 }
 
-fn string_to_cycle_type(s: &str) -> CycleType {
+pub(crate) fn string_to_cycle_type(s: &str) -> CycleType {
     match s {
         "MONTHLY" => CycleType::Monthly,
         "QUARTERLY" => CycleType::Quarterly,
@@ -328,4 +408,6 @@ struct BillingCycleRow {
     due_date: DateTime<Utc>,
     status: String,
     bill_id: Option<Uuid>,
+    tax_country: Option<String>,
+    tax_region: Option<String>,
 }
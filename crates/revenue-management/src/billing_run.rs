@@ -0,0 +1,350 @@
+//! Billing Runs
+//!
+//! A billing run closes every billing cycle due by a cutoff, generating the
+//! bills of all customers in one pass. The outcome of each cycle is recorded
+//! as it completes, so a run that fails or is interrupted is resumed from
+//! where it stopped instead of being restarted: cycles already billed are
+//! skipped and failed ones are retried.
+//!
+//! A running run holds a lease that it renews as cycles complete. Only a run
+//! that failed, finished with failures or whose lease has expired, e.g.
+//! because its process died, can be resumed, so a run is never executed by
+//! two coordinators at once.
+
+use crate::billing_cycle::{cycle_type_to_string, string_to_cycle_type, BillingCycleManager};
+use crate::error::RevenueError;
+use crate::models::{BillingRun, BillingRunOutcome, BillingRunResult, BillingRunStatus, CycleType};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Billing run configuration
+#[derive(Debug, Clone)]
+pub struct BillingRunConfig {
+    /// Maximum number of cycles billed at the same time
+    pub max_concurrency: usize,
+    /// How long a running run may go without progress before it can be resumed
+    pub lease: Duration,
+}
+
+impl Default for BillingRunConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            lease: Duration::minutes(15),
+        }
+    }
+}
+
+/// Billing run coordinator
+pub struct BillingRunCoordinator {
+    pool: PgPool,
+    cycle_manager: BillingCycleManager,
+    config: BillingRunConfig,
+}
+
+impl BillingRunCoordinator {
+    /// Create a new billing run coordinator
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            cycle_manager: BillingCycleManager::new(pool.clone()),
+            pool,
+            config: BillingRunConfig::default(),
+        }
+    }
+
+    /// Bill cycles with a different cycle manager
+    pub fn with_cycle_manager(mut self, cycle_manager: BillingCycleManager) -> Self {
+        self.cycle_manager = cycle_manager;
+        self
+    }
+
+    /// Use a different configuration
+    pub fn with_config(mut self, config: BillingRunConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Start a run billing all open cycles ending at or before `cutoff`
+    ///
+    /// Customer failures do not fail the run; they are reported in its
+    /// results.
+    pub async fn start_run(
+        &self,
+        cycle_type: Option<CycleType>,
+        cutoff: DateTime<Utc>,
+    ) -> Result<BillingRun, RevenueError> {
+        let run_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO billing_runs (id, cycle_type, cutoff, status, lease_expires_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(run_id)
+        .bind(cycle_type.as_ref().map(cycle_type_to_string))
+        .bind(cutoff)
+        .bind(run_status_to_string(&BillingRunStatus::Running))
+        .bind(Utc::now() + self.config.lease)
+        .execute(&self.pool)
+        .await?;
+
+        info!(
+            "Started billing run {} for cycles due by {}",
+            run_id, cutoff
+        );
+        self.execute_or_fail(run_id).await
+    }
+
+    /// Continue a run, billing its cycles that have not been billed yet
+    ///
+    /// Cycles that failed are retried. Resuming a completed run finds nothing
+    /// left to bill. A run that is still running under an unexpired lease
+    /// cannot be resumed.
+    pub async fn resume_run(&self, run_id: Uuid) -> Result<BillingRun, RevenueError> {
+        let run = self.get_run(run_id).await?;
+        if run.status == BillingRunStatus::Completed {
+            return Ok(run);
+        }
+
+        // Take over the run only if nobody else holds it
+        let claimed = sqlx::query(
+            "UPDATE billing_runs SET status = $1, completed_at = NULL, lease_expires_at = $2
+             WHERE id = $3 AND (status IN ($4, $5)
+             OR (status = $1 AND (lease_expires_at IS NULL OR lease_expires_at < CURRENT_TIMESTAMP)))",
+        )
+        .bind(run_status_to_string(&BillingRunStatus::Running))
+        .bind(Utc::now() + self.config.lease)
+        .bind(run_id)
+        .bind(run_status_to_string(&BillingRunStatus::Failed))
+        .bind(run_status_to_string(&BillingRunStatus::CompletedWithFailures))
+        .execute(&self.pool)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            return Err(RevenueError::Validation(format!(
+                "Billing run {} is running and its lease has not expired",
+                run_id
+            )));
+        }
+
+        info!("Resuming billing run {}", run_id);
+        self.execute_or_fail(run_id).await
+    }
+
+    /// Get a billing run with the outcome of each of its cycles
+    pub async fn get_run(&self, run_id: Uuid) -> Result<BillingRun, RevenueError> {
+        let run = sqlx::query_as::<_, BillingRunRow>(
+            "SELECT id, cycle_type, cutoff, status, started_at, completed_at
+             FROM billing_runs WHERE id = $1",
+        )
+        .bind(run_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| RevenueError::NotFound(format!("Billing run {}", run_id)))?;
+
+        let results = sqlx::query_as::<_, BillingRunResultRow>(
+            "SELECT customer_id, cycle_id, outcome, bill_id, error, processed_at
+             FROM billing_run_results WHERE run_id = $1 ORDER BY processed_at",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(BillingRun {
+            id: run.id,
+            cycle_type: run.cycle_type.as_deref().map(string_to_cycle_type),
+            cutoff: run.cutoff,
+            status: string_to_run_status(&run.status),
+            started_at: run.started_at,
+            completed_at: run.completed_at,
+            results: results
+                .into_iter()
+                .map(|r| BillingRunResult {
+                    customer_id: r.customer_id,
+                    cycle_id: r.cycle_id,
+                    outcome: if r.outcome == "SUCCEEDED" {
+                        BillingRunOutcome::Succeeded
+                    } else {
+                        BillingRunOutcome::Failed
+                    },
+                    bill_id: r.bill_id,
+                    error: r.error,
+                    processed_at: r.processed_at,
+                })
+                .collect(),
+        })
+    }
+
+    /// Execute a run, marking it failed if it stops on an error
+    async fn execute_or_fail(&self, run_id: Uuid) -> Result<BillingRun, RevenueError> {
+        let result = self.execute(run_id).await;
+        if let Err(e) = &result {
+            warn!("Billing run {} failed: {}", run_id, e);
+            if let Err(e) = sqlx::query("UPDATE billing_runs SET status = $1 WHERE id = $2")
+                .bind(run_status_to_string(&BillingRunStatus::Failed))
+                .bind(run_id)
+                .execute(&self.pool)
+                .await
+            {
+                warn!("Failed to mark billing run {} as failed: {}", run_id, e);
+            }
+        }
+        result
+    }
+
+    /// Bill the pending cycles of a run and complete it
+    async fn execute(&self, run_id: Uuid) -> Result<BillingRun, RevenueError> {
+        let run = self.get_run(run_id).await?;
+
+        // Cycles billed by this run are no longer open; the checkpoint also
+        // guards against billing them twice
+        let pending = sqlx::query_as::<_, PendingCycleRow>(
+            "SELECT c.id, c.customer_id FROM billing_cycles c
+             WHERE c.status = 'OPEN' AND c.end_date <= $2
+             AND ($3::VARCHAR IS NULL OR c.cycle_type = $3)
+             AND NOT EXISTS (
+                 SELECT 1 FROM billing_run_results r
+                 WHERE r.run_id = $1 AND r.cycle_id = c.id AND r.outcome = 'SUCCEEDED'
+             )
+             ORDER BY c.customer_id, c.end_date",
+        )
+        .bind(run_id)
+        .bind(run.cutoff)
+        .bind(run.cycle_type.as_ref().map(cycle_type_to_string))
+        .fetch_all(&self.pool)
+        .await?;
+
+        info!(
+            "Billing run {}: {} cycles to bill with up to {} at a time",
+            run_id,
+            pending.len(),
+            self.config.max_concurrency
+        );
+
+        stream::iter(pending)
+            .map(|cycle| self.bill_cycle(run_id, cycle))
+            .buffer_unordered(self.config.max_concurrency.max(1))
+            .collect::<Vec<()>>()
+            .await;
+
+        let run = self.get_run(run_id).await?;
+        let status = if run.failed() > 0 {
+            BillingRunStatus::CompletedWithFailures
+        } else {
+            BillingRunStatus::Completed
+        };
+        sqlx::query(
+            "UPDATE billing_runs SET status = $1, completed_at = CURRENT_TIMESTAMP WHERE id = $2",
+        )
+        .bind(run_status_to_string(&status))
+        .bind(run_id)
+        .execute(&self.pool)
+        .await?;
+
+        let run = self.get_run(run_id).await?;
+        info!(
+            "Billing run {} finished: {} billed, {} failed",
+            run_id,
+            run.succeeded(),
+            run.failed()
+        );
+        Ok(run)
+    }
+
+    /// Bill one cycle and record its outcome as checkpoint
+    async fn bill_cycle(&self, run_id: Uuid, cycle: PendingCycleRow) {
+        let (outcome, bill_id, error) = match self.cycle_manager.close_billing_cycle(cycle.id).await
+        {
+            Ok(bill_id) => (BillingRunOutcome::Succeeded, Some(bill_id), None),
+            Err(e) => {
+                warn!(
+                    "Billing run {} failed to bill cycle {} of customer {}: {}",
+                    run_id, cycle.id, cycle.customer_id, e
+                );
+                (BillingRunOutcome::Failed, None, Some(e.to_string()))
+            }
+        };
+
+        // A cycle whose outcome cannot be recorded is picked up again on
+        // resume if it is still open
+        if let Err(e) = sqlx::query(
+            "INSERT INTO billing_run_results (run_id, cycle_id, customer_id, outcome, bill_id, error)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (run_id, cycle_id) DO UPDATE SET
+             outcome = EXCLUDED.outcome, bill_id = EXCLUDED.bill_id, error = EXCLUDED.error,
+             processed_at = CURRENT_TIMESTAMP",
+        )
+        .bind(run_id)
+        .bind(cycle.id)
+        .bind(cycle.customer_id)
+        .bind(match outcome {
+            BillingRunOutcome::Succeeded => "SUCCEEDED",
+            BillingRunOutcome::Failed => "FAILED",
+        })
+        .bind(bill_id)
+        .bind(error)
+        .execute(&self.pool)
+        .await
+        {
+            warn!(
+                "Billing run {} failed to record outcome of cycle {}: {}",
+                run_id, cycle.id, e
+            );
+        }
+
+        // Progress renews the lease
+        if let Err(e) = sqlx::query("UPDATE billing_runs SET lease_expires_at = $1 WHERE id = $2")
+            .bind(Utc::now() + self.config.lease)
+            .bind(run_id)
+            .execute(&self.pool)
+            .await
+        {
+            warn!("Billing run {} failed to renew its lease: {}", run_id, e);
+        }
+    }
+}
+
+fn run_status_to_string(status: &BillingRunStatus) -> String {
+    match status {
+        BillingRunStatus::Running => "RUNNING".to_string(),
+        BillingRunStatus::Completed => "COMPLETED".to_string(),
+        BillingRunStatus::CompletedWithFailures => "COMPLETED_WITH_FAILURES".to_string(),
+        BillingRunStatus::Failed => "FAILED".to_string(),
+    }
+}
+
+fn string_to_run_status(s: &str) -> BillingRunStatus {
+    match s {
+        "COMPLETED" => BillingRunStatus::Completed,
+        "COMPLETED_WITH_FAILURES" => BillingRunStatus::CompletedWithFailures,
+        "FAILED" => BillingRunStatus::Failed,
+        _ => BillingRunStatus::Running,
+    }
+}
+
+/// Internal row structures
+#[derive(Debug, FromRow)]
+struct BillingRunRow {
+    id: Uuid,
+    cycle_type: Option<String>,
+    cutoff: DateTime<Utc>,
+    status: String,
+    started_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+struct BillingRunResultRow {
+    customer_id: Uuid,
+    cycle_id: Uuid,
+    outcome: String,
+    bill_id: Option<Uuid>,
+    error: Option<String>,
+    processed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct PendingCycleRow {
+    id: Uuid,
+    customer_id: Uuid,
+}
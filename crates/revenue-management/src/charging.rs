@@ -2,18 +2,29 @@
 //!
 //! Processes usage events in real-time and applies charging rules
 
+use crate::account::{self, ChargingAccountManager};
 use crate::error::RevenueError;
-use crate::models::{ChargingRequest, ChargingResult, Money};
+use crate::models::{
+    ChargingEventType, ChargingRequest, ChargingResult, Money, PaymentMode,
+    RecordChargingEventRequest, TaxJurisdiction, TaxLine, TaxType,
+};
 use crate::rating::RatingEngine;
+use crate::session_log::{self, ChargingSessionLog};
+use crate::tax::{self, TableTaxCalculator, TaxCalculator};
 use chrono::Utc;
-use log::info;
+use log::{info, warn};
 use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// Charging engine for real-time usage processing
 pub struct ChargingEngine {
     pool: PgPool,
     rating_engine: RatingEngine,
+    tax_calculator: Arc<dyn TaxCalculator>,
+    session_log: ChargingSessionLog,
+    accounts: ChargingAccountManager,
+    default_jurisdiction: Option<TaxJurisdiction>,
 }
 
 impl ChargingEngine {
@@ -22,11 +33,31 @@ impl ChargingEngine {
         let pool_clone = pool.clone();
         Self {
             pool,
-            rating_engine: RatingEngine::new(pool_clone),
+            rating_engine: RatingEngine::new(pool_clone.clone()),
+            tax_calculator: Arc::new(TableTaxCalculator::default()),
+            session_log: ChargingSessionLog::new(pool_clone.clone()),
+            accounts: ChargingAccountManager::new(pool_clone),
+            default_jurisdiction: None,
         }
     }
 
+    /// Use a different tax calculator
+    pub fn with_tax_calculator(mut self, tax_calculator: Arc<dyn TaxCalculator>) -> Self {
+        self.tax_calculator = tax_calculator;
+        self
+    }
+
+    /// Tax usage that names no jurisdiction as consumed in `jurisdiction`
+    pub fn with_default_jurisdiction(mut self, jurisdiction: TaxJurisdiction) -> Self {
+        self.default_jurisdiction = Some(jurisdiction);
+        self
+    }
+
     /// Process a charging request in real-time
+    ///
+    /// The charge is paid from the customer's prepaid balance or invoiced as
+    /// their charging account decides for its category. A prepaid charge the
+    /// balance does not cover is rejected and the usage left unrated.
     pub async fn charge(&self, request: ChargingRequest) -> Result<ChargingResult, RevenueError> {
         info!(
             "Processing real-time charge for usage_id: {}, customer_id: {}",
@@ -44,8 +75,26 @@ impl ChargingEngine {
             )
             .await?;
 
-        // Calculate tax (simplified - in production, this would use tax rules)
-        let tax_amount = self.calculate_tax(rating_result.charge_amount.value)?;
+        // Calculate itemized taxes for the consumer's jurisdiction
+        let jurisdiction = request
+            .jurisdiction
+            .as_ref()
+            .or(self.default_jurisdiction.as_ref())
+            .ok_or_else(|| {
+                RevenueError::Validation(format!(
+                    "No tax jurisdiction for usage_id {} and no default jurisdiction configured",
+                    request.usage_id
+                ))
+            })?;
+        let tax_lines = self
+            .tax_calculator
+            .calculate(&tax::taxable_item(
+                &request.usage_type,
+                &rating_result.charge_amount,
+                jurisdiction,
+            ))
+            .await?;
+        let tax_amount = tax::total_tax(&tax_lines, &rating_result.charge_amount.unit);
 
         let charge_amount_value = rating_result.charge_amount.value;
         let charge_amount_unit = rating_result.charge_amount.unit.clone();
@@ -61,18 +110,81 @@ impl ChargingEngine {
             unit: charge_amount_unit.clone(),
         };
         let currency = charge_amount_unit.clone();
-        self.store_charging_result(
-            request.usage_id,
-            rating_id,
-            &charge_amount,
-            &tax_amount,
-            &total_amount,
-        )
-        .await?;
+        let payment_mode = self
+            .accounts
+            .get_account(request.customer_id)
+            .await?
+            .map_or(PaymentMode::Postpaid, |account| {
+                account.payment_mode(&request.charge_category)
+            });
+        let prepaid_balance_after = self
+            .store_charging_result(
+                request.usage_id,
+                rating_id,
+                &charge_amount,
+                &tax_amount,
+                &total_amount,
+                &tax_lines,
+                request.customer_id,
+                payment_mode == PaymentMode::Prepaid,
+                request.session_id,
+            )
+            .await;
+        if let Err(RevenueError::InsufficientBalance(msg)) = &prepaid_balance_after {
+            warn!("Rejected charge for usage_id {}: {}", request.usage_id, msg);
+            if let Some(session_id) = request.session_id {
+                let event = RecordChargingEventRequest {
+                    session_id,
+                    subscriber_id: request.customer_id,
+                    event_type: ChargingEventType::Reservation,
+                    amount: total_amount.clone(),
+                    balance_after: None,
+                    description: Some(format!(
+                        "Reservation for usage {} rejected: {}",
+                        request.usage_id, msg
+                    )),
+                };
+                if let Err(e) = self.session_log.record(event).await {
+                    warn!(
+                        "Failed to log rejected reservation of usage_id {} in session {}: {}",
+                        request.usage_id, session_id, e
+                    );
+                }
+            }
+        }
+        let prepaid_balance_after = prepaid_balance_after?;
 
         // Update usage record state to "Rated"
         self.update_usage_state(request.usage_id, "RATED").await?;
 
+        // Record the rating decision in the session log
+        if let Some(session_id) = request.session_id {
+            let event = RecordChargingEventRequest {
+                session_id,
+                subscriber_id: request.customer_id,
+                event_type: ChargingEventType::Rating,
+                amount: total_amount.clone(),
+                balance_after: prepaid_balance_after.clone(),
+                description: Some(format!(
+                    "{} {} {} rated by rule {}: charge {} {}, tax {} {}",
+                    request.amount,
+                    request.unit,
+                    request.usage_type,
+                    rating_result.rating_rule_id,
+                    charge_amount.value,
+                    charge_amount.unit,
+                    tax_amount.value,
+                    tax_amount.unit
+                )),
+            };
+            if let Err(e) = self.session_log.record(event).await {
+                warn!(
+                    "Failed to log rating of usage_id {} in session {}: {}",
+                    request.usage_id, session_id, e
+                );
+            }
+        }
+
         let result = ChargingResult {
             usage_id: request.usage_id,
             rating_id,
@@ -81,6 +193,9 @@ impl ChargingEngine {
             total_amount,
             currency,
             timestamp: Utc::now(),
+            tax_lines,
+            payment_mode,
+            prepaid_balance_after,
         };
 
         info!(
@@ -91,17 +206,15 @@ impl ChargingEngine {
         Ok(result)
     }
 
-    /// Calculate tax (simplified implementation)
-    fn calculate_tax(&self, amount: f64) -> Result<Money, RevenueError> {
-        // Default tax rate of 10% - in production, this would be configurable
-        let tax_rate = 0.10;
-        Ok(Money {
-            value: amount * tax_rate,
-            unit: "USD".to_string(),
-        })
-    }
-
     /// Store charging result in database
+    ///
+    /// When `prepaid` is set the total is deducted from the customer's prepaid
+    /// balance in the same transaction, and the balance left is returned. A
+    /// usage record charged again first gets back whatever its earlier charge
+    /// took from the balance, so re-rating never debits twice and a switch
+    /// to postpaid leaves no prepaid debit behind. With a session, the
+    /// refund, reservation and debit are logged in the same transaction.
+    #[allow(clippy::too_many_arguments)]
     async fn store_charging_result(
         &self,
         usage_id: Uuid,
@@ -109,12 +222,80 @@ impl ChargingEngine {
         charge_amount: &Money,
         tax_amount: &Money,
         total_amount: &Money,
-    ) -> Result<(), RevenueError> {
+        tax_lines: &[TaxLine],
+        customer_id: Uuid,
+        prepaid: bool,
+        session_id: Option<Uuid>,
+    ) -> Result<Option<Money>, RevenueError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Serialize charges of the same usage record, including the first one
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1::text))")
+            .bind(usage_id)
+            .execute(&mut *tx)
+            .await?;
+        let previous_debit: Option<(f64, String)> = sqlx::query_as(
+            "SELECT total_amount_value::FLOAT8, total_amount_unit FROM charging_results
+             WHERE usage_id = $1 AND payment_mode = 'PREPAID'",
+        )
+        .bind(usage_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((value, unit)) = previous_debit {
+            let refund = Money { value, unit };
+            let balance = account::credit_prepaid(&mut tx, customer_id, &refund).await?;
+            if let Some(session_id) = session_id {
+                let event = RecordChargingEventRequest {
+                    session_id,
+                    subscriber_id: customer_id,
+                    event_type: ChargingEventType::BalanceChange,
+                    amount: refund,
+                    balance_after: Some(balance),
+                    description: Some(format!(
+                        "Refunded earlier prepaid charge of usage {} before charging it again",
+                        usage_id
+                    )),
+                };
+                session_log::record_event(&mut tx, event).await?;
+            }
+        }
+
+        let balance_after = if prepaid {
+            let balance = account::debit_prepaid(&mut tx, customer_id, total_amount).await?;
+            if let Some(session_id) = session_id {
+                let reservation = RecordChargingEventRequest {
+                    session_id,
+                    subscriber_id: customer_id,
+                    event_type: ChargingEventType::Reservation,
+                    amount: total_amount.clone(),
+                    balance_after: None,
+                    description: Some(format!("Reserved prepaid credit for usage {}", usage_id)),
+                };
+                session_log::record_event(&mut tx, reservation).await?;
+                let debit = RecordChargingEventRequest {
+                    session_id,
+                    subscriber_id: customer_id,
+                    event_type: ChargingEventType::BalanceChange,
+                    amount: Money {
+                        value: -total_amount.value,
+                        unit: total_amount.unit.clone(),
+                    },
+                    balance_after: Some(balance.clone()),
+                    description: Some(format!("Debited prepaid balance for usage {}", usage_id)),
+                };
+                session_log::record_event(&mut tx, debit).await?;
+            }
+            Some(balance)
+        } else {
+            None
+        };
+        let payment_mode = if prepaid { "PREPAID" } else { "POSTPAID" };
+
         sqlx::query(
             "INSERT INTO charging_results (id, usage_id, rating_id, charge_amount_value, 
              charge_amount_unit, tax_amount_value, tax_amount_unit, total_amount_value, 
-             total_amount_unit, created_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             total_amount_unit, payment_mode, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
              ON CONFLICT (usage_id) DO UPDATE SET
              rating_id = EXCLUDED.rating_id,
              charge_amount_value = EXCLUDED.charge_amount_value,
@@ -123,6 +304,7 @@ impl ChargingEngine {
              tax_amount_unit = EXCLUDED.tax_amount_unit,
              total_amount_value = EXCLUDED.total_amount_value,
              total_amount_unit = EXCLUDED.total_amount_unit,
+             payment_mode = EXCLUDED.payment_mode,
              updated_at = CURRENT_TIMESTAMP",
         )
         .bind(Uuid::new_v4())
@@ -134,11 +316,72 @@ impl ChargingEngine {
         .bind(&tax_amount.unit)
         .bind(total_amount.value)
         .bind(&total_amount.unit)
+        .bind(payment_mode)
         .bind(Utc::now())
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(())
+        // Replace the itemized taxes of any earlier charge of this usage
+        sqlx::query("DELETE FROM charging_tax_lines WHERE usage_id = $1")
+            .bind(usage_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for line in tax_lines {
+            sqlx::query(
+                "INSERT INTO charging_tax_lines (id, usage_id, tax_type, country, region,
+                 rate_percentage, taxable_amount_value, tax_amount_value, unit)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(usage_id)
+            .bind(line.tax_type.code())
+            .bind(&line.jurisdiction.country)
+            .bind(&line.jurisdiction.region)
+            .bind(line.rate_percentage)
+            .bind(line.taxable_amount.value)
+            .bind(line.tax_amount.value)
+            .bind(&line.tax_amount.unit)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(balance_after)
+    }
+
+    /// Get the itemized taxes of a charged usage record
+    async fn get_tax_lines(&self, usage_id: Uuid) -> Result<Vec<TaxLine>, RevenueError> {
+        let rows = sqlx::query_as::<_, TaxLineRow>(
+            "SELECT tax_type, country, region, rate_percentage::FLOAT8 AS rate_percentage,
+             taxable_amount_value::FLOAT8 AS taxable_amount_value,
+             tax_amount_value::FLOAT8 AS tax_amount_value, unit
+             FROM charging_tax_lines WHERE usage_id = $1 ORDER BY tax_type",
+        )
+        .bind(usage_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|r| {
+                Ok(TaxLine {
+                    tax_type: string_to_tax_type(&r.tax_type)?,
+                    jurisdiction: TaxJurisdiction {
+                        country: r.country,
+                        region: r.region,
+                    },
+                    rate_percentage: r.rate_percentage,
+                    taxable_amount: Money {
+                        value: r.taxable_amount_value,
+                        unit: r.unit.clone(),
+                    },
+                    tax_amount: Money {
+                        value: r.tax_amount_value,
+                        unit: r.unit,
+                    },
+                })
+            })
+            .collect()
     }
 
     /// Update usage record state
@@ -160,12 +403,17 @@ impl ChargingEngine {
         let row = sqlx::query_as::<_, ChargingResultRow>(
             "SELECT usage_id, rating_id, charge_amount_value, charge_amount_unit,
              tax_amount_value, tax_amount_unit, total_amount_value, total_amount_unit,
-             created_at as timestamp
+             payment_mode, created_at as timestamp
              FROM charging_results WHERE usage_id = $1",
         )
         .bind(usage_id)
         .fetch_optional(&self.pool)
         .await?;
+        let tax_lines = if row.is_some() {
+            self.get_tax_lines(usage_id).await?
+        } else {
+            Vec::new()
+        };
 
         Ok(row.map(|r| {
             let currency = r.total_amount_unit.clone();
@@ -186,11 +434,28 @@ impl ChargingEngine {
                 },
                 currency,
                 timestamp: r.timestamp,
+                tax_lines,
+                payment_mode: if r.payment_mode == "PREPAID" {
+                    PaymentMode::Prepaid
+                } else {
+                    PaymentMode::Postpaid
+                },
+                prepaid_balance_after: None,
             }
         }))
     }
 }
 
+fn string_to_tax_type(s: &str) -> Result<TaxType, RevenueError> {
+    match s {
+        "ICMS" => Ok(TaxType::Icms),
+        "PIS" => Ok(TaxType::Pis),
+        "COFINS" => Ok(TaxType::Cofins),
+        "VAT" => Ok(TaxType::Vat),
+        _ => Err(RevenueError::Database(format!("Unknown tax type {}", s))),
+    }
+}
+
 /// Internal row structure for charging results
 #[derive(Debug, FromRow)]
 struct ChargingResultRow {
@@ -202,5 +467,18 @@ struct ChargingResultRow {
     tax_amount_unit: String,
     total_amount_value: f64,
     total_amount_unit: String,
+    payment_mode: String,
     timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+/// Internal row structure for itemized taxes
+#[derive(Debug, FromRow)]
+struct TaxLineRow {
+    tax_type: String,
+    country: String,
+    region: Option<String>,
+    rate_percentage: f64,
+    taxable_amount_value: f64,
+    tax_amount_value: f64,
+    unit: String,
+}
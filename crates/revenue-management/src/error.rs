@@ -28,6 +28,9 @@ pub enum RevenueError {
 
     #[error("Validation error: {0}")]
     Validation(String),
+
+    #[error("Insufficient prepaid balance: {0}")]
+    InsufficientBalance(String),
 }
 
 impl From<sqlx::Error> for RevenueError {
@@ -2,19 +2,37 @@
 //!
 //! This module provides comprehensive revenue management capabilities including:
 //! - Real-time charging integration
+//! - Prepaid, postpaid and hybrid charging accounts
 //! - Usage aggregation and rating
 //! - Billing cycle management
+//! - Billing runs across all customers with resumable checkpoints
 //! - Partner settlement workflows
+//! - Revenue recognition scheduling
+//! - Pluggable tax calculation
+//! - Charging session event log for dispute resolution
+//! - One-time charges (fees) raised to billing as events
 
+pub mod account;
 pub mod billing_cycle;
+pub mod billing_run;
 pub mod charging;
 pub mod error;
 pub mod models;
+pub mod one_time_charge;
 pub mod rating;
+pub mod recognition;
+pub mod session_log;
 pub mod settlement;
+pub mod tax;
 
+pub use account::ChargingAccountManager;
 pub use billing_cycle::BillingCycleManager;
+pub use billing_run::{BillingRunConfig, BillingRunCoordinator};
 pub use charging::ChargingEngine;
 pub use error::RevenueError;
+pub use one_time_charge::OneTimeChargeEmitter;
 pub use rating::RatingEngine;
+pub use recognition::RevenueRecognitionEngine;
+pub use session_log::ChargingSessionLog;
 pub use settlement::SettlementEngine;
+pub use tax::{TableTaxCalculator, TaxCalculator};
@@ -15,6 +15,16 @@ pub struct ChargingRequest {
     pub unit: String,
     pub start_date: DateTime<Utc>,
     pub end_date: Option<DateTime<Utc>>,
+    /// Where the service is consumed; the engine's default jurisdiction is
+    /// used when unknown
+    #[serde(default)]
+    pub jurisdiction: Option<TaxJurisdiction>,
+    /// Charging session the usage belongs to, for the session event log
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+    /// Category of the charge, which decides how hybrid accounts pay for it
+    #[serde(default)]
+    pub charge_category: ChargeCategory,
 }
 
 /// Charging result
@@ -27,6 +37,15 @@ pub struct ChargingResult {
     pub total_amount: Money,
     pub currency: String,
     pub timestamp: DateTime<Utc>,
+    /// Itemized taxes making up `tax_amount`
+    #[serde(default)]
+    pub tax_lines: Vec<TaxLine>,
+    /// Whether the charge was paid from the prepaid balance or invoiced
+    #[serde(default)]
+    pub payment_mode: PaymentMode,
+    /// Prepaid balance left after a prepaid charge
+    #[serde(default)]
+    pub prepaid_balance_after: Option<Money>,
 }
 
 /// Money representation
@@ -36,6 +55,70 @@ pub struct Money {
     pub unit: String,
 }
 
+/// Account type - How a customer pays for charges
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountType {
+    /// Every charge is paid from the prepaid balance
+    Prepaid,
+    /// Every charge is invoiced
+    Postpaid,
+    /// Charges of the account's prepaid categories are paid from the prepaid
+    /// balance, all others are invoiced
+    Hybrid,
+}
+
+/// Charge category
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChargeCategory {
+    /// Subscription fee
+    Recurring,
+    /// Usage within the subscription's allowance
+    #[default]
+    Usage,
+    /// Usage beyond the subscription's allowance
+    Overage,
+    /// Fee charged once
+    OneTime,
+}
+
+/// Where a charge is paid from
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentMode {
+    /// Deducted from the prepaid balance
+    Prepaid,
+    /// Added to the next invoice
+    #[default]
+    Postpaid,
+}
+
+/// Charging account of a customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingAccount {
+    pub customer_id: Uuid,
+    pub account_type: AccountType,
+    /// Categories paid from the prepaid balance by a hybrid account
+    #[serde(default)]
+    pub prepaid_categories: Vec<ChargeCategory>,
+    pub prepaid_balance: Money,
+}
+
+impl ChargingAccount {
+    /// Where the account pays charges of a category from
+    pub fn payment_mode(&self, category: &ChargeCategory) -> PaymentMode {
+        match self.account_type {
+            AccountType::Prepaid => PaymentMode::Prepaid,
+            AccountType::Postpaid => PaymentMode::Postpaid,
+            AccountType::Hybrid if self.prepaid_categories.contains(category) => {
+                PaymentMode::Prepaid
+            }
+            AccountType::Hybrid => PaymentMode::Postpaid,
+        }
+    }
+}
+
 /// Rating rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RatingRule {
@@ -92,6 +175,8 @@ pub struct BillingCycle {
     pub due_date: DateTime<Utc>,
     pub status: CycleStatus,
     pub bill_id: Option<Uuid>,
+    /// Jurisdiction used to tax the cycle's bill items
+    pub tax_jurisdiction: Option<TaxJurisdiction>,
 }
 
 /// Cycle type
@@ -115,6 +200,71 @@ pub enum CycleStatus {
     Paid,
 }
 
+/// Billing run - Closes all due billing cycles in one pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingRun {
+    pub id: Uuid,
+    /// Only cycles of this type are billed (all types if unset)
+    pub cycle_type: Option<CycleType>,
+    /// Cycles ending at or before this instant are due
+    pub cutoff: DateTime<Utc>,
+    pub status: BillingRunStatus,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Latest outcome of each cycle processed by the run
+    pub results: Vec<BillingRunResult>,
+}
+
+impl BillingRun {
+    /// Number of cycles billed
+    pub fn succeeded(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.outcome == BillingRunOutcome::Succeeded)
+            .count()
+    }
+
+    /// Number of cycles that could not be billed
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.outcome == BillingRunOutcome::Failed)
+            .count()
+    }
+}
+
+/// Billing run status
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BillingRunStatus {
+    /// In progress, or interrupted and waiting to be resumed
+    Running,
+    Completed,
+    /// Finished with cycles that failed; resuming retries them
+    CompletedWithFailures,
+    /// Stopped by an error before every cycle was processed; resuming continues it
+    Failed,
+}
+
+/// Outcome of billing one customer's cycle in a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingRunResult {
+    pub customer_id: Uuid,
+    pub cycle_id: Uuid,
+    pub outcome: BillingRunOutcome,
+    pub bill_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub processed_at: DateTime<Utc>,
+}
+
+/// Billing run outcome of a cycle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BillingRunOutcome {
+    Succeeded,
+    Failed,
+}
+
 /// Partner settlement
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PartnerSettlement {
@@ -125,6 +275,12 @@ pub struct PartnerSettlement {
     pub total_revenue: Money,
     pub partner_share: Money,
     pub platform_share: Money,
+    /// Balance carried in from earlier periods that were below the transfer threshold
+    pub carried_in: Money,
+    /// Amount to transfer this period (zero when below the partner's minimum transfer)
+    pub transfer_amount: Money,
+    /// Balance rolled over to the next period
+    pub carried_forward: Money,
     pub status: SettlementStatus,
     pub settlement_date: Option<DateTime<Utc>>,
 }
@@ -138,6 +294,16 @@ pub enum SettlementStatus {
     Approved,
     Paid,
     Rejected,
+    /// Below the minimum transfer; balance rolled over to the next period
+    CarriedOver,
+}
+
+/// Per-partner settlement configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartnerSettlementConfig {
+    pub partner_id: Uuid,
+    /// Partner payable below this amount is rolled over instead of transferred
+    pub minimum_transfer: Money,
 }
 
 /// Settlement rule
@@ -150,3 +316,186 @@ pub struct SettlementRule {
     pub valid_from: DateTime<Utc>,
     pub valid_to: Option<DateTime<Utc>>,
 }
+
+/// Request to schedule recognition of an upfront charge over its service period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueRecognitionRequest {
+    pub charge_id: Uuid,
+    pub customer_id: Uuid,
+    pub amount: Money,
+    pub service_period_start: DateTime<Utc>,
+    pub service_period_end: DateTime<Utc>,
+}
+
+/// Revenue recognition schedule for one charge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueRecognitionSchedule {
+    pub id: Uuid,
+    pub charge_id: Uuid,
+    pub customer_id: Uuid,
+    pub total_amount: Money,
+    pub service_period_start: DateTime<Utc>,
+    pub service_period_end: DateTime<Utc>,
+    pub entries: Vec<RevenueRecognitionEntry>,
+}
+
+/// Revenue recognized for one calendar month (or part of one) of the service period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueRecognitionEntry {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub amount: Money,
+    pub status: RecognitionStatus,
+    pub recognized_at: Option<DateTime<Utc>>,
+}
+
+/// Recognition status of a schedule entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecognitionStatus {
+    Scheduled,
+    Recognized,
+}
+
+/// Recognized and deferred revenue of a charge at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueRecognitionBalance {
+    pub charge_id: Uuid,
+    pub as_of: DateTime<Utc>,
+    pub recognized: Money,
+    pub deferred: Money,
+}
+
+/// Tax jurisdiction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaxJurisdiction {
+    /// ISO 3166-1 alpha-2 country code
+    pub country: String,
+    /// State or region code within the country (e.g. "SP")
+    pub region: Option<String>,
+}
+
+/// Tax type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaxType {
+    /// Brazilian state tax on goods and communication services
+    Icms,
+    /// Brazilian federal social contribution (PIS)
+    Pis,
+    /// Brazilian federal social contribution (COFINS)
+    Cofins,
+    /// EU value added tax
+    Vat,
+}
+
+impl TaxType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TaxType::Icms => "ICMS",
+            TaxType::Pis => "PIS",
+            TaxType::Cofins => "COFINS",
+            TaxType::Vat => "VAT",
+        }
+    }
+}
+
+/// Tax rate table entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxRate {
+    pub tax_type: TaxType,
+    pub country: String,
+    /// Applies to one region only; country-wide when unset
+    pub region: Option<String>,
+    /// Applies to one product type only; all products when unset
+    pub product_type: Option<String>,
+    pub rate_percentage: f64,
+    /// Tax is calculated "por dentro", on a base that includes the tax itself
+    #[serde(default)]
+    pub inclusive: bool,
+}
+
+/// Line item to be taxed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxableItem {
+    pub product_type: String,
+    pub amount: Money,
+    pub jurisdiction: TaxJurisdiction,
+}
+
+/// Itemized tax on a line item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub tax_type: TaxType,
+    pub jurisdiction: TaxJurisdiction,
+    pub rate_percentage: f64,
+    pub taxable_amount: Money,
+    pub tax_amount: Money,
+}
+
+/// Type of charging session event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChargingEventType {
+    /// Units or credit reserved ahead of consumption
+    Reservation,
+    /// Usage rated into a charge
+    Rating,
+    /// Subscriber balance debited or credited
+    BalanceChange,
+}
+
+/// Charging decision recorded in a session's event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargingSessionEvent {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub subscriber_id: Uuid,
+    pub event_type: ChargingEventType,
+    /// Amount of the decision; balance changes are negative for debits and
+    /// positive for refunds
+    pub amount: Money,
+    /// Subscriber balance after the event, when known
+    pub balance_after: Option<Money>,
+    /// Why the decision was taken, e.g. the rating rule applied
+    pub description: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Event to append to a charging session log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordChargingEventRequest {
+    pub session_id: Uuid,
+    pub subscriber_id: Uuid,
+    pub event_type: ChargingEventType,
+    pub amount: Money,
+    pub balance_after: Option<Money>,
+    pub description: Option<String>,
+}
+
+/// Charge not derived from rated usage, such as a fee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneTimeChargeRequest {
+    /// Customer charged, when known to the raising domain
+    pub customer_id: Option<Uuid>,
+    pub amount: Money,
+    /// Kind of charge, e.g. "LATE_CANCELLATION_FEE"
+    pub charge_type: String,
+    pub description: Option<String>,
+    /// Entity the charge arose from, e.g. an appointment
+    pub reference_id: Option<Uuid>,
+    pub reference_type: Option<String>,
+}
+
+/// One-time charge raised to billing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneTimeCharge {
+    pub id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub amount: Money,
+    pub charge_type: String,
+    pub description: Option<String>,
+    pub reference_id: Option<Uuid>,
+    pub reference_type: Option<String>,
+    pub raised_at: DateTime<Utc>,
+}
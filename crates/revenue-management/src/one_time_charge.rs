@@ -0,0 +1,74 @@
+//! One-Time Charges
+//!
+//! Fees and other charges that do not come from rated usage are raised by the
+//! domain they arise in and published as `OneTimeChargeRaised` events on the
+//! billing topic, from which they are billed.
+
+use crate::error::RevenueError;
+use crate::models::{Money, OneTimeCharge, OneTimeChargeRequest};
+use crate::settlement::round_to_currency;
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use chrono::Utc;
+use log::info;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Raises one-time charges to billing
+pub struct OneTimeChargeEmitter {
+    publisher: Arc<dyn EventPublisher>,
+}
+
+impl OneTimeChargeEmitter {
+    /// Create an emitter publishing to the given event bus
+    pub fn new(publisher: Arc<dyn EventPublisher>) -> Self {
+        Self { publisher }
+    }
+
+    /// Raise a charge, rounded to its currency, and publish it
+    ///
+    /// Fails if the amount is not positive or the event cannot be published.
+    pub async fn emit(&self, request: OneTimeChargeRequest) -> Result<OneTimeCharge, RevenueError> {
+        let value = round_to_currency(request.amount.value, &request.amount.unit);
+        if value <= 0.0 {
+            return Err(RevenueError::Validation(format!(
+                "One-time charge amount must be positive, got {} {}",
+                request.amount.value, request.amount.unit
+            )));
+        }
+
+        let charge = OneTimeCharge {
+            id: Uuid::new_v4(),
+            customer_id: request.customer_id,
+            amount: Money {
+                value,
+                unit: request.amount.unit,
+            },
+            charge_type: request.charge_type,
+            description: request.description,
+            reference_id: request.reference_id,
+            reference_type: request.reference_type,
+            raised_at: Utc::now(),
+        };
+
+        let data = serde_json::to_value(&charge)
+            .map_err(|e| RevenueError::Charging(format!("Failed to encode charge: {}", e)))?;
+        let event = EventEnvelope::new(
+            "OneTimeChargeRaised".to_string(),
+            "revenue-management".to_string(),
+            data,
+        );
+        self.publisher
+            .publish(topics::BILLING_EVENTS, event)
+            .await
+            .map_err(|e| {
+                RevenueError::Charging(format!("Failed to publish charge {}: {}", charge.id, e))
+            })?;
+
+        info!(
+            "Raised {} charge {} of {} {}",
+            charge.charge_type, charge.id, charge.amount.value, charge.amount.unit
+        );
+        Ok(charge)
+    }
+}
@@ -51,6 +51,8 @@ impl RatingEngine {
     }
 
     /// Aggregate usage records for a period
+    ///
+    /// Usage paid from a prepaid balance is left out, as it is not invoiced.
     pub async fn aggregate_usage(
         &self,
         customer_id: Uuid,
@@ -83,6 +85,10 @@ impl RatingEngine {
             INNER JOIN customers c ON urp.name = c.name AND urp.role = 'customer'
             WHERE c.id = $3
             AND u.state = 'RATED'
+            AND NOT EXISTS (
+                SELECT 1 FROM charging_results cr
+                WHERE cr.usage_id = u.id AND cr.payment_mode = 'PREPAID'
+            )
             AND u.usage_date >= $1
             AND u.usage_date <= $2",
         );
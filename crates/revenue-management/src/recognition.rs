@@ -0,0 +1,312 @@
+//! Revenue Recognition Scheduling
+//!
+//! Spreads upfront charges over their service period (IFRS 15, performance
+//! obligations satisfied over time): one entry per calendar month, partial
+//! months prorated by the share of the service period they cover. Amounts are
+//! allocated in minor units so the entries always add up to the charge.
+
+use crate::error::RevenueError;
+use crate::models::{
+    Money, RecognitionStatus, RevenueRecognitionBalance, RevenueRecognitionEntry,
+    RevenueRecognitionRequest, RevenueRecognitionSchedule,
+};
+use crate::settlement::{currency_minor_units, round_to_currency};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use log::info;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Revenue recognition engine
+pub struct RevenueRecognitionEngine {
+    pool: PgPool,
+}
+
+impl RevenueRecognitionEngine {
+    /// Create a new revenue recognition engine
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Build and store the monthly recognition schedule of a charge
+    pub async fn create_schedule(
+        &self,
+        request: RevenueRecognitionRequest,
+    ) -> Result<RevenueRecognitionSchedule, RevenueError> {
+        let entries = build_schedule(
+            &request.amount,
+            request.service_period_start,
+            request.service_period_end,
+        )?;
+        let schedule_id = Uuid::new_v4();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO revenue_recognition_schedules (id, charge_id, customer_id,
+             total_amount_value, total_amount_unit, service_period_start, service_period_end)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(schedule_id)
+        .bind(request.charge_id)
+        .bind(request.customer_id)
+        .bind(request.amount.value)
+        .bind(&request.amount.unit)
+        .bind(request.service_period_start)
+        .bind(request.service_period_end)
+        .execute(&mut *tx)
+        .await?;
+
+        for entry in &entries {
+            sqlx::query(
+                "INSERT INTO revenue_recognition_entries (id, schedule_id, period_start,
+                 period_end, amount_value, status)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(schedule_id)
+            .bind(entry.period_start)
+            .bind(entry.period_end)
+            .bind(entry.amount.value)
+            .bind(recognition_status_to_string(&entry.status))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        info!(
+            "Scheduled recognition of {} {} for charge {} over {} periods",
+            request.amount.value,
+            request.amount.unit,
+            request.charge_id,
+            entries.len()
+        );
+
+        Ok(RevenueRecognitionSchedule {
+            id: schedule_id,
+            charge_id: request.charge_id,
+            customer_id: request.customer_id,
+            total_amount: request.amount,
+            service_period_start: request.service_period_start,
+            service_period_end: request.service_period_end,
+            entries,
+        })
+    }
+
+    /// Get the recognition schedule of a charge
+    pub async fn get_schedule(
+        &self,
+        charge_id: Uuid,
+    ) -> Result<RevenueRecognitionSchedule, RevenueError> {
+        let schedule = sqlx::query_as::<_, ScheduleRow>(
+            "SELECT id, charge_id, customer_id, total_amount_value::FLOAT8 AS total_amount_value,
+             total_amount_unit, service_period_start, service_period_end
+             FROM revenue_recognition_schedules WHERE charge_id = $1",
+        )
+        .bind(charge_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| {
+            RevenueError::NotFound(format!("Recognition schedule for charge {}", charge_id))
+        })?;
+
+        let entries = sqlx::query_as::<_, EntryRow>(
+            "SELECT period_start, period_end, amount_value::FLOAT8 AS amount_value, status,
+             recognized_at
+             FROM revenue_recognition_entries WHERE schedule_id = $1 ORDER BY period_start",
+        )
+        .bind(schedule.id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(RevenueRecognitionSchedule {
+            id: schedule.id,
+            charge_id: schedule.charge_id,
+            customer_id: schedule.customer_id,
+            total_amount: Money {
+                value: schedule.total_amount_value,
+                unit: schedule.total_amount_unit.clone(),
+            },
+            service_period_start: schedule.service_period_start,
+            service_period_end: schedule.service_period_end,
+            entries: entries
+                .into_iter()
+                .map(|e| RevenueRecognitionEntry {
+                    period_start: e.period_start,
+                    period_end: e.period_end,
+                    amount: Money {
+                        value: e.amount_value,
+                        unit: schedule.total_amount_unit.clone(),
+                    },
+                    status: string_to_recognition_status(&e.status),
+                    recognized_at: e.recognized_at,
+                })
+                .collect(),
+        })
+    }
+
+    /// Recognize every scheduled entry whose period has ended by `as_of`
+    pub async fn recognize_due(&self, as_of: DateTime<Utc>) -> Result<u64, RevenueError> {
+        let result = sqlx::query(
+            "UPDATE revenue_recognition_entries
+             SET status = $1, recognized_at = CURRENT_TIMESTAMP
+             WHERE status = $2 AND period_end <= $3",
+        )
+        .bind(recognition_status_to_string(&RecognitionStatus::Recognized))
+        .bind(recognition_status_to_string(&RecognitionStatus::Scheduled))
+        .bind(as_of)
+        .execute(&self.pool)
+        .await?;
+
+        let recognized = result.rows_affected();
+        if recognized > 0 {
+            info!("Recognized {} revenue entries due by {}", recognized, as_of);
+        }
+        Ok(recognized)
+    }
+
+    /// Get the recognized and deferred revenue of a charge as of a date
+    pub async fn get_balance(
+        &self,
+        charge_id: Uuid,
+        as_of: DateTime<Utc>,
+    ) -> Result<RevenueRecognitionBalance, RevenueError> {
+        let schedule = self.get_schedule(charge_id).await?;
+        Ok(balance_at(&schedule, as_of))
+    }
+}
+
+/// First instant of the calendar month following `at`
+fn start_of_next_month(at: DateTime<Utc>) -> DateTime<Utc> {
+    let date = at.date_naive();
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("first of month is a valid date")
+        .and_utc()
+}
+
+/// Split a charge into monthly recognition entries over `[start, end)`
+///
+/// Each entry covers a calendar month clipped to the service period and is
+/// weighted by the time it covers, so partial first and last months are
+/// prorated. Allocation is cumulative in minor units, which makes the entries
+/// sum to the charge exactly.
+pub fn build_schedule(
+    amount: &Money,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<RevenueRecognitionEntry>, RevenueError> {
+    if end <= start {
+        return Err(RevenueError::Validation(
+            "Recognition period must end after it starts".to_string(),
+        ));
+    }
+    if amount.value < 0.0 {
+        return Err(RevenueError::Validation(
+            "Recognized amount cannot be negative".to_string(),
+        ));
+    }
+
+    let factor = 10f64.powi(currency_minor_units(&amount.unit) as i32);
+    let total_minor = (amount.value * factor).round() as i64;
+    let total_seconds = (end - start).num_seconds() as i128;
+
+    let mut entries = Vec::new();
+    let mut period_start = start;
+    let mut elapsed_seconds: i128 = 0;
+    let mut allocated_minor: i64 = 0;
+
+    while period_start < end {
+        let period_end = start_of_next_month(period_start).min(end);
+        elapsed_seconds += (period_end - period_start).num_seconds() as i128;
+
+        let cumulative_minor =
+            ((total_minor as i128 * elapsed_seconds + total_seconds / 2) / total_seconds) as i64;
+        let entry_minor = cumulative_minor - allocated_minor;
+        allocated_minor = cumulative_minor;
+
+        entries.push(RevenueRecognitionEntry {
+            period_start,
+            period_end,
+            amount: Money {
+                value: round_to_currency(entry_minor as f64 / factor, &amount.unit),
+                unit: amount.unit.clone(),
+            },
+            status: RecognitionStatus::Scheduled,
+            recognized_at: None,
+        });
+        period_start = period_end;
+    }
+
+    Ok(entries)
+}
+
+/// Recognized and deferred revenue of a schedule as of a date
+///
+/// Entries whose period has ended by `as_of` count as recognized; the rest of
+/// the charge is deferred.
+pub fn balance_at(
+    schedule: &RevenueRecognitionSchedule,
+    as_of: DateTime<Utc>,
+) -> RevenueRecognitionBalance {
+    let unit = &schedule.total_amount.unit;
+    let recognized: f64 = schedule
+        .entries
+        .iter()
+        .filter(|e| e.period_end <= as_of)
+        .map(|e| e.amount.value)
+        .sum();
+    let recognized = round_to_currency(recognized, unit);
+
+    RevenueRecognitionBalance {
+        charge_id: schedule.charge_id,
+        as_of,
+        recognized: Money {
+            value: recognized,
+            unit: unit.clone(),
+        },
+        deferred: Money {
+            value: round_to_currency(schedule.total_amount.value - recognized, unit),
+            unit: unit.clone(),
+        },
+    }
+}
+
+/// Helper functions
+fn recognition_status_to_string(status: &RecognitionStatus) -> String {
+    match status {
+        RecognitionStatus::Scheduled => "SCHEDULED".to_string(),
+        RecognitionStatus::Recognized => "RECOGNIZED".to_string(),
+    }
+}
+
+fn string_to_recognition_status(s: &str) -> RecognitionStatus {
+    match s {
+        "RECOGNIZED" => RecognitionStatus::Recognized,
+        _ => RecognitionStatus::Scheduled,
+    }
+}
+
+/// Internal row structures
+#[derive(Debug, FromRow)]
+struct ScheduleRow {
+    id: Uuid,
+    charge_id: Uuid,
+    customer_id: Uuid,
+    total_amount_value: f64,
+    total_amount_unit: String,
+    service_period_start: DateTime<Utc>,
+    service_period_end: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct EntryRow {
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    amount_value: f64,
+    status: String,
+    recognized_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,215 @@
+//! Charging Session Event Log
+//!
+//! Append-only record of every reservation, rating and balance change taken in
+//! a charging session, so customer care can reconstruct why a subscriber was
+//! charged. Events are never updated; they are only removed once older than
+//! the retention period.
+
+use crate::error::RevenueError;
+use crate::models::{ChargingEventType, ChargingSessionEvent, Money, RecordChargingEventRequest};
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Default retention, long enough to cover a year of billing disputes
+pub const DEFAULT_RETENTION_DAYS: i64 = 400;
+
+/// Charging session event log
+pub struct ChargingSessionLog {
+    pool: PgPool,
+    retention: Duration,
+}
+
+impl ChargingSessionLog {
+    /// Create a new session log with the default retention
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            retention: Duration::days(DEFAULT_RETENTION_DAYS),
+        }
+    }
+
+    /// Keep events for the given period instead of the default
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Append an event to the log
+    pub async fn record(
+        &self,
+        request: RecordChargingEventRequest,
+    ) -> Result<ChargingSessionEvent, RevenueError> {
+        let mut conn = self.pool.acquire().await?;
+        record_event(&mut conn, request).await
+    }
+
+    /// Get all events of a session in the order they occurred
+    pub async fn get_session_events(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Vec<ChargingSessionEvent>, RevenueError> {
+        let rows = sqlx::query_as::<_, SessionEventRow>(
+            "SELECT id, session_id, subscriber_id, event_type,
+             amount_value::FLOAT8 AS amount_value, amount_unit,
+             balance_after_value::FLOAT8 AS balance_after_value, balance_after_unit,
+             description, occurred_at
+             FROM charging_session_events WHERE session_id = $1
+             ORDER BY occurred_at, id",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(SessionEventRow::into_event).collect())
+    }
+
+    /// Get a subscriber's events within a time range in the order they occurred
+    pub async fn get_subscriber_events(
+        &self,
+        subscriber_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ChargingSessionEvent>, RevenueError> {
+        let rows = sqlx::query_as::<_, SessionEventRow>(
+            "SELECT id, session_id, subscriber_id, event_type,
+             amount_value::FLOAT8 AS amount_value, amount_unit,
+             balance_after_value::FLOAT8 AS balance_after_value, balance_after_unit,
+             description, occurred_at
+             FROM charging_session_events
+             WHERE subscriber_id = $1 AND occurred_at >= $2 AND occurred_at < $3
+             ORDER BY occurred_at, id",
+        )
+        .bind(subscriber_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(SessionEventRow::into_event).collect())
+    }
+
+    /// Remove events older than the retention period
+    pub async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64, RevenueError> {
+        let result = sqlx::query("DELETE FROM charging_session_events WHERE occurred_at < $1")
+            .bind(now - self.retention)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Periodically remove events older than the retention period
+    pub fn spawn_retention(
+        self: Arc<Self>,
+        check_interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                match self.purge_expired(Utc::now()).await {
+                    Ok(purged) if purged > 0 => {
+                        info!("Purged {} expired charging session events", purged)
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Charging session event purge failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Append an event to the log on the given connection, so it can be written
+/// in the transaction that takes the decision it records
+pub(crate) async fn record_event(
+    conn: &mut PgConnection,
+    request: RecordChargingEventRequest,
+) -> Result<ChargingSessionEvent, RevenueError> {
+    let event = ChargingSessionEvent {
+        id: Uuid::new_v4(),
+        session_id: request.session_id,
+        subscriber_id: request.subscriber_id,
+        event_type: request.event_type,
+        amount: request.amount,
+        balance_after: request.balance_after,
+        description: request.description,
+        occurred_at: Utc::now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO charging_session_events (id, session_id, subscriber_id, event_type,
+         amount_value, amount_unit, balance_after_value, balance_after_unit, description,
+         occurred_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+    )
+    .bind(event.id)
+    .bind(event.session_id)
+    .bind(event.subscriber_id)
+    .bind(event_type_to_string(&event.event_type))
+    .bind(event.amount.value)
+    .bind(&event.amount.unit)
+    .bind(event.balance_after.as_ref().map(|m| m.value))
+    .bind(event.balance_after.as_ref().map(|m| m.unit.clone()))
+    .bind(&event.description)
+    .bind(event.occurred_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(event)
+}
+
+/// Helper functions
+fn event_type_to_string(event_type: &ChargingEventType) -> String {
+    match event_type {
+        ChargingEventType::Reservation => "RESERVATION".to_string(),
+        ChargingEventType::Rating => "RATING".to_string(),
+        ChargingEventType::BalanceChange => "BALANCE_CHANGE".to_string(),
+    }
+}
+
+fn string_to_event_type(s: &str) -> ChargingEventType {
+    match s {
+        "RESERVATION" => ChargingEventType::Reservation,
+        "BALANCE_CHANGE" => ChargingEventType::BalanceChange,
+        _ => ChargingEventType::Rating,
+    }
+}
+
+/// Internal row structure
+#[derive(Debug, FromRow)]
+struct SessionEventRow {
+    id: Uuid,
+    session_id: Uuid,
+    subscriber_id: Uuid,
+    event_type: String,
+    amount_value: f64,
+    amount_unit: String,
+    balance_after_value: Option<f64>,
+    balance_after_unit: Option<String>,
+    description: Option<String>,
+    occurred_at: DateTime<Utc>,
+}
+
+impl SessionEventRow {
+    fn into_event(self) -> ChargingSessionEvent {
+        ChargingSessionEvent {
+            id: self.id,
+            session_id: self.session_id,
+            subscriber_id: self.subscriber_id,
+            event_type: string_to_event_type(&self.event_type),
+            amount: Money {
+                value: self.amount_value,
+                unit: self.amount_unit.clone(),
+            },
+            balance_after: self.balance_after_value.map(|value| Money {
+                value,
+                unit: self.balance_after_unit.unwrap_or(self.amount_unit),
+            }),
+            description: self.description,
+            occurred_at: self.occurred_at,
+        }
+    }
+}
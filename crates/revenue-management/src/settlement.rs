@@ -3,7 +3,9 @@
 //! Handles partner revenue sharing and settlement
 
 use crate::error::RevenueError;
-use crate::models::{Money, PartnerSettlement, SettlementRule, SettlementStatus};
+use crate::models::{
+    Money, PartnerSettlement, PartnerSettlementConfig, SettlementRule, SettlementStatus,
+};
 use chrono::{DateTime, Utc};
 use log::info;
 use sqlx::{FromRow, PgPool};
@@ -49,7 +51,44 @@ impl SettlementEngine {
         Ok(rule.id)
     }
 
+    /// Set the settlement configuration for a partner
+    pub async fn set_partner_config(
+        &self,
+        config: PartnerSettlementConfig,
+    ) -> Result<(), RevenueError> {
+        if config.minimum_transfer.value < 0.0 {
+            return Err(RevenueError::Validation(
+                "Minimum transfer cannot be negative".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "INSERT INTO partner_settlement_configs (partner_id, minimum_transfer_value,
+             minimum_transfer_unit)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (partner_id) DO UPDATE SET
+             minimum_transfer_value = EXCLUDED.minimum_transfer_value,
+             minimum_transfer_unit = EXCLUDED.minimum_transfer_unit,
+             updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(config.partner_id)
+        .bind(config.minimum_transfer.value)
+        .bind(&config.minimum_transfer.unit)
+        .execute(&self.pool)
+        .await?;
+
+        info!(
+            "Set minimum transfer for partner {}: {} {}",
+            config.partner_id, config.minimum_transfer.value, config.minimum_transfer.unit
+        );
+        Ok(())
+    }
+
     /// Calculate settlement for a partner for a given period
+    ///
+    /// Figures are rounded to the currency's minor units. If the partner payable
+    /// (this period's share plus any balance carried in) is below the partner's
+    /// minimum transfer, no transfer is generated and the balance rolls over.
     pub async fn calculate_settlement(
         &self,
         partner_id: Uuid,
@@ -105,15 +144,28 @@ impl SettlementEngine {
             }
         }
 
-        let platform_share = total_revenue - partner_share;
+        let total_revenue = round_to_currency(total_revenue, &currency);
+        let partner_share = round_to_currency(partner_share, &currency);
+        let platform_share = round_to_currency(total_revenue - partner_share, &currency);
+
+        // Apply the partner's minimum transfer, rolling small balances forward
+        let carried_in = self.get_carried_balance(partner_id, period_start).await?;
+        let minimum_transfer = self.get_minimum_transfer(partner_id).await?;
+        let payable = round_to_currency(partner_share + carried_in, &currency);
+        let (transfer_amount, carried_forward, status) = if payable < minimum_transfer {
+            (0.0, payable, SettlementStatus::CarriedOver)
+        } else {
+            (payable, 0.0, SettlementStatus::Calculated)
+        };
 
         let settlement_id = Uuid::new_v4();
         let currency_clone = currency.clone();
         sqlx::query(
             "INSERT INTO partner_settlements (id, partner_id, settlement_period_start, 
              settlement_period_end, total_revenue_value, total_revenue_unit, partner_share_value,
-             partner_share_unit, platform_share_value, platform_share_unit, status)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+             partner_share_unit, platform_share_value, platform_share_unit, status,
+             carried_in_value, transfer_amount_value, carried_forward_value)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
         )
         .bind(settlement_id)
         .bind(partner_id)
@@ -125,13 +177,25 @@ impl SettlementEngine {
         .bind(&currency_clone)
         .bind(platform_share)
         .bind(&currency)
-        .bind(settlement_status_to_string(&SettlementStatus::Calculated))
+        .bind(settlement_status_to_string(&status))
+        .bind(carried_in)
+        .bind(transfer_amount)
+        .bind(carried_forward)
         .execute(&self.pool)
         .await?;
 
         info!(
-            "Settlement calculated: total_revenue={} {}, partner_share={} {}, platform_share={} {}",
-            total_revenue, currency, partner_share, currency, platform_share, currency
+            "Settlement calculated: total_revenue={} {}, partner_share={} {}, platform_share={} {}, transfer={} {}, carried_forward={} {}",
+            total_revenue,
+            currency,
+            partner_share,
+            currency,
+            platform_share,
+            currency,
+            transfer_amount,
+            currency,
+            carried_forward,
+            currency
         );
 
         Ok(PartnerSettlement {
@@ -149,13 +213,57 @@ impl SettlementEngine {
             },
             platform_share: Money {
                 value: platform_share,
+                unit: currency.clone(),
+            },
+            carried_in: Money {
+                value: carried_in,
+                unit: currency.clone(),
+            },
+            transfer_amount: Money {
+                value: transfer_amount,
+                unit: currency.clone(),
+            },
+            carried_forward: Money {
+                value: carried_forward,
                 unit: currency,
             },
-            status: SettlementStatus::Calculated,
+            status,
             settlement_date: None,
         })
     }
 
+    /// Get the balance carried forward by the partner's most recent settlement before `before`
+    async fn get_carried_balance(
+        &self,
+        partner_id: Uuid,
+        before: DateTime<Utc>,
+    ) -> Result<f64, RevenueError> {
+        let carried: Option<f64> = sqlx::query_scalar(
+            "SELECT carried_forward_value::FLOAT8 FROM partner_settlements
+             WHERE partner_id = $1 AND settlement_period_end <= $2 AND status != 'REJECTED'
+             ORDER BY settlement_period_end DESC LIMIT 1",
+        )
+        .bind(partner_id)
+        .bind(before)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(carried.unwrap_or(0.0))
+    }
+
+    /// Get the partner's minimum transfer amount (zero when not configured)
+    async fn get_minimum_transfer(&self, partner_id: Uuid) -> Result<f64, RevenueError> {
+        let minimum: Option<f64> = sqlx::query_scalar(
+            "SELECT minimum_transfer_value::FLOAT8 FROM partner_settlement_configs
+             WHERE partner_id = $1",
+        )
+        .bind(partner_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(minimum.unwrap_or(0.0))
+    }
+
     /// Approve a settlement
     pub async fn approve_settlement(&self, settlement_id: Uuid) -> Result<(), RevenueError> {
         sqlx::query(
@@ -194,7 +302,8 @@ impl SettlementEngine {
         let row = sqlx::query_as::<_, SettlementRow>(
             "SELECT id, partner_id, settlement_period_start, settlement_period_end,
              total_revenue_value, total_revenue_unit, partner_share_value, partner_share_unit,
-             platform_share_value, platform_share_unit, status, settlement_date
+             platform_share_value, platform_share_unit, status, settlement_date,
+             carried_in_value, transfer_amount_value, carried_forward_value
              FROM partner_settlements WHERE id = $1",
         )
         .bind(settlement_id)
@@ -219,6 +328,18 @@ impl SettlementEngine {
             },
             platform_share: Money {
                 value: r.platform_share_value,
+                unit: r.platform_share_unit.clone(),
+            },
+            carried_in: Money {
+                value: r.carried_in_value,
+                unit: r.platform_share_unit.clone(),
+            },
+            transfer_amount: Money {
+                value: r.transfer_amount_value,
+                unit: r.platform_share_unit.clone(),
+            },
+            carried_forward: Money {
+                value: r.carried_forward_value,
                 unit: r.platform_share_unit,
             },
             status: string_to_settlement_status(&r.status),
@@ -234,7 +355,8 @@ impl SettlementEngine {
         let rows = sqlx::query_as::<_, SettlementRow>(
             "SELECT id, partner_id, settlement_period_start, settlement_period_end,
              total_revenue_value, total_revenue_unit, partner_share_value, partner_share_unit,
-             platform_share_value, platform_share_unit, status, settlement_date
+             platform_share_value, platform_share_unit, status, settlement_date,
+             carried_in_value, transfer_amount_value, carried_forward_value
              FROM partner_settlements WHERE partner_id = $1 ORDER BY settlement_period_start DESC",
         )
         .bind(partner_id)
@@ -258,6 +380,18 @@ impl SettlementEngine {
                 },
                 platform_share: Money {
                     value: r.platform_share_value,
+                    unit: r.platform_share_unit.clone(),
+                },
+                carried_in: Money {
+                    value: r.carried_in_value,
+                    unit: r.platform_share_unit.clone(),
+                },
+                transfer_amount: Money {
+                    value: r.transfer_amount_value,
+                    unit: r.platform_share_unit.clone(),
+                },
+                carried_forward: Money {
+                    value: r.carried_forward_value,
                     unit: r.platform_share_unit,
                 },
                 status: string_to_settlement_status(&r.status),
@@ -267,6 +401,11 @@ impl SettlementEngine {
     }
 
     /// Get settlement rules for a partner
+    ///
+    /// Besides the partner's own settlement rules, the revenue-share terms of
+    /// its TMF668 partner contracts apply, with the partner ID being the
+    /// partner's party role. Contracts that were activated count over their
+    /// effective period, up to their termination.
     async fn get_settlement_rules(
         &self,
         partner_id: Uuid,
@@ -274,11 +413,21 @@ impl SettlementEngine {
         period_end: DateTime<Utc>,
     ) -> Result<Vec<SettlementRule>, RevenueError> {
         let rows = sqlx::query_as::<_, SettlementRuleRow>(
-            "SELECT id, partner_id, product_offering_id, revenue_share_percentage, valid_from, valid_to
+            "SELECT id, partner_id, product_offering_id, revenue_share_percentage::FLOAT8 AS revenue_share_percentage,
+             valid_from, valid_to
              FROM settlement_rules
              WHERE partner_id = $1
              AND valid_from <= $2
-             AND (valid_to IS NULL OR valid_to >= $3)"
+             AND (valid_to IS NULL OR valid_to >= $3)
+             UNION ALL
+             SELECT s.id, c.party_role_id, s.product_offering_id, s.revenue_share_percentage::FLOAT8,
+             c.effective_from, LEAST(c.effective_to, c.termination_date)
+             FROM partner_contract_revenue_shares s
+             INNER JOIN partner_contracts c ON c.id = s.contract_id
+             WHERE c.party_role_id = $1 AND c.activation_date IS NOT NULL
+             AND c.effective_from <= $2
+             AND (LEAST(c.effective_to, c.termination_date) IS NULL
+                  OR LEAST(c.effective_to, c.termination_date) >= $3)"
         )
         .bind(partner_id)
         .bind(period_end)
@@ -300,6 +449,21 @@ impl SettlementEngine {
     }
 }
 
+/// Number of minor units (decimal places) for an ISO 4217 currency code
+pub fn currency_minor_units(currency: &str) -> u32 {
+    match currency.to_uppercase().as_str() {
+        "JPY" | "KRW" | "CLP" | "ISK" | "VND" | "XAF" | "XOF" => 0,
+        "BHD" | "KWD" | "OMR" | "JOD" | "TND" | "LYD" | "IQD" => 3,
+        _ => 2,
+    }
+}
+
+/// Round an amount half away from zero to the currency's minor units
+pub fn round_to_currency(value: f64, currency: &str) -> f64 {
+    let factor = 10f64.powi(currency_minor_units(currency) as i32);
+    (value * factor).round() / factor
+}
+
 /// Helper functions
 fn settlement_status_to_string(status: &SettlementStatus) -> String {
     match status {
@@ -308,6 +472,7 @@ fn settlement_status_to_string(status: &SettlementStatus) -> String {
         SettlementStatus::Approved => "APPROVED".to_string(),
         SettlementStatus::Paid => "PAID".to_string(),
         SettlementStatus::Rejected => "REJECTED".to_string(),
+        SettlementStatus::CarriedOver => "CARRIED_OVER".to_string(),
     }
 }
 
@@ -318,6 +483,7 @@ fn string_to_settlement_status(s: &str) -> SettlementStatus {
         "APPROVED" => SettlementStatus::Approved,
         "PAID" => SettlementStatus::Paid,
         "REJECTED" => SettlementStatus::Rejected,
+        "CARRIED_OVER" => SettlementStatus::CarriedOver,
         _ => SettlementStatus::Pending,
     }
 }
@@ -344,6 +510,9 @@ struct SettlementRow {
     platform_share_unit: String,
     status: String,
     settlement_date: Option<DateTime<Utc>>,
+    carried_in_value: f64,
+    transfer_amount_value: f64,
+    carried_forward_value: f64,
 }
 
 #[derive(Debug, FromRow)]
@@ -0,0 +1,195 @@
+//! Tax Calculation
+//!
+//! Computes the taxes that apply to a rated line item from its jurisdiction and
+//! product type, returning one itemized line per tax. The calculator is
+//! pluggable; [`TableTaxCalculator`] ships with a rate table covering Brazil
+//! (ICMS, PIS, COFINS) and EU VAT.
+
+use crate::error::RevenueError;
+use crate::models::{Money, TaxJurisdiction, TaxLine, TaxRate, TaxType, TaxableItem};
+use crate::settlement::round_to_currency;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Tax calculator interface
+#[async_trait]
+pub trait TaxCalculator: Send + Sync {
+    /// Compute the itemized taxes applicable to a line item
+    async fn calculate(&self, item: &TaxableItem) -> Result<Vec<TaxLine>, RevenueError>;
+}
+
+/// Table-driven tax calculator
+///
+/// For each tax type the most specific matching rate wins: a region- and
+/// product-specific rate beats a region-wide one, which beats a product-specific
+/// country rate, which beats the country-wide rate.
+#[derive(Debug, Clone)]
+pub struct TableTaxCalculator {
+    rates: Vec<TaxRate>,
+}
+
+impl TableTaxCalculator {
+    /// Create a calculator from a rate table
+    pub fn new(rates: Vec<TaxRate>) -> Self {
+        Self { rates }
+    }
+
+    /// Rates applicable to the item, one per tax type
+    fn applicable_rates(&self, item: &TaxableItem) -> Vec<&TaxRate> {
+        let jurisdiction = &item.jurisdiction;
+        let mut best: HashMap<TaxType, (u8, &TaxRate)> = HashMap::new();
+
+        for rate in &self.rates {
+            if !rate.country.eq_ignore_ascii_case(&jurisdiction.country) {
+                continue;
+            }
+            let region_match = match (&rate.region, &jurisdiction.region) {
+                (None, _) => false,
+                (Some(r), Some(j)) if r.eq_ignore_ascii_case(j) => true,
+                _ => continue,
+            };
+            let product_match = match &rate.product_type {
+                None => false,
+                Some(p) if p.eq_ignore_ascii_case(&item.product_type) => true,
+                _ => continue,
+            };
+
+            let specificity = u8::from(region_match) * 2 + u8::from(product_match);
+            match best.get(&rate.tax_type) {
+                Some((current, _)) if *current >= specificity => {}
+                _ => {
+                    best.insert(rate.tax_type, (specificity, rate));
+                }
+            }
+        }
+
+        let mut rates: Vec<&TaxRate> = best.into_values().map(|(_, rate)| rate).collect();
+        rates.sort_by_key(|rate| rate.tax_type.code());
+        rates
+    }
+}
+
+#[async_trait]
+impl TaxCalculator for TableTaxCalculator {
+    async fn calculate(&self, item: &TaxableItem) -> Result<Vec<TaxLine>, RevenueError> {
+        if item.amount.value < 0.0 {
+            return Err(RevenueError::Validation(
+                "Taxable amount cannot be negative".to_string(),
+            ));
+        }
+
+        let rates = self.applicable_rates(item);
+
+        // Inclusive taxes are levied on the amount grossed up by all of them
+        let inclusive_rate: f64 = rates
+            .iter()
+            .filter(|r| r.inclusive)
+            .map(|r| r.rate_percentage / 100.0)
+            .sum();
+        if inclusive_rate >= 1.0 {
+            return Err(RevenueError::Configuration(format!(
+                "Inclusive tax rates for {} add up to 100% or more",
+                item.jurisdiction.country
+            )));
+        }
+        let unit = &item.amount.unit;
+        let gross_base = round_to_currency(item.amount.value / (1.0 - inclusive_rate), unit);
+
+        Ok(rates
+            .into_iter()
+            .map(|rate| {
+                let base = if rate.inclusive {
+                    gross_base
+                } else {
+                    item.amount.value
+                };
+                TaxLine {
+                    tax_type: rate.tax_type,
+                    jurisdiction: item.jurisdiction.clone(),
+                    rate_percentage: rate.rate_percentage,
+                    taxable_amount: Money {
+                        value: base,
+                        unit: unit.clone(),
+                    },
+                    tax_amount: Money {
+                        value: round_to_currency(base * rate.rate_percentage / 100.0, unit),
+                        unit: unit.clone(),
+                    },
+                }
+            })
+            .collect())
+    }
+}
+
+fn rate(
+    tax_type: TaxType,
+    country: &str,
+    region: Option<&str>,
+    rate_percentage: f64,
+    inclusive: bool,
+) -> TaxRate {
+    TaxRate {
+        tax_type,
+        country: country.to_string(),
+        region: region.map(str::to_string),
+        product_type: None,
+        rate_percentage,
+        inclusive,
+    }
+}
+
+impl Default for TableTaxCalculator {
+    /// Standard rates for telecommunication services; replace with the
+    /// operator's maintained table in production
+    fn default() -> Self {
+        let mut rates = vec![
+            // Brazil: ICMS by state, PIS/COFINS under the cumulative regime
+            rate(TaxType::Icms, "BR", None, 18.0, true),
+            rate(TaxType::Icms, "BR", Some("SP"), 18.0, true),
+            rate(TaxType::Icms, "BR", Some("RJ"), 20.0, true),
+            rate(TaxType::Icms, "BR", Some("MG"), 18.0, true),
+            rate(TaxType::Pis, "BR", None, 0.65, true),
+            rate(TaxType::Cofins, "BR", None, 3.0, true),
+        ];
+
+        // EU standard VAT rates
+        for (country, vat) in [
+            ("AT", 20.0),
+            ("BE", 21.0),
+            ("DE", 19.0),
+            ("ES", 21.0),
+            ("FR", 20.0),
+            ("IE", 23.0),
+            ("IT", 22.0),
+            ("NL", 21.0),
+            ("PL", 23.0),
+            ("PT", 23.0),
+            ("SE", 25.0),
+        ] {
+            rates.push(rate(TaxType::Vat, country, None, vat, false));
+        }
+
+        Self::new(rates)
+    }
+}
+
+/// Sum of the tax lines in the given currency
+pub fn total_tax(lines: &[TaxLine], unit: &str) -> Money {
+    Money {
+        value: round_to_currency(lines.iter().map(|l| l.tax_amount.value).sum(), unit),
+        unit: unit.to_string(),
+    }
+}
+
+/// Taxable item for a rated amount
+pub fn taxable_item(
+    product_type: &str,
+    amount: &Money,
+    jurisdiction: &TaxJurisdiction,
+) -> TaxableItem {
+    TaxableItem {
+        product_type: product_type.to_string(),
+        amount: amount.clone(),
+        jurisdiction: jurisdiction.clone(),
+    }
+}
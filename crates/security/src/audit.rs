@@ -1,23 +1,269 @@
 //! Audit Logging for Security Events
 //!
-//! Logs all security-related events for compliance and forensics
+//! Logs all security-related events for compliance and forensics.
+//! Entries are hash-chained so that edits or deletions can be detected.
+//!
+//! An [`AuditPolicy`] sorts events into categories (auth, data access, admin),
+//! each with its own retention, optional sinks and a choice of whether events
+//! are stored at all. Events that are not stored are counted, never silently
+//! lost. Entries past retention have their content pruned but keep their hash
+//! chain columns, so the chain still links.
 
 use crate::error::SecurityError;
-use crate::models::{AuditEventType, AuditLogEntry, AuditResult};
-use chrono::Utc;
-use log::info;
-use sqlx::{FromRow, PgPool};
+use crate::models::{
+    AuditCategory, AuditCategoryStats, AuditChainBreak, AuditChainVerification, AuditEventType,
+    AuditLogEntry, AuditPurgeResult, AuditResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use log::{debug, error, info, warn};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Advisory lock key serializing appends to the audit hash chain
+const AUDIT_CHAIN_LOCK_KEY: i64 = 0x4155_4449_545f_4c4f;
+
+/// `prev_hash` of the first chained entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Default retention of authentication events
+pub const DEFAULT_AUTH_RETENTION_DAYS: i64 = 365;
+
+/// Default retention of routine data access events
+pub const DEFAULT_DATA_ACCESS_RETENTION_DAYS: i64 = 90;
+
+/// Default retention of administrative events (seven years)
+pub const DEFAULT_ADMIN_RETENTION_DAYS: i64 = 2555;
+
+/// Destination audit events are forwarded to, e.g. a SIEM
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Deliver an audit event
+    async fn send(&self, entry: &AuditLogEntry) -> Result<(), SecurityError>;
+}
+
+/// Rule assigning matching events to a category; unset fields match any event
+#[derive(Debug, Clone)]
+pub struct AuditRule {
+    pub category: AuditCategory,
+    pub event_type: Option<AuditEventType>,
+    pub resource: Option<String>,
+    pub action: Option<String>,
+    pub result: Option<AuditResult>,
+}
+
+impl AuditRule {
+    /// Rule matching every event
+    pub fn new(category: AuditCategory) -> Self {
+        Self {
+            category,
+            event_type: None,
+            resource: None,
+            action: None,
+            result: None,
+        }
+    }
+
+    /// Only match events of this type
+    pub fn with_event_type(mut self, event_type: AuditEventType) -> Self {
+        self.event_type = Some(event_type);
+        self
+    }
+
+    /// Only match events on this resource
+    pub fn with_resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = Some(resource.into());
+        self
+    }
+
+    /// Only match events with this action
+    pub fn with_action(mut self, action: impl Into<String>) -> Self {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Only match events with this result
+    pub fn with_result(mut self, result: AuditResult) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    fn matches(
+        &self,
+        event_type: &AuditEventType,
+        resource: Option<&str>,
+        action: Option<&str>,
+        result: &AuditResult,
+    ) -> bool {
+        self.event_type.as_ref().is_none_or(|t| t == event_type)
+            && self.resource.as_deref().is_none_or(|r| Some(r) == resource)
+            && self.action.as_deref().is_none_or(|a| Some(a) == action)
+            && self.result.as_ref().is_none_or(|r| r == result)
+    }
+}
+
+/// Handling of the events in one category
+#[derive(Clone)]
+pub struct AuditCategoryPolicy {
+    /// Time after which entry content is pruned
+    pub retention: chrono::Duration,
+    /// Whether events are written to the audit log; events that are not still
+    /// reach the sinks and are counted as dropped
+    pub store: bool,
+    pub sinks: Vec<Arc<dyn AuditSink>>,
+}
+
+impl AuditCategoryPolicy {
+    fn retained_for_days(days: i64) -> Self {
+        Self {
+            retention: chrono::Duration::days(days),
+            store: true,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+/// Audit event categorization, retention and routing
+///
+/// Rules are evaluated in the order added and the first match decides the
+/// category; events no rule matches fall back to
+/// [`AuditEventType::default_category`].
+#[derive(Clone)]
+pub struct AuditPolicy {
+    rules: Vec<AuditRule>,
+    categories: HashMap<AuditCategory, AuditCategoryPolicy>,
+}
+
+impl Default for AuditPolicy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            categories: HashMap::from([
+                (
+                    AuditCategory::Auth,
+                    AuditCategoryPolicy::retained_for_days(DEFAULT_AUTH_RETENTION_DAYS),
+                ),
+                (
+                    AuditCategory::DataAccess,
+                    AuditCategoryPolicy::retained_for_days(DEFAULT_DATA_ACCESS_RETENTION_DAYS),
+                ),
+                (
+                    AuditCategory::Admin,
+                    AuditCategoryPolicy::retained_for_days(DEFAULT_ADMIN_RETENTION_DAYS),
+                ),
+            ]),
+        }
+    }
+}
+
+impl AuditPolicy {
+    /// Add a categorization rule
+    pub fn with_rule(mut self, rule: AuditRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Set how long a category's entries are kept
+    pub fn with_retention(mut self, category: AuditCategory, retention: chrono::Duration) -> Self {
+        self.category_mut(category).retention = retention;
+        self
+    }
+
+    /// Set whether a category's events are written to the audit log
+    pub fn with_storage(mut self, category: AuditCategory, store: bool) -> Self {
+        self.category_mut(category).store = store;
+        self
+    }
+
+    /// Forward a category's events to a sink
+    pub fn with_sink(mut self, category: AuditCategory, sink: Arc<dyn AuditSink>) -> Self {
+        self.category_mut(category).sinks.push(sink);
+        self
+    }
+
+    /// Category of an event
+    pub fn categorize(
+        &self,
+        event_type: &AuditEventType,
+        resource: Option<&str>,
+        action: Option<&str>,
+        result: &AuditResult,
+    ) -> AuditCategory {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(event_type, resource, action, result))
+            .map(|rule| rule.category)
+            .unwrap_or_else(|| event_type.default_category())
+    }
+
+    /// Handling of a category
+    pub fn category_policy(&self, category: AuditCategory) -> &AuditCategoryPolicy {
+        &self.categories[&category]
+    }
+
+    fn category_mut(&mut self, category: AuditCategory) -> &mut AuditCategoryPolicy {
+        self.categories
+            .get_mut(&category)
+            .expect("all categories are configured")
+    }
+}
+
+#[derive(Default)]
+struct CategoryCounters {
+    recorded: AtomicU64,
+    dropped: AtomicU64,
+    sink_failures: AtomicU64,
+}
+
 /// Audit Logger
 pub struct AuditLogger {
     pool: PgPool,
+    policy: AuditPolicy,
+    counters: HashMap<AuditCategory, CategoryCounters>,
 }
 
 impl AuditLogger {
     /// Create a new audit logger
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            policy: AuditPolicy::default(),
+            counters: [
+                AuditCategory::Auth,
+                AuditCategory::DataAccess,
+                AuditCategory::Admin,
+            ]
+            .into_iter()
+            .map(|category| (category, CategoryCounters::default()))
+            .collect(),
+        }
+    }
+
+    /// Categorize, retain and route events according to `policy`
+    pub fn with_policy(mut self, policy: AuditPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Recorded, dropped and sink failure counts per category
+    pub fn stats(&self) -> Vec<AuditCategoryStats> {
+        let mut stats: Vec<AuditCategoryStats> = self
+            .counters
+            .iter()
+            .map(|(category, counters)| AuditCategoryStats {
+                category: *category,
+                recorded: counters.recorded.load(Ordering::Relaxed),
+                dropped: counters.dropped.load(Ordering::Relaxed),
+                sink_failures: counters.sink_failures.load(Ordering::Relaxed),
+            })
+            .collect();
+        stats.sort_by_key(|s| category_to_string(&s.category));
+        stats
     }
 
     /// Log an audit event
@@ -33,32 +279,232 @@ impl AuditLogger {
         ip_address: Option<String>,
         user_agent: Option<String>,
         details: Option<serde_json::Value>,
+    ) -> Result<Uuid, SecurityError> {
+        self.log_event_on(
+            None,
+            event_type,
+            identity_id,
+            user_id,
+            resource,
+            action,
+            result,
+            ip_address,
+            user_agent,
+            details,
+        )
+        .await
+    }
+
+    /// Log an audit event in the caller's transaction, so it is only stored
+    /// if the change it describes is committed
+    ///
+    /// Sinks are sent the event straight away, before the caller commits.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn log_event_in(
+        &self,
+        conn: &mut PgConnection,
+        event_type: AuditEventType,
+        identity_id: Option<Uuid>,
+        user_id: Option<String>,
+        resource: Option<String>,
+        action: Option<String>,
+        result: AuditResult,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+        details: Option<serde_json::Value>,
+    ) -> Result<Uuid, SecurityError> {
+        self.log_event_on(
+            Some(conn),
+            event_type,
+            identity_id,
+            user_id,
+            resource,
+            action,
+            result,
+            ip_address,
+            user_agent,
+            details,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn log_event_on(
+        &self,
+        conn: Option<&mut PgConnection>,
+        event_type: AuditEventType,
+        identity_id: Option<Uuid>,
+        user_id: Option<String>,
+        resource: Option<String>,
+        action: Option<String>,
+        result: AuditResult,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+        details: Option<serde_json::Value>,
     ) -> Result<Uuid, SecurityError> {
         let id = Uuid::new_v4();
-        let timestamp = Utc::now();
+        // Postgres keeps microseconds; truncate so the stored row hashes the same
+        let timestamp = Utc::now().trunc_subsecs(6);
+        let category =
+            self.policy
+                .categorize(&event_type, resource.as_deref(), action.as_deref(), &result);
+        let category_policy = self.policy.category_policy(category);
+        let counters = &self.counters[&category];
+
+        let entry = AuditLogRow {
+            id,
+            event_type: event_type_to_string(&event_type),
+            identity_id,
+            user_id,
+            resource,
+            action,
+            result: result_to_string(&result),
+            ip_address,
+            user_agent,
+            details,
+            timestamp,
+        };
+
+        if category_policy.store {
+            match conn {
+                Some(conn) => append_entry(conn, &entry, category).await?,
+                None => self.append_to_chain(&entry, category).await?,
+            }
+            counters.recorded.fetch_add(1, Ordering::Relaxed);
+            info!("Audit event logged: {:?} - {:?}", event_type, result);
+        } else {
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                "Audit event not stored ({:?} storage disabled): {:?} - {:?}",
+                category, event_type, result
+            );
+        }
+
+        if !category_policy.sinks.is_empty() {
+            let log_entry = AuditLogEntry {
+                id,
+                event_type,
+                identity_id: entry.identity_id,
+                user_id: entry.user_id,
+                resource: entry.resource,
+                action: entry.action,
+                result,
+                ip_address: entry.ip_address,
+                user_agent: entry.user_agent,
+                details: entry.details,
+                timestamp,
+            };
+            for sink in &category_policy.sinks {
+                if let Err(e) = sink.send(&log_entry).await {
+                    counters.sink_failures.fetch_add(1, Ordering::Relaxed);
+                    warn!("Audit sink rejected event {}: {}", id, e);
+                }
+            }
+        }
+
+        Ok(id)
+    }
 
-        sqlx::query(
-            "INSERT INTO audit_logs (id, event_type, identity_id, user_id, resource, action,
-             result, ip_address, user_agent, details, timestamp)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+    /// Append an entry to the hash chain
+    async fn append_to_chain(
+        &self,
+        entry: &AuditLogRow,
+        category: AuditCategory,
+    ) -> Result<(), SecurityError> {
+        let mut tx = self.pool.begin().await?;
+        append_entry(&mut tx, entry, category).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Prune entries past their category's retention
+    ///
+    /// Pruning removes an entry's content but keeps its hash chain columns.
+    /// Pruned entries at the start of the chain are then deleted outright,
+    /// recording the last deleted entry's hash as a checkpoint the chain is
+    /// verified from; the most recent chained entry is always kept so new
+    /// entries can link to it. Entries written before categorization are
+    /// left alone.
+    pub async fn purge_expired(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<AuditPurgeResult, SecurityError> {
+        let mut pruned = 0;
+        for (category, policy) in &self.policy.categories {
+            let result = sqlx::query(
+                "UPDATE audit_logs
+                 SET identity_id = NULL, user_id = NULL, resource = NULL, action = NULL,
+                     ip_address = NULL, user_agent = NULL, details = NULL, pruned_at = $1
+                 WHERE category = $2 AND timestamp < $3 AND pruned_at IS NULL",
+            )
+            .bind(now)
+            .bind(category_to_string(category))
+            .bind(now - policy.retention)
+            .execute(&self.pool)
+            .await?;
+            pruned += result.rows_affected();
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Keep appends out while the chain head moves
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(AUDIT_CHAIN_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let removed: Vec<(i64, Option<String>)> = sqlx::query_as(
+            "DELETE FROM audit_logs
+             WHERE pruned_at IS NOT NULL
+             AND sequence_number < COALESCE(
+                 (SELECT MIN(sequence_number) FROM audit_logs
+                  WHERE pruned_at IS NULL AND sequence_number IS NOT NULL),
+                 (SELECT MAX(sequence_number) FROM audit_logs)
+             )
+             RETURNING sequence_number, entry_hash",
         )
-        .bind(id)
-        .bind(event_type_to_string(&event_type))
-        .bind(identity_id)
-        .bind(&user_id)
-        .bind(&resource)
-        .bind(&action)
-        .bind(result_to_string(&result))
-        .bind(&ip_address)
-        .bind(&user_agent)
-        .bind(&details)
-        .bind(timestamp)
-        .execute(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        let deleted = removed.len() as u64;
 
-        info!("Audit event logged: {:?} - {:?}", event_type, result);
+        if let Some((sequence_number, entry_hash)) = removed.into_iter().max_by_key(|r| r.0) {
+            sqlx::query(
+                "INSERT INTO audit_chain_checkpoints (sequence_number, entry_hash, created_at)
+                 VALUES ($1, $2, $3)",
+            )
+            .bind(sequence_number)
+            .bind(entry_hash.unwrap_or_default())
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
 
-        Ok(id)
+        tx.commit().await?;
+
+        if pruned > 0 || deleted > 0 {
+            info!(
+                "Audit retention pruned {} entries and deleted {}",
+                pruned, deleted
+            );
+        }
+
+        Ok(AuditPurgeResult { pruned, deleted })
+    }
+
+    /// Periodically prune entries past retention
+    pub fn spawn_retention(
+        self: Arc<Self>,
+        check_interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.purge_expired(Utc::now()).await {
+                    error!("Audit retention run failed: {}", e);
+                }
+            }
+        })
     }
 
     /// Log authentication event
@@ -277,6 +723,91 @@ impl AuditLogger {
         .await
     }
 
+    /// Verify the audit hash chain
+    ///
+    /// Entries written before chaining was introduced are skipped. Pruned
+    /// entries are checked for linkage only, as their content is gone. The
+    /// chain starts at the genesis hash, or at the newest retention
+    /// checkpoint once leading entries have been deleted.
+    pub async fn verify_chain(&self) -> Result<AuditChainVerification, SecurityError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Read checkpoint and entries from one consistent view of the chain
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(AUDIT_CHAIN_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        let checkpoint: Option<(i64, String)> = sqlx::query_as(
+            "SELECT sequence_number, entry_hash FROM audit_chain_checkpoints
+             ORDER BY sequence_number DESC
+             LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let rows = sqlx::query_as::<_, AuditChainRow>(
+            "SELECT id, event_type, identity_id, user_id, resource, action, result,
+             ip_address, user_agent, details, timestamp, sequence_number, prev_hash, entry_hash,
+             category, pruned_at
+             FROM audit_logs
+             WHERE sequence_number IS NOT NULL
+             ORDER BY sequence_number",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let mut entries_checked = 0;
+        let mut broken_at = None;
+        let (mut expected_sequence, mut expected_prev) = match checkpoint {
+            Some((sequence_number, hash)) => (sequence_number + 1, hash),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+
+        for row in &rows {
+            let reason = if row.sequence_number != expected_sequence {
+                Some(format!(
+                    "expected sequence number {}, found {}",
+                    expected_sequence, row.sequence_number
+                ))
+            } else if row.prev_hash.as_deref() != Some(&expected_prev) {
+                Some("previous hash does not match preceding entry".to_string())
+            } else if row.pruned_at.is_none()
+                && row.entry_hash.as_deref()
+                    != Some(&compute_entry_hash(
+                        row.prev_hash.as_deref().unwrap_or_default(),
+                        &row.entry,
+                        row.category.as_deref(),
+                    ))
+            {
+                Some("entry hash does not match entry content".to_string())
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                broken_at = Some(AuditChainBreak {
+                    entry_id: row.entry.id,
+                    sequence_number: row.sequence_number,
+                    reason,
+                });
+                break;
+            }
+
+            entries_checked += 1;
+            expected_prev = row.entry_hash.clone().unwrap_or_default();
+            expected_sequence = row.sequence_number + 1;
+        }
+
+        Ok(AuditChainVerification {
+            entries_checked,
+            broken_at,
+            verified_at: Utc::now(),
+        })
+    }
+
     /// Get audit logs for an identity
     pub async fn get_identity_logs(
         &self,
@@ -289,7 +820,7 @@ impl AuditLogger {
             "SELECT id, event_type, identity_id, user_id, resource, action, result,
              ip_address, user_agent, details, timestamp
              FROM audit_logs
-             WHERE identity_id = $1
+             WHERE identity_id = $1 AND pruned_at IS NULL
              ORDER BY timestamp DESC
              LIMIT $2",
         )
@@ -328,7 +859,7 @@ impl AuditLogger {
             "SELECT id, event_type, identity_id, user_id, resource, action, result,
              ip_address, user_agent, details, timestamp
              FROM audit_logs
-             WHERE event_type = $1
+             WHERE event_type = $1 AND pruned_at IS NULL
              ORDER BY timestamp DESC
              LIMIT $2",
         )
@@ -368,7 +899,7 @@ impl AuditLogger {
             "SELECT id, event_type, identity_id, user_id, resource, action, result,
              ip_address, user_agent, details, timestamp
              FROM audit_logs
-             WHERE timestamp >= $1 AND timestamp <= $2
+             WHERE timestamp >= $1 AND timestamp <= $2 AND pruned_at IS NULL
              ORDER BY timestamp DESC
              LIMIT $3",
         )
@@ -397,6 +928,198 @@ impl AuditLogger {
     }
 }
 
+/// Callback invoked when the integrity scan finds a broken chain
+pub type TamperCallback = Arc<dyn Fn(&AuditChainVerification) + Send + Sync>;
+
+/// Periodic audit chain integrity scanner
+///
+/// Runs on its own task and only reads the chain, so logging is never blocked
+/// by a scan. Each newly detected break is reported once as a high-severity
+/// `AuditTamperDetected` event and passed to the optional callback.
+pub struct AuditIntegrityMonitor {
+    logger: Arc<AuditLogger>,
+    scan_interval: Duration,
+    on_tamper: Option<TamperCallback>,
+    last_reported: Mutex<Option<AuditChainBreak>>,
+}
+
+impl AuditIntegrityMonitor {
+    /// Create a new monitor scanning every `scan_interval`
+    pub fn new(logger: Arc<AuditLogger>, scan_interval: Duration) -> Self {
+        Self {
+            logger,
+            scan_interval,
+            on_tamper: None,
+            last_reported: Mutex::new(None),
+        }
+    }
+
+    /// Set a callback to trigger when tampering is detected
+    pub fn with_callback(mut self, callback: TamperCallback) -> Self {
+        self.on_tamper = Some(callback);
+        self
+    }
+
+    /// Run a single integrity scan, alerting if a new break is found
+    pub async fn scan(&self) -> Result<AuditChainVerification, SecurityError> {
+        let verification = self.logger.verify_chain().await?;
+
+        let Some(chain_break) = verification.broken_at.clone() else {
+            return Ok(verification);
+        };
+
+        {
+            let mut last_reported = self
+                .last_reported
+                .lock()
+                .map_err(|e| SecurityError::Audit(e.to_string()))?;
+            if last_reported.as_ref() == Some(&chain_break) {
+                return Ok(verification);
+            }
+            *last_reported = Some(chain_break.clone());
+        }
+
+        error!(
+            "Audit log tampering detected at entry {} (sequence {}): {}",
+            chain_break.entry_id, chain_break.sequence_number, chain_break.reason
+        );
+
+        self.logger
+            .log_event(
+                AuditEventType::AuditTamperDetected,
+                None,
+                None,
+                Some("audit_log".to_string()),
+                Some("verify_chain".to_string()),
+                AuditResult::Failure,
+                None,
+                None,
+                Some(serde_json::json!({
+                    "severity": "HIGH",
+                    "entry_id": chain_break.entry_id,
+                    "sequence_number": chain_break.sequence_number,
+                    "reason": chain_break.reason,
+                    "entries_checked": verification.entries_checked
+                })),
+            )
+            .await?;
+
+        if let Some(callback) = &self.on_tamper {
+            callback(&verification);
+        }
+
+        Ok(verification)
+    }
+
+    /// Start scanning in the background
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.scan_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.scan().await {
+                    error!("Audit integrity scan failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Append an entry to the hash chain on the given connection
+async fn append_entry(
+    conn: &mut PgConnection,
+    entry: &AuditLogRow,
+    category: AuditCategory,
+) -> Result<(), SecurityError> {
+    // Serialize appends so every entry links to exactly one predecessor
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(AUDIT_CHAIN_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let last: Option<(i64, Option<String>)> = sqlx::query_as(
+        "SELECT sequence_number, entry_hash FROM audit_logs
+         WHERE sequence_number IS NOT NULL
+         ORDER BY sequence_number DESC
+         LIMIT 1",
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let (sequence_number, prev_hash) = match last {
+        Some((sequence_number, hash)) => (sequence_number + 1, hash.unwrap_or_default()),
+        None => (1, GENESIS_HASH.to_string()),
+    };
+    let category = category_to_string(&category);
+    let entry_hash = compute_entry_hash(&prev_hash, entry, Some(&category));
+
+    sqlx::query(
+        "INSERT INTO audit_logs (id, event_type, identity_id, user_id, resource, action,
+         result, ip_address, user_agent, details, timestamp, sequence_number, prev_hash, entry_hash,
+         category)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+    )
+    .bind(entry.id)
+    .bind(&entry.event_type)
+    .bind(entry.identity_id)
+    .bind(&entry.user_id)
+    .bind(&entry.resource)
+    .bind(&entry.action)
+    .bind(&entry.result)
+    .bind(&entry.ip_address)
+    .bind(&entry.user_agent)
+    .bind(&entry.details)
+    .bind(entry.timestamp)
+    .bind(sequence_number)
+    .bind(&prev_hash)
+    .bind(&entry_hash)
+    .bind(&category)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Hash an audit entry together with the hash of its predecessor
+///
+/// The category is hashed when set, so entries written before
+/// categorization keep their original hashes.
+fn compute_entry_hash(prev_hash: &str, entry: &AuditLogRow, category: Option<&str>) -> String {
+    let details = entry
+        .details
+        .as_ref()
+        .map(|d| d.to_string())
+        .unwrap_or_default();
+    let identity_id = entry
+        .identity_id
+        .map(|id| id.to_string())
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    for field in [
+        prev_hash,
+        &entry.id.to_string(),
+        &entry.event_type,
+        &identity_id,
+        entry.user_id.as_deref().unwrap_or_default(),
+        entry.resource.as_deref().unwrap_or_default(),
+        entry.action.as_deref().unwrap_or_default(),
+        &entry.result,
+        entry.ip_address.as_deref().unwrap_or_default(),
+        entry.user_agent.as_deref().unwrap_or_default(),
+        &details,
+        &entry.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+    ] {
+        hasher.update(field.as_bytes());
+        hasher.update(b"|");
+    }
+    if let Some(category) = category {
+        hasher.update(category.as_bytes());
+        hasher.update(b"|");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
 /// Helper functions
 fn event_type_to_string(event_type: &AuditEventType) -> String {
     match event_type {
@@ -413,6 +1136,7 @@ fn event_type_to_string(event_type: &AuditEventType) -> String {
         AuditEventType::AccountLocked => "ACCOUNT_LOCKED".to_string(),
         AuditEventType::AccountUnlocked => "ACCOUNT_UNLOCKED".to_string(),
         AuditEventType::SecurityPolicyViolation => "SECURITY_POLICY_VIOLATION".to_string(),
+        AuditEventType::AuditTamperDetected => "AUDIT_TAMPER_DETECTED".to_string(),
     }
 }
 
@@ -431,10 +1155,19 @@ fn string_to_event_type(s: &str) -> AuditEventType {
         "ACCOUNT_LOCKED" => AuditEventType::AccountLocked,
         "ACCOUNT_UNLOCKED" => AuditEventType::AccountUnlocked,
         "SECURITY_POLICY_VIOLATION" => AuditEventType::SecurityPolicyViolation,
+        "AUDIT_TAMPER_DETECTED" => AuditEventType::AuditTamperDetected,
         _ => AuditEventType::Authentication,
     }
 }
 
+fn category_to_string(category: &AuditCategory) -> String {
+    match category {
+        AuditCategory::Auth => "AUTH".to_string(),
+        AuditCategory::DataAccess => "DATA_ACCESS".to_string(),
+        AuditCategory::Admin => "ADMIN".to_string(),
+    }
+}
+
 fn result_to_string(result: &AuditResult) -> String {
     match result {
         AuditResult::Success => "SUCCESS".to_string(),
@@ -467,3 +1200,15 @@ struct AuditLogRow {
     details: Option<serde_json::Value>,
     timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+/// Internal row structure including hash chain columns
+#[derive(Debug, FromRow)]
+struct AuditChainRow {
+    #[sqlx(flatten)]
+    entry: AuditLogRow,
+    sequence_number: i64,
+    prev_hash: Option<String>,
+    entry_hash: Option<String>,
+    category: Option<String>,
+    pruned_at: Option<DateTime<Utc>>,
+}
@@ -11,6 +11,22 @@ pub enum SecurityError {
     #[error("OAuth error: {0}")]
     OAuth(String),
 
+    /// Device flow: the user has not yet approved or denied the request
+    #[error("authorization_pending")]
+    AuthorizationPending,
+
+    /// Device flow: the client polled too fast and must wait `interval` seconds
+    #[error("slow_down")]
+    SlowDown { interval: i64 },
+
+    /// Device flow: the user denied the request
+    #[error("access_denied")]
+    AccessDenied,
+
+    /// Device flow: the device code expired before it was approved
+    #[error("expired_token")]
+    ExpiredToken,
+
     #[error("MFA error: {0}")]
     Mfa(String),
 
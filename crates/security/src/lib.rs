@@ -1,10 +1,11 @@
 //! Security System
 //!
 //! This module provides comprehensive security capabilities including:
-//! - OAuth 2.0 / OIDC integration
+//! - OAuth 2.0 / OIDC integration, including the device authorization grant
 //! - Multi-factor authentication (MFA)
-//! - Role-based access control (RBAC)
-//! - Audit logging for security events
+//! - Role-based access control (RBAC) with policy import/export
+//! - Audit logging for security events with hash-chain tamper detection,
+//!   categorization and per-category retention
 
 pub mod audit;
 pub mod error;
@@ -13,7 +14,7 @@ pub mod models;
 pub mod oauth;
 pub mod rbac;
 
-pub use audit::AuditLogger;
+pub use audit::{AuditIntegrityMonitor, AuditLogger, AuditPolicy, AuditRule, AuditSink};
 pub use error::SecurityError;
 pub use mfa::MfaService;
 pub use oauth::OAuthProvider;
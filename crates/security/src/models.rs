@@ -27,6 +27,7 @@ pub enum GrantType {
     ClientCredentials,
     RefreshToken,
     Implicit,
+    DeviceCode,
 }
 
 /// OAuth Authorization Code
@@ -57,6 +58,20 @@ pub struct AccessToken {
     pub expires_at: DateTime<Utc>,
 }
 
+/// Device Authorization Response (RFC 8628 section 3.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    /// Code shown to the user, formatted `XXXX-XXXX`
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: String,
+    /// Lifetime of the device and user codes, in seconds
+    pub expires_in: i64,
+    /// Minimum seconds the client must wait between token polls
+    pub interval: i64,
+}
+
 /// MFA Method
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -136,6 +151,89 @@ pub struct UserRole {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
+/// Current version of the portable RBAC policy document
+pub const RBAC_POLICY_VERSION: u32 = 1;
+
+/// Portable RBAC policy, referencing roles by name so it can be moved between
+/// environments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RbacPolicyDocument {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub roles: Vec<RolePolicy>,
+    pub assignments: Vec<RoleAssignmentPolicy>,
+}
+
+/// Role in a policy document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RolePolicy {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<Permission>,
+}
+
+/// Role assignment in a policy document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoleAssignmentPolicy {
+    pub identity_id: Uuid,
+    pub role: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Changes to an existing role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleChange {
+    pub name: String,
+    pub previous_description: Option<String>,
+    pub description: Option<String>,
+    pub permissions_added: Vec<Permission>,
+    pub permissions_removed: Vec<Permission>,
+}
+
+/// What importing a policy document would change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RbacPolicyDiff {
+    pub roles_added: Vec<RolePolicy>,
+    pub roles_removed: Vec<String>,
+    pub roles_changed: Vec<RoleChange>,
+    pub assignments_added: Vec<RoleAssignmentPolicy>,
+    pub assignments_removed: Vec<RoleAssignmentPolicy>,
+    /// Assignments kept with a different expiry, with their new values
+    pub assignments_changed: Vec<RoleAssignmentPolicy>,
+}
+
+impl RbacPolicyDiff {
+    /// Whether the policies are identical
+    pub fn is_empty(&self) -> bool {
+        self.roles_added.is_empty()
+            && self.roles_removed.is_empty()
+            && self.roles_changed.is_empty()
+            && self.assignments_added.is_empty()
+            && self.assignments_removed.is_empty()
+            && self.assignments_changed.is_empty()
+    }
+}
+
+/// Permission Delegation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionDelegation {
+    pub id: Uuid,
+    pub delegator_id: Uuid,
+    pub delegatee_id: Uuid,
+    pub permissions: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+}
+
+impl PermissionDelegation {
+    /// Whether the delegation currently grants its permissions
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
 /// Audit Event Type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -153,6 +251,59 @@ pub enum AuditEventType {
     AccountLocked,
     AccountUnlocked,
     SecurityPolicyViolation,
+    AuditTamperDetected,
+}
+
+impl AuditEventType {
+    /// Category used when no audit rule matches
+    pub fn default_category(&self) -> AuditCategory {
+        match self {
+            AuditEventType::Authentication
+            | AuditEventType::OAuthTokenIssued
+            | AuditEventType::OAuthTokenRevoked
+            | AuditEventType::MfaVerified
+            | AuditEventType::PasswordChange
+            | AuditEventType::AccountLocked
+            | AuditEventType::AccountUnlocked => AuditCategory::Auth,
+            AuditEventType::Authorization => AuditCategory::DataAccess,
+            AuditEventType::RoleAssignment
+            | AuditEventType::PermissionChange
+            | AuditEventType::MfaEnabled
+            | AuditEventType::MfaDisabled
+            | AuditEventType::SecurityPolicyViolation
+            | AuditEventType::AuditTamperDetected => AuditCategory::Admin,
+        }
+    }
+}
+
+/// Audit Category, deciding retention and routing
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditCategory {
+    Auth,
+    DataAccess,
+    Admin,
+}
+
+/// Per-category audit counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditCategoryStats {
+    pub category: AuditCategory,
+    /// Events written to the audit log
+    pub recorded: u64,
+    /// Events not written to the audit log because the category is not stored
+    pub dropped: u64,
+    /// Events a sink failed to accept
+    pub sink_failures: u64,
+}
+
+/// Result of an audit retention run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPurgeResult {
+    /// Entries whose content was removed
+    pub pruned: u64,
+    /// Pruned entries removed entirely from the start of the chain
+    pub deleted: u64,
 }
 
 /// Audit Log Entry
@@ -179,3 +330,26 @@ pub enum AuditResult {
     Failure,
     Denied,
 }
+
+/// First point where the audit hash chain no longer verifies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditChainBreak {
+    pub entry_id: Uuid,
+    pub sequence_number: i64,
+    pub reason: String,
+}
+
+/// Result of verifying the audit hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainVerification {
+    pub entries_checked: u64,
+    pub broken_at: Option<AuditChainBreak>,
+    pub verified_at: DateTime<Utc>,
+}
+
+impl AuditChainVerification {
+    /// Whether the whole chain verified
+    pub fn is_intact(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
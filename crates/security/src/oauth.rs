@@ -3,7 +3,7 @@
 //! Implements OAuth 2.0 authorization server and OpenID Connect (OIDC) support
 
 use crate::error::SecurityError;
-use crate::models::{AccessToken, AuthorizationCode, GrantType, OAuthClient};
+use crate::models::{AccessToken, AuthorizationCode, DeviceAuthorization, GrantType, OAuthClient};
 use chrono::{Duration, Utc};
 use log::info;
 use rand::Rng;
@@ -11,6 +11,16 @@ use sha2::{Digest, Sha256};
 use sqlx::{FromRow, PgPool};
 use uuid::Uuid;
 
+/// Characters used in device flow user codes: upper-case consonants only, so
+/// codes are easy to type and cannot spell words or be confused with digits
+const USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// Length of a device flow user code, excluding the separator
+const USER_CODE_LENGTH: usize = 8;
+
+/// Seconds added to a device's poll interval on every `slow_down`
+const SLOW_DOWN_INCREMENT: i64 = 5;
+
 /// OAuth 2.0 Provider
 pub struct OAuthProvider {
     pool: PgPool,
@@ -18,6 +28,8 @@ pub struct OAuthProvider {
     access_token_ttl: i64,       // in seconds
     refresh_token_ttl: i64,      // in seconds
     authorization_code_ttl: i64, // in seconds
+    device_code_ttl: i64,        // in seconds
+    device_poll_interval: i64,   // in seconds
 }
 
 impl OAuthProvider {
@@ -29,6 +41,8 @@ impl OAuthProvider {
             access_token_ttl: 3600,        // 1 hour
             refresh_token_ttl: 86400 * 30, // 30 days
             authorization_code_ttl: 600,   // 10 minutes
+            device_code_ttl: 900,          // 15 minutes
+            device_poll_interval: 5,
         }
     }
 
@@ -242,6 +256,179 @@ impl OAuthProvider {
         Ok(access_token)
     }
 
+    /// Start a device authorization (RFC 8628)
+    ///
+    /// Issues a device code the client polls with and a short user code the user
+    /// enters at the verification URI on another device.
+    pub async fn request_device_authorization(
+        &self,
+        client_id: &str,
+        scopes: Vec<String>,
+    ) -> Result<DeviceAuthorization, SecurityError> {
+        let allowed = sqlx::query_scalar::<_, bool>(
+            "SELECT $2 = ANY(grant_types) FROM oauth_clients
+             WHERE client_id = $1 AND is_active = true",
+        )
+        .bind(client_id)
+        .bind(grant_type_to_string(&GrantType::DeviceCode))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SecurityError::OAuth("Invalid client".to_string()))?;
+
+        if !allowed {
+            return Err(SecurityError::OAuth(
+                "Client is not authorized for the device code grant".to_string(),
+            ));
+        }
+
+        let device_code = self.generate_random_code(64);
+        let user_code = self.generate_user_code();
+        let expires_at = Utc::now() + Duration::seconds(self.device_code_ttl);
+
+        sqlx::query(
+            "INSERT INTO device_authorizations (id, device_code, user_code, client_id, scopes,
+             status, poll_interval, expires_at, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(&device_code)
+        .bind(&user_code)
+        .bind(client_id)
+        .bind(&scopes)
+        .bind("PENDING")
+        .bind(self.device_poll_interval as i32)
+        .bind(expires_at)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        info!("Issued device authorization for client: {}", client_id);
+
+        let display_code = format_user_code(&user_code);
+        let verification_uri = format!("{}/oauth/device", self.issuer);
+        Ok(DeviceAuthorization {
+            device_code,
+            verification_uri_complete: format!("{}?user_code={}", verification_uri, display_code),
+            verification_uri,
+            user_code: display_code,
+            expires_in: self.device_code_ttl,
+            interval: self.device_poll_interval,
+        })
+    }
+
+    /// Approve a pending device authorization on behalf of a user
+    pub async fn approve_device_authorization(
+        &self,
+        user_code: &str,
+        user_id: Uuid,
+    ) -> Result<(), SecurityError> {
+        self.decide_device_authorization(user_code, "APPROVED", Some(user_id))
+            .await
+    }
+
+    /// Deny a pending device authorization
+    pub async fn deny_device_authorization(&self, user_code: &str) -> Result<(), SecurityError> {
+        self.decide_device_authorization(user_code, "DENIED", None)
+            .await
+    }
+
+    async fn decide_device_authorization(
+        &self,
+        user_code: &str,
+        status: &str,
+        user_id: Option<Uuid>,
+    ) -> Result<(), SecurityError> {
+        let result = sqlx::query(
+            "UPDATE device_authorizations SET status = $1, user_id = $2
+             WHERE user_code = $3 AND status = 'PENDING' AND expires_at > CURRENT_TIMESTAMP",
+        )
+        .bind(status)
+        .bind(user_id)
+        .bind(normalize_user_code(user_code))
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(SecurityError::OAuth(
+                "Invalid or expired user code".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Poll for the token of a device authorization
+    ///
+    /// Fails with `AuthorizationPending` until the user decides, `SlowDown` if
+    /// polled sooner than the interval allows (the interval then grows by five
+    /// seconds), `AccessDenied` once denied and `ExpiredToken` after expiry.
+    /// The device code can be exchanged only once.
+    pub async fn poll_device_token(
+        &self,
+        device_code: &str,
+        client_id: &str,
+    ) -> Result<AccessToken, SecurityError> {
+        let row = sqlx::query_as::<_, DeviceAuthorizationRow>(
+            "SELECT scopes, status, user_id, poll_interval, last_polled_at, expires_at
+             FROM device_authorizations WHERE device_code = $1 AND client_id = $2",
+        )
+        .bind(device_code)
+        .bind(client_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let authorization =
+            row.ok_or_else(|| SecurityError::OAuth("Invalid device code".to_string()))?;
+
+        let now = Utc::now();
+        if authorization.expires_at <= now {
+            return Err(SecurityError::ExpiredToken);
+        }
+
+        let mut interval = authorization.poll_interval as i64;
+        let too_fast = authorization
+            .last_polled_at
+            .is_some_and(|last| now < last + Duration::seconds(interval));
+        if too_fast {
+            interval += SLOW_DOWN_INCREMENT;
+        }
+        sqlx::query(
+            "UPDATE device_authorizations SET last_polled_at = $1, poll_interval = $2
+             WHERE device_code = $3",
+        )
+        .bind(now)
+        .bind(interval as i32)
+        .bind(device_code)
+        .execute(&self.pool)
+        .await?;
+        if too_fast {
+            return Err(SecurityError::SlowDown { interval });
+        }
+
+        match (authorization.status.as_str(), authorization.user_id) {
+            ("APPROVED", Some(user_id)) => {
+                // Delete first so a concurrent poll cannot exchange the same code
+                let deleted = sqlx::query(
+                    "DELETE FROM device_authorizations WHERE device_code = $1 AND status = 'APPROVED'",
+                )
+                .bind(device_code)
+                .execute(&self.pool)
+                .await?;
+                if deleted.rows_affected() == 0 {
+                    return Err(SecurityError::OAuth("Invalid device code".to_string()));
+                }
+
+                info!(
+                    "Exchanged device code for access token for client: {}",
+                    client_id
+                );
+                self.generate_access_token(client_id, Some(user_id), &authorization.scopes)
+                    .await
+            }
+            ("DENIED", _) => Err(SecurityError::AccessDenied),
+            _ => Err(SecurityError::AuthorizationPending),
+        }
+    }
+
     /// Generate access token (client credentials flow)
     pub async fn generate_client_credentials_token(
         &self,
@@ -380,7 +567,13 @@ impl OAuthProvider {
             "userinfo_endpoint": format!("{}/oauth/userinfo", self.issuer),
             "jwks_uri": format!("{}/oauth/jwks", self.issuer),
             "response_types_supported": ["code", "token", "id_token"],
-            "grant_types_supported": ["authorization_code", "client_credentials", "refresh_token"],
+            "device_authorization_endpoint": format!("{}/oauth/device_authorization", self.issuer),
+            "grant_types_supported": [
+                "authorization_code",
+                "client_credentials",
+                "refresh_token",
+                "urn:ietf:params:oauth:grant-type:device_code"
+            ],
             "scopes_supported": ["openid", "profile", "email", "offline_access"],
             "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post"],
             "code_challenge_methods_supported": ["plain", "S256"]
@@ -400,6 +593,14 @@ impl OAuthProvider {
             .collect()
     }
 
+    /// Helper: Generate device flow user code
+    fn generate_user_code(&self) -> String {
+        let mut rng = rand::thread_rng();
+        (0..USER_CODE_LENGTH)
+            .map(|_| USER_CODE_CHARSET[rng.gen_range(0..USER_CODE_CHARSET.len())] as char)
+            .collect()
+    }
+
     /// Helper: Hash secret
     fn hash_secret(&self, secret: &str) -> String {
         let mut hasher = Sha256::new();
@@ -408,6 +609,22 @@ impl OAuthProvider {
     }
 }
 
+/// Normalize a user code as entered by the user: case-insensitive, ignoring
+/// separators and whitespace
+pub fn normalize_user_code(user_code: &str) -> String {
+    user_code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Format a normalized user code for display, e.g. `WDJB-MJHT`
+pub fn format_user_code(user_code: &str) -> String {
+    let half = user_code.len() / 2;
+    format!("{}-{}", &user_code[..half], &user_code[half..])
+}
+
 /// Helper functions
 fn grant_type_to_string(grant_type: &GrantType) -> String {
     match grant_type {
@@ -415,6 +632,7 @@ fn grant_type_to_string(grant_type: &GrantType) -> String {
         GrantType::ClientCredentials => "CLIENT_CREDENTIALS".to_string(),
         GrantType::RefreshToken => "REFRESH_TOKEN".to_string(),
         GrantType::Implicit => "IMPLICIT".to_string(),
+        GrantType::DeviceCode => "DEVICE_CODE".to_string(),
     }
 }
 
@@ -424,6 +642,7 @@ fn string_to_grant_type(s: &str) -> GrantType {
         "CLIENT_CREDENTIALS" => GrantType::ClientCredentials,
         "REFRESH_TOKEN" => GrantType::RefreshToken,
         "IMPLICIT" => GrantType::Implicit,
+        "DEVICE_CODE" => GrantType::DeviceCode,
         _ => GrantType::AuthorizationCode,
     }
 }
@@ -457,6 +676,16 @@ struct AuthorizationCodeRow {
     created_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, FromRow)]
+struct DeviceAuthorizationRow {
+    scopes: Vec<String>,
+    status: String,
+    user_id: Option<Uuid>,
+    poll_interval: i32,
+    last_polled_at: Option<chrono::DateTime<chrono::Utc>>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, FromRow)]
 struct AccessTokenRow {
     token: String,
@@ -1,23 +1,36 @@
 //! Role-Based Access Control (RBAC)
 //!
-//! Manages roles, permissions, and user-role assignments
+//! Manages roles, permissions, user-role assignments and temporary
+//! permission delegations, and exports and imports the whole policy as a
+//! portable document for promotion between environments
 
+use crate::audit::AuditLogger;
 use crate::error::SecurityError;
-use crate::models::{Permission, Role, UserRole};
-use chrono::Utc;
+use crate::models::{
+    AuditEventType, AuditResult, Permission, PermissionDelegation, RbacPolicyDiff,
+    RbacPolicyDocument, Role, RoleAssignmentPolicy, RoleChange, RolePolicy, UserRole,
+    RBAC_POLICY_VERSION,
+};
+use chrono::{DateTime, Utc};
 use log::info;
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid;
 
 /// RBAC Service
 pub struct RbacService {
     pool: PgPool,
+    audit: AuditLogger,
 }
 
 impl RbacService {
     /// Create a new RBAC service
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            audit: AuditLogger::new(pool.clone()),
+            pool,
+        }
     }
 
     /// Create a new role
@@ -271,10 +284,41 @@ impl RbacService {
         Ok(roles)
     }
 
-    /// Get all permissions for an identity (from all roles)
+    /// Get all permissions for an identity (from all roles and active delegations)
+    ///
+    /// A delegation only grants the permissions its delegator still holds
+    /// through their own roles, so removing a role from the delegator also
+    /// withdraws it from everyone they delegated to.
     pub async fn get_identity_permissions(
         &self,
         identity_id: Uuid,
+    ) -> Result<Vec<Permission>, SecurityError> {
+        let mut permissions = self.get_role_permissions(identity_id).await?;
+        let mut delegator_permissions: HashMap<Uuid, Vec<Permission>> = HashMap::new();
+
+        for delegation in self.get_active_delegations(identity_id).await? {
+            let held = match delegator_permissions.entry(delegation.delegator_id) {
+                Entry::Occupied(held) => held.into_mut(),
+                Entry::Vacant(slot) => {
+                    slot.insert(self.get_role_permissions(delegation.delegator_id).await?)
+                }
+            };
+
+            for permission in delegation.permissions {
+                // Avoid duplicates
+                if held.contains(&permission) && !permissions.contains(&permission) {
+                    permissions.push(permission);
+                }
+            }
+        }
+
+        Ok(permissions)
+    }
+
+    /// Get permissions an identity holds through its own roles
+    async fn get_role_permissions(
+        &self,
+        identity_id: Uuid,
     ) -> Result<Vec<Permission>, SecurityError> {
         let roles = self.get_identity_roles(identity_id).await?;
 
@@ -291,6 +335,348 @@ impl RbacService {
         Ok(permissions)
     }
 
+    /// Delegate permissions from one identity to another until `expires_at`
+    ///
+    /// The delegator must hold every permission through their own roles;
+    /// delegated permissions cannot be delegated further. The delegation stops
+    /// granting permissions once it expires or is revoked, and stops granting
+    /// any permission the delegator no longer holds.
+    pub async fn delegate(
+        &self,
+        from_identity_id: Uuid,
+        to_identity_id: Uuid,
+        permissions: Vec<Permission>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PermissionDelegation, SecurityError> {
+        if from_identity_id == to_identity_id {
+            return Err(SecurityError::Validation(
+                "Cannot delegate permissions to self".to_string(),
+            ));
+        }
+        if permissions.is_empty() {
+            return Err(SecurityError::Validation(
+                "At least one permission must be delegated".to_string(),
+            ));
+        }
+        let now = Utc::now();
+        if expires_at <= now {
+            return Err(SecurityError::Validation(
+                "Delegation expiry must be in the future".to_string(),
+            ));
+        }
+
+        let held = self.get_role_permissions(from_identity_id).await?;
+        if let Some(missing) = permissions.iter().find(|p| !held.contains(p)) {
+            return Err(SecurityError::Authorization(format!(
+                "Delegator does not hold permission {}",
+                missing
+            )));
+        }
+
+        let id = Uuid::new_v4();
+        let permissions_json = serde_json::to_string(&permissions)
+            .map_err(|e| SecurityError::Rbac(format!("Failed to serialize permissions: {}", e)))?;
+
+        sqlx::query(
+            "INSERT INTO permission_delegations (id, delegator_id, delegatee_id, permissions, created_at, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(id)
+        .bind(from_identity_id)
+        .bind(to_identity_id)
+        .bind(&permissions_json)
+        .bind(now)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        self.audit
+            .log_event(
+                AuditEventType::PermissionChange,
+                Some(from_identity_id),
+                None,
+                Some("permission_delegation".to_string()),
+                Some("delegate".to_string()),
+                AuditResult::Success,
+                None,
+                None,
+                Some(serde_json::json!({
+                    "delegation_id": id,
+                    "delegatee_id": to_identity_id,
+                    "permissions": permissions,
+                    "expires_at": expires_at
+                })),
+            )
+            .await?;
+
+        info!(
+            "Delegated {} permission(s) from {} to {} until {}",
+            permissions.len(),
+            from_identity_id,
+            to_identity_id,
+            expires_at
+        );
+
+        Ok(PermissionDelegation {
+            id,
+            delegator_id: from_identity_id,
+            delegatee_id: to_identity_id,
+            permissions,
+            created_at: now,
+            expires_at,
+            revoked_at: None,
+            revoked_by: None,
+        })
+    }
+
+    /// Revoke a delegation before it expires
+    pub async fn revoke_delegation(
+        &self,
+        delegation_id: Uuid,
+        revoked_by: Option<Uuid>,
+    ) -> Result<(), SecurityError> {
+        let now = Utc::now();
+        let delegator_id: Option<Uuid> = sqlx::query_scalar(
+            "UPDATE permission_delegations SET revoked_at = $1, revoked_by = $2
+             WHERE id = $3 AND revoked_at IS NULL
+             RETURNING delegator_id",
+        )
+        .bind(now)
+        .bind(revoked_by)
+        .bind(delegation_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(delegator_id) = delegator_id else {
+            return Err(SecurityError::NotFound(format!(
+                "Active delegation {} not found",
+                delegation_id
+            )));
+        };
+
+        self.audit
+            .log_event(
+                AuditEventType::PermissionChange,
+                Some(delegator_id),
+                None,
+                Some("permission_delegation".to_string()),
+                Some("revoke".to_string()),
+                AuditResult::Success,
+                None,
+                None,
+                Some(serde_json::json!({
+                    "delegation_id": delegation_id,
+                    "revoked_by": revoked_by
+                })),
+            )
+            .await?;
+
+        info!("Revoked delegation {}", delegation_id);
+        Ok(())
+    }
+
+    /// Get delegations currently granting permissions to an identity
+    pub async fn get_active_delegations(
+        &self,
+        delegatee_id: Uuid,
+    ) -> Result<Vec<PermissionDelegation>, SecurityError> {
+        let rows = sqlx::query_as::<_, DelegationRow>(
+            "SELECT id, delegator_id, delegatee_id, permissions, created_at, expires_at, revoked_at, revoked_by
+             FROM permission_delegations
+             WHERE delegatee_id = $1
+             AND revoked_at IS NULL
+             AND expires_at > CURRENT_TIMESTAMP
+             ORDER BY expires_at",
+        )
+        .bind(delegatee_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut delegations = Vec::new();
+        for row in rows {
+            let permissions: Vec<Permission> =
+                serde_json::from_str(&row.permissions).map_err(|e| {
+                    SecurityError::Rbac(format!("Failed to deserialize permissions: {}", e))
+                })?;
+
+            delegations.push(PermissionDelegation {
+                id: row.id,
+                delegator_id: row.delegator_id,
+                delegatee_id: row.delegatee_id,
+                permissions,
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+                revoked_at: row.revoked_at,
+                revoked_by: row.revoked_by,
+            });
+        }
+
+        Ok(delegations)
+    }
+
+    /// Export roles, permissions and role assignments as a portable document
+    pub async fn export_policy(&self) -> Result<RbacPolicyDocument, SecurityError> {
+        let mut conn = self.pool.acquire().await?;
+        export_policy_from(&mut conn).await
+    }
+
+    /// Validate a policy document and show what importing it would change
+    pub async fn plan_import(
+        &self,
+        incoming: &RbacPolicyDocument,
+    ) -> Result<RbacPolicyDiff, SecurityError> {
+        validate_policy(incoming)?;
+        let current = self.export_policy().await?;
+        Ok(diff(&import_scope(&current, incoming), incoming))
+    }
+
+    /// Make the stored policy match a policy document
+    ///
+    /// Only the roles the document names are imported: each replaces the
+    /// stored role of the same name, along with its assignments. Roles the
+    /// document does not mention, and their assignments, are left alone.
+    /// The current policy is read, compared and changed in one transaction
+    /// that holds off other role changes, and the import is audited in it,
+    /// so a failure (such as an assignment to an unknown identity) leaves the
+    /// policy untouched and unaudited.
+    pub async fn import_policy(
+        &self,
+        incoming: &RbacPolicyDocument,
+        imported_by: Option<Uuid>,
+    ) -> Result<RbacPolicyDiff, SecurityError> {
+        validate_policy(incoming)?;
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        // Keep role and assignment changes out until the import commits
+        sqlx::query("LOCK TABLE roles, user_roles IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+        let current = export_policy_from(&mut tx).await?;
+        let changes = diff(&import_scope(&current, incoming), incoming);
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        for assignment in &changes.assignments_removed {
+            sqlx::query(
+                "DELETE FROM user_roles ur USING roles r
+                 WHERE ur.role_id = r.id AND ur.identity_id = $1 AND r.name = $2",
+            )
+            .bind(assignment.identity_id)
+            .bind(&assignment.role)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for name in &changes.roles_removed {
+            sqlx::query("DELETE FROM roles WHERE name = $1")
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for role in &changes.roles_added {
+            let permissions_json = serde_json::to_string(&role.permissions).map_err(|e| {
+                SecurityError::Rbac(format!("Failed to serialize permissions: {}", e))
+            })?;
+            sqlx::query(
+                "INSERT INTO roles (id, name, description, permissions, created_at, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $5)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(&role.name)
+            .bind(&role.description)
+            .bind(&permissions_json)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for change in &changes.roles_changed {
+            let role = incoming
+                .roles
+                .iter()
+                .find(|r| r.name == change.name)
+                .expect("changed roles come from the incoming document");
+            let permissions_json = serde_json::to_string(&role.permissions).map_err(|e| {
+                SecurityError::Rbac(format!("Failed to serialize permissions: {}", e))
+            })?;
+            sqlx::query(
+                "UPDATE roles SET description = $1, permissions = $2, updated_at = $3
+                 WHERE name = $4",
+            )
+            .bind(&role.description)
+            .bind(&permissions_json)
+            .bind(now)
+            .bind(&role.name)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for assignment in &changes.assignments_added {
+            sqlx::query(
+                "INSERT INTO user_roles (id, identity_id, role_id, assigned_at, assigned_by, expires_at)
+                 SELECT $1, $2, r.id, $3, $4, $5 FROM roles r WHERE r.name = $6",
+            )
+            .bind(Uuid::new_v4())
+            .bind(assignment.identity_id)
+            .bind(now)
+            .bind(imported_by)
+            .bind(assignment.expires_at)
+            .bind(&assignment.role)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for assignment in &changes.assignments_changed {
+            sqlx::query(
+                "UPDATE user_roles ur SET expires_at = $1
+                 FROM roles r
+                 WHERE ur.role_id = r.id AND ur.identity_id = $2 AND r.name = $3",
+            )
+            .bind(assignment.expires_at)
+            .bind(assignment.identity_id)
+            .bind(&assignment.role)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        self.audit
+            .log_event_in(
+                &mut tx,
+                AuditEventType::PermissionChange,
+                imported_by,
+                None,
+                Some("rbac_policy".to_string()),
+                Some("import".to_string()),
+                AuditResult::Success,
+                None,
+                None,
+                Some(serde_json::json!({
+                    "roles_added": changes.roles_added.len(),
+                    "roles_removed": changes.roles_removed,
+                    "roles_changed": changes.roles_changed.len(),
+                    "assignments_added": changes.assignments_added.len(),
+                    "assignments_removed": changes.assignments_removed.len(),
+                    "assignments_changed": changes.assignments_changed.len()
+                })),
+            )
+            .await?;
+
+        tx.commit().await?;
+
+        info!(
+            "Imported RBAC policy: {} role(s) added, {} removed, {} changed",
+            changes.roles_added.len(),
+            changes.roles_removed.len(),
+            changes.roles_changed.len()
+        );
+
+        Ok(changes)
+    }
+
     /// Check if identity has a specific role
     pub async fn has_role(
         &self,
@@ -360,6 +746,213 @@ impl RbacService {
     }
 }
 
+/// Read roles, permissions and role assignments as a portable document
+async fn export_policy_from(conn: &mut PgConnection) -> Result<RbacPolicyDocument, SecurityError> {
+    let role_rows = sqlx::query_as::<_, RoleRow>(
+        "SELECT id, name, description, permissions, created_at, updated_at
+         FROM roles ORDER BY name",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut roles = Vec::new();
+    for row in role_rows {
+        let permissions: Vec<Permission> = serde_json::from_str(&row.permissions).map_err(|e| {
+            SecurityError::Rbac(format!("Failed to deserialize permissions: {}", e))
+        })?;
+        roles.push(RolePolicy {
+            name: row.name,
+            description: row.description,
+            permissions,
+        });
+    }
+
+    let assignments = sqlx::query_as::<_, AssignmentPolicyRow>(
+        "SELECT ur.identity_id, r.name AS role, ur.expires_at
+         FROM user_roles ur
+         INNER JOIN roles r ON ur.role_id = r.id
+         ORDER BY ur.identity_id, r.name",
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| RoleAssignmentPolicy {
+        identity_id: row.identity_id,
+        role: row.role,
+        expires_at: row.expires_at,
+    })
+    .collect();
+
+    Ok(RbacPolicyDocument {
+        version: RBAC_POLICY_VERSION,
+        exported_at: Utc::now(),
+        roles,
+        assignments,
+    })
+}
+
+/// The part of the `current` policy an import of `incoming` replaces: the
+/// roles the document names and their assignments
+pub fn import_scope(
+    current: &RbacPolicyDocument,
+    incoming: &RbacPolicyDocument,
+) -> RbacPolicyDocument {
+    let in_scope: HashSet<&str> = incoming.roles.iter().map(|r| r.name.as_str()).collect();
+    RbacPolicyDocument {
+        version: current.version,
+        exported_at: current.exported_at,
+        roles: current
+            .roles
+            .iter()
+            .filter(|r| in_scope.contains(r.name.as_str()))
+            .cloned()
+            .collect(),
+        assignments: current
+            .assignments
+            .iter()
+            .filter(|a| in_scope.contains(a.role.as_str()))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Check that a policy document is well-formed
+///
+/// Role names must be unique and non-empty, permissions must name a resource
+/// and an action, and every assignment must reference a role in the document
+/// at most once per identity.
+pub fn validate_policy(document: &RbacPolicyDocument) -> Result<(), SecurityError> {
+    if document.version != RBAC_POLICY_VERSION {
+        return Err(SecurityError::Validation(format!(
+            "Unsupported RBAC policy version {}",
+            document.version
+        )));
+    }
+
+    let mut role_names = HashSet::new();
+    for role in &document.roles {
+        if role.name.trim().is_empty() {
+            return Err(SecurityError::Validation(
+                "Role name cannot be empty".to_string(),
+            ));
+        }
+        if !role_names.insert(role.name.as_str()) {
+            return Err(SecurityError::Validation(format!(
+                "Duplicate role {}",
+                role.name
+            )));
+        }
+        if let Some(permission) = role
+            .permissions
+            .iter()
+            .find(|p| p.resource.trim().is_empty() || p.action.trim().is_empty())
+        {
+            return Err(SecurityError::Validation(format!(
+                "Role {} has incomplete permission {}",
+                role.name, permission
+            )));
+        }
+    }
+
+    let mut assignments = HashSet::new();
+    for assignment in &document.assignments {
+        if !role_names.contains(assignment.role.as_str()) {
+            return Err(SecurityError::Validation(format!(
+                "Assignment of identity {} references unknown role {}",
+                assignment.identity_id, assignment.role
+            )));
+        }
+        if !assignments.insert((assignment.identity_id, assignment.role.as_str())) {
+            return Err(SecurityError::Validation(format!(
+                "Role {} is assigned to identity {} more than once",
+                assignment.role, assignment.identity_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes that turn the `current` policy into the `incoming` one
+///
+/// Roles are matched by name and assignments by identity and role name;
+/// permission order within a role is ignored.
+pub fn diff(current: &RbacPolicyDocument, incoming: &RbacPolicyDocument) -> RbacPolicyDiff {
+    let current_roles: BTreeMap<&str, &RolePolicy> =
+        current.roles.iter().map(|r| (r.name.as_str(), r)).collect();
+    let incoming_roles: BTreeMap<&str, &RolePolicy> = incoming
+        .roles
+        .iter()
+        .map(|r| (r.name.as_str(), r))
+        .collect();
+
+    let mut changes = RbacPolicyDiff::default();
+
+    for (name, role) in &incoming_roles {
+        let Some(existing) = current_roles.get(name) else {
+            changes.roles_added.push((*role).clone());
+            continue;
+        };
+        let permissions_added: Vec<Permission> = role
+            .permissions
+            .iter()
+            .filter(|p| !existing.permissions.contains(p))
+            .cloned()
+            .collect();
+        let permissions_removed: Vec<Permission> = existing
+            .permissions
+            .iter()
+            .filter(|p| !role.permissions.contains(p))
+            .cloned()
+            .collect();
+        if existing.description != role.description
+            || !permissions_added.is_empty()
+            || !permissions_removed.is_empty()
+        {
+            changes.roles_changed.push(RoleChange {
+                name: name.to_string(),
+                previous_description: existing.description.clone(),
+                description: role.description.clone(),
+                permissions_added,
+                permissions_removed,
+            });
+        }
+    }
+    changes.roles_removed = current_roles
+        .keys()
+        .filter(|name| !incoming_roles.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+
+    let current_assignments: BTreeMap<(Uuid, &str), &RoleAssignmentPolicy> = current
+        .assignments
+        .iter()
+        .map(|a| ((a.identity_id, a.role.as_str()), a))
+        .collect();
+    let incoming_assignments: BTreeMap<(Uuid, &str), &RoleAssignmentPolicy> = incoming
+        .assignments
+        .iter()
+        .map(|a| ((a.identity_id, a.role.as_str()), a))
+        .collect();
+
+    for (key, assignment) in &incoming_assignments {
+        match current_assignments.get(key) {
+            None => changes.assignments_added.push((*assignment).clone()),
+            Some(existing) if existing.expires_at != assignment.expires_at => {
+                changes.assignments_changed.push((*assignment).clone())
+            }
+            Some(_) => {}
+        }
+    }
+    changes.assignments_removed = current_assignments
+        .iter()
+        .filter(|(key, _)| !incoming_assignments.contains_key(*key))
+        .map(|(_, assignment)| (*assignment).clone())
+        .collect();
+
+    changes
+}
+
 /// Internal row structures
 #[derive(Debug, FromRow)]
 struct RoleRow {
@@ -381,3 +974,22 @@ struct UserRoleRow {
     assigned_by: Option<Uuid>,
     expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
+
+#[derive(Debug, FromRow)]
+struct AssignmentPolicyRow {
+    identity_id: Uuid,
+    role: String,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+struct DelegationRow {
+    id: Uuid,
+    delegator_id: Uuid,
+    delegatee_id: Uuid,
+    permissions: String, // JSON string
+    created_at: chrono::DateTime<chrono::Utc>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    revoked_by: Option<Uuid>,
+}
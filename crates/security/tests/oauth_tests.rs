@@ -2,8 +2,9 @@
 
 #[cfg(test)]
 mod tests {
+    use security::error::SecurityError;
     use security::models::GrantType;
-    use security::oauth::OAuthProvider;
+    use security::oauth::{format_user_code, normalize_user_code, OAuthProvider};
     use sqlx::PgPool;
     use test_utils::database::create_test_pool;
     use uuid::Uuid;
@@ -138,4 +139,136 @@ mod tests {
         assert!(discovery["authorization_endpoint"].is_string());
         assert!(discovery["token_endpoint"].is_string());
     }
+
+    #[test]
+    fn test_user_code_normalization() {
+        assert_eq!(normalize_user_code("wdjb-mjht"), "WDJBMJHT");
+        assert_eq!(normalize_user_code(" WDJB MJHT "), "WDJBMJHT");
+        assert_eq!(format_user_code("WDJBMJHT"), "WDJB-MJHT");
+    }
+
+    async fn register_device_client(provider: &OAuthProvider, client_id: &str) {
+        provider
+            .register_client(
+                client_id.to_string(),
+                "unused-secret".to_string(),
+                vec![],
+                vec![GrantType::DeviceCode],
+                vec!["tv".to_string()],
+                Uuid::new_v4(),
+            )
+            .await
+            .expect("Failed to register client");
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_device_flow_pending_then_approved() {
+        let (pool, provider) = setup().await;
+        let client_id = format!("stb-{}", Uuid::new_v4());
+        register_device_client(&provider, &client_id).await;
+
+        let authorization = provider
+            .request_device_authorization(&client_id, vec!["tv".to_string()])
+            .await
+            .expect("Failed to request device authorization");
+        assert_eq!(authorization.user_code.len(), 9);
+        assert!(authorization.interval > 0);
+
+        let pending = provider
+            .poll_device_token(&authorization.device_code, &client_id)
+            .await;
+        assert!(matches!(pending, Err(SecurityError::AuthorizationPending)));
+
+        // Polling again straight away is too fast
+        let too_fast = provider
+            .poll_device_token(&authorization.device_code, &client_id)
+            .await;
+        assert!(
+            matches!(too_fast, Err(SecurityError::SlowDown { interval }) if interval > authorization.interval)
+        );
+
+        let user_id = Uuid::new_v4();
+        provider
+            .approve_device_authorization(&authorization.user_code.to_lowercase(), user_id)
+            .await
+            .expect("Failed to approve device authorization");
+
+        // Let the (slowed down) poll interval pass
+        sqlx::query(
+            "UPDATE device_authorizations SET last_polled_at = last_polled_at - INTERVAL '1 minute'
+             WHERE device_code = $1",
+        )
+        .bind(&authorization.device_code)
+        .execute(&pool)
+        .await
+        .expect("Failed to rewind last poll");
+
+        let token = provider
+            .poll_device_token(&authorization.device_code, &client_id)
+            .await
+            .expect("Failed to exchange approved device code");
+        assert_eq!(token.client_id, client_id);
+        assert_eq!(token.user_id, Some(user_id));
+        assert_eq!(token.scope, vec!["tv".to_string()]);
+
+        let stored_user: Option<Uuid> =
+            sqlx::query_scalar("SELECT user_id FROM access_tokens WHERE token = $1")
+                .bind(&token.token)
+                .fetch_one(&pool)
+                .await
+                .expect("Issued token should be stored");
+        assert_eq!(stored_user, Some(user_id));
+
+        // The device code is single-use
+        let reused = provider
+            .poll_device_token(&authorization.device_code, &client_id)
+            .await;
+        assert!(reused.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_device_flow_denied() {
+        let (_pool, provider) = setup().await;
+        let client_id = format!("cli-{}", Uuid::new_v4());
+        register_device_client(&provider, &client_id).await;
+
+        let authorization = provider
+            .request_device_authorization(&client_id, vec!["tv".to_string()])
+            .await
+            .expect("Failed to request device authorization");
+        provider
+            .deny_device_authorization(&authorization.user_code)
+            .await
+            .expect("Failed to deny device authorization");
+
+        let denied = provider
+            .poll_device_token(&authorization.device_code, &client_id)
+            .await;
+        assert!(matches!(denied, Err(SecurityError::AccessDenied)));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_device_flow_requires_grant() {
+        let (_pool, provider) = setup().await;
+        let client_id = format!("web-{}", Uuid::new_v4());
+        provider
+            .register_client(
+                client_id.clone(),
+                "secret".to_string(),
+                vec!["http://localhost:3000/callback".to_string()],
+                vec![GrantType::AuthorizationCode],
+                vec!["openid".to_string()],
+                Uuid::new_v4(),
+            )
+            .await
+            .expect("Failed to register client");
+
+        let result = provider
+            .request_device_authorization(&client_id, vec!["openid".to_string()])
+            .await;
+        assert!(result.is_err());
+    }
 }
@@ -2,8 +2,10 @@
 
 #[cfg(test)]
 mod tests {
-    use security::models::Permission;
-    use security::rbac::RbacService;
+    use security::models::{
+        Permission, RbacPolicyDocument, RoleAssignmentPolicy, RolePolicy, RBAC_POLICY_VERSION,
+    };
+    use security::rbac::{diff, import_scope, validate_policy, RbacService};
     use test_utils::database::create_test_pool;
     use uuid::Uuid;
 
@@ -106,4 +108,200 @@ mod tests {
         assert_eq!(roles.len(), 1);
         assert_eq!(roles[0].name, "viewer");
     }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_delegate_and_revoke_permission() {
+        let rbac = setup().await;
+        let manager_id = Uuid::new_v4();
+        let cover_id = Uuid::new_v4();
+
+        let permissions = vec![Permission::new(
+            "billing".to_string(),
+            "approve".to_string(),
+        )];
+        let role = rbac
+            .create_role("approver".to_string(), None, permissions.clone())
+            .await
+            .expect("Failed to create role");
+
+        rbac.assign_role(manager_id, role.id, None, None)
+            .await
+            .expect("Failed to assign role");
+
+        let delegation = rbac
+            .delegate(
+                manager_id,
+                cover_id,
+                permissions,
+                chrono::Utc::now() + chrono::Duration::days(7),
+            )
+            .await
+            .expect("Failed to delegate permissions");
+
+        assert!(rbac
+            .has_permission(cover_id, "billing", "approve")
+            .await
+            .expect("Failed to check permission"));
+
+        rbac.revoke_delegation(delegation.id, Some(manager_id))
+            .await
+            .expect("Failed to revoke delegation");
+
+        assert!(!rbac
+            .has_permission(cover_id, "billing", "approve")
+            .await
+            .expect("Failed to check permission"));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_delegation_ends_when_delegator_loses_role() {
+        let rbac = setup().await;
+        let manager_id = Uuid::new_v4();
+        let cover_id = Uuid::new_v4();
+
+        let permissions = vec![Permission::new("billing".to_string(), "refund".to_string())];
+        let role = rbac
+            .create_role(
+                format!("refunder-{}", Uuid::new_v4()),
+                None,
+                permissions.clone(),
+            )
+            .await
+            .expect("Failed to create role");
+
+        rbac.assign_role(manager_id, role.id, None, None)
+            .await
+            .expect("Failed to assign role");
+
+        rbac.delegate(
+            manager_id,
+            cover_id,
+            permissions,
+            chrono::Utc::now() + chrono::Duration::days(7),
+        )
+        .await
+        .expect("Failed to delegate permissions");
+
+        rbac.remove_role(manager_id, role.id)
+            .await
+            .expect("Failed to remove role");
+
+        assert!(!rbac
+            .has_permission(cover_id, "billing", "refund")
+            .await
+            .expect("Failed to check permission"));
+    }
+
+    fn policy(
+        roles: Vec<RolePolicy>,
+        assignments: Vec<RoleAssignmentPolicy>,
+    ) -> RbacPolicyDocument {
+        RbacPolicyDocument {
+            version: RBAC_POLICY_VERSION,
+            exported_at: chrono::Utc::now(),
+            roles,
+            assignments,
+        }
+    }
+
+    fn role(name: &str, permissions: &[(&str, &str)]) -> RolePolicy {
+        RolePolicy {
+            name: name.to_string(),
+            description: None,
+            permissions: permissions
+                .iter()
+                .map(|(resource, action)| Permission::new(resource.to_string(), action.to_string()))
+                .collect(),
+        }
+    }
+
+    fn assignment(identity_id: Uuid, role: &str) -> RoleAssignmentPolicy {
+        RoleAssignmentPolicy {
+            identity_id,
+            role: role.to_string(),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_diff() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let current = policy(
+            vec![
+                role("operator", &[("catalog", "read")]),
+                role("legacy", &[("billing", "read")]),
+            ],
+            vec![assignment(alice, "operator"), assignment(bob, "legacy")],
+        );
+        let incoming = policy(
+            vec![
+                role("operator", &[("catalog", "read"), ("catalog", "write")]),
+                role("auditor", &[("audit", "read")]),
+            ],
+            vec![assignment(alice, "operator"), assignment(bob, "auditor")],
+        );
+
+        let changes = diff(&current, &incoming);
+
+        assert_eq!(changes.roles_added.len(), 1);
+        assert_eq!(changes.roles_added[0].name, "auditor");
+        assert_eq!(changes.roles_removed, vec!["legacy".to_string()]);
+        assert_eq!(changes.roles_changed.len(), 1);
+        assert_eq!(
+            changes.roles_changed[0].permissions_added,
+            vec![Permission::new("catalog".to_string(), "write".to_string())]
+        );
+        assert_eq!(changes.assignments_added, vec![assignment(bob, "auditor")]);
+        assert_eq!(changes.assignments_removed, vec![assignment(bob, "legacy")]);
+        assert!(diff(&incoming, &incoming).is_empty());
+    }
+
+    #[test]
+    fn test_import_leaves_roles_outside_the_document_alone() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let current = policy(
+            vec![
+                role("operator", &[("catalog", "read")]),
+                role("legacy", &[("billing", "read")]),
+            ],
+            vec![
+                assignment(alice, "operator"),
+                assignment(bob, "operator"),
+                assignment(bob, "legacy"),
+            ],
+        );
+        let incoming = policy(
+            vec![role("operator", &[("catalog", "read")])],
+            vec![assignment(alice, "operator")],
+        );
+
+        let changes = diff(&import_scope(&current, &incoming), &incoming);
+
+        assert!(changes.roles_removed.is_empty());
+        assert!(changes.roles_changed.is_empty());
+        assert_eq!(
+            changes.assignments_removed,
+            vec![assignment(bob, "operator")]
+        );
+        assert!(changes.assignments_added.is_empty());
+    }
+
+    #[test]
+    fn test_validate_policy_rejects_unknown_role() {
+        let document = policy(
+            vec![role("operator", &[("catalog", "read")])],
+            vec![assignment(Uuid::new_v4(), "admin")],
+        );
+        assert!(validate_policy(&document).is_err());
+
+        let duplicate = policy(
+            vec![role("operator", &[]), role("operator", &[])],
+            Vec::new(),
+        );
+        assert!(validate_policy(&duplicate).is_err());
+    }
 }
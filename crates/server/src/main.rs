@@ -6,31 +6,41 @@
 
 use actix_web::{middleware::Logger, web, App, HttpResponse, HttpServer, Result as ActixResult};
 use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use bss_oss_customer_360::models::{Customer360, Customer360Source, Customer360SourceError};
+use bss_oss_event_bus::bus::InMemoryEventBus;
+use bss_oss_event_bus::{EventBus, EventPublisher};
+use bss_oss_service_orchestrator::ServiceOrchestrator;
 use bss_oss_utils::init_logger;
 use graphql_api::create_schema;
 use prometheus::{Counter, Gauge, Histogram, Registry, TextEncoder};
+use std::sync::Arc;
 use tmf620_catalog::{db::init_db, models::*};
 use tmf622_ordering::models::{
-    CreateOrderItemRequest, CreateProductOrderRequest, OrderItem, OrderState,
-    ProductOfferingRef as Tmf622ProductOfferingRef, ProductOrder,
+    CreateOrderItemRequest, CreateOrderTemplateRequest, CreateProductOrderRequest,
+    OrderFromTemplateRequest, OrderItem, OrderState, OrderTemplateItem,
+    ProductOfferingRef as Tmf622ProductOfferingRef, ProductOrder, ProductOrderTemplate,
     ProductSpecificationRef as Tmf622ProductSpecificationRef, RelatedParty as Tmf622RelatedParty,
 };
+use tmf629_customer::credit::CreditDecision;
 use tmf629_customer::models::{
     AccountRef as Tmf629AccountRef, Characteristic as Tmf629Characteristic,
     ContactMedium as Tmf629ContactMedium, CreateContactMediumRequest, CreateCustomerRequest,
     CreateRelatedPartyRequest as Tmf629CreateRelatedPartyRequest, Customer, CustomerState,
     RelatedParty as Tmf629RelatedParty,
 };
+use tmf629_customer::patch::PatchOperation;
 use tmf632_party::models::{
     AccountRef as Tmf632AccountRef, Characteristic as Tmf632Characteristic,
     ContactMedium as Tmf632ContactMedium, CreateAccountRefRequest as Tmf632CreateAccountRefRequest,
     CreateCharacteristicRequest as Tmf632CreateCharacteristicRequest,
     CreateContactMediumRequest as Tmf632CreateContactMediumRequest, CreatePartyRequest,
-    CreateRelatedPartyRequest as Tmf632CreateRelatedPartyRequest, Party, PartyState, PartyType,
+    CreateRelatedPartyRequest as Tmf632CreateRelatedPartyRequest, KycDecision, KycEvidence,
+    KycStatus, KycVerification, KycVerificationRequest, Party, PartyState, PartyType,
     RelatedParty as Tmf632RelatedParty,
 };
 use tmf633_trouble_ticket::models::{
-    CreateTroubleTicketRequest, TroubleTicket, TroubleTicketPriority, TroubleTicketStatus,
+    CreateTroubleTicketFromAlarmRequest, CreateTroubleTicketRequest, LinkTroubleTicketRequest,
+    TroubleTicket, TroubleTicketPriority, TroubleTicketRelatedEntities, TroubleTicketStatus,
     TroubleTicketType, UpdateTroubleTicketRequest,
 };
 use tmf634_quote::models::{
@@ -46,16 +56,22 @@ use tmf637_inventory::models::{
     CreateProductInventoryRequest, CreateRelatedPartyRequest as Tmf637CreateRelatedPartyRequest,
     InventoryState, ProductInventory, ProductOfferingRef as Tmf637ProductOfferingRef,
     ProductSpecificationRef as Tmf637ProductSpecificationRef, RelatedParty as Tmf637RelatedParty,
+    StockLevel, StockThreshold, UpdateProductInventoryRequest,
 };
 use tmf638_service_inventory::models::{
     CreateRelatedPartyRequest as Tmf638CreateRelatedPartyRequest, CreateServiceInventoryRequest,
     RelatedParty as Tmf638RelatedParty, ServiceInventory, ServiceInventoryState,
-    ServiceRef as Tmf638ServiceRef, ServiceSpecificationRef as Tmf638ServiceSpecificationRef,
+    ServiceRef as Tmf638ServiceRef, ServiceResourceTrace,
+    ServiceSpecificationRef as Tmf638ServiceSpecificationRef, TracedResource,
 };
 use tmf639_resource_inventory::models::{
+    ApplyCorrectionsRequest, AttributeMismatch, CorrectionOutcome,
     CreateRelatedPartyRequest as Tmf639CreateRelatedPartyRequest, CreateResourceInventoryRequest,
-    RelatedParty as Tmf639RelatedParty, ResourceInventory, ResourceInventoryState,
-    ResourceRef as Tmf639ResourceRef, ResourceSpecificationRef as Tmf639ResourceSpecificationRef,
+    DiscoveredResource, DiscoverySnapshot, DiscrepancyKind, ReconciliationReport,
+    RelatedParty as Tmf639RelatedParty, ResourceCorrection, ResourceDiscrepancy, ResourceInventory,
+    ResourceInventoryState, ResourceLifecycleState, ResourceRef as Tmf639ResourceRef,
+    ResourceSpecificationRef as Tmf639ResourceSpecificationRef, ResourceStateChangeEvent,
+    UpdateResourceInventoryRequest,
 };
 use tmf640_service_activation::models::{
     ConfigurationParameter as Tmf640ConfigurationParameter,
@@ -74,46 +90,54 @@ use tmf642_alarm::models::{
     ResourceRef as Tmf642ResourceRef, UpdateAlarmRequest,
 };
 use tmf645_resource_order::models::{
+    CapacityRequirement as Tmf645CapacityRequirement,
     CreateRelatedPartyRequest as Tmf645CreateRelatedPartyRequest, CreateResourceOrderItemRequest,
     CreateResourceOrderRequest, RelatedParty as Tmf645RelatedParty, ResourceOrder,
-    ResourceOrderItem, ResourceOrderState, ResourceRef as Tmf645ResourceRef,
-    ResourceSpecificationRef as Tmf645ResourceSpecificationRef,
+    ResourceOrderFeasibility, ResourceOrderItem, ResourceOrderItemFeasibility, ResourceOrderState,
+    ResourceRef as Tmf645ResourceRef, ResourceSpecificationRef as Tmf645ResourceSpecificationRef,
 };
 use tmf656_slice::models::{
     CreateNetworkFunctionRefRequest, CreateNetworkSliceRequest, CreateSLAParametersRequest,
-    NetworkFunctionRef, NetworkSlice, SLAParameters, SliceState, SliceType,
-    UpdateNetworkSliceRequest,
+    InstantiateSliceTemplateRequest, KpiRange, NetworkFunctionRef, NetworkSlice, SLAParameters,
+    SliceResourceRequirements, SliceState, SliceTemplate, SliceTemplateRanges,
+    SliceTerminationEvent, SliceType, UpdateNetworkSliceRequest,
 };
 use tmf668_party_role::models::{
-    ContactMedium as Tmf668ContactMedium,
-    CreateContactMediumRequest as Tmf668CreateContactMediumRequest, CreatePartyRoleRequest,
-    CreateRelatedPartyRequest as Tmf668CreateRelatedPartyRequest, PartyRole, PartyRoleState,
-    RelatedParty as Tmf668RelatedParty,
+    ContactMedium as Tmf668ContactMedium, ContractState,
+    CreateContactMediumRequest as Tmf668CreateContactMediumRequest, CreatePartnerContractRequest,
+    CreatePartyRoleRequest, CreateRelatedPartyRequest as Tmf668CreateRelatedPartyRequest,
+    PartnerContract, PartnerContractEvent, PartyRole, PartyRoleConflict, PartyRoleState,
+    RelatedParty as Tmf668RelatedParty, RevenueShareTerm, UpdatePartnerContractRequest,
 };
 use tmf669_identity::models::{
-    CreateCredentialRequest, CreateIdentityRequest, Credential, CredentialType, Identity,
-    IdentityState, PartyRef as Tmf669PartyRef,
+    CreateCredentialRequest, CreateIdentityRequest, Credential, CredentialType,
+    CredentialUsageAnomaly, CredentialUsageRequest, CredentialUsageSummary, Identity,
+    IdentityState, PartyRef as Tmf669PartyRef, SetPasswordRequest, UsageAnomalyKind,
 };
+use tmf669_identity::password::{PasswordPolicyViolation, PasswordRule};
 use tmf678_billing::models::{
-    BillItem, BillState, CreateBillItemRequest, CreateCustomerBillRequest,
-    CreateRelatedPartyRequest as Tmf678CreateRelatedPartyRequest, CustomerBill, Money as BillMoney,
-    ProductOfferingRef as Tmf678ProductOfferingRef, RelatedParty as Tmf678RelatedParty,
+    BillItem, BillItemCategory, BillState, CategorySubtotal, CreateBillItemRequest,
+    CreateCustomerBillRequest, CreateRelatedPartyRequest as Tmf678CreateRelatedPartyRequest,
+    CustomerBill, Money as BillMoney, ProductOfferingRef as Tmf678ProductOfferingRef,
+    RelatedParty as Tmf678RelatedParty,
 };
 use tmf679_usage::models::{
     CreateCustomerUsageRequest, CreateRelatedPartyRequest as Tmf679CreateRelatedPartyRequest,
     CustomerUsage, RelatedParty as Tmf679RelatedParty, UsageState as Tmf679UsageState,
 };
 use tmf688_appointment::models::{
-    Appointment, AppointmentState, ContactMedium as Tmf688ContactMedium, CreateAppointmentRequest,
+    Appointment, AppointmentCancellationPolicy, AppointmentChangeResult, AppointmentState,
+    CancelAppointmentRequest, ContactMedium as Tmf688ContactMedium, CreateAppointmentRequest,
     CreateContactMediumRequest as Tmf688CreateContactMediumRequest,
-    CreateRelatedPartyRequest as Tmf688CreateRelatedPartyRequest,
-    RelatedParty as Tmf688RelatedParty,
+    CreateRelatedPartyRequest as Tmf688CreateRelatedPartyRequest, Money as AppointmentMoney,
+    RelatedParty as Tmf688RelatedParty, RescheduleAppointmentRequest,
 };
 use tmf702_resource_activation::models::{
-    ConfigurationParameter as Tmf702ConfigurationParameter,
+    ActivationJob, ActivationJobState, ConfigurationParameter as Tmf702ConfigurationParameter,
     CreateConfigurationParameterRequest as Tmf702CreateConfigurationParameterRequest,
     CreateResourceActivationRequest, ResourceActivation, ResourceActivationState,
     ResourceRef as Tmf702ResourceRef, ServiceActivationRef as Tmf702ServiceActivationRef,
+    SubmitActivationJobRequest,
 };
 use tmf_apis_core::{BaseEntity, LifecycleStatus, TimePeriod};
 use utoipa::OpenApi;
@@ -128,18 +152,30 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf620_catalog::handlers::create_catalog,
         tmf620_catalog::handlers::get_product_offerings,
         tmf620_catalog::handlers::create_product_offering,
+        tmf620_catalog::handlers::get_product_offering_translations,
+        tmf620_catalog::handlers::set_product_offering_translation,
         // TMF622
         tmf622_ordering::handlers::get_orders,
         tmf622_ordering::handlers::get_order_by_id,
         tmf622_ordering::handlers::create_order,
+        tmf622_ordering::handlers::get_order_templates,
+        tmf622_ordering::handlers::get_order_template_by_id,
+        tmf622_ordering::handlers::create_order_template,
+        tmf622_ordering::handlers::create_order_from_template,
         // TMF637
         tmf637_inventory::handlers::get_inventories,
         tmf637_inventory::handlers::get_inventory_by_id,
         tmf637_inventory::handlers::create_inventory,
+        tmf637_inventory::handlers::update_inventory,
+        tmf637_inventory::handlers::get_stock_levels,
+        tmf637_inventory::handlers::get_stock_level,
+        tmf637_inventory::handlers::get_stock_thresholds,
+        tmf637_inventory::handlers::set_stock_threshold,
         // TMF629
         tmf629_customer::handlers::get_customers,
         tmf629_customer::handlers::get_customer_by_id,
         tmf629_customer::handlers::create_customer,
+        tmf629_customer::handlers::patch_customer,
         // TMF678
         tmf678_billing::handlers::get_bills,
         tmf678_billing::handlers::get_bill_by_id,
@@ -148,10 +184,15 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf679_usage::handlers::get_usages,
         tmf679_usage::handlers::get_usage_by_id,
         tmf679_usage::handlers::create_usage,
+        tmf679_usage::handlers::get_usages_requiring_rerating,
         // TMF688
         tmf688_appointment::handlers::get_appointments,
         tmf688_appointment::handlers::get_appointment_by_id,
         tmf688_appointment::handlers::create_appointment,
+        tmf688_appointment::handlers::cancel_appointment,
+        tmf688_appointment::handlers::reschedule_appointment,
+        tmf688_appointment::handlers::get_cancellation_policies,
+        tmf688_appointment::handlers::set_cancellation_policy,
         // TMF641
         tmf641_service_order::handlers::get_service_orders,
         tmf641_service_order::handlers::get_service_order_by_id,
@@ -160,6 +201,7 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf638_service_inventory::handlers::get_service_inventories,
         tmf638_service_inventory::handlers::get_service_inventory_by_id,
         tmf638_service_inventory::handlers::create_service_inventory,
+        tmf638_service_inventory::handlers::get_service_resources,
         // TMF640
         tmf640_service_activation::handlers::get_service_activations,
         tmf640_service_activation::handlers::get_service_activation_by_id,
@@ -168,14 +210,20 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf702_resource_activation::handlers::get_resource_activations,
         tmf702_resource_activation::handlers::get_resource_activation_by_id,
         tmf702_resource_activation::handlers::create_resource_activation,
+        tmf702_resource_activation::handlers::submit_activation_job,
+        tmf702_resource_activation::handlers::get_activation_job_by_id,
         // TMF639
         tmf639_resource_inventory::handlers::get_resource_inventories,
         tmf639_resource_inventory::handlers::get_resource_inventory_by_id,
         tmf639_resource_inventory::handlers::create_resource_inventory,
+        tmf639_resource_inventory::handlers::update_resource_inventory,
+        tmf639_resource_inventory::handlers::reconcile_resource_inventory,
+        tmf639_resource_inventory::handlers::apply_reconciliation_corrections,
         // TMF645
         tmf645_resource_order::handlers::get_resource_orders,
         tmf645_resource_order::handlers::get_resource_order_by_id,
         tmf645_resource_order::handlers::create_resource_order,
+        tmf645_resource_order::handlers::check_resource_order_feasibility,
         // TMF635
         tmf635_usage::handlers::get_usages,
         tmf635_usage::handlers::get_usage_by_id,
@@ -184,14 +232,25 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf668_party_role::handlers::get_party_roles,
         tmf668_party_role::handlers::get_party_role_by_id,
         tmf668_party_role::handlers::create_party_role,
+        tmf668_party_role::handlers::get_party_role_conflicts,
+        tmf668_party_role::handlers::create_partner_contract,
+        tmf668_party_role::handlers::get_partner_contracts,
+        tmf668_party_role::handlers::get_partner_contract_by_id,
+        tmf668_party_role::handlers::update_partner_contract,
         // TMF632
         tmf632_party::handlers::get_parties,
         tmf632_party::handlers::get_party_by_id,
         tmf632_party::handlers::create_party,
+        tmf632_party::handlers::create_kyc_verification,
+        tmf632_party::handlers::get_kyc_verifications,
         // TMF669
         tmf669_identity::handlers::get_identities,
         tmf669_identity::handlers::get_identity_by_id,
         tmf669_identity::handlers::create_identity,
+        tmf669_identity::handlers::set_password,
+        tmf669_identity::handlers::record_credential_usage,
+        tmf669_identity::handlers::get_credential_usage,
+        tmf669_identity::handlers::get_credential_usage_anomalies,
         // TMF642
         tmf642_alarm::handlers::get_alarms,
         tmf642_alarm::handlers::get_alarm_by_id,
@@ -204,18 +263,26 @@ use utoipa_swagger_ui::SwaggerUi;
         tmf656_slice::handlers::create_network_slice,
         tmf656_slice::handlers::update_network_slice,
         tmf656_slice::handlers::delete_network_slice,
+        tmf656_slice::handlers::get_slice_templates,
+        tmf656_slice::handlers::get_slice_template_by_id,
+        tmf656_slice::handlers::instantiate_slice_template,
         // TMF633
         tmf633_trouble_ticket::handlers::get_trouble_tickets,
         tmf633_trouble_ticket::handlers::get_trouble_ticket_by_id,
         tmf633_trouble_ticket::handlers::create_trouble_ticket,
         tmf633_trouble_ticket::handlers::update_trouble_ticket,
         tmf633_trouble_ticket::handlers::delete_trouble_ticket,
+        tmf633_trouble_ticket::handlers::create_trouble_ticket_from_alarm,
+        tmf633_trouble_ticket::handlers::link_trouble_ticket,
+        tmf633_trouble_ticket::handlers::get_trouble_ticket_related_entities,
         // TMF634
         tmf634_quote::handlers::get_quotes,
         tmf634_quote::handlers::get_quote_by_id,
         tmf634_quote::handlers::create_quote,
         tmf634_quote::handlers::update_quote,
         tmf634_quote::handlers::delete_quote,
+        // Customer 360
+        bss_oss_customer_360::handlers::get_customer_360,
     ),
     components(schemas(
         // TMF620
@@ -223,6 +290,7 @@ use utoipa_swagger_ui::SwaggerUi;
         ProductOffering,
         CreateCatalogRequest,
         CreateProductOfferingRequest,
+        LocalizedContent,
         ProductOfferingRef,
         ProductOfferingPrice,
         ProductSpecificationRef,
@@ -237,9 +305,16 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf622ProductOfferingRef,
         Tmf622ProductSpecificationRef,
         Tmf622RelatedParty,
+        ProductOrderTemplate,
+        OrderTemplateItem,
+        CreateOrderTemplateRequest,
+        OrderFromTemplateRequest,
         // TMF637
         ProductInventory,
         CreateProductInventoryRequest,
+        UpdateProductInventoryRequest,
+        StockLevel,
+        StockThreshold,
         Tmf637CreateRelatedPartyRequest,
         InventoryState,
         Tmf637ProductOfferingRef,
@@ -248,6 +323,7 @@ use utoipa_swagger_ui::SwaggerUi;
         // TMF629
         Customer,
         CreateCustomerRequest,
+        CreditDecision,
         CreateContactMediumRequest,
         Tmf629CreateRelatedPartyRequest,
         CustomerState,
@@ -255,6 +331,7 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf629Characteristic,
         Tmf629ContactMedium,
         Tmf629RelatedParty,
+        PatchOperation,
         // TMF678
         CustomerBill,
         CreateCustomerBillRequest,
@@ -262,6 +339,8 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf678CreateRelatedPartyRequest,
         BillState,
         BillItem,
+        BillItemCategory,
+        CategorySubtotal,
         BillMoney,
         Tmf678ProductOfferingRef,
         Tmf678RelatedParty,
@@ -279,6 +358,11 @@ use utoipa_swagger_ui::SwaggerUi;
         AppointmentState,
         Tmf688ContactMedium,
         Tmf688RelatedParty,
+        AppointmentCancellationPolicy,
+        AppointmentMoney,
+        CancelAppointmentRequest,
+        RescheduleAppointmentRequest,
+        AppointmentChangeResult,
         // TMF641
         ServiceOrder,
         CreateServiceOrderRequest,
@@ -297,6 +381,8 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf638ServiceRef,
         Tmf638RelatedParty,
         Tmf638CreateRelatedPartyRequest,
+        ServiceResourceTrace,
+        TracedResource,
         // TMF640
         ServiceActivation,
         CreateServiceActivationRequest,
@@ -313,19 +399,37 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf702ServiceActivationRef,
         Tmf702ConfigurationParameter,
         Tmf702CreateConfigurationParameterRequest,
+        ActivationJob,
+        ActivationJobState,
+        SubmitActivationJobRequest,
         // TMF639
         ResourceInventory,
         CreateResourceInventoryRequest,
         ResourceInventoryState,
+        ResourceLifecycleState,
+        UpdateResourceInventoryRequest,
+        ResourceStateChangeEvent,
         Tmf639ResourceSpecificationRef,
         Tmf639ResourceRef,
         Tmf639RelatedParty,
         Tmf639CreateRelatedPartyRequest,
+        DiscoveredResource,
+        DiscoverySnapshot,
+        DiscrepancyKind,
+        AttributeMismatch,
+        ResourceCorrection,
+        ResourceDiscrepancy,
+        ReconciliationReport,
+        ApplyCorrectionsRequest,
+        CorrectionOutcome,
         // TMF645
         ResourceOrder,
         CreateResourceOrderRequest,
         ResourceOrderItem,
         CreateResourceOrderItemRequest,
+        Tmf645CapacityRequirement,
+        ResourceOrderFeasibility,
+        ResourceOrderItemFeasibility,
         ResourceOrderState,
         Tmf645ResourceSpecificationRef,
         Tmf645ResourceRef,
@@ -347,11 +451,23 @@ use utoipa_swagger_ui::SwaggerUi;
         Tmf668CreateContactMediumRequest,
         Tmf668RelatedParty,
         Tmf668CreateRelatedPartyRequest,
+        PartyRoleConflict,
+        ContractState,
+        RevenueShareTerm,
+        PartnerContract,
+        CreatePartnerContractRequest,
+        UpdatePartnerContractRequest,
+        PartnerContractEvent,
         // TMF632
         Party,
         CreatePartyRequest,
         PartyState,
         PartyType,
+        KycStatus,
+        KycEvidence,
+        KycVerificationRequest,
+        KycDecision,
+        KycVerification,
         Tmf632ContactMedium,
         Tmf632CreateContactMediumRequest,
         Tmf632RelatedParty,
@@ -368,6 +484,13 @@ use utoipa_swagger_ui::SwaggerUi;
         CreateCredentialRequest,
         CredentialType,
         Tmf669PartyRef,
+        SetPasswordRequest,
+        PasswordPolicyViolation,
+        PasswordRule,
+        CredentialUsageRequest,
+        CredentialUsageSummary,
+        CredentialUsageAnomaly,
+        UsageAnomalyKind,
         // TMF642
         Alarm,
         CreateAlarmRequest,
@@ -381,15 +504,24 @@ use utoipa_swagger_ui::SwaggerUi;
         CreateNetworkSliceRequest,
         UpdateNetworkSliceRequest,
         SliceState,
+        SliceTerminationEvent,
         SliceType,
         SLAParameters,
         CreateSLAParametersRequest,
         NetworkFunctionRef,
         CreateNetworkFunctionRefRequest,
+        SliceTemplate,
+        SliceTemplateRanges,
+        KpiRange,
+        SliceResourceRequirements,
+        InstantiateSliceTemplateRequest,
         // TMF633
         TroubleTicket,
         CreateTroubleTicketRequest,
         UpdateTroubleTicketRequest,
+        CreateTroubleTicketFromAlarmRequest,
+        LinkTroubleTicketRequest,
+        TroubleTicketRelatedEntities,
         TroubleTicketStatus,
         TroubleTicketPriority,
         TroubleTicketType,
@@ -400,6 +532,10 @@ use utoipa_swagger_ui::SwaggerUi;
         QuoteState,
         QuoteItem,
         Tmf634RelatedParty,
+        // Customer 360
+        Customer360,
+        Customer360Source,
+        Customer360SourceError,
         // Common
         BaseEntity,
         LifecycleStatus,
@@ -426,7 +562,8 @@ use utoipa_swagger_ui::SwaggerUi;
         (name = "TMF642", description = "Alarm Management API"),
         (name = "TMF656", description = "Slice Management API"),
         (name = "TMF633", description = "Trouble Ticket Management API"),
-        (name = "TMF634", description = "Quote Management API")
+        (name = "TMF634", description = "Quote Management API"),
+        (name = "Customer 360", description = "Customer 360 View for care agents")
     ),
     info(
         title = "BSS/OSS Rust - TM Forum Open APIs",
@@ -566,6 +703,23 @@ async fn main() -> std::io::Result<()> {
     let pool = init_db().await;
     log::info!("✅ Database connection established");
 
+    // Activation jobs run in-process, so any left unfinished were lost with the previous run
+    match tmf702_resource_activation::jobs::fail_interrupted_jobs(&pool).await {
+        Ok(0) => {}
+        Ok(failed) => log::warn!("⚠️  Marked {} interrupted activation jobs failed", failed),
+        Err(e) => log::error!("Failed to recover interrupted activation jobs: {}", e),
+    }
+
+    // Fail service order workflows whose approval gate nobody decided on in time
+    Arc::new(ServiceOrchestrator::new(pool.clone()))
+        .spawn_approval_timeouts(std::time::Duration::from_secs(60));
+
+    // Terminate party roles once their validity period has lapsed
+    tmf668_party_role::effective::spawn_role_expiry(
+        pool.clone(),
+        std::time::Duration::from_secs(300),
+    );
+
     let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = std::env::var("PORT")
         .unwrap_or_else(|_| "8080".to_string())
@@ -646,6 +800,26 @@ async fn main() -> std::io::Result<()> {
 
     let registry_data = web::Data::new(registry.clone());
 
+    // One event bus for the whole app, so every API publishes to the same subscribers
+    let event_bus = InMemoryEventBus::new();
+    let event_publisher: web::Data<Arc<dyn EventPublisher>> =
+        web::Data::new(Arc::from(event_bus.publisher()));
+
+    // Care agents allowed to view any customer's 360 view
+    let customer_360_config = web::Data::new(bss_oss_customer_360::Customer360Config {
+        agents: std::env::var("CUSTOMER360_AGENTS")
+            .map(|agents| {
+                agents
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|agent| !agent.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ..Default::default()
+    });
+
     let server = HttpServer::new(move || {
         let schema = schema.clone();
         let registry = registry_data.clone();
@@ -653,6 +827,8 @@ async fn main() -> std::io::Result<()> {
             .app_data(actix_web::web::Data::new(pool.clone()))
             .app_data(actix_web::web::Data::new(schema))
             .app_data(registry.clone())
+            .app_data(customer_360_config.clone())
+            .app_data(event_publisher.clone())
             .wrap(Logger::default())
             .route("/health", web::get().to(health_check))
             .route("/ready", web::get().to(readiness_check))
@@ -689,6 +865,7 @@ async fn main() -> std::io::Result<()> {
             .configure(tmf656_slice::api::configure_routes)
             .configure(tmf633_trouble_ticket::api::configure_routes)
             .configure(tmf634_quote::api::configure_routes)
+            .configure(bss_oss_customer_360::handlers::configure_routes)
     })
     .bind((host.as_str(), port))?
     .shutdown_timeout(30); // 30 seconds for graceful shutdown
@@ -2,16 +2,54 @@
 
 use crate::dependencies::ServiceDependencyGraph;
 use crate::state::{ServiceLifecycleState, ServiceWorkflowContext};
+use async_trait::async_trait;
 use sqlx::PgPool;
 use std::sync::Arc;
 use tmf638_service_inventory::CreateServiceInventoryRequest;
 use tmf640_service_activation::CreateServiceActivationRequest;
 use uuid::Uuid;
 
+/// Why a network element did not apply a service configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementFailure {
+    /// The element is temporarily unable to take the change; the step may be retried
+    Busy(String),
+    /// The element refused the change
+    Rejected(String),
+}
+
+/// Configures the network elements for a service activation
+#[async_trait]
+pub trait ServiceActivator: Send + Sync {
+    /// Apply the configuration for one service specification of an order
+    async fn activate(
+        &self,
+        activation_id: Uuid,
+        service_spec_id: Uuid,
+    ) -> Result<(), ElementFailure>;
+}
+
+/// Activator that completes immediately, used when no network element
+/// integration is registered
+#[derive(Debug, Default)]
+pub struct ImmediateActivator;
+
+#[async_trait]
+impl ServiceActivator for ImmediateActivator {
+    async fn activate(
+        &self,
+        _activation_id: Uuid,
+        _service_spec_id: Uuid,
+    ) -> Result<(), ElementFailure> {
+        Ok(())
+    }
+}
+
 /// Service activation automation engine
 pub struct ServiceActivationEngine {
     pool: Arc<PgPool>,
     dependency_graph: Arc<tokio::sync::RwLock<ServiceDependencyGraph>>,
+    activator: Arc<dyn ServiceActivator>,
 }
 
 impl ServiceActivationEngine {
@@ -19,9 +57,16 @@ impl ServiceActivationEngine {
         Self {
             pool: Arc::new(pool),
             dependency_graph: Arc::new(tokio::sync::RwLock::new(ServiceDependencyGraph::new())),
+            activator: Arc::new(ImmediateActivator),
         }
     }
 
+    /// Configure network elements through the given activator
+    pub fn with_activator(mut self, activator: Arc<dyn ServiceActivator>) -> Self {
+        self.activator = activator;
+        self
+    }
+
     /// Automatically trigger service activation when dependencies are met
     pub async fn auto_activate_service(
         &self,
@@ -46,8 +91,10 @@ impl ServiceActivationEngine {
             configuration: None,
         };
 
-        // Create service activation via database
-        let activation_id = self.create_service_activation(activation_request).await?;
+        // Find or create the order item's activation, so a retry reuses it
+        let (activation_id, state) = self
+            .find_or_create_service_activation(activation_request, service_spec_id)
+            .await?;
 
         // Update workflow context
         if let Some(task) = context
@@ -59,8 +106,11 @@ impl ServiceActivationEngine {
             task.state = ServiceLifecycleState::ReadyForActivation;
         }
 
-        // Execute activation
-        self.execute_activation(activation_id).await?;
+        // Execute activation unless an earlier attempt already completed it
+        if state != "COMPLETED" {
+            self.execute_activation(activation_id, service_spec_id)
+                .await?;
+        }
 
         // Update workflow context
         if let Some(task) = context.tasks.iter_mut().find(|t| {
@@ -93,6 +143,8 @@ impl ServiceActivationEngine {
             service_specification_id: Some(service_spec_id),
             service_id: None,
             related_party: None,
+            supporting_service_ids: None,
+            supporting_resource_ids: None,
         };
 
         let inventory_id = self.create_inventory(inventory_request).await?;
@@ -117,18 +169,23 @@ impl ServiceActivationEngine {
         Ok(inventory_id)
     }
 
-    /// Create service activation in database
-    async fn create_service_activation(
+    /// Find the activation for an order item, creating it on first use,
+    /// and return its id and state
+    async fn find_or_create_service_activation(
         &self,
         request: CreateServiceActivationRequest,
-    ) -> Result<Uuid, ActivationError> {
+        service_spec_id: Uuid,
+    ) -> Result<(Uuid, String), ActivationError> {
         let id = Uuid::new_v4();
         let now = chrono::Utc::now();
         let state = "PENDING";
 
-        sqlx::query(
-            "INSERT INTO service_activations (id, name, description, version, state, activation_date, service_order_id)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        let row: (Uuid, String) = sqlx::query_as(
+            "INSERT INTO service_activations (id, name, description, version, state, activation_date, service_order_id, service_specification_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (service_order_id, service_specification_id)
+             DO UPDATE SET last_update = EXCLUDED.last_update
+             RETURNING id, state",
         )
         .bind(id)
         .bind(&request.name)
@@ -137,15 +194,20 @@ impl ServiceActivationEngine {
         .bind(state)
         .bind(now)
         .bind(request.service_order_id)
-        .execute(self.pool.as_ref())
+        .bind(service_spec_id)
+        .fetch_one(self.pool.as_ref())
         .await
         .map_err(ActivationError::Database)?;
 
-        Ok(id)
+        Ok(row)
     }
 
     /// Execute service activation
-    async fn execute_activation(&self, activation_id: Uuid) -> Result<(), ActivationError> {
+    async fn execute_activation(
+        &self,
+        activation_id: Uuid,
+        service_spec_id: Uuid,
+    ) -> Result<(), ActivationError> {
         // Update activation state to IN_PROGRESS
         sqlx::query("UPDATE service_activations SET state = $1 WHERE id = $2")
             .bind("IN_PROGRESS")
@@ -154,13 +216,26 @@ impl ServiceActivationEngine {
             .await
             .map_err(ActivationError::Database)?;
 
-        // In a real implementation, this would:
-        // 1. Call external provisioning systems
-        // 2. Configure network elements
-        // 3. Update service state
-        // For now, we'll just mark it as completed
+        // Configure the network elements
+        if let Err(failure) = self
+            .activator
+            .activate(activation_id, service_spec_id)
+            .await
+        {
+            // A busy element leaves the activation pending for the next attempt
+            let (state, error) = match failure {
+                ElementFailure::Busy(msg) => ("PENDING", ActivationError::ElementBusy(msg)),
+                ElementFailure::Rejected(msg) => ("FAILED", ActivationError::ElementRejected(msg)),
+            };
+            sqlx::query("UPDATE service_activations SET state = $1 WHERE id = $2")
+                .bind(state)
+                .bind(activation_id)
+                .execute(self.pool.as_ref())
+                .await
+                .map_err(ActivationError::Database)?;
+            return Err(error);
+        }
 
-        // Simulate activation completion
         let completion_date = chrono::Utc::now();
         sqlx::query(
             "UPDATE service_activations SET state = $1, completion_date = $2 WHERE id = $3",
@@ -216,4 +291,31 @@ pub enum ActivationError {
     InventoryNotFound,
     #[error("Invalid activation state")]
     InvalidState,
+    #[error("Network element busy: {0}")]
+    ElementBusy(String),
+    #[error("Network element rejected the activation: {0}")]
+    ElementRejected(String),
+}
+
+impl ActivationError {
+    /// Whether the failure is transient and the step may be retried
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ActivationError::ElementBusy(_) => true,
+            ActivationError::Database(err) => match err {
+                sqlx::Error::PoolTimedOut | sqlx::Error::Io(_) | sqlx::Error::WorkerCrashed => true,
+                // Serialization failure, deadlock and lock timeout
+                sqlx::Error::Database(db_err) => matches!(
+                    db_err.code().as_deref(),
+                    Some("40001") | Some("40P01") | Some("55P03")
+                ),
+                _ => false,
+            },
+            ActivationError::DependenciesNotMet
+            | ActivationError::ActivationNotFound
+            | ActivationError::InventoryNotFound
+            | ActivationError::InvalidState
+            | ActivationError::ElementRejected(_) => false,
+        }
+    }
 }
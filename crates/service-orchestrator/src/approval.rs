@@ -0,0 +1,116 @@
+//! Manual Approval Gates
+//!
+//! A manual-approval task pauses the workflow in the `AwaitingApproval` state
+//! once its dependencies complete. An approve call resumes the workflow; a
+//! reject call, or the gate timing out, fails it. The decision, who made it and
+//! when are kept on the gate in the workflow context.
+
+use crate::state::{ServiceLifecycleState, ServiceTaskType};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Name recorded as the decider when a gate times out
+pub const TIMEOUT_DECIDER: &str = "system";
+
+/// Configuration of an approval gate inserted into new workflows
+#[derive(Debug, Clone)]
+pub struct ApprovalGateConfig {
+    /// Name shown to approvers
+    pub name: String,
+    /// Task the gate must be passed before
+    pub before: ServiceTaskType,
+    /// Time after which a pending gate is rejected automatically
+    pub timeout: Duration,
+}
+
+impl ApprovalGateConfig {
+    /// Gate in front of service activation
+    pub fn before_activation(name: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            name: name.into(),
+            before: ServiceTaskType::CreateActivation,
+            timeout,
+        }
+    }
+}
+
+/// Outcome of an approval gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApprovalOutcome {
+    Approved,
+    Rejected,
+    TimedOut,
+}
+
+/// Recorded approval decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub outcome: ApprovalOutcome,
+    /// Approver, or [`TIMEOUT_DECIDER`] for timeouts
+    pub decided_by: String,
+    pub decided_at: DateTime<Utc>,
+    pub comment: Option<String>,
+}
+
+/// Approval gate state held on a manual-approval task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalGate {
+    pub name: String,
+    pub timeout_seconds: i64,
+    /// When the gate opened and the workflow paused
+    pub requested_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Workflow state to resume in once approved
+    pub resume_state: Option<ServiceLifecycleState>,
+    pub decision: Option<ApprovalDecision>,
+}
+
+impl ApprovalGate {
+    pub fn new(config: &ApprovalGateConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            timeout_seconds: config.timeout.num_seconds(),
+            requested_at: None,
+            expires_at: None,
+            resume_state: None,
+            decision: None,
+        }
+    }
+
+    /// Open the gate, pausing a workflow currently in `current_state`
+    pub fn open(&mut self, current_state: ServiceLifecycleState, now: DateTime<Utc>) {
+        self.requested_at = Some(now);
+        self.expires_at = Some(now + Duration::seconds(self.timeout_seconds));
+        self.resume_state = Some(current_state);
+    }
+
+    /// Clear the request and decision so approval is asked for again
+    pub fn reset(&mut self) {
+        self.requested_at = None;
+        self.expires_at = None;
+        self.resume_state = None;
+        self.decision = None;
+    }
+
+    /// Whether a pending gate has passed its deadline
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.decision.is_none() && self.expires_at.is_some_and(|expires| now >= expires)
+    }
+
+    /// Record the decision
+    pub fn decide(
+        &mut self,
+        outcome: ApprovalOutcome,
+        decided_by: impl Into<String>,
+        comment: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        self.decision = Some(ApprovalDecision {
+            outcome,
+            decided_by: decided_by.into(),
+            decided_at: now,
+            comment,
+        });
+    }
+}
@@ -1,7 +1,9 @@
 //! Service Dependency Management
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Service dependency relationship
@@ -15,6 +17,73 @@ pub struct ServiceDependency {
     pub dependency_type: DependencyType,
     /// Whether the dependency is required
     pub required: bool,
+    /// What to do with the dependent service if this dependency fails
+    #[serde(default)]
+    pub failure_policy: DependencyFailurePolicy,
+}
+
+/// Behavior applied to a dependent service when one of its dependencies fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "policy", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DependencyFailurePolicy {
+    /// Fail the dependent service immediately
+    FailDependent,
+    /// Send the failed dependency back for provisioning, failing the dependent
+    /// once `max_retries` is exhausted
+    RetryDependency { max_retries: u32 },
+    /// Keep the dependent pending until the dependency recovers
+    #[default]
+    HoldPending,
+}
+
+impl DependencyFailurePolicy {
+    fn to_db(self) -> (&'static str, i32) {
+        match self {
+            DependencyFailurePolicy::FailDependent => ("FAIL_DEPENDENT", 0),
+            DependencyFailurePolicy::RetryDependency { max_retries } => {
+                ("RETRY_DEPENDENCY", max_retries as i32)
+            }
+            DependencyFailurePolicy::HoldPending => ("HOLD_PENDING", 0),
+        }
+    }
+
+    fn from_db(policy: &str, max_retries: i32) -> Self {
+        match policy {
+            "FAIL_DEPENDENT" => DependencyFailurePolicy::FailDependent,
+            "RETRY_DEPENDENCY" => DependencyFailurePolicy::RetryDependency {
+                max_retries: max_retries.max(0) as u32,
+            },
+            _ => DependencyFailurePolicy::HoldPending,
+        }
+    }
+}
+
+/// Action taken on a dependent service after a dependency failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DependencyFailureAction {
+    /// The dependent was failed
+    FailedDependent,
+    /// The dependency was sent back for provisioning
+    RetriedDependency,
+    /// The dependent is held pending
+    HeldPending,
+}
+
+/// Recorded decision for a failed dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyFailureDecision {
+    /// Service specification whose dependency failed
+    pub service_spec_id: Uuid,
+    /// Service specification that failed
+    pub failed_dependency_spec_id: Uuid,
+    /// Policy that drove the decision
+    pub policy: DependencyFailurePolicy,
+    /// Action taken
+    pub action: DependencyFailureAction,
+    /// Retry attempt number (for retries)
+    pub attempt: u32,
+    pub decided_at: DateTime<Utc>,
 }
 
 /// Type of service dependency
@@ -43,6 +112,8 @@ struct ServiceDependencyNode {
     pub dependencies: Vec<Uuid>, // IDs of service specs this depends on
     pub dependents: Vec<Uuid>,   // IDs of service specs that depend on this
     pub state: DependencyNodeState,
+    pub failure_policies: HashMap<Uuid, DependencyFailurePolicy>, // Per-dependency failure policy
+    pub retry_count: u32, // Times this spec was sent back for provisioning after failing
 }
 
 /// Dependency node state
@@ -52,6 +123,7 @@ enum DependencyNodeState {
     Provisioning,
     Active,
     Inactive,
+    Failed,
 }
 
 impl ServiceDependencyGraph {
@@ -65,7 +137,8 @@ impl ServiceDependencyGraph {
 
         // Load all service dependencies
         let rows = sqlx::query(
-            "SELECT service_specification_id, depends_on_specification_id, dependency_type, required
+            "SELECT service_specification_id, depends_on_specification_id, dependency_type, required,
+             failure_policy, failure_max_retries
              FROM service_dependencies",
         )
         .fetch_all(pool)
@@ -73,10 +146,18 @@ impl ServiceDependencyGraph {
 
         let mut spec_deps: std::collections::HashMap<Uuid, Vec<Uuid>> =
             std::collections::HashMap::new();
+        let mut spec_policies: HashMap<Uuid, HashMap<Uuid, DependencyFailurePolicy>> =
+            HashMap::new();
         for row in rows {
             let spec_id: Uuid = row.get(0);
             let dep_spec_id: Uuid = row.get(1);
+            let policy: String = row.get(4);
+            let max_retries: i32 = row.get(5);
             spec_deps.entry(spec_id).or_default().push(dep_spec_id);
+            spec_policies.entry(spec_id).or_default().insert(
+                dep_spec_id,
+                DependencyFailurePolicy::from_db(&policy, max_retries),
+            );
         }
 
         // Load service specification states
@@ -98,6 +179,7 @@ impl ServiceDependencyGraph {
                 "PROVISIONING" => DependencyNodeState::Provisioning,
                 "ACTIVE" => DependencyNodeState::Active,
                 "INACTIVE" => DependencyNodeState::Inactive,
+                "FAILED" => DependencyNodeState::Failed,
                 _ => DependencyNodeState::NotProvisioned,
             };
             spec_states.insert(spec_id, (service_id, state));
@@ -115,6 +197,8 @@ impl ServiceDependencyGraph {
                 dependencies: deps.clone(),
                 dependents: vec![],
                 state,
+                failure_policies: spec_policies.remove(&spec_id).unwrap_or_default(),
+                retry_count: 0,
             });
         }
 
@@ -151,15 +235,24 @@ impl ServiceDependencyGraph {
         // Insert all dependencies
         for node in &self.nodes {
             for dep_spec_id in &node.dependencies {
+                let (policy, max_retries) = node
+                    .failure_policies
+                    .get(dep_spec_id)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_db();
                 sqlx::query(
-                    "INSERT INTO service_dependencies (service_specification_id, depends_on_specification_id, dependency_type, required)
-                     VALUES ($1, $2, $3, $4)
+                    "INSERT INTO service_dependencies (service_specification_id, depends_on_specification_id, dependency_type, required,
+                     failure_policy, failure_max_retries)
+                     VALUES ($1, $2, $3, $4, $5, $6)
                      ON CONFLICT (service_specification_id, depends_on_specification_id) DO NOTHING",
                 )
                 .bind(node.service_spec_id)
                 .bind(dep_spec_id)
                 .bind("REQUIRES_ACTIVE")
                 .bind(true)
+                .bind(policy)
+                .bind(max_retries)
                 .execute(pool)
                 .await?;
             }
@@ -172,6 +265,7 @@ impl ServiceDependencyGraph {
                 DependencyNodeState::Provisioning => "PROVISIONING",
                 DependencyNodeState::Active => "ACTIVE",
                 DependencyNodeState::Inactive => "INACTIVE",
+                DependencyNodeState::Failed => "FAILED",
             };
 
             sqlx::query(
@@ -207,6 +301,32 @@ impl ServiceDependencyGraph {
         Ok(rows.iter().map(|row| row.get(0)).collect())
     }
 
+    /// Load failure policies for a specific service specification's dependencies
+    pub async fn load_failure_policies_for_spec(
+        pool: &PgPool,
+        service_spec_id: Uuid,
+    ) -> Result<Vec<(Uuid, DependencyFailurePolicy)>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT depends_on_specification_id, failure_policy, failure_max_retries
+             FROM service_dependencies
+             WHERE service_specification_id = $1",
+        )
+        .bind(service_spec_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let policy: String = row.get(1);
+                (
+                    row.get(0),
+                    DependencyFailurePolicy::from_db(&policy, row.get(2)),
+                )
+            })
+            .collect())
+    }
+
     /// Add a service specification with its dependencies
     pub fn add_service_spec(&mut self, service_spec_id: Uuid, dependencies: Vec<Uuid>) {
         // Update dependents for each dependency
@@ -234,10 +354,28 @@ impl ServiceDependencyGraph {
                 dependencies,
                 dependents: vec![],
                 state: DependencyNodeState::NotProvisioned,
+                failure_policies: HashMap::new(),
+                retry_count: 0,
             });
         }
     }
 
+    /// Set the failure policy for one dependency of a service specification
+    pub fn set_failure_policy(
+        &mut self,
+        service_spec_id: Uuid,
+        depends_on_spec_id: Uuid,
+        policy: DependencyFailurePolicy,
+    ) {
+        if let Some(node) = self
+            .nodes
+            .iter_mut()
+            .find(|n| n.service_spec_id == service_spec_id)
+        {
+            node.failure_policies.insert(depends_on_spec_id, policy);
+        }
+    }
+
     /// Get service specifications that are ready to be provisioned
     /// (all their dependencies are active)
     pub fn get_ready_specs(&self) -> Vec<Uuid> {
@@ -295,6 +433,95 @@ impl ServiceDependencyGraph {
         }
     }
 
+    /// Mark a service specification as failed
+    pub fn mark_failed(&mut self, service_spec_id: Uuid) {
+        if let Some(node) = self
+            .nodes
+            .iter_mut()
+            .find(|n| n.service_spec_id == service_spec_id)
+        {
+            node.state = DependencyNodeState::Failed;
+        }
+    }
+
+    /// Apply failure policies for any failed dependencies of a service specification
+    ///
+    /// Returns one decision per failed dependency. Retried dependencies are reset
+    /// to not provisioned so they are picked up again by `get_ready_specs`.
+    pub fn resolve_dependency_failures(
+        &mut self,
+        service_spec_id: Uuid,
+    ) -> Vec<DependencyFailureDecision> {
+        let Some(node) = self
+            .nodes
+            .iter()
+            .find(|n| n.service_spec_id == service_spec_id)
+        else {
+            return vec![];
+        };
+
+        let failed: Vec<(Uuid, DependencyFailurePolicy)> = node
+            .dependencies
+            .iter()
+            .filter(|dep_spec_id| {
+                self.nodes.iter().any(|n| {
+                    n.service_spec_id == **dep_spec_id && n.state == DependencyNodeState::Failed
+                })
+            })
+            .map(|dep_spec_id| {
+                (
+                    *dep_spec_id,
+                    node.failure_policies
+                        .get(dep_spec_id)
+                        .copied()
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        let mut decisions = Vec::with_capacity(failed.len());
+        for (dep_spec_id, policy) in failed {
+            let (action, attempt) = match policy {
+                DependencyFailurePolicy::FailDependent => {
+                    (DependencyFailureAction::FailedDependent, 0)
+                }
+                DependencyFailurePolicy::HoldPending => (DependencyFailureAction::HeldPending, 0),
+                DependencyFailurePolicy::RetryDependency { max_retries } => {
+                    match self
+                        .nodes
+                        .iter_mut()
+                        .find(|n| n.service_spec_id == dep_spec_id)
+                    {
+                        Some(dep_node) if dep_node.retry_count < max_retries => {
+                            dep_node.retry_count += 1;
+                            dep_node.state = DependencyNodeState::NotProvisioned;
+                            (
+                                DependencyFailureAction::RetriedDependency,
+                                dep_node.retry_count,
+                            )
+                        }
+                        Some(dep_node) => (
+                            DependencyFailureAction::FailedDependent,
+                            dep_node.retry_count,
+                        ),
+                        None => (DependencyFailureAction::FailedDependent, 0),
+                    }
+                }
+            };
+
+            decisions.push(DependencyFailureDecision {
+                service_spec_id,
+                failed_dependency_spec_id: dep_spec_id,
+                policy,
+                action,
+                attempt,
+                decided_at: Utc::now(),
+            });
+        }
+
+        decisions
+    }
+
     /// Get dependents of a service specification
     pub fn get_dependents(&self, service_spec_id: Uuid) -> Vec<Uuid> {
         self.nodes
@@ -5,13 +5,26 @@
 //! - Service dependency management
 //! - Automatic service activation when dependencies are met
 //! - Service lifecycle state tracking
+//! - Manual approval gates that pause workflows until signed off
+//! - Retry with backoff for transient activation failures, such as a busy network element
+//! - Milestone tracking with jeopardy alerts for orders falling behind
 
 pub mod activation;
+pub mod approval;
 pub mod dependencies;
+pub mod milestones;
 pub mod orchestrator;
+pub mod retry;
 pub mod state;
 pub mod workflow;
 
-pub use dependencies::{ServiceDependency, ServiceDependencyGraph};
+pub use activation::{ElementFailure, ImmediateActivator, ServiceActivator};
+pub use approval::{ApprovalDecision, ApprovalGate, ApprovalGateConfig, ApprovalOutcome};
+pub use dependencies::{
+    DependencyFailureAction, DependencyFailureDecision, DependencyFailurePolicy, ServiceDependency,
+    ServiceDependencyGraph,
+};
+pub use milestones::{derive_milestones, track_milestones, MilestonePlan};
 pub use orchestrator::ServiceOrchestrator;
+pub use retry::{RetryAttempt, RetryPolicy};
 pub use state::{ServiceLifecycleState, ServiceWorkflowContext};
@@ -0,0 +1,190 @@
+//! Service Order Milestones and Jeopardy Tracking
+//!
+//! Milestones are derived from the workflow steps of a service order: each
+//! step becomes a milestone whose expected date follows from the expected dates
+//! of the steps it depends on plus the step's planned duration. While a
+//! workflow runs, milestones not yet reached are put in jeopardy when their
+//! expected date comes within the at-risk window, and again when it passes.
+//! Every such escalation produces a jeopardy alert.
+
+use crate::state::{ServiceLifecycleState, ServiceTaskType, ServiceWorkflowContext};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use tmf641_service_order::models::{
+    ServiceOrderJeopardyAlert, ServiceOrderMilestone, ServiceOrderMilestoneStatus,
+};
+use uuid::Uuid;
+
+/// Planned durations of workflow steps used to derive milestone dates
+#[derive(Debug, Clone)]
+pub struct MilestonePlan {
+    step_durations: HashMap<ServiceTaskType, Duration>,
+    /// Duration of steps without a planned duration
+    pub default_step_duration: Duration,
+    /// Time before its expected date from which a milestone is at risk
+    pub at_risk_window: Duration,
+}
+
+impl MilestonePlan {
+    /// Plan the duration of a workflow step
+    pub fn with_step_duration(mut self, task_type: ServiceTaskType, duration: Duration) -> Self {
+        self.step_durations.insert(task_type, duration);
+        self
+    }
+
+    /// Use a different at-risk window
+    pub fn with_at_risk_window(mut self, window: Duration) -> Self {
+        self.at_risk_window = window;
+        self
+    }
+
+    /// Planned duration of a workflow step
+    pub fn step_duration(&self, task_type: &ServiceTaskType) -> Duration {
+        self.step_durations
+            .get(task_type)
+            .copied()
+            .unwrap_or(self.default_step_duration)
+    }
+}
+
+impl Default for MilestonePlan {
+    fn default() -> Self {
+        Self {
+            step_durations: HashMap::from([
+                (ServiceTaskType::ValidateOrder, Duration::hours(1)),
+                (ServiceTaskType::CheckDependencies, Duration::hours(24)),
+                (ServiceTaskType::ManualApproval, Duration::hours(48)),
+                (ServiceTaskType::CreateActivation, Duration::hours(24)),
+                (ServiceTaskType::ExecuteActivation, Duration::hours(24)),
+                (ServiceTaskType::CreateInventory, Duration::hours(4)),
+                (ServiceTaskType::UpdateInventory, Duration::hours(4)),
+            ]),
+            default_step_duration: Duration::hours(24),
+            at_risk_window: Duration::minutes(30),
+        }
+    }
+}
+
+/// Name of the milestone reached when a workflow step completes
+fn milestone_name(task_type: &ServiceTaskType) -> &'static str {
+    match task_type {
+        ServiceTaskType::ValidateOrder => "Order validated",
+        ServiceTaskType::CheckDependencies => "Dependencies met",
+        ServiceTaskType::CreateActivation => "Activation created",
+        ServiceTaskType::ExecuteActivation => "Service activated",
+        ServiceTaskType::CreateInventory => "Inventory created",
+        ServiceTaskType::UpdateInventory => "Inventory updated",
+        ServiceTaskType::ManualApproval => "Approval granted",
+    }
+}
+
+/// Derive the milestones of a workflow from its steps
+///
+/// Expected dates start from the creation of the workflow. A milestone takes
+/// the id of its step and is completed if the step is.
+pub fn derive_milestones(
+    context: &ServiceWorkflowContext,
+    plan: &MilestonePlan,
+) -> Vec<ServiceOrderMilestone> {
+    let mut expected_dates: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+    let mut milestones = Vec::with_capacity(context.tasks.len());
+
+    for task in &context.tasks {
+        let start = task
+            .dependencies
+            .iter()
+            .filter_map(|dep_id| expected_dates.get(dep_id))
+            .max()
+            .copied()
+            .unwrap_or(context.created_at);
+        let expected_date = start + plan.step_duration(&task.task_type);
+        expected_dates.insert(task.id, expected_date);
+
+        let name = match &task.approval {
+            Some(gate) => gate.name.clone(),
+            None => milestone_name(&task.task_type).to_string(),
+        };
+        let completed_date = if task.state == ServiceLifecycleState::Completed {
+            task.completed_at
+        } else {
+            None
+        };
+        milestones.push(ServiceOrderMilestone {
+            id: task.id,
+            name,
+            expected_date,
+            completed_date,
+            status: if completed_date.is_some() {
+                ServiceOrderMilestoneStatus::Completed
+            } else {
+                ServiceOrderMilestoneStatus::Pending
+            },
+            jeopardy: false,
+        });
+    }
+
+    milestones
+}
+
+/// Bring the milestones of a workflow up to date at `now`
+///
+/// Milestones are derived first if the workflow has none yet. Milestones
+/// whose step completed are marked completed and leave jeopardy. Milestones of
+/// a running workflow escalate to at risk or missed, returning one alert per
+/// escalation; those of a failed or cancelled workflow are left as they are.
+pub fn track_milestones(
+    context: &mut ServiceWorkflowContext,
+    plan: &MilestonePlan,
+    now: DateTime<Utc>,
+) -> Vec<ServiceOrderJeopardyAlert> {
+    if context.milestones.is_empty() {
+        context.milestones = derive_milestones(context, plan);
+    }
+
+    let running = !matches!(
+        context.state,
+        ServiceLifecycleState::Failed | ServiceLifecycleState::Cancelled
+    );
+    let mut alerts = Vec::new();
+
+    for milestone in &mut context.milestones {
+        if milestone.status == ServiceOrderMilestoneStatus::Completed {
+            continue;
+        }
+
+        let step = context.tasks.iter().find(|t| t.id == milestone.id);
+        if let Some(task) = step.filter(|t| t.state == ServiceLifecycleState::Completed) {
+            milestone.completed_date = task.completed_at.or(Some(now));
+            milestone.status = ServiceOrderMilestoneStatus::Completed;
+            milestone.jeopardy = false;
+            continue;
+        }
+        if !running {
+            continue;
+        }
+
+        let status = if now > milestone.expected_date {
+            ServiceOrderMilestoneStatus::Missed
+        } else if now > milestone.expected_date - plan.at_risk_window {
+            ServiceOrderMilestoneStatus::AtRisk
+        } else {
+            ServiceOrderMilestoneStatus::Pending
+        };
+        if status == milestone.status || status == ServiceOrderMilestoneStatus::Pending {
+            continue;
+        }
+
+        milestone.status = status;
+        milestone.jeopardy = true;
+        alerts.push(ServiceOrderJeopardyAlert {
+            service_order_id: context.service_order_id,
+            milestone_id: milestone.id,
+            milestone_name: milestone.name.clone(),
+            status,
+            expected_date: milestone.expected_date,
+            alert_date: now,
+        });
+    }
+
+    alerts
+}
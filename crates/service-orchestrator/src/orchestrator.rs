@@ -1,11 +1,18 @@
 //! Main Service Orchestrator
 
-use crate::activation::{ActivationError, ServiceActivationEngine};
-use crate::dependencies::ServiceDependencyGraph;
-use crate::state::{ServiceLifecycleState, ServiceWorkflowContext};
+use crate::activation::{ActivationError, ServiceActivationEngine, ServiceActivator};
+use crate::approval::ApprovalGateConfig;
+use crate::dependencies::{DependencyFailureAction, ServiceDependencyGraph};
+use crate::milestones::{derive_milestones, track_milestones, MilestonePlan};
+use crate::retry::RetryPolicy;
+use crate::state::{ServiceLifecycleState, ServiceTaskType, ServiceWorkflowContext};
 use crate::workflow::{ServiceWorkflowEngine, WorkflowError};
 use async_trait::async_trait;
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use chrono::Utc;
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tmf641_service_order::models::ServiceOrder;
 use uuid::Uuid;
@@ -27,6 +34,24 @@ pub trait ServiceOrchestratorTrait: Send + Sync {
 
     /// Check and update service dependencies
     async fn check_dependencies(&self, service_order_id: Uuid) -> Result<(), OrchestratorError>;
+
+    /// Approve a manual approval gate and resume the workflow
+    async fn approve(
+        &self,
+        service_order_id: Uuid,
+        task_id: Uuid,
+        approver: &str,
+        comment: Option<String>,
+    ) -> Result<(), OrchestratorError>;
+
+    /// Reject a manual approval gate, failing the workflow
+    async fn reject(
+        &self,
+        service_order_id: Uuid,
+        task_id: Uuid,
+        approver: &str,
+        reason: Option<String>,
+    ) -> Result<(), OrchestratorError>;
 }
 
 /// Service orchestrator implementation
@@ -34,6 +59,10 @@ pub struct ServiceOrchestrator {
     pool: Arc<PgPool>,
     activation_engine: Arc<ServiceActivationEngine>,
     dependency_graph: Arc<tokio::sync::RwLock<ServiceDependencyGraph>>,
+    approval_gate: Option<ApprovalGateConfig>,
+    retry_policies: HashMap<ServiceTaskType, RetryPolicy>,
+    milestone_plan: MilestonePlan,
+    publisher: Option<Arc<dyn EventPublisher>>,
 }
 
 impl ServiceOrchestrator {
@@ -43,9 +72,27 @@ impl ServiceOrchestrator {
             pool: Arc::new(pool),
             activation_engine,
             dependency_graph: Arc::new(tokio::sync::RwLock::new(ServiceDependencyGraph::new())),
+            approval_gate: None,
+            retry_policies: HashMap::new(),
+            milestone_plan: MilestonePlan::default(),
+            publisher: None,
         }
     }
 
+    /// Configure network elements through the given activator
+    pub fn with_activator(mut self, activator: Arc<dyn ServiceActivator>) -> Self {
+        self.activation_engine = Arc::new(
+            ServiceActivationEngine::new(self.pool.as_ref().clone()).with_activator(activator),
+        );
+        self
+    }
+
+    /// Require manual approval in every new workflow
+    pub fn with_approval_gate(mut self, config: ApprovalGateConfig) -> Self {
+        self.approval_gate = Some(config);
+        self
+    }
+
     /// Initialize orchestrator and load dependency graph from database
     pub async fn initialize(pool: PgPool) -> Result<Self, OrchestratorError> {
         let dependency_graph = ServiceDependencyGraph::load_from_db(&pool)
@@ -57,9 +104,113 @@ impl ServiceOrchestrator {
             pool: Arc::new(pool),
             activation_engine,
             dependency_graph: Arc::new(tokio::sync::RwLock::new(dependency_graph)),
+            approval_gate: None,
+            retry_policies: HashMap::new(),
+            milestone_plan: MilestonePlan::default(),
+            publisher: None,
         })
     }
 
+    /// Configure retries for a workflow step; steps without one use the default policy
+    pub fn with_retry_policy(mut self, task_type: ServiceTaskType, policy: RetryPolicy) -> Self {
+        self.retry_policies.insert(task_type, policy);
+        self
+    }
+
+    /// Derive milestone dates of new workflows from a different plan
+    pub fn with_milestone_plan(mut self, plan: MilestonePlan) -> Self {
+        self.milestone_plan = plan;
+        self
+    }
+
+    /// Publish service order jeopardy alerts
+    pub fn with_event_publisher(mut self, publisher: Arc<dyn EventPublisher>) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
+    /// Bring the milestones of a workflow up to date, publishing an event for
+    /// every milestone that falls further behind
+    async fn track_milestones(&self, context: &mut ServiceWorkflowContext) {
+        for alert in track_milestones(context, &self.milestone_plan, Utc::now()) {
+            log::warn!(
+                "Service order {} milestone '{}' is {:?}, expected by {}",
+                alert.service_order_id,
+                alert.milestone_name,
+                alert.status,
+                alert.expected_date
+            );
+
+            let Some(publisher) = &self.publisher else {
+                continue;
+            };
+            let data = match serde_json::to_value(&alert) {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to serialize jeopardy alert for service order {}: {}",
+                        alert.service_order_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let event = EventEnvelope::new(
+                "ServiceOrderJeopardyAlert".to_string(),
+                "service-orchestrator".to_string(),
+                data,
+            );
+            if let Err(e) = publisher.publish(topics::ORDER_EVENTS, event).await {
+                log::warn!(
+                    "Failed to publish jeopardy alert for service order {}: {}",
+                    alert.service_order_id,
+                    e
+                );
+            }
+        }
+    }
+
+    fn retry_policy(&self, task_type: &ServiceTaskType) -> RetryPolicy {
+        self.retry_policies
+            .get(task_type)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Decide whether a failed attempt of a step is retried
+    ///
+    /// When it is, the attempt is recorded on the task, the context is stored so
+    /// the retry is visible while waiting, and the backoff delay is waited out.
+    async fn retry_after_failure(
+        &self,
+        context: &mut ServiceWorkflowContext,
+        task_id: Uuid,
+        task_type: &ServiceTaskType,
+        attempt: u32,
+        error: &ActivationError,
+    ) -> Result<bool, OrchestratorError> {
+        let policy = self.retry_policy(task_type);
+        if !policy.should_retry(attempt, error.is_retryable()) {
+            return Ok(false);
+        }
+
+        let delay = policy.backoff(attempt);
+        log::warn!(
+            "{:?} attempt {}/{} for service order {} failed: {}; retrying in {:?}",
+            task_type,
+            attempt,
+            policy.max_attempts,
+            context.service_order_id,
+            error,
+            delay
+        );
+        context.record_retry_attempt(task_id, attempt, error.to_string(), delay);
+        self.store_context(context).await?;
+        tokio::time::sleep(delay).await;
+
+        Ok(true)
+    }
+
     /// Load service order items from database
     async fn load_service_order_items(
         &self,
@@ -127,6 +278,45 @@ impl ServiceOrchestrator {
             None => Err(OrchestratorError::ContextNotFound),
         }
     }
+
+    /// Apply dependency failure policies for every service in the order
+    ///
+    /// Decisions are recorded on the workflow context. Returns an error message
+    /// if any policy requires the dependent to fail.
+    async fn apply_dependency_failure_policies(
+        &self,
+        context: &mut ServiceWorkflowContext,
+        service_order_id: Uuid,
+    ) -> Result<Option<String>, OrchestratorError> {
+        let service_specs = self.load_service_order_items(service_order_id).await?;
+
+        let mut dependency_graph = self.dependency_graph.write().await;
+        let decisions: Vec<_> = service_specs
+            .iter()
+            .filter(|(spec_id, _)| *spec_id != Uuid::nil())
+            .flat_map(|(spec_id, _)| dependency_graph.resolve_dependency_failures(*spec_id))
+            .collect();
+        drop(dependency_graph);
+
+        let mut failure = None;
+        for decision in &decisions {
+            log::info!(
+                "Dependency {} of service spec {} failed, action: {:?}",
+                decision.failed_dependency_spec_id,
+                decision.service_spec_id,
+                decision.action
+            );
+            if decision.action == DependencyFailureAction::FailedDependent && failure.is_none() {
+                failure = Some(format!(
+                    "Dependency {} of service specification {} failed",
+                    decision.failed_dependency_spec_id, decision.service_spec_id
+                ));
+            }
+        }
+
+        context.dependency_decisions.extend(decisions);
+        Ok(failure)
+    }
 }
 
 #[async_trait]
@@ -135,7 +325,11 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
         let service_order_id = service_order.base.id;
 
         // Create workflow context
-        let context = ServiceWorkflowEngine::create_workflow(service_order_id);
+        let mut context = ServiceWorkflowEngine::create_workflow(service_order_id);
+        if let Some(gate) = &self.approval_gate {
+            ServiceWorkflowEngine::add_approval_gate(&mut context, gate)?;
+        }
+        context.milestones = derive_milestones(&context, &self.milestone_plan);
 
         // Extract service specification IDs from service order items
         // First try from the service order object, then fall back to database
@@ -166,8 +360,18 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                     .await
                     .map_err(OrchestratorError::Database)?;
 
+            let failure_policies = ServiceDependencyGraph::load_failure_policies_for_spec(
+                self.pool.as_ref(),
+                *spec_id,
+            )
+            .await
+            .map_err(OrchestratorError::Database)?;
+
             let mut dependency_graph = self.dependency_graph.write().await;
             dependency_graph.add_service_spec(*spec_id, dependencies);
+            for (dep_spec_id, policy) in failure_policies {
+                dependency_graph.set_failure_policy(*spec_id, dep_spec_id, policy);
+            }
             drop(dependency_graph);
         }
 
@@ -218,6 +422,16 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                     context.update_task_state(task_id, ServiceLifecycleState::Completed);
                 }
                 crate::state::ServiceTaskType::CheckDependencies => {
+                    // Apply failure policies for dependencies that have failed
+                    if let Some(err) = self
+                        .apply_dependency_failure_policies(&mut context, service_order_id)
+                        .await?
+                    {
+                        context.update_task_state(task_id, ServiceLifecycleState::Failed);
+                        ServiceWorkflowEngine::fail_workflow(&mut context, err);
+                        continue;
+                    }
+
                     // Check dependencies
                     match self.check_dependencies(service_order_id).await {
                         Ok(_) => {
@@ -248,12 +462,39 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                             continue;
                         }
 
-                        // Auto-activate service
-                        match self
-                            .activation_engine
-                            .auto_activate_service(&mut context, service_order_id, service_spec_id)
-                            .await
-                        {
+                        // Auto-activate service, retrying transient failures
+                        let mut attempt = 1;
+                        let result = loop {
+                            match self
+                                .activation_engine
+                                .auto_activate_service(
+                                    &mut context,
+                                    service_order_id,
+                                    service_spec_id,
+                                )
+                                .await
+                            {
+                                Err(e) => {
+                                    if self
+                                        .retry_after_failure(
+                                            &mut context,
+                                            task_id,
+                                            &ServiceTaskType::CreateActivation,
+                                            attempt,
+                                            &e,
+                                        )
+                                        .await?
+                                    {
+                                        attempt += 1;
+                                        continue;
+                                    }
+                                    break Err(e);
+                                }
+                                ok => break ok,
+                            }
+                        };
+
+                        match result {
                             Ok(_) => {
                                 // Activation successful for this spec
                             }
@@ -269,6 +510,12 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                                 }
                                 activation_succeeded = false;
                                 activation_error = Some(e.to_string());
+
+                                // Let dependents of this spec apply their failure policies
+                                self.dependency_graph
+                                    .write()
+                                    .await
+                                    .mark_failed(service_spec_id);
                                 break;
                             }
                         }
@@ -312,16 +559,39 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                             continue;
                         }
 
-                        match self
-                            .activation_engine
-                            .create_service_inventory(
-                                &mut context,
-                                service_order_id,
-                                service_spec_id,
-                                activation_id,
-                            )
-                            .await
-                        {
+                        let mut attempt = 1;
+                        let result = loop {
+                            match self
+                                .activation_engine
+                                .create_service_inventory(
+                                    &mut context,
+                                    service_order_id,
+                                    service_spec_id,
+                                    activation_id,
+                                )
+                                .await
+                            {
+                                Err(e) => {
+                                    if self
+                                        .retry_after_failure(
+                                            &mut context,
+                                            task_id,
+                                            &ServiceTaskType::CreateInventory,
+                                            attempt,
+                                            &e,
+                                        )
+                                        .await?
+                                    {
+                                        attempt += 1;
+                                        continue;
+                                    }
+                                    break Err(e);
+                                }
+                                ok => break ok,
+                            }
+                        };
+
+                        match result {
                             Ok(_) => {
                                 // Inventory created successfully
                             }
@@ -345,9 +615,14 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
                     // Update inventory task
                     context.update_task_state(task_id, ServiceLifecycleState::Completed);
                 }
+                crate::state::ServiceTaskType::ManualApproval => {
+                    // Gates are resolved by approve/reject or time out in advance_workflow
+                }
             }
         }
 
+        self.track_milestones(&mut context).await;
+
         // Store updated context
         self.store_context(&context).await?;
 
@@ -376,6 +651,49 @@ impl ServiceOrchestratorTrait for ServiceOrchestrator {
 
         Ok(())
     }
+
+    async fn approve(
+        &self,
+        service_order_id: Uuid,
+        task_id: Uuid,
+        approver: &str,
+        comment: Option<String>,
+    ) -> Result<(), OrchestratorError> {
+        let mut context = self.load_context(service_order_id).await?;
+        let result = ServiceWorkflowEngine::approve_gate(&mut context, task_id, approver, comment);
+        // A gate found expired is rejected on the context, so store it either way
+        self.store_context(&context).await?;
+        result?;
+
+        log::info!(
+            "Approval gate {} of service order {} approved by {}",
+            task_id,
+            service_order_id,
+            approver
+        );
+        self.process_workflow(service_order_id).await
+    }
+
+    async fn reject(
+        &self,
+        service_order_id: Uuid,
+        task_id: Uuid,
+        approver: &str,
+        reason: Option<String>,
+    ) -> Result<(), OrchestratorError> {
+        let mut context = self.load_context(service_order_id).await?;
+        let result = ServiceWorkflowEngine::reject_gate(&mut context, task_id, approver, reason);
+        self.store_context(&context).await?;
+        result?;
+
+        log::info!(
+            "Approval gate {} of service order {} rejected by {}",
+            task_id,
+            service_order_id,
+            approver
+        );
+        Ok(())
+    }
 }
 
 impl ServiceOrchestrator {
@@ -409,6 +727,81 @@ impl ServiceOrchestrator {
         Ok(processed)
     }
 
+    /// Workflows still running with at least one milestone in jeopardy
+    pub async fn service_orders_in_jeopardy(
+        &self,
+    ) -> Result<Vec<ServiceWorkflowContext>, OrchestratorError> {
+        let rows = sqlx::query(
+            "SELECT context_data FROM service_workflow_contexts
+             WHERE state NOT IN ('Completed', 'Failed', 'Cancelled')
+             ORDER BY created_at ASC",
+        )
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(OrchestratorError::Database)?;
+
+        let mut contexts = Vec::new();
+        for row in rows {
+            let context_json: String = row.get(0);
+            let context: ServiceWorkflowContext = serde_json::from_str(&context_json)
+                .map_err(|e| OrchestratorError::Deserialization(e.to_string()))?;
+            if !context.milestones_in_jeopardy().is_empty() {
+                contexts.push(context);
+            }
+        }
+
+        Ok(contexts)
+    }
+
+    /// Reject approval gates left undecided past their timeout, returning how
+    /// many workflows were failed
+    pub async fn expire_approval_gates(&self) -> Result<usize, OrchestratorError> {
+        let rows = sqlx::query(
+            "SELECT service_order_id FROM service_workflow_contexts
+             WHERE state = 'AwaitingApproval'
+             ORDER BY updated_at ASC",
+        )
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(OrchestratorError::Database)?;
+
+        let mut expired = 0;
+        for row in rows {
+            let service_order_id: Uuid = row.get(0);
+            let mut context = self.load_context(service_order_id).await?;
+            ServiceWorkflowEngine::advance_approval_gates(&mut context, Utc::now());
+            if context.state == ServiceLifecycleState::AwaitingApproval {
+                continue;
+            }
+
+            self.store_context(&context).await?;
+            log::warn!(
+                "Approval gate of service order {} timed out",
+                service_order_id
+            );
+            expired += 1;
+        }
+
+        Ok(expired)
+    }
+
+    /// Periodically reject approval gates that timed out, so workflows nobody
+    /// acts on do not wait forever
+    pub fn spawn_approval_timeouts(
+        self: Arc<Self>,
+        check_interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.expire_approval_gates().await {
+                    log::error!("Error expiring approval gates: {}", e);
+                }
+            }
+        })
+    }
+
     /// Start background worker to process workflows periodically
     pub fn start_background_worker(
         self: Arc<Self>,
@@ -0,0 +1,71 @@
+//! Step Retry with Backoff
+//!
+//! Activation steps that fail with a transient error (network element busy,
+//! database connection dropped) are retried with exponential backoff before the
+//! step is marked failed. Permanent errors fail the step straight away. Each
+//! retried attempt is recorded on its task in the workflow context.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Per-step retry configuration
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Factor the delay grows by after each retry
+    pub multiplier: u32,
+    /// Upper bound on the delay between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            multiplier: 2,
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay to wait after the given failed attempt (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1)
+            .saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Whether another attempt may follow the given failed attempt
+    pub fn should_retry(&self, attempt: u32, retryable: bool) -> bool {
+        retryable && attempt < self.max_attempts
+    }
+}
+
+/// Record of a failed attempt that was retried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryAttempt {
+    /// Attempt number that failed (1-based)
+    pub attempt: u32,
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+    /// When the next attempt is made
+    pub retry_at: DateTime<Utc>,
+}
@@ -1,7 +1,11 @@
 //! Service Lifecycle State Management
 
+use crate::approval::ApprovalGate;
+use crate::dependencies::DependencyFailureDecision;
+use crate::retry::RetryAttempt;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tmf641_service_order::models::ServiceOrderMilestone;
 use uuid::Uuid;
 
 /// Service lifecycle state
@@ -18,6 +22,8 @@ pub enum ServiceLifecycleState {
     WaitingForDependencies,
     /// Dependencies met, ready for activation
     ReadyForActivation,
+    /// Paused at a manual approval gate
+    AwaitingApproval,
     /// Activating service
     Activating,
     /// Service activated
@@ -47,10 +53,16 @@ pub struct ServiceWorkflowTask {
     pub service_id: Option<Uuid>,
     pub activation_id: Option<Uuid>,
     pub inventory_id: Option<Uuid>,
+    /// Approval gate of a manual-approval task
+    #[serde(default)]
+    pub approval: Option<ApprovalGate>,
+    /// Failed attempts of this step that were retried
+    #[serde(default)]
+    pub retry_attempts: Vec<RetryAttempt>,
 }
 
 /// Service task type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ServiceTaskType {
     /// Service order validation
@@ -65,6 +77,8 @@ pub enum ServiceTaskType {
     CreateInventory,
     /// Update service inventory
     UpdateInventory,
+    /// Wait for a manual approve/reject decision
+    ManualApproval,
 }
 
 /// Service workflow context
@@ -77,6 +91,12 @@ pub struct ServiceWorkflowContext {
     pub updated_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// Decisions taken when dependencies of this order's services failed
+    #[serde(default)]
+    pub dependency_decisions: Vec<DependencyFailureDecision>,
+    /// Milestones derived from the workflow steps
+    #[serde(default)]
+    pub milestones: Vec<ServiceOrderMilestone>,
 }
 
 impl ServiceWorkflowContext {
@@ -90,6 +110,8 @@ impl ServiceWorkflowContext {
             updated_at: now,
             completed_at: None,
             error: None,
+            dependency_decisions: vec![],
+            milestones: vec![],
         }
     }
 
@@ -109,6 +131,32 @@ impl ServiceWorkflowContext {
         }
     }
 
+    /// Record a failed attempt of a task that will be retried after `delay`
+    pub fn record_retry_attempt(
+        &mut self,
+        task_id: Uuid,
+        attempt: u32,
+        error: String,
+        delay: std::time::Duration,
+    ) {
+        let now = Utc::now();
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.retry_attempts.push(RetryAttempt {
+                attempt,
+                error,
+                failed_at: now,
+                retry_at: now + chrono::Duration::from_std(delay).unwrap_or_default(),
+            });
+            task.updated_at = now;
+            self.updated_at = now;
+        }
+    }
+
+    /// Milestones currently in jeopardy
+    pub fn milestones_in_jeopardy(&self) -> Vec<&ServiceOrderMilestone> {
+        self.milestones.iter().filter(|m| m.jeopardy).collect()
+    }
+
     pub fn get_task(&self, task_id: Uuid) -> Option<&ServiceWorkflowTask> {
         self.tasks.iter().find(|t| t.id == task_id)
     }
@@ -117,6 +165,17 @@ impl ServiceWorkflowContext {
         self.tasks.iter_mut().find(|t| t.id == task_id)
     }
 
+    /// Whether all dependencies of a task are completed
+    pub fn dependencies_met(&self, task: &ServiceWorkflowTask) -> bool {
+        task.dependencies.iter().all(|dep_id| {
+            self.tasks
+                .iter()
+                .find(|t| t.id == *dep_id)
+                .map(|t| t.state == ServiceLifecycleState::Completed)
+                .unwrap_or(true)
+        })
+    }
+
     pub fn get_ready_tasks(&self) -> Vec<&ServiceWorkflowTask> {
         self.tasks
             .iter()
@@ -124,13 +183,7 @@ impl ServiceWorkflowContext {
                 // Task is ready if:
                 // 1. It's in a state that allows progression
                 // 2. All its dependencies are completed
-                let dependencies_met = task.dependencies.iter().all(|dep_id| {
-                    self.tasks
-                        .iter()
-                        .find(|t| t.id == *dep_id)
-                        .map(|t| t.state == ServiceLifecycleState::Completed)
-                        .unwrap_or(true)
-                });
+                let dependencies_met = self.dependencies_met(task);
 
                 matches!(
                     task.state,
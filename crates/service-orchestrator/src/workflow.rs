@@ -1,9 +1,10 @@
 //! Service Orchestration Workflows
 
+use crate::approval::{ApprovalGate, ApprovalGateConfig, ApprovalOutcome, TIMEOUT_DECIDER};
 use crate::state::{
     ServiceLifecycleState, ServiceTaskType, ServiceWorkflowContext, ServiceWorkflowTask,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 /// Service workflow engine
@@ -28,6 +29,8 @@ impl ServiceWorkflowEngine {
             service_id: None,
             activation_id: None,
             inventory_id: None,
+            approval: None,
+            retry_attempts: vec![],
         };
 
         let check_deps_task = ServiceWorkflowTask {
@@ -43,6 +46,8 @@ impl ServiceWorkflowEngine {
             service_id: None,
             activation_id: None,
             inventory_id: None,
+            approval: None,
+            retry_attempts: vec![],
         };
 
         let create_activation_task = ServiceWorkflowTask {
@@ -58,6 +63,8 @@ impl ServiceWorkflowEngine {
             service_id: None,
             activation_id: None,
             inventory_id: None,
+            approval: None,
+            retry_attempts: vec![],
         };
 
         let execute_activation_task = ServiceWorkflowTask {
@@ -73,6 +80,8 @@ impl ServiceWorkflowEngine {
             service_id: None,
             activation_id: None,
             inventory_id: None,
+            approval: None,
+            retry_attempts: vec![],
         };
 
         let create_inventory_task = ServiceWorkflowTask {
@@ -88,6 +97,8 @@ impl ServiceWorkflowEngine {
             service_id: None,
             activation_id: None,
             inventory_id: None,
+            approval: None,
+            retry_attempts: vec![],
         };
 
         context.add_task(validate_task);
@@ -99,6 +110,46 @@ impl ServiceWorkflowEngine {
         context
     }
 
+    /// Insert a manual approval gate in front of the configured task
+    ///
+    /// The gate takes over the task's dependencies and the task then depends on
+    /// the gate alone.
+    pub fn add_approval_gate(
+        context: &mut ServiceWorkflowContext,
+        config: &ApprovalGateConfig,
+    ) -> Result<Uuid, WorkflowError> {
+        let position = context
+            .tasks
+            .iter()
+            .position(|t| t.task_type == config.before)
+            .ok_or_else(|| WorkflowError::MissingTask(config.before.clone()))?;
+
+        let target = &mut context.tasks[position];
+        let gate_task = ServiceWorkflowTask {
+            id: Uuid::new_v4(),
+            service_order_id: target.service_order_id,
+            task_type: ServiceTaskType::ManualApproval,
+            state: ServiceLifecycleState::WaitingForDependencies,
+            dependencies: std::mem::take(&mut target.dependencies),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            completed_at: None,
+            error: None,
+            service_id: None,
+            activation_id: None,
+            inventory_id: None,
+            approval: Some(ApprovalGate::new(config)),
+            retry_attempts: vec![],
+        };
+        let gate_id = gate_task.id;
+        target.dependencies = vec![gate_id];
+
+        context.tasks.insert(position, gate_task);
+        context.updated_at = Utc::now();
+
+        Ok(gate_id)
+    }
+
     /// Advance workflow to next state
     pub fn advance_workflow(context: &mut ServiceWorkflowContext) -> Result<(), WorkflowError> {
         let ready_task_ids: Vec<Uuid> = context.get_ready_tasks().iter().map(|t| t.id).collect();
@@ -151,12 +202,168 @@ impl ServiceWorkflowEngine {
                         context.update_task_state(task_id, ServiceLifecycleState::Completed);
                     }
                 }
+                ServiceTaskType::ManualApproval => {
+                    // Gates are opened and expired below
+                }
             }
         }
 
+        Self::advance_approval_gates(context, Utc::now());
+
         Ok(())
     }
 
+    /// Open gates whose dependencies are met and reject gates that timed out
+    pub fn advance_approval_gates(context: &mut ServiceWorkflowContext, now: DateTime<Utc>) {
+        let gate_ids: Vec<Uuid> = context
+            .tasks
+            .iter()
+            .filter(|t| t.task_type == ServiceTaskType::ManualApproval)
+            .map(|t| t.id)
+            .collect();
+
+        for task_id in gate_ids {
+            let Some(task) = context.get_task(task_id) else {
+                continue;
+            };
+            let opens = task.state == ServiceLifecycleState::WaitingForDependencies
+                && context.dependencies_met(task);
+            let expired = task.state == ServiceLifecycleState::AwaitingApproval
+                && task.approval.as_ref().is_some_and(|g| g.is_expired(now));
+
+            if opens {
+                let resume_state = context.state;
+                if let Some(gate) = context
+                    .get_task_mut(task_id)
+                    .and_then(|t| t.approval.as_mut())
+                {
+                    gate.open(resume_state, now);
+                }
+                context.update_task_state(task_id, ServiceLifecycleState::AwaitingApproval);
+                context.state = ServiceLifecycleState::AwaitingApproval;
+            } else if expired {
+                Self::close_gate(
+                    context,
+                    task_id,
+                    ApprovalOutcome::TimedOut,
+                    TIMEOUT_DECIDER,
+                    None,
+                    now,
+                );
+            }
+        }
+    }
+
+    /// Approve a pending gate and resume the workflow
+    pub fn approve_gate(
+        context: &mut ServiceWorkflowContext,
+        task_id: Uuid,
+        approver: &str,
+        comment: Option<String>,
+    ) -> Result<(), WorkflowError> {
+        Self::decide_gate(
+            context,
+            task_id,
+            ApprovalOutcome::Approved,
+            approver,
+            comment,
+        )
+    }
+
+    /// Reject a pending gate, failing the workflow
+    pub fn reject_gate(
+        context: &mut ServiceWorkflowContext,
+        task_id: Uuid,
+        approver: &str,
+        reason: Option<String>,
+    ) -> Result<(), WorkflowError> {
+        Self::decide_gate(
+            context,
+            task_id,
+            ApprovalOutcome::Rejected,
+            approver,
+            reason,
+        )
+    }
+
+    fn decide_gate(
+        context: &mut ServiceWorkflowContext,
+        task_id: Uuid,
+        outcome: ApprovalOutcome,
+        approver: &str,
+        comment: Option<String>,
+    ) -> Result<(), WorkflowError> {
+        let now = Utc::now();
+        let task = context
+            .get_task(task_id)
+            .ok_or(WorkflowError::TaskNotFound(task_id))?;
+        let gate = match &task.approval {
+            Some(gate) if task.state == ServiceLifecycleState::AwaitingApproval => gate,
+            _ => return Err(WorkflowError::NotAwaitingApproval(task_id)),
+        };
+
+        if gate.is_expired(now) {
+            Self::close_gate(
+                context,
+                task_id,
+                ApprovalOutcome::TimedOut,
+                TIMEOUT_DECIDER,
+                None,
+                now,
+            );
+            return Err(WorkflowError::ApprovalTimedOut(task_id));
+        }
+
+        Self::close_gate(context, task_id, outcome, approver, comment, now);
+        Ok(())
+    }
+
+    fn close_gate(
+        context: &mut ServiceWorkflowContext,
+        task_id: Uuid,
+        outcome: ApprovalOutcome,
+        decided_by: &str,
+        comment: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        let Some(task) = context.get_task_mut(task_id) else {
+            return;
+        };
+        let Some(gate) = task.approval.as_mut() else {
+            return;
+        };
+        gate.decide(outcome, decided_by, comment.clone(), now);
+        let gate_name = gate.name.clone();
+        let resume_state = gate.resume_state;
+
+        match outcome {
+            ApprovalOutcome::Approved => {
+                context.update_task_state(task_id, ServiceLifecycleState::Completed);
+                context.state = resume_state.unwrap_or(ServiceLifecycleState::ReadyForActivation);
+                context.updated_at = now;
+            }
+            ApprovalOutcome::Rejected | ApprovalOutcome::TimedOut => {
+                let error = match (outcome, comment) {
+                    (ApprovalOutcome::TimedOut, _) => {
+                        format!("Approval gate '{}' timed out", gate_name)
+                    }
+                    (_, Some(reason)) => format!(
+                        "Approval gate '{}' rejected by {}: {}",
+                        gate_name, decided_by, reason
+                    ),
+                    (_, None) => {
+                        format!("Approval gate '{}' rejected by {}", gate_name, decided_by)
+                    }
+                };
+                if let Some(task) = context.get_task_mut(task_id) {
+                    task.error = Some(error.clone());
+                }
+                context.update_task_state(task_id, ServiceLifecycleState::Failed);
+                Self::fail_workflow(context, error);
+            }
+        }
+    }
+
     /// Retry failed tasks in workflow
     pub fn retry_failed_tasks(context: &mut ServiceWorkflowContext) {
         for task in &mut context.tasks {
@@ -181,6 +388,13 @@ impl ServiceWorkflowEngine {
                     ServiceTaskType::UpdateInventory => {
                         task.state = ServiceLifecycleState::InventoryCreated;
                     }
+                    ServiceTaskType::ManualApproval => {
+                        // Ask for approval again
+                        task.state = ServiceLifecycleState::WaitingForDependencies;
+                        if let Some(gate) = task.approval.as_mut() {
+                            gate.reset();
+                        }
+                    }
                 }
                 task.error = None;
                 task.completed_at = None;
@@ -214,4 +428,10 @@ pub enum WorkflowError {
     TaskNotFound(Uuid),
     #[error("Dependencies not met")]
     DependenciesNotMet,
+    #[error("Workflow has no {0:?} task")]
+    MissingTask(ServiceTaskType),
+    #[error("Task {0} is not awaiting approval")]
+    NotAwaitingApproval(Uuid),
+    #[error("Approval for task {0} timed out")]
+    ApprovalTimedOut(Uuid),
 }
@@ -0,0 +1,359 @@
+//! Deterministic interleaving exploration for concurrency tests
+//!
+//! Runs a set of async tasks on a single-threaded scheduler that decides which
+//! task is polled at every step, then checks an invariant once all tasks are
+//! done. Exploring many schedules surfaces lost updates and ordering bugs that
+//! only show up intermittently on a real runtime, and a failing schedule can be
+//! replayed exactly with [`replay_interleaving`].
+//!
+//! Tasks switch only where they await something that is not ready. Put
+//! [`yield_point`] between the steps whose ordering matters, for example
+//! between reading and writing a quota balance or between a capacity check and
+//! the reservation insert. Tasks may block on `tokio::sync` primitives, but not
+//! on timers or I/O, which this scheduler never drives.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+type TaskFuture = Pin<Box<dyn Future<Output = ()>>>;
+type Check = Box<dyn FnOnce() -> Result<(), String>>;
+
+/// Tasks to interleave and the invariant to check when they have finished
+pub struct Scenario {
+    tasks: Vec<TaskFuture>,
+    check: Option<Check>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            check: None,
+        }
+    }
+
+    /// Add a task; tasks are numbered from 0 in the order they are added
+    pub fn task(mut self, task: impl Future<Output = ()> + 'static) -> Self {
+        self.tasks.push(Box::pin(task));
+        self
+    }
+
+    /// Invariant checked after every task has completed
+    pub fn check(mut self, check: impl FnOnce() -> Result<(), String> + 'static) -> Self {
+        self.check = Some(Box::new(check));
+        self
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How schedules are chosen
+#[derive(Debug, Clone, Copy)]
+pub enum ExplorationStrategy {
+    /// Enumerate schedules depth-first until all are covered or the limit is hit
+    Exhaustive { max_schedules: usize },
+    /// Run schedules chosen by a seeded pseudo-random generator
+    Random { iterations: usize, seed: u64 },
+}
+
+/// Interleaving exploration configuration
+#[derive(Debug, Clone)]
+pub struct InterleavingConfig {
+    pub strategy: ExplorationStrategy,
+    /// Steps after which a schedule is reported as not terminating
+    pub max_steps: usize,
+}
+
+impl Default for InterleavingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ExplorationStrategy::Exhaustive {
+                max_schedules: 10_000,
+            },
+            max_steps: 10_000,
+        }
+    }
+}
+
+/// Outcome of an exploration in which every schedule passed
+#[derive(Debug, Clone)]
+pub struct InterleavingReport {
+    pub schedules_explored: usize,
+    /// Whether every possible schedule was covered
+    pub exhaustive: bool,
+}
+
+/// A schedule that broke the scenario
+#[derive(Debug, Clone)]
+pub struct InterleavingFailure {
+    /// Task polled at each step, replayable with [`replay_interleaving`]
+    pub schedule: Vec<usize>,
+    pub message: String,
+    pub schedules_explored: usize,
+}
+
+impl fmt::Display for InterleavingFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (schedule {:?}, after {} schedules)",
+            self.message, self.schedule, self.schedules_explored
+        )
+    }
+}
+
+impl std::error::Error for InterleavingFailure {}
+
+/// Yield to the scheduler, letting another task run before continuing
+pub fn yield_point() -> impl Future<Output = ()> {
+    YieldPoint { yielded: false }
+}
+
+struct YieldPoint {
+    yielded: bool,
+}
+
+impl Future for YieldPoint {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Marks its task runnable when woken
+struct TaskWaker {
+    runnable: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.runnable.store(true, Ordering::Release);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.runnable.store(true, Ordering::Release);
+    }
+}
+
+/// Choice made at one step: position among the runnable tasks and how many
+/// tasks were runnable
+#[derive(Debug, Clone, Copy)]
+struct Choice {
+    taken: usize,
+    options: usize,
+}
+
+struct Run {
+    schedule: Vec<usize>,
+    choices: Vec<Choice>,
+    failure: Option<String>,
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "task panicked".to_string())
+}
+
+/// Run a scenario once, asking `choose` which runnable task to poll at each step
+fn run_scenario(
+    scenario: Scenario,
+    max_steps: usize,
+    mut choose: impl FnMut(usize, &[usize]) -> usize,
+) -> Run {
+    let Scenario { mut tasks, check } = scenario;
+    let wakers: Vec<Arc<TaskWaker>> = tasks
+        .iter()
+        .map(|_| {
+            Arc::new(TaskWaker {
+                runnable: AtomicBool::new(true),
+            })
+        })
+        .collect();
+    let mut done = vec![false; tasks.len()];
+    let mut run = Run {
+        schedule: Vec::new(),
+        choices: Vec::new(),
+        failure: None,
+    };
+
+    loop {
+        let runnable: Vec<usize> = (0..tasks.len())
+            .filter(|&i| !done[i] && wakers[i].runnable.load(Ordering::Acquire))
+            .collect();
+
+        if runnable.is_empty() {
+            if done.iter().all(|d| *d) {
+                break;
+            }
+            let blocked: Vec<usize> = (0..tasks.len()).filter(|&i| !done[i]).collect();
+            run.failure = Some(format!("Deadlock: tasks {:?} can never proceed", blocked));
+            return run;
+        }
+        if run.schedule.len() >= max_steps {
+            run.failure = Some(format!("Schedule exceeded {} steps", max_steps));
+            return run;
+        }
+
+        let taken = choose(run.choices.len(), &runnable).min(runnable.len() - 1);
+        let index = runnable[taken];
+        run.choices.push(Choice {
+            taken,
+            options: runnable.len(),
+        });
+        run.schedule.push(index);
+
+        wakers[index].runnable.store(false, Ordering::Release);
+        let waker = Waker::from(Arc::clone(&wakers[index]));
+        let mut cx = Context::from_waker(&waker);
+        match catch_unwind(AssertUnwindSafe(|| tasks[index].as_mut().poll(&mut cx))) {
+            Ok(Poll::Ready(())) => done[index] = true,
+            Ok(Poll::Pending) => {}
+            Err(payload) => {
+                run.failure = Some(format!(
+                    "Task {} panicked: {}",
+                    index,
+                    panic_message(payload)
+                ));
+                return run;
+            }
+        }
+    }
+
+    if let Some(check) = check {
+        match catch_unwind(AssertUnwindSafe(check)) {
+            Ok(Ok(())) => {}
+            Ok(Err(message)) => run.failure = Some(message),
+            Err(payload) => run.failure = Some(panic_message(payload)),
+        }
+    }
+    run
+}
+
+/// Small deterministic generator so random exploration is reproducible
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+/// Explore interleavings of the scenario built by `setup`
+///
+/// `setup` is called once per schedule and must build fresh shared state each
+/// time. Returns the first failing schedule, if any.
+pub fn explore_interleavings(
+    config: &InterleavingConfig,
+    setup: impl Fn() -> Scenario,
+) -> Result<InterleavingReport, InterleavingFailure> {
+    let mut explored = 0;
+
+    let fail = |run: Run, explored: usize| InterleavingFailure {
+        schedule: run.schedule,
+        message: run.failure.unwrap_or_default(),
+        schedules_explored: explored,
+    };
+
+    match config.strategy {
+        ExplorationStrategy::Exhaustive { max_schedules } => {
+            // Choices forced at the start of the next schedule; later steps take
+            // the first runnable task
+            let mut prefix: Vec<usize> = Vec::new();
+            while explored < max_schedules {
+                let forced = prefix.clone();
+                let run = run_scenario(setup(), config.max_steps, |step, _| {
+                    forced.get(step).copied().unwrap_or(0)
+                });
+                explored += 1;
+                if run.failure.is_some() {
+                    return Err(fail(run, explored));
+                }
+
+                // Backtrack to the deepest step with an untried alternative
+                let mut choices = run.choices;
+                while let Some(last) = choices.last() {
+                    if last.taken + 1 < last.options {
+                        break;
+                    }
+                    choices.pop();
+                }
+                let Some(last) = choices.pop() else {
+                    return Ok(InterleavingReport {
+                        schedules_explored: explored,
+                        exhaustive: true,
+                    });
+                };
+                prefix = choices.iter().map(|c| c.taken).collect();
+                prefix.push(last.taken + 1);
+            }
+            Ok(InterleavingReport {
+                schedules_explored: explored,
+                exhaustive: false,
+            })
+        }
+        ExplorationStrategy::Random { iterations, seed } => {
+            let rng = RefCell::new(XorShift(seed.max(1)));
+            for _ in 0..iterations {
+                let run = run_scenario(setup(), config.max_steps, |_, runnable| {
+                    (rng.borrow_mut().next() % runnable.len() as u64) as usize
+                });
+                explored += 1;
+                if run.failure.is_some() {
+                    return Err(fail(run, explored));
+                }
+            }
+            Ok(InterleavingReport {
+                schedules_explored: explored,
+                exhaustive: false,
+            })
+        }
+    }
+}
+
+/// Run one scenario with the exact schedule from an [`InterleavingFailure`]
+///
+/// Once the schedule is used up, or names a task that is not runnable, the
+/// first runnable task is polled.
+pub fn replay_interleaving(
+    schedule: &[usize],
+    scenario: Scenario,
+) -> Result<(), InterleavingFailure> {
+    let run = run_scenario(scenario, usize::MAX, |step, runnable| {
+        schedule
+            .get(step)
+            .and_then(|task| runnable.iter().position(|r| r == task))
+            .unwrap_or(0)
+    });
+    match run.failure {
+        None => Ok(()),
+        Some(message) => Err(InterleavingFailure {
+            schedule: run.schedule,
+            message,
+            schedules_explored: 1,
+        }),
+    }
+}
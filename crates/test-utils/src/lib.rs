@@ -5,11 +5,15 @@ pub mod database;
 pub mod fixtures;
 pub mod helpers;
 pub mod integration_tests;
+pub mod interleaving;
 pub mod load_testing;
+pub mod mock_server;
 
 pub use coverage::*;
 pub use database::*;
 pub use fixtures::*;
 pub use helpers::*;
 pub use integration_tests::*;
+pub use interleaving::*;
 pub use load_testing::*;
+pub use mock_server::*;
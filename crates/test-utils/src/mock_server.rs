@@ -0,0 +1,351 @@
+//! In-process TMF mock server
+//!
+//! Serves canned responses for TMF API paths on a local port so clients can be
+//! integration-tested without the real services. Responses are built from the
+//! real TMF models, every received request is recorded for assertions, and any
+//! path can be programmed to fail or respond slowly.
+//!
+//! Paths may contain `{param}` segments that match any single segment. When
+//! several stubs match, the most recently added wins, and stubs limited to a
+//! number of uses take precedence until they are used up. Resources registered
+//! with [`MockTmfServer::resource`] answer list and get-by-id requests for
+//! anything not stubbed.
+
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tmf_apis_core::TmfError;
+
+/// Canned response of the mock server
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: StatusCode,
+    pub body: Option<Value>,
+    /// Delay before the response is sent
+    pub latency: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Respond with a model serialized as JSON
+    pub fn json<T: Serialize>(status: StatusCode, body: &T) -> Self {
+        Self {
+            status,
+            body: Some(serde_json::to_value(body).expect("Failed to serialize mock response")),
+            latency: None,
+        }
+    }
+
+    /// 200 OK with a model
+    pub fn ok<T: Serialize>(body: &T) -> Self {
+        Self::json(StatusCode::OK, body)
+    }
+
+    /// 201 Created with a model
+    pub fn created<T: Serialize>(body: &T) -> Self {
+        Self::json(StatusCode::CREATED, body)
+    }
+
+    /// 204 No Content
+    pub fn no_content() -> Self {
+        Self {
+            status: StatusCode::NO_CONTENT,
+            body: None,
+            latency: None,
+        }
+    }
+
+    /// Error response shaped like the TMF handlers' own error responses
+    pub fn error(error: TmfError) -> Self {
+        let status = match &error {
+            TmfError::Authentication(_) => StatusCode::UNAUTHORIZED,
+            TmfError::Validation(_) | TmfError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            TmfError::NotFound(_) => StatusCode::NOT_FOUND,
+            TmfError::Conflict(_) => StatusCode::CONFLICT,
+            TmfError::Database(_) | TmfError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::json(status, &json!({ "error": error.to_string() }))
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+}
+
+/// Request received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Deserialize the body into a request model
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.body)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+struct Stub {
+    method: Method,
+    path: Vec<String>,
+    response: MockResponse,
+    /// Uses left, or `None` for unlimited
+    remaining: Option<usize>,
+}
+
+#[derive(Default)]
+struct MockState {
+    stubs: Vec<Stub>,
+    resources: Vec<(Vec<String>, Vec<Value>)>,
+    requests: Vec<RecordedRequest>,
+    latency: Option<Duration>,
+}
+
+fn segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn path_matches(pattern: &[String], path: &[String]) -> bool {
+    pattern.len() == path.len()
+        && pattern
+            .iter()
+            .zip(path)
+            .all(|(p, s)| (p.starts_with('{') && p.ends_with('}')) || p == s)
+}
+
+impl MockState {
+    fn respond(&mut self, method: &Method, path: &str) -> MockResponse {
+        let path_segments = segments(path);
+
+        let matching =
+            |stub: &Stub| stub.method == method && path_matches(&stub.path, &path_segments);
+        let index = self
+            .stubs
+            .iter()
+            .rposition(|stub| stub.remaining.is_some_and(|n| n > 0) && matching(stub))
+            .or_else(|| {
+                self.stubs
+                    .iter()
+                    .rposition(|stub| stub.remaining.is_none() && matching(stub))
+            });
+        if let Some(index) = index {
+            let stub = &mut self.stubs[index];
+            if let Some(remaining) = &mut stub.remaining {
+                *remaining -= 1;
+            }
+            return stub.response.clone();
+        }
+
+        if method == Method::GET {
+            for (base, items) in self.resources.iter().rev() {
+                if path_segments == *base {
+                    return MockResponse::ok(items);
+                }
+                if path_segments.len() == base.len() + 1 && path_segments.starts_with(base) {
+                    let id = &path_segments[base.len()];
+                    return items
+                        .iter()
+                        .find(|item| item.get("id").and_then(Value::as_str) == Some(id))
+                        .map(MockResponse::ok)
+                        .unwrap_or_else(|| {
+                            MockResponse::error(TmfError::NotFound(format!(
+                                "Resource {} not found",
+                                id
+                            )))
+                        });
+                }
+            }
+        }
+
+        MockResponse::error(TmfError::NotFound(format!(
+            "No mock response for {} {}",
+            method, path
+        )))
+    }
+}
+
+/// Running in-process mock server
+pub struct MockTmfServer {
+    base_url: String,
+    state: Arc<Mutex<MockState>>,
+    handle: actix_web::dev::ServerHandle,
+}
+
+impl MockTmfServer {
+    /// Start a mock server on a free local port
+    pub async fn start() -> std::io::Result<Self> {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let app_state = web::Data::from(Arc::clone(&state));
+
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_state.clone())
+                .default_service(web::to(handle_request))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))?;
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        tokio::spawn(server);
+
+        Ok(Self {
+            base_url: format!("http://{}", addr),
+            state,
+            handle,
+        })
+    }
+
+    /// Base URL to point the client under test at
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Full URL of a path on the mock server
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock server lock poisoned")
+    }
+
+    /// Respond to every matching request
+    pub fn stub(&self, method: Method, path: &str, response: MockResponse) -> &Self {
+        self.add_stub(method, path, response, None)
+    }
+
+    /// Respond to the next `times` matching requests, ahead of other stubs
+    pub fn stub_times(
+        &self,
+        method: Method,
+        path: &str,
+        response: MockResponse,
+        times: usize,
+    ) -> &Self {
+        self.add_stub(method, path, response, Some(times))
+    }
+
+    fn add_stub(
+        &self,
+        method: Method,
+        path: &str,
+        response: MockResponse,
+        remaining: Option<usize>,
+    ) -> &Self {
+        self.state().stubs.push(Stub {
+            method,
+            path: segments(path),
+            response,
+            remaining,
+        });
+        self
+    }
+
+    /// Serve a collection at `base_path` and each item, by its `id` field, at
+    /// `base_path/{id}`
+    pub fn resource<T: Serialize>(&self, base_path: &str, items: &[T]) -> &Self {
+        let items = items
+            .iter()
+            .map(|item| serde_json::to_value(item).expect("Failed to serialize mock resource"))
+            .collect();
+        self.state().resources.push((segments(base_path), items));
+        self
+    }
+
+    /// Delay applied to every response without its own latency
+    pub fn set_latency(&self, latency: Option<Duration>) -> &Self {
+        self.state().latency = latency;
+        self
+    }
+
+    /// Requests received so far, in arrival order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Requests received for a method and path pattern
+    pub fn requests_to(&self, method: Method, path: &str) -> Vec<RecordedRequest> {
+        let pattern = segments(path);
+        self.state()
+            .requests
+            .iter()
+            .filter(|r| r.method == method && path_matches(&pattern, &segments(&r.path)))
+            .cloned()
+            .collect()
+    }
+
+    /// Forget all stubs, resources, latency and recorded requests
+    pub fn reset(&self) {
+        *self.state() = MockState::default();
+    }
+
+    /// Stop the server
+    pub async fn stop(self) {
+        self.handle.stop(true).await;
+    }
+}
+
+impl Drop for MockTmfServer {
+    fn drop(&mut self) {
+        // The stop command is sent immediately; the returned future only waits
+        // for shutdown to complete
+        drop(self.handle.stop(false));
+    }
+}
+
+async fn handle_request(
+    req: HttpRequest,
+    body: web::Bytes,
+    state: web::Data<Mutex<MockState>>,
+) -> HttpResponse {
+    let (response, default_latency) = {
+        let mut state = state.lock().expect("mock server lock poisoned");
+        state.requests.push(RecordedRequest {
+            method: req.method().clone(),
+            path: req.path().to_string(),
+            query: req.query_string().to_string(),
+            headers: req
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|v| (name.as_str().to_string(), v.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        });
+        (state.respond(req.method(), req.path()), state.latency)
+    };
+
+    if let Some(latency) = response.latency.or(default_latency) {
+        tokio::time::sleep(latency).await;
+    }
+
+    let mut builder = HttpResponse::build(response.status);
+    match response.body {
+        Some(body) => builder.json(body),
+        None => builder.finish(),
+    }
+}
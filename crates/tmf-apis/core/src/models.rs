@@ -2,7 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 /// Lifecycle status of a resource
@@ -58,12 +58,22 @@ pub struct TimePeriod {
 }
 
 /// Pagination parameters
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `cursor` is an opaque position returned with the previous page; when given,
+/// `offset` counts from that position rather than from the start.
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationParams {
+    /// Maximum number of items to return
     #[serde(default = "default_limit")]
+    #[param(default = 100)]
     pub limit: u32,
+    /// Number of items to skip
     #[serde(default)]
     pub offset: u32,
+    /// Cursor from the previous page's next link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
 }
 
 fn default_limit() -> u32 {
@@ -17,6 +17,11 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 web::resource("/productOffering")
                     .route(web::get().to(get_product_offerings))
                     .route(web::post().to(create_product_offering)),
+            )
+            .service(
+                web::resource("/productOffering/{id}/localizedContent")
+                    .route(web::get().to(get_product_offering_translations))
+                    .route(web::put().to(set_product_offering_translation)),
             ),
     );
 }
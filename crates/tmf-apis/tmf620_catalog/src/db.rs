@@ -1,10 +1,19 @@
 //! Database operations for TMF620 Product Catalog
 
-use crate::models::{Catalog, CreateCatalogRequest, CreateProductOfferingRequest, ProductOffering};
+use crate::locale::{normalize_locale, resolve_content};
+use crate::models::{
+    Catalog, CreateCatalogRequest, CreateProductOfferingRequest, LocalizedContent, ProductOffering,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use sqlx::{Pool, Postgres, Row};
-use tmf_apis_core::{LifecycleStatus, TmfError, TmfResult};
+use std::collections::HashMap;
+use tmf_apis_core::{LifecycleStatus, PaginationParams, TmfError, TmfResult};
 use uuid::Uuid;
 
+/// Largest page of catalogs returned at once; larger limits are capped
+pub const MAX_PAGE_LIMIT: u32 = 1000;
+
 // Helper to convert sqlx::Error to TmfError
 fn map_sqlx_error(err: sqlx::Error) -> TmfError {
     TmfError::Database(err.to_string())
@@ -70,18 +79,70 @@ pub async fn init_db() -> Pool<Postgres> {
     }
 }
 
-/// Get all catalogs
-pub async fn get_catalogs(pool: &Pool<Postgres>) -> TmfResult<Vec<Catalog>> {
+/// A page of catalogs
+#[derive(Debug, Clone)]
+pub struct CatalogPage {
+    pub catalogs: Vec<Catalog>,
+    /// Number of catalogs in the whole collection
+    pub total: u64,
+    /// Cursor for the following page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// Encode the sort key of the last catalog on a page as an opaque cursor
+fn encode_cursor(name: &str, id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}\n{}", id, name))
+}
+
+fn decode_cursor(cursor: &str) -> TmfResult<(String, Uuid)> {
+    let invalid = || TmfError::BadRequest("Invalid pagination cursor".to_string());
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (id, name) = text.split_once('\n').ok_or_else(invalid)?;
+    let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+    Ok((name.to_string(), id))
+}
+
+/// Get a page of catalogs, ordered by name and then id
+///
+/// Pages after the first are addressed by the cursor of the previous page,
+/// which points just past its last catalog, so catalogs added or removed
+/// meanwhile do not shift or repeat results. An offset past the end gives an
+/// empty page.
+pub async fn get_catalogs(
+    pool: &Pool<Postgres>,
+    params: &PaginationParams,
+) -> TmfResult<CatalogPage> {
+    if params.limit == 0 {
+        return Err(TmfError::BadRequest("limit must be at least 1".to_string()));
+    }
+    let limit = params.limit.min(MAX_PAGE_LIMIT);
+    let after = params.cursor.as_deref().map(decode_cursor).transpose()?;
+    let (after_name, after_id) = after.unzip();
+
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM catalogs")
+        .fetch_one(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+    // One extra row tells whether there is a following page
     let rows = sqlx::query(
-        "SELECT id, name, description, version, lifecycle_status, 
+        "SELECT id, name, description, version, lifecycle_status,
          href, last_update, valid_for_start, valid_for_end
-         FROM catalogs ORDER BY name",
+         FROM catalogs
+         WHERE $1::TEXT IS NULL OR (name, id) > ($1::TEXT, $2::UUID)
+         ORDER BY name, id
+         OFFSET $3 LIMIT $4",
     )
+    .bind(after_name)
+    .bind(after_id)
+    .bind(params.offset as i64)
+    .bind(limit as i64 + 1)
     .fetch_all(pool)
     .await
     .map_err(map_sqlx_error)?;
 
-    let catalogs = rows
+    let mut catalogs: Vec<Catalog> = rows
         .into_iter()
         .map(|row| Catalog {
             base: tmf_apis_core::BaseEntity {
@@ -107,7 +168,20 @@ pub async fn get_catalogs(pool: &Pool<Postgres>) -> TmfResult<Vec<Catalog>> {
         })
         .collect();
 
-    Ok(catalogs)
+    let next_cursor = if catalogs.len() > limit as usize {
+        catalogs.truncate(limit as usize);
+        catalogs
+            .last()
+            .map(|last| encode_cursor(&last.base.name, last.base.id))
+    } else {
+        None
+    };
+
+    Ok(CatalogPage {
+        catalogs,
+        total: total as u64,
+        next_cursor,
+    })
 }
 
 /// Get a catalog by ID
@@ -177,7 +251,7 @@ pub async fn get_product_offerings(pool: &Pool<Postgres>) -> TmfResult<Vec<Produ
     let rows = sqlx::query(
         "SELECT id, name, description, version, lifecycle_status,
          href, last_update, valid_for_start, valid_for_end,
-         is_sellable, is_bundle
+         is_sellable, is_bundle, kyc_required
          FROM product_offerings ORDER BY name",
     )
     .fetch_all(pool)
@@ -208,26 +282,181 @@ pub async fn get_product_offerings(pool: &Pool<Postgres>) -> TmfResult<Vec<Produ
             },
             is_sellable: row.get("is_sellable"),
             is_bundle: row.get("is_bundle"),
+            kyc_required: row.get("kyc_required"),
             product_specification: None,
             bundled_product_offering: None,
             product_offering_price: None,
+            locale: None,
         })
         .collect();
 
     Ok(offerings)
 }
 
-/// Create a new product offering
+/// Get all product offerings in the most preferred locale available
+///
+/// Offerings not translated into any of the `preferred` locales are returned
+/// in the default locale.
+pub async fn get_localized_product_offerings(
+    pool: &Pool<Postgres>,
+    preferred: &[String],
+    default_locale: &str,
+) -> TmfResult<Vec<ProductOffering>> {
+    let mut offerings = get_product_offerings(pool).await?;
+    let ids: Vec<Uuid> = offerings.iter().map(|o| o.base.id).collect();
+
+    let rows = sqlx::query(
+        "SELECT product_offering_id, locale, name, description
+         FROM product_offering_translations WHERE product_offering_id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut translations: HashMap<Uuid, Vec<LocalizedContent>> = HashMap::new();
+    for row in rows {
+        translations
+            .entry(row.get("product_offering_id"))
+            .or_default()
+            .push(LocalizedContent {
+                locale: row.get("locale"),
+                name: row.get("name"),
+                description: row.get("description"),
+            });
+    }
+
+    for offering in &mut offerings {
+        let content = translations
+            .get(&offering.base.id)
+            .and_then(|t| resolve_content(t, preferred, default_locale));
+        match content {
+            Some(content) => {
+                offering.base.name = content.name.clone();
+                if content.description.is_some() {
+                    offering.base.description = content.description.clone();
+                }
+                offering.locale = Some(content.locale.clone());
+            }
+            None => offering.locale = Some(default_locale.to_string()),
+        }
+    }
+
+    Ok(offerings)
+}
+
+/// Get the translations of a product offering
+pub async fn get_product_offering_translations(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+) -> TmfResult<Vec<LocalizedContent>> {
+    ensure_product_offering_exists(pool, id).await?;
+
+    let rows = sqlx::query(
+        "SELECT locale, name, description FROM product_offering_translations
+         WHERE product_offering_id = $1 ORDER BY locale",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LocalizedContent {
+            locale: row.get("locale"),
+            name: row.get("name"),
+            description: row.get("description"),
+        })
+        .collect())
+}
+
+/// Add or replace the translation of a product offering into a locale
+pub async fn set_product_offering_translation(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    content: LocalizedContent,
+    default_locale: &str,
+) -> TmfResult<LocalizedContent> {
+    let content = validate_translation(content, default_locale)?;
+    ensure_product_offering_exists(pool, id).await?;
+
+    sqlx::query(
+        "INSERT INTO product_offering_translations (product_offering_id, locale, name, description)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (product_offering_id, locale) DO UPDATE SET
+         name = EXCLUDED.name, description = EXCLUDED.description,
+         updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(id)
+    .bind(&content.locale)
+    .bind(&content.name)
+    .bind(&content.description)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(content)
+}
+
+/// Normalize the locale of a translation and check it is usable
+fn validate_translation(
+    content: LocalizedContent,
+    default_locale: &str,
+) -> TmfResult<LocalizedContent> {
+    let locale = normalize_locale(&content.locale)?;
+    if locale == default_locale {
+        return Err(TmfError::Validation(format!(
+            "Content in the default locale '{}' is the offering's own name and description",
+            locale
+        )));
+    }
+    if content.name.trim().is_empty() {
+        return Err(TmfError::Validation(format!(
+            "Translation into '{}' needs a name",
+            locale
+        )));
+    }
+    Ok(LocalizedContent { locale, ..content })
+}
+
+async fn ensure_product_offering_exists(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<()> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM product_offerings WHERE id = $1)")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .map_err(map_sqlx_error)?;
+    if exists {
+        Ok(())
+    } else {
+        Err(TmfError::NotFound(format!(
+            "Product offering with id {} not found",
+            id
+        )))
+    }
+}
+
+/// Create a new product offering, with its name and description in the
+/// default locale and any translations
 pub async fn create_product_offering(
     pool: &Pool<Postgres>,
     request: CreateProductOfferingRequest,
+    default_locale: &str,
 ) -> TmfResult<ProductOffering> {
     let id = Uuid::new_v4();
     let lifecycle_status = lifecycle_status_to_string(&request.lifecycle_status);
+    let translations = request
+        .localized_content
+        .iter()
+        .cloned()
+        .map(|content| validate_translation(content, default_locale))
+        .collect::<TmfResult<Vec<_>>>()?;
 
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
     sqlx::query(
-        "INSERT INTO product_offerings (id, name, description, version, lifecycle_status, is_sellable, is_bundle)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        "INSERT INTO product_offerings (id, name, description, version, lifecycle_status, is_sellable, is_bundle, kyc_required)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
     )
     .bind(id)
     .bind(&request.name)
@@ -236,14 +465,32 @@ pub async fn create_product_offering(
     .bind(&lifecycle_status)
     .bind(request.is_sellable)
     .bind(request.is_bundle)
-    .execute(pool)
+    .bind(request.kyc_required)
+    .execute(&mut *tx)
     .await
     .map_err(map_sqlx_error)?;
 
+    for content in &translations {
+        sqlx::query(
+            "INSERT INTO product_offering_translations (product_offering_id, locale, name, description)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (product_offering_id, locale) DO UPDATE SET
+             name = EXCLUDED.name, description = EXCLUDED.description",
+        )
+        .bind(id)
+        .bind(&content.locale)
+        .bind(&content.name)
+        .bind(&content.description)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+    tx.commit().await.map_err(map_sqlx_error)?;
+
     let row = sqlx::query(
         "SELECT id, name, description, version, lifecycle_status,
          href, last_update, valid_for_start, valid_for_end,
-         is_sellable, is_bundle
+         is_sellable, is_bundle, kyc_required
          FROM product_offerings WHERE id = $1",
     )
     .bind(id)
@@ -273,8 +520,10 @@ pub async fn create_product_offering(
         },
         is_sellable: row.get("is_sellable"),
         is_bundle: row.get("is_bundle"),
+        kyc_required: row.get("kyc_required"),
         product_specification: None,
         bundled_product_offering: None,
         product_offering_price: None,
+        locale: Some(default_locale.to_string()),
     })
 }
@@ -2,18 +2,41 @@
 
 use crate::auth::validate_token;
 use crate::db;
+use crate::locale::{preferred_locales, CatalogLocaleConfig};
 use crate::models::*;
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use actix_web::{http::header, web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
-use tmf_apis_core::TmfError;
+use tmf_apis_core::{PaginationParams, TmfError};
 use uuid::Uuid;
 
-/// Get all catalogs
+/// Default catalog locale, from the registered locale config if any
+fn default_locale(config: &Option<web::Data<CatalogLocaleConfig>>) -> String {
+    config
+        .as_ref()
+        .map(|c| c.default_locale.clone())
+        .unwrap_or_else(|| CatalogLocaleConfig::default().default_locale)
+        .to_ascii_lowercase()
+}
+
+/// Get a page of catalogs
+///
+/// The body is the page of catalogs. `X-Total-Count` gives the size of the
+/// whole collection and `X-Result-Count` the size of this page; when more
+/// catalogs follow, the opaque cursor for them is returned in `X-Next-Cursor`
+/// and as a `Link` with `rel="next"`.
 #[utoipa::path(
     get,
     path = "/tmf-api/productCatalogManagement/v4/catalog",
+    params(PaginationParams),
     responses(
-        (status = 200, description = "List of catalogs", body = Vec<Catalog>),
+        (status = 200, description = "Page of catalogs", body = Vec<Catalog>,
+            headers(
+                ("X-Total-Count" = u64, description = "Number of catalogs in the collection"),
+                ("X-Result-Count" = u64, description = "Number of catalogs in this page"),
+                ("X-Next-Cursor" = String, description = "Cursor for the next page, if any")
+            )
+        ),
+        (status = 400, description = "Invalid limit or cursor"),
         (status = 401, description = "Unauthorized")
     ),
     tag = "TMF620"
@@ -21,11 +44,36 @@ use uuid::Uuid;
 pub async fn get_catalogs(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
+    query: web::Query<PaginationParams>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::get_catalogs(pool.get_ref()).await {
-        Ok(catalogs) => Ok(HttpResponse::Ok().json(catalogs)),
+    let params = query.into_inner();
+    match db::get_catalogs(pool.get_ref(), &params).await {
+        Ok(page) => {
+            let mut response = HttpResponse::Ok();
+            response
+                .insert_header(("X-Total-Count", page.total.to_string()))
+                .insert_header(("X-Result-Count", page.catalogs.len().to_string()));
+            if let Some(cursor) = &page.next_cursor {
+                let limit = params.limit.min(db::MAX_PAGE_LIMIT);
+                response
+                    .insert_header(("X-Next-Cursor", cursor.as_str()))
+                    .insert_header((
+                        header::LINK,
+                        format!(
+                            "<{}?limit={}&cursor={}>; rel=\"next\"",
+                            req.path(),
+                            limit,
+                            cursor
+                        ),
+                    ));
+            }
+            Ok(response.json(page.catalogs))
+        }
+        Err(TmfError::BadRequest(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
@@ -102,6 +150,10 @@ pub async fn create_catalog(
 }
 
 /// Get all product offerings
+///
+/// Names and descriptions are in the most preferred locale of the
+/// `Accept-Language` header that an offering is translated into, falling back
+/// to the default locale.
 #[utoipa::path(
     get,
     path = "/tmf-api/productCatalogManagement/v4/productOffering",
@@ -109,15 +161,25 @@ pub async fn create_catalog(
         (status = 200, description = "List of product offerings", body = Vec<ProductOffering>),
         (status = 401, description = "Unauthorized")
     ),
+    params(
+        ("Accept-Language" = Option<String>, Header, description = "Preferred locales (e.g. \"pt-BR,es;q=0.8\")")
+    ),
     tag = "TMF620"
 )]
 pub async fn get_product_offerings(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
+    locale_config: Option<web::Data<CatalogLocaleConfig>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::get_product_offerings(pool.get_ref()).await {
+    let preferred = preferred_locales(
+        req.headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let default_locale = default_locale(&locale_config);
+    match db::get_localized_product_offerings(pool.get_ref(), &preferred, &default_locale).await {
         Ok(offerings) => Ok(HttpResponse::Ok().json(offerings)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
@@ -141,13 +203,113 @@ pub async fn create_product_offering(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
     body: web::Json<CreateProductOfferingRequest>,
+    locale_config: Option<web::Data<CatalogLocaleConfig>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_product_offering(pool.get_ref(), body.into_inner()).await {
+    let default_locale = default_locale(&locale_config);
+    match db::create_product_offering(pool.get_ref(), body.into_inner(), &default_locale).await {
         Ok(offering) => Ok(HttpResponse::Created().json(offering)),
         Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
             "error": e.to_string()
         }))),
     }
 }
+
+/// Get the translations of a product offering
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productCatalogManagement/v4/productOffering/{id}/localizedContent",
+    responses(
+        (status = 200, description = "Translations of the product offering", body = Vec<LocalizedContent>),
+        (status = 404, description = "Product offering not found"),
+        (status = 400, description = "Invalid product offering ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Product offering ID (UUID)")
+    ),
+    tag = "TMF620"
+)]
+pub async fn get_product_offering_translations(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid product offering ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::get_product_offering_translations(pool.get_ref(), id).await {
+        Ok(translations) => Ok(HttpResponse::Ok().json(translations)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Add or replace the translation of a product offering into a locale
+#[utoipa::path(
+    put,
+    path = "/tmf-api/productCatalogManagement/v4/productOffering/{id}/localizedContent",
+    request_body = LocalizedContent,
+    responses(
+        (status = 200, description = "Translation saved", body = LocalizedContent),
+        (status = 400, description = "Invalid locale or content"),
+        (status = 404, description = "Product offering not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Product offering ID (UUID)")
+    ),
+    tag = "TMF620"
+)]
+pub async fn set_product_offering_translation(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<LocalizedContent>,
+    locale_config: Option<web::Data<CatalogLocaleConfig>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid product offering ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let default_locale = default_locale(&locale_config);
+    match db::set_product_offering_translation(
+        pool.get_ref(),
+        id,
+        body.into_inner(),
+        &default_locale,
+    )
+    .await
+    {
+        Ok(content) => Ok(HttpResponse::Ok().json(content)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
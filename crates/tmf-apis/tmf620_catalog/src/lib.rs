@@ -8,10 +8,12 @@ pub mod api;
 pub mod auth;
 pub mod db;
 pub mod handlers;
+pub mod locale;
 pub mod models;
 
 pub use auth::*;
 pub use handlers::*;
+pub use locale::*;
 pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
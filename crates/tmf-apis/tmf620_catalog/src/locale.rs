@@ -0,0 +1,111 @@
+//! Localized catalog content
+//!
+//! Product offerings carry their name and description in the catalog's
+//! default locale, plus translations into other locales. Reads resolve the
+//! locale from the `Accept-Language` header: each requested language is tried
+//! in order of preference, and content missing in every requested language
+//! falls back to the default locale rather than coming back blank.
+
+use crate::models::LocalizedContent;
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Locale settings of the catalog
+#[derive(Debug, Clone)]
+pub struct CatalogLocaleConfig {
+    /// Locale of the name and description stored on offerings themselves
+    pub default_locale: String,
+}
+
+impl Default for CatalogLocaleConfig {
+    fn default() -> Self {
+        Self {
+            default_locale: "en".to_string(),
+        }
+    }
+}
+
+/// Normalize a language tag (e.g. "pt-BR" to "pt-br"), rejecting malformed
+/// ones
+pub fn normalize_locale(locale: &str) -> TmfResult<String> {
+    let locale = locale.trim().to_ascii_lowercase();
+    let well_formed = !locale.is_empty()
+        && locale.len() <= 35
+        && locale.split('-').all(|subtag| {
+            !subtag.is_empty()
+                && subtag.len() <= 8
+                && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if well_formed {
+        Ok(locale)
+    } else {
+        Err(TmfError::Validation(format!("Invalid locale '{}'", locale)))
+    }
+}
+
+/// Locales requested by an `Accept-Language` header, most preferred first
+///
+/// Each tag is followed by its more general forms ("pt-br" then "pt"), so a
+/// regional request still matches the plain language. Wildcards and malformed
+/// or refused (`q=0`) entries are skipped.
+pub fn preferred_locales(accept_language: Option<&str>) -> Vec<String> {
+    let mut ranges: Vec<(String, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = normalize_locale(parts.next()?).ok()?;
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equally weighted tags keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut locales: Vec<String> = Vec::new();
+    for (tag, _) in ranges {
+        let mut candidate = tag.as_str();
+        loop {
+            if !locales.iter().any(|l| l == candidate) {
+                locales.push(candidate.to_string());
+            }
+            match candidate.rfind('-') {
+                Some(end) => candidate = &candidate[..end],
+                None => break,
+            }
+        }
+    }
+    locales
+}
+
+/// Pick the content to serve from an offering's translations
+///
+/// Returns the matching translation, or `None` when the default-locale content
+/// should be served. A translation without a description keeps the default
+/// description.
+pub fn resolve_content<'a>(
+    translations: &'a [LocalizedContent],
+    preferred: &[String],
+    default_locale: &str,
+) -> Option<&'a LocalizedContent> {
+    for locale in preferred {
+        if locale == default_locale {
+            return None;
+        }
+        let regional = format!("{}-", locale);
+        let translation = translations
+            .iter()
+            .find(|t| &t.locale == locale)
+            .or_else(|| {
+                translations
+                    .iter()
+                    .find(|t| t.locale.starts_with(&regional))
+            });
+        if translation.is_some() {
+            return translation;
+        }
+    }
+    None
+}
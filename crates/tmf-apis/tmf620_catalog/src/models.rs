@@ -36,6 +36,9 @@ pub struct ProductOffering {
     /// Whether this offering can be bundled
     #[serde(default)]
     pub is_bundle: bool,
+    /// Regulated offering that may only be ordered by a KYC-verified party
+    #[serde(default)]
+    pub kyc_required: bool,
     /// Product specifications
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_specification: Option<ProductSpecificationRef>,
@@ -45,6 +48,20 @@ pub struct ProductOffering {
     /// Product offering prices
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_offering_price: Option<Vec<ProductOfferingPrice>>,
+    /// Locale of the name and description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+/// Name and description of a product offering in one locale
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LocalizedContent {
+    /// Language tag (e.g. "pt-BR", "es")
+    pub locale: String,
+    pub name: String,
+    /// Falls back to the default-locale description if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 /// Reference to a product specification
@@ -109,4 +126,10 @@ pub struct CreateProductOfferingRequest {
     pub is_sellable: bool,
     #[serde(default)]
     pub is_bundle: bool,
+    /// Regulated offering that may only be ordered by a KYC-verified party
+    #[serde(default)]
+    pub kyc_required: bool,
+    /// Translations of the name and description into other locales
+    #[serde(default)]
+    pub localized_content: Vec<LocalizedContent>,
 }
@@ -12,6 +12,19 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_orders))
                     .route(web::post().to(create_order)),
             )
-            .service(web::resource("/productOrder/{id}").route(web::get().to(get_order_by_id))),
+            .service(web::resource("/productOrder/{id}").route(web::get().to(get_order_by_id)))
+            .service(
+                web::resource("/productOrderTemplate")
+                    .route(web::get().to(get_order_templates))
+                    .route(web::post().to(create_order_template)),
+            )
+            .service(
+                web::resource("/productOrderTemplate/{id}")
+                    .route(web::get().to(get_order_template_by_id)),
+            )
+            .service(
+                web::resource("/productOrderTemplate/{id}/productOrder")
+                    .route(web::post().to(create_order_from_template)),
+            ),
     );
 }
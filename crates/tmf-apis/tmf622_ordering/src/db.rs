@@ -1,8 +1,14 @@
 //! Database operations for TMF622 Product Ordering
 
-use crate::models::{CreateProductOrderRequest, OrderState, ProductOrder};
+use crate::models::{
+    CreateOrderItemRequest, CreateOrderTemplateRequest, CreateProductOrderRequest,
+    CreateRelatedPartyRequest, OrderFromTemplateRequest, OrderState, OrderTemplateItem,
+    ProductOrder, ProductOrderTemplate,
+};
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
 use sqlx::{Pool, Postgres, Row};
+use tmf629_customer::credit::{CreditCheck, CreditCheckRequest, CreditDecision};
 use tmf_apis_core::{TmfError, TmfResult};
 use uuid::Uuid;
 
@@ -38,48 +44,140 @@ fn order_state_to_string(state: &OrderState) -> String {
     }
 }
 
+/// Parse a stored credit decision
+fn parse_credit_decision(value: Option<serde_json::Value>) -> Option<CreditDecision> {
+    value.and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Order state resulting from a credit decision
+fn state_for_credit_decision(decision: &CreditDecision) -> OrderState {
+    match decision {
+        CreditDecision::Approved => OrderState::Acknowledged,
+        CreditDecision::Declined { .. } => OrderState::Rejected,
+        // Held until the deposit has been collected
+        CreditDecision::DepositRequired { .. } => OrderState::Held,
+    }
+}
+
+/// Build the credit check request for an order
+fn credit_check_request(request: &CreateProductOrderRequest) -> CreditCheckRequest {
+    CreditCheckRequest {
+        customer_id: request.customer_id,
+        customer_name: request.related_party.as_ref().and_then(|parties| {
+            parties
+                .iter()
+                .find(|p| p.role.eq_ignore_ascii_case("customer"))
+                .map(|p| p.name.clone())
+        }),
+        product_offering_ids: request
+            .order_item
+            .iter()
+            .flatten()
+            .filter_map(|item| item.product_offering_id)
+            .collect(),
+        priority: request.priority.clone(),
+    }
+}
+
+/// Require a KYC-verified party when any ordered offering requires KYC
+async fn check_kyc(pool: &Pool<Postgres>, request: &CreateProductOrderRequest) -> TmfResult<()> {
+    let offering_ids: Vec<Uuid> = request
+        .order_item
+        .iter()
+        .flatten()
+        .filter_map(|item| item.product_offering_id)
+        .collect();
+    if offering_ids.is_empty() {
+        return Ok(());
+    }
+
+    let regulated: Vec<Uuid> =
+        sqlx::query_scalar("SELECT id FROM product_offerings WHERE id = ANY($1) AND kyc_required")
+            .bind(&offering_ids)
+            .fetch_all(pool)
+            .await
+            .map_err(map_sqlx_error)?;
+    if regulated.is_empty() {
+        return Ok(());
+    }
+
+    let party_id = request.party_id.ok_or_else(|| {
+        TmfError::Validation(format!(
+            "Product offering {} requires KYC; the order must name the ordering party",
+            regulated[0]
+        ))
+    })?;
+    tmf632_party::kyc::require_verified(pool, party_id).await
+}
+
+/// Helper to convert database row to ProductOrder
+fn row_to_order(row: &sqlx::postgres::PgRow) -> ProductOrder {
+    ProductOrder {
+        base: tmf_apis_core::BaseEntity {
+            id: row.get::<Uuid, _>("id"),
+            href: row.get::<Option<String>, _>("href"),
+            name: row.get::<String, _>("name"),
+            description: row.get::<Option<String>, _>("description"),
+            version: row.get::<Option<String>, _>("version"),
+            lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
+            last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
+            valid_for: None,
+        },
+        state: parse_order_state(&row.get::<String, _>("state")),
+        order_item: None,    // Load separately if needed
+        related_party: None, // Load separately if needed
+        order_date: row.get::<Option<DateTime<Utc>>, _>("order_date"),
+        expected_completion_date: row.get::<Option<DateTime<Utc>>, _>("expected_completion_date"),
+        priority: row.get::<Option<String>, _>("priority"),
+        customer_id: row.get::<Option<Uuid>, _>("customer_id"),
+        party_id: row.get::<Option<Uuid>, _>("party_id"),
+        credit_decision: parse_credit_decision(row.get("credit_decision")),
+    }
+}
+
 /// Get all product orders
 pub async fn get_orders(pool: &Pool<Postgres>) -> TmfResult<Vec<ProductOrder>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, order_date, 
-         expected_completion_date, priority, href, last_update
+         expected_completion_date, priority, href, last_update, customer_id, party_id,
+         credit_decision
          FROM product_orders ORDER BY order_date DESC",
     )
     .fetch_all(pool)
     .await
     .map_err(map_sqlx_error)?;
 
-    let mut orders = Vec::new();
-    for row in rows {
-        orders.push(ProductOrder {
-            base: tmf_apis_core::BaseEntity {
-                id: row.get::<Uuid, _>("id"),
-                href: row.get::<Option<String>, _>("href"),
-                name: row.get::<String, _>("name"),
-                description: row.get::<Option<String>, _>("description"),
-                version: row.get::<Option<String>, _>("version"),
-                lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
-                last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
-                valid_for: None,
-            },
-            state: parse_order_state(&row.get::<String, _>("state")),
-            order_item: None,    // Load separately if needed
-            related_party: None, // Load separately if needed
-            order_date: row.get::<Option<DateTime<Utc>>, _>("order_date"),
-            expected_completion_date: row
-                .get::<Option<DateTime<Utc>>, _>("expected_completion_date"),
-            priority: row.get::<Option<String>, _>("priority"),
-        });
-    }
-
-    Ok(orders)
+    Ok(rows.iter().map(row_to_order).collect())
+}
+
+/// Get the most recent product orders of a customer, newest first
+pub async fn get_recent_orders_by_customer(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+    limit: i64,
+) -> TmfResult<Vec<ProductOrder>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, order_date, 
+         expected_completion_date, priority, href, last_update, customer_id, party_id,
+         credit_decision
+         FROM product_orders WHERE customer_id = $1
+         ORDER BY order_date DESC NULLS LAST LIMIT $2",
+    )
+    .bind(customer_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows.iter().map(row_to_order).collect())
 }
 
 /// Get product order by ID
 pub async fn get_order_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<ProductOrder> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, order_date, 
-         expected_completion_date, priority, href, last_update
+         expected_completion_date, priority, href, last_update, customer_id, party_id,
+         credit_decision
          FROM product_orders WHERE id = $1",
     )
     .bind(id)
@@ -88,38 +186,46 @@ pub async fn get_order_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Produ
     .map_err(map_sqlx_error)?
     .ok_or_else(|| TmfError::NotFound(format!("Product order with id {} not found", id)))?;
 
-    Ok(ProductOrder {
-        base: tmf_apis_core::BaseEntity {
-            id: row.get::<Uuid, _>("id"),
-            href: row.get::<Option<String>, _>("href"),
-            name: row.get::<String, _>("name"),
-            description: row.get::<Option<String>, _>("description"),
-            version: row.get::<Option<String>, _>("version"),
-            lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
-            last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
-            valid_for: None,
-        },
-        state: parse_order_state(&row.get::<String, _>("state")),
-        order_item: None,
-        related_party: None,
-        order_date: row.get::<Option<DateTime<Utc>>, _>("order_date"),
-        expected_completion_date: row.get::<Option<DateTime<Utc>>, _>("expected_completion_date"),
-        priority: row.get::<Option<String>, _>("priority"),
-    })
+    Ok(row_to_order(&row))
 }
 
 /// Create a new product order
+///
+/// Offerings that require KYC may only be ordered by a KYC-verified party.
+/// The order is then run through the credit check; declined orders are
+/// recorded as rejected and orders requiring a deposit are held.
 pub async fn create_order(
     pool: &Pool<Postgres>,
     request: CreateProductOrderRequest,
+    credit_check: &dyn CreditCheck,
 ) -> TmfResult<ProductOrder> {
+    check_kyc(pool, &request).await?;
+    let decision = credit_check.check(&credit_check_request(&request)).await?;
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    let id = insert_order(&mut tx, request, &decision).await?;
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    // Fetch the created order
+    get_order_by_id(pool, id).await
+}
+
+/// Insert an order with its items and related parties, returning its id
+async fn insert_order(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    request: CreateProductOrderRequest,
+    decision: &CreditDecision,
+) -> TmfResult<Uuid> {
+    let decision_json =
+        serde_json::to_value(decision).map_err(|e| TmfError::Internal(e.to_string()))?;
     let id = Uuid::new_v4();
-    let state = order_state_to_string(&OrderState::Acknowledged);
+    let state = order_state_to_string(&state_for_credit_decision(decision));
     let now = Utc::now();
 
     sqlx::query(
-        "INSERT INTO product_orders (id, name, description, version, state, order_date, priority)
-         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        "INSERT INTO product_orders (id, name, description, version, state, order_date, priority,
+         customer_id, party_id, credit_decision)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
     )
     .bind(id)
     .bind(&request.name)
@@ -128,7 +234,10 @@ pub async fn create_order(
     .bind(&state)
     .bind(now)
     .bind(&request.priority)
-    .execute(pool)
+    .bind(request.customer_id)
+    .bind(request.party_id)
+    .bind(&decision_json)
+    .execute(&mut **tx)
     .await
     .map_err(map_sqlx_error)?;
 
@@ -148,7 +257,7 @@ pub async fn create_order(
             .bind(item.product_specification_id)
             .bind(&state)
             .bind(item.quantity)
-            .execute(pool)
+            .execute(&mut **tx)
             .await
             .map_err(map_sqlx_error)?;
         }
@@ -166,12 +275,489 @@ pub async fn create_order(
             .bind(id)
             .bind(&party.name)
             .bind(&party.role)
-            .execute(pool)
+            .execute(&mut **tx)
             .await
             .map_err(map_sqlx_error)?;
         }
     }
 
-    // Fetch the created order
+    Ok(id)
+}
+
+/// Idempotent order creation settings
+#[derive(Debug, Clone)]
+pub struct IdempotencyConfig {
+    /// How long a key returns its order before it may be reused
+    pub ttl: chrono::Duration,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl: chrono::Duration::hours(24),
+        }
+    }
+}
+
+/// Outcome of claiming an idempotency key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdempotencyClaim {
+    /// The key is new, or its previous use expired
+    Claimed,
+    /// An order was already created with the key
+    Completed(Uuid),
+    /// Another request with the key is still being processed
+    InProgress,
+    /// The key was used with a different request body
+    Mismatch,
+}
+
+/// Result of an idempotent order submission
+#[derive(Debug, Clone)]
+pub enum IdempotentOrder {
+    /// The order was created by this submission
+    Created(ProductOrder),
+    /// The order was created by an earlier submission with the same key
+    Existing(ProductOrder),
+    /// The key was already used for a different request body
+    KeyMismatch,
+}
+
+/// Fingerprint of an order submission, used to detect a key reused for another request
+pub fn request_fingerprint(request: &CreateProductOrderRequest) -> TmfResult<String> {
+    let body = serde_json::to_vec(request).map_err(|e| TmfError::Internal(e.to_string()))?;
+    Ok(format!("{:x}", Sha256::digest(&body)))
+}
+
+/// Claim an idempotency key for a client
+///
+/// Claiming is a single insert, so of several concurrent requests with the
+/// same key exactly one gets [`IdempotencyClaim::Claimed`].
+pub async fn claim_idempotency_key(
+    pool: &Pool<Postgres>,
+    client_id: &str,
+    key: &str,
+    fingerprint: &str,
+    ttl: chrono::Duration,
+) -> TmfResult<IdempotencyClaim> {
+    let now = Utc::now();
+    let claimed = sqlx::query(
+        "INSERT INTO product_order_idempotency_keys
+         (client_id, idempotency_key, request_fingerprint, created_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (client_id, idempotency_key) DO UPDATE
+         SET order_id = NULL, request_fingerprint = EXCLUDED.request_fingerprint,
+             created_at = EXCLUDED.created_at, expires_at = EXCLUDED.expires_at
+         WHERE product_order_idempotency_keys.expires_at <= EXCLUDED.created_at
+         RETURNING client_id",
+    )
+    .bind(client_id)
+    .bind(key)
+    .bind(fingerprint)
+    .bind(now)
+    .bind(now + ttl)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    if claimed.is_some() {
+        return Ok(IdempotencyClaim::Claimed);
+    }
+
+    let existing: Option<(Option<Uuid>, Option<String>)> = sqlx::query_as(
+        "SELECT order_id, request_fingerprint FROM product_order_idempotency_keys
+         WHERE client_id = $1 AND idempotency_key = $2",
+    )
+    .bind(client_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(match existing {
+        Some((_, Some(stored))) if stored != fingerprint => IdempotencyClaim::Mismatch,
+        Some((Some(order_id), _)) => IdempotencyClaim::Completed(order_id),
+        _ => IdempotencyClaim::InProgress,
+    })
+}
+
+/// Record the order created for a claimed idempotency key
+async fn complete_idempotency_key(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    client_id: &str,
+    key: &str,
+    order_id: Uuid,
+) -> TmfResult<()> {
+    sqlx::query(
+        "UPDATE product_order_idempotency_keys SET order_id = $3
+         WHERE client_id = $1 AND idempotency_key = $2",
+    )
+    .bind(client_id)
+    .bind(key)
+    .bind(order_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    Ok(())
+}
+
+/// Release a claimed idempotency key whose order could not be created
+async fn release_idempotency_key(
+    pool: &Pool<Postgres>,
+    client_id: &str,
+    key: &str,
+) -> TmfResult<()> {
+    sqlx::query(
+        "DELETE FROM product_order_idempotency_keys
+         WHERE client_id = $1 AND idempotency_key = $2 AND order_id IS NULL",
+    )
+    .bind(client_id)
+    .bind(key)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    Ok(())
+}
+
+/// Create a product order once per client and idempotency key
+///
+/// A repeated request returns the order created by the first one until the
+/// key expires; while the first request is still being processed, repeats
+/// are rejected as a conflict, and a repeat with a different body is
+/// reported as [`IdempotentOrder::KeyMismatch`]. The order and the key's
+/// completion are written in one transaction, so a key never stays claimed
+/// for an order that was committed. If the order cannot be created the key
+/// is released so the client can retry with it.
+pub async fn create_order_idempotent(
+    pool: &Pool<Postgres>,
+    request: CreateProductOrderRequest,
+    credit_check: &dyn CreditCheck,
+    client_id: &str,
+    key: &str,
+    config: &IdempotencyConfig,
+) -> TmfResult<IdempotentOrder> {
+    let fingerprint = request_fingerprint(&request)?;
+    match claim_idempotency_key(pool, client_id, key, &fingerprint, config.ttl).await? {
+        IdempotencyClaim::Completed(order_id) => {
+            return Ok(IdempotentOrder::Existing(
+                get_order_by_id(pool, order_id).await?,
+            ));
+        }
+        IdempotencyClaim::InProgress => {
+            return Err(TmfError::Conflict(format!(
+                "An order with idempotency key {} is still being processed",
+                key
+            )));
+        }
+        IdempotencyClaim::Mismatch => return Ok(IdempotentOrder::KeyMismatch),
+        IdempotencyClaim::Claimed => {}
+    }
+
+    match create_order_with_key(pool, request, credit_check, client_id, key).await {
+        Ok(order) => Ok(IdempotentOrder::Created(order)),
+        Err(e) => {
+            if let Err(release_err) = release_idempotency_key(pool, client_id, key).await {
+                log::warn!(
+                    "Failed to release idempotency key {} of client {}: {}",
+                    key,
+                    client_id,
+                    release_err
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Create an order and record it against a claimed idempotency key in one transaction
+async fn create_order_with_key(
+    pool: &Pool<Postgres>,
+    request: CreateProductOrderRequest,
+    credit_check: &dyn CreditCheck,
+    client_id: &str,
+    key: &str,
+) -> TmfResult<ProductOrder> {
+    check_kyc(pool, &request).await?;
+    let decision = credit_check.check(&credit_check_request(&request)).await?;
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    let id = insert_order(&mut tx, request, &decision).await?;
+    complete_idempotency_key(&mut tx, client_id, key, id).await?;
+    tx.commit().await.map_err(map_sqlx_error)?;
+
     get_order_by_id(pool, id).await
 }
+
+/// Delete idempotency keys that expired before `now`, returning how many
+pub async fn purge_expired_idempotency_keys(
+    pool: &Pool<Postgres>,
+    now: DateTime<Utc>,
+) -> TmfResult<u64> {
+    let result = sqlx::query("DELETE FROM product_order_idempotency_keys WHERE expires_at <= $1")
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+    Ok(result.rows_affected())
+}
+
+/// Save an existing order as a reusable template
+///
+/// Each item records the current version of its product offering so later
+/// catalog changes are detected when ordering from the template.
+pub async fn create_template(
+    pool: &Pool<Postgres>,
+    request: CreateOrderTemplateRequest,
+) -> TmfResult<ProductOrderTemplate> {
+    let order = get_order_by_id(pool, request.order_id).await?;
+
+    let item_rows = sqlx::query(
+        "SELECT oi.action, oi.product_offering_id, oi.product_specification_id, oi.quantity,
+         po.version AS product_offering_version
+         FROM order_items oi
+         LEFT JOIN product_offerings po ON po.id = oi.product_offering_id
+         WHERE oi.order_id = $1 ORDER BY oi.created_at, oi.id",
+    )
+    .bind(request.order_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let related_party: Vec<CreateRelatedPartyRequest> =
+        sqlx::query("SELECT name, role FROM related_parties WHERE order_id = $1")
+            .bind(request.order_id)
+            .fetch_all(pool)
+            .await
+            .map_err(map_sqlx_error)?
+            .iter()
+            .map(|row| CreateRelatedPartyRequest {
+                name: row.get("name"),
+                role: row.get("role"),
+            })
+            .collect();
+
+    let id = Uuid::new_v4();
+    let related_party_json =
+        serde_json::to_value(&related_party).map_err(|e| TmfError::Internal(e.to_string()))?;
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
+    sqlx::query(
+        "INSERT INTO product_order_templates (id, name, description, source_order_id, priority,
+         customer_id, related_parties)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(id)
+    .bind(&request.name)
+    .bind(&request.description)
+    .bind(request.order_id)
+    .bind(&order.priority)
+    .bind(order.customer_id)
+    .bind(&related_party_json)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    for (position, row) in item_rows.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO product_order_template_items (id, template_id, position, action,
+             product_offering_id, product_offering_version, product_specification_id, quantity)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(id)
+        .bind(position as i32)
+        .bind(row.get::<String, _>("action"))
+        .bind(row.get::<Option<Uuid>, _>("product_offering_id"))
+        .bind(row.get::<Option<String>, _>("product_offering_version"))
+        .bind(row.get::<Option<Uuid>, _>("product_specification_id"))
+        .bind(row.get::<Option<i32>, _>("quantity"))
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    get_template_by_id(pool, id).await
+}
+
+/// Get all order templates
+pub async fn get_templates(pool: &Pool<Postgres>) -> TmfResult<Vec<ProductOrderTemplate>> {
+    let ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM product_order_templates ORDER BY name")
+        .fetch_all(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+
+    let mut templates = Vec::with_capacity(ids.len());
+    for id in ids {
+        templates.push(get_template_by_id(pool, id).await?);
+    }
+    Ok(templates)
+}
+
+/// Get order template by ID
+pub async fn get_template_by_id(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+) -> TmfResult<ProductOrderTemplate> {
+    let row = sqlx::query(
+        "SELECT id, name, description, source_order_id, priority, customer_id, related_parties,
+         created_at
+         FROM product_order_templates WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?
+    .ok_or_else(|| TmfError::NotFound(format!("Order template with id {} not found", id)))?;
+
+    let template_item = sqlx::query(
+        "SELECT id, action, product_offering_id, product_offering_version,
+         product_specification_id, quantity
+         FROM product_order_template_items WHERE template_id = $1 ORDER BY position",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?
+    .iter()
+    .map(|item| OrderTemplateItem {
+        id: item.get("id"),
+        action: item.get("action"),
+        product_offering_id: item.get("product_offering_id"),
+        product_offering_version: item.get("product_offering_version"),
+        product_specification_id: item.get("product_specification_id"),
+        quantity: item.get("quantity"),
+    })
+    .collect();
+
+    let related_party = serde_json::from_value(row.get::<serde_json::Value, _>("related_parties"))
+        .map_err(|e| TmfError::Internal(e.to_string()))?;
+
+    Ok(ProductOrderTemplate {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        source_order_id: row.get("source_order_id"),
+        priority: row.get("priority"),
+        customer_id: row.get("customer_id"),
+        template_item,
+        related_party,
+        created_at: row.get("created_at"),
+    })
+}
+
+/// Check template items against the current catalog
+///
+/// Missing or retired offerings always fail; a changed offering version fails
+/// unless the caller accepts catalog changes.
+async fn check_template_catalog(
+    pool: &Pool<Postgres>,
+    template: &ProductOrderTemplate,
+    accept_catalog_changes: bool,
+) -> TmfResult<()> {
+    let mut problems = Vec::new();
+
+    for item in &template.template_item {
+        let Some(offering_id) = item.product_offering_id else {
+            continue;
+        };
+
+        let row =
+            sqlx::query("SELECT version, lifecycle_status FROM product_offerings WHERE id = $1")
+                .bind(offering_id)
+                .fetch_optional(pool)
+                .await
+                .map_err(map_sqlx_error)?;
+
+        match row {
+            None => problems.push(format!("offering {} no longer exists", offering_id)),
+            Some(row) => {
+                let status = row.get::<String, _>("lifecycle_status").to_uppercase();
+                let version: Option<String> = row.get("version");
+                if status == "RETIRED" || status == "OBSOLETE" {
+                    problems.push(format!("offering {} is {}", offering_id, status));
+                } else if version != item.product_offering_version && !accept_catalog_changes {
+                    problems.push(format!(
+                        "offering {} changed from version {} to {}",
+                        offering_id,
+                        item.product_offering_version.as_deref().unwrap_or("none"),
+                        version.as_deref().unwrap_or("none")
+                    ));
+                }
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(TmfError::Conflict(format!(
+            "Order template {} is out of date with the catalog: {}",
+            template.id,
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Create a new product order from a template
+pub async fn create_order_from_template(
+    pool: &Pool<Postgres>,
+    template_id: Uuid,
+    request: OrderFromTemplateRequest,
+    credit_check: &dyn CreditCheck,
+) -> TmfResult<ProductOrder> {
+    let template = get_template_by_id(pool, template_id).await?;
+
+    for (item_id, quantity) in &request.quantity_overrides {
+        if !template
+            .template_item
+            .iter()
+            .any(|item| item.id == *item_id)
+        {
+            return Err(TmfError::BadRequest(format!(
+                "Template item {} not found in template {}",
+                item_id, template_id
+            )));
+        }
+        if *quantity <= 0 {
+            return Err(TmfError::Validation(format!(
+                "Quantity for template item {} must be positive",
+                item_id
+            )));
+        }
+    }
+
+    check_template_catalog(pool, &template, request.accept_catalog_changes).await?;
+
+    let order_item = template
+        .template_item
+        .iter()
+        .map(|item| CreateOrderItemRequest {
+            action: item.action.clone(),
+            product_offering_id: item.product_offering_id,
+            product_specification_id: item.product_specification_id,
+            quantity: request
+                .quantity_overrides
+                .get(&item.id)
+                .copied()
+                .or(item.quantity),
+        })
+        .collect();
+
+    create_order(
+        pool,
+        CreateProductOrderRequest {
+            name: request.name.unwrap_or_else(|| template.name.clone()),
+            description: template.description.clone(),
+            version: None,
+            priority: template.priority.clone(),
+            order_item: Some(order_item),
+            related_party: Some(template.related_party),
+            customer_id: template.customer_id,
+            party_id: request.party_id,
+        },
+        credit_check,
+    )
+    .await
+}
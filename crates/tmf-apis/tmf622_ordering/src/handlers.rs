@@ -1,13 +1,26 @@
 //! Request handlers for TMF622 API endpoints
 
 use crate::auth::validate_token;
-use crate::db;
+use crate::db::{self, IdempotencyConfig, IdempotentOrder};
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
+use std::sync::Arc;
+use tmf629_customer::credit::{ApproveAllCreditCheck, CreditCheck};
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
+/// Header carrying the client's idempotency key for order submissions
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Credit check registered as app data, or the approve-all default
+fn credit_check(registered: &Option<web::Data<Arc<dyn CreditCheck>>>) -> Arc<dyn CreditCheck> {
+    registered
+        .as_ref()
+        .map(|data| Arc::clone(data.get_ref()))
+        .unwrap_or_else(|| Arc::new(ApproveAllCreditCheck))
+}
+
 /// Get all product orders
 #[utoipa::path(
     get,
@@ -79,9 +92,15 @@ pub async fn get_order_by_id(
     post,
     path = "/tmf-api/productOrderingManagement/v4/productOrder",
     request_body = CreateProductOrderRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key; a retried submission with the same key returns the original order")
+    ),
     responses(
-        (status = 201, description = "Product order created", body = ProductOrder),
+        (status = 201, description = "Product order created; check the credit decision and state", body = ProductOrder),
+        (status = 200, description = "Order already created with this idempotency key", body = ProductOrder),
         (status = 400, description = "Invalid request"),
+        (status = 409, description = "Ordering party is not KYC verified for a regulated offering, or a request with this idempotency key is still being processed"),
+        (status = 422, description = "Idempotency key was already used for a different request body"),
         (status = 401, description = "Unauthorized")
     ),
     tag = "TMF622"
@@ -90,13 +109,177 @@ pub async fn create_order(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
     body: web::Json<CreateProductOrderRequest>,
+    registered_credit_check: Option<web::Data<Arc<dyn CreditCheck>>>,
+    idempotency_config: Option<web::Data<IdempotencyConfig>>,
+) -> ActixResult<HttpResponse> {
+    let client_id = validate_token(&req)?;
+
+    let credit_check = credit_check(&registered_credit_check);
+    let Some(header) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return match db::create_order(pool.get_ref(), body.into_inner(), credit_check.as_ref())
+            .await
+        {
+            Ok(order) => Ok(HttpResponse::Created().json(order)),
+            Err(e) => Ok(error_response(e)),
+        };
+    };
+
+    let key = match header.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= 255 => key.to_string(),
+        _ => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Idempotency-Key must be 1 to 255 visible ASCII characters"
+            })));
+        }
+    };
+    let config = idempotency_config
+        .map(|data| data.get_ref().clone())
+        .unwrap_or_default();
+    match db::create_order_idempotent(
+        pool.get_ref(),
+        body.into_inner(),
+        credit_check.as_ref(),
+        &client_id,
+        &key,
+        &config,
+    )
+    .await
+    {
+        Ok(IdempotentOrder::Created(order)) => Ok(HttpResponse::Created().json(order)),
+        Ok(IdempotentOrder::Existing(order)) => Ok(HttpResponse::Ok().json(order)),
+        Ok(IdempotentOrder::KeyMismatch) => {
+            Ok(HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "error": format!(
+                    "Idempotency-Key {} was already used for a different request",
+                    key
+                )
+            })))
+        }
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Map a TMF error to an HTTP response
+fn error_response(err: TmfError) -> HttpResponse {
+    let body = serde_json::json!({ "error": err.to_string() });
+    match err {
+        TmfError::NotFound(_) => HttpResponse::NotFound().json(body),
+        TmfError::Conflict(_) => HttpResponse::Conflict().json(body),
+        TmfError::BadRequest(_) | TmfError::Validation(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+/// Get all product order templates
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productOrderingManagement/v4/productOrderTemplate",
+    responses(
+        (status = 200, description = "List of order templates", body = Vec<ProductOrderTemplate>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF622"
+)]
+pub async fn get_order_templates(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_order(pool.get_ref(), body.into_inner()).await {
+    match db::get_templates(pool.get_ref()).await {
+        Ok(templates) => Ok(HttpResponse::Ok().json(templates)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Get product order template by ID
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productOrderingManagement/v4/productOrderTemplate/{id}",
+    responses(
+        (status = 200, description = "Order template found", body = ProductOrderTemplate),
+        (status = 404, description = "Order template not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Order Template ID (UUID)")
+    ),
+    tag = "TMF622"
+)]
+pub async fn get_order_template_by_id(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<Uuid>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::get_template_by_id(pool.get_ref(), path.into_inner()).await {
+        Ok(template) => Ok(HttpResponse::Ok().json(template)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Save an existing product order as a template
+#[utoipa::path(
+    post,
+    path = "/tmf-api/productOrderingManagement/v4/productOrderTemplate",
+    request_body = CreateOrderTemplateRequest,
+    responses(
+        (status = 201, description = "Order template created", body = ProductOrderTemplate),
+        (status = 404, description = "Source order not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF622"
+)]
+pub async fn create_order_template(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<CreateOrderTemplateRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::create_template(pool.get_ref(), body.into_inner()).await {
+        Ok(template) => Ok(HttpResponse::Created().json(template)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Create a new product order from a template
+#[utoipa::path(
+    post,
+    path = "/tmf-api/productOrderingManagement/v4/productOrderTemplate/{id}/productOrder",
+    request_body = OrderFromTemplateRequest,
+    responses(
+        (status = 201, description = "Product order created", body = ProductOrder),
+        (status = 400, description = "Invalid quantity override"),
+        (status = 404, description = "Order template not found"),
+        (status = 409, description = "Template is out of date with the catalog, or the ordering party is not KYC verified"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Order Template ID (UUID)")
+    ),
+    tag = "TMF622"
+)]
+pub async fn create_order_from_template(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<OrderFromTemplateRequest>,
+    registered_credit_check: Option<web::Data<Arc<dyn CreditCheck>>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let credit_check = credit_check(&registered_credit_check);
+    match db::create_order_from_template(
+        pool.get_ref(),
+        path.into_inner(),
+        body.into_inner(),
+        credit_check.as_ref(),
+    )
+    .await
+    {
         Ok(order) => Ok(HttpResponse::Created().json(order)),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e.to_string()
-        }))),
+        Err(e) => Ok(error_response(e)),
     }
 }
@@ -14,4 +14,7 @@ pub use handlers::*;
 pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
-pub use db::{get_order_by_id as db_get_order_by_id, get_orders as db_get_orders};
+pub use db::{
+    get_order_by_id as db_get_order_by_id, get_orders as db_get_orders,
+    get_recent_orders_by_customer as db_get_recent_orders_by_customer,
+};
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tmf629_customer::credit::CreditDecision;
 use tmf_apis_core::BaseEntity;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -43,6 +44,17 @@ pub struct ProductOrder {
     /// Priority
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<String>,
+    /// Ordering customer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
+    /// Party placing the order, KYC checked for regulated offerings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub party_id: Option<Uuid>,
+    /// Credit check decision taken when the order was created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit_decision: Option<CreditDecision>,
 }
 
 /// Order Item - Individual item within a product order
@@ -110,6 +122,15 @@ pub struct CreateProductOrderRequest {
     pub order_item: Option<Vec<CreateOrderItemRequest>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<CreateRelatedPartyRequest>>,
+    /// Ordering customer, passed to the credit check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
+    /// Party placing the order; required and KYC verified when ordering
+    /// offerings that require KYC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub party_id: Option<Uuid>,
 }
 
 /// Request to create an order item
@@ -132,3 +153,74 @@ pub struct CreateRelatedPartyRequest {
     pub name: String,
     pub role: String,
 }
+
+/// Product Order Template - Reusable order for repeat purchases
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProductOrderTemplate {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Order the template was saved from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub source_order_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
+    pub template_item: Vec<OrderTemplateItem>,
+    pub related_party: Vec<CreateRelatedPartyRequest>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Order Template Item - Order item pinned to the catalog version it was saved against
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderTemplateItem {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub product_offering_id: Option<Uuid>,
+    /// Product offering version when the template was saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_offering_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub product_specification_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<i32>,
+}
+
+/// Request to save an existing order as a template
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateOrderTemplateRequest {
+    #[schema(value_type = String, format = "uuid")]
+    pub order_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Request to place a new order from a template
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct OrderFromTemplateRequest {
+    /// Order name; defaults to the template name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Quantity overrides keyed by template item ID
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub quantity_overrides: std::collections::HashMap<Uuid, i32>,
+    /// Place the order even if referenced offerings changed version since the template was saved
+    #[serde(default)]
+    pub accept_catalog_changes: bool,
+    /// Party placing the order, for offerings that require KYC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub party_id: Option<Uuid>,
+}
@@ -12,6 +12,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_customers))
                     .route(web::post().to(create_customer)),
             )
-            .service(web::resource("/customer/{id}").route(web::get().to(get_customer_by_id))),
+            .service(
+                web::resource("/customer/{id}")
+                    .route(web::get().to(get_customer_by_id))
+                    .route(web::patch().to(patch_customer)),
+            ),
     );
 }
@@ -0,0 +1,59 @@
+//! Customer credit check hook
+//!
+//! Orders for customers are run through a [`CreditCheck`] before they are
+//! accepted. Deployments plug in their own risk assessment; the default
+//! [`ApproveAllCreditCheck`] approves everything.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tmf_apis_core::TmfResult;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Credit Decision - Outcome of a customer credit check
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+#[serde(tag = "decision", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CreditDecision {
+    Approved,
+    Declined {
+        reason: String,
+    },
+    DepositRequired {
+        amount: f64,
+        currency: String,
+        reason: String,
+    },
+}
+
+/// Credit Check Request - What is known about the customer and the order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreditCheckRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
+    /// Name of the related party with the customer role
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_name: Option<String>,
+    /// Product offerings being ordered
+    #[schema(value_type = Vec<String>)]
+    pub product_offering_ids: Vec<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+}
+
+/// Pluggable credit / risk check
+#[async_trait]
+pub trait CreditCheck: Send + Sync {
+    async fn check(&self, request: &CreditCheckRequest) -> TmfResult<CreditDecision>;
+}
+
+/// Default credit check that approves every order
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproveAllCreditCheck;
+
+#[async_trait]
+impl CreditCheck for ApproveAllCreditCheck {
+    async fn check(&self, _request: &CreditCheckRequest) -> TmfResult<CreditDecision> {
+        Ok(CreditDecision::Approved)
+    }
+}
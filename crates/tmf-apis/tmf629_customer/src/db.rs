@@ -1,6 +1,10 @@
 //! Database operations for TMF629 Customer Management
 
-use crate::models::{CreateCustomerRequest, Customer, CustomerState};
+use crate::models::{
+    ContactCharacteristic, ContactMedium, CreateCustomerRequest, Customer, CustomerState,
+    RelatedParty,
+};
+use crate::patch::{self, CustomerPatch};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -92,13 +96,176 @@ pub async fn get_customer_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Cu
         },
         state: parse_customer_state(&row.get::<String, _>("state")),
         status: row.get::<Option<String>, _>("status"),
-        contact_medium: None,
+        contact_medium: Some(get_contact_mediums(pool, id).await?),
         account: None,
-        related_party: None,
+        related_party: Some(get_related_parties(pool, id).await?),
         characteristic: None,
     })
 }
 
+/// Get the contact mediums of a customer
+async fn get_contact_mediums(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+) -> TmfResult<Vec<ContactMedium>> {
+    let rows = sqlx::query(
+        "SELECT id, medium_type, preferred, value, contact_type
+         FROM customer_contact_mediums WHERE customer_id = $1 ORDER BY created_at, id",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ContactMedium {
+            id: row.get("id"),
+            medium_type: row.get("medium_type"),
+            preferred: row.get::<Option<bool>, _>("preferred").unwrap_or(false),
+            characteristic: Some(ContactCharacteristic {
+                value: row.get("value"),
+                contact_type: row.get("contact_type"),
+            }),
+        })
+        .collect())
+}
+
+/// Get the related parties of a customer
+async fn get_related_parties(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+) -> TmfResult<Vec<RelatedParty>> {
+    let rows = sqlx::query(
+        "SELECT id, href, name, role
+         FROM customer_related_parties WHERE customer_id = $1 ORDER BY created_at, id",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RelatedParty {
+            id: row.get("id"),
+            href: row.get("href"),
+            name: row.get("name"),
+            role: row.get("role"),
+        })
+        .collect())
+}
+
+/// Apply a JSON Patch or merge patch to a stored customer
+///
+/// The patched customer replaces the stored one only if nobody updated the
+/// customer since it was read; a concurrent update is reported as a conflict.
+pub async fn patch_customer(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    customer_patch: &CustomerPatch,
+) -> TmfResult<Customer> {
+    let current = get_customer_by_id(pool, id).await?;
+    let patched = patch::apply_customer_patch(&current, customer_patch)?;
+    update_customer(pool, &patched, current.base.last_update).await?;
+    get_customer_by_id(pool, id).await
+}
+
+/// Replace a customer along with its contact mediums and related parties
+///
+/// `expected_last_update` guards against lost updates: the customer is only
+/// written if it was last updated at that time.
+async fn update_customer(
+    pool: &Pool<Postgres>,
+    customer: &Customer,
+    expected_last_update: Option<DateTime<Utc>>,
+) -> TmfResult<()> {
+    let id = customer.base.id;
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
+    let result = sqlx::query(
+        "UPDATE customers SET name = $2, description = $3, version = $4, state = $5,
+         status = $6, last_update = CURRENT_TIMESTAMP
+         WHERE id = $1 AND last_update IS NOT DISTINCT FROM $7",
+    )
+    .bind(id)
+    .bind(&customer.base.name)
+    .bind(&customer.base.description)
+    .bind(&customer.base.version)
+    .bind(customer_state_to_string(&customer.state))
+    .bind(&customer.status)
+    .bind(expected_last_update)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    if result.rows_affected() == 0 {
+        return Err(TmfError::Conflict(format!(
+            "Customer {} was modified concurrently; retry the patch",
+            id
+        )));
+    }
+
+    sqlx::query("DELETE FROM customer_contact_mediums WHERE customer_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    for contact in customer.contact_medium.iter().flatten() {
+        let characteristic = contact.characteristic.as_ref().ok_or_else(|| {
+            TmfError::Validation(format!("Contact medium {} has no value", contact.id))
+        })?;
+        let inserted = sqlx::query(
+            "INSERT INTO customer_contact_mediums (id, customer_id, medium_type, preferred, value, contact_type)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(contact.id)
+        .bind(id)
+        .bind(&contact.medium_type)
+        .bind(contact.preferred)
+        .bind(&characteristic.value)
+        .bind(&characteristic.contact_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+        if inserted.rows_affected() == 0 {
+            return Err(TmfError::Conflict(format!(
+                "Contact medium {} belongs to another customer",
+                contact.id
+            )));
+        }
+    }
+
+    sqlx::query("DELETE FROM customer_related_parties WHERE customer_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    for party in customer.related_party.iter().flatten() {
+        let inserted = sqlx::query(
+            "INSERT INTO customer_related_parties (id, customer_id, name, role, href)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(party.id)
+        .bind(id)
+        .bind(&party.name)
+        .bind(&party.role)
+        .bind(&party.href)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+        if inserted.rows_affected() == 0 {
+            return Err(TmfError::Conflict(format!(
+                "Related party {} belongs to another customer",
+                party.id
+            )));
+        }
+    }
+
+    tx.commit().await.map_err(map_sqlx_error)
+}
+
 /// Create a new customer
 pub async fn create_customer(
     pool: &Pool<Postgres>,
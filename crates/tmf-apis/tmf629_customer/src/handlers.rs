@@ -3,6 +3,7 @@
 use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
+use crate::patch;
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
 use tmf_apis_core::TmfError;
@@ -100,3 +101,99 @@ pub async fn create_customer(
         }))),
     }
 }
+
+/// Partially update a customer
+///
+/// Accepts an RFC 6902 JSON Patch (`application/json-patch+json`) or an
+/// RFC 7386 merge patch (`application/merge-patch+json`). Plain
+/// `application/json` is treated as a JSON Patch if it is an array and as a
+/// merge patch otherwise.
+#[utoipa::path(
+    patch,
+    path = "/tmf-api/customerManagement/v4/customer/{id}",
+    request_body(
+        content = Vec<PatchOperation>,
+        content_type = "application/json-patch+json",
+        description = "JSON Patch operations, or a merge patch sent as application/merge-patch+json"
+    ),
+    responses(
+        (status = 200, description = "Customer updated", body = Customer),
+        (status = 400, description = "Malformed patch or invalid customer ID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Customer not found"),
+        (status = 409, description = "Test operation failed or customer modified concurrently"),
+        (status = 415, description = "Unsupported patch content type"),
+        (status = 422, description = "Patch cannot be applied or leaves an invalid customer")
+    ),
+    params(
+        ("id" = String, Path, description = "Customer ID (UUID)")
+    ),
+    tag = "TMF629"
+)]
+pub async fn patch_customer(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Bytes,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid customer ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let customer_patch = match content_type.as_str() {
+        patch::JSON_PATCH_CONTENT_TYPE => patch::parse_json_patch(&body),
+        patch::MERGE_PATCH_CONTENT_TYPE => patch::parse_merge_patch(&body),
+        "application/json" => patch::parse_patch(&body),
+        _ => {
+            return Ok(
+                HttpResponse::UnsupportedMediaType().json(serde_json::json!({
+                    "error": format!(
+                        "Unsupported patch content type {:?}; use {} or {}",
+                        content_type,
+                        patch::JSON_PATCH_CONTENT_TYPE,
+                        patch::MERGE_PATCH_CONTENT_TYPE
+                    )
+                })),
+            );
+        }
+    };
+
+    let result = match customer_patch {
+        Ok(customer_patch) => db::patch_customer(pool.get_ref(), id, &customer_patch).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(customer) => Ok(HttpResponse::Ok().json(customer)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::BadRequest(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Conflict(msg)) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => {
+            Ok(HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "error": msg
+            })))
+        }
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
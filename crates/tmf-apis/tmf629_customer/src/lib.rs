@@ -6,11 +6,14 @@
 
 pub mod api;
 pub mod auth;
+pub mod credit;
 pub mod db;
 pub mod handlers;
 pub mod models;
+pub mod patch;
 
 pub use auth::*;
+pub use credit::*;
 pub use handlers::*;
 pub use models::*;
 
@@ -0,0 +1,591 @@
+//! Partial customer updates with JSON Patch and JSON Merge Patch
+//!
+//! PATCH requests carry either RFC 6902 JSON Patch operations or an RFC 7386
+//! merge patch. Either is applied to the stored customer as a JSON document,
+//! and the result is checked against the customer model before it is saved.
+//!
+//! Errors follow RFC 5789: malformed patches are bad requests, patches that
+//! cannot be applied or would leave an invalid customer are validation errors
+//! (422), and failed `test` operations are conflicts.
+
+use crate::models::Customer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use tmf_apis_core::{TmfError, TmfResult};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Content type of RFC 6902 JSON Patch documents
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+/// Content type of RFC 7386 JSON Merge Patch documents
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// Fields a customer cannot be without
+const REQUIRED_FIELDS: &[&str] = &["id", "name", "state", "lifecycle_status"];
+/// Fields each contact medium cannot be without
+const REQUIRED_CONTACT_FIELDS: &[&str] = &["medium_type", "characteristic/value"];
+/// Fields kept by the server, which a patch must leave unchanged
+const READ_ONLY_FIELDS: &[&str] = &["id", "href", "last_update"];
+/// Fields returned with the customer but not stored, which a patch must leave unchanged
+const UNSTORED_FIELDS: &[&str] = &["lifecycle_status", "valid_for"];
+/// Fields each related party cannot be without
+const REQUIRED_RELATED_PARTY_FIELDS: &[&str] = &["name", "role"];
+
+/// JSON Patch Operation - A single RFC 6902 operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// A partial customer update
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomerPatch {
+    /// RFC 6902 operations, applied in order
+    Json(Vec<PatchOperation>),
+    /// RFC 7386 merge patch
+    Merge(Value),
+}
+
+/// Parse an RFC 6902 JSON Patch document
+pub fn parse_json_patch(body: &[u8]) -> TmfResult<CustomerPatch> {
+    serde_json::from_slice(body)
+        .map(CustomerPatch::Json)
+        .map_err(|e| TmfError::BadRequest(format!("Invalid JSON Patch document: {}", e)))
+}
+
+/// Parse an RFC 7386 merge patch document
+pub fn parse_merge_patch(body: &[u8]) -> TmfResult<CustomerPatch> {
+    serde_json::from_slice(body)
+        .map(CustomerPatch::Merge)
+        .map_err(|e| TmfError::BadRequest(format!("Invalid merge patch document: {}", e)))
+}
+
+/// Parse a patch sent as plain JSON: an array is a JSON Patch, anything else
+/// a merge patch
+pub fn parse_patch(body: &[u8]) -> TmfResult<CustomerPatch> {
+    let value: Value = serde_json::from_slice(body)
+        .map_err(|e| TmfError::BadRequest(format!("Invalid patch document: {}", e)))?;
+    if value.is_array() {
+        serde_json::from_value(value)
+            .map(CustomerPatch::Json)
+            .map_err(|e| TmfError::BadRequest(format!("Invalid JSON Patch document: {}", e)))
+    } else {
+        Ok(CustomerPatch::Merge(value))
+    }
+}
+
+/// Apply a patch to a customer, returning the patched customer
+///
+/// Read-only and unstored fields cannot change and required fields cannot be
+/// removed, including those of nested contact mediums and related parties. Contact
+/// mediums and related parties added without an id are given one.
+pub fn apply_customer_patch(customer: &Customer, patch: &CustomerPatch) -> TmfResult<Customer> {
+    let original = serde_json::to_value(customer).map_err(|e| TmfError::Internal(e.to_string()))?;
+    let mut doc = original.clone();
+    match patch {
+        CustomerPatch::Json(operations) => apply_json_patch(&mut doc, operations)?,
+        CustomerPatch::Merge(merge) => apply_merge_patch(&mut doc, merge),
+    }
+
+    check_required(&doc, "", REQUIRED_FIELDS)?;
+    for field in READ_ONLY_FIELDS {
+        if doc[field] != original[field] {
+            return Err(TmfError::Validation(format!(
+                "Customer {} is read-only and cannot be patched",
+                field
+            )));
+        }
+    }
+    for field in UNSTORED_FIELDS {
+        if doc[field] != original[field] {
+            return Err(TmfError::Validation(format!(
+                "Customer {} is not stored and cannot be patched",
+                field
+            )));
+        }
+    }
+    for field in ["account", "characteristic"] {
+        if !doc[field].is_null() {
+            return Err(TmfError::Validation(format!(
+                "Customer {} is not stored and cannot be patched",
+                field
+            )));
+        }
+    }
+    prepare_items(&mut doc, "contact_medium", REQUIRED_CONTACT_FIELDS)?;
+    prepare_items(&mut doc, "related_party", REQUIRED_RELATED_PARTY_FIELDS)?;
+
+    serde_json::from_value(doc)
+        .map_err(|e| TmfError::Validation(format!("Patched customer is invalid: {}", e)))
+}
+
+/// Fail if any required field of `doc` is missing or null, naming the field
+/// by its path under `base`
+fn check_required(doc: &Value, base: &str, fields: &[&str]) -> TmfResult<()> {
+    for field in fields {
+        if doc
+            .pointer(&format!("/{}", field))
+            .is_none_or(Value::is_null)
+        {
+            return Err(TmfError::Validation(format!(
+                "Patch would remove required field {}/{}",
+                base, field
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check the items of a nested array and give new items an id
+fn prepare_items(doc: &mut Value, field: &str, required: &[&str]) -> TmfResult<()> {
+    let Some(items) = doc.get_mut(field) else {
+        return Ok(());
+    };
+    if items.is_null() {
+        return Ok(());
+    }
+    let Some(items) = items.as_array_mut() else {
+        return Err(TmfError::Validation(format!("/{} must be an array", field)));
+    };
+
+    let mut ids = HashSet::new();
+    for (index, item) in items.iter_mut().enumerate() {
+        let base = format!("/{}/{}", field, index);
+        let Some(object) = item.as_object_mut() else {
+            return Err(TmfError::Validation(format!("{} must be an object", base)));
+        };
+        if object.get("id").is_none_or(Value::is_null) {
+            object.insert("id".to_string(), Value::String(Uuid::new_v4().to_string()));
+        }
+        if !ids.insert(object["id"].to_string()) {
+            return Err(TmfError::Validation(format!(
+                "{}/id duplicates the id of another item",
+                base
+            )));
+        }
+        check_required(item, &base, required)?;
+    }
+    Ok(())
+}
+
+/// Apply RFC 6902 operations in order, stopping at the first failure
+pub fn apply_json_patch(doc: &mut Value, operations: &[PatchOperation]) -> TmfResult<()> {
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(doc, operation).map_err(|e| match e {
+            TmfError::BadRequest(msg) => {
+                TmfError::BadRequest(format!("Patch operation {}: {}", index, msg))
+            }
+            TmfError::Conflict(msg) => {
+                TmfError::Conflict(format!("Patch operation {}: {}", index, msg))
+            }
+            TmfError::Validation(msg) => {
+                TmfError::Validation(format!("Patch operation {}: {}", index, msg))
+            }
+            other => other,
+        })?;
+    }
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, operation: &PatchOperation) -> TmfResult<()> {
+    match operation {
+        PatchOperation::Add { path, value } => add(doc, path, value.clone()),
+        PatchOperation::Remove { path } => remove(doc, path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            check_pointer(path)?;
+            let target = doc.pointer_mut(path).ok_or_else(|| path_not_found(path))?;
+            *target = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            check_pointer(from)?;
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(TmfError::BadRequest(format!(
+                    "Cannot move {} into its own child {}",
+                    from, path
+                )));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            check_pointer(from)?;
+            let value = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| path_not_found(from))?;
+            add(doc, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            check_pointer(path)?;
+            match doc.pointer(path) {
+                Some(actual) if actual == value => Ok(()),
+                Some(actual) => Err(TmfError::Conflict(format!(
+                    "Test failed: {} is {}, expected {}",
+                    path, actual, value
+                ))),
+                None => Err(TmfError::Conflict(format!(
+                    "Test failed: {} does not exist",
+                    path
+                ))),
+            }
+        }
+    }
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> TmfResult<()> {
+    let Some((parent, token)) = split_pointer(path)? else {
+        *doc = value;
+        return Ok(());
+    };
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(token, value);
+            Ok(())
+        }
+        Some(Value::Array(items)) => {
+            let index = if token == "-" {
+                items.len()
+            } else {
+                array_index(&token, path)?
+            };
+            if index > items.len() {
+                return Err(TmfError::Validation(format!(
+                    "Index {} is out of bounds",
+                    path
+                )));
+            }
+            items.insert(index, value);
+            Ok(())
+        }
+        Some(_) => Err(TmfError::Validation(format!(
+            "Cannot add {}: parent is not an object or array",
+            path
+        ))),
+        None => Err(path_not_found(parent)),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> TmfResult<Value> {
+    let Some((parent, token)) = split_pointer(path)? else {
+        return Err(TmfError::Validation(
+            "Cannot remove the whole customer".to_string(),
+        ));
+    };
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => map.remove(&token).ok_or_else(|| path_not_found(path)),
+        Some(Value::Array(items)) => {
+            let index = array_index(&token, path)?;
+            if index >= items.len() {
+                return Err(path_not_found(path));
+            }
+            Ok(items.remove(index))
+        }
+        _ => Err(path_not_found(path)),
+    }
+}
+
+fn check_pointer(pointer: &str) -> TmfResult<()> {
+    if pointer.is_empty() || pointer.starts_with('/') {
+        Ok(())
+    } else {
+        Err(TmfError::BadRequest(format!(
+            "Invalid JSON pointer {:?}: must be empty or start with /",
+            pointer
+        )))
+    }
+}
+
+/// Split a JSON pointer into its parent pointer and unescaped last token,
+/// or `None` for the whole document
+fn split_pointer(pointer: &str) -> TmfResult<Option<(&str, String)>> {
+    check_pointer(pointer)?;
+    Ok(pointer
+        .rsplit_once('/')
+        .map(|(parent, token)| (parent, token.replace("~1", "/").replace("~0", "~"))))
+}
+
+fn array_index(token: &str, path: &str) -> TmfResult<usize> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    if !valid {
+        return Err(TmfError::Validation(format!(
+            "{} is not a valid array index",
+            path
+        )));
+    }
+    token
+        .parse()
+        .map_err(|_| TmfError::Validation(format!("{} is not a valid array index", path)))
+}
+
+fn path_not_found(path: &str) -> TmfError {
+    TmfError::Validation(format!("Path {} does not exist", path))
+}
+
+/// Apply an RFC 7386 merge patch: objects merge recursively, null removes a
+/// member and any other value replaces the target
+pub fn apply_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+            } else {
+                apply_merge_patch(map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ContactCharacteristic, ContactMedium, CustomerState};
+    use serde_json::json;
+    use tmf_apis_core::{BaseEntity, LifecycleStatus};
+
+    fn contact(medium_type: &str, value: &str) -> ContactMedium {
+        ContactMedium {
+            id: Uuid::new_v4(),
+            medium_type: medium_type.to_string(),
+            preferred: false,
+            characteristic: Some(ContactCharacteristic {
+                value: value.to_string(),
+                contact_type: None,
+            }),
+        }
+    }
+
+    fn customer() -> Customer {
+        Customer {
+            base: BaseEntity {
+                id: Uuid::new_v4(),
+                href: None,
+                name: "Acme".to_string(),
+                description: None,
+                version: None,
+                lifecycle_status: LifecycleStatus::Active,
+                valid_for: None,
+                last_update: None,
+            },
+            state: CustomerState::Active,
+            status: None,
+            contact_medium: Some(vec![
+                contact("email", "ops@acme.test"),
+                contact("phone", "+15550100"),
+            ]),
+            account: None,
+            related_party: None,
+            characteristic: None,
+        }
+    }
+
+    fn json_patch(operations: Value) -> CustomerPatch {
+        CustomerPatch::Json(serde_json::from_value(operations).unwrap())
+    }
+
+    fn contact_values(customer: &Customer) -> Vec<String> {
+        customer
+            .contact_medium
+            .iter()
+            .flatten()
+            .map(|c| c.characteristic.as_ref().unwrap().value.clone())
+            .collect()
+    }
+
+    #[test]
+    fn adds_contact_medium_to_nested_array() {
+        let patch = json_patch(json!([
+            {"op": "add", "path": "/contact_medium/-", "value": {
+                "medium_type": "sms", "characteristic": {"value": "+15550199"}
+            }},
+            {"op": "add", "path": "/contact_medium/0", "value": {
+                "medium_type": "fax", "characteristic": {"value": "+15550111"}
+            }}
+        ]));
+
+        let patched = apply_customer_patch(&customer(), &patch).unwrap();
+
+        assert_eq!(
+            contact_values(&patched),
+            ["+15550111", "ops@acme.test", "+15550100", "+15550199"]
+        );
+        let ids: HashSet<Uuid> = patched
+            .contact_medium
+            .iter()
+            .flatten()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn removes_contact_medium_from_nested_array() {
+        let original = customer();
+        let patch = json_patch(json!([{"op": "remove", "path": "/contact_medium/0"}]));
+
+        let patched = apply_customer_patch(&original, &patch).unwrap();
+
+        assert_eq!(contact_values(&patched), ["+15550100"]);
+        assert_eq!(
+            patched.contact_medium.unwrap()[0].id,
+            original.contact_medium.unwrap()[1].id
+        );
+    }
+
+    #[test]
+    fn replaces_nested_contact_value() {
+        let patch = json_patch(json!([
+            {"op": "replace", "path": "/contact_medium/1/characteristic/value", "value": "+15550123"},
+            {"op": "replace", "path": "/contact_medium/1/preferred", "value": true}
+        ]));
+
+        let patched = apply_customer_patch(&customer(), &patch).unwrap();
+
+        assert_eq!(contact_values(&patched), ["ops@acme.test", "+15550123"]);
+        assert!(patched.contact_medium.unwrap()[1].preferred);
+    }
+
+    #[test]
+    fn rejects_removing_required_fields() {
+        let nested = json_patch(json!([
+            {"op": "remove", "path": "/contact_medium/0/medium_type"}
+        ]));
+        let err = apply_customer_patch(&customer(), &nested).unwrap_err();
+        assert!(
+            matches!(&err, TmfError::Validation(msg) if msg.contains("/contact_medium/0/medium_type")),
+            "{:?}",
+            err
+        );
+
+        let top_level = CustomerPatch::Merge(json!({"name": null}));
+        let err = apply_customer_patch(&customer(), &top_level).unwrap_err();
+        assert!(
+            matches!(&err, TmfError::Validation(msg) if msg.contains("/name")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_and_missing_paths() {
+        let patch = json_patch(json!([{"op": "remove", "path": "/contact_medium/5"}]));
+        assert!(matches!(
+            apply_customer_patch(&customer(), &patch),
+            Err(TmfError::Validation(_))
+        ));
+
+        let patch = json_patch(json!([{"op": "replace", "path": "contact_medium", "value": []}]));
+        assert!(matches!(
+            apply_customer_patch(&customer(), &patch),
+            Err(TmfError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn failed_test_operation_is_a_conflict_and_changes_nothing() {
+        let original = customer();
+        let patch = json_patch(json!([
+            {"op": "remove", "path": "/contact_medium/0"},
+            {"op": "test", "path": "/name", "value": "Globex"}
+        ]));
+
+        assert!(matches!(
+            apply_customer_patch(&original, &patch),
+            Err(TmfError::Conflict(_))
+        ));
+        assert_eq!(contact_values(&original).len(), 2);
+    }
+
+    #[test]
+    fn rejects_changing_the_id() {
+        let patch = json_patch(json!([
+            {"op": "replace", "path": "/id", "value": Uuid::new_v4()}
+        ]));
+        assert!(matches!(
+            apply_customer_patch(&customer(), &patch),
+            Err(TmfError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_changing_read_only_and_unstored_fields() {
+        for patch in [
+            json_patch(json!([{"op": "add", "path": "/href", "value": "/customer/other"}])),
+            json_patch(json!([{"op": "replace", "path": "/lifecycle_status", "value": "Retired"}])),
+            CustomerPatch::Merge(json!({"valid_for": {"start_date_time": "2026-01-01T00:00:00Z"}})),
+            CustomerPatch::Merge(json!({"last_update": "2026-01-01T00:00:00Z"})),
+        ] {
+            assert!(
+                matches!(
+                    apply_customer_patch(&customer(), &patch),
+                    Err(TmfError::Validation(_))
+                ),
+                "{:?}",
+                patch
+            );
+        }
+    }
+
+    #[test]
+    fn merge_patch_replaces_arrays_and_removes_nulls() {
+        let mut original = customer();
+        original.status = Some("Gold".to_string());
+        let patch = CustomerPatch::Merge(json!({
+            "status": null,
+            "description": "Key account",
+            "contact_medium": [{"medium_type": "email", "characteristic": {"value": "cfo@acme.test"}}]
+        }));
+
+        let patched = apply_customer_patch(&original, &patch).unwrap();
+
+        assert_eq!(patched.status, None);
+        assert_eq!(patched.base.description.as_deref(), Some("Key account"));
+        assert_eq!(contact_values(&patched), ["cfo@acme.test"]);
+    }
+
+    #[test]
+    fn move_and_copy_within_contact_array() {
+        let patch = json_patch(json!([
+            {"op": "move", "from": "/contact_medium/1", "path": "/contact_medium/0"},
+            {"op": "copy", "from": "/contact_medium/0/medium_type", "path": "/contact_medium/1/medium_type"}
+        ]));
+
+        let patched = apply_customer_patch(&customer(), &patch).unwrap();
+
+        assert_eq!(contact_values(&patched), ["+15550100", "ops@acme.test"]);
+        let contacts = patched.contact_medium.unwrap();
+        assert_eq!(contacts[1].medium_type, "phone");
+    }
+
+    #[test]
+    fn plain_json_is_parsed_by_shape() {
+        assert!(matches!(
+            parse_patch(br#"[{"op": "remove", "path": "/status"}]"#),
+            Ok(CustomerPatch::Json(_))
+        ));
+        assert!(matches!(
+            parse_patch(br#"{"status": "Gold"}"#),
+            Ok(CustomerPatch::Merge(_))
+        ));
+        assert!(matches!(
+            parse_json_patch(br#"[{"op": "frobnicate", "path": "/status"}]"#),
+            Err(TmfError::BadRequest(_))
+        ));
+    }
+}
@@ -12,6 +12,11 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_parties))
                     .route(web::post().to(create_party)),
             )
-            .service(web::resource("/party/{id}").route(web::get().to(get_party_by_id))),
+            .service(web::resource("/party/{id}").route(web::get().to(get_party_by_id)))
+            .service(
+                web::resource("/party/{id}/kycVerification")
+                    .route(web::get().to(get_kyc_verifications))
+                    .route(web::post().to(create_kyc_verification)),
+            ),
     );
 }
@@ -1,6 +1,6 @@
 //! Database operations for TMF632 Party Management
 
-use crate::models::{CreatePartyRequest, Party, PartyState, PartyType};
+use crate::models::{CreatePartyRequest, KycStatus, Party, PartyState, PartyType};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -51,11 +51,29 @@ fn party_type_to_string(party_type: &PartyType) -> String {
     }
 }
 
+/// Parse KYC status from database string
+pub(crate) fn parse_kyc_status(s: &str) -> KycStatus {
+    match s.to_uppercase().as_str() {
+        "VERIFIED" => KycStatus::Verified,
+        "REJECTED" => KycStatus::Rejected,
+        _ => KycStatus::Pending,
+    }
+}
+
+/// Convert KYC status to database string
+pub(crate) fn kyc_status_to_string(status: &KycStatus) -> String {
+    match status {
+        KycStatus::Pending => "PENDING".to_string(),
+        KycStatus::Verified => "VERIFIED".to_string(),
+        KycStatus::Rejected => "REJECTED".to_string(),
+    }
+}
+
 /// Get all parties
 pub async fn get_parties(pool: &Pool<Postgres>) -> TmfResult<Vec<Party>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, party_type, registration_date, 
-         href, last_update
+         href, last_update, kyc_status, kyc_verified_by, kyc_verified_at, kyc_review_due
          FROM parties ORDER BY name",
     )
     .fetch_all(pool)
@@ -82,6 +100,10 @@ pub async fn get_parties(pool: &Pool<Postgres>) -> TmfResult<Vec<Party>> {
             account: None,        // Load separately if needed
             characteristic: None, // Load separately if needed
             registration_date: row.get::<Option<DateTime<Utc>>, _>("registration_date"),
+            kyc_status: parse_kyc_status(&row.get::<String, _>("kyc_status")),
+            kyc_verified_by: row.get::<Option<String>, _>("kyc_verified_by"),
+            kyc_verified_at: row.get::<Option<DateTime<Utc>>, _>("kyc_verified_at"),
+            kyc_review_due: row.get::<Option<DateTime<Utc>>, _>("kyc_review_due"),
         });
     }
 
@@ -92,7 +114,7 @@ pub async fn get_parties(pool: &Pool<Postgres>) -> TmfResult<Vec<Party>> {
 pub async fn get_party_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Party> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, party_type, registration_date, 
-         href, last_update
+         href, last_update, kyc_status, kyc_verified_by, kyc_verified_at, kyc_review_due
          FROM parties WHERE id = $1",
     )
     .bind(id)
@@ -119,6 +141,10 @@ pub async fn get_party_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Party
         account: None,
         characteristic: None,
         registration_date: row.get::<Option<DateTime<Utc>>, _>("registration_date"),
+        kyc_status: parse_kyc_status(&row.get::<String, _>("kyc_status")),
+        kyc_verified_by: row.get::<Option<String>, _>("kyc_verified_by"),
+        kyc_verified_at: row.get::<Option<DateTime<Utc>>, _>("kyc_verified_at"),
+        kyc_review_due: row.get::<Option<DateTime<Utc>>, _>("kyc_review_due"),
     })
 }
 
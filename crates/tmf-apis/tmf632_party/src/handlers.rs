@@ -2,12 +2,33 @@
 
 use crate::auth::validate_token;
 use crate::db;
+use crate::kyc::{self, KycConfig, KycVerifier, ManualKycVerifier};
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
+/// KYC verifier registered as app data, or the manual-review default
+fn kyc_verifier(registered: &Option<web::Data<Arc<dyn KycVerifier>>>) -> Arc<dyn KycVerifier> {
+    registered
+        .as_ref()
+        .map(|data| Arc::clone(data.get_ref()))
+        .unwrap_or_else(|| Arc::new(ManualKycVerifier))
+}
+
+/// Map a TMF error to an HTTP response
+fn error_response(err: TmfError) -> HttpResponse {
+    let body = serde_json::json!({ "error": err.to_string() });
+    match err {
+        TmfError::NotFound(_) => HttpResponse::NotFound().json(body),
+        TmfError::Conflict(_) => HttpResponse::Conflict().json(body),
+        TmfError::BadRequest(_) | TmfError::Validation(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
 /// Get all parties
 #[utoipa::path(
     get,
@@ -100,3 +121,93 @@ pub async fn create_party(
         }))),
     }
 }
+
+/// Submit KYC evidence for a party
+#[utoipa::path(
+    post,
+    path = "/tmf-api/partyManagement/v4/party/{id}/kycVerification",
+    request_body = KycVerificationRequest,
+    responses(
+        (status = 201, description = "KYC decision recorded", body = KycVerification),
+        (status = 400, description = "Invalid request or missing evidence"),
+        (status = 404, description = "Party not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Party ID (UUID)")
+    ),
+    tag = "TMF632"
+)]
+pub async fn create_kyc_verification(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<KycVerificationRequest>,
+    registered_verifier: Option<web::Data<Arc<dyn KycVerifier>>>,
+    registered_config: Option<web::Data<KycConfig>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid party ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let verifier = kyc_verifier(&registered_verifier);
+    let config = registered_config
+        .map(|data| data.get_ref().clone())
+        .unwrap_or_default();
+    match kyc::verify_party(
+        pool.get_ref(),
+        verifier.as_ref(),
+        &config,
+        id,
+        body.into_inner(),
+    )
+    .await
+    {
+        Ok(verification) => Ok(HttpResponse::Created().json(verification)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Get the KYC verification history of a party
+#[utoipa::path(
+    get,
+    path = "/tmf-api/partyManagement/v4/party/{id}/kycVerification",
+    responses(
+        (status = 200, description = "KYC verifications, latest first", body = Vec<KycVerification>),
+        (status = 404, description = "Party not found"),
+        (status = 400, description = "Invalid party ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Party ID (UUID)")
+    ),
+    tag = "TMF632"
+)]
+pub async fn get_kyc_verifications(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid party ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match kyc::get_kyc_verifications(pool.get_ref(), id).await {
+        Ok(verifications) => Ok(HttpResponse::Ok().json(verifications)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
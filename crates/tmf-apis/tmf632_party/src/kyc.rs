@@ -0,0 +1,276 @@
+//! KYC verification workflow for TMF632
+//!
+//! Parties start out pending KYC. Evidence submitted for a party is run
+//! through a pluggable [`KycVerifier`], which verifies or rejects it; the
+//! default [`ManualKycVerifier`] records the decision of a human reviewer.
+//! Verified parties are due for review after the configured interval, when
+//! the review task returns them to pending. Operations on regulated products
+//! call [`require_verified`] before going ahead.
+
+use crate::db::{self, kyc_status_to_string, parse_kyc_status};
+use crate::models::{KycDecision, KycStatus, KycVerification, KycVerificationRequest, Party};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use sqlx::{PgPool, Pool, Postgres, Row};
+use std::time::Duration;
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+fn map_sqlx_error(err: sqlx::Error) -> TmfError {
+    TmfError::Database(err.to_string())
+}
+
+/// Pluggable KYC verification, e.g. backed by an identity provider
+#[async_trait]
+pub trait KycVerifier: Send + Sync {
+    async fn verify(
+        &self,
+        party: &Party,
+        request: &KycVerificationRequest,
+    ) -> TmfResult<KycDecision>;
+}
+
+/// Default verifier recording the decision of a manual review
+///
+/// Requests without a decision only record the evidence and leave the party
+/// pending; a decision must name who made it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ManualKycVerifier;
+
+#[async_trait]
+impl KycVerifier for ManualKycVerifier {
+    async fn verify(
+        &self,
+        _party: &Party,
+        request: &KycVerificationRequest,
+    ) -> TmfResult<KycDecision> {
+        let status = request.decision.unwrap_or(KycStatus::Pending);
+        let verified_by = request
+            .verified_by
+            .as_ref()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        if status != KycStatus::Pending && verified_by.is_none() {
+            return Err(TmfError::Validation(
+                "A manual KYC decision must name the verifier".to_string(),
+            ));
+        }
+        Ok(KycDecision {
+            status,
+            verified_by,
+            reason: request.reason.clone(),
+        })
+    }
+}
+
+/// KYC workflow settings
+#[derive(Debug, Clone)]
+pub struct KycConfig {
+    /// How long a verification stays valid before the party is re-verified
+    pub reverification_interval: chrono::Duration,
+}
+
+impl Default for KycConfig {
+    fn default() -> Self {
+        Self {
+            reverification_interval: chrono::Duration::days(365),
+        }
+    }
+}
+
+/// Run submitted evidence through the verifier and record its decision
+///
+/// The decision is kept in the party's verification history and becomes the
+/// party's KYC status. Verified parties are due for review after the
+/// configured re-verification interval.
+pub async fn verify_party(
+    pool: &Pool<Postgres>,
+    verifier: &dyn KycVerifier,
+    config: &KycConfig,
+    party_id: Uuid,
+    request: KycVerificationRequest,
+) -> TmfResult<KycVerification> {
+    if request.evidence.is_empty() {
+        return Err(TmfError::Validation(
+            "KYC verification requires at least one evidence reference".to_string(),
+        ));
+    }
+    if request
+        .evidence
+        .iter()
+        .any(|e| e.reference.trim().is_empty())
+    {
+        return Err(TmfError::Validation(
+            "KYC evidence references must not be empty".to_string(),
+        ));
+    }
+
+    let party = db::get_party_by_id(pool, party_id).await?;
+    let decision = verifier.verify(&party, &request).await?;
+
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let decided_at = (decision.status != KycStatus::Pending).then_some(now);
+    let review_due =
+        (decision.status == KycStatus::Verified).then(|| now + config.reverification_interval);
+    let status = kyc_status_to_string(&decision.status);
+    let evidence_json =
+        serde_json::to_value(&request.evidence).map_err(|e| TmfError::Internal(e.to_string()))?;
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    sqlx::query(
+        "INSERT INTO party_kyc_verifications (id, party_id, status, evidence, verified_by,
+         reason, verified_at, review_due)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(id)
+    .bind(party_id)
+    .bind(&status)
+    .bind(&evidence_json)
+    .bind(&decision.verified_by)
+    .bind(&decision.reason)
+    .bind(now)
+    .bind(review_due)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    sqlx::query(
+        "UPDATE parties SET kyc_status = $2, kyc_verified_by = $3, kyc_verified_at = $4,
+         kyc_review_due = $5, last_update = $6
+         WHERE id = $1",
+    )
+    .bind(party_id)
+    .bind(&status)
+    .bind(&decision.verified_by)
+    .bind(decided_at)
+    .bind(review_due)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    info!(
+        "KYC for party {} is now {} (was {})",
+        party_id,
+        status,
+        kyc_status_to_string(&party.kyc_status)
+    );
+
+    Ok(KycVerification {
+        id,
+        party_id,
+        status: decision.status,
+        evidence: request.evidence,
+        verified_by: decision.verified_by,
+        reason: decision.reason,
+        verified_at: now,
+        review_due,
+    })
+}
+
+/// Get the KYC verification history of a party, latest first
+pub async fn get_kyc_verifications(
+    pool: &Pool<Postgres>,
+    party_id: Uuid,
+) -> TmfResult<Vec<KycVerification>> {
+    db::get_party_by_id(pool, party_id).await?;
+
+    let rows = sqlx::query(
+        "SELECT id, party_id, status, evidence, verified_by, reason, verified_at, review_due
+         FROM party_kyc_verifications WHERE party_id = $1
+         ORDER BY verified_at DESC",
+    )
+    .bind(party_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(KycVerification {
+                id: row.get("id"),
+                party_id: row.get("party_id"),
+                status: parse_kyc_status(&row.get::<String, _>("status")),
+                evidence: serde_json::from_value(row.get("evidence"))
+                    .map_err(|e| TmfError::Internal(e.to_string()))?,
+                verified_by: row.get("verified_by"),
+                reason: row.get("reason"),
+                verified_at: row.get("verified_at"),
+                review_due: row.get("review_due"),
+            })
+        })
+        .collect()
+}
+
+/// Fail with a conflict unless the party is KYC verified and not due for
+/// review
+pub async fn require_verified(pool: &Pool<Postgres>, party_id: Uuid) -> TmfResult<()> {
+    let row = sqlx::query("SELECT kyc_status, kyc_review_due FROM parties WHERE id = $1")
+        .bind(party_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or_else(|| TmfError::NotFound(format!("Party with id {} not found", party_id)))?;
+
+    check_verified(
+        party_id,
+        parse_kyc_status(&row.get::<String, _>("kyc_status")),
+        row.get("kyc_review_due"),
+        Utc::now(),
+    )
+}
+
+fn check_verified(
+    party_id: Uuid,
+    status: KycStatus,
+    review_due: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> TmfResult<()> {
+    match status {
+        KycStatus::Verified if review_due.is_some_and(|due| due <= now) => Err(TmfError::Conflict(
+            format!("Party {} is due for KYC re-verification", party_id),
+        )),
+        KycStatus::Verified => Ok(()),
+        KycStatus::Pending => Err(TmfError::Conflict(format!(
+            "Party {} has not completed KYC verification",
+            party_id
+        ))),
+        KycStatus::Rejected => Err(TmfError::Conflict(format!(
+            "Party {} failed KYC verification",
+            party_id
+        ))),
+    }
+}
+
+/// Return verified parties whose review is due to pending, returning how
+/// many were affected
+pub async fn expire_due_verifications(pool: &Pool<Postgres>, now: DateTime<Utc>) -> TmfResult<u64> {
+    let result = sqlx::query(
+        "UPDATE parties SET kyc_status = 'PENDING', last_update = $1
+         WHERE kyc_status = 'VERIFIED' AND kyc_review_due <= $1",
+    )
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Periodically send parties due for KYC review back to pending
+pub fn spawn_kyc_review(pool: PgPool, check_interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(check_interval);
+        loop {
+            interval.tick().await;
+            match expire_due_verifications(&pool, Utc::now()).await {
+                Ok(due) if due > 0 => info!("{} parties are due for KYC re-verification", due),
+                Ok(_) => {}
+                Err(e) => error!("KYC review failed: {}", e),
+            }
+        }
+    })
+}
@@ -7,6 +7,7 @@ pub mod api;
 pub mod auth;
 pub mod db;
 pub mod handlers;
+pub mod kyc;
 pub mod models;
 
 pub use auth::*;
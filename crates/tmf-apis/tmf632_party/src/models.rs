@@ -25,6 +25,15 @@ pub enum PartyType {
     Organization,
 }
 
+/// KYC Status - Know-your-customer verification of a party
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KycStatus {
+    Pending,
+    Verified,
+    Rejected,
+}
+
 /// Party - Represents an individual or organization
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Party {
@@ -50,6 +59,19 @@ pub struct Party {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(value_type = String, format = "date-time")]
     pub registration_date: Option<DateTime<Utc>>,
+    /// KYC verification status
+    pub kyc_status: KycStatus,
+    /// Who made the latest KYC decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kyc_verified_by: Option<String>,
+    /// When the latest KYC decision was made
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub kyc_verified_at: Option<DateTime<Utc>>,
+    /// When a verified party must be re-verified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub kyc_review_due: Option<DateTime<Utc>>,
 }
 
 /// Contact Medium
@@ -143,3 +165,60 @@ pub struct CreateCharacteristicRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value_type: Option<String>,
 }
+
+/// KYC Evidence - Reference to a document checked during verification
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KycEvidence {
+    /// Reference to the stored document (e.g. a document management URI)
+    pub reference: String,
+    /// Kind of document (e.g. passport, certificate of incorporation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_type: Option<String>,
+}
+
+/// Request to verify a party's KYC
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KycVerificationRequest {
+    /// Evidence submitted for verification
+    pub evidence: Vec<KycEvidence>,
+    /// Who is verifying the party
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_by: Option<String>,
+    /// Decision of a manual review; left to the verifier when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<KycStatus>,
+    /// Reason for the decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// KYC Decision - Outcome of a KYC verifier
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KycDecision {
+    pub status: KycStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// KYC Verification - A recorded KYC decision and the evidence it was based on
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct KycVerification {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub party_id: Uuid,
+    pub status: KycStatus,
+    pub evidence: Vec<KycEvidence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub verified_at: DateTime<Utc>,
+    /// When the party must be re-verified, for verified parties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub review_due: Option<DateTime<Utc>>,
+}
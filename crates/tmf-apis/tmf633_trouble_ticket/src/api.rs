@@ -12,11 +12,23 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_trouble_tickets))
                     .route(web::post().to(create_trouble_ticket)),
             )
+            .service(
+                web::resource("/troubleTicket/fromAlarm")
+                    .route(web::post().to(create_trouble_ticket_from_alarm)),
+            )
             .service(
                 web::resource("/troubleTicket/{id}")
                     .route(web::get().to(get_trouble_ticket_by_id))
                     .route(web::patch().to(update_trouble_ticket))
                     .route(web::delete().to(delete_trouble_ticket)),
+            )
+            .service(
+                web::resource("/troubleTicket/{id}/link")
+                    .route(web::post().to(link_trouble_ticket)),
+            )
+            .service(
+                web::resource("/troubleTicket/{id}/relatedEntities")
+                    .route(web::get().to(get_trouble_ticket_related_entities)),
             ),
     );
 }
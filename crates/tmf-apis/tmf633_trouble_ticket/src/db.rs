@@ -1,7 +1,8 @@
 //! Database operations for TMF633 Trouble Ticket Management
 
 use crate::models::{
-    CreateTroubleTicketRequest, TroubleTicket, TroubleTicketPriority, TroubleTicketStatus,
+    CreateTroubleTicketFromAlarmRequest, CreateTroubleTicketRequest, LinkTroubleTicketRequest,
+    TroubleTicket, TroubleTicketPriority, TroubleTicketRelatedEntities, TroubleTicketStatus,
     TroubleTicketType, UpdateTroubleTicketRequest,
 };
 use chrono::{DateTime, Utc};
@@ -112,9 +113,46 @@ fn row_to_trouble_ticket(row: &sqlx::postgres::PgRow) -> TroubleTicket {
         customer_id: row.get("customer_id"),
         assigned_to: row.get("assigned_to"),
         tenant_id: row.get("tenant_id"),
+        triggering_alarm_ids: Vec::new(),
+        affected_order_ids: Vec::new(),
     }
 }
 
+/// Fill in the alarms and orders linked to each ticket
+async fn load_links(pool: &Pool<Postgres>, tickets: &mut [TroubleTicket]) -> TmfResult<()> {
+    if tickets.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<Uuid> = tickets.iter().map(|t| t.base.id).collect();
+
+    let rows = sqlx::query(
+        "SELECT ticket_id, alarm_id AS related_id, 'ALARM' AS kind
+         FROM trouble_ticket_alarms WHERE ticket_id = ANY($1)
+         UNION ALL
+         SELECT ticket_id, order_id AS related_id, 'ORDER' AS kind
+         FROM trouble_ticket_orders WHERE ticket_id = ANY($1)
+         ORDER BY ticket_id, kind, related_id",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    for row in rows {
+        let ticket_id: Uuid = row.get("ticket_id");
+        let related_id: Uuid = row.get("related_id");
+        let Some(ticket) = tickets.iter_mut().find(|t| t.base.id == ticket_id) else {
+            continue;
+        };
+        match row.get::<&str, _>("kind") {
+            "ALARM" => ticket.triggering_alarm_ids.push(related_id),
+            _ => ticket.affected_order_ids.push(related_id),
+        }
+    }
+
+    Ok(())
+}
+
 /// Get all trouble tickets
 pub async fn get_trouble_tickets(pool: &Pool<Postgres>) -> TmfResult<Vec<TroubleTicket>> {
     let rows = sqlx::query(
@@ -127,7 +165,81 @@ pub async fn get_trouble_tickets(pool: &Pool<Postgres>) -> TmfResult<Vec<Trouble
     .await
     .map_err(map_sqlx_error)?;
 
-    Ok(rows.iter().map(row_to_trouble_ticket).collect())
+    let mut tickets: Vec<TroubleTicket> = rows.iter().map(row_to_trouble_ticket).collect();
+    load_links(pool, &mut tickets).await?;
+    Ok(tickets)
+}
+
+/// Get the trouble tickets of a customer that are not resolved, closed or
+/// cancelled, newest first
+pub async fn get_open_trouble_tickets_by_customer(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+) -> TmfResult<Vec<TroubleTicket>> {
+    let rows = sqlx::query(
+        "SELECT id, href, name, description, version, status, priority, ticket_type, 
+         description, resolution, resolution_date, related_entity, customer_id, 
+         assigned_to, tenant_id, last_update
+         FROM trouble_tickets
+         WHERE customer_id = $1 AND status NOT IN ('RESOLVED', 'CLOSED', 'CANCELLED')
+         ORDER BY created_at DESC",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut tickets: Vec<TroubleTicket> = rows.iter().map(row_to_trouble_ticket).collect();
+    load_links(pool, &mut tickets).await?;
+    Ok(tickets)
+}
+
+/// Get the trouble tickets triggered by an alarm, newest first
+pub async fn get_trouble_tickets_by_alarm(
+    pool: &Pool<Postgres>,
+    alarm_id: Uuid,
+) -> TmfResult<Vec<TroubleTicket>> {
+    let rows = sqlx::query(
+        "SELECT t.id, t.href, t.name, t.description, t.version, t.status, t.priority,
+         t.ticket_type, t.resolution, t.resolution_date, t.related_entity, t.customer_id,
+         t.assigned_to, t.tenant_id, t.last_update
+         FROM trouble_tickets t
+         JOIN trouble_ticket_alarms l ON l.ticket_id = t.id
+         WHERE l.alarm_id = $1
+         ORDER BY t.created_at DESC",
+    )
+    .bind(alarm_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut tickets: Vec<TroubleTicket> = rows.iter().map(row_to_trouble_ticket).collect();
+    load_links(pool, &mut tickets).await?;
+    Ok(tickets)
+}
+
+/// Get the trouble tickets affecting a product order, newest first
+pub async fn get_trouble_tickets_by_order(
+    pool: &Pool<Postgres>,
+    order_id: Uuid,
+) -> TmfResult<Vec<TroubleTicket>> {
+    let rows = sqlx::query(
+        "SELECT t.id, t.href, t.name, t.description, t.version, t.status, t.priority,
+         t.ticket_type, t.resolution, t.resolution_date, t.related_entity, t.customer_id,
+         t.assigned_to, t.tenant_id, t.last_update
+         FROM trouble_tickets t
+         JOIN trouble_ticket_orders l ON l.ticket_id = t.id
+         WHERE l.order_id = $1
+         ORDER BY t.created_at DESC",
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut tickets: Vec<TroubleTicket> = rows.iter().map(row_to_trouble_ticket).collect();
+    load_links(pool, &mut tickets).await?;
+    Ok(tickets)
 }
 
 /// Get trouble ticket by ID
@@ -146,7 +258,13 @@ pub async fn get_trouble_ticket_by_id(
     .await
     .map_err(map_sqlx_error)?;
 
-    Ok(row.map(|r| row_to_trouble_ticket(&r)))
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let mut tickets = [row_to_trouble_ticket(&row)];
+    load_links(pool, &mut tickets).await?;
+    let [ticket] = tickets;
+    Ok(Some(ticket))
 }
 
 /// Create a new trouble ticket
@@ -194,11 +312,148 @@ pub async fn create_trouble_ticket(
     .await
     .map_err(map_sqlx_error)?;
 
+    insert_links(
+        pool,
+        id,
+        request.triggering_alarm_ids.as_deref().unwrap_or_default(),
+        request.affected_order_ids.as_deref().unwrap_or_default(),
+    )
+    .await?;
+
     get_trouble_ticket_by_id(pool, id)
         .await?
         .ok_or_else(|| TmfError::NotFound("Trouble ticket not found after creation".to_string()))
 }
 
+/// Create a trouble ticket for an alarm, linked to it
+pub async fn create_trouble_ticket_from_alarm(
+    pool: &Pool<Postgres>,
+    request: CreateTroubleTicketFromAlarmRequest,
+) -> TmfResult<TroubleTicket> {
+    let alarm = tmf642_alarm::db::get_alarm_by_id(pool, request.alarm_id).await?;
+
+    let description = request
+        .description
+        .or_else(|| alarm.alarm_details.clone())
+        .or_else(|| alarm.base.description.clone());
+    let ticket = CreateTroubleTicketRequest {
+        name: request
+            .name
+            .unwrap_or_else(|| format!("Alarm: {}", alarm.base.name)),
+        description,
+        ticket_type: request
+            .ticket_type
+            .unwrap_or(TroubleTicketType::TechnicalIssue),
+        priority: request
+            .priority
+            .unwrap_or_else(|| TroubleTicketPriority::from(&alarm.severity)),
+        customer_id: request.customer_id,
+        related_entity: None,
+        assigned_to: request.assigned_to,
+        triggering_alarm_ids: Some(vec![alarm.base.id]),
+        affected_order_ids: request.affected_order_ids,
+    };
+
+    create_trouble_ticket(pool, ticket).await
+}
+
+/// Link alarms and orders to an existing trouble ticket
+pub async fn link_trouble_ticket(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    request: LinkTroubleTicketRequest,
+) -> TmfResult<TroubleTicket> {
+    if get_trouble_ticket_by_id(pool, id).await?.is_none() {
+        return Err(TmfError::NotFound("Trouble ticket not found".to_string()));
+    }
+
+    insert_links(
+        pool,
+        id,
+        request.triggering_alarm_ids.as_deref().unwrap_or_default(),
+        request.affected_order_ids.as_deref().unwrap_or_default(),
+    )
+    .await?;
+
+    get_trouble_ticket_by_id(pool, id)
+        .await?
+        .ok_or_else(|| TmfError::NotFound("Trouble ticket not found".to_string()))
+}
+
+/// Link a ticket to alarms and orders, which must exist
+async fn insert_links(
+    pool: &Pool<Postgres>,
+    ticket_id: Uuid,
+    alarm_ids: &[Uuid],
+    order_ids: &[Uuid],
+) -> TmfResult<()> {
+    for alarm_id in alarm_ids {
+        tmf642_alarm::db::get_alarm_by_id(pool, *alarm_id)
+            .await
+            .map_err(not_found_as_validation)?;
+        sqlx::query(
+            "INSERT INTO trouble_ticket_alarms (ticket_id, alarm_id)
+             VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(ticket_id)
+        .bind(alarm_id)
+        .execute(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    for order_id in order_ids {
+        tmf622_ordering::db::get_order_by_id(pool, *order_id)
+            .await
+            .map_err(not_found_as_validation)?;
+        sqlx::query(
+            "INSERT INTO trouble_ticket_orders (ticket_id, order_id)
+             VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(ticket_id)
+        .bind(order_id)
+        .execute(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    Ok(())
+}
+
+/// A linked entity that does not exist makes the request invalid rather than
+/// the ticket missing
+fn not_found_as_validation(err: TmfError) -> TmfError {
+    match err {
+        TmfError::NotFound(msg) => TmfError::Validation(msg),
+        other => other,
+    }
+}
+
+/// Get a trouble ticket with the alarms and orders it is linked to
+pub async fn get_trouble_ticket_related_entities(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+) -> TmfResult<TroubleTicketRelatedEntities> {
+    let ticket = get_trouble_ticket_by_id(pool, id)
+        .await?
+        .ok_or_else(|| TmfError::NotFound("Trouble ticket not found".to_string()))?;
+
+    let mut triggering_alarms = Vec::with_capacity(ticket.triggering_alarm_ids.len());
+    for alarm_id in &ticket.triggering_alarm_ids {
+        triggering_alarms.push(tmf642_alarm::db::get_alarm_by_id(pool, *alarm_id).await?);
+    }
+    let mut affected_orders = Vec::with_capacity(ticket.affected_order_ids.len());
+    for order_id in &ticket.affected_order_ids {
+        affected_orders.push(tmf622_ordering::db::get_order_by_id(pool, *order_id).await?);
+    }
+
+    Ok(TroubleTicketRelatedEntities {
+        ticket,
+        triggering_alarms,
+        affected_orders,
+    })
+}
+
 /// Update a trouble ticket
 pub async fn update_trouble_ticket(
     pool: &Pool<Postgres>,
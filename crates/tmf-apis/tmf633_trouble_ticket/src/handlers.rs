@@ -4,27 +4,51 @@ use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use serde::Deserialize;
 use sqlx::PgPool;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
-/// Get all trouble tickets
+/// Query parameters for listing trouble tickets
+#[derive(Debug, Deserialize)]
+pub struct TroubleTicketQuery {
+    pub alarm_id: Option<Uuid>,
+    pub order_id: Option<Uuid>,
+}
+
+/// Get all trouble tickets, or those linked to an alarm or order
 #[utoipa::path(
     get,
     path = "/tmf-api/troubleTicket/v4/troubleTicket",
     responses(
         (status = 200, description = "List of trouble tickets", body = Vec<TroubleTicket>),
+        (status = 400, description = "Both alarm_id and order_id given"),
         (status = 401, description = "Unauthorized")
     ),
+    params(
+        ("alarm_id" = Option<String>, Query, description = "Only tickets triggered by this alarm (UUID)"),
+        ("order_id" = Option<String>, Query, description = "Only tickets affecting this product order (UUID)")
+    ),
     tag = "TMF633"
 )]
 pub async fn get_trouble_tickets(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
+    query: web::Query<TroubleTicketQuery>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::get_trouble_tickets(pool.get_ref()).await {
+    let tickets = match (query.alarm_id, query.order_id) {
+        (Some(alarm_id), None) => db::get_trouble_tickets_by_alarm(pool.get_ref(), alarm_id).await,
+        (None, Some(order_id)) => db::get_trouble_tickets_by_order(pool.get_ref(), order_id).await,
+        (None, None) => db::get_trouble_tickets(pool.get_ref()).await,
+        (Some(_), Some(_)) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Filter by either alarm_id or order_id, not both."
+            })));
+        }
+    };
+    match tickets {
         Ok(tickets) => Ok(HttpResponse::Ok().json(tickets)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
@@ -95,6 +119,9 @@ pub async fn create_trouble_ticket(
 
     match db::create_trouble_ticket(pool.get_ref(), body.into_inner()).await {
         Ok(ticket) => Ok(HttpResponse::Created().json(ticket)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
@@ -186,3 +213,128 @@ pub async fn delete_trouble_ticket(
         }))),
     }
 }
+
+/// Create a trouble ticket from an alarm
+///
+/// The ticket is linked to the alarm it was raised for.
+#[utoipa::path(
+    post,
+    path = "/tmf-api/troubleTicket/v4/troubleTicket/fromAlarm",
+    request_body = CreateTroubleTicketFromAlarmRequest,
+    responses(
+        (status = 201, description = "Trouble ticket created", body = TroubleTicket),
+        (status = 404, description = "Alarm not found"),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF633"
+)]
+pub async fn create_trouble_ticket_from_alarm(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<CreateTroubleTicketFromAlarmRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::create_trouble_ticket_from_alarm(pool.get_ref(), body.into_inner()).await {
+        Ok(ticket) => Ok(HttpResponse::Created().json(ticket)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Link alarms and orders to a trouble ticket
+#[utoipa::path(
+    post,
+    path = "/tmf-api/troubleTicket/v4/troubleTicket/{id}/link",
+    request_body = LinkTroubleTicketRequest,
+    responses(
+        (status = 200, description = "Trouble ticket linked", body = TroubleTicket),
+        (status = 404, description = "Trouble ticket not found"),
+        (status = 400, description = "Invalid request or unknown alarm or order"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Trouble Ticket ID (UUID)")
+    ),
+    tag = "TMF633"
+)]
+pub async fn link_trouble_ticket(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<LinkTroubleTicketRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid trouble ticket ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::link_trouble_ticket(pool.get_ref(), id, body.into_inner()).await {
+        Ok(ticket) => Ok(HttpResponse::Ok().json(ticket)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get a trouble ticket with its triggering alarms and affected orders
+#[utoipa::path(
+    get,
+    path = "/tmf-api/troubleTicket/v4/troubleTicket/{id}/relatedEntities",
+    responses(
+        (status = 200, description = "Trouble ticket with related entities", body = TroubleTicketRelatedEntities),
+        (status = 404, description = "Trouble ticket not found"),
+        (status = 400, description = "Invalid trouble ticket ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Trouble Ticket ID (UUID)")
+    ),
+    tag = "TMF633"
+)]
+pub async fn get_trouble_ticket_related_entities(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid trouble ticket ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::get_trouble_ticket_related_entities(pool.get_ref(), id).await {
+        Ok(related) => Ok(HttpResponse::Ok().json(related)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
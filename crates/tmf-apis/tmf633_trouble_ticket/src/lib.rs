@@ -2,6 +2,8 @@
 //!
 //! This module implements the TM Forum Trouble Ticket Management API,
 //! providing a standardized interface for managing customer service tickets and issues.
+//! Tickets can be linked to the alarms (TMF642) that triggered them and the
+//! orders (TMF622) they affect.
 
 pub mod api;
 pub mod auth;
@@ -15,6 +17,10 @@ pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
 pub use db::{
+    get_open_trouble_tickets_by_customer as db_get_open_trouble_tickets_by_customer,
     get_trouble_ticket_by_id as db_get_trouble_ticket_by_id,
+    get_trouble_ticket_related_entities as db_get_trouble_ticket_related_entities,
     get_trouble_tickets as db_get_trouble_tickets,
+    get_trouble_tickets_by_alarm as db_get_trouble_tickets_by_alarm,
+    get_trouble_tickets_by_order as db_get_trouble_tickets_by_order,
 };
@@ -2,6 +2,8 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tmf622_ordering::models::ProductOrder;
+use tmf642_alarm::models::{Alarm, AlarmSeverity};
 use tmf_apis_core::BaseEntity;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -69,6 +71,14 @@ pub struct TroubleTicket {
     pub assigned_to: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tenant_id: Option<Uuid>,
+    /// Alarms (TMF642) that triggered the ticket
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<String>)]
+    pub triggering_alarm_ids: Vec<Uuid>,
+    /// Product orders (TMF622) affected by the issue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<String>)]
+    pub affected_order_ids: Vec<Uuid>,
 }
 
 /// Create Trouble Ticket Request
@@ -85,6 +95,71 @@ pub struct CreateTroubleTicketRequest {
     pub related_entity: Option<Vec<RelatedEntity>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assigned_to: Option<String>,
+    /// Alarms (TMF642) that triggered the ticket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub triggering_alarm_ids: Option<Vec<Uuid>>,
+    /// Product orders (TMF622) affected by the issue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub affected_order_ids: Option<Vec<Uuid>>,
+}
+
+/// Create Trouble Ticket From Alarm Request
+///
+/// The ticket is linked to the alarm. Unset fields are derived from it: the
+/// name and description from the alarm's, the priority from its severity.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateTroubleTicketFromAlarmRequest {
+    #[schema(value_type = String, format = "uuid")]
+    pub alarm_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Ticket type (defaults to TECHNICAL_ISSUE)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket_type: Option<TroubleTicketType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<TroubleTicketPriority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    /// Product orders (TMF622) affected by the issue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub affected_order_ids: Option<Vec<Uuid>>,
+}
+
+/// Link Trouble Ticket Request - Alarms and orders to add to a ticket
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LinkTroubleTicketRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub triggering_alarm_ids: Option<Vec<Uuid>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub affected_order_ids: Option<Vec<Uuid>>,
+}
+
+/// Trouble ticket with the alarms and orders it is linked to
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TroubleTicketRelatedEntities {
+    pub ticket: TroubleTicket,
+    pub triggering_alarms: Vec<Alarm>,
+    pub affected_orders: Vec<ProductOrder>,
+}
+
+impl From<&AlarmSeverity> for TroubleTicketPriority {
+    fn from(severity: &AlarmSeverity) -> Self {
+        match severity {
+            AlarmSeverity::Critical => TroubleTicketPriority::Critical,
+            AlarmSeverity::Major => TroubleTicketPriority::High,
+            AlarmSeverity::Minor => TroubleTicketPriority::Medium,
+            AlarmSeverity::Warning | AlarmSeverity::Indeterminate => TroubleTicketPriority::Low,
+        }
+    }
 }
 
 /// Update Trouble Ticket Request
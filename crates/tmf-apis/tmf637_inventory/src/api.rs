@@ -13,7 +13,16 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::post().to(create_inventory)),
             )
             .service(
-                web::resource("/productInventory/{id}").route(web::get().to(get_inventory_by_id)),
+                web::resource("/productInventory/{id}")
+                    .route(web::get().to(get_inventory_by_id))
+                    .route(web::patch().to(update_inventory)),
+            )
+            .service(web::resource("/stockLevel").route(web::get().to(get_stock_levels)))
+            .service(web::resource("/stockLevel/{category}").route(web::get().to(get_stock_level)))
+            .service(
+                web::resource("/stockThreshold")
+                    .route(web::get().to(get_stock_thresholds))
+                    .route(web::put().to(set_stock_threshold)),
             ),
     );
 }
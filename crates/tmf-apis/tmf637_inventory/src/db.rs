@@ -1,6 +1,10 @@
 //! Database operations for TMF637 Product Inventory
 
-use crate::models::{CreateProductInventoryRequest, InventoryState, ProductInventory};
+use crate::models::{
+    CreateProductInventoryRequest, InventoryState, ProductInventory, UpdateProductInventoryRequest,
+};
+use crate::stock::check_stock_level;
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -37,7 +41,7 @@ fn inventory_state_to_string(state: &InventoryState) -> String {
 /// Get all product inventories
 pub async fn get_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<ProductInventory>> {
     let rows = sqlx::query(
-        "SELECT id, name, description, version, state, quantity, reserved_quantity,
+        "SELECT id, name, description, version, state, quantity, reserved_quantity, category,
          activation_date, last_modified_date, href, last_update
          FROM product_inventories ORDER BY name",
     )
@@ -63,6 +67,7 @@ pub async fn get_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<ProductInve
             product_offering: None,      // Load separately if needed
             quantity: row.get::<Option<i32>, _>("quantity"),
             reserved_quantity: row.get::<Option<i32>, _>("reserved_quantity"),
+            category: row.get::<Option<String>, _>("category"),
             related_party: None, // Load separately if needed
             activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
             last_modified_date: row.get::<Option<DateTime<Utc>>, _>("last_modified_date"),
@@ -72,10 +77,57 @@ pub async fn get_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<ProductInve
     Ok(inventories)
 }
 
+/// Get the product inventories in use by a customer, matched by the id of
+/// their related party in the customer role
+pub async fn get_active_inventories_by_customer(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+) -> TmfResult<Vec<ProductInventory>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, quantity, reserved_quantity, category,
+         activation_date, last_modified_date, href, last_update
+         FROM product_inventories i
+         WHERE state = 'IN_USE' AND EXISTS (
+             SELECT 1 FROM inventory_related_parties p
+             WHERE p.inventory_id = i.id AND p.party_id = $1 AND LOWER(p.role) = 'customer'
+         )
+         ORDER BY activation_date DESC NULLS LAST, name",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ProductInventory {
+            base: tmf_apis_core::BaseEntity {
+                id: row.get::<Uuid, _>("id"),
+                href: row.get::<Option<String>, _>("href"),
+                name: row.get::<String, _>("name"),
+                description: row.get::<Option<String>, _>("description"),
+                version: row.get::<Option<String>, _>("version"),
+                lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
+                last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
+                valid_for: None,
+            },
+            state: parse_inventory_state(&row.get::<String, _>("state")),
+            product_specification: None,
+            product_offering: None,
+            quantity: row.get::<Option<i32>, _>("quantity"),
+            reserved_quantity: row.get::<Option<i32>, _>("reserved_quantity"),
+            category: row.get::<Option<String>, _>("category"),
+            related_party: None,
+            activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
+            last_modified_date: row.get::<Option<DateTime<Utc>>, _>("last_modified_date"),
+        })
+        .collect())
+}
+
 /// Get product inventory by ID
 pub async fn get_inventory_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<ProductInventory> {
     let row = sqlx::query(
-        "SELECT id, name, description, version, state, quantity, reserved_quantity,
+        "SELECT id, name, description, version, state, quantity, reserved_quantity, category,
          activation_date, last_modified_date, href, last_update
          FROM product_inventories WHERE id = $1",
     )
@@ -101,6 +153,7 @@ pub async fn get_inventory_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<P
         product_offering: None,
         quantity: row.get::<Option<i32>, _>("quantity"),
         reserved_quantity: row.get::<Option<i32>, _>("reserved_quantity"),
+        category: row.get::<Option<String>, _>("category"),
         related_party: None,
         activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
         last_modified_date: row.get::<Option<DateTime<Utc>>, _>("last_modified_date"),
@@ -108,8 +161,11 @@ pub async fn get_inventory_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<P
 }
 
 /// Create a new product inventory
+///
+/// Inventories in a category are checked against its low-stock threshold.
 pub async fn create_inventory(
     pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
     request: CreateProductInventoryRequest,
 ) -> TmfResult<ProductInventory> {
     let id = Uuid::new_v4();
@@ -118,8 +174,8 @@ pub async fn create_inventory(
 
     sqlx::query(
         "INSERT INTO product_inventories (id, name, description, version, state, quantity, 
-         reserved_quantity, activation_date, last_modified_date)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+         reserved_quantity, activation_date, last_modified_date, category)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
     )
     .bind(id)
     .bind(&request.name)
@@ -130,6 +186,7 @@ pub async fn create_inventory(
     .bind(0i32) // reserved_quantity defaults to 0
     .bind(now)
     .bind(now)
+    .bind(&request.category)
     .execute(pool)
     .await
     .map_err(map_sqlx_error)?;
@@ -137,13 +194,13 @@ pub async fn create_inventory(
     // Create related parties if provided
     if let Some(parties) = request.related_party {
         for party in parties {
-            let party_id = Uuid::new_v4();
             sqlx::query(
-                "INSERT INTO inventory_related_parties (id, inventory_id, name, role)
-                 VALUES ($1, $2, $3, $4)",
+                "INSERT INTO inventory_related_parties (id, inventory_id, party_id, name, role)
+                 VALUES ($1, $2, $3, $4, $5)",
             )
-            .bind(party_id)
+            .bind(Uuid::new_v4())
             .bind(id)
+            .bind(party.id)
             .bind(&party.name)
             .bind(&party.role)
             .execute(pool)
@@ -152,6 +209,84 @@ pub async fn create_inventory(
         }
     }
 
+    if let Some(category) = &request.category {
+        check_category_stock(pool, publisher, category).await;
+    }
+
     // Fetch the created inventory
     get_inventory_by_id(pool, id).await
 }
+
+/// Update the state, quantities or category of a product inventory
+///
+/// The categories whose stock changed are checked against their low-stock
+/// thresholds.
+pub async fn update_inventory(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    id: Uuid,
+    request: UpdateProductInventoryRequest,
+) -> TmfResult<ProductInventory> {
+    let current = get_inventory_by_id(pool, id).await?;
+
+    let quantity = request.quantity.or(current.quantity);
+    let reserved_quantity = request.reserved_quantity.or(current.reserved_quantity);
+    if quantity.is_some_and(|q| q < 0) || reserved_quantity.is_some_and(|r| r < 0) {
+        return Err(TmfError::Validation(
+            "Quantities must not be negative".to_string(),
+        ));
+    }
+    if let (Some(quantity), Some(reserved)) = (quantity, reserved_quantity) {
+        if reserved > quantity {
+            return Err(TmfError::Validation(format!(
+                "Reserved quantity {} exceeds quantity {}",
+                reserved, quantity
+            )));
+        }
+    }
+
+    sqlx::query(
+        "UPDATE product_inventories SET
+         state = COALESCE($1, state),
+         quantity = $2,
+         reserved_quantity = $3,
+         category = COALESCE($4, category),
+         last_modified_date = CURRENT_TIMESTAMP,
+         last_update = CURRENT_TIMESTAMP
+         WHERE id = $5",
+    )
+    .bind(request.state.as_ref().map(inventory_state_to_string))
+    .bind(quantity)
+    .bind(reserved_quantity)
+    .bind(&request.category)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let updated = get_inventory_by_id(pool, id).await?;
+    for category in [&current.category, &updated.category]
+        .into_iter()
+        .flatten()
+        .collect::<std::collections::BTreeSet<_>>()
+    {
+        check_category_stock(pool, publisher, category).await;
+    }
+    Ok(updated)
+}
+
+/// Check the stock of a category after a change, logging failures since the
+/// change itself succeeded
+async fn check_category_stock(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    category: &str,
+) {
+    if let Err(e) = check_stock_level(pool, publisher, category).await {
+        log::warn!(
+            "Failed to check stock level of inventory category {}: {}",
+            category,
+            e
+        );
+    }
+}
@@ -3,8 +3,11 @@
 use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
+use crate::stock;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
@@ -90,13 +93,176 @@ pub async fn create_inventory(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
     body: web::Json<CreateProductInventoryRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_inventory(pool.get_ref(), body.into_inner()).await {
+    let publisher = Arc::clone(publisher.get_ref());
+    match db::create_inventory(pool.get_ref(), publisher.as_ref(), body.into_inner()).await {
         Ok(inventory) => Ok(HttpResponse::Created().json(inventory)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
     }
 }
+
+/// Update a product inventory
+#[utoipa::path(
+    patch,
+    path = "/tmf-api/productInventoryManagement/v4/productInventory/{id}",
+    request_body = UpdateProductInventoryRequest,
+    responses(
+        (status = 200, description = "Product inventory updated", body = ProductInventory),
+        (status = 400, description = "Invalid inventory ID or quantities"),
+        (status = 404, description = "Product inventory not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Product Inventory ID (UUID)")
+    ),
+    tag = "TMF637"
+)]
+pub async fn update_inventory(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<UpdateProductInventoryRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid product inventory ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let publisher = Arc::clone(publisher.get_ref());
+    match db::update_inventory(pool.get_ref(), publisher.as_ref(), id, body.into_inner()).await {
+        Ok(inventory) => Ok(HttpResponse::Ok().json(inventory)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get the stock levels of all inventory categories
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productInventoryManagement/v4/stockLevel",
+    responses(
+        (status = 200, description = "Stock levels by category", body = Vec<StockLevel>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF637"
+)]
+pub async fn get_stock_levels(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match stock::get_stock_levels(pool.get_ref()).await {
+        Ok(levels) => Ok(HttpResponse::Ok().json(levels)),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get the stock level of an inventory category
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productInventoryManagement/v4/stockLevel/{category}",
+    responses(
+        (status = 200, description = "Stock level of the category", body = StockLevel),
+        (status = 404, description = "Inventory category not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("category" = String, Path, description = "Inventory category")
+    ),
+    tag = "TMF637"
+)]
+pub async fn get_stock_level(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match stock::get_stock_level(pool.get_ref(), &path.into_inner()).await {
+        Ok(level) => Ok(HttpResponse::Ok().json(level)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get the low-stock thresholds of all inventory categories
+#[utoipa::path(
+    get,
+    path = "/tmf-api/productInventoryManagement/v4/stockThreshold",
+    responses(
+        (status = 200, description = "Low-stock thresholds", body = Vec<StockThreshold>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF637"
+)]
+pub async fn get_stock_thresholds(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match stock::get_stock_thresholds(pool.get_ref()).await {
+        Ok(thresholds) => Ok(HttpResponse::Ok().json(thresholds)),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Set the low-stock threshold of an inventory category
+#[utoipa::path(
+    put,
+    path = "/tmf-api/productInventoryManagement/v4/stockThreshold",
+    request_body = StockThreshold,
+    responses(
+        (status = 200, description = "Threshold set; current stock level of the category", body = StockLevel),
+        (status = 400, description = "Invalid threshold"),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF637"
+)]
+pub async fn set_stock_threshold(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<StockThreshold>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let publisher = Arc::clone(publisher.get_ref());
+    match stock::set_stock_threshold(pool.get_ref(), publisher.as_ref(), body.into_inner()).await {
+        Ok(level) => Ok(HttpResponse::Ok().json(level)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
@@ -8,6 +8,7 @@ pub mod auth;
 pub mod db;
 pub mod handlers;
 pub mod models;
+pub mod stock;
 
 pub use auth::*;
 pub use handlers::*;
@@ -15,5 +16,8 @@ pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
 pub use db::{
+    get_active_inventories_by_customer as db_get_active_inventories_by_customer,
     get_inventories as db_get_inventories, get_inventory_by_id as db_get_inventory_by_id,
+    update_inventory as db_update_inventory,
 };
+pub use stock::{LOW_STOCK_EVENT, STOCK_RECOVERED_EVENT};
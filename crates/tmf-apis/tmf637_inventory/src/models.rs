@@ -36,6 +36,9 @@ pub struct ProductInventory {
     /// Reserved quantity
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reserved_quantity: Option<i32>,
+    /// Stock category (e.g. MSISDN, SIM) whose level the inventory counts towards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
     /// Related party (customer who owns/reserves this inventory)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
@@ -97,12 +100,62 @@ pub struct CreateProductInventoryRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<CreateRelatedPartyRequest>>,
 }
 
+/// Request to update a product inventory
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateProductInventoryRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<InventoryState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved_quantity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
 /// Request to create a related party
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateRelatedPartyRequest {
+    /// Id of the referenced party, e.g. the customer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub id: Option<Uuid>,
     pub name: String,
     pub role: String,
 }
+
+/// Low-stock threshold of an inventory category
+///
+/// An alert is raised when available stock drops below `low_threshold` and is
+/// not raised again until stock has recovered to `recovery_threshold`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StockThreshold {
+    pub category: String,
+    pub low_threshold: i64,
+    /// Defaults to 20% above the low threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_threshold: Option<i64>,
+}
+
+/// Current stock of an inventory category
+///
+/// Stock counts inventories in the `AVAILABLE` state; an inventory without a
+/// quantity counts as one unit.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StockLevel {
+    pub category: String,
+    pub total: i64,
+    pub reserved: i64,
+    pub available: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_threshold: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_threshold: Option<i64>,
+    /// A low-stock alert was raised and stock has not recovered since
+    pub low_stock_alert: bool,
+}
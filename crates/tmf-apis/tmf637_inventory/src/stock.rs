@@ -0,0 +1,226 @@
+//! Stock levels and low-stock alerts
+//!
+//! Finite pools such as phone numbers and SIMs are tracked as inventory
+//! categories. When the available stock of a category drops below its low
+//! threshold a `LowStockAlert` event is published; no further alert is raised
+//! until stock climbs back to the recovery threshold, which publishes a
+//! `StockLevelRecovered` event and re-arms the alert.
+
+use crate::models::{StockLevel, StockThreshold};
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use sqlx::{Pool, Postgres, Row};
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Event published when available stock drops below the low threshold
+pub const LOW_STOCK_EVENT: &str = "LowStockAlert";
+/// Event published when available stock recovers after a low-stock alert
+pub const STOCK_RECOVERED_EVENT: &str = "StockLevelRecovered";
+
+fn map_sqlx_error(err: sqlx::Error) -> TmfError {
+    TmfError::Database(err.to_string())
+}
+
+/// Get the stock level of every category with inventory or a threshold
+pub async fn get_stock_levels(pool: &Pool<Postgres>) -> TmfResult<Vec<StockLevel>> {
+    query_stock_levels(pool, None).await
+}
+
+/// Get the stock level of a category
+pub async fn get_stock_level(pool: &Pool<Postgres>, category: &str) -> TmfResult<StockLevel> {
+    query_stock_levels(pool, Some(category))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| TmfError::NotFound(format!("Inventory category {} not found", category)))
+}
+
+async fn query_stock_levels(
+    pool: &Pool<Postgres>,
+    category: Option<&str>,
+) -> TmfResult<Vec<StockLevel>> {
+    let rows = sqlx::query(
+        "SELECT c.category,
+         COALESCE(s.total, 0) AS total, COALESCE(s.reserved, 0) AS reserved,
+         t.low_threshold, t.recovery_threshold, COALESCE(t.alert_active, false) AS alert_active
+         FROM (
+             SELECT category FROM product_inventories WHERE category IS NOT NULL
+             UNION SELECT category FROM inventory_stock_thresholds
+         ) c
+         LEFT JOIN (
+             SELECT category, SUM(COALESCE(quantity, 1))::BIGINT AS total,
+             SUM(COALESCE(reserved_quantity, 0))::BIGINT AS reserved
+             FROM product_inventories WHERE state = 'AVAILABLE' AND category IS NOT NULL
+             GROUP BY category
+         ) s ON s.category = c.category
+         LEFT JOIN inventory_stock_thresholds t ON t.category = c.category
+         WHERE $1::VARCHAR IS NULL OR c.category = $1
+         ORDER BY c.category",
+    )
+    .bind(category)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let total: i64 = row.get("total");
+            let reserved: i64 = row.get("reserved");
+            StockLevel {
+                category: row.get("category"),
+                total,
+                reserved,
+                available: total - reserved,
+                low_threshold: row.get("low_threshold"),
+                recovery_threshold: row.get("recovery_threshold"),
+                low_stock_alert: row.get("alert_active"),
+            }
+        })
+        .collect())
+}
+
+/// Get the low-stock thresholds of all categories
+pub async fn get_stock_thresholds(pool: &Pool<Postgres>) -> TmfResult<Vec<StockThreshold>> {
+    let rows = sqlx::query(
+        "SELECT category, low_threshold, recovery_threshold
+         FROM inventory_stock_thresholds ORDER BY category",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| StockThreshold {
+            category: row.get("category"),
+            low_threshold: row.get("low_threshold"),
+            recovery_threshold: row.get("recovery_threshold"),
+        })
+        .collect())
+}
+
+/// Set the low-stock threshold of a category, then check its current stock
+/// against it
+pub async fn set_stock_threshold(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    threshold: StockThreshold,
+) -> TmfResult<StockLevel> {
+    let category = threshold.category.trim().to_string();
+    if category.is_empty() {
+        return Err(TmfError::Validation(
+            "Category must not be empty".to_string(),
+        ));
+    }
+    if threshold.low_threshold < 0 {
+        return Err(TmfError::Validation(format!(
+            "Low threshold must not be negative, got {}",
+            threshold.low_threshold
+        )));
+    }
+    let recovery_threshold = threshold
+        .recovery_threshold
+        .unwrap_or_else(|| default_recovery_threshold(threshold.low_threshold));
+    if recovery_threshold <= threshold.low_threshold {
+        return Err(TmfError::Validation(format!(
+            "Recovery threshold {} must be above the low threshold {}",
+            recovery_threshold, threshold.low_threshold
+        )));
+    }
+
+    sqlx::query(
+        "INSERT INTO inventory_stock_thresholds (category, low_threshold, recovery_threshold)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (category) DO UPDATE SET
+         low_threshold = EXCLUDED.low_threshold,
+         recovery_threshold = EXCLUDED.recovery_threshold,
+         updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(&category)
+    .bind(threshold.low_threshold)
+    .bind(recovery_threshold)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    check_stock_level(pool, publisher, &category).await
+}
+
+/// Recovery threshold 20% (at least one unit) above the low threshold
+fn default_recovery_threshold(low_threshold: i64) -> i64 {
+    low_threshold + (low_threshold / 5).max(1)
+}
+
+/// Compare the stock of a category with its threshold, raising or clearing
+/// the low-stock alert
+///
+/// The alert flag is switched atomically, so concurrent stock changes raise
+/// one alert only. If the event cannot be published the flag is switched
+/// back, so the next stock change tries again.
+pub async fn check_stock_level(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    category: &str,
+) -> TmfResult<StockLevel> {
+    let mut level = get_stock_level(pool, category).await?;
+    let (Some(low_threshold), Some(recovery_threshold)) =
+        (level.low_threshold, level.recovery_threshold)
+    else {
+        return Ok(level);
+    };
+
+    let (raise, event_type) = if level.available < low_threshold && !level.low_stock_alert {
+        (true, LOW_STOCK_EVENT)
+    } else if level.available >= recovery_threshold && level.low_stock_alert {
+        (false, STOCK_RECOVERED_EVENT)
+    } else {
+        return Ok(level);
+    };
+
+    if !set_alert_active(pool, category, raise).await? {
+        // Another stock change switched the alert first
+        return get_stock_level(pool, category).await;
+    }
+    level.low_stock_alert = raise;
+
+    log::info!(
+        "Inventory category {}: {} with {} available (low threshold {}, recovery {})",
+        category,
+        event_type,
+        level.available,
+        low_threshold,
+        recovery_threshold
+    );
+    let data = serde_json::to_value(&level).map_err(|e| TmfError::Internal(e.to_string()))?;
+    let event = EventEnvelope::new(event_type.to_string(), "tmf637-inventory".to_string(), data);
+    if let Err(e) = publisher.publish(topics::INVENTORY_EVENTS, event).await {
+        log::warn!(
+            "Failed to publish {} for inventory category {}: {}",
+            event_type,
+            category,
+            e
+        );
+        set_alert_active(pool, category, !raise).await?;
+        level.low_stock_alert = !raise;
+    }
+
+    Ok(level)
+}
+
+/// Switch the alert flag of a category, returning whether it changed
+async fn set_alert_active(pool: &Pool<Postgres>, category: &str, active: bool) -> TmfResult<bool> {
+    let result = sqlx::query(
+        "UPDATE inventory_stock_thresholds
+         SET alert_active = $2,
+         alerted_at = CASE WHEN $2 THEN CURRENT_TIMESTAMP ELSE alerted_at END
+         WHERE category = $1 AND alert_active <> $2",
+    )
+    .bind(category)
+    .bind(active)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -15,6 +15,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .service(
                 web::resource("/serviceInventory/{id}")
                     .route(web::get().to(get_service_inventory_by_id)),
+            )
+            .service(
+                web::resource("/serviceInventory/{id}/resource")
+                    .route(web::get().to(get_service_resources)),
             ),
     );
 }
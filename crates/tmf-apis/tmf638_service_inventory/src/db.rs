@@ -1,11 +1,22 @@
 //! Database operations for TMF638 Service Inventory
 
-use crate::models::{CreateServiceInventoryRequest, ServiceInventory, ServiceInventoryState};
+use crate::models::{
+    CreateServiceInventoryRequest, ServiceInventory, ServiceInventoryState, ServiceResourceTrace,
+    TracedResource,
+};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
+use std::collections::HashMap;
+use tmf639_resource_inventory::db::get_resource_inventories_by_ids;
 use tmf_apis_core::{TmfError, TmfResult};
 use uuid::Uuid;
 
+/// Default depth for service-to-resource traces
+pub const DEFAULT_TRACE_DEPTH: u32 = 5;
+
+/// Upper bound on trace depth for composite services
+pub const MAX_TRACE_DEPTH: u32 = 10;
+
 // Helper to convert sqlx::Error to TmfError
 fn map_sqlx_error(err: sqlx::Error) -> TmfError {
     TmfError::Database(err.to_string())
@@ -115,6 +126,9 @@ pub async fn create_service_inventory(
     let state = service_inventory_state_to_string(&ServiceInventoryState::Active);
     let now = Utc::now();
 
+    // The inventory and its relationships are created together or not at all
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
     sqlx::query(
         "INSERT INTO service_inventories (id, name, description, version, state, 
          service_specification_id, service_id, activation_date, last_modified_date)
@@ -129,7 +143,7 @@ pub async fn create_service_inventory(
     .bind(request.service_id)
     .bind(now)
     .bind(now)
-    .execute(pool)
+    .execute(&mut *tx)
     .await
     .map_err(map_sqlx_error)?;
 
@@ -145,12 +159,148 @@ pub async fn create_service_inventory(
             .bind(id)
             .bind(&party.name)
             .bind(&party.role)
-            .execute(pool)
+            .execute(&mut *tx)
             .await
             .map_err(map_sqlx_error)?;
         }
     }
 
+    for child_id in request.supporting_service_ids.iter().flatten() {
+        sqlx::query(
+            "INSERT INTO service_inventory_relationships (parent_service_id, child_service_id)
+             VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(id)
+        .bind(child_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    for resource_id in request.supporting_resource_ids.iter().flatten() {
+        sqlx::query(
+            "INSERT INTO service_inventory_resources (service_id, resource_inventory_id)
+             VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(id)
+        .bind(resource_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    tx.commit().await.map_err(map_sqlx_error)?;
+
     // Fetch the created service inventory
     get_service_inventory_by_id(pool, id).await
 }
+
+/// Trace a service down to the resources supporting it
+///
+/// Follows supporting-service relationships of composite services up to
+/// `max_depth` levels (capped at `MAX_TRACE_DEPTH`) and returns every resource
+/// found with its current state. Each resource is reported once, at the
+/// shallowest depth it was reached.
+pub async fn resources_for_service(
+    pool: &Pool<Postgres>,
+    service_id: Uuid,
+    max_depth: u32,
+) -> TmfResult<ServiceResourceTrace> {
+    // Ensure the service exists
+    get_service_inventory_by_id(pool, service_id).await?;
+
+    let max_depth = max_depth.min(MAX_TRACE_DEPTH);
+
+    // UNION and the depth bound keep cyclic relationships from recursing forever
+    let rows = sqlx::query(
+        "WITH RECURSIVE service_tree (service_id, depth) AS (
+             SELECT $1::uuid, 0
+             UNION
+             SELECT r.child_service_id, t.depth + 1
+             FROM service_inventory_relationships r
+             JOIN service_tree t ON r.parent_service_id = t.service_id
+             WHERE t.depth < $2
+         )
+         SELECT service_id, MIN(depth) AS depth FROM service_tree GROUP BY service_id",
+    )
+    .bind(service_id)
+    .bind(max_depth as i32)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let depths: HashMap<Uuid, u32> = rows
+        .iter()
+        .map(|row| {
+            (
+                row.get::<Uuid, _>("service_id"),
+                row.get::<i32, _>("depth") as u32,
+            )
+        })
+        .collect();
+    let service_ids: Vec<Uuid> = depths.keys().copied().collect();
+    let frontier: Vec<Uuid> = depths
+        .iter()
+        .filter(|(_, depth)| **depth == max_depth)
+        .map(|(id, _)| *id)
+        .collect();
+
+    let truncated = if frontier.is_empty() {
+        false
+    } else {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM service_inventory_relationships
+             WHERE parent_service_id = ANY($1))",
+        )
+        .bind(&frontier)
+        .fetch_one(pool)
+        .await
+        .map_err(map_sqlx_error)?
+    };
+
+    let links = sqlx::query(
+        "SELECT service_id, resource_inventory_id FROM service_inventory_resources
+         WHERE service_id = ANY($1)",
+    )
+    .bind(&service_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    // Keep the shallowest supporting service for each resource
+    let mut supported_by: HashMap<Uuid, (Uuid, u32)> = HashMap::new();
+    for link in links {
+        let service = link.get::<Uuid, _>("service_id");
+        let resource = link.get::<Uuid, _>("resource_inventory_id");
+        let depth = depths[&service];
+        supported_by
+            .entry(resource)
+            .and_modify(|entry| {
+                if depth < entry.1 {
+                    *entry = (service, depth);
+                }
+            })
+            .or_insert((service, depth));
+    }
+
+    let resource_ids: Vec<Uuid> = supported_by.keys().copied().collect();
+    let resources = get_resource_inventories_by_ids(pool, &resource_ids)
+        .await?
+        .into_iter()
+        .map(|resource| {
+            let (supported_service_id, depth) = supported_by[&resource.base.id];
+            TracedResource {
+                resource,
+                supported_service_id,
+                depth,
+            }
+        })
+        .collect();
+
+    Ok(ServiceResourceTrace {
+        service_id,
+        max_depth,
+        truncated,
+        resources,
+    })
+}
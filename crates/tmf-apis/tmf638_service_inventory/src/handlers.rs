@@ -4,6 +4,7 @@ use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use serde::Deserialize;
 use sqlx::PgPool;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
@@ -100,3 +101,54 @@ pub async fn create_service_inventory(
         }))),
     }
 }
+
+/// Query parameters for a service-to-resource trace
+#[derive(Debug, Deserialize)]
+pub struct ResourceTraceQuery {
+    pub depth: Option<u32>,
+}
+
+/// Get the resources underlying a service
+#[utoipa::path(
+    get,
+    path = "/tmf-api/serviceInventoryManagement/v4/serviceInventory/{id}/resource",
+    responses(
+        (status = 200, description = "Resources supporting the service", body = ServiceResourceTrace),
+        (status = 404, description = "Service inventory not found"),
+        (status = 400, description = "Invalid service inventory ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Service Inventory ID (UUID)"),
+        ("depth" = Option<u32>, Query, description = "Maximum composite service depth to traverse (default 5, max 10)")
+    ),
+    tag = "TMF638"
+)]
+pub async fn get_service_resources(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<ResourceTraceQuery>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid service inventory ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let depth = query.depth.unwrap_or(db::DEFAULT_TRACE_DEPTH);
+    match db::resources_for_service(pool.get_ref(), id, depth).await {
+        Ok(trace) => Ok(HttpResponse::Ok().json(trace)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
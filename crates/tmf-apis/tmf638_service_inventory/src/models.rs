@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tmf639_resource_inventory::models::ResourceInventory;
 use tmf_apis_core::BaseEntity;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -90,6 +91,14 @@ pub struct CreateServiceInventoryRequest {
     pub service_id: Option<Uuid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<CreateRelatedPartyRequest>>,
+    /// Child service inventories composing this service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub supporting_service_ids: Option<Vec<Uuid>>,
+    /// Resource inventories this service runs on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub supporting_resource_ids: Option<Vec<Uuid>>,
 }
 
 /// Request to create a related party
@@ -98,3 +107,26 @@ pub struct CreateRelatedPartyRequest {
     pub name: String,
     pub role: String,
 }
+
+/// Resource reached while tracing a service down to its resources
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TracedResource {
+    pub resource: ResourceInventory,
+    /// Service inventory the resource directly supports
+    #[schema(value_type = String, format = "uuid")]
+    pub supported_service_id: Uuid,
+    /// Number of service relationships between the traced service and that service
+    pub depth: u32,
+}
+
+/// Service-to-resource trace - Resources underlying a (possibly composite) service
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ServiceResourceTrace {
+    #[schema(value_type = String, format = "uuid")]
+    pub service_id: Uuid,
+    /// Depth limit the trace was run with
+    pub max_depth: u32,
+    /// Whether supporting services exist beyond the depth limit
+    pub truncated: bool,
+    pub resources: Vec<TracedResource>,
+}
@@ -14,7 +14,16 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             )
             .service(
                 web::resource("/resourceInventory/{id}")
-                    .route(web::get().to(get_resource_inventory_by_id)),
+                    .route(web::get().to(get_resource_inventory_by_id))
+                    .route(web::patch().to(update_resource_inventory)),
+            )
+            .service(
+                web::resource("/resourceReconciliation")
+                    .route(web::post().to(reconcile_resource_inventory)),
+            )
+            .service(
+                web::resource("/resourceReconciliation/apply")
+                    .route(web::post().to(apply_reconciliation_corrections)),
             ),
     );
 }
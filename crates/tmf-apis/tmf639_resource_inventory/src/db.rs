@@ -1,6 +1,12 @@
 //! Database operations for TMF639 Resource Inventory
 
-use crate::models::{CreateResourceInventoryRequest, ResourceInventory, ResourceInventoryState};
+use crate::lifecycle::{validate_transition, RESOURCE_STATE_CHANGED_EVENT};
+use crate::models::{
+    CreateResourceInventoryRequest, ResourceInventory, ResourceInventoryState,
+    ResourceLifecycleState, ResourceStateChangeEvent, UpdateResourceInventoryRequest,
+};
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -34,11 +40,37 @@ fn resource_inventory_state_to_string(state: &ResourceInventoryState) -> String
     }
 }
 
+/// Parse resource lifecycle state from database string, rejecting unknown values
+fn parse_resource_lifecycle_state(s: &str) -> TmfResult<ResourceLifecycleState> {
+    match s.to_uppercase().as_str() {
+        "PLANNED" => Ok(ResourceLifecycleState::Planned),
+        "INSTALLED" => Ok(ResourceLifecycleState::Installed),
+        "OPERATING" => Ok(ResourceLifecycleState::Operating),
+        "DEGRADED" => Ok(ResourceLifecycleState::Degraded),
+        "RETIRED" => Ok(ResourceLifecycleState::Retired),
+        _ => Err(TmfError::Validation(format!(
+            "Unknown resource lifecycle state: {}",
+            s
+        ))),
+    }
+}
+
+/// Convert resource lifecycle state to database string
+pub(crate) fn resource_lifecycle_state_to_string(state: &ResourceLifecycleState) -> String {
+    match state {
+        ResourceLifecycleState::Planned => "PLANNED".to_string(),
+        ResourceLifecycleState::Installed => "INSTALLED".to_string(),
+        ResourceLifecycleState::Operating => "OPERATING".to_string(),
+        ResourceLifecycleState::Degraded => "DEGRADED".to_string(),
+        ResourceLifecycleState::Retired => "RETIRED".to_string(),
+    }
+}
+
 /// Get all resource inventories
 pub async fn get_resource_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<ResourceInventory>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, resource_type, activation_date, 
-         last_modified_date, href, last_update
+         last_modified_date, href, last_update, lifecycle_state
          FROM resource_inventories ORDER BY name",
     )
     .fetch_all(pool)
@@ -59,6 +91,9 @@ pub async fn get_resource_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<Re
                 valid_for: None,
             },
             state: parse_resource_inventory_state(&row.get::<String, _>("state")),
+            lifecycle_state: parse_resource_lifecycle_state(
+                &row.get::<String, _>("lifecycle_state"),
+            )?,
             resource_specification: None, // Load separately if needed
             resource: None,               // Load separately if needed
             resource_type: row.get::<Option<String>, _>("resource_type"),
@@ -71,6 +106,50 @@ pub async fn get_resource_inventories(pool: &Pool<Postgres>) -> TmfResult<Vec<Re
     Ok(inventories)
 }
 
+/// Get the resource inventories with the given IDs
+pub async fn get_resource_inventories_by_ids(
+    pool: &Pool<Postgres>,
+    ids: &[Uuid],
+) -> TmfResult<Vec<ResourceInventory>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, resource_type, activation_date, 
+         last_modified_date, href, last_update, lifecycle_state
+         FROM resource_inventories WHERE id = ANY($1) ORDER BY name",
+    )
+    .bind(ids)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut inventories = Vec::new();
+    for row in rows {
+        inventories.push(ResourceInventory {
+            base: tmf_apis_core::BaseEntity {
+                id: row.get::<Uuid, _>("id"),
+                href: row.get::<Option<String>, _>("href"),
+                name: row.get::<String, _>("name"),
+                description: row.get::<Option<String>, _>("description"),
+                version: row.get::<Option<String>, _>("version"),
+                lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
+                last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
+                valid_for: None,
+            },
+            state: parse_resource_inventory_state(&row.get::<String, _>("state")),
+            lifecycle_state: parse_resource_lifecycle_state(
+                &row.get::<String, _>("lifecycle_state"),
+            )?,
+            resource_specification: None,
+            resource: None,
+            resource_type: row.get::<Option<String>, _>("resource_type"),
+            related_party: None,
+            activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
+            last_modified_date: row.get::<Option<DateTime<Utc>>, _>("last_modified_date"),
+        });
+    }
+
+    Ok(inventories)
+}
+
 /// Get resource inventory by ID
 pub async fn get_resource_inventory_by_id(
     pool: &Pool<Postgres>,
@@ -78,7 +157,7 @@ pub async fn get_resource_inventory_by_id(
 ) -> TmfResult<ResourceInventory> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, resource_type, activation_date, 
-         last_modified_date, href, last_update
+         last_modified_date, href, last_update, lifecycle_state
          FROM resource_inventories WHERE id = $1",
     )
     .bind(id)
@@ -99,6 +178,7 @@ pub async fn get_resource_inventory_by_id(
             valid_for: None,
         },
         state: parse_resource_inventory_state(&row.get::<String, _>("state")),
+        lifecycle_state: parse_resource_lifecycle_state(&row.get::<String, _>("lifecycle_state"))?,
         resource_specification: None,
         resource: None,
         resource_type: row.get::<Option<String>, _>("resource_type"),
@@ -115,18 +195,20 @@ pub async fn create_resource_inventory(
 ) -> TmfResult<ResourceInventory> {
     let id = Uuid::new_v4();
     let state = resource_inventory_state_to_string(&ResourceInventoryState::Available);
+    let lifecycle_state = resource_lifecycle_state_to_string(&ResourceLifecycleState::Planned);
     let now = Utc::now();
 
     sqlx::query(
-        "INSERT INTO resource_inventories (id, name, description, version, state, 
+        "INSERT INTO resource_inventories (id, name, description, version, state, lifecycle_state,
          resource_type, resource_specification_id, resource_id, activation_date, last_modified_date)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
     )
     .bind(id)
     .bind(&request.name)
     .bind(&request.description)
     .bind(&request.version)
     .bind(&state)
+    .bind(&lifecycle_state)
     .bind(&request.resource_type)
     .bind(request.resource_specification_id)
     .bind(request.resource_id)
@@ -157,3 +239,91 @@ pub async fn create_resource_inventory(
     // Fetch the created resource inventory
     get_resource_inventory_by_id(pool, id).await
 }
+
+/// Update a resource inventory
+///
+/// A lifecycle state change must be an allowed transition from the current
+/// state, otherwise a `Conflict` is returned. Successful changes publish a
+/// `ResourceStateChanged` event; retiring a resource also retires its inventory state.
+pub async fn update_resource_inventory(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    id: Uuid,
+    request: UpdateResourceInventoryRequest,
+) -> TmfResult<ResourceInventory> {
+    let current = get_resource_inventory_by_id(pool, id).await?;
+    let now = Utc::now();
+
+    let transition = match request.lifecycle_state {
+        Some(target) if target != current.lifecycle_state => {
+            validate_transition(current.lifecycle_state, target)?;
+            Some((current.lifecycle_state, target))
+        }
+        _ => None,
+    };
+    let lifecycle_state = transition
+        .map(|(_, to)| to)
+        .unwrap_or(current.lifecycle_state);
+    let state = if lifecycle_state == ResourceLifecycleState::Retired {
+        ResourceInventoryState::Retired
+    } else {
+        current.state
+    };
+
+    // Guard on the state we validated against so a concurrent transition is not overwritten
+    let result = sqlx::query(
+        "UPDATE resource_inventories SET
+         name = COALESCE($1, name),
+         description = COALESCE($2, description),
+         version = COALESCE($3, version),
+         resource_type = COALESCE($4, resource_type),
+         lifecycle_state = $5,
+         state = $6,
+         last_modified_date = $7,
+         last_update = CURRENT_TIMESTAMP
+         WHERE id = $8 AND lifecycle_state = $9",
+    )
+    .bind(&request.name)
+    .bind(&request.description)
+    .bind(&request.version)
+    .bind(&request.resource_type)
+    .bind(resource_lifecycle_state_to_string(&lifecycle_state))
+    .bind(resource_inventory_state_to_string(&state))
+    .bind(now)
+    .bind(id)
+    .bind(resource_lifecycle_state_to_string(&current.lifecycle_state))
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(TmfError::Conflict(format!(
+            "Resource inventory {} changed lifecycle state concurrently",
+            id
+        )));
+    }
+
+    if let Some((from_state, to_state)) = transition {
+        let change = ResourceStateChangeEvent {
+            resource_id: id,
+            from_state,
+            to_state,
+            changed_at: now,
+        };
+        let data = serde_json::to_value(&change).map_err(|e| TmfError::Internal(e.to_string()))?;
+        let event = EventEnvelope::new(
+            RESOURCE_STATE_CHANGED_EVENT.to_string(),
+            "tmf639-resource-inventory".to_string(),
+            data,
+        );
+        if let Err(e) = publisher.publish(topics::RESOURCE_EVENTS, event).await {
+            log::warn!(
+                "Failed to publish state change event for resource inventory {}: {}",
+                id,
+                e
+            );
+        }
+    }
+
+    get_resource_inventory_by_id(pool, id).await
+}
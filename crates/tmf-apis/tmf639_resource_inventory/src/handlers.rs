@@ -3,8 +3,11 @@
 use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
+use crate::reconciliation;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
@@ -100,3 +103,116 @@ pub async fn create_resource_inventory(
         }))),
     }
 }
+
+/// Update a resource inventory
+#[utoipa::path(
+    patch,
+    path = "/tmf-api/resourceInventoryManagement/v4/resourceInventory/{id}",
+    request_body = UpdateResourceInventoryRequest,
+    responses(
+        (status = 200, description = "Resource inventory updated", body = ResourceInventory),
+        (status = 404, description = "Resource inventory not found"),
+        (status = 409, description = "Illegal lifecycle state transition"),
+        (status = 400, description = "Invalid resource inventory ID or lifecycle state"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Resource Inventory ID (UUID)")
+    ),
+    tag = "TMF639"
+)]
+pub async fn update_resource_inventory(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<UpdateResourceInventoryRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid resource inventory ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let publisher = Arc::clone(publisher.get_ref());
+    match db::update_resource_inventory(pool.get_ref(), publisher.as_ref(), id, body.into_inner())
+        .await
+    {
+        Ok(inventory) => Ok(HttpResponse::Ok().json(inventory)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Conflict(msg)) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Compare the resource inventory with a live network discovery snapshot
+///
+/// Reports discrepancies with suggested corrections; the inventory is not
+/// changed.
+#[utoipa::path(
+    post,
+    path = "/tmf-api/resourceInventoryManagement/v4/resourceReconciliation",
+    request_body = DiscoverySnapshot,
+    responses(
+        (status = 200, description = "Reconciliation report", body = ReconciliationReport),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF639"
+)]
+pub async fn reconcile_resource_inventory(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<DiscoverySnapshot>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match reconciliation::reconcile_inventory(pool.get_ref(), &body).await {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Apply selected reconciliation corrections to the resource inventory
+#[utoipa::path(
+    post,
+    path = "/tmf-api/resourceInventoryManagement/v4/resourceReconciliation/apply",
+    request_body = ApplyCorrectionsRequest,
+    responses(
+        (status = 200, description = "Outcome of each correction", body = Vec<CorrectionOutcome>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF639"
+)]
+pub async fn apply_reconciliation_corrections(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<ApplyCorrectionsRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let publisher = Arc::clone(publisher.get_ref());
+    let outcomes = reconciliation::apply_corrections(
+        pool.get_ref(),
+        publisher.as_ref(),
+        body.into_inner().corrections,
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(outcomes))
+}
@@ -0,0 +1,43 @@
+//! Resource lifecycle state machine for TMF639
+//!
+//! Resources move planned → installed → operating, may drop to degraded and
+//! recover to operating, and can be retired from any state. Retired is final.
+
+use crate::models::ResourceLifecycleState;
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Event type published on the resource events topic for lifecycle changes
+pub const RESOURCE_STATE_CHANGED_EVENT: &str = "ResourceStateChanged";
+
+impl ResourceLifecycleState {
+    /// States reachable from this state in a single transition
+    pub fn allowed_transitions(&self) -> &'static [ResourceLifecycleState] {
+        use ResourceLifecycleState::*;
+        match self {
+            Planned => &[Installed, Retired],
+            Installed => &[Operating, Retired],
+            Operating => &[Degraded, Retired],
+            Degraded => &[Operating, Retired],
+            Retired => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, target: ResourceLifecycleState) -> bool {
+        self.allowed_transitions().contains(&target)
+    }
+}
+
+/// Check a lifecycle transition, rejecting illegal ones as a conflict
+pub fn validate_transition(
+    from: ResourceLifecycleState,
+    to: ResourceLifecycleState,
+) -> TmfResult<()> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(TmfError::Conflict(format!(
+            "Illegal resource lifecycle transition from {:?} to {:?}",
+            from, to
+        )))
+    }
+}
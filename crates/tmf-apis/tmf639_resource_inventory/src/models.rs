@@ -17,13 +17,26 @@ pub enum ResourceInventoryState {
     Retired,
 }
 
+/// Resource Lifecycle State - Operational lifecycle of a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResourceLifecycleState {
+    Planned,
+    Installed,
+    Operating,
+    Degraded,
+    Retired,
+}
+
 /// Resource Inventory - Represents inventory of physical/virtual network resources
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ResourceInventory {
     #[serde(flatten)]
     pub base: BaseEntity,
-    /// Resource inventory state
+    /// Resource inventory state; `Retired` exactly when the lifecycle state is
     pub state: ResourceInventoryState,
+    /// Resource lifecycle state, changed through validated transitions
+    pub lifecycle_state: ResourceLifecycleState,
     /// Resource specification reference
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resource_specification: Option<ResourceSpecificationRef>,
@@ -105,3 +118,138 @@ pub struct CreateRelatedPartyRequest {
     pub name: String,
     pub role: String,
 }
+
+/// Request to update a resource inventory
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateResourceInventoryRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    /// Target lifecycle state; must be an allowed transition from the current state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle_state: Option<ResourceLifecycleState>,
+}
+
+/// Resource State Change Event - Emitted when a resource changes lifecycle state
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResourceStateChangeEvent {
+    #[schema(value_type = String, format = "uuid")]
+    pub resource_id: Uuid,
+    pub from_state: ResourceLifecycleState,
+    pub to_state: ResourceLifecycleState,
+    #[schema(value_type = String, format = "date-time")]
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Discovered Resource - A resource found deployed in the live network
+///
+/// Attributes the discovery did not report are left unset and not compared.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DiscoveredResource {
+    /// Name identifying the resource, matched against inventory names
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle_state: Option<ResourceLifecycleState>,
+}
+
+/// Discovery Snapshot - Resources found in the live network at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DiscoverySnapshot {
+    pub resources: Vec<DiscoveredResource>,
+}
+
+/// Discrepancy Kind - How inventory and network disagree about a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DiscrepancyKind {
+    /// In inventory but not found in the network
+    MissingFromNetwork,
+    /// Found in the network but not in inventory
+    MissingFromInventory,
+    /// In both, with differing attributes
+    AttributeMismatch,
+}
+
+/// Attribute Mismatch - An attribute recorded differently than discovered
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttributeMismatch {
+    pub attribute: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_value: Option<String>,
+    pub network_value: String,
+}
+
+/// Resource Correction - Change bringing inventory in line with the network
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResourceCorrection {
+    /// Record a resource found in the network
+    Create {
+        request: CreateResourceInventoryRequest,
+    },
+    /// Update a record to the discovered attributes
+    Update {
+        #[schema(value_type = String, format = "uuid")]
+        inventory_id: Uuid,
+        request: UpdateResourceInventoryRequest,
+    },
+    /// Retire a record no longer deployed
+    Retire {
+        #[schema(value_type = String, format = "uuid")]
+        inventory_id: Uuid,
+    },
+}
+
+/// Resource Discrepancy - A disagreement with its suggested correction
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResourceDiscrepancy {
+    pub kind: DiscrepancyKind,
+    pub name: String,
+    /// Inventory record, absent for resources missing from inventory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub inventory_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<AttributeMismatch>,
+    pub suggested_correction: ResourceCorrection,
+}
+
+/// Reconciliation Report - Discrepancies between inventory and a discovery snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReconciliationReport {
+    /// Inventory records compared
+    pub inventory_count: usize,
+    /// Discovered resources compared
+    pub discovered_count: usize,
+    pub discrepancies: Vec<ResourceDiscrepancy>,
+    #[schema(value_type = String, format = "date-time")]
+    pub reconciled_at: DateTime<Utc>,
+}
+
+/// Request to apply reconciliation corrections
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApplyCorrectionsRequest {
+    pub corrections: Vec<ResourceCorrection>,
+}
+
+/// Correction Outcome - Result of applying one correction
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CorrectionOutcome {
+    pub correction: ResourceCorrection,
+    pub applied: bool,
+    /// Record created or updated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub inventory_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
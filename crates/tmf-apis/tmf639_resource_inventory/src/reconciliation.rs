@@ -0,0 +1,207 @@
+//! Reconciliation of resource inventory with the live network
+//!
+//! Inventory records are compared against a snapshot of the resources
+//! discovered in the network, matched by name. Each discrepancy comes with a
+//! suggested correction, but reconciling never changes the inventory: the
+//! corrections to keep are applied in a separate, explicit step.
+
+use crate::db::{self, resource_lifecycle_state_to_string};
+use crate::models::{
+    AttributeMismatch, CorrectionOutcome, CreateResourceInventoryRequest, DiscoverySnapshot,
+    DiscrepancyKind, ReconciliationReport, ResourceCorrection, ResourceDiscrepancy,
+    ResourceInventory, ResourceLifecycleState, UpdateResourceInventoryRequest,
+};
+use bss_oss_event_bus::EventPublisher;
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use tmf_apis_core::TmfResult;
+
+/// Compare the resource inventory with a discovery snapshot
+pub async fn reconcile_inventory(
+    pool: &Pool<Postgres>,
+    snapshot: &DiscoverySnapshot,
+) -> TmfResult<ReconciliationReport> {
+    let inventory = db::get_resource_inventories(pool).await?;
+    let report = reconcile(&inventory, snapshot);
+    log::info!(
+        "Reconciled {} inventory records with {} discovered resources: {} discrepancies",
+        report.inventory_count,
+        report.discovered_count,
+        report.discrepancies.len()
+    );
+    Ok(report)
+}
+
+/// Compare inventory records with a discovery snapshot
+///
+/// Planned and retired records missing from the network are expected and not
+/// reported. When several records share a name only the first is matched, so
+/// the duplicates are reported as missing from the network. Lifecycle
+/// corrections must be allowed transitions to apply successfully.
+pub fn reconcile(
+    inventory: &[ResourceInventory],
+    snapshot: &DiscoverySnapshot,
+) -> ReconciliationReport {
+    let mut discovered = HashMap::new();
+    for resource in &snapshot.resources {
+        discovered.entry(resource.name.trim()).or_insert(resource);
+    }
+
+    let mut discrepancies = Vec::new();
+    for record in inventory {
+        let Some(resource) = discovered.remove(record.base.name.trim()) else {
+            if !matches!(
+                record.lifecycle_state,
+                ResourceLifecycleState::Planned | ResourceLifecycleState::Retired
+            ) {
+                discrepancies.push(ResourceDiscrepancy {
+                    kind: DiscrepancyKind::MissingFromNetwork,
+                    name: record.base.name.clone(),
+                    inventory_id: Some(record.base.id),
+                    mismatches: Vec::new(),
+                    suggested_correction: ResourceCorrection::Retire {
+                        inventory_id: record.base.id,
+                    },
+                });
+            }
+            continue;
+        };
+
+        let mut mismatches = Vec::new();
+        let mut update = UpdateResourceInventoryRequest {
+            name: None,
+            description: None,
+            version: None,
+            resource_type: None,
+            lifecycle_state: None,
+        };
+        if let Some(ref resource_type) = resource.resource_type {
+            if record.resource_type.as_ref() != Some(resource_type) {
+                mismatches.push(AttributeMismatch {
+                    attribute: "resource_type".to_string(),
+                    inventory_value: record.resource_type.clone(),
+                    network_value: resource_type.clone(),
+                });
+                update.resource_type = Some(resource_type.clone());
+            }
+        }
+        if let Some(ref version) = resource.version {
+            if record.base.version.as_ref() != Some(version) {
+                mismatches.push(AttributeMismatch {
+                    attribute: "version".to_string(),
+                    inventory_value: record.base.version.clone(),
+                    network_value: version.clone(),
+                });
+                update.version = Some(version.clone());
+            }
+        }
+        if let Some(lifecycle_state) = resource.lifecycle_state {
+            if record.lifecycle_state != lifecycle_state {
+                mismatches.push(AttributeMismatch {
+                    attribute: "lifecycle_state".to_string(),
+                    inventory_value: Some(resource_lifecycle_state_to_string(
+                        &record.lifecycle_state,
+                    )),
+                    network_value: resource_lifecycle_state_to_string(&lifecycle_state),
+                });
+                update.lifecycle_state = Some(lifecycle_state);
+            }
+        }
+        if !mismatches.is_empty() {
+            discrepancies.push(ResourceDiscrepancy {
+                kind: DiscrepancyKind::AttributeMismatch,
+                name: record.base.name.clone(),
+                inventory_id: Some(record.base.id),
+                mismatches,
+                suggested_correction: ResourceCorrection::Update {
+                    inventory_id: record.base.id,
+                    request: update,
+                },
+            });
+        }
+    }
+
+    // Keep the snapshot's order for resources not in inventory
+    for resource in &snapshot.resources {
+        if discovered.remove(resource.name.trim()).is_none() {
+            continue;
+        }
+        discrepancies.push(ResourceDiscrepancy {
+            kind: DiscrepancyKind::MissingFromInventory,
+            name: resource.name.clone(),
+            inventory_id: None,
+            mismatches: Vec::new(),
+            suggested_correction: ResourceCorrection::Create {
+                request: CreateResourceInventoryRequest {
+                    name: resource.name.trim().to_string(),
+                    description: None,
+                    version: resource.version.clone(),
+                    resource_type: resource.resource_type.clone(),
+                    resource_specification_id: None,
+                    resource_id: None,
+                    related_party: None,
+                },
+            },
+        });
+    }
+
+    ReconciliationReport {
+        inventory_count: inventory.len(),
+        discovered_count: snapshot.resources.len(),
+        discrepancies,
+        reconciled_at: Utc::now(),
+    }
+}
+
+/// Apply reconciliation corrections one by one
+///
+/// A failing correction does not stop the others; each outcome reports
+/// whether it was applied. Lifecycle changes publish `ResourceStateChanged`
+/// events like any other update.
+pub async fn apply_corrections(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    corrections: Vec<ResourceCorrection>,
+) -> Vec<CorrectionOutcome> {
+    let mut outcomes = Vec::with_capacity(corrections.len());
+    for correction in corrections {
+        let result = match correction.clone() {
+            ResourceCorrection::Create { request } => {
+                db::create_resource_inventory(pool, request).await
+            }
+            ResourceCorrection::Update {
+                inventory_id,
+                request,
+            } => db::update_resource_inventory(pool, publisher, inventory_id, request).await,
+            ResourceCorrection::Retire { inventory_id } => {
+                let request = UpdateResourceInventoryRequest {
+                    name: None,
+                    description: None,
+                    version: None,
+                    resource_type: None,
+                    lifecycle_state: Some(ResourceLifecycleState::Retired),
+                };
+                db::update_resource_inventory(pool, publisher, inventory_id, request).await
+            }
+        };
+        outcomes.push(match result {
+            Ok(inventory) => CorrectionOutcome {
+                correction,
+                applied: true,
+                inventory_id: Some(inventory.base.id),
+                error: None,
+            },
+            Err(e) => {
+                log::warn!("Failed to apply reconciliation correction: {}", e);
+                CorrectionOutcome {
+                    correction,
+                    applied: false,
+                    inventory_id: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        });
+    }
+    outcomes
+}
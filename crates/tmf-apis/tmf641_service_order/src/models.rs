@@ -99,6 +99,54 @@ pub struct RelatedParty {
     pub role: String,
 }
 
+/// Service Order Milestone Status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServiceOrderMilestoneStatus {
+    /// Not reached yet, on track
+    Pending,
+    /// Not reached yet and close to its expected date
+    AtRisk,
+    /// Not reached by its expected date
+    Missed,
+    /// Reached
+    Completed,
+}
+
+/// Service Order Milestone - Expected progress point of a service order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ServiceOrderMilestone {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    pub name: String,
+    /// Date by which the milestone is expected to be reached
+    #[schema(value_type = String, format = "date-time")]
+    pub expected_date: DateTime<Utc>,
+    /// Date the milestone was reached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub completed_date: Option<DateTime<Utc>>,
+    pub status: ServiceOrderMilestoneStatus,
+    /// Raised while the milestone is at risk or missed
+    pub jeopardy: bool,
+}
+
+/// Jeopardy Alert - Raised when a service order milestone falls behind
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ServiceOrderJeopardyAlert {
+    #[schema(value_type = String, format = "uuid")]
+    pub service_order_id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub milestone_id: Uuid,
+    pub milestone_name: String,
+    /// Milestone status that raised the alert (at risk or missed)
+    pub status: ServiceOrderMilestoneStatus,
+    #[schema(value_type = String, format = "date-time")]
+    pub expected_date: DateTime<Utc>,
+    #[schema(value_type = String, format = "date-time")]
+    pub alert_date: DateTime<Utc>,
+}
+
 /// Request to create a service order
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateServiceOrderRequest {
@@ -12,6 +12,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_resource_orders))
                     .route(web::post().to(create_resource_order)),
             )
+            .service(
+                web::resource("/resourceOrder/feasibilityCheck")
+                    .route(web::post().to(check_resource_order_feasibility)),
+            )
             .service(
                 web::resource("/resourceOrder/{id}").route(web::get().to(get_resource_order_by_id)),
             ),
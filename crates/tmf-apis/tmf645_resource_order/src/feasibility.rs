@@ -0,0 +1,127 @@
+//! Resource order feasibility pre-check
+//!
+//! Verifies that a resource order can be fulfilled before it is accepted: item
+//! actions must be known, items changing an existing resource must reference
+//! it, and the capacity the items need must be available in resource
+//! management. Capacity needed by several items on the same resource is
+//! added up, so an order cannot claim the same headroom twice.
+//!
+//! The check does not reserve anything; capacity may still be taken by other
+//! orders before this one is fulfilled.
+
+use crate::models::{
+    CreateResourceOrderRequest, ResourceOrderFeasibility, ResourceOrderItemFeasibility,
+};
+use bss_oss_resource_management::get_available_capacity;
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+/// Item actions of TMF645
+const ITEM_ACTIONS: [&str; 4] = ["add", "modify", "delete", "nochange"];
+
+/// Check whether a resource order can be fulfilled
+pub async fn check_feasibility(
+    pool: &Pool<Postgres>,
+    order: &CreateResourceOrderRequest,
+) -> TmfResult<ResourceOrderFeasibility> {
+    let mut available = HashMap::new();
+    for item in order.order_item.iter().flatten() {
+        let Some(resource_id) = item.resource_id else {
+            continue;
+        };
+        for requirement in item.capacity_requirement.iter().flatten() {
+            let key = (resource_id, requirement.capacity_type.clone());
+            if available.contains_key(&key) {
+                continue;
+            }
+            let capacity = get_available_capacity(pool, resource_id, &requirement.capacity_type)
+                .await
+                .map_err(|e| TmfError::Database(e.to_string()))?;
+            available.insert(key, capacity);
+        }
+    }
+
+    Ok(assess_feasibility(order, &available))
+}
+
+/// Assess an order against the available capacity per resource and type
+/// (`None` when the resource does not track the type)
+fn assess_feasibility(
+    order: &CreateResourceOrderRequest,
+    available: &HashMap<(Uuid, String), Option<f64>>,
+) -> ResourceOrderFeasibility {
+    let mut demand: HashMap<(Uuid, String), f64> = HashMap::new();
+    let mut order_item = Vec::new();
+
+    for (index, item) in order.order_item.iter().flatten().enumerate() {
+        let mut reasons = Vec::new();
+        let action = item.action.to_lowercase();
+
+        if !ITEM_ACTIONS.contains(&action.as_str()) {
+            reasons.push(format!("Unknown action '{}'", item.action));
+        }
+        if (action == "modify" || action == "delete") && item.resource_id.is_none() {
+            reasons.push(format!("Action '{}' needs a resource_id", item.action));
+        }
+        if item.quantity.is_some_and(|quantity| quantity <= 0) {
+            reasons.push("Quantity must be positive".to_string());
+        }
+
+        for requirement in item.capacity_requirement.iter().flatten() {
+            if !(requirement.amount.is_finite() && requirement.amount > 0.0) {
+                reasons.push(format!(
+                    "{} capacity requirement must be a positive amount",
+                    requirement.capacity_type
+                ));
+                continue;
+            }
+            let Some(resource_id) = item.resource_id else {
+                reasons.push(format!(
+                    "{} capacity requirement needs a resource_id",
+                    requirement.capacity_type
+                ));
+                continue;
+            };
+
+            let key = (resource_id, requirement.capacity_type.clone());
+            let requested = demand.entry(key.clone()).or_default();
+            *requested += requirement.amount;
+            match available.get(&key).copied().flatten() {
+                None => reasons.push(format!(
+                    "Resource {} has no {} capacity",
+                    resource_id, requirement.capacity_type
+                )),
+                Some(available) if *requested > available => reasons.push(format!(
+                    "Insufficient {} capacity on resource {}: {} requested by the order, {} available",
+                    requirement.capacity_type, resource_id, requested, available
+                )),
+                Some(_) => {}
+            }
+        }
+
+        order_item.push(ResourceOrderItemFeasibility {
+            index,
+            feasible: reasons.is_empty(),
+            reasons,
+        });
+    }
+
+    let reasons: Vec<String> = order_item
+        .iter()
+        .flat_map(|item| {
+            item.reasons
+                .iter()
+                .map(move |reason| format!("Item {}: {}", item.index, reason))
+        })
+        .collect();
+
+    ResourceOrderFeasibility {
+        feasible: reasons.is_empty(),
+        reasons,
+        order_item,
+        checked_at: Utc::now(),
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::auth::validate_token;
 use crate::db;
+use crate::feasibility::check_feasibility;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
@@ -82,7 +83,8 @@ pub async fn get_resource_order_by_id(
     responses(
         (status = 201, description = "Resource order created", body = ResourceOrder),
         (status = 400, description = "Invalid request"),
-        (status = 401, description = "Unauthorized")
+        (status = 401, description = "Unauthorized"),
+        (status = 422, description = "Resource order cannot be fulfilled", body = ResourceOrderFeasibility)
     ),
     tag = "TMF645"
 )]
@@ -93,10 +95,49 @@ pub async fn create_resource_order(
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_resource_order(pool.get_ref(), body.into_inner()).await {
+    let order = body.into_inner();
+    match check_feasibility(pool.get_ref(), &order).await {
+        Ok(feasibility) if !feasibility.feasible => {
+            return Ok(HttpResponse::UnprocessableEntity().json(feasibility));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": e.to_string()
+            })));
+        }
+    }
+
+    match db::create_resource_order(pool.get_ref(), order).await {
         Ok(order) => Ok(HttpResponse::Created().json(order)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
     }
 }
+
+/// Check whether a resource order can be fulfilled, without creating it
+#[utoipa::path(
+    post,
+    path = "/tmf-api/resourceOrderingManagement/v4/resourceOrder/feasibilityCheck",
+    request_body = CreateResourceOrderRequest,
+    responses(
+        (status = 200, description = "Feasibility verdict", body = ResourceOrderFeasibility),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF645"
+)]
+pub async fn check_resource_order_feasibility(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<CreateResourceOrderRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match check_feasibility(pool.get_ref(), &body).await {
+        Ok(feasibility) => Ok(HttpResponse::Ok().json(feasibility)),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
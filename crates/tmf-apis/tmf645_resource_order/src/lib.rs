@@ -6,10 +6,12 @@
 pub mod api;
 pub mod auth;
 pub mod db;
+pub mod feasibility;
 pub mod handlers;
 pub mod models;
 
 pub use auth::*;
+pub use feasibility::check_feasibility;
 pub use handlers::*;
 pub use models::*;
 
@@ -131,6 +131,41 @@ pub struct CreateResourceOrderItemRequest {
     pub resource_id: Option<Uuid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<i32>,
+    /// Capacity the item needs on the resource identified by `resource_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_requirement: Option<Vec<CapacityRequirement>>,
+}
+
+/// Capacity of one type needed by a resource order item
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CapacityRequirement {
+    /// Capacity type as tracked by resource management (e.g., "BANDWIDTH")
+    pub capacity_type: String,
+    /// Amount needed, in the capacity's unit
+    pub amount: f64,
+}
+
+/// Feasibility verdict for a resource order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResourceOrderFeasibility {
+    /// Whether every item can be fulfilled
+    pub feasible: bool,
+    /// Reasons the order cannot be fulfilled
+    pub reasons: Vec<String>,
+    /// Verdict per order item, in request order
+    pub order_item: Vec<ResourceOrderItemFeasibility>,
+    #[schema(value_type = String, format = "date-time")]
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Feasibility verdict for a resource order item
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResourceOrderItemFeasibility {
+    /// Position of the item in the order (0-based)
+    pub index: usize,
+    pub feasible: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
 }
 
 /// Request to create a related party
@@ -17,6 +17,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_network_slice_by_id))
                     .route(web::patch().to(update_network_slice))
                     .route(web::delete().to(delete_network_slice)),
+            )
+            .service(web::resource("/sliceTemplate").route(web::get().to(get_slice_templates)))
+            .service(
+                web::resource("/sliceTemplate/{id}").route(web::get().to(get_slice_template_by_id)),
+            )
+            .service(
+                web::resource("/sliceTemplate/{id}/networkSlice")
+                    .route(web::post().to(instantiate_slice_template)),
             ),
     );
 }
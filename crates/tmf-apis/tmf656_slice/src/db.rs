@@ -1,6 +1,13 @@
 //! Database operations for TMF656 Slice Management
 
-use crate::models::{CreateNetworkSliceRequest, NetworkSlice, SliceState, SliceType};
+use crate::lifecycle::{validate_transition, SLICE_TERMINATED_EVENT};
+use crate::models::{
+    CreateNetworkSliceRequest, InstantiateSliceTemplateRequest, NetworkSlice, SLAParameters,
+    SliceState, SliceTerminationEvent, SliceType,
+};
+use crate::templates::find_template;
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -16,6 +23,7 @@ fn parse_slice_state(s: &str) -> SliceState {
     match s.to_uppercase().as_str() {
         "PLANNED" => SliceState::Planned,
         "ACTIVE" => SliceState::Active,
+        "MODIFYING" => SliceState::Modifying,
         "INACTIVE" => SliceState::Inactive,
         "TERMINATED" => SliceState::Terminated,
         _ => SliceState::Planned,
@@ -27,6 +35,7 @@ fn slice_state_to_string(state: &SliceState) -> String {
     match state {
         SliceState::Planned => "PLANNED".to_string(),
         SliceState::Active => "ACTIVE".to_string(),
+        SliceState::Modifying => "MODIFYING".to_string(),
         SliceState::Inactive => "INACTIVE".to_string(),
         SliceState::Terminated => "TERMINATED".to_string(),
     }
@@ -59,7 +68,7 @@ fn slice_type_to_string(slice_type: &SliceType) -> String {
 pub async fn get_network_slices(pool: &Pool<Postgres>) -> TmfResult<Vec<NetworkSlice>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, slice_type, 
-         activation_date, termination_date, href, last_update
+         activation_date, termination_date, href, last_update, template_id
          FROM network_slices ORDER BY activation_date DESC",
     )
     .fetch_all(pool)
@@ -85,6 +94,7 @@ pub async fn get_network_slices(pool: &Pool<Postgres>) -> TmfResult<Vec<NetworkS
             network_functions: None, // Load separately if needed
             activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
             termination_date: row.get::<Option<DateTime<Utc>>, _>("termination_date"),
+            template_id: row.get::<Option<String>, _>("template_id"),
         });
     }
 
@@ -95,7 +105,7 @@ pub async fn get_network_slices(pool: &Pool<Postgres>) -> TmfResult<Vec<NetworkS
 pub async fn get_network_slice_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<NetworkSlice> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, slice_type, 
-         activation_date, termination_date, href, last_update
+         activation_date, termination_date, href, last_update, template_id
          FROM network_slices WHERE id = $1",
     )
     .bind(id)
@@ -104,6 +114,8 @@ pub async fn get_network_slice_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResu
     .map_err(map_sqlx_error)?
     .ok_or_else(|| TmfError::NotFound(format!("Network slice with id {} not found", id)))?;
 
+    let sla_parameters = get_sla_parameters(pool, id).await?;
+
     Ok(NetworkSlice {
         base: tmf_apis_core::BaseEntity {
             id: row.get::<Uuid, _>("id"),
@@ -117,25 +129,72 @@ pub async fn get_network_slice_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResu
         },
         state: parse_slice_state(&row.get::<String, _>("state")),
         slice_type: parse_slice_type(&row.get::<String, _>("slice_type")),
-        sla_parameters: None,    // Load separately if needed
+        sla_parameters,
         network_functions: None, // Load separately if needed
         activation_date: row.get::<Option<DateTime<Utc>>, _>("activation_date"),
         termination_date: row.get::<Option<DateTime<Utc>>, _>("termination_date"),
+        template_id: row.get::<Option<String>, _>("template_id"),
     })
 }
 
+/// Get the SLA parameters of a network slice
+async fn get_sla_parameters(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Option<SLAParameters>> {
+    let row = sqlx::query(
+        "SELECT max_latency_ms, min_throughput_mbps, max_devices, coverage_area
+         FROM network_slice_sla_parameters WHERE network_slice_id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(row.map(|row| SLAParameters {
+        max_latency_ms: row
+            .get::<Option<i32>, _>("max_latency_ms")
+            .map(|v| v as u32),
+        min_throughput_mbps: row
+            .get::<Option<i32>, _>("min_throughput_mbps")
+            .map(|v| v as u32),
+        max_devices: row.get::<Option<i32>, _>("max_devices").map(|v| v as u32),
+        coverage_area: row.get::<Option<String>, _>("coverage_area"),
+    }))
+}
+
 /// Create a new network slice
 pub async fn create_network_slice(
     pool: &Pool<Postgres>,
     request: CreateNetworkSliceRequest,
+) -> TmfResult<NetworkSlice> {
+    insert_network_slice(pool, request, None).await
+}
+
+/// Create a network slice pre-filled from a template
+///
+/// SLA overrides must fall within the template's allowed ranges.
+pub async fn instantiate_from_template(
+    pool: &Pool<Postgres>,
+    template_id: &str,
+    overrides: InstantiateSliceTemplateRequest,
+) -> TmfResult<NetworkSlice> {
+    let template = find_template(template_id)?;
+    let request = template.apply(overrides)?;
+    insert_network_slice(pool, request, Some(&template.id)).await
+}
+
+async fn insert_network_slice(
+    pool: &Pool<Postgres>,
+    request: CreateNetworkSliceRequest,
+    template_id: Option<&str>,
 ) -> TmfResult<NetworkSlice> {
     let id = Uuid::new_v4();
     let href = Some(format!("/tmf-api/sliceManagement/v4/networkSlice/{}", id));
 
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
     sqlx::query(
         "INSERT INTO network_slices (id, name, description, version, state, slice_type, 
-         activation_date, href)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+         activation_date, href, template_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
     )
     .bind(id)
     .bind(&request.name)
@@ -145,38 +204,148 @@ pub async fn create_network_slice(
     .bind(slice_type_to_string(&request.slice_type))
     .bind(request.activation_date)
     .bind(&href)
-    .execute(pool)
+    .bind(template_id)
+    .execute(&mut *tx)
     .await
     .map_err(map_sqlx_error)?;
 
+    if let Some(sla) = &request.sla_parameters {
+        sqlx::query(
+            "INSERT INTO network_slice_sla_parameters (id, network_slice_id, max_latency_ms,
+             min_throughput_mbps, max_devices, coverage_area)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(id)
+        .bind(sla.max_latency_ms.map(|v| v as i32))
+        .bind(sla.min_throughput_mbps.map(|v| v as i32))
+        .bind(sla.max_devices.map(|v| v as i32))
+        .bind(&sla.coverage_area)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+
+    tx.commit().await.map_err(map_sqlx_error)?;
+
     // Fetch the created network slice
     get_network_slice_by_id(pool, id).await
 }
 
 /// Update a network slice
+///
+/// A state change must be an allowed lifecycle transition, and a terminated
+/// slice cannot be changed at all; both are rejected as a `Conflict`.
+/// Terminating a slice releases the resource reservations made for it and
+/// publishes a `NetworkSliceTerminated` event.
 pub async fn update_network_slice(
     pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
     id: Uuid,
     state: Option<SliceState>,
     activation_date: Option<DateTime<Utc>>,
     termination_date: Option<DateTime<Utc>>,
 ) -> TmfResult<NetworkSlice> {
-    sqlx::query(
+    let current = get_network_slice_by_id(pool, id).await?;
+    if current.state == SliceState::Terminated {
+        return Err(TmfError::Conflict(format!(
+            "Network slice {} is terminated and cannot be changed",
+            id
+        )));
+    }
+
+    let target = match state {
+        Some(target) if target != current.state => {
+            validate_transition(current.state, target)?;
+            target
+        }
+        _ => current.state,
+    };
+    let terminating = target == SliceState::Terminated;
+    let now = Utc::now();
+    let termination_date = if terminating {
+        termination_date.or(Some(now))
+    } else {
+        termination_date
+    };
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
+    // Guard on the state we validated against so a concurrent transition is not overwritten
+    let result = sqlx::query(
         "UPDATE network_slices SET 
-         state = COALESCE($1, state), 
+         state = $1, 
          activation_date = COALESCE($2, activation_date),
          termination_date = COALESCE($3, termination_date),
          last_update = CURRENT_TIMESTAMP
-         WHERE id = $4",
+         WHERE id = $4 AND state = $5",
     )
-    .bind(state.as_ref().map(slice_state_to_string))
+    .bind(slice_state_to_string(&target))
     .bind(activation_date)
     .bind(termination_date)
     .bind(id)
-    .execute(pool)
+    .bind(slice_state_to_string(&current.state))
+    .execute(&mut *tx)
     .await
     .map_err(map_sqlx_error)?;
 
+    if result.rows_affected() == 0 {
+        return Err(TmfError::Conflict(format!(
+            "Network slice {} changed state concurrently",
+            id
+        )));
+    }
+
+    let released_reservation_ids: Vec<Uuid> = if terminating {
+        sqlx::query_scalar(
+            "UPDATE resource_reservations SET
+             reservation_status = 'CANCELLED',
+             cancelled_at = $2,
+             cancellation_reason = 'Network slice terminated',
+             updated_at = $2
+             WHERE network_slice_id = $1
+             AND reservation_status IN ('PENDING', 'CONFIRMED', 'ACTIVE')
+             RETURNING id",
+        )
+        .bind(id)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?
+    } else {
+        Vec::new()
+    };
+
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    if terminating {
+        log::info!(
+            "Network slice {} terminated, released {} resource reservation(s)",
+            id,
+            released_reservation_ids.len()
+        );
+        let termination = SliceTerminationEvent {
+            slice_id: id,
+            from_state: current.state,
+            released_reservation_ids,
+            terminated_at: termination_date.unwrap_or(now),
+        };
+        let data =
+            serde_json::to_value(&termination).map_err(|e| TmfError::Internal(e.to_string()))?;
+        let event = EventEnvelope::new(
+            SLICE_TERMINATED_EVENT.to_string(),
+            "tmf656-slice".to_string(),
+            data,
+        );
+        if let Err(e) = publisher.publish(topics::RESOURCE_EVENTS, event).await {
+            log::warn!(
+                "Failed to publish termination event for network slice {}: {}",
+                id,
+                e
+            );
+        }
+    }
+
     // Fetch the updated network slice
     get_network_slice_by_id(pool, id).await
 }
@@ -3,8 +3,11 @@
 use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
+use crate::templates;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
@@ -109,6 +112,7 @@ pub async fn create_network_slice(
     responses(
         (status = 200, description = "Network slice updated", body = NetworkSlice),
         (status = 404, description = "Network slice not found"),
+        (status = 409, description = "Illegal slice state transition or slice terminated"),
         (status = 400, description = "Invalid request"),
         (status = 401, description = "Unauthorized")
     ),
@@ -122,6 +126,7 @@ pub async fn update_network_slice(
     req: actix_web::HttpRequest,
     path: web::Path<String>,
     body: web::Json<UpdateNetworkSliceRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
@@ -134,10 +139,12 @@ pub async fn update_network_slice(
         }
     };
 
+    let publisher = Arc::clone(publisher.get_ref());
     match db::update_network_slice(
         pool.get_ref(),
+        publisher.as_ref(),
         id,
-        body.state.clone(),
+        body.state,
         body.activation_date,
         body.termination_date,
     )
@@ -147,6 +154,9 @@ pub async fn update_network_slice(
         Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
             "error": msg
         }))),
+        Err(TmfError::Conflict(msg)) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": msg
+        }))),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
@@ -194,3 +204,86 @@ pub async fn delete_network_slice(
         }))),
     }
 }
+
+/// List slice templates
+#[utoipa::path(
+    get,
+    path = "/tmf-api/sliceManagement/v4/sliceTemplate",
+    responses(
+        (status = 200, description = "List of slice templates", body = Vec<SliceTemplate>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF656"
+)]
+pub async fn get_slice_templates(req: actix_web::HttpRequest) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    Ok(HttpResponse::Ok().json(templates::builtin_templates()))
+}
+
+/// Get slice template by ID
+#[utoipa::path(
+    get,
+    path = "/tmf-api/sliceManagement/v4/sliceTemplate/{id}",
+    responses(
+        (status = 200, description = "Slice template found", body = SliceTemplate),
+        (status = 404, description = "Slice template not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Slice Template ID")
+    ),
+    tag = "TMF656"
+)]
+pub async fn get_slice_template_by_id(
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match templates::find_template(&path.into_inner()) {
+        Ok(template) => Ok(HttpResponse::Ok().json(template)),
+        Err(e) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Instantiate a network slice from a template
+#[utoipa::path(
+    post,
+    path = "/tmf-api/sliceManagement/v4/sliceTemplate/{id}/networkSlice",
+    request_body = InstantiateSliceTemplateRequest,
+    responses(
+        (status = 201, description = "Network slice created from template", body = NetworkSlice),
+        (status = 400, description = "Override outside the template's allowed range"),
+        (status = 404, description = "Slice template not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Slice Template ID")
+    ),
+    tag = "TMF656"
+)]
+pub async fn instantiate_slice_template(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<InstantiateSliceTemplateRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::instantiate_from_template(pool.get_ref(), &path.into_inner(), body.into_inner()).await
+    {
+        Ok(slice) => Ok(HttpResponse::Created().json(slice)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
@@ -7,7 +7,9 @@ pub mod api;
 pub mod auth;
 pub mod db;
 pub mod handlers;
+pub mod lifecycle;
 pub mod models;
+pub mod templates;
 
 pub use auth::*;
 pub use handlers::*;
@@ -0,0 +1,42 @@
+//! Network slice lifecycle state machine for TMF656
+//!
+//! Slices move planned → active, and from active into modifying while their
+//! configuration changes and back again when it is applied. Active slices may
+//! be deactivated and reactivated. Every state but terminated can terminate,
+//! and terminated is final.
+
+use crate::models::SliceState;
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Event type published on the resource events topic when a slice terminates
+pub const SLICE_TERMINATED_EVENT: &str = "NetworkSliceTerminated";
+
+impl SliceState {
+    /// States reachable from this state in a single transition
+    pub fn allowed_transitions(&self) -> &'static [SliceState] {
+        use SliceState::*;
+        match self {
+            Planned => &[Active, Terminated],
+            Active => &[Modifying, Inactive, Terminated],
+            Modifying => &[Active, Terminated],
+            Inactive => &[Active, Terminated],
+            Terminated => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, target: SliceState) -> bool {
+        self.allowed_transitions().contains(&target)
+    }
+}
+
+/// Check a lifecycle transition, rejecting illegal ones as a conflict
+pub fn validate_transition(from: SliceState, to: SliceState) -> TmfResult<()> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(TmfError::Conflict(format!(
+            "Illegal network slice transition from {:?} to {:?}",
+            from, to
+        )))
+    }
+}
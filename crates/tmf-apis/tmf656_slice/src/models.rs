@@ -6,12 +6,13 @@ use tmf_apis_core::BaseEntity;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-/// Slice State
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+/// Slice State - Lifecycle state of a network slice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SliceState {
     Planned,
     Active,
+    Modifying,
     Inactive,
     Terminated,
 }
@@ -49,6 +50,9 @@ pub struct NetworkSlice {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(value_type = String, format = "date-time")]
     pub termination_date: Option<DateTime<Utc>>,
+    /// Template the slice was instantiated from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
 }
 
 /// SLA Parameters
@@ -124,6 +128,7 @@ pub struct CreateNetworkFunctionRefRequest {
 /// Request to update a network slice
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateNetworkSliceRequest {
+    /// Target state; must be an allowed transition from the current state
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<SliceState>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -133,3 +138,78 @@ pub struct UpdateNetworkSliceRequest {
     #[schema(value_type = String, format = "date-time")]
     pub termination_date: Option<DateTime<Utc>>,
 }
+
+/// Slice Termination Event - Emitted when a network slice terminates
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SliceTerminationEvent {
+    #[schema(value_type = String, format = "uuid")]
+    pub slice_id: Uuid,
+    pub from_state: SliceState,
+    /// Resource reservations released with the slice
+    #[schema(value_type = Vec<String>)]
+    pub released_reservation_ids: Vec<Uuid>,
+    #[schema(value_type = String, format = "date-time")]
+    pub terminated_at: DateTime<Utc>,
+}
+
+/// Allowed range for a template KPI (inclusive)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct KpiRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl KpiRange {
+    pub fn contains(&self, value: u32) -> bool {
+        value >= self.min && value <= self.max
+    }
+}
+
+/// Allowed ranges for overriding a template's SLA parameters
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SliceTemplateRanges {
+    pub max_latency_ms: KpiRange,
+    pub min_throughput_mbps: KpiRange,
+    pub max_devices: KpiRange,
+}
+
+/// Resource requirements of a slice profile
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SliceResourceRequirements {
+    pub cpu_cores: u32,
+    pub memory_gb: u32,
+    pub storage_gb: u32,
+    pub bandwidth_mbps: u32,
+}
+
+/// Slice Template - Standard slice profile with default KPIs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SliceTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub slice_type: SliceType,
+    /// Default SLA parameters applied to instantiated slices
+    pub default_sla: SLAParameters,
+    /// Ranges that SLA overrides must stay within
+    pub allowed_ranges: SliceTemplateRanges,
+    pub resource_requirements: SliceResourceRequirements,
+}
+
+/// Request to instantiate a network slice from a template
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InstantiateSliceTemplateRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// SLA parameters overriding the template defaults
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sla_overrides: Option<CreateSLAParametersRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_functions: Option<Vec<CreateNetworkFunctionRefRequest>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub activation_date: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,176 @@
+//! Slice template catalog for TMF656
+//!
+//! Standard eMBB, URLLC and mMTC profiles with default KPIs, the ranges within
+//! which operators may override them, and the resources each profile needs.
+
+use crate::models::{
+    CreateNetworkSliceRequest, CreateSLAParametersRequest, InstantiateSliceTemplateRequest,
+    KpiRange, SLAParameters, SliceResourceRequirements, SliceTemplate, SliceTemplateRanges,
+    SliceType,
+};
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Built-in slice templates
+pub fn builtin_templates() -> Vec<SliceTemplate> {
+    vec![
+        SliceTemplate {
+            id: "embb".to_string(),
+            name: "Enhanced Mobile Broadband".to_string(),
+            description: "High-throughput slice for video streaming and mobile broadband"
+                .to_string(),
+            slice_type: SliceType::EnhancedMobileBroadband,
+            default_sla: SLAParameters {
+                max_latency_ms: Some(20),
+                min_throughput_mbps: Some(100),
+                max_devices: Some(10_000),
+                coverage_area: None,
+            },
+            allowed_ranges: SliceTemplateRanges {
+                max_latency_ms: KpiRange { min: 10, max: 50 },
+                min_throughput_mbps: KpiRange {
+                    min: 50,
+                    max: 1_000,
+                },
+                max_devices: KpiRange {
+                    min: 1_000,
+                    max: 100_000,
+                },
+            },
+            resource_requirements: SliceResourceRequirements {
+                cpu_cores: 16,
+                memory_gb: 64,
+                storage_gb: 500,
+                bandwidth_mbps: 10_000,
+            },
+        },
+        SliceTemplate {
+            id: "urllc".to_string(),
+            name: "Ultra-Reliable Low Latency".to_string(),
+            description: "Low-latency slice for industrial automation and mission-critical control"
+                .to_string(),
+            slice_type: SliceType::UltraReliableLowLatency,
+            default_sla: SLAParameters {
+                max_latency_ms: Some(1),
+                min_throughput_mbps: Some(10),
+                max_devices: Some(1_000),
+                coverage_area: None,
+            },
+            allowed_ranges: SliceTemplateRanges {
+                max_latency_ms: KpiRange { min: 1, max: 10 },
+                min_throughput_mbps: KpiRange { min: 1, max: 100 },
+                max_devices: KpiRange {
+                    min: 10,
+                    max: 10_000,
+                },
+            },
+            resource_requirements: SliceResourceRequirements {
+                cpu_cores: 8,
+                memory_gb: 32,
+                storage_gb: 100,
+                bandwidth_mbps: 1_000,
+            },
+        },
+        SliceTemplate {
+            id: "mmtc".to_string(),
+            name: "Massive Machine Type Communications".to_string(),
+            description: "High-density slice for IoT sensors and metering".to_string(),
+            slice_type: SliceType::MassiveMachineTypeCommunications,
+            default_sla: SLAParameters {
+                max_latency_ms: Some(100),
+                min_throughput_mbps: Some(1),
+                max_devices: Some(1_000_000),
+                coverage_area: None,
+            },
+            allowed_ranges: SliceTemplateRanges {
+                max_latency_ms: KpiRange {
+                    min: 50,
+                    max: 1_000,
+                },
+                min_throughput_mbps: KpiRange { min: 1, max: 10 },
+                max_devices: KpiRange {
+                    min: 10_000,
+                    max: 10_000_000,
+                },
+            },
+            resource_requirements: SliceResourceRequirements {
+                cpu_cores: 4,
+                memory_gb: 16,
+                storage_gb: 200,
+                bandwidth_mbps: 500,
+            },
+        },
+    ]
+}
+
+/// Look up a template by ID
+pub fn find_template(template_id: &str) -> TmfResult<SliceTemplate> {
+    builtin_templates()
+        .into_iter()
+        .find(|t| t.id == template_id)
+        .ok_or_else(|| TmfError::NotFound(format!("Slice template {} not found", template_id)))
+}
+
+fn check_range(template: &SliceTemplate, kpi: &str, value: u32, range: KpiRange) -> TmfResult<()> {
+    if range.contains(value) {
+        Ok(())
+    } else {
+        Err(TmfError::Validation(format!(
+            "{} of {} is outside the allowed range {}-{} for template {}",
+            kpi, value, range.min, range.max, template.id
+        )))
+    }
+}
+
+impl SliceTemplate {
+    /// Build a slice creation request from the template defaults and overrides
+    ///
+    /// Fails with a validation error if any SLA override falls outside the
+    /// template's allowed ranges.
+    pub fn apply(
+        &self,
+        overrides: InstantiateSliceTemplateRequest,
+    ) -> TmfResult<CreateNetworkSliceRequest> {
+        let sla = overrides
+            .sla_overrides
+            .unwrap_or(CreateSLAParametersRequest {
+                max_latency_ms: None,
+                min_throughput_mbps: None,
+                max_devices: None,
+                coverage_area: None,
+            });
+        let ranges = &self.allowed_ranges;
+
+        if let Some(value) = sla.max_latency_ms {
+            check_range(self, "max_latency_ms", value, ranges.max_latency_ms)?;
+        }
+        if let Some(value) = sla.min_throughput_mbps {
+            check_range(
+                self,
+                "min_throughput_mbps",
+                value,
+                ranges.min_throughput_mbps,
+            )?;
+        }
+        if let Some(value) = sla.max_devices {
+            check_range(self, "max_devices", value, ranges.max_devices)?;
+        }
+
+        let defaults = &self.default_sla;
+        Ok(CreateNetworkSliceRequest {
+            name: overrides.name,
+            description: overrides
+                .description
+                .or_else(|| Some(self.description.clone())),
+            version: overrides.version,
+            slice_type: self.slice_type.clone(),
+            sla_parameters: Some(CreateSLAParametersRequest {
+                max_latency_ms: sla.max_latency_ms.or(defaults.max_latency_ms),
+                min_throughput_mbps: sla.min_throughput_mbps.or(defaults.min_throughput_mbps),
+                max_devices: sla.max_devices.or(defaults.max_devices),
+                coverage_area: sla.coverage_area.or_else(|| defaults.coverage_area.clone()),
+            }),
+            network_functions: overrides.network_functions,
+            activation_date: overrides.activation_date,
+        })
+    }
+}
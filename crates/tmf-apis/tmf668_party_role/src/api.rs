@@ -12,6 +12,19 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_party_roles))
                     .route(web::post().to(create_party_role)),
             )
-            .service(web::resource("/partyRole/{id}").route(web::get().to(get_party_role_by_id))),
+            .service(web::resource("/partyRole/{id}").route(web::get().to(get_party_role_by_id)))
+            .service(
+                web::resource("/partyRole/{id}/contract")
+                    .route(web::get().to(get_partner_contracts))
+                    .route(web::post().to(create_partner_contract)),
+            )
+            .service(
+                web::resource("/partyRoleConflict").route(web::get().to(get_party_role_conflicts)),
+            )
+            .service(
+                web::resource("/partnerContract/{id}")
+                    .route(web::get().to(get_partner_contract_by_id))
+                    .route(web::patch().to(update_partner_contract)),
+            ),
     );
 }
@@ -0,0 +1,187 @@
+//! Partner contract lifecycle for TMF668
+//!
+//! Contracts with a partner role start as drafts and are activated once
+//! agreed. Active contracts expire when their effective period ends and may be
+//! terminated early; expired contracts are terminated to close them, and
+//! terminated is final. A warning event is published ahead of expiry so the
+//! partnership can be renewed in time.
+
+use crate::db;
+use crate::models::{
+    ContractState, CreatePartnerContractRequest, PartnerContract, PartnerContractEvent,
+};
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Event published when a contract is activated
+pub const CONTRACT_ACTIVATED_EVENT: &str = "PartnerContractActivated";
+/// Event published ahead of an active contract expiring
+pub const CONTRACT_EXPIRING_EVENT: &str = "PartnerContractExpiring";
+/// Event published when a contract's effective period has ended
+pub const CONTRACT_EXPIRED_EVENT: &str = "PartnerContractExpired";
+/// Event published when a contract is terminated
+pub const CONTRACT_TERMINATED_EVENT: &str = "PartnerContractTerminated";
+
+impl ContractState {
+    /// States reachable from this state in a single transition
+    pub fn allowed_transitions(&self) -> &'static [ContractState] {
+        use ContractState::*;
+        match self {
+            Draft => &[Active, Terminated],
+            Active => &[Expired, Terminated],
+            Expired => &[Terminated],
+            Terminated => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, target: ContractState) -> bool {
+        self.allowed_transitions().contains(&target)
+    }
+}
+
+/// Check a lifecycle transition, rejecting illegal ones as a conflict
+pub fn validate_transition(from: ContractState, to: ContractState) -> TmfResult<()> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(TmfError::Conflict(format!(
+            "Illegal partner contract transition from {:?} to {:?}",
+            from, to
+        )))
+    }
+}
+
+/// Reject contracts with an empty period or invalid revenue-share terms
+///
+/// Each offering may have one term, plus at most one term for all offerings.
+pub fn validate_contract(request: &CreatePartnerContractRequest) -> TmfResult<()> {
+    if request.name.trim().is_empty() {
+        return Err(TmfError::Validation(
+            "Partner contract name must not be empty".to_string(),
+        ));
+    }
+    if let Some(end) = request.valid_for.end_date_time {
+        if end <= request.valid_for.start_date_time {
+            return Err(TmfError::Validation(
+                "Partner contract must end after it starts".to_string(),
+            ));
+        }
+    }
+
+    let mut offerings = HashSet::new();
+    for term in &request.revenue_share {
+        if !(0.0..=100.0).contains(&term.revenue_share_percentage) {
+            return Err(TmfError::Validation(format!(
+                "Revenue share must be between 0 and 100 percent, got {}",
+                term.revenue_share_percentage
+            )));
+        }
+        if !offerings.insert(term.product_offering_id) {
+            return Err(TmfError::Validation(match term.product_offering_id {
+                Some(id) => format!("Duplicate revenue share term for product offering {}", id),
+                None => "Duplicate revenue share term for all product offerings".to_string(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Publish a contract event on the partner events topic
+///
+/// Publishing failures are returned so callers can decide whether to retry.
+pub(crate) async fn publish_contract_event(
+    publisher: &dyn EventPublisher,
+    event_type: &str,
+    contract: &PartnerContract,
+) -> TmfResult<()> {
+    let event = PartnerContractEvent {
+        contract_id: contract.id,
+        party_role_id: contract.party_role_id,
+        name: contract.name.clone(),
+        state: contract.state,
+        expires_at: contract
+            .termination_date
+            .or(contract.valid_for.end_date_time),
+    };
+    let data = serde_json::to_value(&event).map_err(|e| TmfError::Internal(e.to_string()))?;
+    let envelope = EventEnvelope::new(
+        event_type.to_string(),
+        "tmf668-party-role".to_string(),
+        data,
+    );
+    publisher
+        .publish(topics::PARTNER_EVENTS, envelope)
+        .await
+        .map_err(|e| TmfError::Internal(e.to_string()))
+}
+
+/// Warn about contracts expiring within `warning_period` and expire lapsed ones
+///
+/// Each contract is warned about once; if the warning cannot be published it
+/// is retried on the next run. Returns the number of contracts warned about
+/// and expired.
+pub async fn process_contract_expiry(
+    pool: &PgPool,
+    publisher: &dyn EventPublisher,
+    warning_period: chrono::Duration,
+    now: DateTime<Utc>,
+) -> TmfResult<(usize, usize)> {
+    let expiring = db::claim_contract_expiry_warnings(pool, now + warning_period, now).await?;
+    let mut warned = 0;
+    for contract in &expiring {
+        match publish_contract_event(publisher, CONTRACT_EXPIRING_EVENT, contract).await {
+            Ok(()) => warned += 1,
+            Err(e) => {
+                warn!(
+                    "Failed to publish {} for partner contract {}: {}",
+                    CONTRACT_EXPIRING_EVENT, contract.id, e
+                );
+                db::clear_contract_expiry_warning(pool, contract.id).await?;
+            }
+        }
+    }
+
+    let expired = db::expire_lapsed_partner_contracts(pool, now).await?;
+    for contract in &expired {
+        if let Err(e) = publish_contract_event(publisher, CONTRACT_EXPIRED_EVENT, contract).await {
+            warn!(
+                "Failed to publish {} for partner contract {}: {}",
+                CONTRACT_EXPIRED_EVENT, contract.id, e
+            );
+        }
+    }
+
+    Ok((warned, expired.len()))
+}
+
+/// Periodically warn about expiring partner contracts and expire lapsed ones
+pub fn spawn_contract_expiry(
+    pool: PgPool,
+    publisher: Arc<dyn EventPublisher>,
+    check_interval: Duration,
+    warning_period: chrono::Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(check_interval);
+        loop {
+            interval.tick().await;
+            match process_contract_expiry(&pool, publisher.as_ref(), warning_period, Utc::now())
+                .await
+            {
+                Ok((0, 0)) => {}
+                Ok((warned, expired)) => info!(
+                    "Partner contracts: {} expiring soon, {} expired",
+                    warned, expired
+                ),
+                Err(e) => error!("Partner contract expiry failed: {}", e),
+            }
+        }
+    })
+}
@@ -1,9 +1,20 @@
 //! Database operations for TMF668 Party Role Management
 
-use crate::models::{CreatePartyRoleRequest, PartyRole, PartyRoleState};
+use crate::contract::{
+    publish_contract_event, validate_contract, validate_transition, CONTRACT_ACTIVATED_EVENT,
+    CONTRACT_EXPIRED_EVENT, CONTRACT_TERMINATED_EVENT,
+};
+use crate::effective::{find_conflicts, validate_period};
+use crate::models::{
+    ContractState, CreatePartnerContractRequest, CreatePartyRoleRequest, PartnerContract,
+    PartyRole, PartyRoleConflict, PartyRoleState, RevenueShareTerm,
+};
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
 use sqlx::{Pool, Postgres, Row};
-use tmf_apis_core::{TmfError, TmfResult};
+use std::collections::HashMap;
+use tmf_apis_core::{TimePeriod, TmfError, TmfResult};
 use uuid::Uuid;
 
 // Helper to convert sqlx::Error to TmfError
@@ -34,47 +45,77 @@ fn party_role_state_to_string(state: &PartyRoleState) -> String {
     }
 }
 
+/// Parse contract state from database string
+fn parse_contract_state(s: &str) -> ContractState {
+    match s.to_uppercase().as_str() {
+        "ACTIVE" => ContractState::Active,
+        "EXPIRED" => ContractState::Expired,
+        "TERMINATED" => ContractState::Terminated,
+        _ => ContractState::Draft,
+    }
+}
+
+/// Convert contract state to database string
+fn contract_state_to_string(state: &ContractState) -> String {
+    match state {
+        ContractState::Draft => "DRAFT".to_string(),
+        ContractState::Active => "ACTIVE".to_string(),
+        ContractState::Expired => "EXPIRED".to_string(),
+        ContractState::Terminated => "TERMINATED".to_string(),
+    }
+}
+
+/// Validity period from the effective-from/to columns
+fn row_valid_for(row: &PgRow) -> Option<TimePeriod> {
+    row.get::<Option<DateTime<Utc>>, _>("effective_from")
+        .map(|start_date_time| TimePeriod {
+            start_date_time,
+            end_date_time: row.get::<Option<DateTime<Utc>>, _>("effective_to"),
+        })
+}
+
+/// Helper to convert database row to PartyRole
+fn row_to_party_role(row: &PgRow) -> PartyRole {
+    PartyRole {
+        base: tmf_apis_core::BaseEntity {
+            id: row.get::<Uuid, _>("id"),
+            href: row.get::<Option<String>, _>("href"),
+            name: row.get::<String, _>("name"),
+            description: row.get::<Option<String>, _>("description"),
+            version: row.get::<Option<String>, _>("version"),
+            lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
+            last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
+            valid_for: row_valid_for(row),
+        },
+        state: parse_party_role_state(&row.get::<String, _>("state")),
+        role: row.get::<String, _>("role"),
+        party_id: row.get::<Option<Uuid>, _>("party_id"),
+        party_type: row.get::<Option<String>, _>("party_type"),
+        contact_medium: None, // Load separately if needed
+        related_party: None,  // Load separately if needed
+        engagement_date: row.get::<Option<DateTime<Utc>>, _>("engagement_date"),
+    }
+}
+
 /// Get all party roles
 pub async fn get_party_roles(pool: &Pool<Postgres>) -> TmfResult<Vec<PartyRole>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, role, party_type, engagement_date, 
-         href, last_update
+         href, last_update, party_id, effective_from, effective_to
          FROM party_roles ORDER BY name",
     )
     .fetch_all(pool)
     .await
     .map_err(map_sqlx_error)?;
 
-    let mut party_roles = Vec::new();
-    for row in rows {
-        party_roles.push(PartyRole {
-            base: tmf_apis_core::BaseEntity {
-                id: row.get::<Uuid, _>("id"),
-                href: row.get::<Option<String>, _>("href"),
-                name: row.get::<String, _>("name"),
-                description: row.get::<Option<String>, _>("description"),
-                version: row.get::<Option<String>, _>("version"),
-                lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
-                last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
-                valid_for: None,
-            },
-            state: parse_party_role_state(&row.get::<String, _>("state")),
-            role: row.get::<String, _>("role"),
-            party_type: row.get::<Option<String>, _>("party_type"),
-            contact_medium: None, // Load separately if needed
-            related_party: None,  // Load separately if needed
-            engagement_date: row.get::<Option<DateTime<Utc>>, _>("engagement_date"),
-        });
-    }
-
-    Ok(party_roles)
+    Ok(rows.iter().map(row_to_party_role).collect())
 }
 
 /// Get party role by ID
 pub async fn get_party_role_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<PartyRole> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, role, party_type, engagement_date, 
-         href, last_update
+         href, last_update, party_id, effective_from, effective_to
          FROM party_roles WHERE id = $1",
     )
     .bind(id)
@@ -83,24 +124,7 @@ pub async fn get_party_role_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<
     .map_err(map_sqlx_error)?
     .ok_or_else(|| TmfError::NotFound(format!("Party role with id {} not found", id)))?;
 
-    Ok(PartyRole {
-        base: tmf_apis_core::BaseEntity {
-            id: row.get::<Uuid, _>("id"),
-            href: row.get::<Option<String>, _>("href"),
-            name: row.get::<String, _>("name"),
-            description: row.get::<Option<String>, _>("description"),
-            version: row.get::<Option<String>, _>("version"),
-            lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
-            last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
-            valid_for: None,
-        },
-        state: parse_party_role_state(&row.get::<String, _>("state")),
-        role: row.get::<String, _>("role"),
-        party_type: row.get::<Option<String>, _>("party_type"),
-        contact_medium: None,
-        related_party: None,
-        engagement_date: row.get::<Option<DateTime<Utc>>, _>("engagement_date"),
-    })
+    Ok(row_to_party_role(&row))
 }
 
 /// Create a new party role
@@ -111,9 +135,17 @@ pub async fn create_party_role(
     let id = Uuid::new_v4();
     let state = party_role_state_to_string(&PartyRoleState::Initialized);
 
+    // Roles without an explicit validity period are effective from engagement
+    let valid_for = request.valid_for.clone().unwrap_or_else(|| TimePeriod {
+        start_date_time: request.engagement_date.unwrap_or_else(Utc::now),
+        end_date_time: None,
+    });
+    validate_period(&valid_for)?;
+
     sqlx::query(
-        "INSERT INTO party_roles (id, name, description, version, state, role, party_type, engagement_date)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        "INSERT INTO party_roles (id, name, description, version, state, role, party_type, engagement_date,
+         party_id, effective_from, effective_to)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
     )
     .bind(id)
     .bind(&request.name)
@@ -123,6 +155,9 @@ pub async fn create_party_role(
     .bind(&request.role)
     .bind(&request.party_type)
     .bind(request.engagement_date)
+    .bind(request.party_id)
+    .bind(valid_for.start_date_time)
+    .bind(valid_for.end_date_time)
     .execute(pool)
     .await
     .map_err(map_sqlx_error)?;
@@ -167,3 +202,347 @@ pub async fn create_party_role(
     // Fetch the created party role
     get_party_role_by_id(pool, id).await
 }
+
+/// Get non-terminated party roles effective at the given instant
+pub async fn get_party_roles_active_at(
+    pool: &Pool<Postgres>,
+    at: DateTime<Utc>,
+) -> TmfResult<Vec<PartyRole>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, role, party_type, engagement_date, 
+         href, last_update, party_id, effective_from, effective_to
+         FROM party_roles
+         WHERE effective_from <= $1 AND (effective_to IS NULL OR $1 < effective_to)
+         AND state <> $2
+         ORDER BY name",
+    )
+    .bind(at)
+    .bind(party_role_state_to_string(&PartyRoleState::Terminated))
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows.iter().map(row_to_party_role).collect())
+}
+
+/// Find non-terminated roles of the same kind held by a party over
+/// overlapping periods
+pub async fn find_conflicting_party_roles(
+    pool: &Pool<Postgres>,
+    party_id: Uuid,
+) -> TmfResult<Vec<PartyRoleConflict>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, role, party_type, engagement_date, 
+         href, last_update, party_id, effective_from, effective_to
+         FROM party_roles
+         WHERE party_id = $1 AND effective_from IS NOT NULL AND state <> $2
+         ORDER BY effective_from",
+    )
+    .bind(party_id)
+    .bind(party_role_state_to_string(&PartyRoleState::Terminated))
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let party_roles: Vec<PartyRole> = rows.iter().map(row_to_party_role).collect();
+    Ok(find_conflicts(&party_roles))
+}
+
+/// Terminate roles whose validity ended at or before `now`
+///
+/// Returns the number of roles expired.
+pub async fn expire_lapsed_party_roles(
+    pool: &Pool<Postgres>,
+    now: DateTime<Utc>,
+) -> TmfResult<u64> {
+    let result = sqlx::query(
+        "UPDATE party_roles SET state = $1, last_update = CURRENT_TIMESTAMP
+         WHERE effective_to IS NOT NULL AND effective_to <= $2 AND state <> $1",
+    )
+    .bind(party_role_state_to_string(&PartyRoleState::Terminated))
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Create a draft partner contract for a party role
+///
+/// The role must exist and not be terminated.
+pub async fn create_partner_contract(
+    pool: &Pool<Postgres>,
+    party_role_id: Uuid,
+    request: CreatePartnerContractRequest,
+) -> TmfResult<PartnerContract> {
+    validate_contract(&request)?;
+    let role = get_party_role_by_id(pool, party_role_id).await?;
+    if matches!(role.state, PartyRoleState::Terminated) {
+        return Err(TmfError::Conflict(format!(
+            "Party role {} is terminated",
+            party_role_id
+        )));
+    }
+
+    let id = Uuid::new_v4();
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    sqlx::query(
+        "INSERT INTO partner_contracts (id, party_role_id, name, description, state,
+         effective_from, effective_to)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(id)
+    .bind(party_role_id)
+    .bind(request.name.trim())
+    .bind(&request.description)
+    .bind(contract_state_to_string(&ContractState::Draft))
+    .bind(request.valid_for.start_date_time)
+    .bind(request.valid_for.end_date_time)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    for term in &request.revenue_share {
+        sqlx::query(
+            "INSERT INTO partner_contract_revenue_shares (id, contract_id, product_offering_id,
+             revenue_share_percentage)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(id)
+        .bind(term.product_offering_id)
+        .bind(term.revenue_share_percentage)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    }
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    get_partner_contract_by_id(pool, id).await
+}
+
+const PARTNER_CONTRACT_COLUMNS: &str = "id, party_role_id, name, description, state,
+    effective_from, effective_to, activation_date, expiry_warning_sent_at, termination_date,
+    href, last_update";
+
+/// Load the revenue-share terms of the given contract rows
+async fn rows_to_contracts(
+    pool: &Pool<Postgres>,
+    rows: Vec<PgRow>,
+) -> TmfResult<Vec<PartnerContract>> {
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+    let term_rows = sqlx::query(
+        "SELECT contract_id, product_offering_id, revenue_share_percentage::FLOAT8 AS revenue_share_percentage
+         FROM partner_contract_revenue_shares WHERE contract_id = ANY($1)
+         ORDER BY product_offering_id NULLS LAST",
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut terms: HashMap<Uuid, Vec<RevenueShareTerm>> = HashMap::new();
+    for row in term_rows {
+        terms
+            .entry(row.get("contract_id"))
+            .or_default()
+            .push(RevenueShareTerm {
+                product_offering_id: row.get("product_offering_id"),
+                revenue_share_percentage: row.get("revenue_share_percentage"),
+            });
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let id: Uuid = row.get("id");
+            PartnerContract {
+                id,
+                href: row.get("href"),
+                name: row.get("name"),
+                description: row.get("description"),
+                party_role_id: row.get("party_role_id"),
+                state: parse_contract_state(&row.get::<String, _>("state")),
+                valid_for: TimePeriod {
+                    start_date_time: row.get("effective_from"),
+                    end_date_time: row.get("effective_to"),
+                },
+                revenue_share: terms.remove(&id).unwrap_or_default(),
+                activation_date: row.get("activation_date"),
+                expiry_warning_sent_at: row.get("expiry_warning_sent_at"),
+                termination_date: row.get("termination_date"),
+                last_update: row.get("last_update"),
+            }
+        })
+        .collect())
+}
+
+/// Get partner contract by ID
+pub async fn get_partner_contract_by_id(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+) -> TmfResult<PartnerContract> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM partner_contracts WHERE id = $1",
+        PARTNER_CONTRACT_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?
+    .ok_or_else(|| TmfError::NotFound(format!("Partner contract with id {} not found", id)))?;
+
+    rows_to_contracts(pool, vec![row])
+        .await?
+        .pop()
+        .ok_or_else(|| TmfError::Internal(format!("Partner contract {} not loaded", id)))
+}
+
+/// Get the contracts of a party role, latest effective first
+pub async fn get_partner_contracts(
+    pool: &Pool<Postgres>,
+    party_role_id: Uuid,
+) -> TmfResult<Vec<PartnerContract>> {
+    get_party_role_by_id(pool, party_role_id).await?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM partner_contracts WHERE party_role_id = $1
+         ORDER BY effective_from DESC",
+        PARTNER_CONTRACT_COLUMNS
+    ))
+    .bind(party_role_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    rows_to_contracts(pool, rows).await
+}
+
+/// Move a partner contract to a new lifecycle state
+///
+/// Contracts can only be activated before their period ends, and only expire
+/// once it has ended. The update is guarded on the current state, so a
+/// concurrent change is reported as a conflict.
+pub async fn update_partner_contract_state(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    id: Uuid,
+    state: ContractState,
+) -> TmfResult<PartnerContract> {
+    let current = get_partner_contract_by_id(pool, id).await?;
+    validate_transition(current.state, state)?;
+
+    let now = Utc::now();
+    let ended = current
+        .valid_for
+        .end_date_time
+        .is_some_and(|end| end <= now);
+    match state {
+        ContractState::Active if ended => {
+            return Err(TmfError::Conflict(format!(
+                "Partner contract {} ended before it was activated",
+                id
+            )));
+        }
+        ContractState::Expired if !ended => {
+            return Err(TmfError::Conflict(format!(
+                "Partner contract {} has not reached the end of its period",
+                id
+            )));
+        }
+        _ => {}
+    }
+
+    let result = sqlx::query(
+        "UPDATE partner_contracts SET state = $3, last_update = $4,
+         activation_date = CASE WHEN $3 = 'ACTIVE' THEN $4 ELSE activation_date END,
+         termination_date = CASE WHEN $3 = 'TERMINATED' THEN $4 ELSE termination_date END
+         WHERE id = $1 AND state = $2",
+    )
+    .bind(id)
+    .bind(contract_state_to_string(&current.state))
+    .bind(contract_state_to_string(&state))
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    if result.rows_affected() == 0 {
+        return Err(TmfError::Conflict(format!(
+            "Partner contract {} was changed concurrently",
+            id
+        )));
+    }
+
+    let contract = get_partner_contract_by_id(pool, id).await?;
+    let event_type = match state {
+        ContractState::Active => Some(CONTRACT_ACTIVATED_EVENT),
+        ContractState::Expired => Some(CONTRACT_EXPIRED_EVENT),
+        ContractState::Terminated => Some(CONTRACT_TERMINATED_EVENT),
+        ContractState::Draft => None,
+    };
+    if let Some(event_type) = event_type {
+        if let Err(e) = publish_contract_event(publisher, event_type, &contract).await {
+            log::warn!(
+                "Failed to publish {} for partner contract {}: {}",
+                event_type,
+                id,
+                e
+            );
+        }
+    }
+    Ok(contract)
+}
+
+/// Mark active contracts ending before `horizon` as warned about, returning
+/// those not warned about before
+pub async fn claim_contract_expiry_warnings(
+    pool: &Pool<Postgres>,
+    horizon: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> TmfResult<Vec<PartnerContract>> {
+    let rows = sqlx::query(&format!(
+        "UPDATE partner_contracts SET expiry_warning_sent_at = $2
+         WHERE state = 'ACTIVE' AND expiry_warning_sent_at IS NULL
+         AND effective_to IS NOT NULL AND effective_to <= $1 AND effective_to > $2
+         RETURNING {}",
+        PARTNER_CONTRACT_COLUMNS
+    ))
+    .bind(horizon)
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    rows_to_contracts(pool, rows).await
+}
+
+/// Clear the expiry warning of a contract so it is warned about again
+pub async fn clear_contract_expiry_warning(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<()> {
+    sqlx::query("UPDATE partner_contracts SET expiry_warning_sent_at = NULL WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(map_sqlx_error)?;
+    Ok(())
+}
+
+/// Expire active contracts whose period ended at or before `now`, returning
+/// the contracts expired
+pub async fn expire_lapsed_partner_contracts(
+    pool: &Pool<Postgres>,
+    now: DateTime<Utc>,
+) -> TmfResult<Vec<PartnerContract>> {
+    let rows = sqlx::query(&format!(
+        "UPDATE partner_contracts SET state = 'EXPIRED', last_update = $1
+         WHERE state = 'ACTIVE' AND effective_to IS NOT NULL AND effective_to <= $1
+         RETURNING {}",
+        PARTNER_CONTRACT_COLUMNS
+    ))
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    rows_to_contracts(pool, rows).await
+}
@@ -0,0 +1,91 @@
+//! Party role effective dating for TMF668
+//!
+//! Roles are effective from `valid_for.start_date_time` until the optional
+//! `valid_for.end_date_time` (exclusive). Lapsed roles are terminated by the
+//! expiry task, and roles of the same kind held by one party over overlapping
+//! periods are reported as conflicts.
+
+use crate::db;
+use crate::models::{PartyRole, PartyRoleConflict, PartyRoleState};
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use sqlx::PgPool;
+use std::time::Duration;
+use tmf_apis_core::{TimePeriod, TmfError, TmfResult};
+
+/// Whether a validity period covers the given instant
+pub fn is_effective_at(period: &TimePeriod, at: DateTime<Utc>) -> bool {
+    period.start_date_time <= at && period.end_date_time.is_none_or(|end| at < end)
+}
+
+/// Reject periods that end before they start
+pub fn validate_period(period: &TimePeriod) -> TmfResult<()> {
+    match period.end_date_time {
+        Some(end) if end <= period.start_date_time => Err(TmfError::Validation(
+            "Party role validity must end after it starts".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Overlap of two validity periods, if any
+pub fn overlap(a: &TimePeriod, b: &TimePeriod) -> Option<TimePeriod> {
+    let start = a.start_date_time.max(b.start_date_time);
+    let end = match (a.end_date_time, b.end_date_time) {
+        (Some(x), Some(y)) => Some(x.min(y)),
+        (x, y) => x.or(y),
+    };
+    match end {
+        Some(end) if end <= start => None,
+        _ => Some(TimePeriod {
+            start_date_time: start,
+            end_date_time: end,
+        }),
+    }
+}
+
+/// Find pairs of non-terminated roles of the same kind held by the same party
+/// whose validity periods overlap
+pub fn find_conflicts(roles: &[PartyRole]) -> Vec<PartyRoleConflict> {
+    let candidates: Vec<&PartyRole> = roles
+        .iter()
+        .filter(|r| r.party_id.is_some() && !matches!(r.state, PartyRoleState::Terminated))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, a) in candidates.iter().enumerate() {
+        for b in &candidates[i + 1..] {
+            if a.party_id != b.party_id || !a.role.eq_ignore_ascii_case(&b.role) {
+                continue;
+            }
+            let (Some(pa), Some(pb)) = (&a.base.valid_for, &b.base.valid_for) else {
+                continue;
+            };
+            if let (Some(party_id), Some(period)) = (a.party_id, overlap(pa, pb)) {
+                conflicts.push(PartyRoleConflict {
+                    party_id,
+                    role: a.role.clone(),
+                    party_role_id: a.base.id,
+                    conflicting_party_role_id: b.base.id,
+                    overlap: period,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Periodically terminate roles whose validity has lapsed
+pub fn spawn_role_expiry(pool: PgPool, check_interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(check_interval);
+        loop {
+            interval.tick().await;
+            match db::expire_lapsed_party_roles(&pool, Utc::now()).await {
+                Ok(expired) if expired > 0 => info!("Expired {} lapsed party roles", expired),
+                Ok(_) => {}
+                Err(e) => error!("Party role expiry failed: {}", e),
+            }
+        }
+    })
+}
@@ -4,10 +4,32 @@ use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
+/// Map a TMF error to an HTTP response
+fn error_response(err: TmfError) -> HttpResponse {
+    let body = serde_json::json!({ "error": err.to_string() });
+    match err {
+        TmfError::NotFound(_) => HttpResponse::NotFound().json(body),
+        TmfError::Conflict(_) => HttpResponse::Conflict().json(body),
+        TmfError::BadRequest(_) | TmfError::Validation(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+/// Query parameters for listing party roles
+#[derive(Debug, Deserialize)]
+pub struct PartyRoleQuery {
+    /// Only return roles effective at this instant
+    pub active_at: Option<DateTime<Utc>>,
+}
+
 /// Get all party roles
 #[utoipa::path(
     get,
@@ -16,15 +38,23 @@ use uuid::Uuid;
         (status = 200, description = "List of party roles", body = Vec<PartyRole>),
         (status = 401, description = "Unauthorized")
     ),
+    params(
+        ("active_at" = Option<String>, Query, description = "Only return roles effective at this date-time")
+    ),
     tag = "TMF668"
 )]
 pub async fn get_party_roles(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
+    query: web::Query<PartyRoleQuery>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::get_party_roles(pool.get_ref()).await {
+    let result = match query.active_at {
+        Some(at) => db::get_party_roles_active_at(pool.get_ref(), at).await,
+        None => db::get_party_roles(pool.get_ref()).await,
+    };
+    match result {
         Ok(party_roles) => Ok(HttpResponse::Ok().json(party_roles)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
@@ -95,8 +125,204 @@ pub async fn create_party_role(
 
     match db::create_party_role(pool.get_ref(), body.into_inner()).await {
         Ok(party_role) => Ok(HttpResponse::Created().json(party_role)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Query parameters for party role conflict detection
+#[derive(Debug, Deserialize)]
+pub struct PartyRoleConflictQuery {
+    pub party_id: Uuid,
+}
+
+/// Find overlapping roles of the same kind held by a party
+#[utoipa::path(
+    get,
+    path = "/tmf-api/partyRoleManagement/v4/partyRoleConflict",
+    responses(
+        (status = 200, description = "Conflicting party roles", body = Vec<PartyRoleConflict>),
+        (status = 400, description = "Missing or invalid party ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("party_id" = String, Query, description = "Party ID (UUID)")
+    ),
+    tag = "TMF668"
+)]
+pub async fn get_party_role_conflicts(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    query: web::Query<PartyRoleConflictQuery>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::find_conflicting_party_roles(pool.get_ref(), query.party_id).await {
+        Ok(conflicts) => Ok(HttpResponse::Ok().json(conflicts)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
     }
 }
+
+/// Create a draft contract for a partner role
+#[utoipa::path(
+    post,
+    path = "/tmf-api/partyRoleManagement/v4/partyRole/{id}/contract",
+    request_body = CreatePartnerContractRequest,
+    responses(
+        (status = 201, description = "Partner contract created in draft", body = PartnerContract),
+        (status = 400, description = "Invalid period or revenue-share terms"),
+        (status = 404, description = "Party role not found"),
+        (status = 409, description = "Party role is terminated"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Party Role ID (UUID)")
+    ),
+    tag = "TMF668"
+)]
+pub async fn create_partner_contract(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<CreatePartnerContractRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid party role ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::create_partner_contract(pool.get_ref(), id, body.into_inner()).await {
+        Ok(contract) => Ok(HttpResponse::Created().json(contract)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Get the contracts of a partner role
+#[utoipa::path(
+    get,
+    path = "/tmf-api/partyRoleManagement/v4/partyRole/{id}/contract",
+    responses(
+        (status = 200, description = "Partner contracts, latest effective first", body = Vec<PartnerContract>),
+        (status = 400, description = "Invalid party role ID"),
+        (status = 404, description = "Party role not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Party Role ID (UUID)")
+    ),
+    tag = "TMF668"
+)]
+pub async fn get_partner_contracts(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid party role ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::get_partner_contracts(pool.get_ref(), id).await {
+        Ok(contracts) => Ok(HttpResponse::Ok().json(contracts)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Get partner contract by ID
+#[utoipa::path(
+    get,
+    path = "/tmf-api/partyRoleManagement/v4/partnerContract/{id}",
+    responses(
+        (status = 200, description = "Partner contract found", body = PartnerContract),
+        (status = 400, description = "Invalid partner contract ID"),
+        (status = 404, description = "Partner contract not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Partner Contract ID (UUID)")
+    ),
+    tag = "TMF668"
+)]
+pub async fn get_partner_contract_by_id(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid partner contract ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::get_partner_contract_by_id(pool.get_ref(), id).await {
+        Ok(contract) => Ok(HttpResponse::Ok().json(contract)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
+
+/// Move a partner contract through its lifecycle
+#[utoipa::path(
+    patch,
+    path = "/tmf-api/partyRoleManagement/v4/partnerContract/{id}",
+    request_body = UpdatePartnerContractRequest,
+    responses(
+        (status = 200, description = "Partner contract updated", body = PartnerContract),
+        (status = 400, description = "Invalid partner contract ID"),
+        (status = 404, description = "Partner contract not found"),
+        (status = 409, description = "Illegal lifecycle transition"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Partner Contract ID (UUID)")
+    ),
+    tag = "TMF668"
+)]
+pub async fn update_partner_contract(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<UpdatePartnerContractRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid partner contract ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let publisher = Arc::clone(publisher.get_ref());
+    match db::update_partner_contract_state(pool.get_ref(), publisher.as_ref(), id, body.state)
+        .await
+    {
+        Ok(contract) => Ok(HttpResponse::Ok().json(contract)),
+        Err(e) => Ok(error_response(e)),
+    }
+}
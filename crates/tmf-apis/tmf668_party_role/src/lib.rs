@@ -5,7 +5,9 @@
 
 pub mod api;
 pub mod auth;
+pub mod contract;
 pub mod db;
+pub mod effective;
 pub mod handlers;
 pub mod models;
 
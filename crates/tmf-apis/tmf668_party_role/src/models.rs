@@ -2,7 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tmf_apis_core::BaseEntity;
+use tmf_apis_core::{BaseEntity, TimePeriod};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -26,6 +26,10 @@ pub struct PartyRole {
     pub state: PartyRoleState,
     /// Role name (e.g., CUSTOMER, PARTNER, VENDOR, RESELLER)
     pub role: String,
+    /// Party holding the role
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub party_id: Option<Uuid>,
     /// Party type (INDIVIDUAL, ORGANIZATION)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub party_type: Option<String>,
@@ -71,7 +75,13 @@ pub struct CreatePartyRoleRequest {
     pub version: Option<String>,
     pub role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub party_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub party_type: Option<String>,
+    /// Period during which the role is effective; starts at the engagement date if omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_for: Option<TimePeriod>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_medium: Option<Vec<CreateContactMediumRequest>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -96,3 +106,102 @@ pub struct CreateRelatedPartyRequest {
     pub name: String,
     pub role: String,
 }
+
+/// Party Role Conflict - Two roles of the same kind held by a party over overlapping periods
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartyRoleConflict {
+    #[schema(value_type = String, format = "uuid")]
+    pub party_id: Uuid,
+    pub role: String,
+    #[schema(value_type = String, format = "uuid")]
+    pub party_role_id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub conflicting_party_role_id: Uuid,
+    /// Period during which both roles are effective
+    pub overlap: TimePeriod,
+}
+
+/// Partner Contract State
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContractState {
+    Draft,
+    Active,
+    Expired,
+    Terminated,
+}
+
+/// Revenue Share Term - Partner share of the revenue of an offering
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RevenueShareTerm {
+    /// Offering the share applies to; applies to all offerings if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub product_offering_id: Option<Uuid>,
+    /// Partner share of the revenue, 0 to 100
+    pub revenue_share_percentage: f64,
+}
+
+/// Partner Contract - Terms agreed with a partner role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerContract {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Partner role the contract is with
+    #[schema(value_type = String, format = "uuid")]
+    pub party_role_id: Uuid,
+    pub state: ContractState,
+    /// Period during which the contract is effective
+    pub valid_for: TimePeriod,
+    pub revenue_share: Vec<RevenueShareTerm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub activation_date: Option<DateTime<Utc>>,
+    /// When the advance warning of the contract expiring was published
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub expiry_warning_sent_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub termination_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub last_update: Option<DateTime<Utc>>,
+}
+
+/// Request to create a partner contract, in draft
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatePartnerContractRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Period during which the contract is effective
+    pub valid_for: TimePeriod,
+    pub revenue_share: Vec<RevenueShareTerm>,
+}
+
+/// Request to move a partner contract through its lifecycle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePartnerContractRequest {
+    pub state: ContractState,
+}
+
+/// Partner Contract Event - Published on the partner events topic
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerContractEvent {
+    #[schema(value_type = String, format = "uuid")]
+    pub contract_id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub party_role_id: Uuid,
+    pub name: String,
+    pub state: ContractState,
+    /// When the contract stops being effective
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -12,6 +12,16 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_identities))
                     .route(web::post().to(create_identity)),
             )
-            .service(web::resource("/identity/{id}").route(web::get().to(get_identity_by_id))),
+            .service(web::resource("/identity/{id}").route(web::get().to(get_identity_by_id)))
+            .service(web::resource("/identity/{id}/password").route(web::put().to(set_password)))
+            .service(
+                web::resource("/credential/{id}/usage")
+                    .route(web::get().to(get_credential_usage))
+                    .route(web::post().to(record_credential_usage)),
+            )
+            .service(
+                web::resource("/credential/{id}/usageAnomaly")
+                    .route(web::get().to(get_credential_usage_anomalies)),
+            ),
     );
 }
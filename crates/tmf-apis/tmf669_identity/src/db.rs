@@ -1,6 +1,7 @@
 //! Database operations for TMF669 Identity & Credential Management
 
-use crate::models::{CreateIdentityRequest, CredentialType, Identity, IdentityState};
+use crate::models::{CreateIdentityRequest, Credential, CredentialType, Identity, IdentityState};
+use crate::password::{hash_password, PasswordError, PasswordPolicy};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -132,13 +133,24 @@ pub async fn get_identity_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Id
 }
 
 /// Create a new identity
+///
+/// Password credentials must satisfy the password policy and are stored hashed.
 pub async fn create_identity(
     pool: &Pool<Postgres>,
     request: CreateIdentityRequest,
+    policy: &PasswordPolicy,
 ) -> TmfResult<Identity> {
     let id = Uuid::new_v4();
     let state = identity_state_to_string(&IdentityState::Created);
 
+    for cred in request.credential.iter().flatten() {
+        if let (CredentialType::Password, Some(password)) =
+            (&cred.credential_type, &cred.credential_value)
+        {
+            policy.check(password, &[]).await?;
+        }
+    }
+
     sqlx::query(
         "INSERT INTO identities (id, name, description, version, state, identity_type, 
          party_id, oauth_client_id, oauth_client_secret, jwt_issuer, expiration_date)
@@ -165,6 +177,11 @@ pub async fn create_identity(
             let cred_id = Uuid::new_v4();
             let cred_type = credential_type_to_string(&cred.credential_type);
             let now = Utc::now();
+            let is_password = matches!(cred.credential_type, CredentialType::Password);
+            let value = match &cred.credential_value {
+                Some(password) if is_password => Some(hash_password(password)),
+                other => other.clone(),
+            };
 
             sqlx::query(
                 "INSERT INTO identity_credentials (id, identity_id, credential_type, 
@@ -174,15 +191,117 @@ pub async fn create_identity(
             .bind(cred_id)
             .bind(id)
             .bind(&cred_type)
-            .bind(&cred.credential_value)
+            .bind(&value)
             .bind(now)
             .bind(cred.expiration_date)
             .execute(pool)
             .await
             .map_err(map_sqlx_error)?;
+
+            if let (true, Some(hash)) = (is_password, &value) {
+                record_password_history(pool, id, hash).await?;
+            }
         }
     }
 
     // Fetch the created identity
     get_identity_by_id(pool, id).await
 }
+
+async fn record_password_history(
+    pool: &Pool<Postgres>,
+    identity_id: Uuid,
+    password_hash: &str,
+) -> TmfResult<()> {
+    sqlx::query(
+        "INSERT INTO identity_password_history (id, identity_id, password_hash)
+         VALUES ($1, $2, $3)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(identity_id)
+    .bind(password_hash)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    Ok(())
+}
+
+/// Set or rotate an identity's password
+///
+/// The password must satisfy the policy, including not matching any of the
+/// last `history_depth` passwords. It replaces the current password credential.
+pub async fn set_password(
+    pool: &Pool<Postgres>,
+    policy: &PasswordPolicy,
+    identity_id: Uuid,
+    password: &str,
+) -> Result<Credential, PasswordError> {
+    policy.check_strength(password)?;
+    policy.check_breached(password).await?;
+
+    let id = Uuid::new_v4();
+    let hash = hash_password(password);
+    let now = Utc::now();
+    let cred_type = credential_type_to_string(&CredentialType::Password);
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
+    // Lock the identity so concurrent changes see each other's history
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM identities WHERE id = $1 FOR UPDATE")
+        .bind(identity_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or_else(|| TmfError::NotFound(format!("Identity with id {} not found", identity_id)))?;
+
+    let history: Vec<String> = sqlx::query_scalar(
+        "SELECT password_hash FROM identity_password_history
+         WHERE identity_id = $1 ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(identity_id)
+    .bind(policy.history_depth as i64)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    policy.check_reuse(password, &history)?;
+
+    sqlx::query("DELETE FROM identity_credentials WHERE identity_id = $1 AND credential_type = $2")
+        .bind(identity_id)
+        .bind(&cred_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+    sqlx::query(
+        "INSERT INTO identity_credentials (id, identity_id, credential_type, 
+         credential_value, created_date)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(identity_id)
+    .bind(&cred_type)
+    .bind(&hash)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    sqlx::query(
+        "INSERT INTO identity_password_history (id, identity_id, password_hash, created_at)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(identity_id)
+    .bind(&hash)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    Ok(Credential {
+        id,
+        credential_type: CredentialType::Password,
+        credential_value: None,
+        created_date: Some(now),
+        expiration_date: None,
+    })
+}
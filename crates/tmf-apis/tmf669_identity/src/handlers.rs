@@ -3,11 +3,23 @@
 use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
+use crate::password::{PasswordError, PasswordPolicy};
+use crate::usage::{self, UsageAnomalyConfig};
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
+/// Password policy registered as app data, or the default policy
+fn password_policy(registered: &Option<web::Data<PasswordPolicy>>) -> PasswordPolicy {
+    registered
+        .as_ref()
+        .map(|data| data.get_ref().clone())
+        .unwrap_or_default()
+}
+
 /// Get all identities
 #[utoipa::path(
     get,
@@ -90,11 +102,202 @@ pub async fn create_identity(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
     body: web::Json<CreateIdentityRequest>,
+    registered_policy: Option<web::Data<PasswordPolicy>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_identity(pool.get_ref(), body.into_inner()).await {
+    let policy = password_policy(&registered_policy);
+    match db::create_identity(pool.get_ref(), body.into_inner(), &policy).await {
         Ok(identity) => Ok(HttpResponse::Created().json(identity)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Set or rotate an identity's password
+#[utoipa::path(
+    put,
+    path = "/tmf-api/identityManagement/v4/identity/{id}/password",
+    request_body = SetPasswordRequest,
+    responses(
+        (status = 200, description = "Password set", body = Credential),
+        (status = 400, description = "Password rejected by policy", body = PasswordPolicyViolation),
+        (status = 404, description = "Identity not found"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Identity ID (UUID)")
+    ),
+    tag = "TMF669"
+)]
+pub async fn set_password(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<SetPasswordRequest>,
+    registered_policy: Option<web::Data<PasswordPolicy>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid identity ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let policy = password_policy(&registered_policy);
+    match db::set_password(pool.get_ref(), &policy, id, &body.password).await {
+        Ok(credential) => Ok(HttpResponse::Ok().json(credential)),
+        Err(PasswordError::Policy(violation)) => Ok(HttpResponse::BadRequest().json(violation)),
+        Err(PasswordError::Tmf(TmfError::NotFound(msg))) => {
+            Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": msg
+            })))
+        }
+        Err(PasswordError::Tmf(e)) => {
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Report a use of a credential
+///
+/// The use is recorded in the background against the credential's usage
+/// baseline; unusual location, rate or time of use is flagged and published
+/// as a security event. The caller is never held up or refused.
+#[utoipa::path(
+    post,
+    path = "/tmf-api/identityManagement/v4/credential/{id}/usage",
+    request_body = CredentialUsageRequest,
+    responses(
+        (status = 202, description = "Usage accepted for analysis"),
+        (status = 400, description = "Invalid credential ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Credential ID (UUID)")
+    ),
+    tag = "TMF669"
+)]
+pub async fn record_credential_usage(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<CredentialUsageRequest>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+    registered_config: Option<web::Data<UsageAnomalyConfig>>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid credential ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let config = registered_config
+        .map(|data| data.get_ref().clone())
+        .unwrap_or_default();
+    usage::track_credential_usage(
+        pool.get_ref().clone(),
+        Arc::clone(publisher.get_ref()),
+        config,
+        id,
+        body.into_inner(),
+    );
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Get the usage baseline of a credential
+#[utoipa::path(
+    get,
+    path = "/tmf-api/identityManagement/v4/credential/{id}/usage",
+    responses(
+        (status = 200, description = "Credential usage baseline", body = CredentialUsageSummary),
+        (status = 404, description = "Credential not found"),
+        (status = 400, description = "Invalid credential ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Credential ID (UUID)")
+    ),
+    tag = "TMF669"
+)]
+pub async fn get_credential_usage(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid credential ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match usage::get_usage_summary(pool.get_ref(), id).await {
+        Ok(summary) => Ok(HttpResponse::Ok().json(summary)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get the usage anomalies flagged for a credential
+#[utoipa::path(
+    get,
+    path = "/tmf-api/identityManagement/v4/credential/{id}/usageAnomaly",
+    responses(
+        (status = 200, description = "Usage anomalies, latest first", body = Vec<CredentialUsageAnomaly>),
+        (status = 404, description = "Credential not found"),
+        (status = 400, description = "Invalid credential ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Credential ID (UUID)")
+    ),
+    tag = "TMF669"
+)]
+pub async fn get_credential_usage_anomalies(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid credential ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match usage::get_usage_anomalies(pool.get_ref(), id).await {
+        Ok(anomalies) => Ok(HttpResponse::Ok().json(anomalies)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
@@ -8,6 +8,8 @@ pub mod auth;
 pub mod db;
 pub mod handlers;
 pub mod models;
+pub mod password;
+pub mod usage;
 
 pub use auth::*;
 pub use handlers::*;
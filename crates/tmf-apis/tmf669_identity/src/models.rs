@@ -125,3 +125,72 @@ pub struct CreateCredentialRequest {
     #[schema(value_type = String, format = "date-time")]
     pub expiration_date: Option<DateTime<Utc>>,
 }
+
+/// Request to set or rotate an identity's password
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetPasswordRequest {
+    pub password: String,
+}
+
+/// Report of a credential being used, e.g. by the API gateway
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct CredentialUsageRequest {
+    /// Where the request came from, e.g. an ISO country code from geo-IP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Client address the credential was presented from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ip: Option<String>,
+    /// When the credential was used; defaults to now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Kind of unusual credential use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UsageAnomalyKind {
+    /// Used from a location not in the credential's baseline
+    UnusualLocation,
+    /// Called far more often than usual
+    UnusualRate,
+    /// Used at an hour of the day it is rarely used at
+    UnusualTime,
+}
+
+/// Credential Usage Anomaly - Unusual use of a credential, flagged without blocking it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CredentialUsageAnomaly {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub credential_id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub identity_id: Uuid,
+    pub kind: UsageAnomalyKind,
+    /// What was unusual, compared with the baseline
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_ip: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub observed_at: DateTime<Utc>,
+}
+
+/// Credential Usage Summary - The usage baseline of a credential
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CredentialUsageSummary {
+    #[schema(value_type = String, format = "uuid")]
+    pub credential_id: Uuid,
+    /// Number of uses recorded
+    pub observations: u64,
+    /// Locations the credential is usually used from, most used first
+    pub usual_locations: Vec<String>,
+    /// Average calls per rate window while in use
+    pub mean_window_calls: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,339 @@
+//! Password policy for TMF669 credentials
+//!
+//! Enforced whenever a password credential is set or rotated: length and
+//! character-class rules, a breach check and a ban on reusing recent passwords.
+//!
+//! The breach check uses k-anonymity: only the first five hex characters of the
+//! password's SHA-1 are handed to the [`BreachedPasswordRange`], which returns
+//! the hash suffixes it knows under that prefix. The password itself and its
+//! full hash never leave the process.
+//!
+//! Passwords are stored as salted PBKDF2-HMAC-SHA256 hashes and compared in
+//! constant time.
+
+use async_trait::async_trait;
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tmf_apis_core::{TmfError, TmfResult};
+use utoipa::ToSchema;
+
+/// Length of the SHA-1 prefix sent for a breach range lookup
+pub const RANGE_PREFIX_LEN: usize = 5;
+
+/// PBKDF2-HMAC-SHA256 iterations for newly hashed passwords
+pub const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Length in bytes of the derived password hash
+const HASH_LEN: usize = 32;
+
+/// Source of breached password hashes, queried by SHA-1 prefix
+#[async_trait]
+pub trait BreachedPasswordRange: Send + Sync {
+    /// Uppercase hex SHA-1 suffixes of breached passwords starting with `prefix`
+    async fn range(&self, prefix: &str) -> TmfResult<HashSet<String>>;
+}
+
+/// Breached password set held in memory, e.g. loaded from a common-password list
+#[derive(Debug, Default)]
+pub struct InMemoryBreachedPasswords {
+    by_prefix: HashMap<String, HashSet<String>>,
+}
+
+impl InMemoryBreachedPasswords {
+    /// Build from full SHA-1 hashes in hex
+    pub fn from_sha1_hashes<I, S>(hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut by_prefix: HashMap<String, HashSet<String>> = HashMap::new();
+        for hash in hashes {
+            let hash = hash.as_ref().trim().to_uppercase();
+            if hash.len() <= RANGE_PREFIX_LEN {
+                continue;
+            }
+            let (prefix, suffix) = hash.split_at(RANGE_PREFIX_LEN);
+            by_prefix
+                .entry(prefix.to_string())
+                .or_default()
+                .insert(suffix.to_string());
+        }
+        Self { by_prefix }
+    }
+
+    /// Build from plaintext passwords
+    pub fn from_passwords<I, S>(passwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::from_sha1_hashes(passwords.into_iter().map(|p| sha1_hex(p.as_ref())))
+    }
+}
+
+#[async_trait]
+impl BreachedPasswordRange for InMemoryBreachedPasswords {
+    async fn range(&self, prefix: &str) -> TmfResult<HashSet<String>> {
+        Ok(self
+            .by_prefix
+            .get(&prefix.to_uppercase())
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+/// Password policy rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PasswordRule {
+    MinLength,
+    MaxLength,
+    Uppercase,
+    Lowercase,
+    Digit,
+    Symbol,
+    Breached,
+    Reused,
+}
+
+/// A password rejected by the policy, naming the rule it failed
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PasswordPolicyViolation {
+    pub rule: PasswordRule,
+    pub message: String,
+}
+
+impl fmt::Display for PasswordPolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Password rejected ({:?}): {}", self.rule, self.message)
+    }
+}
+
+impl From<PasswordPolicyViolation> for TmfError {
+    fn from(violation: PasswordPolicyViolation) -> Self {
+        TmfError::Validation(violation.to_string())
+    }
+}
+
+/// Error setting a password
+#[derive(Debug)]
+pub enum PasswordError {
+    Policy(PasswordPolicyViolation),
+    Tmf(TmfError),
+}
+
+impl From<PasswordPolicyViolation> for PasswordError {
+    fn from(violation: PasswordPolicyViolation) -> Self {
+        PasswordError::Policy(violation)
+    }
+}
+
+impl From<TmfError> for PasswordError {
+    fn from(err: TmfError) -> Self {
+        PasswordError::Tmf(err)
+    }
+}
+
+impl From<PasswordError> for TmfError {
+    fn from(err: PasswordError) -> Self {
+        match err {
+            PasswordError::Policy(violation) => violation.into(),
+            PasswordError::Tmf(err) => err,
+        }
+    }
+}
+
+/// Configurable password policy
+#[derive(Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Number of previous passwords that may not be reused
+    pub history_depth: usize,
+    /// Breached password source; no breach check when unset
+    pub breached_passwords: Option<Arc<dyn BreachedPasswordRange>>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 12,
+            max_length: 128,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: false,
+            history_depth: 5,
+            breached_passwords: None,
+        }
+    }
+}
+
+fn violation(rule: PasswordRule, message: impl Into<String>) -> PasswordPolicyViolation {
+    PasswordPolicyViolation {
+        rule,
+        message: message.into(),
+    }
+}
+
+impl PasswordPolicy {
+    pub fn with_breached_passwords(mut self, source: Arc<dyn BreachedPasswordRange>) -> Self {
+        self.breached_passwords = Some(source);
+        self
+    }
+
+    /// Check length and character-class rules
+    pub fn check_strength(&self, password: &str) -> Result<(), PasswordPolicyViolation> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(violation(
+                PasswordRule::MinLength,
+                format!("must be at least {} characters", self.min_length),
+            ));
+        }
+        if length > self.max_length {
+            return Err(violation(
+                PasswordRule::MaxLength,
+                format!("must be at most {} characters", self.max_length),
+            ));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Err(violation(
+                PasswordRule::Uppercase,
+                "must contain an uppercase letter",
+            ));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            return Err(violation(
+                PasswordRule::Lowercase,
+                "must contain a lowercase letter",
+            ));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err(violation(PasswordRule::Digit, "must contain a digit"));
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            return Err(violation(PasswordRule::Symbol, "must contain a symbol"));
+        }
+        Ok(())
+    }
+
+    /// Check the password against the breached password source
+    pub async fn check_breached(&self, password: &str) -> Result<(), PasswordError> {
+        let Some(source) = &self.breached_passwords else {
+            return Ok(());
+        };
+        let hash = sha1_hex(password);
+        let (prefix, suffix) = hash.split_at(RANGE_PREFIX_LEN);
+        if source.range(prefix).await?.contains(suffix) {
+            return Err(violation(
+                PasswordRule::Breached,
+                "appears in a list of common or breached passwords",
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Check the password against previously used password hashes, most recent first
+    pub fn check_reuse(
+        &self,
+        password: &str,
+        previous_hashes: &[String],
+    ) -> Result<(), PasswordPolicyViolation> {
+        if previous_hashes
+            .iter()
+            .take(self.history_depth)
+            .any(|stored| verify_password(password, stored))
+        {
+            return Err(violation(
+                PasswordRule::Reused,
+                format!(
+                    "must not match any of the last {} passwords",
+                    self.history_depth
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply every rule of the policy
+    pub async fn check(
+        &self,
+        password: &str,
+        previous_hashes: &[String],
+    ) -> Result<(), PasswordError> {
+        self.check_strength(password)?;
+        self.check_reuse(password, previous_hashes)?;
+        self.check_breached(password).await
+    }
+}
+
+/// Uppercase hex SHA-1 of a password, as used for range lookups
+pub fn sha1_hex(password: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(password.as_bytes());
+    format!("{:X}", hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn pbkdf2_sha256(salt: &str, password: &str, iterations: u32) -> [u8; HASH_LEN] {
+    let mut hash = [0u8; HASH_LEN];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt.as_bytes(), iterations, &mut hash);
+    hash
+}
+
+/// Unstretched hash of the earlier `sha256$<salt>$<hash>` format, still
+/// accepted so stored credentials and password history keep verifying
+fn legacy_salted_sha256(salt: &str, password: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(password.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash a password for storage as `pbkdf2-sha256$<iterations>$<salt>$<hash>`
+pub fn hash_password(password: &str) -> String {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let salt = to_hex(&salt);
+    let hash = pbkdf2_sha256(&salt, password, PBKDF2_ITERATIONS);
+    format!(
+        "pbkdf2-sha256${}${}${}",
+        PBKDF2_ITERATIONS,
+        salt,
+        to_hex(&hash)
+    )
+}
+
+/// Check a password against a stored hash in constant time
+pub fn verify_password(password: &str, stored: &str) -> bool {
+    match stored.split('$').collect::<Vec<_>>().as_slice() {
+        ["pbkdf2-sha256", iterations, salt, hash] => match iterations.parse::<u32>() {
+            Ok(iterations) if iterations > 0 => {
+                let computed = to_hex(&pbkdf2_sha256(salt, password, iterations));
+                computed.as_bytes().ct_eq(hash.as_bytes()).into()
+            }
+            _ => false,
+        },
+        ["sha256", salt, hash] => legacy_salted_sha256(salt, password)
+            .as_bytes()
+            .ct_eq(hash.as_bytes())
+            .into(),
+        _ => false,
+    }
+}
@@ -0,0 +1,451 @@
+//! Credential usage analytics for TMF669
+//!
+//! Every reported use of a credential updates a per-credential baseline of
+//! where it is used from, at which hours of the day and how many calls it
+//! makes per rate window. Uses that stand out from the baseline are flagged
+//! as anomalies and published as security events; the request itself is never
+//! blocked. Baselines adapt: location and hour weights decay with a
+//! configurable half-life, so a credential that moves to a new region or
+//! schedule stops being flagged once the new pattern is established.
+
+use crate::models::{
+    CredentialUsageAnomaly, CredentialUsageRequest, CredentialUsageSummary, UsageAnomalyKind,
+};
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use chrono::{DateTime, Timelike, Utc};
+use log::{info, warn};
+use sqlx::{PgPool, Pool, Postgres, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+/// Event published on the security events topic for each anomaly
+pub const CREDENTIAL_USAGE_ANOMALY_EVENT: &str = "CredentialUsageAnomaly";
+
+const HOURS_PER_DAY: usize = 24;
+
+fn map_sqlx_error(err: sqlx::Error) -> TmfError {
+    TmfError::Database(err.to_string())
+}
+
+/// Anomaly detection settings
+#[derive(Debug, Clone)]
+pub struct UsageAnomalyConfig {
+    /// Uses to learn from before anything is flagged
+    pub min_observations: u64,
+    /// Time after which the weight of a location or hour of use has halved
+    pub baseline_half_life: chrono::Duration,
+    /// Locations whose decayed weight falls below this are forgotten
+    pub forget_below: f64,
+    /// Hours of the day with less than this share of recent use are unusual
+    pub unusual_hour_share: f64,
+    /// Window over which calls are counted for rate anomalies
+    pub rate_window: chrono::Duration,
+    /// A window is unusual when it has this many times the mean calls...
+    pub rate_multiplier: f64,
+    /// ...and at least this many calls
+    pub min_window_calls: u32,
+    /// Weight of each completed window in the mean calls per window
+    pub rate_adaptation: f64,
+}
+
+impl Default for UsageAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            min_observations: 50,
+            baseline_half_life: chrono::Duration::days(14),
+            forget_below: 0.1,
+            unusual_hour_share: 0.02,
+            rate_window: chrono::Duration::minutes(5),
+            rate_multiplier: 5.0,
+            min_window_calls: 20,
+            rate_adaptation: 0.1,
+        }
+    }
+}
+
+/// Usage baseline of a single credential
+#[derive(Debug, Clone, Default)]
+struct UsageBaseline {
+    observations: u64,
+    location_weights: HashMap<String, f64>,
+    hour_weights: Vec<f64>,
+    mean_window_calls: f64,
+    completed_windows: u64,
+    window_start: Option<DateTime<Utc>>,
+    window_calls: u32,
+    window_flagged: bool,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+impl UsageBaseline {
+    /// Record a use, returning what was unusual about it
+    ///
+    /// Each use is compared with the baseline before it is folded in.
+    fn observe(
+        &mut self,
+        location: Option<&str>,
+        used_at: DateTime<Utc>,
+        config: &UsageAnomalyConfig,
+    ) -> Vec<(UsageAnomalyKind, String)> {
+        let mut anomalies = Vec::new();
+        let warmed_up = self.observations >= config.min_observations;
+        self.decay(used_at, config);
+
+        if let Some(location) = location.map(|l| l.trim().to_uppercase()) {
+            if !location.is_empty() {
+                if warmed_up && !self.location_weights.contains_key(&location) {
+                    anomalies.push((
+                        UsageAnomalyKind::UnusualLocation,
+                        format!(
+                            "First use from {}; usually used from {}",
+                            location,
+                            self.usual_locations().join(", ")
+                        ),
+                    ));
+                }
+                *self.location_weights.entry(location).or_default() += 1.0;
+            }
+        }
+
+        let hour = used_at.hour() as usize;
+        let total: f64 = self.hour_weights.iter().sum();
+        let share = if total > 0.0 {
+            self.hour_weights[hour] / total
+        } else {
+            0.0
+        };
+        if warmed_up && total > 0.0 && share < config.unusual_hour_share {
+            anomalies.push((
+                UsageAnomalyKind::UnusualTime,
+                format!(
+                    "Used at {:02}:00 UTC, an hour with {:.1}% of recent use",
+                    hour,
+                    share * 100.0
+                ),
+            ));
+        }
+        self.hour_weights[hour] += 1.0;
+
+        match self.window_start {
+            Some(start) if used_at >= start + config.rate_window => {
+                self.complete_window(config);
+                self.window_start = Some(used_at);
+            }
+            Some(_) => {}
+            None => self.window_start = Some(used_at),
+        }
+        self.window_calls += 1;
+        let limit =
+            (self.mean_window_calls * config.rate_multiplier).max(config.min_window_calls as f64);
+        if warmed_up
+            && self.completed_windows > 0
+            && !self.window_flagged
+            && self.window_calls as f64 > limit
+        {
+            self.window_flagged = true;
+            anomalies.push((
+                UsageAnomalyKind::UnusualRate,
+                format!(
+                    "{} calls within {} minutes; usually {:.1}",
+                    self.window_calls,
+                    config.rate_window.num_minutes(),
+                    self.mean_window_calls
+                ),
+            ));
+        }
+
+        self.observations += 1;
+        self.last_used_at = self.last_used_at.max(Some(used_at));
+        anomalies
+    }
+
+    /// Decay location and hour weights for the time since the last use
+    fn decay(&mut self, now: DateTime<Utc>, config: &UsageAnomalyConfig) {
+        self.hour_weights.resize(HOURS_PER_DAY, 0.0);
+        let Some(last) = self.last_used_at else {
+            return;
+        };
+        let elapsed = (now - last).num_seconds().max(0) as f64;
+        let half_life = config.baseline_half_life.num_seconds().max(1) as f64;
+        let factor = 0.5_f64.powf(elapsed / half_life);
+
+        for weight in self.location_weights.values_mut() {
+            *weight *= factor;
+        }
+        self.location_weights
+            .retain(|_, weight| *weight >= config.forget_below);
+        for weight in &mut self.hour_weights {
+            *weight *= factor;
+        }
+    }
+
+    /// Fold the calls of the current window into the mean and start afresh
+    fn complete_window(&mut self, config: &UsageAnomalyConfig) {
+        let calls = self.window_calls as f64;
+        self.mean_window_calls = if self.completed_windows == 0 {
+            calls
+        } else {
+            self.mean_window_calls + config.rate_adaptation * (calls - self.mean_window_calls)
+        };
+        self.completed_windows += 1;
+        self.window_calls = 0;
+        self.window_flagged = false;
+    }
+
+    /// Known locations, most used first
+    fn usual_locations(&self) -> Vec<String> {
+        let mut locations: Vec<_> = self.location_weights.iter().collect();
+        locations.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        locations.into_iter().map(|(l, _)| l.clone()).collect()
+    }
+
+    fn from_row(row: &sqlx::postgres::PgRow) -> TmfResult<Self> {
+        Ok(Self {
+            observations: row.get::<i64, _>("observations") as u64,
+            location_weights: serde_json::from_value(row.get("location_weights"))
+                .map_err(|e| TmfError::Internal(e.to_string()))?,
+            hour_weights: serde_json::from_value(row.get("hour_weights"))
+                .map_err(|e| TmfError::Internal(e.to_string()))?,
+            mean_window_calls: row.get("mean_window_calls"),
+            completed_windows: row.get::<i64, _>("completed_windows") as u64,
+            window_start: row.get("window_start"),
+            window_calls: row.get::<i32, _>("window_calls") as u32,
+            window_flagged: row.get("window_flagged"),
+            last_used_at: row.get("last_used_at"),
+        })
+    }
+}
+
+/// Record a use of a credential and flag anything unusual about it
+///
+/// Anomalies are stored and published as security events; failing to publish
+/// is logged and does not fail the call.
+pub async fn record_credential_usage(
+    pool: &Pool<Postgres>,
+    publisher: &dyn EventPublisher,
+    config: &UsageAnomalyConfig,
+    credential_id: Uuid,
+    usage: &CredentialUsageRequest,
+) -> TmfResult<Vec<CredentialUsageAnomaly>> {
+    let identity_id = credential_identity(pool, credential_id).await?;
+    let used_at = usage.used_at.unwrap_or_else(Utc::now);
+
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    sqlx::query(
+        "INSERT INTO credential_usage_baselines (credential_id) VALUES ($1)
+         ON CONFLICT (credential_id) DO NOTHING",
+    )
+    .bind(credential_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+    let row = sqlx::query(
+        "SELECT observations, location_weights, hour_weights, mean_window_calls,
+         completed_windows, window_start, window_calls, window_flagged, last_used_at
+         FROM credential_usage_baselines WHERE credential_id = $1 FOR UPDATE",
+    )
+    .bind(credential_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut baseline = UsageBaseline::from_row(&row)?;
+    let flagged = baseline.observe(usage.location.as_deref(), used_at, config);
+
+    sqlx::query(
+        "UPDATE credential_usage_baselines SET observations = $2, location_weights = $3,
+         hour_weights = $4, mean_window_calls = $5, completed_windows = $6, window_start = $7,
+         window_calls = $8, window_flagged = $9, last_used_at = $10
+         WHERE credential_id = $1",
+    )
+    .bind(credential_id)
+    .bind(baseline.observations as i64)
+    .bind(serde_json::json!(baseline.location_weights))
+    .bind(serde_json::json!(baseline.hour_weights))
+    .bind(baseline.mean_window_calls)
+    .bind(baseline.completed_windows as i64)
+    .bind(baseline.window_start)
+    .bind(baseline.window_calls as i32)
+    .bind(baseline.window_flagged)
+    .bind(baseline.last_used_at)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let mut anomalies = Vec::with_capacity(flagged.len());
+    for (kind, detail) in flagged {
+        let anomaly = CredentialUsageAnomaly {
+            id: Uuid::new_v4(),
+            credential_id,
+            identity_id,
+            kind,
+            detail,
+            location: usage.location.clone(),
+            source_ip: usage.source_ip.clone(),
+            observed_at: used_at,
+        };
+        sqlx::query(
+            "INSERT INTO credential_usage_anomalies (id, credential_id, identity_id, kind,
+             detail, location, source_ip, observed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(anomaly.id)
+        .bind(credential_id)
+        .bind(identity_id)
+        .bind(anomaly_kind_to_string(kind))
+        .bind(&anomaly.detail)
+        .bind(&anomaly.location)
+        .bind(&anomaly.source_ip)
+        .bind(used_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_sqlx_error)?;
+        anomalies.push(anomaly);
+    }
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    for anomaly in &anomalies {
+        if let Err(e) = publish_anomaly(publisher, anomaly).await {
+            warn!(
+                "Failed to publish {} for credential {}: {}",
+                CREDENTIAL_USAGE_ANOMALY_EVENT, credential_id, e
+            );
+        }
+    }
+
+    Ok(anomalies)
+}
+
+/// Record a credential use in the background so the request is not held up
+pub fn track_credential_usage(
+    pool: PgPool,
+    publisher: Arc<dyn EventPublisher>,
+    config: UsageAnomalyConfig,
+    credential_id: Uuid,
+    usage: CredentialUsageRequest,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        match record_credential_usage(&pool, publisher.as_ref(), &config, credential_id, &usage)
+            .await
+        {
+            Ok(anomalies) if !anomalies.is_empty() => info!(
+                "Flagged {} usage anomalies for credential {}",
+                anomalies.len(),
+                credential_id
+            ),
+            Ok(_) => {}
+            Err(e) => warn!(
+                "Failed to record usage of credential {}: {}",
+                credential_id, e
+            ),
+        }
+    })
+}
+
+async fn publish_anomaly(
+    publisher: &dyn EventPublisher,
+    anomaly: &CredentialUsageAnomaly,
+) -> TmfResult<()> {
+    let data = serde_json::to_value(anomaly).map_err(|e| TmfError::Internal(e.to_string()))?;
+    let envelope = EventEnvelope::new(
+        CREDENTIAL_USAGE_ANOMALY_EVENT.to_string(),
+        "tmf669-identity".to_string(),
+        data,
+    );
+    publisher
+        .publish(topics::SECURITY_EVENTS, envelope)
+        .await
+        .map_err(|e| TmfError::Internal(e.to_string()))
+}
+
+async fn credential_identity(pool: &Pool<Postgres>, credential_id: Uuid) -> TmfResult<Uuid> {
+    sqlx::query_scalar("SELECT identity_id FROM identity_credentials WHERE id = $1")
+        .bind(credential_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(map_sqlx_error)?
+        .ok_or_else(|| {
+            TmfError::NotFound(format!("Credential with id {} not found", credential_id))
+        })
+}
+
+/// Get the usage baseline of a credential
+pub async fn get_usage_summary(
+    pool: &Pool<Postgres>,
+    credential_id: Uuid,
+) -> TmfResult<CredentialUsageSummary> {
+    credential_identity(pool, credential_id).await?;
+
+    let row = sqlx::query(
+        "SELECT observations, location_weights, hour_weights, mean_window_calls,
+         completed_windows, window_start, window_calls, window_flagged, last_used_at
+         FROM credential_usage_baselines WHERE credential_id = $1",
+    )
+    .bind(credential_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    let baseline = match row {
+        Some(row) => UsageBaseline::from_row(&row)?,
+        None => UsageBaseline::default(),
+    };
+
+    Ok(CredentialUsageSummary {
+        credential_id,
+        observations: baseline.observations,
+        usual_locations: baseline.usual_locations(),
+        mean_window_calls: baseline.mean_window_calls,
+        last_used_at: baseline.last_used_at,
+    })
+}
+
+/// Get the usage anomalies flagged for a credential, latest first
+pub async fn get_usage_anomalies(
+    pool: &Pool<Postgres>,
+    credential_id: Uuid,
+) -> TmfResult<Vec<CredentialUsageAnomaly>> {
+    credential_identity(pool, credential_id).await?;
+
+    let rows = sqlx::query(
+        "SELECT id, credential_id, identity_id, kind, detail, location, source_ip, observed_at
+         FROM credential_usage_anomalies WHERE credential_id = $1
+         ORDER BY observed_at DESC",
+    )
+    .bind(credential_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| CredentialUsageAnomaly {
+            id: row.get("id"),
+            credential_id: row.get("credential_id"),
+            identity_id: row.get("identity_id"),
+            kind: parse_anomaly_kind(&row.get::<String, _>("kind")),
+            detail: row.get("detail"),
+            location: row.get("location"),
+            source_ip: row.get("source_ip"),
+            observed_at: row.get("observed_at"),
+        })
+        .collect())
+}
+
+fn parse_anomaly_kind(s: &str) -> UsageAnomalyKind {
+    match s.to_uppercase().as_str() {
+        "UNUSUAL_RATE" => UsageAnomalyKind::UnusualRate,
+        "UNUSUAL_TIME" => UsageAnomalyKind::UnusualTime,
+        _ => UsageAnomalyKind::UnusualLocation,
+    }
+}
+
+fn anomaly_kind_to_string(kind: UsageAnomalyKind) -> &'static str {
+    match kind {
+        UsageAnomalyKind::UnusualLocation => "UNUSUAL_LOCATION",
+        UsageAnomalyKind::UnusualRate => "UNUSUAL_RATE",
+        UsageAnomalyKind::UnusualTime => "UNUSUAL_TIME",
+    }
+}
@@ -1,8 +1,11 @@
 //! Database operations for TMF678 Customer Bill Management
 
-use crate::models::{BillState, CreateCustomerBillRequest, CustomerBill, Money};
+use crate::models::{
+    BillItem, BillItemCategory, BillState, CreateCustomerBillRequest, CustomerBill, Money,
+};
+use crate::subtotal::{compute_totals, reconcile_total};
 use chrono::{DateTime, Utc};
-use sqlx::{Pool, Postgres, Row};
+use sqlx::{PgConnection, Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
 use uuid::Uuid;
 
@@ -32,6 +35,55 @@ fn bill_state_to_string(state: &BillState) -> String {
     }
 }
 
+/// Parse bill item category from database string
+fn parse_bill_item_category(s: &str) -> BillItemCategory {
+    match s.to_uppercase().as_str() {
+        "SUBSCRIPTION" => BillItemCategory::Subscription,
+        "USAGE" => BillItemCategory::Usage,
+        "TAX" => BillItemCategory::Tax,
+        _ => BillItemCategory::OneTime,
+    }
+}
+
+/// Convert bill item category to database string
+fn bill_item_category_to_string(category: &BillItemCategory) -> String {
+    match category {
+        BillItemCategory::Subscription => "SUBSCRIPTION".to_string(),
+        BillItemCategory::Usage => "USAGE".to_string(),
+        BillItemCategory::OneTime => "ONE_TIME".to_string(),
+        BillItemCategory::Tax => "TAX".to_string(),
+    }
+}
+
+/// Get the line items of a bill
+pub async fn get_bill_items(pool: &Pool<Postgres>, bill_id: Uuid) -> TmfResult<Vec<BillItem>> {
+    let rows = sqlx::query(
+        "SELECT id, description, category, amount_value, amount_unit, quantity
+         FROM bill_items WHERE bill_id = $1 ORDER BY created_at, id",
+    )
+    .bind(bill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| BillItem {
+            id: row.get::<Uuid, _>("id"),
+            description: row.get::<String, _>("description"),
+            category: parse_bill_item_category(&row.get::<String, _>("category")),
+            amount: Money {
+                value: row.get::<f64, _>("amount_value"),
+                unit: row
+                    .get::<Option<String>, _>("amount_unit")
+                    .unwrap_or_else(|| "USD".to_string()),
+            },
+            quantity: row.get::<Option<i32>, _>("quantity"),
+            product_offering: None,
+        })
+        .collect())
+}
+
 /// Get all customer bills
 pub async fn get_bills(pool: &Pool<Postgres>) -> TmfResult<Vec<CustomerBill>> {
     let rows = sqlx::query(
@@ -70,7 +122,8 @@ pub async fn get_bills(pool: &Pool<Postgres>) -> TmfResult<Vec<CustomerBill>> {
             due_date: row.get::<Option<DateTime<Utc>>, _>("due_date"),
             total_amount,
             tax_included: row.get::<bool, _>("tax_included"),
-            bill_item: None,     // Load separately if needed
+            bill_item: None, // Load separately if needed
+            category_subtotal: None,
             related_party: None, // Load separately if needed
         });
     }
@@ -93,11 +146,20 @@ pub async fn get_bill_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Custom
 
     let total_amount_value: Option<f64> = row.get("total_amount_value");
     let total_amount_unit: Option<String> = row.get("total_amount_unit");
-    let total_amount = if let (Some(value), Some(unit)) = (total_amount_value, total_amount_unit) {
-        Some(Money { value, unit })
-    } else {
-        None
-    };
+    let mut total_amount =
+        if let (Some(value), Some(unit)) = (total_amount_value, total_amount_unit) {
+            Some(Money { value, unit })
+        } else {
+            None
+        };
+
+    // Group items by category; the grand total is the exact sum of the subtotals
+    let items = get_bill_items(pool, id).await?;
+    let totals = compute_totals(items.iter().map(|item| (item.category, &item.amount)))?;
+    let category_subtotal = totals.map(|totals| {
+        total_amount = Some(totals.grand_total);
+        totals.subtotals
+    });
 
     Ok(CustomerBill {
         base: tmf_apis_core::BaseEntity {
@@ -115,22 +177,81 @@ pub async fn get_bill_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Custom
         due_date: row.get::<Option<DateTime<Utc>>, _>("due_date"),
         total_amount,
         tax_included: row.get::<bool, _>("tax_included"),
-        bill_item: None,
+        bill_item: if items.is_empty() { None } else { Some(items) },
+        category_subtotal,
         related_party: None,
     })
 }
 
+/// Get the latest bill of a customer, matched by the id of its related party
+/// in the customer role
+pub async fn get_latest_bill_by_customer(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+) -> TmfResult<Option<CustomerBill>> {
+    let row = sqlx::query(
+        "SELECT b.id FROM customer_bills b
+         WHERE EXISTS (
+             SELECT 1 FROM bill_related_parties p
+             WHERE p.bill_id = b.id AND p.party_id = $1 AND LOWER(p.role) = 'customer'
+         )
+         ORDER BY b.bill_date DESC NULLS LAST LIMIT 1",
+    )
+    .bind(customer_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    match row {
+        Some(row) => get_bill_by_id(pool, row.get::<Uuid, _>("id"))
+            .await
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
 /// Create a new customer bill
 pub async fn create_bill(
     pool: &Pool<Postgres>,
     request: CreateCustomerBillRequest,
 ) -> TmfResult<CustomerBill> {
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+    let id = insert_bill(&mut tx, request).await?;
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    // Fetch the created bill
+    get_bill_by_id(pool, id).await
+}
+
+/// Insert a bill with its items and related parties on an open connection,
+/// so callers can create it in their own transaction
+pub async fn insert_bill(
+    conn: &mut PgConnection,
+    request: CreateCustomerBillRequest,
+) -> TmfResult<Uuid> {
     let id = Uuid::new_v4();
     let state = bill_state_to_string(&BillState::Pending);
     let now = Utc::now();
 
-    let total_amount_value = request.total_amount.as_ref().map(|m| m.value);
-    let total_amount_unit = request.total_amount.as_ref().map(|m| m.unit.clone());
+    // A stated total must reconcile with the items; otherwise it is derived from them
+    let totals = compute_totals(
+        request
+            .bill_item
+            .iter()
+            .flatten()
+            .map(|item| (item.category, &item.amount)),
+    )?;
+    let total_amount = match (&request.total_amount, totals) {
+        (Some(stated), Some(totals)) => {
+            reconcile_total(stated, &totals)?;
+            Some(totals.grand_total)
+        }
+        (None, Some(totals)) => Some(totals.grand_total),
+        (stated, None) => stated.clone(),
+    };
+
+    let total_amount_value = total_amount.as_ref().map(|m| m.value);
+    let total_amount_unit = total_amount.as_ref().map(|m| m.unit.clone());
 
     sqlx::query(
         "INSERT INTO customer_bills (id, name, description, version, state, bill_date, due_date, 
@@ -147,7 +268,7 @@ pub async fn create_bill(
     .bind(total_amount_value)
     .bind(total_amount_unit)
     .bind(request.tax_included)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .map_err(map_sqlx_error)?;
 
@@ -156,17 +277,18 @@ pub async fn create_bill(
         for item in items {
             let item_id = Uuid::new_v4();
             sqlx::query(
-                "INSERT INTO bill_items (id, bill_id, description, amount_value, amount_unit, quantity, product_offering_id)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                "INSERT INTO bill_items (id, bill_id, description, category, amount_value, amount_unit, quantity, product_offering_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
             )
             .bind(item_id)
             .bind(id)
             .bind(&item.description)
+            .bind(bill_item_category_to_string(&item.category))
             .bind(item.amount.value)
             .bind(&item.amount.unit)
             .bind(item.quantity)
             .bind(item.product_offering_id)
-            .execute(pool)
+            .execute(&mut *conn)
             .await
             .map_err(map_sqlx_error)?;
         }
@@ -175,21 +297,20 @@ pub async fn create_bill(
     // Create related parties if provided
     if let Some(parties) = request.related_party {
         for party in parties {
-            let party_id = Uuid::new_v4();
             sqlx::query(
-                "INSERT INTO bill_related_parties (id, bill_id, name, role)
-                 VALUES ($1, $2, $3, $4)",
+                "INSERT INTO bill_related_parties (id, bill_id, party_id, name, role)
+                 VALUES ($1, $2, $3, $4, $5)",
             )
-            .bind(party_id)
+            .bind(Uuid::new_v4())
             .bind(id)
+            .bind(party.id)
             .bind(&party.name)
             .bind(&party.role)
-            .execute(pool)
+            .execute(&mut *conn)
             .await
             .map_err(map_sqlx_error)?;
         }
     }
 
-    // Fetch the created bill
-    get_bill_by_id(pool, id).await
+    Ok(id)
 }
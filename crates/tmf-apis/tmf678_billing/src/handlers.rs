@@ -95,6 +95,9 @@ pub async fn create_bill(
 
     match db::create_bill(pool.get_ref(), body.into_inner()).await {
         Ok(bill) => Ok(HttpResponse::Created().json(bill)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
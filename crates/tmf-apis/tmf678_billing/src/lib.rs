@@ -8,10 +8,14 @@ pub mod auth;
 pub mod db;
 pub mod handlers;
 pub mod models;
+pub mod subtotal;
 
 pub use auth::*;
 pub use handlers::*;
 pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
-pub use db::{get_bill_by_id as db_get_bill_by_id, get_bills as db_get_bills};
+pub use db::{
+    get_bill_by_id as db_get_bill_by_id, get_bills as db_get_bills,
+    get_latest_bill_by_customer as db_get_latest_bill_by_customer,
+};
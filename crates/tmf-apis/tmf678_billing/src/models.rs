@@ -40,11 +40,25 @@ pub struct CustomerBill {
     /// Bill items
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bill_item: Option<Vec<BillItem>>,
+    /// Per-category subtotals of the bill items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_subtotal: Option<Vec<CategorySubtotal>>,
     /// Related party (customer)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
 }
 
+/// Bill Item Category - Grouping of a charge on the bill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BillItemCategory {
+    Subscription,
+    Usage,
+    #[default]
+    OneTime,
+    Tax,
+}
+
 /// Bill Item - Individual item within a bill
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BillItem {
@@ -52,6 +66,8 @@ pub struct BillItem {
     pub id: Uuid,
     /// Item description
     pub description: String,
+    /// Item category
+    pub category: BillItemCategory,
     /// Item amount
     pub amount: Money,
     /// Item quantity
@@ -62,6 +78,15 @@ pub struct BillItem {
     pub product_offering: Option<ProductOfferingRef>,
 }
 
+/// Category Subtotal - Sum of the bill items in one category
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CategorySubtotal {
+    pub category: BillItemCategory,
+    pub amount: Money,
+    /// Number of items in the category
+    pub item_count: usize,
+}
+
 /// Money representation
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Money {
@@ -118,6 +143,8 @@ pub struct CreateCustomerBillRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateBillItemRequest {
     pub description: String,
+    #[serde(default)]
+    pub category: BillItemCategory,
     pub amount: Money,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<i32>,
@@ -129,6 +156,10 @@ pub struct CreateBillItemRequest {
 /// Request to create a related party
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateRelatedPartyRequest {
+    /// Id of the referenced party, e.g. the customer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub id: Option<Uuid>,
     pub name: String,
     pub role: String,
 }
@@ -0,0 +1,103 @@
+//! Bill line-item categorization and subtotaling
+//!
+//! Amounts are summed in minor units (cents, matching the two-decimal storage
+//! of bill amounts) so the category subtotals always add up to the grand total
+//! exactly, without floating-point drift.
+
+use crate::models::{BillItemCategory, CategorySubtotal, Money};
+use tmf_apis_core::{TmfError, TmfResult};
+
+/// Order in which categories are presented on a bill; tax always comes last
+pub const CATEGORY_ORDER: [BillItemCategory; 4] = [
+    BillItemCategory::Subscription,
+    BillItemCategory::Usage,
+    BillItemCategory::OneTime,
+    BillItemCategory::Tax,
+];
+
+/// Category subtotals and the grand total they reconcile to
+#[derive(Debug, Clone)]
+pub struct BillTotals {
+    pub subtotals: Vec<CategorySubtotal>,
+    pub grand_total: Money,
+}
+
+/// Convert an amount to minor units, rounding to the nearest cent
+pub fn to_minor_units(value: f64) -> i64 {
+    (value * 100.0).round() as i64
+}
+
+fn from_minor_units(units: i64) -> f64 {
+    units as f64 / 100.0
+}
+
+/// Compute per-category subtotals and the grand total of a set of line items
+///
+/// Returns `None` when there are no items. All items must share a currency.
+pub fn compute_totals<'a>(
+    items: impl IntoIterator<Item = (BillItemCategory, &'a Money)>,
+) -> TmfResult<Option<BillTotals>> {
+    let mut unit: Option<&str> = None;
+    let mut sums = [(0i64, 0usize); CATEGORY_ORDER.len()];
+
+    for (category, amount) in items {
+        match unit {
+            None => unit = Some(&amount.unit),
+            Some(u) if u != amount.unit => {
+                return Err(TmfError::Validation(format!(
+                    "Bill items must share a currency, found {} and {}",
+                    u, amount.unit
+                )));
+            }
+            Some(_) => {}
+        }
+
+        let slot = CATEGORY_ORDER
+            .iter()
+            .position(|c| *c == category)
+            .expect("every category is listed in CATEGORY_ORDER");
+        sums[slot].0 += to_minor_units(amount.value);
+        sums[slot].1 += 1;
+    }
+
+    let Some(unit) = unit else {
+        return Ok(None);
+    };
+
+    let subtotals: Vec<CategorySubtotal> = CATEGORY_ORDER
+        .iter()
+        .zip(sums)
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(category, (minor, count))| CategorySubtotal {
+            category: *category,
+            amount: Money {
+                value: from_minor_units(minor),
+                unit: unit.to_string(),
+            },
+            item_count: count,
+        })
+        .collect();
+
+    let grand_total: i64 = sums.iter().map(|(minor, _)| minor).sum();
+
+    Ok(Some(BillTotals {
+        subtotals,
+        grand_total: Money {
+            value: from_minor_units(grand_total),
+            unit: unit.to_string(),
+        },
+    }))
+}
+
+/// Check that a stated bill total matches the sum of its items to the cent
+pub fn reconcile_total(stated: &Money, totals: &BillTotals) -> TmfResult<()> {
+    if stated.unit != totals.grand_total.unit
+        || to_minor_units(stated.value) != to_minor_units(totals.grand_total.value)
+    {
+        return Err(TmfError::Validation(format!(
+            "Bill total {} {} does not match the sum of its items {} {}",
+            stated.value, stated.unit, totals.grand_total.value, totals.grand_total.unit
+        )));
+    }
+    Ok(())
+}
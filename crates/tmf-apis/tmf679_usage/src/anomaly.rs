@@ -0,0 +1,236 @@
+//! Usage anomaly detection
+//!
+//! Usage far above a subscriber's usual consumption can indicate fraud, such
+//! as a cloned SIM or a compromised device. Every ingested record is handed to
+//! a detector in the background, so detection never delays or rejects
+//! ingestion; suspect records are published as `UsageFraudSuspected` events on
+//! the fraud topic.
+//!
+//! The default detector learns a baseline per subscriber and usage type from
+//! recent history, with thresholds configured per customer segment.
+
+use crate::models::CustomerUsage;
+use async_trait::async_trait;
+use bss_oss_event_bus::events::{topics, EventEnvelope};
+use bss_oss_event_bus::EventPublisher;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tmf_apis_core::{TmfError, TmfResult};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Detector of suspect usage records
+#[async_trait]
+pub trait UsageAnomalyDetector: Send + Sync {
+    /// Inspect a newly ingested record, returning the anomaly if it is suspect
+    async fn inspect(
+        &self,
+        pool: &Pool<Postgres>,
+        usage: &CustomerUsage,
+    ) -> TmfResult<Option<UsageAnomaly>>;
+}
+
+/// Suspect usage record
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UsageAnomaly {
+    #[schema(value_type = String, format = "uuid")]
+    pub usage_id: Uuid,
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_type: Option<String>,
+    pub amount: f64,
+    /// Mean amount of the subscriber's recent records
+    pub baseline_mean: f64,
+    /// Standard deviation of the subscriber's recent records
+    pub baseline_std_dev: f64,
+    /// Amount above which records are flagged
+    pub threshold: f64,
+    #[schema(value_type = String, format = "date-time")]
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Anomaly thresholds of a customer segment
+#[derive(Debug, Clone)]
+pub struct AnomalyThresholds {
+    /// History the baseline is learned from
+    pub baseline_window: Duration,
+    /// Records needed in the window before the baseline is trusted
+    pub min_samples: i64,
+    /// Standard deviations above the mean at which a record is flagged
+    pub sensitivity: f64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            baseline_window: Duration::days(30),
+            min_samples: 10,
+            sensitivity: 3.0,
+        }
+    }
+}
+
+impl AnomalyThresholds {
+    /// Amount above which a record is flagged
+    ///
+    /// The spread is taken as at least a tenth of the mean, so that perfectly
+    /// regular usage is not flagged for any small increase.
+    pub fn threshold(&self, mean: f64, std_dev: f64) -> f64 {
+        mean + self.sensitivity * std_dev.max(mean.abs() / 10.0)
+    }
+}
+
+/// Anomaly detection configuration
+#[derive(Debug, Clone, Default)]
+pub struct UsageAnomalyConfig {
+    /// Thresholds of customers without a configured segment
+    pub default_thresholds: AnomalyThresholds,
+    /// Thresholds by customer segment
+    pub segment_thresholds: HashMap<String, AnomalyThresholds>,
+}
+
+impl UsageAnomalyConfig {
+    /// Thresholds applying to a segment
+    pub fn thresholds_for(&self, segment: Option<&str>) -> &AnomalyThresholds {
+        segment
+            .and_then(|segment| self.segment_thresholds.get(segment))
+            .unwrap_or(&self.default_thresholds)
+    }
+}
+
+/// Detector flagging records far above the subscriber's recent baseline
+///
+/// The baseline covers earlier records of the same customer and usage type.
+/// Records without a customer or amount are never flagged.
+#[derive(Debug, Clone, Default)]
+pub struct BaselineAnomalyDetector {
+    config: UsageAnomalyConfig,
+}
+
+impl BaselineAnomalyDetector {
+    /// Create a detector with the given configuration
+    pub fn new(config: UsageAnomalyConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl UsageAnomalyDetector for BaselineAnomalyDetector {
+    async fn inspect(
+        &self,
+        pool: &Pool<Postgres>,
+        usage: &CustomerUsage,
+    ) -> TmfResult<Option<UsageAnomaly>> {
+        let (Some(customer_id), Some(amount)) = (usage.customer_id, usage.amount) else {
+            return Ok(None);
+        };
+
+        let segment: Option<String> =
+            sqlx::query_scalar("SELECT segment FROM customers WHERE id = $1")
+                .bind(customer_id)
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| TmfError::Database(e.to_string()))?
+                .flatten();
+        let thresholds = self.config.thresholds_for(segment.as_deref());
+
+        let event_time = usage
+            .start_date
+            .or(usage.usage_date)
+            .unwrap_or_else(Utc::now);
+        let row = sqlx::query(
+            "SELECT COUNT(amount) AS samples, AVG(amount)::FLOAT8 AS mean,
+             STDDEV_POP(amount)::FLOAT8 AS std_dev
+             FROM customer_usages
+             WHERE customer_id = $1 AND usage_type IS NOT DISTINCT FROM $2 AND id <> $3
+             AND COALESCE(start_date, usage_date) >= $4
+             AND COALESCE(start_date, usage_date) < $5",
+        )
+        .bind(customer_id)
+        .bind(&usage.usage_type)
+        .bind(usage.base.id)
+        .bind(event_time - thresholds.baseline_window)
+        .bind(event_time)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| TmfError::Database(e.to_string()))?;
+
+        let samples: i64 = row.get("samples");
+        if samples < thresholds.min_samples {
+            return Ok(None);
+        }
+        let mean = row.get::<Option<f64>, _>("mean").unwrap_or_default();
+        let std_dev = row.get::<Option<f64>, _>("std_dev").unwrap_or_default();
+        let threshold = thresholds.threshold(mean, std_dev);
+        if amount <= threshold {
+            return Ok(None);
+        }
+
+        Ok(Some(UsageAnomaly {
+            usage_id: usage.base.id,
+            customer_id,
+            segment,
+            usage_type: usage.usage_type.clone(),
+            amount,
+            baseline_mean: mean,
+            baseline_std_dev: std_dev,
+            threshold,
+            detected_at: Utc::now(),
+        }))
+    }
+}
+
+/// Inspect an ingested record in the background, publishing a
+/// `UsageFraudSuspected` event if the detector flags it
+///
+/// Detection failures are logged; they never affect the ingested record.
+pub fn spawn_anomaly_check(
+    pool: Pool<Postgres>,
+    detector: Arc<dyn UsageAnomalyDetector>,
+    publisher: Arc<dyn EventPublisher>,
+    usage: CustomerUsage,
+) {
+    tokio::spawn(async move {
+        let anomaly = match detector.inspect(&pool, &usage).await {
+            Ok(Some(anomaly)) => anomaly,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!("Anomaly check of usage {} failed: {}", usage.base.id, e);
+                return;
+            }
+        };
+
+        log::warn!(
+            "Usage {} of customer {} is suspect: {} above threshold {}",
+            anomaly.usage_id,
+            anomaly.customer_id,
+            anomaly.amount,
+            anomaly.threshold
+        );
+        let data = match serde_json::to_value(&anomaly) {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("Failed to encode anomaly of usage {}: {}", usage.base.id, e);
+                return;
+            }
+        };
+        let event = EventEnvelope::new(
+            "UsageFraudSuspected".to_string(),
+            "tmf679-usage".to_string(),
+            data,
+        );
+        if let Err(e) = publisher.publish(topics::FRAUD_EVENTS, event).await {
+            log::warn!(
+                "Failed to publish fraud suspicion for usage {}: {}",
+                usage.base.id,
+                e
+            );
+        }
+    });
+}
@@ -12,6 +12,10 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_usages))
                     .route(web::post().to(create_usage)),
             )
+            .service(
+                web::resource("/customerUsage/rerating")
+                    .route(web::get().to(get_usages_requiring_rerating)),
+            )
             .service(web::resource("/customerUsage/{id}").route(web::get().to(get_usage_by_id))),
     );
 }
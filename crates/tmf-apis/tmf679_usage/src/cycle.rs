@@ -0,0 +1,86 @@
+//! Billing cycle attribution for usage records
+//!
+//! Usage is attributed to the customer's billing cycle covering its event
+//! time, not the cycle open when the record arrives. Records for a cycle that
+//! has already closed are accepted within a grace window and flagged for
+//! rerating; later records are rejected.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{Pool, Postgres, Row};
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+/// How late a usage record may arrive after its billing cycle closed
+#[derive(Debug, Clone)]
+pub struct LateUsagePolicy {
+    /// Accept records up to this long after the end of their cycle
+    pub grace_period: Duration,
+}
+
+impl Default for LateUsagePolicy {
+    fn default() -> Self {
+        Self {
+            grace_period: Duration::days(30),
+        }
+    }
+}
+
+/// Billing cycle a usage record was attributed to
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleAttribution {
+    pub billing_cycle_id: Uuid,
+    /// The cycle was no longer open when the record arrived
+    pub late_arrival: bool,
+}
+
+/// Find the billing cycle covering a usage event
+///
+/// Returns `None` when the customer has no cycle covering the event time.
+pub async fn attribute_to_cycle(
+    pool: &Pool<Postgres>,
+    customer_id: Uuid,
+    event_time: DateTime<Utc>,
+    policy: &LateUsagePolicy,
+    now: DateTime<Utc>,
+) -> TmfResult<Option<CycleAttribution>> {
+    let row = sqlx::query(
+        "SELECT id, end_date, status FROM billing_cycles
+         WHERE customer_id = $1 AND start_date <= $2 AND end_date > $2
+         ORDER BY start_date DESC LIMIT 1",
+    )
+    .bind(customer_id)
+    .bind(event_time)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| TmfError::Database(e.to_string()))?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let billing_cycle_id: Uuid = row.get("id");
+    let end_date: DateTime<Utc> = row.get("end_date");
+    let status: String = row.get("status");
+
+    if status.eq_ignore_ascii_case("OPEN") {
+        return Ok(Some(CycleAttribution {
+            billing_cycle_id,
+            late_arrival: false,
+        }));
+    }
+
+    if now - end_date > policy.grace_period {
+        return Err(TmfError::Validation(format!(
+            "Usage at {} belongs to billing cycle {} which closed at {}, beyond the {}-day late arrival window",
+            event_time,
+            billing_cycle_id,
+            end_date,
+            policy.grace_period.num_days()
+        )));
+    }
+
+    Ok(Some(CycleAttribution {
+        billing_cycle_id,
+        late_arrival: true,
+    }))
+}
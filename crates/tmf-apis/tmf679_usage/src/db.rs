@@ -1,5 +1,6 @@
 //! Database operations for TMF679 Customer Usage Management
 
+use crate::cycle::{attribute_to_cycle, LateUsagePolicy};
 use crate::models::{CreateCustomerUsageRequest, CustomerUsage, UsageState};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
@@ -34,55 +35,56 @@ fn usage_state_to_string(state: &UsageState) -> String {
 pub async fn get_usages(pool: &Pool<Postgres>) -> TmfResult<Vec<CustomerUsage>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, usage_date, start_date, end_date, 
-         usage_type, amount, unit, href, last_update
+         usage_type, amount, unit, href, last_update, customer_id, billing_cycle_id,
+         late_arrival, rerating_required
          FROM customer_usages ORDER BY usage_date DESC",
     )
     .fetch_all(pool)
     .await
     .map_err(map_sqlx_error)?;
 
-    let mut usages = Vec::new();
-    for row in rows {
-        usages.push(CustomerUsage {
-            base: tmf_apis_core::BaseEntity {
-                id: row.get::<Uuid, _>("id"),
-                href: row.get::<Option<String>, _>("href"),
-                name: row.get::<String, _>("name"),
-                description: row.get::<Option<String>, _>("description"),
-                version: row.get::<Option<String>, _>("version"),
-                lifecycle_status: tmf_apis_core::LifecycleStatus::Active,
-                last_update: row.get::<Option<DateTime<Utc>>, _>("last_update"),
-                valid_for: None,
-            },
-            state: parse_usage_state(&row.get::<String, _>("state")),
-            usage_date: row.get::<Option<DateTime<Utc>>, _>("usage_date"),
-            start_date: row.get::<Option<DateTime<Utc>>, _>("start_date"),
-            end_date: row.get::<Option<DateTime<Utc>>, _>("end_date"),
-            usage_type: row.get::<Option<String>, _>("usage_type"),
-            amount: row.get::<Option<f64>, _>("amount"),
-            unit: row.get::<Option<String>, _>("unit"),
-            product_offering: None, // Load separately if needed
-            related_party: None,    // Load separately if needed
-        });
-    }
+    Ok(rows.iter().map(row_to_usage).collect())
+}
+
+/// Get late-arriving usages awaiting rerating against their closed cycle
+pub async fn get_usages_requiring_rerating(pool: &Pool<Postgres>) -> TmfResult<Vec<CustomerUsage>> {
+    let rows = sqlx::query(
+        "SELECT id, name, description, version, state, usage_date, start_date, end_date,
+         usage_type, amount, unit, href, last_update, customer_id, billing_cycle_id,
+         late_arrival, rerating_required
+         FROM customer_usages WHERE rerating_required = true ORDER BY billing_cycle_id, usage_date",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
 
-    Ok(usages)
+    Ok(rows.iter().map(row_to_usage).collect())
 }
 
-/// Get customer usage by ID
-pub async fn get_usage_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<CustomerUsage> {
-    let row = sqlx::query(
-        "SELECT id, name, description, version, state, usage_date, start_date, end_date, 
-         usage_type, amount, unit, href, last_update
-         FROM customer_usages WHERE id = $1",
+/// Clear the rerating flag once an adjustment has been issued
+pub async fn mark_usage_rerated(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<()> {
+    let result = sqlx::query(
+        "UPDATE customer_usages SET rerating_required = false, rerated_at = $1, last_update = $1
+         WHERE id = $2 AND rerating_required = true",
     )
+    .bind(Utc::now())
     .bind(id)
-    .fetch_optional(pool)
+    .execute(pool)
     .await
-    .map_err(map_sqlx_error)?
-    .ok_or_else(|| TmfError::NotFound(format!("Customer usage with id {} not found", id)))?;
+    .map_err(map_sqlx_error)?;
 
-    Ok(CustomerUsage {
+    if result.rows_affected() == 0 {
+        return Err(TmfError::NotFound(format!(
+            "Customer usage with id {} awaiting rerating not found",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Convert a database row to a customer usage
+fn row_to_usage(row: &sqlx::postgres::PgRow) -> CustomerUsage {
+    CustomerUsage {
         base: tmf_apis_core::BaseEntity {
             id: row.get::<Uuid, _>("id"),
             href: row.get::<Option<String>, _>("href"),
@@ -100,24 +102,58 @@ pub async fn get_usage_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Custo
         usage_type: row.get::<Option<String>, _>("usage_type"),
         amount: row.get::<Option<f64>, _>("amount"),
         unit: row.get::<Option<String>, _>("unit"),
-        product_offering: None,
-        related_party: None,
-    })
+        product_offering: None, // Load separately if needed
+        related_party: None,    // Load separately if needed
+        customer_id: row.get::<Option<Uuid>, _>("customer_id"),
+        billing_cycle_id: row.get::<Option<Uuid>, _>("billing_cycle_id"),
+        late_arrival: row.get::<bool, _>("late_arrival"),
+        rerating_required: row.get::<bool, _>("rerating_required"),
+    }
+}
+
+/// Get customer usage by ID
+pub async fn get_usage_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<CustomerUsage> {
+    let row = sqlx::query(
+        "SELECT id, name, description, version, state, usage_date, start_date, end_date, 
+         usage_type, amount, unit, href, last_update, customer_id, billing_cycle_id,
+         late_arrival, rerating_required
+         FROM customer_usages WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?
+    .ok_or_else(|| TmfError::NotFound(format!("Customer usage with id {} not found", id)))?;
+
+    Ok(row_to_usage(&row))
 }
 
 /// Create a new customer usage record
+///
+/// Usage with a customer is attributed to the billing cycle covering its event
+/// time (start date, else usage date). Records for a closed cycle within the
+/// policy's grace window are flagged for rerating.
 pub async fn create_usage(
     pool: &Pool<Postgres>,
     request: CreateCustomerUsageRequest,
+    policy: &LateUsagePolicy,
 ) -> TmfResult<CustomerUsage> {
     let id = Uuid::new_v4();
     let state = usage_state_to_string(&UsageState::Pending);
     let now = Utc::now();
 
+    let event_time = request.start_date.or(request.usage_date).unwrap_or(now);
+    let attribution = match request.customer_id {
+        Some(customer_id) => attribute_to_cycle(pool, customer_id, event_time, policy, now).await?,
+        None => None,
+    };
+    let late_arrival = attribution.as_ref().is_some_and(|a| a.late_arrival);
+
     sqlx::query(
         "INSERT INTO customer_usages (id, name, description, version, state, usage_date, start_date, 
-         end_date, usage_type, amount, unit, product_offering_id)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+         end_date, usage_type, amount, unit, product_offering_id, customer_id, billing_cycle_id,
+         late_arrival, rerating_required)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $15)",
     )
     .bind(id)
     .bind(&request.name)
@@ -131,6 +167,9 @@ pub async fn create_usage(
     .bind(request.amount)
     .bind(&request.unit)
     .bind(request.product_offering_id)
+    .bind(request.customer_id)
+    .bind(attribution.as_ref().map(|a| a.billing_cycle_id))
+    .bind(late_arrival)
     .execute(pool)
     .await
     .map_err(map_sqlx_error)?;
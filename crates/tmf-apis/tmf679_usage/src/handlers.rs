@@ -1,10 +1,14 @@
 //! Request handlers for TMF679 API endpoints
 
+use crate::anomaly::{spawn_anomaly_check, BaselineAnomalyDetector, UsageAnomalyDetector};
 use crate::auth::validate_token;
+use crate::cycle::LateUsagePolicy;
 use crate::db;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
@@ -81,7 +85,7 @@ pub async fn get_usage_by_id(
     request_body = CreateCustomerUsageRequest,
     responses(
         (status = 201, description = "Customer usage created", body = CustomerUsage),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request or usage beyond the late arrival window"),
         (status = 401, description = "Unauthorized")
     ),
     tag = "TMF679"
@@ -90,11 +94,55 @@ pub async fn create_usage(
     pool: web::Data<PgPool>,
     req: actix_web::HttpRequest,
     body: web::Json<CreateCustomerUsageRequest>,
+    late_usage_policy: Option<web::Data<LateUsagePolicy>>,
+    anomaly_detector: Option<web::Data<Arc<dyn UsageAnomalyDetector>>>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
 ) -> ActixResult<HttpResponse> {
     validate_token(&req)?;
 
-    match db::create_usage(pool.get_ref(), body.into_inner()).await {
-        Ok(usage) => Ok(HttpResponse::Created().json(usage)),
+    let policy = late_usage_policy
+        .map(|p| p.get_ref().clone())
+        .unwrap_or_default();
+    match db::create_usage(pool.get_ref(), body.into_inner(), &policy).await {
+        Ok(usage) => {
+            let detector = anomaly_detector
+                .map(|d| Arc::clone(d.get_ref()))
+                .unwrap_or_else(|| Arc::new(BaselineAnomalyDetector::default()));
+            spawn_anomaly_check(
+                pool.get_ref().clone(),
+                detector,
+                Arc::clone(publisher.get_ref()),
+                usage.clone(),
+            );
+            Ok(HttpResponse::Created().json(usage))
+        }
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get late-arriving usages awaiting rerating
+#[utoipa::path(
+    get,
+    path = "/tmf-api/customerUsageManagement/v4/customerUsage/rerating",
+    responses(
+        (status = 200, description = "Usages flagged for rerating", body = Vec<CustomerUsage>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF679"
+)]
+pub async fn get_usages_requiring_rerating(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::get_usages_requiring_rerating(pool.get_ref()).await {
+        Ok(usages) => Ok(HttpResponse::Ok().json(usages)),
         Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
             "error": e.to_string()
         }))),
@@ -3,13 +3,17 @@
 //! This module implements the TM Forum Customer Usage Management API,
 //! providing a standardized interface for handling CDRs, usage records, and consumption.
 
+pub mod anomaly;
 pub mod api;
 pub mod auth;
+pub mod cycle;
 pub mod db;
 pub mod handlers;
 pub mod models;
 
+pub use anomaly::*;
 pub use auth::*;
+pub use cycle::*;
 pub use handlers::*;
 pub use models::*;
 
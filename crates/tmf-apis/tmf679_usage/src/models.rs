@@ -49,6 +49,20 @@ pub struct CustomerUsage {
     /// Related party (customer)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
+    /// Customer the usage is billed to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
+    /// Billing cycle covering the usage event time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub billing_cycle_id: Option<Uuid>,
+    /// Arrived after its billing cycle had closed
+    #[serde(default)]
+    pub late_arrival: bool,
+    /// Needs rerating / adjustment against its closed billing cycle
+    #[serde(default)]
+    pub rerating_required: bool,
 }
 
 /// Product Offering Reference
@@ -100,6 +114,10 @@ pub struct CreateCustomerUsageRequest {
     pub product_offering_id: Option<Uuid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<CreateRelatedPartyRequest>>,
+    /// Customer the usage is billed to; used to attribute it to a billing cycle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "uuid")]
+    pub customer_id: Option<Uuid>,
 }
 
 /// Request to create a related party
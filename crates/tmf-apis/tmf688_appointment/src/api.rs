@@ -12,8 +12,18 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                     .route(web::get().to(get_appointments))
                     .route(web::post().to(create_appointment)),
             )
+            .service(web::resource("/appointment/{id}").route(web::get().to(get_appointment_by_id)))
             .service(
-                web::resource("/appointment/{id}").route(web::get().to(get_appointment_by_id)),
+                web::resource("/appointment/{id}/cancel").route(web::post().to(cancel_appointment)),
+            )
+            .service(
+                web::resource("/appointment/{id}/reschedule")
+                    .route(web::post().to(reschedule_appointment)),
+            )
+            .service(
+                web::resource("/cancellationPolicy")
+                    .route(web::get().to(get_cancellation_policies))
+                    .route(web::put().to(set_cancellation_policy)),
             ),
     );
 }
@@ -1,8 +1,15 @@
 //! Database operations for TMF688 Appointment Management
 
-use crate::models::{Appointment, AppointmentState, CreateAppointmentRequest};
+use crate::models::{
+    Appointment, AppointmentCancellationPolicy, AppointmentChangeResult, AppointmentState,
+    CancelAppointmentRequest, CreateAppointmentRequest, Money, RescheduleAppointmentRequest,
+};
+use bss_oss_event_bus::EventPublisher;
 use chrono::{DateTime, Utc};
+use revenue_management::models::{Money as ChargeMoney, OneTimeChargeRequest};
+use revenue_management::OneTimeChargeEmitter;
 use sqlx::{Pool, Postgres, Row};
+use std::sync::Arc;
 use tmf_apis_core::{TmfError, TmfResult};
 use uuid::Uuid;
 
@@ -40,7 +47,7 @@ fn appointment_state_to_string(state: &AppointmentState) -> String {
 pub async fn get_appointments(pool: &Pool<Postgres>) -> TmfResult<Vec<Appointment>> {
     let rows = sqlx::query(
         "SELECT id, name, description, version, state, appointment_date, duration, 
-         appointment_type, href, last_update
+         appointment_type, customer_id, href, last_update
          FROM appointments ORDER BY appointment_date DESC",
     )
     .fetch_all(pool)
@@ -65,6 +72,7 @@ pub async fn get_appointments(pool: &Pool<Postgres>) -> TmfResult<Vec<Appointmen
             duration: row.get::<Option<i32>, _>("duration"),
             appointment_type: row.get::<Option<String>, _>("appointment_type"),
             description: row.get::<Option<String>, _>("description"),
+            customer_id: row.get::<Option<Uuid>, _>("customer_id"),
             related_party: None,  // Load separately if needed
             contact_medium: None, // Load separately if needed
         });
@@ -77,7 +85,7 @@ pub async fn get_appointments(pool: &Pool<Postgres>) -> TmfResult<Vec<Appointmen
 pub async fn get_appointment_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<Appointment> {
     let row = sqlx::query(
         "SELECT id, name, description, version, state, appointment_date, duration, 
-         appointment_type, href, last_update
+         appointment_type, customer_id, href, last_update
          FROM appointments WHERE id = $1",
     )
     .bind(id)
@@ -102,6 +110,7 @@ pub async fn get_appointment_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult
         duration: row.get::<Option<i32>, _>("duration"),
         appointment_type: row.get::<Option<String>, _>("appointment_type"),
         description: row.get::<Option<String>, _>("description"),
+        customer_id: row.get::<Option<Uuid>, _>("customer_id"),
         related_party: None,
         contact_medium: None,
     })
@@ -117,8 +126,8 @@ pub async fn create_appointment(
     let now = Utc::now();
 
     sqlx::query(
-        "INSERT INTO appointments (id, name, description, version, state, appointment_date, duration, appointment_type)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        "INSERT INTO appointments (id, name, description, version, state, appointment_date, duration, appointment_type, customer_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
     )
     .bind(id)
     .bind(&request.name)
@@ -128,6 +137,7 @@ pub async fn create_appointment(
     .bind(request.appointment_date.unwrap_or(now))
     .bind(request.duration)
     .bind(&request.appointment_type)
+    .bind(request.customer_id)
     .execute(pool)
     .await
     .map_err(map_sqlx_error)?;
@@ -171,3 +181,271 @@ pub async fn create_appointment(
     // Fetch the created appointment
     get_appointment_by_id(pool, id).await
 }
+
+/// Get all cancellation policies
+pub async fn get_cancellation_policies(
+    pool: &Pool<Postgres>,
+) -> TmfResult<Vec<AppointmentCancellationPolicy>> {
+    let rows = sqlx::query(
+        "SELECT appointment_type, free_cancellation_hours,
+         late_fee_value::DOUBLE PRECISION AS late_fee_value, late_fee_unit
+         FROM appointment_cancellation_policies ORDER BY appointment_type NULLS FIRST",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(rows.iter().map(row_to_cancellation_policy).collect())
+}
+
+/// Create or replace the cancellation policy of an appointment type
+pub async fn set_cancellation_policy(
+    pool: &Pool<Postgres>,
+    policy: AppointmentCancellationPolicy,
+) -> TmfResult<AppointmentCancellationPolicy> {
+    if policy.free_cancellation_hours < 0 {
+        return Err(TmfError::Validation(
+            "free_cancellation_hours must not be negative".to_string(),
+        ));
+    }
+    if policy.late_fee.value < 0.0 {
+        return Err(TmfError::Validation(
+            "late_fee must not be negative".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO appointment_cancellation_policies
+         (id, appointment_type, free_cancellation_hours, late_fee_value, late_fee_unit)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT ((COALESCE(appointment_type, ''))) DO UPDATE SET
+         free_cancellation_hours = EXCLUDED.free_cancellation_hours,
+         late_fee_value = EXCLUDED.late_fee_value,
+         late_fee_unit = EXCLUDED.late_fee_unit,
+         last_update = CURRENT_TIMESTAMP",
+    )
+    .bind(Uuid::new_v4())
+    .bind(&policy.appointment_type)
+    .bind(policy.free_cancellation_hours)
+    .bind(policy.late_fee.value)
+    .bind(&policy.late_fee.unit)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(policy)
+}
+
+/// Get the cancellation policy applying to an appointment type
+///
+/// A policy for the type takes precedence over the default policy.
+pub async fn get_cancellation_policy_for_type(
+    pool: &Pool<Postgres>,
+    appointment_type: Option<&str>,
+) -> TmfResult<Option<AppointmentCancellationPolicy>> {
+    let row = sqlx::query(
+        "SELECT appointment_type, free_cancellation_hours,
+         late_fee_value::DOUBLE PRECISION AS late_fee_value, late_fee_unit
+         FROM appointment_cancellation_policies
+         WHERE appointment_type = $1 OR appointment_type IS NULL
+         ORDER BY appointment_type NULLS LAST LIMIT 1",
+    )
+    .bind(appointment_type)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(row.as_ref().map(row_to_cancellation_policy))
+}
+
+fn row_to_cancellation_policy(row: &sqlx::postgres::PgRow) -> AppointmentCancellationPolicy {
+    AppointmentCancellationPolicy {
+        appointment_type: row.get::<Option<String>, _>("appointment_type"),
+        free_cancellation_hours: row.get::<i32, _>("free_cancellation_hours"),
+        late_fee: Money {
+            value: row.get::<f64, _>("late_fee_value"),
+            unit: row.get::<String, _>("late_fee_unit"),
+        },
+    }
+}
+
+/// Cancel an appointment, charging the late fee of its cancellation policy
+/// to the appointment's customer
+///
+/// The state change is a single guarded update, so concurrent cancellations
+/// cancel (and charge) once. The fee is raised as a one-time charge. A charge
+/// that cannot be raised is logged and the appointment is still cancelled;
+/// the result then carries the fee without a charge ID.
+pub async fn cancel_appointment(
+    pool: &Pool<Postgres>,
+    publisher: Arc<dyn EventPublisher>,
+    id: Uuid,
+    request: CancelAppointmentRequest,
+) -> TmfResult<AppointmentChangeResult> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        "UPDATE appointments SET state = $1, last_update = CURRENT_TIMESTAMP
+         WHERE id = $2 AND state NOT IN ($3, $4, $5)
+         RETURNING appointment_date, appointment_type, customer_id",
+    )
+    .bind(appointment_state_to_string(&AppointmentState::Cancelled))
+    .bind(id)
+    .bind(appointment_state_to_string(&AppointmentState::Cancelled))
+    .bind(appointment_state_to_string(&AppointmentState::Completed))
+    .bind(appointment_state_to_string(&AppointmentState::Failed))
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    let Some(row) = row else {
+        return Err(unchangeable(pool, id, "cancelled").await);
+    };
+
+    let fee = late_fee(
+        pool,
+        row.get::<Option<String>, _>("appointment_type").as_deref(),
+        row.get::<Option<DateTime<Utc>>, _>("appointment_date"),
+        now,
+    )
+    .await?;
+
+    let description = match &request.reason {
+        Some(reason) => format!("Late cancellation of appointment {}: {}", id, reason),
+        None => format!("Late cancellation of appointment {}", id),
+    };
+    let charge_id = charge_fee(
+        publisher,
+        id,
+        row.get::<Option<Uuid>, _>("customer_id"),
+        fee.as_ref(),
+        "LATE_CANCELLATION_FEE",
+        description,
+    )
+    .await;
+
+    Ok(AppointmentChangeResult {
+        appointment: get_appointment_by_id(pool, id).await?,
+        fee,
+        charge_id,
+    })
+}
+
+/// Move an appointment to a new date, charging the late fee of its
+/// cancellation policy to the appointment's customer
+///
+/// The fee depends on how close the current date is, so rescheduling within
+/// the free window costs nothing.
+pub async fn reschedule_appointment(
+    pool: &Pool<Postgres>,
+    publisher: Arc<dyn EventPublisher>,
+    id: Uuid,
+    request: RescheduleAppointmentRequest,
+) -> TmfResult<AppointmentChangeResult> {
+    let now = Utc::now();
+    if request.appointment_date <= now {
+        return Err(TmfError::Validation(
+            "New appointment date must be in the future".to_string(),
+        ));
+    }
+
+    // Returns the date being replaced, which decides the fee
+    let row = sqlx::query(
+        "UPDATE appointments a SET appointment_date = $1, last_update = CURRENT_TIMESTAMP
+         FROM (SELECT id, appointment_date FROM appointments WHERE id = $2 FOR UPDATE) previous
+         WHERE a.id = previous.id AND a.state NOT IN ($3, $4, $5)
+         RETURNING previous.appointment_date, a.appointment_type, a.customer_id",
+    )
+    .bind(request.appointment_date)
+    .bind(id)
+    .bind(appointment_state_to_string(&AppointmentState::Cancelled))
+    .bind(appointment_state_to_string(&AppointmentState::Completed))
+    .bind(appointment_state_to_string(&AppointmentState::Failed))
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+    let Some(row) = row else {
+        return Err(unchangeable(pool, id, "rescheduled").await);
+    };
+
+    let fee = late_fee(
+        pool,
+        row.get::<Option<String>, _>("appointment_type").as_deref(),
+        row.get::<Option<DateTime<Utc>>, _>("appointment_date"),
+        now,
+    )
+    .await?;
+
+    let charge_id = charge_fee(
+        publisher,
+        id,
+        row.get::<Option<Uuid>, _>("customer_id"),
+        fee.as_ref(),
+        "LATE_RESCHEDULING_FEE",
+        format!("Late rescheduling of appointment {}", id),
+    )
+    .await;
+
+    Ok(AppointmentChangeResult {
+        appointment: get_appointment_by_id(pool, id).await?,
+        fee,
+        charge_id,
+    })
+}
+
+/// Error for an appointment a guarded change did not match: missing, or ended
+async fn unchangeable(pool: &Pool<Postgres>, id: Uuid, action: &str) -> TmfError {
+    match get_appointment_by_id(pool, id).await {
+        Ok(appointment) => TmfError::Conflict(format!(
+            "Appointment {} is {} and cannot be {}",
+            id,
+            appointment_state_to_string(&appointment.state),
+            action
+        )),
+        Err(e) => e,
+    }
+}
+
+/// Fee due for changing an appointment at the given instant
+async fn late_fee(
+    pool: &Pool<Postgres>,
+    appointment_type: Option<&str>,
+    appointment_date: Option<DateTime<Utc>>,
+    at: DateTime<Utc>,
+) -> TmfResult<Option<Money>> {
+    let policy = get_cancellation_policy_for_type(pool, appointment_type).await?;
+    Ok(policy.and_then(|policy| policy.fee_at(appointment_date, at)))
+}
+
+/// Raise a fee as a one-time charge, returning the charge ID
+async fn charge_fee(
+    publisher: Arc<dyn EventPublisher>,
+    appointment_id: Uuid,
+    customer_id: Option<Uuid>,
+    fee: Option<&Money>,
+    charge_type: &str,
+    description: String,
+) -> Option<Uuid> {
+    let fee = fee?;
+    let request = OneTimeChargeRequest {
+        customer_id,
+        amount: ChargeMoney {
+            value: fee.value,
+            unit: fee.unit.clone(),
+        },
+        charge_type: charge_type.to_string(),
+        description: Some(description),
+        reference_id: Some(appointment_id),
+        reference_type: Some("Appointment".to_string()),
+    };
+    match OneTimeChargeEmitter::new(publisher).emit(request).await {
+        Ok(charge) => Some(charge.id),
+        Err(e) => {
+            log::warn!(
+                "Failed to charge {} for appointment {}: {}",
+                charge_type,
+                appointment_id,
+                e
+            );
+            None
+        }
+    }
+}
@@ -4,7 +4,9 @@ use crate::auth::validate_token;
 use crate::db;
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
+use bss_oss_event_bus::EventPublisher;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
@@ -100,3 +102,163 @@ pub async fn create_appointment(
         }))),
     }
 }
+
+/// Map the result of cancelling or rescheduling an appointment to a response
+fn change_response(result: Result<AppointmentChangeResult, TmfError>) -> ActixResult<HttpResponse> {
+    match result {
+        Ok(change) => Ok(HttpResponse::Ok().json(change)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(TmfError::Conflict(msg)) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Cancel an appointment
+///
+/// Cancelling later than the cancellation policy allows charges its late fee.
+#[utoipa::path(
+    post,
+    path = "/tmf-api/appointmentManagement/v4/appointment/{id}/cancel",
+    request_body = CancelAppointmentRequest,
+    responses(
+        (status = 200, description = "Appointment cancelled", body = AppointmentChangeResult),
+        (status = 404, description = "Appointment not found"),
+        (status = 400, description = "Invalid appointment ID"),
+        (status = 409, description = "Appointment already ended"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Appointment ID (UUID)")
+    ),
+    tag = "TMF688"
+)]
+pub async fn cancel_appointment(
+    pool: web::Data<PgPool>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<CancelAppointmentRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid appointment ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let publisher = Arc::clone(publisher.get_ref());
+    change_response(db::cancel_appointment(pool.get_ref(), publisher, id, body.into_inner()).await)
+}
+
+/// Reschedule an appointment
+///
+/// Rescheduling within the free window of the cancellation policy is free;
+/// later rescheduling charges its late fee.
+#[utoipa::path(
+    post,
+    path = "/tmf-api/appointmentManagement/v4/appointment/{id}/reschedule",
+    request_body = RescheduleAppointmentRequest,
+    responses(
+        (status = 200, description = "Appointment rescheduled", body = AppointmentChangeResult),
+        (status = 404, description = "Appointment not found"),
+        (status = 400, description = "Invalid request"),
+        (status = 409, description = "Appointment already ended"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Appointment ID (UUID)")
+    ),
+    tag = "TMF688"
+)]
+pub async fn reschedule_appointment(
+    pool: web::Data<PgPool>,
+    publisher: web::Data<Arc<dyn EventPublisher>>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+    body: web::Json<RescheduleAppointmentRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid appointment ID format. Expected UUID."
+            })));
+        }
+    };
+
+    let publisher = Arc::clone(publisher.get_ref());
+    change_response(
+        db::reschedule_appointment(pool.get_ref(), publisher, id, body.into_inner()).await,
+    )
+}
+
+/// Get all cancellation policies
+#[utoipa::path(
+    get,
+    path = "/tmf-api/appointmentManagement/v4/cancellationPolicy",
+    responses(
+        (status = 200, description = "List of cancellation policies", body = Vec<AppointmentCancellationPolicy>),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF688"
+)]
+pub async fn get_cancellation_policies(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::get_cancellation_policies(pool.get_ref()).await {
+        Ok(policies) => Ok(HttpResponse::Ok().json(policies)),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Create or replace a cancellation policy
+///
+/// A policy without an appointment type is the default for all types.
+#[utoipa::path(
+    put,
+    path = "/tmf-api/appointmentManagement/v4/cancellationPolicy",
+    request_body = AppointmentCancellationPolicy,
+    responses(
+        (status = 200, description = "Cancellation policy set", body = AppointmentCancellationPolicy),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF688"
+)]
+pub async fn set_cancellation_policy(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<AppointmentCancellationPolicy>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    match db::set_cancellation_policy(pool.get_ref(), body.into_inner()).await {
+        Ok(policy) => Ok(HttpResponse::Ok().json(policy)),
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
@@ -15,5 +15,9 @@ pub use models::*;
 
 // Re-export db functions with explicit names to avoid conflicts
 pub use db::{
-    get_appointment_by_id as db_get_appointment_by_id, get_appointments as db_get_appointments,
+    cancel_appointment as db_cancel_appointment, get_appointment_by_id as db_get_appointment_by_id,
+    get_appointments as db_get_appointments,
+    get_cancellation_policies as db_get_cancellation_policies,
+    reschedule_appointment as db_reschedule_appointment,
+    set_cancellation_policy as db_set_cancellation_policy,
 };
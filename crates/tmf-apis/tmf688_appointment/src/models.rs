@@ -38,6 +38,10 @@ pub struct Appointment {
     /// Description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Customer the appointment is for, charged any late fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub customer_id: Option<Uuid>,
     /// Related party (customer, technician, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<RelatedParty>>,
@@ -83,6 +87,10 @@ pub struct CreateAppointmentRequest {
     pub duration: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub appointment_type: Option<String>,
+    /// Customer the appointment is for, charged any late fee
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub customer_id: Option<Uuid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub related_party: Option<Vec<CreateRelatedPartyRequest>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -102,3 +110,65 @@ pub struct CreateContactMediumRequest {
     pub medium_type: String,
     pub value: String,
 }
+
+/// Money representation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Money {
+    pub value: f64,
+    pub unit: String,
+}
+
+/// Cancellation Policy - When cancelling or rescheduling an appointment incurs a fee
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppointmentCancellationPolicy {
+    /// Appointment type the policy applies to (unset = default for all types)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appointment_type: Option<String>,
+    /// Hours before the appointment until which changes are free
+    pub free_cancellation_hours: i32,
+    /// Fee for cancelling or rescheduling later than that
+    pub late_fee: Money,
+}
+
+impl AppointmentCancellationPolicy {
+    /// Fee for changing an appointment scheduled at `appointment_date` at `at`
+    ///
+    /// Appointments without a date can always be changed for free.
+    pub fn fee_at(
+        &self,
+        appointment_date: Option<DateTime<Utc>>,
+        at: DateTime<Utc>,
+    ) -> Option<Money> {
+        let appointment_date = appointment_date?;
+        let free_until =
+            appointment_date - chrono::Duration::hours(i64::from(self.free_cancellation_hours));
+        (at > free_until && self.late_fee.value > 0.0).then(|| self.late_fee.clone())
+    }
+}
+
+/// Request to cancel an appointment
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct CancelAppointmentRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Request to reschedule an appointment
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RescheduleAppointmentRequest {
+    #[schema(value_type = String, format = "date-time")]
+    pub appointment_date: DateTime<Utc>,
+}
+
+/// Appointment after a cancellation or rescheduling, with any fee charged
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AppointmentChangeResult {
+    pub appointment: Appointment,
+    /// Fee due under the cancellation policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<Money>,
+    /// Charge raised for the fee (absent if it could not be raised)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = "uuid")]
+    pub charge_id: Option<Uuid>,
+}
@@ -15,6 +15,14 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .service(
                 web::resource("/resourceActivation/{id}")
                     .route(web::get().to(get_resource_activation_by_id)),
+            )
+            .service(
+                web::resource("/resourceActivationJob")
+                    .route(web::post().to(submit_activation_job)),
+            )
+            .service(
+                web::resource("/resourceActivationJob/{id}")
+                    .route(web::get().to(get_activation_job_by_id)),
             ),
     );
 }
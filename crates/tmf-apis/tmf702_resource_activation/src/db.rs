@@ -1,6 +1,9 @@
 //! Database operations for TMF702 Resource Activation & Configuration
 
-use crate::models::{CreateResourceActivationRequest, ResourceActivation, ResourceActivationState};
+use crate::models::{
+    ActivationJob, ActivationJobState, CreateResourceActivationRequest, ResourceActivation,
+    ResourceActivationState,
+};
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use tmf_apis_core::{TmfError, TmfResult};
@@ -34,6 +37,27 @@ fn resource_activation_state_to_string(state: &ResourceActivationState) -> Strin
     }
 }
 
+/// Parse activation job state from database string
+fn parse_activation_job_state(s: &str) -> ActivationJobState {
+    match s.to_uppercase().as_str() {
+        "QUEUED" => ActivationJobState::Queued,
+        "RUNNING" => ActivationJobState::Running,
+        "COMPLETED" => ActivationJobState::Completed,
+        "FAILED" => ActivationJobState::Failed,
+        _ => ActivationJobState::Queued,
+    }
+}
+
+/// Convert activation job state to database string
+fn activation_job_state_to_string(state: &ActivationJobState) -> String {
+    match state {
+        ActivationJobState::Queued => "QUEUED".to_string(),
+        ActivationJobState::Running => "RUNNING".to_string(),
+        ActivationJobState::Completed => "COMPLETED".to_string(),
+        ActivationJobState::Failed => "FAILED".to_string(),
+    }
+}
+
 /// Get all resource activations
 pub async fn get_resource_activations(pool: &Pool<Postgres>) -> TmfResult<Vec<ResourceActivation>> {
     let rows = sqlx::query(
@@ -154,3 +178,197 @@ pub async fn create_resource_activation(
     // Fetch the created resource activation
     get_resource_activation_by_id(pool, id).await
 }
+
+/// Update the state of a resource activation, stamping the completion date
+/// when it reaches a final state
+pub async fn update_resource_activation_state(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    state: ResourceActivationState,
+) -> TmfResult<()> {
+    let completion_date = match state {
+        ResourceActivationState::Completed
+        | ResourceActivationState::Failed
+        | ResourceActivationState::Cancelled => Some(Utc::now()),
+        _ => None,
+    };
+
+    sqlx::query(
+        "UPDATE resource_activations
+         SET state = $2, completion_date = COALESCE($3, completion_date), last_update = CURRENT_TIMESTAMP
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(resource_activation_state_to_string(&state))
+    .bind(completion_date)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(())
+}
+
+/// Get activation job by ID
+pub async fn get_activation_job_by_id(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<ActivationJob> {
+    let row = sqlx::query(
+        "SELECT id, activation_id, state, progress_percent, progress_message, callback_url,
+         error_message, created_at, started_at, completed_at
+         FROM resource_activation_jobs WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(map_sqlx_error)?
+    .ok_or_else(|| TmfError::NotFound(format!("Activation job with id {} not found", id)))?;
+
+    let id = row.get::<Uuid, _>("id");
+    Ok(ActivationJob {
+        id,
+        href: Some(format!(
+            "/tmf-api/resourceActivationAndConfiguration/v4/resourceActivationJob/{}",
+            id
+        )),
+        activation_id: row.get::<Uuid, _>("activation_id"),
+        state: parse_activation_job_state(&row.get::<String, _>("state")),
+        progress_percent: row.get::<i16, _>("progress_percent").clamp(0, 100) as u8,
+        progress_message: row.get::<Option<String>, _>("progress_message"),
+        callback_url: row.get::<Option<String>, _>("callback_url"),
+        error_message: row.get::<Option<String>, _>("error_message"),
+        created_at: row.get::<DateTime<Utc>, _>("created_at"),
+        started_at: row.get::<Option<DateTime<Utc>>, _>("started_at"),
+        completed_at: row.get::<Option<DateTime<Utc>>, _>("completed_at"),
+    })
+}
+
+/// Create a queued activation job for an existing resource activation
+pub async fn create_activation_job(
+    pool: &Pool<Postgres>,
+    activation_id: Uuid,
+    callback_url: Option<String>,
+) -> TmfResult<ActivationJob> {
+    let id = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO resource_activation_jobs (id, activation_id, state, progress_percent, callback_url)
+         VALUES ($1, $2, $3, 0, $4)",
+    )
+    .bind(id)
+    .bind(activation_id)
+    .bind(activation_job_state_to_string(&ActivationJobState::Queued))
+    .bind(&callback_url)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    get_activation_job_by_id(pool, id).await
+}
+
+/// Mark an activation job as running
+pub async fn start_activation_job(pool: &Pool<Postgres>, id: Uuid) -> TmfResult<()> {
+    sqlx::query(
+        "UPDATE resource_activation_jobs
+         SET state = $2, started_at = CURRENT_TIMESTAMP
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(activation_job_state_to_string(&ActivationJobState::Running))
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(())
+}
+
+/// Record intermediate progress of a running activation job
+pub async fn update_activation_job_progress(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    percent: u8,
+    message: &str,
+) -> TmfResult<()> {
+    sqlx::query(
+        "UPDATE resource_activation_jobs
+         SET progress_percent = $2, progress_message = $3
+         WHERE id = $1 AND state = $4",
+    )
+    .bind(id)
+    .bind(i16::from(percent.min(100)))
+    .bind(message)
+    .bind(activation_job_state_to_string(&ActivationJobState::Running))
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    Ok(())
+}
+
+/// Mark an activation job as completed, or failed with the given reason
+pub async fn finish_activation_job(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    outcome: Result<(), String>,
+) -> TmfResult<ActivationJob> {
+    let (state, progress, error_message) = match outcome {
+        Ok(()) => (ActivationJobState::Completed, Some(100i16), None),
+        Err(reason) => (ActivationJobState::Failed, None, Some(reason)),
+    };
+
+    sqlx::query(
+        "UPDATE resource_activation_jobs
+         SET state = $2, progress_percent = COALESCE($3, progress_percent),
+             error_message = $4, completed_at = CURRENT_TIMESTAMP
+         WHERE id = $1",
+    )
+    .bind(id)
+    .bind(activation_job_state_to_string(&state))
+    .bind(progress)
+    .bind(&error_message)
+    .execute(pool)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    get_activation_job_by_id(pool, id).await
+}
+
+/// Fail every queued or running activation job, returning the failed job IDs
+pub async fn fail_unfinished_activation_jobs(
+    pool: &Pool<Postgres>,
+    reason: &str,
+) -> TmfResult<Vec<Uuid>> {
+    let mut tx = pool.begin().await.map_err(map_sqlx_error)?;
+
+    let rows = sqlx::query(
+        "UPDATE resource_activation_jobs
+         SET state = $1, error_message = $2, completed_at = CURRENT_TIMESTAMP
+         WHERE state IN ($3, $4)
+         RETURNING id, activation_id",
+    )
+    .bind(activation_job_state_to_string(&ActivationJobState::Failed))
+    .bind(reason)
+    .bind(activation_job_state_to_string(&ActivationJobState::Queued))
+    .bind(activation_job_state_to_string(&ActivationJobState::Running))
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    let activation_ids: Vec<Uuid> = rows
+        .iter()
+        .map(|row| row.get::<Uuid, _>("activation_id"))
+        .collect();
+    sqlx::query(
+        "UPDATE resource_activations
+         SET state = $1, completion_date = CURRENT_TIMESTAMP, last_update = CURRENT_TIMESTAMP
+         WHERE id = ANY($2)",
+    )
+    .bind(resource_activation_state_to_string(
+        &ResourceActivationState::Failed,
+    ))
+    .bind(&activation_ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(map_sqlx_error)?;
+
+    tx.commit().await.map_err(map_sqlx_error)?;
+
+    Ok(rows.iter().map(|row| row.get::<Uuid, _>("id")).collect())
+}
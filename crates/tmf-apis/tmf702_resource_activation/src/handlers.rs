@@ -2,12 +2,24 @@
 
 use crate::auth::validate_token;
 use crate::db;
+use crate::jobs::{self, ImmediateActivator, ResourceActivator};
 use crate::models::*;
 use actix_web::{web, HttpResponse, Result as ActixResult};
 use sqlx::PgPool;
+use std::sync::Arc;
 use tmf_apis_core::TmfError;
 use uuid::Uuid;
 
+/// Resource activator registered as app data, or the immediate default
+fn resource_activator(
+    registered: &Option<web::Data<Arc<dyn ResourceActivator>>>,
+) -> Arc<dyn ResourceActivator> {
+    registered
+        .as_ref()
+        .map(|data| Arc::clone(data.get_ref()))
+        .unwrap_or_else(|| Arc::new(ImmediateActivator))
+}
+
 /// Get all resource activations
 #[utoipa::path(
     get,
@@ -100,3 +112,82 @@ pub async fn create_resource_activation(
         }))),
     }
 }
+
+/// Submit a resource activation as an asynchronous job
+#[utoipa::path(
+    post,
+    path = "/tmf-api/resourceActivationAndConfiguration/v4/resourceActivationJob",
+    request_body = SubmitActivationJobRequest,
+    responses(
+        (status = 202, description = "Activation job accepted", body = ActivationJob),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized")
+    ),
+    tag = "TMF702"
+)]
+pub async fn submit_activation_job(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    activator: Option<web::Data<Arc<dyn ResourceActivator>>>,
+    body: web::Json<SubmitActivationJobRequest>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let activator = resource_activator(&activator);
+    match jobs::submit_activation_job(pool.get_ref(), activator, body.into_inner()).await {
+        Ok(job) => {
+            let location = job.href.clone().unwrap_or_default();
+            Ok(HttpResponse::Accepted()
+                .insert_header(("Location", location))
+                .json(job))
+        }
+        Err(TmfError::Validation(msg)) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Get activation job by ID, for polling its progress
+#[utoipa::path(
+    get,
+    path = "/tmf-api/resourceActivationAndConfiguration/v4/resourceActivationJob/{id}",
+    responses(
+        (status = 200, description = "Activation job found", body = ActivationJob),
+        (status = 404, description = "Activation job not found"),
+        (status = 400, description = "Invalid job ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    params(
+        ("id" = String, Path, description = "Activation Job ID (UUID)")
+    ),
+    tag = "TMF702"
+)]
+pub async fn get_activation_job_by_id(
+    pool: web::Data<PgPool>,
+    req: actix_web::HttpRequest,
+    path: web::Path<String>,
+) -> ActixResult<HttpResponse> {
+    validate_token(&req)?;
+
+    let id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid activation job ID format. Expected UUID."
+            })));
+        }
+    };
+
+    match db::get_activation_job_by_id(pool.get_ref(), id).await {
+        Ok(job) => Ok(HttpResponse::Ok().json(job)),
+        Err(TmfError::NotFound(msg)) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": msg
+        }))),
+        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": e.to_string()
+        }))),
+    }
+}
@@ -0,0 +1,335 @@
+//! Asynchronous activation jobs for TMF702
+//!
+//! Slow network elements can take minutes to configure, so an activation may be
+//! submitted as a job instead: the request returns immediately with a job ID,
+//! the activation runs in the background, and the client either polls the job
+//! or is notified at its callback URL when the job completes or fails.
+//!
+//! Callback URLs must use https and must not point at loopback, private or
+//! link-local addresses, so a job cannot be used to reach internal services.
+
+use crate::db;
+use crate::models::{
+    ActivationJob, ResourceActivation, ResourceActivationState, SubmitActivationJobRequest,
+};
+use async_trait::async_trait;
+use log::{error, info, warn};
+use sqlx::PgPool;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tmf_apis_core::{TmfError, TmfResult};
+use uuid::Uuid;
+
+/// Timeout for delivering a job to its callback URL
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reason recorded on jobs whose background task was lost with the process
+const INTERRUPTED_REASON: &str = "Activation job interrupted by a restart";
+
+/// Records intermediate progress of a running job
+pub struct ProgressReporter {
+    pool: PgPool,
+    job_id: Uuid,
+}
+
+impl ProgressReporter {
+    pub fn new(pool: PgPool, job_id: Uuid) -> Self {
+        Self { pool, job_id }
+    }
+
+    pub fn job_id(&self) -> Uuid {
+        self.job_id
+    }
+
+    /// Record progress; failures are logged and do not abort the activation
+    pub async fn report(&self, percent: u8, message: &str) {
+        if let Err(e) =
+            db::update_activation_job_progress(&self.pool, self.job_id, percent, message).await
+        {
+            warn!(
+                "Failed to record progress for activation job {}: {}",
+                self.job_id, e
+            );
+        }
+    }
+}
+
+/// Performs the actual configuration of a network element
+#[async_trait]
+pub trait ResourceActivator: Send + Sync {
+    /// Activate the resource, reporting progress as steps complete
+    async fn activate(
+        &self,
+        activation: &ResourceActivation,
+        progress: &ProgressReporter,
+    ) -> Result<(), String>;
+}
+
+/// Activator that completes immediately, used when no network element
+/// integration is registered
+#[derive(Debug, Default)]
+pub struct ImmediateActivator;
+
+#[async_trait]
+impl ResourceActivator for ImmediateActivator {
+    async fn activate(
+        &self,
+        _activation: &ResourceActivation,
+        progress: &ProgressReporter,
+    ) -> Result<(), String> {
+        progress.report(50, "Applying configuration").await;
+        Ok(())
+    }
+}
+
+/// Create the activation and a queued job for it, then run the job in the background
+pub async fn submit_activation_job(
+    pool: &PgPool,
+    activator: Arc<dyn ResourceActivator>,
+    request: SubmitActivationJobRequest,
+) -> TmfResult<ActivationJob> {
+    if let Some(url) = &request.callback_url {
+        validate_callback_url(url)?;
+    }
+
+    let activation = db::create_resource_activation(pool, request.activation).await?;
+    let job = db::create_activation_job(pool, activation.base.id, request.callback_url).await?;
+
+    tokio::spawn(run_activation_job(
+        pool.clone(),
+        activator,
+        job.id,
+        activation,
+    ));
+
+    Ok(job)
+}
+
+async fn run_activation_job(
+    pool: PgPool,
+    activator: Arc<dyn ResourceActivator>,
+    job_id: Uuid,
+    activation: ResourceActivation,
+) {
+    let activation_id = activation.base.id;
+    let outcome = match start(&pool, job_id, activation_id).await {
+        Ok(()) => {
+            let progress = ProgressReporter::new(pool.clone(), job_id);
+            activator.activate(&activation, &progress).await
+        }
+        Err(e) => Err(format!("Failed to start activation job: {}", e)),
+    };
+
+    let final_state = if outcome.is_ok() {
+        ResourceActivationState::Completed
+    } else {
+        ResourceActivationState::Failed
+    };
+    if let Err(e) = db::update_resource_activation_state(&pool, activation_id, final_state).await {
+        error!(
+            "Failed to update resource activation {} after job {}: {}",
+            activation_id, job_id, e
+        );
+    }
+
+    match db::finish_activation_job(&pool, job_id, outcome).await {
+        Ok(job) => {
+            info!("Activation job {} finished as {:?}", job.id, job.state);
+            notify_callback(&job).await;
+        }
+        Err(e) => error!("Failed to finish activation job {}: {}", job_id, e),
+    }
+}
+
+async fn start(pool: &PgPool, job_id: Uuid, activation_id: Uuid) -> TmfResult<()> {
+    db::start_activation_job(pool, job_id).await?;
+    db::update_resource_activation_state(pool, activation_id, ResourceActivationState::InProgress)
+        .await
+}
+
+/// Fail jobs left queued or running by a previous process
+///
+/// Jobs run in tasks of the process that accepted them, so after a restart
+/// nothing will finish them. Re-running an activation could configure an
+/// element twice, so they are failed and their callbacks notified instead.
+/// Run once at startup, before accepting requests; this assumes a single
+/// instance runs activation jobs.
+pub async fn fail_interrupted_jobs(pool: &PgPool) -> TmfResult<usize> {
+    let job_ids = db::fail_unfinished_activation_jobs(pool, INTERRUPTED_REASON).await?;
+
+    for job_id in &job_ids {
+        warn!(
+            "Activation job {} was interrupted and marked failed",
+            job_id
+        );
+        match db::get_activation_job_by_id(pool, *job_id).await {
+            Ok(job) => notify_callback(&job).await,
+            Err(e) => error!(
+                "Failed to load interrupted activation job {}: {}",
+                job_id, e
+            ),
+        }
+    }
+
+    Ok(job_ids.len())
+}
+
+/// Check a callback URL uses https and does not name a non-public address
+pub fn validate_callback_url(url: &str) -> TmfResult<()> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| TmfError::Validation(format!("Invalid callback URL: {}", e)))?;
+
+    if parsed.scheme() != "https" {
+        return Err(TmfError::Validation(
+            "Callback URL must use https".to_string(),
+        ));
+    }
+
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| TmfError::Validation("Callback URL must have a host".to_string()))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let internal = match host.parse::<IpAddr>() {
+        Ok(ip) => !is_public_address(ip),
+        Err(_) => {
+            let host = host.to_ascii_lowercase();
+            host == "localhost" || host.ends_with(".localhost")
+        }
+    };
+    if internal {
+        return Err(TmfError::Validation(format!(
+            "Callback URL host {} is not a public address",
+            host
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether an address is publicly routable
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Resolve a callback URL's host, failing if any address is not public
+async fn resolve_callback_host(url: &reqwest::Url) -> Result<Option<(String, SocketAddr)>, String> {
+    let host = url.host_str().ok_or("Callback URL has no host")?;
+    if host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok()
+    {
+        return Ok(None);
+    }
+
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .collect();
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip())) {
+        return Err(format!(
+            "{} resolves to non-public address {}",
+            host,
+            addr.ip()
+        ));
+    }
+
+    addrs
+        .first()
+        .map(|addr| Some((host.to_string(), *addr)))
+        .ok_or_else(|| format!("{} did not resolve", host))
+}
+
+/// POST the finished job to its callback URL, if one was given
+///
+/// The host is resolved and checked again at delivery time and the request
+/// is pinned to the checked address, so DNS changes after submission cannot
+/// redirect it; HTTP redirects are not followed.
+async fn notify_callback(job: &ActivationJob) {
+    let Some(url) = &job.callback_url else {
+        return;
+    };
+
+    let result = deliver_callback(url, job).await;
+
+    if let Err(e) = result {
+        warn!(
+            "Failed to notify callback {} for activation job {}: {}",
+            url, job.id, e
+        );
+    }
+}
+
+async fn deliver_callback(url: &str, job: &ActivationJob) -> Result<(), String> {
+    validate_callback_url(url).map_err(|e| e.to_string())?;
+    let parsed = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+
+    let mut client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    if let Some((host, addr)) = resolve_callback_host(&parsed).await? {
+        client = client.resolve(&host, addr);
+    }
+
+    client
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(parsed)
+        .json(job)
+        .timeout(CALLBACK_TIMEOUT)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_url_must_use_https() {
+        assert!(validate_callback_url("http://example.com/hook").is_err());
+        assert!(validate_callback_url("https://example.com/hook").is_ok());
+    }
+
+    #[test]
+    fn test_callback_url_rejects_internal_hosts() {
+        for url in [
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.1.2.3/hook",
+            "https://192.168.0.10/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:10.0.0.1]/hook",
+        ] {
+            assert!(validate_callback_url(url).is_err(), "{} accepted", url);
+        }
+    }
+}
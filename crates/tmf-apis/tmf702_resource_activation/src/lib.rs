@@ -7,6 +7,7 @@ pub mod api;
 pub mod auth;
 pub mod db;
 pub mod handlers;
+pub mod jobs;
 pub mod models;
 
 pub use auth::*;
@@ -100,3 +100,65 @@ pub struct CreateConfigurationParameterRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 }
+
+/// Asynchronous activation job state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActivationJobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl ActivationJobState {
+    /// Whether the job has finished, successfully or not
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ActivationJobState::Completed | ActivationJobState::Failed
+        )
+    }
+}
+
+/// Activation Job - Tracks a resource activation running in the background
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ActivationJob {
+    #[schema(value_type = String, format = "uuid")]
+    pub id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    /// Resource activation performed by the job
+    #[schema(value_type = String, format = "uuid")]
+    pub activation_id: Uuid,
+    /// Job state
+    pub state: ActivationJobState,
+    /// Progress from 0 to 100
+    pub progress_percent: u8,
+    /// Latest progress step reported by the activator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_message: Option<String>,
+    /// URL notified with the job once it completes or fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    /// Failure reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = String, format = "date-time")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Request to submit a resource activation as an asynchronous job
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SubmitActivationJobRequest {
+    pub activation: CreateResourceActivationRequest,
+    /// URL to POST the finished job to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
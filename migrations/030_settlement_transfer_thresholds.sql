@@ -0,0 +1,35 @@
+-- Settlement transfer thresholds
+-- Amounts below a partner's minimum transfer roll over to the next settlement period
+
+-- Partner settlement configuration table
+CREATE TABLE
+    IF NOT EXISTS partner_settlement_configs (
+        partner_id UUID PRIMARY KEY,
+        minimum_transfer_value DECIMAL(15, 4) NOT NULL DEFAULT 0,
+        minimum_transfer_unit VARCHAR(10) DEFAULT 'USD',
+        created_at TIMESTAMP
+        WITH
+            TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP
+        WITH
+            TIME ZONE DEFAULT CURRENT_TIMESTAMP
+    );
+
+-- Settlement amounts are rounded to the currency's minor units, up to 3 decimals (BHD, KWD, ...)
+ALTER TABLE partner_settlements ALTER COLUMN total_revenue_value TYPE DECIMAL(15, 4);
+
+ALTER TABLE partner_settlements ALTER COLUMN partner_share_value TYPE DECIMAL(15, 4);
+
+ALTER TABLE partner_settlements ALTER COLUMN platform_share_value TYPE DECIMAL(15, 4);
+
+-- Carry-over tracking on partner settlements
+ALTER TABLE partner_settlements ADD COLUMN IF NOT EXISTS carried_in_value DECIMAL(15, 4) NOT NULL DEFAULT 0;
+
+ALTER TABLE partner_settlements ADD COLUMN IF NOT EXISTS transfer_amount_value DECIMAL(15, 4) NOT NULL DEFAULT 0;
+
+ALTER TABLE partner_settlements ADD COLUMN IF NOT EXISTS carried_forward_value DECIMAL(15, 4) NOT NULL DEFAULT 0;
+
+-- Comments
+COMMENT ON TABLE partner_settlement_configs IS 'Per-partner settlement configuration such as minimum transfer thresholds';
+
+COMMENT ON COLUMN partner_settlements.carried_forward_value IS 'Partner share below the minimum transfer, rolled over to the next period';
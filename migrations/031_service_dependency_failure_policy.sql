@@ -0,0 +1,9 @@
+-- Service dependency failure propagation policy
+-- Controls what happens to a dependent service when one of its dependencies fails
+
+ALTER TABLE service_dependencies ADD COLUMN IF NOT EXISTS failure_policy VARCHAR(50) NOT NULL DEFAULT 'HOLD_PENDING'; -- FAIL_DEPENDENT, RETRY_DEPENDENCY, HOLD_PENDING
+
+ALTER TABLE service_dependencies ADD COLUMN IF NOT EXISTS failure_max_retries INTEGER NOT NULL DEFAULT 0;
+
+-- Comments
+COMMENT ON COLUMN service_dependencies.failure_policy IS 'Action taken on the dependent service when this dependency fails';
@@ -0,0 +1,20 @@
+-- Resource reservation priority and preemption
+-- Higher-priority reservations may evict lower-priority, preemptible ones when capacity is short
+
+ALTER TABLE resource_reservations ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0; -- Higher value wins
+
+ALTER TABLE resource_reservations ADD COLUMN IF NOT EXISTS preemptible BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE resource_reservations ADD COLUMN IF NOT EXISTS emergency BOOLEAN NOT NULL DEFAULT FALSE; -- Emergency slices may evict any non-emergency reservation
+
+ALTER TABLE resource_reservations ADD COLUMN IF NOT EXISTS preempted_by_reservation_id UUID REFERENCES resource_reservations (id) ON DELETE SET NULL;
+
+-- Index for preemption candidate lookups
+CREATE INDEX IF NOT EXISTS idx_resource_reservations_priority ON resource_reservations (resource_inventory_id, priority);
+
+-- Comments
+COMMENT ON COLUMN resource_reservations.priority IS 'Reservation priority used for preemption decisions';
+
+COMMENT ON COLUMN resource_reservations.preemptible IS 'Whether a higher-priority reservation may evict this one';
+
+COMMENT ON COLUMN resource_reservations.preempted_by_reservation_id IS 'Reservation that evicted this one (status PREEMPTED)';
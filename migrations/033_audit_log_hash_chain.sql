@@ -0,0 +1,18 @@
+-- Audit log hash chain
+-- Each audit entry stores the hash of the previous entry so tampering breaks the chain
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS sequence_number BIGINT;
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS prev_hash VARCHAR(64);
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS entry_hash VARCHAR(64);
+
+-- Chain order index (entries written before the chain was introduced have no sequence number)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_logs_sequence_number ON audit_logs (sequence_number)
+WHERE
+    sequence_number IS NOT NULL;
+
+-- Comments
+COMMENT ON COLUMN audit_logs.prev_hash IS 'SHA-256 entry hash of the previous audit log entry in the chain';
+
+COMMENT ON COLUMN audit_logs.entry_hash IS 'SHA-256 over this entry''s content and prev_hash';
@@ -0,0 +1,21 @@
+-- Permission Delegations
+-- Temporary delegation of a user's permissions to another user (e.g. staff coverage)
+
+CREATE TABLE IF NOT EXISTS permission_delegations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    delegator_id UUID NOT NULL REFERENCES identities(id) ON DELETE CASCADE,
+    delegatee_id UUID NOT NULL REFERENCES identities(id) ON DELETE CASCADE,
+    permissions TEXT NOT NULL, -- JSON array of permissions
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    revoked_by UUID,
+    CHECK (delegator_id <> delegatee_id)
+);
+
+CREATE INDEX idx_permission_delegations_delegatee_id ON permission_delegations(delegatee_id);
+CREATE INDEX idx_permission_delegations_delegator_id ON permission_delegations(delegator_id);
+CREATE INDEX idx_permission_delegations_expires_at ON permission_delegations(expires_at);
+
+-- Comments
+COMMENT ON TABLE permission_delegations IS 'Permission Delegations - Time-limited delegation of permissions between identities';
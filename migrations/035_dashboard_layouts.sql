@@ -0,0 +1,17 @@
+-- Dashboard Layouts
+-- Per-user real-time analytics dashboard layouts (widgets, metrics, positions)
+
+CREATE TABLE IF NOT EXISTS dashboard_layouts (
+    user_id UUID PRIMARY KEY,
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    schema_version INTEGER NOT NULL DEFAULT 1,
+    widgets JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_dashboard_layouts_tenant_id ON dashboard_layouts(tenant_id);
+
+-- Comments
+COMMENT ON TABLE dashboard_layouts IS 'Dashboard Layouts - Saved real-time analytics dashboard per user';
+COMMENT ON COLUMN dashboard_layouts.schema_version IS 'Widget schema version the layout was written in; upgraded on read';
@@ -0,0 +1,25 @@
+-- IoT Device Certificates
+-- Per-device certificates issued on provisioning and rotated before expiry
+
+CREATE TABLE IF NOT EXISTS iot_device_certificates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    device_id UUID NOT NULL REFERENCES iot_devices(id) ON DELETE CASCADE,
+    serial_number VARCHAR(128) NOT NULL UNIQUE,
+    certificate_pem TEXT NOT NULL,
+    public_key_pem TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE',
+    not_before TIMESTAMP WITH TIME ZONE NOT NULL,
+    not_after TIMESTAMP WITH TIME ZONE NOT NULL,
+    replaces_certificate_id UUID REFERENCES iot_device_certificates(id),
+    delivered_at TIMESTAMP WITH TIME ZONE,
+    confirmed_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_iot_device_certificates_device_id ON iot_device_certificates(device_id);
+CREATE INDEX idx_iot_device_certificates_status_not_after ON iot_device_certificates(status, not_after);
+
+-- Comments
+COMMENT ON TABLE iot_device_certificates IS 'IoT Device Certificates - Issued device certificates and their rotation state';
+COMMENT ON COLUMN iot_device_certificates.status IS 'Certificate status: ACTIVE, PENDING (rotated, awaiting device confirmation), REVOKED';
@@ -0,0 +1,29 @@
+-- TMF622 Product Order Templates
+-- Reusable orders for repeat B2B purchasing
+
+CREATE TABLE IF NOT EXISTS product_order_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    source_order_id UUID REFERENCES product_orders(id) ON DELETE SET NULL,
+    priority VARCHAR(50),
+    related_parties JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS product_order_template_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    template_id UUID NOT NULL REFERENCES product_order_templates(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    product_offering_id UUID,
+    product_offering_version VARCHAR(50),
+    product_specification_id UUID,
+    quantity INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_order_template_items_template_id ON product_order_template_items (template_id);
+
+-- Comments
+COMMENT ON TABLE product_order_templates IS 'TMF622 Product Order Templates - Saved orders for reordering';
+COMMENT ON COLUMN product_order_template_items.product_offering_version IS 'Offering version pinned at save time; orders from the template are rejected if it changed';
@@ -0,0 +1,13 @@
+-- Order credit check
+-- Ordering customer and the credit decision taken when an order is created
+
+ALTER TABLE product_orders ADD COLUMN IF NOT EXISTS customer_id UUID;
+
+ALTER TABLE product_orders ADD COLUMN IF NOT EXISTS credit_decision JSONB; -- APPROVED, DECLINED, DEPOSIT_REQUIRED
+
+ALTER TABLE product_order_templates ADD COLUMN IF NOT EXISTS customer_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_product_orders_customer_id ON product_orders (customer_id);
+
+-- Comments
+COMMENT ON COLUMN product_orders.credit_decision IS 'Credit check decision; declined orders are REJECTED, deposit-required orders are HELD';
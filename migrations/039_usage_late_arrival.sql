@@ -0,0 +1,20 @@
+-- Usage late arrival handling
+-- Attribute usage to the billing cycle of its event time and flag late records for rerating
+
+ALTER TABLE customer_usages ADD COLUMN IF NOT EXISTS customer_id UUID;
+
+ALTER TABLE customer_usages ADD COLUMN IF NOT EXISTS billing_cycle_id UUID REFERENCES billing_cycles (id);
+
+ALTER TABLE customer_usages ADD COLUMN IF NOT EXISTS late_arrival BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE customer_usages ADD COLUMN IF NOT EXISTS rerating_required BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE customer_usages ADD COLUMN IF NOT EXISTS rerated_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_customer_usages_billing_cycle_id ON customer_usages (billing_cycle_id);
+
+CREATE INDEX IF NOT EXISTS idx_customer_usages_rerating_required ON customer_usages (rerating_required) WHERE rerating_required;
+
+-- Comments
+COMMENT ON COLUMN customer_usages.late_arrival IS 'Usage arrived after its billing cycle had closed';
+COMMENT ON COLUMN customer_usages.rerating_required IS 'Late usage awaiting rerating / adjustment against its closed billing cycle';
@@ -0,0 +1,9 @@
+-- Bill line-item categories
+-- Tag bill items as subscription, usage, one-time or tax charges for per-category subtotals
+
+ALTER TABLE bill_items ADD COLUMN IF NOT EXISTS category VARCHAR(20) NOT NULL DEFAULT 'ONE_TIME';
+
+CREATE INDEX IF NOT EXISTS idx_bill_items_category ON bill_items (bill_id, category);
+
+-- Comments
+COMMENT ON COLUMN bill_items.category IS 'Bill item category: SUBSCRIPTION, USAGE, ONE_TIME or TAX';
@@ -0,0 +1,9 @@
+-- TMF656 slice templates
+-- Record the template a network slice was instantiated from
+
+ALTER TABLE network_slices ADD COLUMN IF NOT EXISTS template_id VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_network_slices_template_id ON network_slices (template_id);
+
+-- Comments
+COMMENT ON COLUMN network_slices.template_id IS 'Slice template (embb, urllc, mmtc) the slice was instantiated from';
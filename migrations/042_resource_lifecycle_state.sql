@@ -0,0 +1,11 @@
+-- TMF639 resource lifecycle
+-- Operational lifecycle (planned -> installed -> operating -> degraded -> retired) with validated transitions
+
+ALTER TABLE resource_inventories ADD COLUMN IF NOT EXISTS lifecycle_state VARCHAR(20) NOT NULL DEFAULT 'PLANNED';
+
+UPDATE resource_inventories SET lifecycle_state = 'RETIRED' WHERE state = 'RETIRED';
+
+CREATE INDEX IF NOT EXISTS idx_resource_inventories_lifecycle_state ON resource_inventories (lifecycle_state);
+
+-- Comments
+COMMENT ON COLUMN resource_inventories.lifecycle_state IS 'Resource lifecycle state: PLANNED, INSTALLED, OPERATING, DEGRADED or RETIRED';
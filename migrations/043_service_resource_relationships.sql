@@ -0,0 +1,24 @@
+-- TMF638 service-to-resource traceability
+-- Composite service relationships and the resources supporting each service
+
+CREATE TABLE IF NOT EXISTS service_inventory_relationships (
+    parent_service_id UUID NOT NULL REFERENCES service_inventories (id) ON DELETE CASCADE,
+    child_service_id UUID NOT NULL REFERENCES service_inventories (id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (parent_service_id, child_service_id),
+    CHECK (parent_service_id <> child_service_id)
+);
+
+CREATE TABLE IF NOT EXISTS service_inventory_resources (
+    service_id UUID NOT NULL REFERENCES service_inventories (id) ON DELETE CASCADE,
+    resource_inventory_id UUID NOT NULL REFERENCES resource_inventories (id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (service_id, resource_inventory_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_service_inventory_resources_resource ON service_inventory_resources (resource_inventory_id);
+
+-- Comments
+COMMENT ON TABLE service_inventory_relationships IS 'TMF638 Service Relationships - Supporting services of composite services';
+
+COMMENT ON TABLE service_inventory_resources IS 'TMF638 Supporting Resources - TMF639 resources a service runs on';
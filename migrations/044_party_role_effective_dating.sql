@@ -0,0 +1,22 @@
+-- TMF668 party role effective dating
+-- Time-bounded roles with automatic expiry and overlap detection per party
+
+ALTER TABLE party_roles ADD COLUMN IF NOT EXISTS party_id UUID;
+
+ALTER TABLE party_roles ADD COLUMN IF NOT EXISTS effective_from TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE party_roles ADD COLUMN IF NOT EXISTS effective_to TIMESTAMP WITH TIME ZONE;
+
+UPDATE party_roles SET effective_from = COALESCE(engagement_date, created_at) WHERE effective_from IS NULL;
+
+ALTER TABLE party_roles DROP CONSTRAINT IF EXISTS chk_party_roles_effective_period;
+
+ALTER TABLE party_roles ADD CONSTRAINT chk_party_roles_effective_period CHECK (effective_to IS NULL OR effective_to > effective_from);
+
+CREATE INDEX IF NOT EXISTS idx_party_roles_party_id ON party_roles (party_id);
+
+CREATE INDEX IF NOT EXISTS idx_party_roles_effective_to ON party_roles (effective_to) WHERE effective_to IS NOT NULL;
+
+-- Comments
+COMMENT ON COLUMN party_roles.effective_from IS 'Start of the period the role is effective';
+COMMENT ON COLUMN party_roles.effective_to IS 'End of the period the role is effective (exclusive); lapsed roles are terminated';
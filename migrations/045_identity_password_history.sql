@@ -0,0 +1,14 @@
+-- TMF669 password policy
+-- Hashes of previously set passwords, used to block reuse of the last N passwords
+
+CREATE TABLE IF NOT EXISTS identity_password_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    identity_id UUID NOT NULL REFERENCES identities (id) ON DELETE CASCADE,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_identity_password_history_identity ON identity_password_history (identity_id, created_at DESC);
+
+-- Comments
+COMMENT ON TABLE identity_password_history IS 'TMF669 Password History - Salted hashes of previous passwords per identity';
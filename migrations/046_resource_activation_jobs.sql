@@ -0,0 +1,22 @@
+-- TMF702 asynchronous activation jobs
+-- Long-running activations submitted as background jobs, polled or notified via callback
+
+CREATE TABLE IF NOT EXISTS resource_activation_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    activation_id UUID NOT NULL REFERENCES resource_activations (id) ON DELETE CASCADE,
+    state VARCHAR(50) NOT NULL DEFAULT 'QUEUED',
+    progress_percent SMALLINT NOT NULL DEFAULT 0 CHECK (progress_percent BETWEEN 0 AND 100),
+    progress_message TEXT,
+    callback_url VARCHAR(2048),
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_resource_activation_jobs_activation_id ON resource_activation_jobs (activation_id);
+
+CREATE INDEX IF NOT EXISTS idx_resource_activation_jobs_state ON resource_activation_jobs (state);
+
+-- Comments
+COMMENT ON TABLE resource_activation_jobs IS 'TMF702 Activation Jobs - Background execution and progress of long-running resource activations';
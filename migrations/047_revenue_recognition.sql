@@ -0,0 +1,32 @@
+-- Revenue recognition scheduling
+-- Upfront charges recognized monthly over their service period (IFRS 15)
+
+CREATE TABLE IF NOT EXISTS revenue_recognition_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    charge_id UUID NOT NULL UNIQUE,
+    customer_id UUID NOT NULL,
+    total_amount_value DECIMAL(15, 4) NOT NULL,
+    total_amount_unit VARCHAR(10) NOT NULL DEFAULT 'USD',
+    service_period_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    service_period_end TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS revenue_recognition_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    schedule_id UUID NOT NULL REFERENCES revenue_recognition_schedules (id) ON DELETE CASCADE,
+    period_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    period_end TIMESTAMP WITH TIME ZONE NOT NULL,
+    amount_value DECIMAL(15, 4) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'SCHEDULED',
+    recognized_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_revenue_recognition_schedules_customer ON revenue_recognition_schedules (customer_id);
+
+CREATE INDEX IF NOT EXISTS idx_revenue_recognition_entries_due ON revenue_recognition_entries (status, period_end);
+
+-- Comments
+COMMENT ON TABLE revenue_recognition_schedules IS 'Recognition schedules spreading upfront charges over their service period';
+
+COMMENT ON TABLE revenue_recognition_entries IS 'Monthly recognized-revenue entries, prorated for partial months';
@@ -0,0 +1,26 @@
+-- Tax calculation
+-- Itemized taxes per charged usage and the tax jurisdiction of billing cycles
+
+CREATE TABLE IF NOT EXISTS charging_tax_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    usage_id UUID NOT NULL,
+    tax_type VARCHAR(20) NOT NULL,
+    country VARCHAR(2) NOT NULL,
+    region VARCHAR(10),
+    rate_percentage DECIMAL(7, 4) NOT NULL,
+    taxable_amount_value DECIMAL(15, 4) NOT NULL,
+    tax_amount_value DECIMAL(15, 4) NOT NULL,
+    unit VARCHAR(10) NOT NULL DEFAULT 'USD',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_charging_tax_lines_usage ON charging_tax_lines (usage_id);
+
+ALTER TABLE billing_cycles ADD COLUMN IF NOT EXISTS tax_country VARCHAR(2);
+
+ALTER TABLE billing_cycles ADD COLUMN IF NOT EXISTS tax_region VARCHAR(10);
+
+-- Comments
+COMMENT ON TABLE charging_tax_lines IS 'Itemized taxes (ICMS, PIS, COFINS, VAT) applied to each charged usage';
+
+COMMENT ON COLUMN billing_cycles.tax_country IS 'Country whose taxes apply to the cycle bill; untaxed when NULL';
@@ -0,0 +1,38 @@
+-- Charging session event log
+-- Append-only record of reservations, ratings and balance changes per charging session
+
+CREATE TABLE IF NOT EXISTS charging_session_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    session_id UUID NOT NULL,
+    subscriber_id UUID NOT NULL,
+    event_type VARCHAR(20) NOT NULL, -- RESERVATION, RATING, BALANCE_CHANGE
+    amount_value DECIMAL(15, 4) NOT NULL,
+    amount_unit VARCHAR(10) NOT NULL,
+    balance_after_value DECIMAL(15, 4),
+    balance_after_unit VARCHAR(10),
+    description TEXT,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_charging_session_events_session ON charging_session_events (session_id, occurred_at);
+
+CREATE INDEX IF NOT EXISTS idx_charging_session_events_subscriber ON charging_session_events (subscriber_id, occurred_at);
+
+CREATE INDEX IF NOT EXISTS idx_charging_session_events_occurred_at ON charging_session_events (occurred_at);
+
+-- Reject updates so recorded decisions cannot be rewritten
+CREATE OR REPLACE FUNCTION reject_charging_session_event_update()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'charging_session_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_charging_session_events_append_only ON charging_session_events;
+CREATE TRIGGER trigger_charging_session_events_append_only
+    BEFORE UPDATE ON charging_session_events
+    FOR EACH ROW
+    EXECUTE FUNCTION reject_charging_session_event_update();
+
+-- Comments
+COMMENT ON TABLE charging_session_events IS 'Append-only charging decisions per session, kept for dispute resolution until retention expires';
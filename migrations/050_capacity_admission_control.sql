@@ -0,0 +1,17 @@
+-- Capacity admission control
+-- Per capacity type soft utilization ceiling above which only high-priority reservations are admitted
+
+CREATE TABLE IF NOT EXISTS capacity_admission_ceilings (
+    capacity_type VARCHAR(100) PRIMARY KEY,
+    soft_ceiling_percentage DECIMAL(5, 2) NOT NULL DEFAULT 85.00 CHECK (soft_ceiling_percentage > 0 AND soft_ceiling_percentage <= 100),
+    high_priority_threshold INTEGER NOT NULL DEFAULT 100, -- Minimum priority admitted above the soft ceiling
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Comments
+COMMENT ON TABLE capacity_admission_ceilings IS 'Soft utilization ceilings used for reservation admission control; capacity types without a row use 85%';
+
+COMMENT ON COLUMN capacity_admission_ceilings.soft_ceiling_percentage IS 'Utilization, including the new reservation, above which only high-priority or emergency reservations are admitted';
+
+COMMENT ON COLUMN capacity_admission_ceilings.high_priority_threshold IS 'Reservations with at least this priority are admitted above the soft ceiling';
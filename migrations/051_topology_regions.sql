@@ -0,0 +1,17 @@
+-- Multi-region network topology
+-- Region labels on topology nodes for per-region subgraphs, cross-region link detection and region-aware routing
+
+CREATE TABLE IF NOT EXISTS topology_node_regions (
+    resource_inventory_id UUID PRIMARY KEY,
+    region VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index for per-region subgraph lookups
+CREATE INDEX IF NOT EXISTS idx_topology_node_regions_region ON topology_node_regions (region);
+
+-- Comments
+COMMENT ON TABLE topology_node_regions IS 'Region (failure domain) of each labelled network topology node';
+
+COMMENT ON COLUMN topology_node_regions.region IS 'Region label; links between nodes of different regions are cross-region links';
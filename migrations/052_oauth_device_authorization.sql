@@ -0,0 +1,27 @@
+-- OAuth 2.0 device authorization grant (RFC 8628)
+-- Pending device authorizations for headless clients such as set-top boxes and CLI tools
+
+CREATE TABLE IF NOT EXISTS device_authorizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    device_code VARCHAR(255) NOT NULL UNIQUE,
+    user_code VARCHAR(16) NOT NULL UNIQUE, -- Normalized, without separator
+    client_id VARCHAR(255) NOT NULL,
+    scopes TEXT[] NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, APPROVED, DENIED
+    user_id UUID,
+    poll_interval INTEGER NOT NULL, -- Seconds, raised on every slow_down
+    last_polled_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_authorizations_client_id ON device_authorizations (client_id);
+
+CREATE INDEX IF NOT EXISTS idx_device_authorizations_expires_at ON device_authorizations (expires_at);
+
+-- Comments
+COMMENT ON TABLE device_authorizations IS 'RFC 8628 device authorization requests awaiting user approval or token exchange';
+
+COMMENT ON COLUMN device_authorizations.user_code IS 'Short code the user enters on a secondary device';
+
+COMMENT ON COLUMN device_authorizations.poll_interval IS 'Minimum seconds between token polls';
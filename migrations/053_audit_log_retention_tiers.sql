@@ -0,0 +1,16 @@
+-- Audit log categories and retention tiers
+-- Events are categorized (AUTH, DATA_ACCESS, ADMIN) and pruned per category once past retention
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS category VARCHAR(20);
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS pruned_at TIMESTAMP WITH TIME ZONE;
+
+-- Index for per-category retention scans
+CREATE INDEX IF NOT EXISTS idx_audit_logs_category_timestamp ON audit_logs (category, timestamp)
+WHERE
+    pruned_at IS NULL;
+
+-- Comments
+COMMENT ON COLUMN audit_logs.category IS 'Audit category (AUTH, DATA_ACCESS, ADMIN) deciding retention; NULL for entries written before categorization';
+
+COMMENT ON COLUMN audit_logs.pruned_at IS 'When the entry content was removed after its retention period; the hash chain columns are kept so the chain still links';
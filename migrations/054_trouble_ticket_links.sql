@@ -0,0 +1,25 @@
+-- TMF633 trouble ticket linkage
+-- Alarms (TMF642) that triggered a ticket and product orders (TMF622) it affects
+
+CREATE TABLE IF NOT EXISTS trouble_ticket_alarms (
+    ticket_id UUID NOT NULL REFERENCES trouble_tickets (id) ON DELETE CASCADE,
+    alarm_id UUID NOT NULL REFERENCES alarms (id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticket_id, alarm_id)
+);
+
+CREATE TABLE IF NOT EXISTS trouble_ticket_orders (
+    ticket_id UUID NOT NULL REFERENCES trouble_tickets (id) ON DELETE CASCADE,
+    order_id UUID NOT NULL REFERENCES product_orders (id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticket_id, order_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trouble_ticket_alarms_alarm ON trouble_ticket_alarms (alarm_id);
+
+CREATE INDEX IF NOT EXISTS idx_trouble_ticket_orders_order ON trouble_ticket_orders (order_id);
+
+-- Comments
+COMMENT ON TABLE trouble_ticket_alarms IS 'TMF633 Triggering Alarms - TMF642 alarms a trouble ticket was raised for';
+
+COMMENT ON TABLE trouble_ticket_orders IS 'TMF633 Affected Orders - TMF622 product orders affected by a trouble ticket';
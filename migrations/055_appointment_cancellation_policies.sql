@@ -0,0 +1,17 @@
+-- TMF688 appointment cancellation policies
+-- Free cancellation/rescheduling window and late fee, per appointment type
+
+CREATE TABLE IF NOT EXISTS appointment_cancellation_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    appointment_type VARCHAR(100),
+    free_cancellation_hours INTEGER NOT NULL CHECK (free_cancellation_hours >= 0),
+    late_fee_value DECIMAL(15, 2) NOT NULL CHECK (late_fee_value >= 0),
+    late_fee_unit VARCHAR(10) NOT NULL,
+    last_update TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One policy per appointment type, plus one default (NULL type)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_appointment_cancellation_policies_type ON appointment_cancellation_policies (COALESCE(appointment_type, ''));
+
+-- Comments
+COMMENT ON TABLE appointment_cancellation_policies IS 'TMF688 Cancellation Policies - Hours before an appointment until which it can be cancelled or rescheduled for free, and the fee after that';
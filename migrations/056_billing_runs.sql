@@ -0,0 +1,31 @@
+-- Billing runs
+-- Runs closing all due billing cycles, with the outcome of each cycle as checkpoint
+
+CREATE TABLE IF NOT EXISTS billing_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    cycle_type VARCHAR(20), -- NULL = all cycle types
+    cutoff TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(30) NOT NULL DEFAULT 'RUNNING', -- RUNNING, COMPLETED, COMPLETED_WITH_FAILURES
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE TABLE IF NOT EXISTS billing_run_results (
+    run_id UUID NOT NULL REFERENCES billing_runs (id) ON DELETE CASCADE,
+    cycle_id UUID NOT NULL REFERENCES billing_cycles (id) ON DELETE CASCADE,
+    customer_id UUID NOT NULL,
+    outcome VARCHAR(20) NOT NULL, -- SUCCEEDED, FAILED
+    bill_id UUID,
+    error TEXT,
+    processed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (run_id, cycle_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_billing_runs_status ON billing_runs (status);
+
+CREATE INDEX IF NOT EXISTS idx_billing_run_results_customer ON billing_run_results (customer_id);
+
+-- Comments
+COMMENT ON TABLE billing_runs IS 'Billing runs closing all due billing cycles';
+
+COMMENT ON TABLE billing_run_results IS 'Latest outcome of each billing cycle in a run; cycles without a success are billed when the run is resumed';
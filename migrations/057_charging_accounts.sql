@@ -0,0 +1,18 @@
+-- Prepaid, postpaid and hybrid charging accounts
+-- Hybrid accounts pay charges of their prepaid categories from the prepaid balance and invoice the rest
+
+CREATE TABLE IF NOT EXISTS charging_accounts (
+    customer_id UUID PRIMARY KEY,
+    account_type VARCHAR(20) NOT NULL, -- PREPAID, POSTPAID, HYBRID
+    prepaid_categories TEXT[] NOT NULL DEFAULT '{}', -- RECURRING, USAGE, OVERAGE, ONE_TIME
+    prepaid_balance_value DECIMAL(15, 2) NOT NULL DEFAULT 0 CHECK (prepaid_balance_value >= 0),
+    prepaid_balance_unit VARCHAR(10) NOT NULL DEFAULT 'USD',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Charges paid from a prepaid balance are not invoiced
+ALTER TABLE charging_results ADD COLUMN IF NOT EXISTS payment_mode VARCHAR(20) NOT NULL DEFAULT 'POSTPAID';
+
+-- Comments
+COMMENT ON TABLE charging_accounts IS 'How customers pay for charges, with their prepaid balance';
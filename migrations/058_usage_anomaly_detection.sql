@@ -0,0 +1,11 @@
+-- Usage anomaly detection
+-- Usage far above a subscriber's learned baseline is reported as suspected fraud;
+-- thresholds are configured per customer segment
+
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS segment VARCHAR(100);
+
+-- Baseline lookups by subscriber and usage type
+CREATE INDEX IF NOT EXISTS idx_customer_usages_customer_type_date ON customer_usages (customer_id, usage_type, (COALESCE(start_date, usage_date)));
+
+-- Comments
+COMMENT ON COLUMN customers.segment IS 'Customer segment selecting usage anomaly thresholds (e.g. consumer, enterprise)';
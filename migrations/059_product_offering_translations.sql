@@ -0,0 +1,16 @@
+-- Localized product catalog content
+-- Offerings keep their name and description in the catalog's default locale; translations into other locales live here
+
+CREATE TABLE IF NOT EXISTS product_offering_translations (
+    product_offering_id UUID NOT NULL REFERENCES product_offerings (id) ON DELETE CASCADE,
+    locale VARCHAR(35) NOT NULL, -- lowercase language tag, e.g. pt-br, es
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (product_offering_id, locale)
+);
+
+-- Comments
+COMMENT ON TABLE product_offering_translations IS 'TMF620 product offering names and descriptions per locale';
+COMMENT ON COLUMN product_offering_translations.description IS 'Falls back to the default-locale description when NULL';
@@ -0,0 +1,23 @@
+-- Inventory stock levels and low-stock alerts
+-- Finite pools (numbers, SIMs) are grouped by category; available stock below a category's low threshold raises one alert until it recovers
+
+ALTER TABLE product_inventories ADD COLUMN IF NOT EXISTS category VARCHAR(100);
+
+CREATE INDEX IF NOT EXISTS idx_product_inventories_category ON product_inventories (category, state);
+
+CREATE TABLE IF NOT EXISTS inventory_stock_thresholds (
+    category VARCHAR(100) PRIMARY KEY,
+    low_threshold BIGINT NOT NULL CHECK (low_threshold >= 0),
+    recovery_threshold BIGINT NOT NULL,
+    alert_active BOOLEAN NOT NULL DEFAULT false,
+    alerted_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK (recovery_threshold > low_threshold)
+);
+
+-- Comments
+COMMENT ON COLUMN product_inventories.category IS 'Stock category (e.g. MSISDN, SIM) the inventory counts towards';
+COMMENT ON TABLE inventory_stock_thresholds IS 'Low-stock thresholds per inventory category';
+COMMENT ON COLUMN inventory_stock_thresholds.recovery_threshold IS 'Available stock at which a raised low-stock alert clears and re-arms';
+COMMENT ON COLUMN inventory_stock_thresholds.alert_active IS 'A low-stock alert was raised and stock has not recovered since';
@@ -0,0 +1,11 @@
+-- TMF656 slice lifecycle
+-- Slices move planned -> active -> modifying -> terminated; resources reserved for a slice are released when it terminates
+
+ALTER TABLE resource_reservations ADD COLUMN IF NOT EXISTS network_slice_id UUID REFERENCES network_slices (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_resource_reservations_network_slice_id ON resource_reservations (network_slice_id, reservation_status);
+
+-- Comments
+COMMENT ON COLUMN network_slices.state IS 'Slice lifecycle state: PLANNED, ACTIVE, MODIFYING, INACTIVE or TERMINATED';
+
+COMMENT ON COLUMN resource_reservations.network_slice_id IS 'Network slice the reservation was made for; released (CANCELLED) when the slice terminates';
@@ -0,0 +1,33 @@
+-- TMF632 party KYC verification
+-- Parties start PENDING and are VERIFIED or REJECTED with evidence; verified parties fall back to PENDING when their review is due
+
+ALTER TABLE parties ADD COLUMN IF NOT EXISTS kyc_status VARCHAR(20) NOT NULL DEFAULT 'PENDING';
+ALTER TABLE parties ADD COLUMN IF NOT EXISTS kyc_verified_by VARCHAR(255);
+ALTER TABLE parties ADD COLUMN IF NOT EXISTS kyc_verified_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE parties ADD COLUMN IF NOT EXISTS kyc_review_due TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_parties_kyc_review_due ON parties (kyc_review_due) WHERE kyc_status = 'VERIFIED';
+
+CREATE TABLE IF NOT EXISTS party_kyc_verifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    party_id UUID NOT NULL REFERENCES parties (id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL,
+    evidence JSONB NOT NULL DEFAULT '[]',
+    verified_by VARCHAR(255),
+    reason TEXT,
+    verified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    review_due TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_party_kyc_verifications_party_id ON party_kyc_verifications (party_id, verified_at DESC);
+
+ALTER TABLE product_offerings ADD COLUMN IF NOT EXISTS kyc_required BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE product_orders ADD COLUMN IF NOT EXISTS party_id UUID REFERENCES parties (id);
+
+-- Comments
+COMMENT ON COLUMN parties.kyc_status IS 'KYC status: PENDING, VERIFIED or REJECTED';
+COMMENT ON COLUMN parties.kyc_review_due IS 'When a verified party must be re-verified; it returns to PENDING after this date';
+COMMENT ON TABLE party_kyc_verifications IS 'History of KYC verifications with references to the evidence checked';
+COMMENT ON COLUMN product_offerings.kyc_required IS 'Regulated offering that may only be ordered by a KYC-verified party';
+COMMENT ON COLUMN product_orders.party_id IS 'Party placing the order, checked for KYC when ordering regulated offerings';
@@ -0,0 +1,19 @@
+-- TMF622 idempotent order creation
+-- Clients send an Idempotency-Key with order submissions; a retried submission with the same key returns the order already created
+
+CREATE TABLE IF NOT EXISTS product_order_idempotency_keys (
+    client_id VARCHAR(255) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    order_id UUID REFERENCES product_orders (id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (client_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_order_idempotency_keys_expires_at ON product_order_idempotency_keys (expires_at);
+
+-- Comments
+COMMENT ON TABLE product_order_idempotency_keys IS 'Idempotency keys of product order submissions, scoped per client';
+COMMENT ON COLUMN product_order_idempotency_keys.client_id IS 'Authenticated client (token subject) that sent the key';
+COMMENT ON COLUMN product_order_idempotency_keys.order_id IS 'Order created for the key; NULL while the first request is still being processed';
+COMMENT ON COLUMN product_order_idempotency_keys.expires_at IS 'After this time the key may be reused for a new order';
@@ -0,0 +1,40 @@
+-- TMF668 partner contracts
+-- Contracts link a partner role to revenue-share terms; they move draft -> active -> expired -> terminated and warn ahead of expiry
+
+CREATE TABLE IF NOT EXISTS partner_contracts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    party_role_id UUID NOT NULL REFERENCES party_roles (id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    href VARCHAR(500),
+    description TEXT,
+    state VARCHAR(20) NOT NULL DEFAULT 'DRAFT',
+    effective_from TIMESTAMP WITH TIME ZONE NOT NULL,
+    effective_to TIMESTAMP WITH TIME ZONE,
+    activation_date TIMESTAMP WITH TIME ZONE,
+    expiry_warning_sent_at TIMESTAMP WITH TIME ZONE,
+    termination_date TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    last_update TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    CHECK (effective_to IS NULL OR effective_to > effective_from)
+);
+
+CREATE INDEX IF NOT EXISTS idx_partner_contracts_party_role_id ON partner_contracts (party_role_id);
+CREATE INDEX IF NOT EXISTS idx_partner_contracts_expiry ON partner_contracts (effective_to) WHERE state = 'ACTIVE';
+
+CREATE TABLE IF NOT EXISTS partner_contract_revenue_shares (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    contract_id UUID NOT NULL REFERENCES partner_contracts (id) ON DELETE CASCADE,
+    product_offering_id UUID,
+    revenue_share_percentage DECIMAL(5, 2) NOT NULL CHECK (revenue_share_percentage BETWEEN 0 AND 100)
+);
+
+CREATE INDEX IF NOT EXISTS idx_partner_contract_revenue_shares_contract_id ON partner_contract_revenue_shares (contract_id);
+
+-- Comments
+COMMENT ON TABLE partner_contracts IS 'Partner contracts linked to a TMF668 partner role';
+COMMENT ON COLUMN partner_contracts.state IS 'Contract lifecycle state: DRAFT, ACTIVE, EXPIRED or TERMINATED';
+COMMENT ON COLUMN partner_contracts.activation_date IS 'When the contract was activated; contracts never activated are ignored by settlement';
+COMMENT ON COLUMN partner_contracts.expiry_warning_sent_at IS 'When the advance warning of the contract expiring was published';
+COMMENT ON COLUMN partner_contracts.termination_date IS 'When the contract was terminated; revenue share stops at this date';
+COMMENT ON TABLE partner_contract_revenue_shares IS 'Revenue-share terms of a partner contract, read by partner settlement';
+COMMENT ON COLUMN partner_contract_revenue_shares.product_offering_id IS 'Offering the share applies to; NULL applies to all offerings';
@@ -0,0 +1,34 @@
+-- TMF669 credential usage analytics
+-- Per-credential usage baselines that adapt over time, and the anomalies flagged against them
+
+CREATE TABLE IF NOT EXISTS credential_usage_baselines (
+    credential_id UUID PRIMARY KEY REFERENCES identity_credentials (id) ON DELETE CASCADE,
+    observations BIGINT NOT NULL DEFAULT 0,
+    location_weights JSONB NOT NULL DEFAULT '{}',
+    hour_weights JSONB NOT NULL DEFAULT '[]',
+    mean_window_calls DOUBLE PRECISION NOT NULL DEFAULT 0,
+    completed_windows BIGINT NOT NULL DEFAULT 0,
+    window_start TIMESTAMP WITH TIME ZONE,
+    window_calls INTEGER NOT NULL DEFAULT 0,
+    window_flagged BOOLEAN NOT NULL DEFAULT false,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE TABLE IF NOT EXISTS credential_usage_anomalies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    credential_id UUID NOT NULL REFERENCES identity_credentials (id) ON DELETE CASCADE,
+    identity_id UUID NOT NULL REFERENCES identities (id) ON DELETE CASCADE,
+    kind VARCHAR(30) NOT NULL,
+    detail TEXT NOT NULL,
+    location VARCHAR(100),
+    source_ip VARCHAR(64),
+    observed_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_credential_usage_anomalies_credential_id ON credential_usage_anomalies (credential_id, observed_at DESC);
+
+-- Comments
+COMMENT ON TABLE credential_usage_baselines IS 'Adaptive usage baseline per credential: time-decayed location and hour-of-day weights and mean calls per rate window';
+COMMENT ON COLUMN credential_usage_baselines.hour_weights IS 'Time-decayed use count for each UTC hour of the day';
+COMMENT ON COLUMN credential_usage_baselines.window_flagged IS 'Whether the current rate window has already been flagged, so a spike is reported once';
+COMMENT ON TABLE credential_usage_anomalies IS 'Unusual location, rate or time of credential use; flagged without blocking the request';
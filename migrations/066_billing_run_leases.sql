@@ -0,0 +1,7 @@
+-- Billing run leases
+-- A running run holds a lease it renews as it progresses; only failed runs or runs with an expired lease can be resumed
+
+ALTER TABLE billing_runs ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMP WITH TIME ZONE;
+
+-- Comments
+COMMENT ON COLUMN billing_runs.lease_expires_at IS 'When a RUNNING run stops being held by its coordinator and may be resumed';
@@ -0,0 +1,14 @@
+-- Related party references for TMF637 and TMF678
+-- Store the id of the referenced party so inventories and bills are linked to customers by id rather than by name
+
+ALTER TABLE inventory_related_parties ADD COLUMN IF NOT EXISTS party_id UUID;
+
+ALTER TABLE bill_related_parties ADD COLUMN IF NOT EXISTS party_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_related_parties_party_id ON inventory_related_parties (party_id);
+
+CREATE INDEX IF NOT EXISTS idx_bill_related_parties_party_id ON bill_related_parties (party_id);
+
+-- Comments
+COMMENT ON COLUMN inventory_related_parties.party_id IS 'Id of the referenced party, e.g. the customer';
+COMMENT ON COLUMN bill_related_parties.party_id IS 'Id of the referenced party, e.g. the customer';
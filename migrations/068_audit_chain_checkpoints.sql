@@ -0,0 +1,11 @@
+-- Audit log chain checkpoints
+-- Records the last entry deleted by retention so the first remaining entry can still be linked
+
+CREATE TABLE IF NOT EXISTS audit_chain_checkpoints (
+    sequence_number BIGINT PRIMARY KEY,
+    entry_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Comments
+COMMENT ON TABLE audit_chain_checkpoints IS 'Sequence number and entry hash of the last audit log entry deleted by a retention run; the newest checkpoint anchors chain verification';
@@ -0,0 +1,7 @@
+-- TMF688 appointment customers
+-- The customer an appointment is for, charged any late cancellation or rescheduling fee
+
+ALTER TABLE appointments ADD COLUMN IF NOT EXISTS customer_id UUID;
+
+-- Comments
+COMMENT ON COLUMN appointments.customer_id IS 'Customer the appointment is for; late cancellation and rescheduling fees are charged to this customer';
@@ -0,0 +1,10 @@
+-- TMF639 resource lifecycle state checks
+-- Only known lifecycle states are stored, and the inventory state is RETIRED exactly when the lifecycle state is
+
+ALTER TABLE resource_inventories DROP CONSTRAINT IF EXISTS chk_resource_inventories_lifecycle_state;
+
+ALTER TABLE resource_inventories ADD CONSTRAINT chk_resource_inventories_lifecycle_state CHECK (lifecycle_state IN ('PLANNED', 'INSTALLED', 'OPERATING', 'DEGRADED', 'RETIRED'));
+
+ALTER TABLE resource_inventories DROP CONSTRAINT IF EXISTS chk_resource_inventories_retired;
+
+ALTER TABLE resource_inventories ADD CONSTRAINT chk_resource_inventories_retired CHECK ((state = 'RETIRED') = (lifecycle_state = 'RETIRED'));
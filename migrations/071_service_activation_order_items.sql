@@ -0,0 +1,9 @@
+-- Service orchestrator activation per order item
+-- One service activation per service order and specification, so retried activations reuse the same row
+
+ALTER TABLE service_activations ADD COLUMN IF NOT EXISTS service_specification_id UUID;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_service_activations_order_item ON service_activations (service_order_id, service_specification_id);
+
+-- Comments
+COMMENT ON COLUMN service_activations.service_specification_id IS 'Service specification of the order item this activation provisions';
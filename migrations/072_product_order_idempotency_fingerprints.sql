@@ -0,0 +1,7 @@
+-- TMF622 idempotency key request fingerprints
+-- A key reused with a different request body is rejected instead of returning the first order
+
+ALTER TABLE product_order_idempotency_keys ADD COLUMN IF NOT EXISTS request_fingerprint VARCHAR(64);
+
+-- Comments
+COMMENT ON COLUMN product_order_idempotency_keys.request_fingerprint IS 'SHA-256 of the request body the key was first used with; NULL for keys claimed before fingerprints were recorded';